@@ -0,0 +1,38 @@
+use std::process::Command;
+
+fn main() {
+    // Release CI can pass the sha explicitly; local builds ask git.
+    let sha = std::env::var("NQRUST_GIT_SHA").ok().or_else(|| {
+        Command::new("git")
+            .args(["rev-parse", "--short=12", "HEAD"])
+            .output()
+            .ok()
+            .filter(|out| out.status.success())
+            .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+    });
+
+    println!(
+        "cargo:rustc-env=NQRUST_GIT_SHA={}",
+        sha.unwrap_or_else(|| "unknown".to_string())
+    );
+    // Reported by the hidden `--diagnostics` flag to tell binary variants apart.
+    let mut features: Vec<String> = std::env::vars()
+        .filter_map(|(key, _)| key.strip_prefix("CARGO_FEATURE_").map(|f| f.to_lowercase()))
+        .collect();
+    features.sort();
+    println!(
+        "cargo:rustc-env=NQRUST_BUILD_FEATURES={}",
+        features.join(",")
+    );
+    println!(
+        "cargo:rustc-env=NQRUST_BUILD_TARGET={}",
+        std::env::var("TARGET").unwrap_or_default()
+    );
+    println!(
+        "cargo:rustc-env=NQRUST_BUILD_PROFILE={}",
+        std::env::var("PROFILE").unwrap_or_default()
+    );
+    println!("cargo:rerun-if-env-changed=NQRUST_GIT_SHA");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs/heads");
+}
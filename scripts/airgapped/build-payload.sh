@@ -15,5 +15,8 @@ mkdir -p "$(dirname "${OUTPUT_PAYLOAD}")"
 echo "📦 Creating payload archive from ${IMAGES_DIR}..."
 tar -C "${IMAGES_DIR}" -czf "${OUTPUT_PAYLOAD}" .
 
+# Sidecar checksum, verified by `nqrust-identity --payload <file>`
+sha256sum "${OUTPUT_PAYLOAD}" > "${OUTPUT_PAYLOAD}.sha256"
+
 SIZE=$(stat -c%s "${OUTPUT_PAYLOAD}")
 echo "✅ Payload created: ${OUTPUT_PAYLOAD} ($(numfmt --to=iec-i --suffix=B ${SIZE}))"
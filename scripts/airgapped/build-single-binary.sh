@@ -3,6 +3,8 @@
 set -euo pipefail
 
 FORCE_REFRESH="${1:-}"
+# Set BASE_MANIFEST to an earlier release's manifest.json to build a delta
+BASE_MANIFEST="${BASE_MANIFEST:-}"
 BIN_NAME="nqrust-identity-airgapped"
 IMAGES_DIR="build/images"
 PAYLOAD_PATH="build/payload.tar.gz"
@@ -14,8 +16,8 @@ echo ""
 echo "=== Step 1/3: Saving Docker images ==="
 chmod +x scripts/airgapped/save-images.sh
 
-if [ "${FORCE_REFRESH}" == "--force-refresh" ] || [ ! -f "${IMAGES_DIR}/manifest.json" ]; then
-  ./scripts/airgapped/save-images.sh "${IMAGES_DIR}"
+if [ "${FORCE_REFRESH}" == "--force-refresh" ] || [ -n "${BASE_MANIFEST}" ] || [ ! -f "${IMAGES_DIR}/manifest.json" ]; then
+  ./scripts/airgapped/save-images.sh "${IMAGES_DIR}" "${BASE_MANIFEST}"
 else
   echo "ℹ Using cached images (use --force-refresh to re-pull)"
 fi
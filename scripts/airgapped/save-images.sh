@@ -3,7 +3,11 @@
 set -euo pipefail
 
 IMAGES_DIR="${1:-build/images}"
+# Optional manifest.json of an earlier release: images with the same image ID
+# are listed but not saved, producing a delta payload on top of that release.
+BASE_MANIFEST="${2:-}"
 mkdir -p "$IMAGES_DIR"
+rm -f "${IMAGES_DIR}/SHA256SUMS"
 
 # Resolve latest nqrust-identity tag from GitHub Releases
 OWNER="NexusQuantum"
@@ -28,6 +32,24 @@ for IMAGE in "ghcr.io/nexusquantum/nqrust-identity:${IDENTITY_TAG}" "postgres:16
   echo "Pulling ${IMAGE}..."
   docker pull "${IMAGE}"
 
+  # Image ID: lets the installer skip images that are already loaded
+  DIGEST=$(docker image inspect --format '{{.Id}}' "${IMAGE}")
+
+  if [ -n "${BASE_MANIFEST}" ]; then
+    BASE_ENTRY=$(jq -c --arg file "$FILENAME" '.images[] | select(.file == $file)' "${BASE_MANIFEST}")
+    if [ -n "${BASE_ENTRY}" ] && [ "$(echo "${BASE_ENTRY}" | jq -r '.digest // empty')" == "${DIGEST}" ]; then
+      echo "Unchanged since base release, leaving ${FILENAME} out of the delta"
+      rm -f "${OUTPUT}"
+      MANIFEST_ENTRIES=$(echo "$MANIFEST_ENTRIES" | jq \
+        --arg name "$IMAGE" \
+        --arg file "$FILENAME" \
+        --arg sha256 "$(echo "${BASE_ENTRY}" | jq -r '.sha256')" \
+        --arg digest "$DIGEST" \
+        '. + [{"name": $name, "file": $file, "sha256": $sha256, "digest": $digest, "from_base": true}]')
+      continue
+    fi
+  fi
+
   echo "Saving ${IMAGE} → ${OUTPUT}..."
   docker save "${IMAGE}" | gzip -9 > "${OUTPUT}"
 
@@ -41,12 +63,18 @@ for IMAGE in "ghcr.io/nexusquantum/nqrust-identity:${IDENTITY_TAG}" "postgres:16
     --arg file "$FILENAME" \
     --arg size "$SIZE" \
     --arg sha256 "$CHECKSUM" \
-    '. + [{"name": $name, "file": $file, "size": $size, "sha256": $sha256}]')
+    --arg digest "$DIGEST" \
+    '. + [{"name": $name, "file": $file, "size": $size, "sha256": $sha256, "digest": $digest}]')
 done
 
 # Write manifest
+BASE_TAG=""
+if [ -n "${BASE_MANIFEST}" ]; then
+  BASE_TAG=$(jq -r '.identity_tag // "the base release"' "${BASE_MANIFEST}")
+fi
 echo "{\"images\": ${MANIFEST_ENTRIES}, \"identity_tag\": \"${IDENTITY_TAG}\"}" \
-  | jq . > "${IMAGES_DIR}/manifest.json"
+  | jq --arg base "$BASE_TAG" 'if $base != "" then . + {base: $base} else . end' \
+  > "${IMAGES_DIR}/manifest.json"
 
 echo "✅ Images saved to ${IMAGES_DIR}/"
-cat "${IMAGES_DIR}/manifest.json" | jq -r '.images[] | "  - \(.name) → \(.file) (\(.size | tonumber / 1048576 | floor)MB)"'
+cat "${IMAGES_DIR}/manifest.json" | jq -r '.images[] | if .from_base then "  - \(.name) (unchanged, from base)" else "  - \(.name) → \(.file) (\(.size | tonumber / 1048576 | floor)MB)" end'
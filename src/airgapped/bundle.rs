@@ -0,0 +1,401 @@
+// airgapped/bundle.rs
+// Offline bundle export (connected machine) and loading from a bundle
+// directory (airgapped machine), as an alternative to the embedded payload.
+
+use color_eyre::{Result, eyre::eyre};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+use std::process::Stdio;
+
+use super::docker::{self, required_images};
+use super::progress::{Reporter, report};
+use crate::runner::{Cmd, CommandRunner, blocking_output};
+use crate::utils;
+
+const CHECKSUMS_FILE: &str = "SHA256SUMS";
+const MANIFEST_FILE: &str = "manifest.json";
+/// Copy of the manifest of the last payload loaded on this host. Handing it
+/// to `--export-bundle --since` on the connected side builds the next delta.
+const INSTALLED_MANIFEST: &str = ".nqrust-payload-manifest.json";
+
+/// `manifest.json` of a bundle or payload, as written by `save-images.sh`
+/// and `export`: one entry per image archive.
+#[derive(Debug, Deserialize)]
+pub struct Manifest {
+    pub images: Vec<ManifestEntry>,
+    #[serde(default)]
+    pub identity_tag: Option<String>,
+    /// Set on delta payloads: the release whose images they build on.
+    #[serde(default)]
+    pub base: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ManifestEntry {
+    /// Image reference the archive was saved from.
+    pub name: String,
+    pub file: String,
+    pub sha256: String,
+    /// Image ID (`sha256:…` of the image config) at save time. Unlike
+    /// RepoDigests it survives `docker save` / `docker load`, so it tells
+    /// whether the exact image is already loaded. Absent in older manifests.
+    #[serde(default)]
+    pub digest: Option<String>,
+    /// Unchanged since the base release and left out of this delta payload;
+    /// the host must already have it loaded.
+    #[serde(default)]
+    pub from_base: bool,
+}
+
+impl Manifest {
+    /// `None` when `dir` has no manifest; an error when it has a broken one.
+    pub fn load(dir: &Path) -> Result<Option<Self>> {
+        let path = dir.join(MANIFEST_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Self::read(&path).map(Some)
+    }
+
+    /// Read a manifest file, or the one inside a bundle directory.
+    pub fn read(path: &Path) -> Result<Self> {
+        let path = if path.is_dir() {
+            path.join(MANIFEST_FILE)
+        } else {
+            path.to_path_buf()
+        };
+        let content =
+            fs::read_to_string(&path).map_err(|e| eyre!("Cannot read {}: {e}", path.display()))?;
+        serde_json::from_str(&content).map_err(|e| eyre!("{} is not valid: {e}", path.display()))
+    }
+
+    pub fn entry(&self, file: &str) -> Option<&ManifestEntry> {
+        self.images.iter().find(|entry| entry.file == file)
+    }
+}
+
+impl ManifestEntry {
+    /// Hash the archive in `dir` and compare it with the manifest.
+    pub fn verify(&self, dir: &Path) -> Result<()> {
+        let actual = sha256_file(&dir.join(&self.file))
+            .map_err(|e| eyre!("Cannot read {}: {e}", self.file))?;
+        if !actual.eq_ignore_ascii_case(&self.sha256) {
+            return Err(eyre!(
+                "Image archive {} ({}) is corrupted: expected SHA256 {}, got {actual}\n\
+                 Re-copy the payload; this file was damaged in transfer.",
+                self.file,
+                self.name,
+                self.sha256
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Pull every required image and save it as `<file>.tar.gz` plus SHA256SUMS
+/// and manifest.json — the same layout `save-images.sh` produces, so the
+/// result can also be fed to `build-payload.sh`. A `dest` ending in
+/// `.tar.gz` / `.tgz` is packed into a single archive instead of a directory.
+///
+/// With `since` (a manifest.json or bundle directory of an earlier release,
+/// e.g. the `.nqrust-payload-manifest.json` left on the airgapped host),
+/// images whose ID has not changed are listed in the manifest but not saved,
+/// giving a delta bundle that only loads on top of that release.
+///
+/// `identity_tag` is the release pulled for the image the stack references
+/// as `:latest`; it is retagged to that reference before saving and recorded
+/// in the manifest, as `save-images.sh` does.
+pub fn export(
+    runner: &dyn CommandRunner,
+    dest: &Path,
+    since: Option<&Path>,
+    identity_tag: &str,
+) -> Result<()> {
+    docker::check_docker_available(runner)?;
+    docker::check_docker_running(runner)?;
+    let base = since.map(Manifest::read).transpose()?;
+
+    let as_tarball = dest
+        .to_str()
+        .is_some_and(|d| d.ends_with(".tar.gz") || d.ends_with(".tgz"));
+    let staging = if as_tarball {
+        tempfile::tempdir()?.keep()
+    } else {
+        dest.to_path_buf()
+    };
+    fs::create_dir_all(&staging)?;
+
+    println!("📦 Exporting offline bundle to {}", dest.display());
+    let mut sums = String::new();
+    let mut entries = Vec::new();
+    let total = required_images().len();
+    for (idx, (image, file)) in required_images().iter().enumerate() {
+        println!("  [{}/{}] {image}", idx + 1, total);
+        let source = release_ref(image, identity_tag);
+        println!("    Pulling {source}...");
+        let mut pull = runner.spawn(&Cmd::new("docker").args(["pull", &source]))?;
+        let pulled = futures::executor::block_on(async {
+            while let Some(line) = pull.next_line().await {
+                println!("      {line}");
+            }
+            pull.wait().await
+        });
+        if !pulled {
+            return Err(eyre!("docker pull {source} failed"));
+        }
+        if source != *image {
+            let tag = Cmd::new("docker").args(["tag", &source, image]);
+            if !blocking_output(runner, &tag)?.success {
+                return Err(eyre!("docker tag {source} {image} failed"));
+            }
+        }
+
+        let digest = docker::image_id(runner, image);
+        if let Some(unchanged) = base
+            .as_ref()
+            .and_then(|base| base.entry(file))
+            .filter(|entry| digest.is_some() && entry.digest == digest)
+        {
+            println!("    Unchanged since the base release, left out of the delta");
+            entries.push(serde_json::json!({
+                "name": image,
+                "file": file,
+                "sha256": unchanged.sha256,
+                "digest": digest,
+                "from_base": true,
+            }));
+            continue;
+        }
+
+        println!("    Saving → {file}...");
+        let path = staging.join(file);
+        let (size, sha256) = save_image(runner, image, &path)?;
+        sums.push_str(&format!("{sha256}  {file}\n"));
+        entries.push(serde_json::json!({
+            "name": image,
+            "file": file,
+            "size": size.to_string(),
+            "sha256": sha256,
+            "digest": digest,
+        }));
+    }
+
+    fs::write(staging.join(CHECKSUMS_FILE), sums)?;
+    let mut manifest = serde_json::json!({ "images": entries, "identity_tag": identity_tag });
+    if let Some(base) = &base {
+        manifest["base"] = base_label(base).into();
+    }
+    fs::write(
+        staging.join(MANIFEST_FILE),
+        serde_json::to_string_pretty(&manifest)? + "\n",
+    )?;
+
+    if as_tarball {
+        println!("  Packing {}...", dest.display());
+        let encoder = GzEncoder::new(BufWriter::new(File::create(dest)?), Compression::fast());
+        let mut archive = tar::Builder::new(encoder);
+        archive.append_dir_all(".", &staging)?;
+        archive.into_inner()?.finish()?.flush()?;
+        fs::remove_dir_all(&staging)?;
+    }
+
+    if let Some(base) = &base {
+        let included = entries.iter().filter(|e| e["from_base"].is_null()).count();
+        println!(
+            "ℹ Delta bundle: {included} of {total} images; the host must already have {} loaded.",
+            base_label(base)
+        );
+    }
+    println!("✅ Bundle ready. On the airgapped host run:");
+    if as_tarball {
+        println!("   mkdir bundle && tar -xzf {} -C bundle", dest.display());
+        println!("   nqrust-identity-airgapped --payload-dir bundle");
+    } else {
+        println!(
+            "   nqrust-identity-airgapped --payload-dir {}",
+            dest.display()
+        );
+    }
+    Ok(())
+}
+
+/// How a delta refers to its base release.
+fn base_label(base: &Manifest) -> String {
+    base.identity_tag
+        .clone()
+        .unwrap_or_else(|| "the base release".to_string())
+}
+
+/// Keep the manifest of a payload that was loaded, so the next delta can be
+/// built against it. Best effort: the images are already in Docker.
+pub fn record_installed(dir: &Path, reporter: &Reporter) {
+    let target = utils::project_root().join(INSTALLED_MANIFEST);
+    if fs::copy(dir.join(MANIFEST_FILE), &target).is_ok() {
+        report!(reporter, "  Recorded loaded images in {}", target.display());
+    }
+}
+
+/// `docker save <image> | gzip > path`, returning the file size and SHA256.
+/// The reference to pull for `image`: a `:latest` image at `identity_tag`.
+fn release_ref(image: &str, identity_tag: &str) -> String {
+    match image.strip_suffix(":latest") {
+        Some(repository) => format!("{repository}:{identity_tag}"),
+        None => image.to_string(),
+    }
+}
+
+fn save_image(runner: &dyn CommandRunner, image: &str, path: &Path) -> Result<(u64, String)> {
+    // Spawned here rather than by the runner: `Cmd` can only capture stdout
+    // or send it to a file, and it is compressed on the way out.
+    let mut child = runner
+        .resolve(&Cmd::new("docker").args(["save", image]))
+        .std_command()
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let mut stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| eyre!("Failed to read docker save output"))?;
+
+    let mut encoder = GzEncoder::new(BufWriter::new(File::create(path)?), Compression::default());
+    io::copy(&mut stdout, &mut encoder)?;
+    encoder.finish()?.flush()?;
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        let _ = fs::remove_file(path);
+        return Err(eyre!(
+            "docker save {image} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let size = fs::metadata(path)?.len();
+    Ok((size, sha256_file(path)?))
+}
+
+/// Check every file listed in the bundle's SHA256SUMS. Bundles without the
+/// file (hand-assembled ones) are accepted with a warning.
+pub fn verify_dir(dir: &Path, reporter: &Reporter) -> Result<()> {
+    let Ok(sums) = fs::read_to_string(dir.join(CHECKSUMS_FILE)) else {
+        report!(
+            reporter,
+            "  ⚠️  No {CHECKSUMS_FILE} in bundle, skipping checksum verification"
+        );
+        return Ok(());
+    };
+    for line in sums.lines().filter(|l| !l.trim().is_empty()) {
+        let mut parts = line.split_whitespace();
+        let (Some(expected), Some(file)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        let file = file.trim_start_matches('*');
+        let actual = sha256_file(&dir.join(file))
+            .map_err(|e| eyre!("Cannot read {file} from bundle: {e}"))?;
+        if !actual.eq_ignore_ascii_case(expected) {
+            return Err(eyre!(
+                "Checksum mismatch for {file}: expected {expected}, got {actual}\n\
+                 Re-copy the bundle; it was corrupted in transfer."
+            ));
+        }
+        report!(reporter, "  ✓ {file}");
+    }
+    Ok(())
+}
+
+fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_names_corrupted_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("caddy.tar.gz"), "caddy").unwrap();
+        fs::write(dir.path().join("postgres.tar.gz"), "tampered").unwrap();
+        let manifest = serde_json::json!({
+            "images": [
+                {
+                    "name": "caddy:2-alpine",
+                    "file": "caddy.tar.gz",
+                    "size": "5",
+                    "sha256": "d6e0ff2b7a534a9750d13545971476557164f72cb92ad74be7f145491ec4acf4",
+                },
+                {
+                    "name": "postgres:16-alpine",
+                    "file": "postgres.tar.gz",
+                    "size": "8",
+                    "sha256": "0000",
+                },
+            ],
+            "identity_tag": "v1.0.0",
+        });
+        fs::write(dir.path().join(MANIFEST_FILE), manifest.to_string()).unwrap();
+
+        let manifest = Manifest::load(dir.path()).unwrap().unwrap();
+        let caddy = manifest.entry("caddy.tar.gz").unwrap();
+        assert_eq!(caddy.name, "caddy:2-alpine");
+        caddy.verify(dir.path()).unwrap();
+        let error = manifest
+            .entry("postgres.tar.gz")
+            .unwrap()
+            .verify(dir.path())
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("postgres.tar.gz (postgres:16-alpine) is corrupted"));
+        assert!(
+            Manifest::load(&dir.path().join("missing"))
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_delta_manifest() {
+        let manifest: Manifest = serde_json::from_str(
+            r#"{
+                "images": [
+                    {"name": "postgres:16-alpine", "file": "postgres.tar.gz",
+                     "sha256": "ab", "digest": "sha256:1", "from_base": true},
+                    {"name": "caddy:2-alpine", "file": "caddy.tar.gz", "size": "5",
+                     "sha256": "cd", "digest": "sha256:2"}
+                ],
+                "identity_tag": "v1.1.0",
+                "base": "v1.0.0"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(manifest.base.as_deref(), Some("v1.0.0"));
+        assert!(manifest.entry("postgres.tar.gz").unwrap().from_base);
+        assert!(!manifest.entry("caddy.tar.gz").unwrap().from_base);
+        assert_eq!(base_label(&manifest), "v1.1.0");
+    }
+
+    #[test]
+    fn test_release_ref() {
+        assert_eq!(
+            release_ref("ghcr.io/nexusquantum/nqrust-identity:latest", "v1.2.0"),
+            "ghcr.io/nexusquantum/nqrust-identity:v1.2.0"
+        );
+        assert_eq!(release_ref("caddy:2-alpine", "v1.2.0"), "caddy:2-alpine");
+    }
+}
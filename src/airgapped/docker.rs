@@ -6,21 +6,21 @@ use flate2::read::GzDecoder;
 use std::fs::File;
 use std::io;
 use std::path::Path;
-use std::process::{Command, Stdio};
-
-/// List of required Docker images for NQRust Identity (must match save-images.sh)
-const REQUIRED_IMAGES: &[(&str, &str)] = &[
-    ("postgres:16-alpine", "postgres.tar.gz"),
-    (
-        "ghcr.io/nexusquantum/nqrust-identity:latest",
-        "nqrust-identity.tar.gz",
-    ),
-    ("caddy:2-alpine", "caddy.tar.gz"),
-];
+use std::process::Stdio;
+
+use super::bundle::{Manifest, ManifestEntry};
+use super::progress::{Progress, ProgressReader, Reporter, report};
+use crate::platform::{self, DockerRuntime};
+use crate::runner::{Cmd, CommandRunner, blocking_output};
+
+/// Required Docker images for the selected stack (must match save-images.sh)
+pub fn required_images() -> &'static [(&'static str, &'static str)] {
+    crate::stack::current().required_images
+}
 
 /// Check if Docker is available
-pub fn check_docker_available() -> Result<()> {
-    let output = Command::new("docker").arg("--version").output();
+pub fn check_docker_available(runner: &dyn CommandRunner) -> Result<()> {
+    let output = blocking_output(runner, &Cmd::new("docker").arg("--version"));
 
     match output {
         Ok(_) => Ok(()),
@@ -35,45 +35,66 @@ pub fn check_docker_available() -> Result<()> {
 }
 
 /// Check if Docker daemon is running
-pub fn check_docker_running() -> Result<()> {
-    let output = Command::new("docker")
-        .arg("info")
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .status();
+pub fn check_docker_running(runner: &dyn CommandRunner) -> Result<()> {
+    let output = blocking_output(runner, &Cmd::new("docker").arg("info"));
 
     match output {
-        Ok(status) if status.success() => Ok(()),
-        _ => Err(eyre!(
-            "Docker daemon is not running\n\n\
-             Troubleshooting:\n\
-             - Start Docker daemon: sudo systemctl start docker\n\
-             - Enable Docker on boot: sudo systemctl enable docker\n\
-             - Check Docker status: sudo systemctl status docker\n\
-             - Ensure your user is in docker group: sudo usermod -aG docker $USER"
-        )),
+        Ok(output) if output.success => Ok(()),
+        _ => {
+            let runtime = DockerRuntime::detect();
+            Err(eyre!(
+                "Docker daemon is not running ({})\n\n\
+                 Troubleshooting:\n{}",
+                runtime.label(),
+                runtime.daemon_help()
+            ))
+        }
     }
 }
 
 /// Check if a specific Docker image exists locally
-fn image_exists(image_name: &str) -> Result<bool> {
-    let output = Command::new("docker")
-        .args(&["images", "-q", image_name])
-        .output()?;
+fn image_exists(runner: &dyn CommandRunner, image_name: &str) -> Result<bool> {
+    let output = blocking_output(
+        runner,
+        &Cmd::new("docker").args(["images", "-q", image_name]),
+    )?;
 
     Ok(!output.stdout.is_empty())
 }
 
+/// Image ID of a local image, `None` when it is not present.
+pub fn image_id(runner: &dyn CommandRunner, image_name: &str) -> Option<String> {
+    let inspect = Cmd::new("docker").args(["image", "inspect", "--format", "{{.Id}}", image_name]);
+    blocking_output(runner, &inspect).ok()?.value()
+}
+
+/// Whether the image an archive holds is already loaded: by image ID when
+/// the manifest records one, otherwise by tag.
+fn already_loaded(
+    runner: &dyn CommandRunner,
+    image_name: &str,
+    entry: Option<&ManifestEntry>,
+) -> Result<bool> {
+    match entry {
+        Some(ManifestEntry {
+            name,
+            digest: Some(digest),
+            ..
+        }) => Ok(image_id(runner, name).is_some_and(|id| id == *digest)),
+        _ => image_exists(runner, image_name),
+    }
+}
+
 /// Check if all required images are already loaded
-pub fn check_all_images_exist() -> Result<bool> {
+pub fn check_all_images_exist(runner: &dyn CommandRunner) -> Result<bool> {
     // First check if Docker is available
-    if check_docker_available().is_err() || check_docker_running().is_err() {
+    if check_docker_available(runner).is_err() || check_docker_running(runner).is_err() {
         return Ok(false);
     }
 
     // Check each required image
-    for (image_name, _) in REQUIRED_IMAGES {
-        if !image_exists(image_name)? {
+    for (image_name, _) in required_images() {
+        if !image_exists(runner, image_name)? {
             return Ok(false);
         }
     }
@@ -82,8 +103,13 @@ pub fn check_all_images_exist() -> Result<bool> {
 }
 
 /// Load a single Docker image from tar.gz file using Rust native decompression
-fn load_image(tar_gz_path: &Path, image_name: &str) -> Result<()> {
-    println!("    Loading {}...", image_name);
+fn load_image(
+    runner: &dyn CommandRunner,
+    tar_gz_path: &Path,
+    image_name: &str,
+    reporter: &Reporter,
+) -> Result<()> {
+    report!(reporter, "    Loading {}...", image_name);
 
     // Open the compressed tar.gz file
     let file = File::open(tar_gz_path).map_err(|e| {
@@ -101,11 +127,19 @@ fn load_image(tar_gz_path: &Path, image_name: &str) -> Result<()> {
     })?;
 
     // Decompress with Rust native GzDecoder
-    let mut decoder = GzDecoder::new(file);
+    let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+    let reader = ProgressReader::new(file, size, reporter, |done, total| Progress::ImageLoad {
+        done,
+        total,
+    });
+    let mut decoder = GzDecoder::new(reader);
 
-    // Spawn docker load process
-    let mut docker_load = Command::new("docker")
-        .arg("load")
+    // Spawned here rather than by the runner: `Cmd` can only feed stdin from
+    // a string or a file, and the archive is decompressed on the way in so
+    // its progress can be reported.
+    let mut docker_load = runner
+        .resolve(&Cmd::new("docker").arg("load"))
+        .std_command()
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
@@ -115,7 +149,7 @@ fn load_image(tar_gz_path: &Path, image_name: &str) -> Result<()> {
                 "Failed to spawn 'docker load' command: {}\n\n\
                  Troubleshooting:\n\
                  - Ensure Docker is installed: docker --version\n\
-                 - Check Docker daemon is running: sudo systemctl status docker\n\
+                 - Check Docker daemon is running: docker info\n\
                  - Verify Docker permissions: docker ps",
                 e
             )
@@ -134,14 +168,16 @@ fn load_image(tar_gz_path: &Path, image_name: &str) -> Result<()> {
              Troubleshooting:\n\
              - Check disk space: df -h\n\
              - Verify image file is not corrupted: gzip -t {}\n\
-             - Check Docker daemon logs: sudo journalctl -u docker -n 50",
+             - Check Docker daemon logs: {}",
             e,
-            tar_gz_path.display()
+            tar_gz_path.display(),
+            DockerRuntime::detect().logs_hint()
         )
     })?;
 
     // Close stdin to signal end of input
     drop(stdin);
+    decoder.into_inner().finish();
 
     // Wait for docker load to complete
     let output = docker_load
@@ -153,14 +189,16 @@ fn load_image(tar_gz_path: &Path, image_name: &str) -> Result<()> {
         return Err(eyre!(
             "Failed to load image '{}': {}\n\n\
              Troubleshooting:\n\
-             - Ensure Docker daemon is running: sudo systemctl start docker\n\
-             - Check disk space: df -h /var/lib/docker\n\
-             - Verify image file integrity: sha256sum {}\n\
-             - Check Docker logs: sudo journalctl -u docker -n 50\n\
-             - Try manual load: gunzip -c {} | docker load",
+             - Ensure Docker daemon is running: {}\n\
+             - Check disk space: docker system df\n\
+             - Verify image file integrity: {}\n\
+             - Check Docker logs: {}\n\
+             - Try manual load: docker load -i {}",
             image_name,
             stderr.trim(),
-            tar_gz_path.display(),
+            platform::docker_start_hint(),
+            platform::sha256_command(&tar_gz_path.display().to_string()),
+            DockerRuntime::detect().logs_hint(),
             tar_gz_path.display()
         ));
     }
@@ -169,41 +207,106 @@ fn load_image(tar_gz_path: &Path, image_name: &str) -> Result<()> {
 }
 
 /// Load all Docker images from extracted payload directory
-pub fn load_all_images(payload_dir: &Path) -> Result<()> {
+pub fn load_all_images(
+    runner: &dyn CommandRunner,
+    payload_dir: &Path,
+    reporter: &Reporter,
+) -> Result<()> {
     // Pre-flight checks
-    check_docker_available()?;
-    check_docker_running()?;
+    check_docker_available(runner)?;
+    check_docker_running(runner)?;
+
+    let manifest = Manifest::load(payload_dir)?;
+    if manifest.is_none() {
+        report!(
+            reporter,
+            "  ⚠️  No manifest.json in payload, image archives are not checksummed"
+        );
+    }
 
-    let total = REQUIRED_IMAGES.len();
-    println!("  Loading {} Docker images...", total);
+    let total = required_images().len();
+    report!(reporter, "  Loading {} Docker images...", total);
 
-    for (idx, (image_name, filename)) in REQUIRED_IMAGES.iter().enumerate() {
+    let mut skipped = 0;
+    for (idx, (image_name, filename)) in required_images().iter().enumerate() {
         let tar_gz_path = payload_dir.join(filename);
+        let entry = manifest.as_ref().and_then(|m| m.entry(filename));
+
+        report!(reporter, "  [{}/{}] {}", idx + 1, total, image_name);
+        reporter.send(Progress::ImageStarted {
+            index: idx + 1,
+            total,
+            name: image_name.to_string(),
+        });
+        // A previous run may have loaded some images before failing.
+        if already_loaded(runner, image_name, entry)? {
+            report!(reporter, "    ✓ already loaded");
+            skipped += 1;
+            continue;
+        }
+        if entry.is_some_and(|entry| entry.from_base) {
+            let base = manifest
+                .as_ref()
+                .and_then(|m| m.base.as_deref())
+                .unwrap_or("its base release");
+            return Err(eyre!(
+                "{} is not loaded, and this delta payload does not include it.\n\
+                 The payload only carries images changed since {}; load the full\n\
+                 payload for that release first, then this one.",
+                image_name,
+                base
+            ));
+        }
 
         if !tar_gz_path.exists() {
             return Err(eyre!("Image file not found: {}", filename));
         }
 
-        println!("  [{}/{}] {}", idx + 1, total, image_name);
-        load_image(&tar_gz_path, image_name)?;
+        // Hash each archive right before loading it, so a corrupted file is
+        // named instead of surfacing as a `docker load` or gzip error.
+        match manifest.as_ref().map(|m| m.entry(filename)) {
+            Some(Some(entry)) => {
+                entry.verify(payload_dir)?;
+                report!(reporter, "    ✓ {} checksum OK", filename);
+            }
+            Some(None) => report!(
+                reporter,
+                "    ⚠️  {} is not listed in manifest.json",
+                filename
+            ),
+            None => {}
+        }
+        load_image(runner, &tar_gz_path, image_name, reporter)?;
     }
 
-    println!("  ✓ All images loaded successfully");
+    if manifest.is_some() {
+        super::bundle::record_installed(payload_dir, reporter);
+    }
+
+    if skipped > 0 {
+        report!(
+            reporter,
+            "  ✓ All images loaded successfully ({} were already present)",
+            skipped
+        );
+    } else {
+        report!(reporter, "  ✓ All images loaded successfully");
+    }
 
     Ok(())
 }
 
 /// Verify all images are loaded correctly
 #[allow(dead_code)]
-pub fn verify_images_loaded() -> Result<()> {
-    println!("  Verifying images...");
+pub fn verify_images_loaded(runner: &dyn CommandRunner, reporter: &Reporter) -> Result<()> {
+    report!(reporter, "  Verifying images...");
 
-    for (image_name, _) in REQUIRED_IMAGES {
-        if !image_exists(image_name)? {
+    for (image_name, _) in required_images() {
+        if !image_exists(runner, image_name)? {
             return Err(eyre!("Image not found after loading: {}", image_name));
         }
     }
 
-    println!("  ✓ All images verified");
+    report!(reporter, "  ✓ All images verified");
     Ok(())
 }
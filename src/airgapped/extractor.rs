@@ -3,13 +3,16 @@
 
 use color_eyre::{Result, eyre::eyre};
 use flate2::read::GzDecoder;
-use indicatif::{ProgressBar, ProgressStyle};
 use std::fs::File;
 use std::io::{Read, Seek, SeekFrom};
 use std::path::Path;
 use tar::Archive;
 
 use super::PAYLOAD_MARKER;
+use super::progress::{Progress, ProgressReader, Reporter, report};
+use crate::disk;
+use crate::platform;
+use crate::runner::CommandRunner;
 
 const GZIP_MAGIC: [u8; 3] = [0x1f, 0x8b, 0x08];
 
@@ -20,6 +23,12 @@ pub fn has_payload_marker(path: &Path) -> Result<bool> {
     Ok(find_marker_position(&mut file).is_ok())
 }
 
+/// Offset of the payload marker, or None for a binary without a payload.
+pub fn marker_offset(path: &Path) -> Result<Option<u64>> {
+    let mut file = File::open(path)?;
+    Ok(find_marker_position(&mut file).ok())
+}
+
 /// Find the position of the payload marker in the file
 fn find_marker_position(file: &mut File) -> Result<u64> {
     let marker_len = PAYLOAD_MARKER.len();
@@ -79,54 +88,145 @@ fn payload_looks_valid(file: &File, payload_start: u64) -> Result<bool> {
     }
 }
 
-/// Extract the embedded payload to a temporary directory  
-pub fn extract_payload() -> Result<std::path::PathBuf> {
+/// Extract the embedded payload to a temporary directory under `workdir`
+/// (picked by free space when unset).
+pub fn extract_payload(
+    workdir: Option<&Path>,
+    runner: &dyn CommandRunner,
+    reporter: &Reporter,
+) -> Result<std::path::PathBuf> {
     let exe_path = std::env::current_exe()?;
     let mut exe_file = File::open(&exe_path)?;
 
     // Find marker position
-    println!("  Locating payload...");
+    report!(reporter, "  Locating payload...");
     let marker_pos = find_marker_position(&mut exe_file)?;
 
-    // Seek to start of payload (after marker)
+    // Payload starts right after the marker
     let payload_start = marker_pos + PAYLOAD_MARKER.len() as u64;
-    exe_file.seek(SeekFrom::Start(payload_start))?;
+    extract_from(exe_file, payload_start, None, workdir, runner, reporter)
+}
+
+/// Extract a sidecar `payload.tar.gz` (`--payload`) to a temporary directory.
+/// If `<path>.sha256` exists next to it, the payload must match that checksum.
+pub fn extract_payload_file(
+    path: &Path,
+    workdir: Option<&Path>,
+    runner: &dyn CommandRunner,
+    reporter: &Reporter,
+) -> Result<std::path::PathBuf> {
+    let mut file =
+        File::open(path).map_err(|e| eyre!("Cannot open payload {}: {}", path.display(), e))?;
+
+    let mut magic = [0u8; 3];
+    file.read_exact(&mut magic)
+        .map_err(|_| eyre!("{} is not a gzip payload", path.display()))?;
+    if magic != GZIP_MAGIC || !payload_looks_valid(&file, 0)? {
+        return Err(eyre!("{} is not a valid payload.tar.gz", path.display()));
+    }
+
+    let sidecar = format!("{}.sha256", path.display());
+    let expected = std::fs::read_to_string(&sidecar).ok().and_then(|s| {
+        s.split_whitespace()
+            .next()
+            .map(|sum| sum.to_ascii_lowercase())
+    });
+    extract_from(file, 0, expected.as_deref(), workdir, runner, reporter)
+}
+
+/// Shared extraction for embedded and sidecar payloads: `payload_start` is
+/// where the gzip stream begins, and the payload runs to the end of the file.
+fn extract_from(
+    mut file: File,
+    payload_start: u64,
+    expected_sha256: Option<&str>,
+    workdir: Option<&Path>,
+    runner: &dyn CommandRunner,
+    reporter: &Reporter,
+) -> Result<std::path::PathBuf> {
+    file.seek(SeekFrom::Start(payload_start))?;
 
     // Get payload size
-    let file_size = exe_file.metadata()?.len();
+    let file_size = file.metadata()?.len();
     let payload_size = file_size - payload_start;
 
-    println!(
+    report!(
+        reporter,
         "  Payload size: {:.2} GB",
         payload_size as f64 / 1_073_741_824.0
     );
 
+    let work_dir = match workdir {
+        Some(dir) => {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| eyre!("Cannot create work directory {}: {}", dir.display(), e))?;
+            dir.to_path_buf()
+        }
+        None => disk::pick_workdir(disk::extraction_size(payload_size)),
+    };
+    report!(
+        reporter,
+        "  Extracting into {} ({} free){}",
+        work_dir.display(),
+        disk::free_bytes(&work_dir).map_or_else(|| "unknown".to_string(), disk::gib),
+        if workdir.is_some() {
+            ""
+        } else {
+            " — largest suitable filesystem; override with --workdir"
+        }
+    );
+
+    // Fail now rather than halfway through extraction or `docker load`.
+    let requirements =
+        disk::payload_requirements(payload_size, work_dir.clone(), disk::docker_root(runner));
+    for requirement in &requirements {
+        report!(reporter, "  {}", requirement.line());
+    }
+    if requirements.iter().any(disk::Requirement::is_short) {
+        return Err(eyre!(
+            "Not enough free disk space to extract and load the images.\n\n\
+             Free some space and run the installer again:\n\
+             - docker system prune -f  (stopped containers, unused networks,\n\
+             \u{20}  dangling images and build cache; volumes are kept)\n\
+             - pass --workdir (or set NQRUST_WORKDIR) to extract on a larger filesystem"
+        ));
+    }
+
     // Verify payload integrity with quick checksum
-    println!("  Verifying payload integrity...");
-    let payload_checksum = verify_payload_integrity(&mut exe_file, payload_start, payload_size)?;
-    println!("  ✓ Payload checksum: {}...", &payload_checksum[..16]);
+    report!(reporter, "  Verifying payload integrity...");
+    let payload_checksum = verify_payload_integrity(&mut file, payload_start, payload_size)?;
+    if let Some(expected) = expected_sha256
+        && payload_checksum != expected
+    {
+        return Err(eyre!(
+            "Payload checksum mismatch: expected {}, got {}\n\
+             The payload was corrupted in transfer; copy it again.",
+            expected,
+            payload_checksum
+        ));
+    }
+    report!(
+        reporter,
+        "  ✓ Payload checksum: {}...",
+        &payload_checksum[..16]
+    );
 
     // Reset to payload start for extraction
-    exe_file.seek(SeekFrom::Start(payload_start))?;
+    file.seek(SeekFrom::Start(payload_start))?;
 
     // Create temporary directory
-    let temp_dir = tempfile::tempdir()?;
+    let temp_dir = tempfile::Builder::new()
+        .prefix("nqrust-payload-")
+        .tempdir_in(&work_dir)?;
     let temp_path = temp_dir.keep(); // Use keep() instead of into_path()
 
-    // Setup progress bar
-    let pb = ProgressBar::new(payload_size);
-    pb.set_style(
-        ProgressStyle::default_bar()
-            .template("  [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
-            .unwrap()
-            .progress_chars("#>-"),
-    );
-
     // Extract tar.gz payload with streaming
-    println!("  Extracting...");
+    report!(reporter, "  Extracting...");
 
     // Wrap file reader with progress tracking
-    let reader = ProgressReader::new(exe_file, pb.clone());
+    let reader = ProgressReader::new(file, payload_size, reporter, |done, total| {
+        Progress::Extract { done, total }
+    });
 
     // Decompress gzip
     let decoder = GzDecoder::new(reader);
@@ -138,16 +238,18 @@ pub fn extract_payload() -> Result<std::path::PathBuf> {
             "Failed to extract payload: {}\n\n\
              Troubleshooting:\n\
              - Payload may be corrupted during transfer\n\
-             - Verify binary checksum: sha256sum -c nqrust-analytics-airgapped.sha256\n\
-             - Check disk space: df -h /tmp\n\
+             - Verify binary checksum: {} (compare with the .sha256 file)\n\
+             - Check free disk space in {}\n\
              - Re-download or re-transfer the binary\n\
              Original error: {}",
             e,
+            platform::sha256_command(&platform::exe_name()),
+            work_dir.display(),
             e
         )
     })?;
 
-    pb.finish_with_message("Extraction complete");
+    archive.into_inner().into_inner().finish();
 
     Ok(temp_path)
 }
@@ -175,23 +277,3 @@ fn verify_payload_integrity(file: &mut File, start: u64, size: u64) -> Result<St
     let result = hasher.finalize();
     Ok(format!("{:x}", result))
 }
-
-/// Wrapper to track read progress
-struct ProgressReader<R> {
-    inner: R,
-    progress: ProgressBar,
-}
-
-impl<R> ProgressReader<R> {
-    fn new(inner: R, progress: ProgressBar) -> Self {
-        Self { inner, progress }
-    }
-}
-
-impl<R: Read> Read for ProgressReader<R> {
-    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        let n = self.inner.read(buf)?;
-        self.progress.inc(n as u64);
-        Ok(n)
-    }
-}
@@ -1,10 +1,20 @@
 // airgapped/mod.rs
 // Main module for airgapped installer functionality
 
+pub mod bundle;
 pub mod docker;
 pub mod extractor;
+pub mod progress;
 
 use color_eyre::Result;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use crate::cli::Cli;
+use crate::install_log;
+use crate::runner::CommandRunner;
+use progress::{Progress, Reporter, report};
 
 /// Marker string that separates binary code from embedded payload
 pub const PAYLOAD_MARKER: &[u8] = b"__NQRUST_PAYLOAD__";
@@ -20,37 +30,141 @@ pub fn is_airgapped_binary() -> Result<bool> {
 }
 
 /// Check if Docker images are already loaded locally
-pub fn images_already_loaded() -> Result<bool> {
-    docker::check_all_images_exist()
+pub fn images_already_loaded(runner: &dyn CommandRunner) -> Result<bool> {
+    docker::check_all_images_exist(runner)
+}
+
+/// Where an offline run gets its images from.
+#[derive(Debug, Clone)]
+pub enum Source {
+    /// Payload appended to this binary.
+    Embedded,
+    /// Sidecar payload.tar.gz given with `--payload`.
+    File(PathBuf),
+    /// Exported bundle directory given with `--payload-dir`.
+    Dir(PathBuf),
+}
+
+impl Source {
+    /// `None` for an online run.
+    pub fn from_cli(cli: &Cli) -> Result<Option<Self>> {
+        Ok(if let Some(dir) = &cli.payload_dir {
+            Some(Source::Dir(dir.clone()))
+        } else if let Some(path) = &cli.payload {
+            Some(Source::File(path.clone()))
+        } else if is_airgapped_binary()? {
+            Some(Source::Embedded)
+        } else {
+            None
+        })
+    }
 }
 
 /// Main setup function for airgapped installation
-/// Extracts payload and loads Docker images
-pub async fn setup() -> Result<()> {
-    println!("\n🔒 Airgapped mode detected");
+/// Loads Docker images from `source`, extracting payloads into `workdir`.
+/// Blocks; docker runs through `runner` (see `runner::blocking_output`).
+pub fn setup(
+    source: &Source,
+    workdir: Option<&Path>,
+    runner: &dyn CommandRunner,
+    reporter: &Reporter,
+) -> Result<()> {
+    install_log::start();
+    match source {
+        Source::Dir(dir) => setup_from_dir(dir, runner, reporter),
+        Source::File(path) => setup_from_payload(Some(path), workdir, runner, reporter),
+        Source::Embedded => setup_from_payload(None, workdir, runner, reporter),
+    }
+}
+
+/// Run `setup` on a blocking thread, reporting over a channel. The last
+/// event is always `Progress::Finished`.
+pub fn spawn_setup(
+    source: Source,
+    workdir: Option<PathBuf>,
+    runner: Arc<dyn CommandRunner>,
+) -> UnboundedReceiver<Progress> {
+    let (reporter, events) = Reporter::channel();
+    tokio::task::spawn_blocking(move || {
+        let result = setup(&source, workdir.as_deref(), runner.as_ref(), &reporter);
+        reporter.send(Progress::Finished(result.map_err(|e| format!("{e:#}"))));
+    });
+    events
+}
+
+/// Extracts payload (embedded, or the sidecar file given with `--payload`)
+/// and loads Docker images
+fn setup_from_payload(
+    payload: Option<&Path>,
+    workdir: Option<&Path>,
+    runner: &dyn CommandRunner,
+    reporter: &Reporter,
+) -> Result<()> {
+    report!(reporter, "\n🔒 Airgapped mode detected");
 
     // Check if images already loaded
-    if images_already_loaded()? {
-        println!("✓ Docker images already loaded, skipping extraction");
+    if images_already_loaded(runner)? {
+        report!(
+            reporter,
+            "✓ Docker images already loaded, skipping extraction"
+        );
         return Ok(());
     }
 
-    println!("📦 Extracting embedded Docker images...");
-
     // Extract payload to temporary directory
-    let temp_dir = extractor::extract_payload()?;
+    let temp_dir = match payload {
+        Some(path) => {
+            report!(
+                reporter,
+                "📦 Extracting Docker images from {}...",
+                path.display()
+            );
+            extractor::extract_payload_file(path, workdir, runner, reporter)?
+        }
+        None => {
+            report!(reporter, "📦 Extracting embedded Docker images...");
+            extractor::extract_payload(workdir, runner, reporter)?
+        }
+    };
 
-    println!("🐳 Loading images to Docker...");
+    report!(reporter, "🐳 Loading images to Docker...");
 
     // Load all images to Docker
-    docker::load_all_images(&temp_dir)?;
+    docker::load_all_images(runner, &temp_dir, reporter)?;
 
-    println!("🧹 Cleaning up temporary files...");
+    report!(reporter, "🧹 Cleaning up temporary files...");
 
     // Cleanup temp directory
     std::fs::remove_dir_all(&temp_dir)?;
 
-    println!("✓ Airgapped setup complete!\n");
+    report!(reporter, "✓ Airgapped setup complete!\n");
+
+    Ok(())
+}
+
+/// Airgapped setup from an exported bundle directory (`--payload-dir`)
+/// instead of the payload embedded in the binary.
+fn setup_from_dir(dir: &Path, runner: &dyn CommandRunner, reporter: &Reporter) -> Result<()> {
+    report!(
+        reporter,
+        "\n🔒 Offline mode — using bundle at {}",
+        dir.display()
+    );
+
+    if images_already_loaded(runner)? {
+        report!(reporter, "✓ Docker images already loaded, skipping bundle");
+        return Ok(());
+    }
+
+    // With a manifest, each archive is checked as it is loaded.
+    if bundle::Manifest::load(dir)?.is_none() {
+        report!(reporter, "🔍 Verifying bundle...");
+        bundle::verify_dir(dir, reporter)?;
+    }
+
+    report!(reporter, "🐳 Loading images to Docker...");
+    docker::load_all_images(runner, dir, reporter)?;
 
+    report!(reporter, "✓ Airgapped setup complete!\n");
     Ok(())
 }
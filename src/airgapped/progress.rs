@@ -0,0 +1,126 @@
+// airgapped/progress.rs
+// Progress of an airgapped setup. On its own (headless, or before the TUI
+// exists) lines are printed and extraction draws an indicatif bar; inside the
+// TUI the same information arrives as `Progress` events over a channel.
+
+use indicatif::{ProgressBar, ProgressStyle};
+use std::io::Read;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+use crate::install_log::{self, logln};
+
+#[derive(Debug, Clone)]
+pub enum Progress {
+    /// A display line, already recorded in the install log.
+    Log(String),
+    /// Bytes of the payload read by the extraction so far.
+    Extract { done: u64, total: u64 },
+    /// Image `index` (1-based) of `total` is being loaded.
+    ImageStarted {
+        index: usize,
+        total: usize,
+        name: String,
+    },
+    /// Bytes of the current image archive streamed into `docker load`.
+    ImageLoad { done: u64, total: u64 },
+    /// Setup is over; the error is the full report.
+    Finished(Result<(), String>),
+}
+
+/// Where a setup reports to: stdout, or a channel read by the TUI.
+#[derive(Clone, Default)]
+pub struct Reporter {
+    tx: Option<UnboundedSender<Progress>>,
+}
+
+impl Reporter {
+    pub fn channel() -> (Self, UnboundedReceiver<Progress>) {
+        let (tx, rx) = unbounded_channel();
+        (Self { tx: Some(tx) }, rx)
+    }
+
+    /// `logln!`, or a `Log` event when reporting to the TUI.
+    pub fn log(&self, line: String) {
+        match &self.tx {
+            Some(tx) => {
+                install_log::record(&line);
+                let _ = tx.send(Progress::Log(line));
+            }
+            None => logln!("{line}"),
+        }
+    }
+
+    /// Send a progress event; dropped when printing to stdout.
+    pub fn send(&self, event: Progress) {
+        if let Some(tx) = &self.tx {
+            let _ = tx.send(event);
+        }
+    }
+
+    /// Terminal progress bar for `total` bytes, hidden inside the TUI.
+    pub fn bar(&self, total: u64) -> ProgressBar {
+        if self.tx.is_some() {
+            return ProgressBar::hidden();
+        }
+        let bar = ProgressBar::new(total);
+        bar.set_style(
+            ProgressStyle::default_bar()
+                .template("  [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
+                .unwrap()
+                .progress_chars("#>-"),
+        );
+        bar
+    }
+}
+
+/// `format!` into `Reporter::log`, the way `logln!` wraps `println!`.
+macro_rules! report {
+    ($reporter:expr, $($arg:tt)*) => {
+        $reporter.log(format!($($arg)*))
+    };
+}
+pub(crate) use report;
+
+/// Reader that advances a bar and reports bytes read, in steps of about
+/// half a percent so a multi-GB stream doesn't flood the channel.
+pub struct ProgressReader<R> {
+    inner: R,
+    bar: ProgressBar,
+    reporter: Reporter,
+    event: fn(u64, u64) -> Progress,
+    done: u64,
+    total: u64,
+    reported: u64,
+}
+
+impl<R> ProgressReader<R> {
+    pub fn new(inner: R, total: u64, reporter: &Reporter, event: fn(u64, u64) -> Progress) -> Self {
+        Self {
+            inner,
+            bar: reporter.bar(total),
+            reporter: reporter.clone(),
+            event,
+            done: 0,
+            total,
+            reported: 0,
+        }
+    }
+
+    pub fn finish(&self) {
+        self.bar.finish();
+        self.reporter.send((self.event)(self.done, self.total));
+    }
+}
+
+impl<R: Read> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.bar.inc(n as u64);
+        self.done += n as u64;
+        if self.done - self.reported >= (self.total / 200).max(1) {
+            self.reported = self.done;
+            self.reporter.send((self.event)(self.done, self.total));
+        }
+        Ok(n)
+    }
+}
@@ -0,0 +1,164 @@
+//! Programmatic installer API.
+//!
+//! Runs the same steps as `nqrust-identity --headless` / `--uninstall`, but
+//! reports through a callback instead of stdout, so tests, a GUI or other
+//! automation can drive an install without the TUI.
+//!
+//! ```no_run
+//! use nqrust_installer::api::{Event, InstallOptions, Installer};
+//!
+//! # async fn run() -> color_eyre::Result<()> {
+//! let installer = Installer::new(InstallOptions::default())?;
+//! installer
+//!     .install(|event| {
+//!         if let Event::StepStarted { step, index, total } = event {
+//!             println!("[{index}/{total}] {step}");
+//!         }
+//!     })
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Events go to one listener per process, so run one installer operation at
+//! a time. On macOS, call [`crate::platform::configure_docker_host`] once at
+//! startup, before spawning threads, as the binary does.
+
+use std::path::PathBuf;
+
+use color_eyre::Result;
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use crate::airgapped::{self, Source, progress::Progress};
+use crate::cli::Cli;
+use crate::headless;
+use crate::remote::{self, RemoteTarget};
+use crate::settings::Settings;
+use crate::throttle::{self, Limits};
+use crate::update_check::{self, UpdateStatus};
+use crate::{docker_context, output, stack};
+
+pub use crate::install_log::Level;
+pub use crate::output::Event;
+
+/// What to install. Each field behaves like the command-line flag of the
+/// same name.
+#[derive(Debug, Clone)]
+pub struct InstallOptions {
+    /// Stack id, e.g. `identity`.
+    pub stack: String,
+    /// SERVER_IP for the certificate and .env; auto-detected when `None`.
+    pub server_ip: Option<String>,
+    /// Offline install from a sidecar payload.tar.gz.
+    pub payload: Option<PathBuf>,
+    /// Offline install from an exported bundle directory.
+    pub payload_dir: Option<PathBuf>,
+    /// Where payloads are extracted; picked by free space when `None`.
+    pub workdir: Option<PathBuf>,
+    /// Self-update into ~/.local/bin without sudo.
+    pub no_root: bool,
+    /// Install onto this host over SSH instead of the local Docker.
+    pub remote: Option<RemoteTarget>,
+}
+
+impl Default for InstallOptions {
+    fn default() -> Self {
+        Self {
+            stack: "identity".to_string(),
+            server_ip: None,
+            payload: None,
+            payload_dir: None,
+            workdir: None,
+            no_root: false,
+            remote: None,
+        }
+    }
+}
+
+pub struct Installer {
+    cli: Cli,
+}
+
+impl Installer {
+    /// Select the stack for this process. Fails for an unknown stack, or
+    /// when a different stack was already selected.
+    pub fn new(options: InstallOptions) -> Result<Self> {
+        if let Err(e) = stack::select(&options.stack)
+            && stack::current().id != options.stack
+        {
+            return Err(e);
+        }
+        let settings = Settings::load();
+        // With a remote target the SSH host fixes the daemon, not a context.
+        docker_context::select(settings.docker_context.filter(|_| options.remote.is_none()));
+        throttle::select(Limits::from_settings(&settings.bandwidth));
+        Ok(Self {
+            cli: Cli {
+                headless: true,
+                stack: options.stack,
+                server_ip: options.server_ip,
+                payload: options.payload,
+                payload_dir: options.payload_dir,
+                workdir: options.workdir,
+                no_root: options.no_root,
+                remote: options.remote,
+                ..Cli::default()
+            },
+        })
+    }
+
+    /// Where offline images come from; `None` for an online install.
+    pub fn offline_source(&self) -> Result<Option<Source>> {
+        Source::from_cli(&self.cli)
+    }
+
+    /// Load offline images (if any), then install and start the stack and
+    /// wait until every service answers. Ends with an `Event::Finished`.
+    pub async fn install(
+        &self,
+        on_event: impl Fn(&Event<'_>) + Send + Sync + 'static,
+    ) -> Result<()> {
+        let _guard = output::listen(on_event);
+        if let Some(source) = self.offline_source()? {
+            // Extraction and `docker load` block; keep them off the runtime.
+            let workdir = self.cli.workdir();
+            let runner = remote::runner(self.cli.remote().as_ref());
+            tokio::task::spawn_blocking(move || {
+                airgapped::setup(
+                    &source,
+                    workdir.as_deref(),
+                    runner.as_ref(),
+                    &airgapped::progress::Reporter::default(),
+                )
+            })
+            .await??;
+        }
+        headless::install(&self.cli).await
+    }
+
+    /// Extract and load offline images on a background thread, reporting
+    /// byte-level progress; `None` for an online install. `install` does
+    /// this itself, this is for frontends that show it as its own step.
+    pub fn prepare_offline_images(&self) -> Result<Option<UnboundedReceiver<Progress>>> {
+        Ok(self.offline_source()?.map(|source| {
+            let runner = remote::runner(self.cli.remote().as_ref());
+            airgapped::spawn_setup(source, self.cli.workdir(), runner)
+        }))
+    }
+
+    /// Stop and remove the stack; `purge` also deletes the database volume.
+    pub async fn uninstall(
+        &self,
+        purge: bool,
+        on_event: impl Fn(&Event<'_>) + Send + Sync + 'static,
+    ) -> Result<()> {
+        let _guard = output::listen(on_event);
+        headless::uninstall(&self.cli, purge).await
+    }
+
+    /// Look up image and installer updates once. Network errors are
+    /// reported in `UpdateStatus::error`.
+    pub async fn check_updates(&self) -> UpdateStatus {
+        update_check::check_once(&Settings::load(), self.cli.no_root).await
+    }
+}
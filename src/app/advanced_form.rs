@@ -0,0 +1,233 @@
+use std::collections::BTreeMap;
+
+use crate::app::form::{Field, Form};
+use crate::env_file;
+use crate::i18n::{tr, trf};
+use crate::settings::{AdvancedSettings, LogLevel, RestartPolicy, ServiceTuning};
+use crate::stack;
+
+/// Fields before the per-service ones: network name and log level.
+const GLOBAL_FIELDS: usize = 2;
+/// Fields per service.
+const SERVICE_FIELDS: usize = 4;
+
+#[derive(Debug)]
+pub struct AdvancedForm {
+    /// Network name, log level, then CPUs, memory, restart and environment
+    /// per service.
+    pub form: Form,
+    services: Vec<&'static str>,
+}
+
+impl AdvancedForm {
+    pub fn from_settings(advanced: &AdvancedSettings) -> Self {
+        let services: Vec<&'static str> = stack::current().service_names().collect();
+        let mut fields = vec![
+            Field::new(
+                tr("advanced.network"),
+                advanced.network_name.clone().unwrap_or_default(),
+            )
+            .placeholder(trf(
+                "advanced.network_default",
+                &[("network", stack::current().network)],
+            ))
+            .validator(validate_network_name),
+            Field::new(
+                tr("advanced.log_level"),
+                advanced.log_level.map(LogLevel::as_str).unwrap_or_default(),
+            )
+            .placeholder("<error, warn, info, debug>")
+            .validator(|value| {
+                if value.is_empty() || LogLevel::parse(value).is_some() {
+                    Ok(())
+                } else {
+                    Err(trf(
+                        "advanced.log_level_invalid",
+                        &[("levels", &LogLevel::ALL.map(LogLevel::as_str).join(", "))],
+                    ))
+                }
+            }),
+        ];
+        for service in &services {
+            let tuning = advanced.services.get(*service).cloned().unwrap_or_default();
+            fields.push(
+                Field::new(
+                    trf("advanced.cpus", &[("service", service)]),
+                    tuning.cpus.unwrap_or_default(),
+                )
+                .placeholder(tr("advanced.cpus_placeholder"))
+                .validator(validate_cpus),
+            );
+            fields.push(
+                Field::new(
+                    trf("advanced.memory", &[("service", service)]),
+                    tuning.memory.unwrap_or_default(),
+                )
+                .placeholder(tr("advanced.memory_placeholder"))
+                .validator(validate_memory),
+            );
+            fields.push(
+                Field::new(
+                    trf("advanced.restart", &[("service", service)]),
+                    tuning
+                        .restart
+                        .map(RestartPolicy::as_str)
+                        .unwrap_or_default(),
+                )
+                .placeholder("<no, always, on-failure, unless-stopped>")
+                .validator(|value| {
+                    if value.is_empty() || RestartPolicy::parse(value).is_some() {
+                        Ok(())
+                    } else {
+                        Err(trf(
+                            "advanced.restart_invalid",
+                            &[(
+                                "policies",
+                                &RestartPolicy::ALL.map(RestartPolicy::as_str).join(", "),
+                            )],
+                        ))
+                    }
+                }),
+            );
+            fields.push(
+                Field::new(
+                    trf("advanced.environment", &[("service", service)]),
+                    format_environment(&tuning.environment),
+                )
+                .placeholder("<KEY=VALUE; KEY=VALUE>")
+                .validator(|value| parse_environment(value).map(|_| ())),
+            );
+        }
+        Self {
+            form: Form::new(fields),
+            services,
+        }
+    }
+
+    /// Call after `validate`; fields that fail to parse are left out.
+    pub fn to_settings(&self) -> AdvancedSettings {
+        let value = |idx: usize| {
+            let v = self.form.value(idx);
+            (!v.is_empty()).then(|| v.to_string())
+        };
+        let mut advanced = AdvancedSettings {
+            network_name: value(0),
+            log_level: value(1).and_then(|v| LogLevel::parse(&v)),
+            services: BTreeMap::new(),
+        };
+        for (n, service) in self.services.iter().enumerate() {
+            let base = GLOBAL_FIELDS + n * SERVICE_FIELDS;
+            let tuning = ServiceTuning {
+                cpus: value(base),
+                memory: value(base + 1),
+                restart: value(base + 2).and_then(|v| RestartPolicy::parse(&v)),
+                environment: parse_environment(self.form.value(base + 3)).unwrap_or_default(),
+            };
+            if !tuning.is_empty() {
+                advanced.services.insert(service.to_string(), tuning);
+            }
+        }
+        advanced
+    }
+
+    pub fn validate(&mut self) -> bool {
+        self.form.validate()
+    }
+}
+
+fn validate_network_name(name: &str) -> Result<(), String> {
+    let valid = name
+        .chars()
+        .enumerate()
+        .all(|(i, c)| c.is_ascii_alphanumeric() || (i > 0 && matches!(c, '_' | '.' | '-')));
+    if valid {
+        Ok(())
+    } else {
+        Err(tr("advanced.network_invalid").to_string())
+    }
+}
+
+fn validate_cpus(value: &str) -> Result<(), String> {
+    match value.parse::<f64>() {
+        _ if value.is_empty() => Ok(()),
+        Ok(cpus) if cpus > 0.0 && cpus.is_finite() => Ok(()),
+        _ => Err(trf("advanced.cpus_invalid", &[("value", value)])),
+    }
+}
+
+fn validate_memory(value: &str) -> Result<(), String> {
+    let digits = value.trim_end_matches(['b', 'k', 'm', 'g', 'B', 'K', 'M', 'G']);
+    let unit_len = value.len() - digits.len();
+    if value.is_empty() || (unit_len <= 1 && digits.parse::<u64>().is_ok_and(|bytes| bytes > 0)) {
+        Ok(())
+    } else {
+        Err(trf("advanced.memory_invalid", &[("value", value)]))
+    }
+}
+
+/// `KEY=VALUE` pairs separated by `;`.
+fn parse_environment(value: &str) -> Result<BTreeMap<String, String>, String> {
+    let mut environment = BTreeMap::new();
+    for pair in value.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+        let Some((key, value)) = pair.split_once('=') else {
+            return Err(trf("advanced.pair_invalid", &[("pair", pair)]));
+        };
+        let key = key.trim();
+        if !env_file::is_key(key) {
+            return Err(trf("advanced.key_invalid", &[("key", key)]));
+        }
+        environment.insert(key.to_string(), value.trim().to_string());
+    }
+    Ok(environment)
+}
+
+fn format_environment(environment: &BTreeMap<String, String>) -> String {
+    environment
+        .iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::text_input::TextInput;
+
+    #[test]
+    fn test_round_trip_and_rejects_bad_values() {
+        let mut advanced = AdvancedSettings {
+            network_name: Some("corp-identity".to_string()),
+            log_level: Some(LogLevel::Debug),
+            services: BTreeMap::new(),
+        };
+        advanced.services.insert(
+            "identity".to_string(),
+            ServiceTuning {
+                cpus: Some("2".to_string()),
+                memory: Some("1g".to_string()),
+                restart: Some(RestartPolicy::Always),
+                environment: BTreeMap::from([("TZ".to_string(), "Asia/Jakarta".to_string())]),
+            },
+        );
+        let mut form = AdvancedForm::from_settings(&advanced);
+        assert!(form.validate());
+        assert_eq!(form.to_settings(), advanced);
+
+        // postgres comes first: its memory, then its restart policy.
+        form.form.fields[3].input = TextInput::new("lots");
+        assert!(!form.validate());
+        assert!(form.form.error_message.contains("512m"));
+        form.form.fields[3].input = TextInput::new("");
+        form.form.fields[4].input = TextInput::new("sometimes");
+        assert!(!form.validate());
+        assert!(form.form.error_message.contains("unless-stopped"));
+        form.form.fields[4].input = TextInput::new("");
+        form.form.fields[1].input = TextInput::new("verbose");
+        assert!(!form.validate());
+        assert!(form.form.error_message.contains("debug"));
+
+        assert!(parse_environment("A=1; bad pair").is_err());
+        assert_eq!(parse_environment(" A=1 ;; B = x=y ").unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,101 @@
+// airgapped_setup.rs - offline image setup as the first TUI screen
+// Extraction and `docker load` run on a blocking thread; the screen drains
+// their progress events each frame.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::mpsc::error::TryRecvError;
+
+use crate::airgapped::{self, Source, progress::Progress};
+use crate::i18n::tr;
+use crate::install_log::{self, Level};
+use crate::runner::CommandRunner;
+
+#[derive(Debug)]
+pub struct AirgappedSetup {
+    source: Source,
+    workdir: Option<PathBuf>,
+    runner: Arc<dyn CommandRunner>,
+    events: Option<UnboundedReceiver<Progress>>,
+    /// Bytes of the payload extracted, out of its size.
+    pub extract: Option<(u64, u64)>,
+    /// Image being loaded: position, count and name.
+    pub image: Option<(usize, usize, String)>,
+    /// Bytes of the current image archive loaded, out of its size.
+    pub image_load: Option<(u64, u64)>,
+    /// Set when the setup failed; Enter retries.
+    pub error: Option<String>,
+    pub done: bool,
+}
+
+impl AirgappedSetup {
+    pub fn new(source: Source, workdir: Option<PathBuf>, runner: Arc<dyn CommandRunner>) -> Self {
+        Self {
+            source,
+            workdir,
+            runner,
+            events: None,
+            extract: None,
+            image: None,
+            image_load: None,
+            error: None,
+            done: false,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.events.is_some()
+    }
+
+    /// Start (or restart, after a failure) the setup in the background.
+    pub fn start(&mut self) {
+        self.extract = None;
+        self.image = None;
+        self.image_load = None;
+        self.error = None;
+        self.events = Some(airgapped::spawn_setup(
+            self.source.clone(),
+            self.workdir.clone(),
+            self.runner.clone(),
+        ));
+    }
+
+    /// Apply the events received since the last call; returns their log lines.
+    pub fn poll(&mut self) -> Vec<String> {
+        let mut lines = Vec::new();
+        let Some(events) = self.events.as_mut() else {
+            return lines;
+        };
+        loop {
+            match events.try_recv() {
+                Ok(Progress::Log(line)) => lines.push(line),
+                Ok(Progress::Extract { done, total }) => self.extract = Some((done, total)),
+                Ok(Progress::ImageStarted { index, total, name }) => {
+                    self.image = Some((index, total, name));
+                    self.image_load = None;
+                }
+                Ok(Progress::ImageLoad { done, total }) => self.image_load = Some((done, total)),
+                Ok(Progress::Finished(result)) => {
+                    self.events = None;
+                    match result {
+                        Ok(()) => self.done = true,
+                        Err(e) => {
+                            install_log::write(Level::Error, &e);
+                            self.error = Some(e);
+                        }
+                    }
+                    break;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.events = None;
+                    self.error = Some(tr("airgapped.stopped").to_string());
+                    break;
+                }
+            }
+        }
+        lines
+    }
+}
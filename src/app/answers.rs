@@ -0,0 +1,377 @@
+// answers.rs - `--answers install.yaml` for unattended TUI installs
+// The file pre-fills what the forms would ask for (GHCR token, SSL address,
+// admin credentials, .env values and config templates) and the TUI walks
+// through the screens on its own, still showing progress. Any screen the
+// answers cannot get past is left to the user. `export` writes one from the
+// current install, with secrets as `${VAR}` references to the environment.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use color_eyre::{Result, eyre::eyre};
+use serde::{Deserialize, Serialize};
+
+use super::template_form;
+use crate::env_file::{self, EnvFile, is_secret_key};
+use crate::settings::Settings;
+use crate::{stack, templates};
+
+/// Written by the "Export answers" action, in the project directory.
+pub const EXPORT_FILE: &str = "answers.yaml";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Answers {
+    /// GHCR login. Without it the token comes from GHCR_TOKEN or the saved
+    /// credential, or the login is skipped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registry: Option<RegistryAnswers>,
+    pub ssl: SslAnswers,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub admin: Option<AdminAnswers>,
+    /// Written to .env before the install; only keys the stack reads.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Config templates to render, by template file name without extension,
+    /// then field key. Fields left out keep their defaults.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: BTreeMap<String, BTreeMap<String, String>>,
+    /// Start the install once everything is set up.
+    pub install: bool,
+    /// Quit after a successful install instead of showing the result.
+    pub exit_when_done: bool,
+}
+
+impl Default for Answers {
+    fn default() -> Self {
+        Self {
+            registry: None,
+            ssl: SslAnswers::default(),
+            admin: None,
+            env: BTreeMap::new(),
+            templates: BTreeMap::new(),
+            install: true,
+            exit_when_done: false,
+        }
+    }
+}
+
+/// Where the GHCR token comes from, so the token stays out of the answers.
+/// `token_file` wins when both are given.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RegistryAnswers {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_file: Option<PathBuf>,
+    /// Name of an environment variable holding the token.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_env: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SslAnswers {
+    /// Address for the certificate and SERVER_IP (default: detected).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_ip: Option<String>,
+    /// Generate a certificate when certs/ or SERVER_IP is missing.
+    pub generate: bool,
+}
+
+impl Default for SslAnswers {
+    fn default() -> Self {
+        Self {
+            server_ip: None,
+            generate: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AdminAnswers {
+    pub username: String,
+    pub password: String,
+}
+
+impl Answers {
+    /// Read and check an answers file, filling in `${VAR}` references from
+    /// the environment; errors name the file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content =
+            fs::read_to_string(path).map_err(|e| eyre!("Cannot read {}: {e}", path.display()))?;
+        let mut answers: Self =
+            serde_yaml::from_str(&content).map_err(|e| eyre!("{}: {e}", path.display()))?;
+        answers
+            .resolve(|name| std::env::var(name).ok())
+            .and_then(|()| answers.check())
+            .map_err(|e| eyre!("{}: {e}", path.display()))?;
+        Ok(answers)
+    }
+
+    /// Answers that repeat the install under `root`: its SERVER_IP, admin
+    /// and .env values. Secrets become `${VAR}` references named after their
+    /// .env key; the GHCR token is read from GHCR_TOKEN. Template values are
+    /// not recorded, so rendered files are left out.
+    pub fn from_install(root: &Path, has_ghcr_token: bool) -> Self {
+        let env = EnvFile::load(&root.join(".env")).unwrap_or_default();
+        let get = |key: &str| env.get(key).filter(|v| !v.is_empty()).map(str::to_string);
+        let admin = get("KC_BOOTSTRAP_ADMIN_USERNAME")
+            .zip(get("KC_BOOTSTRAP_ADMIN_PASSWORD"))
+            .map(|(username, _)| AdminAnswers {
+                username,
+                password: reference("KC_BOOTSTRAP_ADMIN_PASSWORD"),
+            });
+        let values = env
+            .entries()
+            .into_iter()
+            .filter(|(key, value)| {
+                !value.is_empty()
+                    && stack::current().env_keys.contains(&key.as_str())
+                    && key != "SERVER_IP"
+                    && !key.starts_with("KC_BOOTSTRAP_ADMIN_")
+            })
+            .map(|(key, value)| {
+                let value = if is_secret_key(&key) {
+                    reference(&key)
+                } else {
+                    value
+                };
+                (key, value)
+            })
+            .collect();
+        Self {
+            registry: has_ghcr_token.then(|| RegistryAnswers {
+                token_env: Some("GHCR_TOKEN".to_string()),
+                ..RegistryAnswers::default()
+            }),
+            ssl: SslAnswers {
+                server_ip: get("SERVER_IP"),
+                generate: true,
+            },
+            admin,
+            env: values,
+            templates: BTreeMap::new(),
+            install: true,
+            exit_when_done: true,
+        }
+    }
+
+    /// Write these answers to `answers.yaml` under `root`, keeping an existing
+    /// one as `.bak`. Returns the path written.
+    pub fn export(&self, root: &Path) -> Result<PathBuf> {
+        let path = root.join(EXPORT_FILE);
+        if path.is_file() {
+            fs::copy(&path, root.join(format!("{EXPORT_FILE}.bak")))?;
+        }
+        let header = "# Answers for `nqrust-identity --answers answers.yaml`.\n\
+                      # ${VAR} values are read from the environment when the file is used.\n";
+        fs::write(&path, format!("{header}{}", serde_yaml::to_string(self)?))?;
+        Ok(path)
+    }
+
+    /// Replace `${VAR}` values with `lookup(VAR)`.
+    fn resolve(
+        &mut self,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> std::result::Result<(), String> {
+        let fill = |value: &mut String| {
+            if let Some(name) = value.strip_prefix("${").and_then(|v| v.strip_suffix('}')) {
+                *value = lookup(name).ok_or_else(|| format!("{name} is not set"))?;
+            }
+            Ok::<_, String>(())
+        };
+        for value in self.env.values_mut() {
+            fill(value)?;
+        }
+        if let Some(admin) = &mut self.admin {
+            fill(&mut admin.username)?;
+            fill(&mut admin.password)?;
+        }
+        Ok(())
+    }
+
+    fn check(&self) -> std::result::Result<(), String> {
+        if let Some(ip) = &self.ssl.server_ip {
+            ip.parse::<std::net::IpAddr>()
+                .map_err(|_| format!("ssl.server_ip: '{ip}' is not an IP address"))?;
+        }
+        let mut env = EnvFile::default();
+        for (key, value) in self.env_values() {
+            env.set(&key, &value);
+        }
+        env_file::check(&env.render(), Some(stack::current().env_keys))
+            .map_err(|e| format!("env: {e}"))
+    }
+
+    /// The GHCR token `registry` points at, if it names one.
+    pub fn registry_token(&self) -> Option<Result<String>> {
+        let registry = self.registry.as_ref()?;
+        if let Some(path) = &registry.token_file {
+            return Some(
+                fs::read_to_string(path)
+                    .map(|token| token.trim().to_string())
+                    .map_err(|e| eyre!("Cannot read {}: {e}", path.display())),
+            );
+        }
+        let name = registry.token_env.as_ref()?;
+        Some(
+            std::env::var(name)
+                .ok()
+                .filter(|token| !token.is_empty())
+                .ok_or_else(|| eyre!("{name} is not set")),
+        )
+    }
+
+    /// `env` plus the admin credentials as `KC_BOOTSTRAP_ADMIN_*`.
+    fn env_values(&self) -> BTreeMap<String, String> {
+        let mut values = self.env.clone();
+        if let Some(admin) = &self.admin {
+            values.insert(
+                "KC_BOOTSTRAP_ADMIN_USERNAME".to_string(),
+                admin.username.clone(),
+            );
+            values.insert(
+                "KC_BOOTSTRAP_ADMIN_PASSWORD".to_string(),
+                admin.password.clone(),
+            );
+        }
+        values
+    }
+
+    /// Write the .env values and render the templates under `root`, noting
+    /// each template's output in `settings`. Returns a log line per file
+    /// written.
+    pub fn apply(&self, root: &Path, settings: &mut Settings) -> Result<Vec<String>> {
+        let mut written = Vec::new();
+        let values = self.env_values();
+        if !values.is_empty() {
+            let path = root.join(".env");
+            let mut env = EnvFile::load(&path).unwrap_or_default();
+            for (key, value) in &values {
+                env.set(key, value);
+            }
+            env.save(&path)?;
+            written.push(format!("Set {} in .env", join_keys(values.keys())));
+        }
+
+        if self.templates.is_empty() {
+            return Ok(written);
+        }
+        let (available, _) = templates::load_all(root);
+        for (key, values) in &self.templates {
+            let template = available
+                .iter()
+                .find(|t| &t.key == key)
+                .ok_or_else(|| eyre!("No config template named '{key}'"))?;
+            if let Some(unknown) = values
+                .keys()
+                .find(|name| !template.fields.iter().any(|f| &f.key == *name))
+            {
+                return Err(eyre!("Template '{key}' has no field '{unknown}'"));
+            }
+            let path = template_form::write(template, values, root)?;
+            settings
+                .generated_files
+                .insert(template.output.display().to_string(), template.name.clone());
+            written.push(format!("Wrote {} from a template", path.display()));
+        }
+        Ok(written)
+    }
+}
+
+fn reference(name: &str) -> String {
+    format!("${{{name}}}")
+}
+
+fn join_keys<'a>(keys: impl Iterator<Item = &'a String>) -> String {
+    keys.map(String::as_str).collect::<Vec<_>>().join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answers_fill_env_and_templates() {
+        let answers: Answers = serde_yaml::from_str(
+            r#"
+ssl:
+  server_ip: 10.0.0.5
+admin:
+  username: ops
+  password: "s3cret pass"
+env:
+  KC_THEME_DEFAULT: corporate
+templates:
+  caddy:
+    https_port: "8443"
+exit_when_done: true
+"#,
+        )
+        .unwrap();
+        assert!(answers.check().is_ok());
+        assert!(answers.install && answers.ssl.generate);
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(".env"), "SERVER_IP=10.0.0.5\n").unwrap();
+        let mut settings = Settings::default();
+        let written = answers.apply(dir.path(), &mut settings).unwrap();
+        assert_eq!(written.len(), 2);
+        assert!(settings.generated_files.contains_key("Caddyfile"));
+        let env = EnvFile::load(&dir.path().join(".env")).unwrap();
+        assert_eq!(env.get("SERVER_IP"), Some("10.0.0.5"));
+        assert_eq!(env.get("KC_BOOTSTRAP_ADMIN_USERNAME"), Some("ops"));
+        assert_eq!(env.get("KC_BOOTSTRAP_ADMIN_PASSWORD"), Some("s3cret pass"));
+        let caddyfile = fs::read_to_string(dir.path().join("Caddyfile")).unwrap();
+        assert!(caddyfile.contains("https://{host}:8443{uri}"));
+    }
+
+    #[test]
+    fn test_export_references_secrets_and_loads_back() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join(".env"),
+            "SERVER_IP=10.0.0.5\nKC_THEME_DEFAULT=corporate\n\
+             KC_BOOTSTRAP_ADMIN_USERNAME=ops\nKC_BOOTSTRAP_ADMIN_PASSWORD=s3cret\n",
+        )
+        .unwrap();
+        let answers = Answers::from_install(dir.path(), true);
+        let path = answers.export(dir.path()).unwrap();
+        let yaml = fs::read_to_string(&path).unwrap();
+        assert!(!yaml.contains("s3cret"));
+        assert!(yaml.contains("token_env: GHCR_TOKEN"));
+
+        let mut loaded: Answers = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(loaded, answers);
+        assert_eq!(
+            loaded.clone().resolve(|_| None).unwrap_err(),
+            "KC_BOOTSTRAP_ADMIN_PASSWORD is not set"
+        );
+        loaded
+            .resolve(|name| (name == "KC_BOOTSTRAP_ADMIN_PASSWORD").then(|| "other".into()))
+            .unwrap();
+        assert!(loaded.check().is_ok());
+        assert_eq!(loaded.ssl.server_ip.as_deref(), Some("10.0.0.5"));
+        assert_eq!(loaded.admin.unwrap().password, "other");
+        assert_eq!(loaded.env["KC_THEME_DEFAULT"], "corporate");
+    }
+
+    #[test]
+    fn test_answers_refuse_unknown_keys() {
+        let unknown_env: Answers = serde_yaml::from_str("env:\n  NOT_READ: x\n").unwrap();
+        assert!(unknown_env.check().unwrap_err().contains("NOT_READ"));
+        assert!(serde_yaml::from_str::<Answers>("ssl:\n  ip: 10.0.0.5\n").is_err());
+
+        let typo: Answers =
+            serde_yaml::from_str("templates:\n  caddy:\n    http_port: '80'\n").unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            typo.apply(dir.path(), &mut Settings::default())
+                .unwrap_err()
+                .to_string(),
+            "Template 'caddy' has no field 'http_port'"
+        );
+    }
+}
@@ -0,0 +1,119 @@
+// backup.rs - pg_dump / pg_restore of the identity database
+// Both run inside the identity-db container over its local socket, so no
+// password or host-side Postgres client is needed. Archives use pg_dump's
+// custom format and are named by timestamp, which also gives their order.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+
+use crate::runner::{Cmd, CommandRunner};
+
+const DB_CONTAINER: &str = "identity-db";
+const DB_USER: &str = "identity";
+const DB_NAME: &str = "identity";
+/// Stopped while a restore replaces the schema under it.
+pub const APP_CONTAINER: &str = "identity";
+const PREFIX: &str = "identity-";
+const EXTENSION: &str = ".dump";
+
+/// Path for a new backup taken now.
+pub fn new_path(dir: &Path) -> PathBuf {
+    dir.join(format!(
+        "{PREFIX}{}{EXTENSION}",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ))
+}
+
+/// Backups in `dir`, newest first.
+pub fn list(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(PREFIX) && name.ends_with(EXTENSION))
+        })
+        .collect();
+    backups.sort();
+    backups.reverse();
+    backups
+}
+
+/// Delete all but the newest `keep` backups; returns what was removed.
+pub fn prune(dir: &Path, keep: usize) -> Vec<PathBuf> {
+    if keep == 0 {
+        return Vec::new();
+    }
+    list(dir)
+        .into_iter()
+        .skip(keep)
+        .filter(|path| fs::remove_file(path).is_ok())
+        .collect()
+}
+
+/// `pg_dump` writing to `path`; progress comes on stderr (`--verbose`).
+pub fn dump_cmd(path: &Path) -> Cmd {
+    Cmd::new("docker")
+        .args([
+            "exec",
+            DB_CONTAINER,
+            "pg_dump",
+            "--format=custom",
+            "--verbose",
+        ])
+        .args(["-U", DB_USER, "-d", DB_NAME])
+        .stdout_file(path)
+}
+
+/// `pg_restore` reading `path`, replacing the objects it contains.
+pub fn restore_cmd(path: &Path) -> Cmd {
+    Cmd::new("docker")
+        .args(["exec", "-i", DB_CONTAINER, "pg_restore", "--verbose"])
+        .args(["--clean", "--if-exists", "--no-owner"])
+        .args(["-U", DB_USER, "-d", DB_NAME])
+        .stdin_file(path)
+}
+
+/// `docker stop` / `docker start` a container; false if docker refused.
+pub async fn set_running(runner: &dyn CommandRunner, container: &str, running: bool) -> bool {
+    runner
+        .output(&Cmd::new("docker").args([if running { "start" } else { "stop" }, container]))
+        .await
+        .is_ok_and(|output| output.success)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prune_keeps_newest() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in [
+            "identity-20260101-000000.dump",
+            "identity-20260102-000000.dump",
+            "identity-20260103-000000.dump",
+            "notes.txt",
+        ] {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+        let removed = prune(dir.path(), 2);
+        assert_eq!(
+            removed,
+            vec![dir.path().join("identity-20260101-000000.dump")]
+        );
+        assert_eq!(
+            list(dir.path()),
+            vec![
+                dir.path().join("identity-20260103-000000.dump"),
+                dir.path().join("identity-20260102-000000.dump"),
+            ]
+        );
+        assert!(dir.path().join("notes.txt").exists());
+    }
+}
@@ -0,0 +1,119 @@
+// connection.rs - how to reach the installed stack, for the Success screen
+// Values come from the project's .env and secrets so the screen shows the
+// address users actually type, not localhost.
+
+use std::path::Path;
+
+use qrcode::QrCode;
+use qrcode::render::unicode::Dense1x2;
+
+use crate::env_file::EnvFile;
+use crate::external_db::ExternalDb;
+use crate::i18n::{tr, trf};
+use crate::keycloak_migrate::{TARGET_ADMIN, target_admin};
+use crate::secrets;
+use crate::settings::Settings;
+use crate::stack;
+
+#[derive(Debug, Clone)]
+pub struct ConnectionDetails {
+    /// `https://SERVER_IP:8008`, or Keycloak's own port on localhost when the
+    /// HTTPS proxy is left out for one already on the host.
+    pub url: String,
+    pub admin_console: String,
+    pub admin_user: String,
+    /// The password itself when it is the compose default, otherwise where
+    /// it is set or stored.
+    pub admin_password_hint: String,
+    /// `url` as a QR code, two modules per character row.
+    pub qr: Option<String>,
+    /// Where the data lives: the bundled identity-db or the external server.
+    pub database: String,
+}
+
+impl ConnectionDetails {
+    pub fn load(root: &Path, settings: &Settings) -> Self {
+        let env = EnvFile::load(&root.join(".env")).unwrap_or_default();
+        let host = env
+            .get("SERVER_IP")
+            .filter(|ip| !ip.is_empty())
+            .unwrap_or("localhost");
+        let url = if settings.skips_entry() {
+            format!("http://{}", stack::current().direct.1)
+        } else {
+            format!("https://{host}:8008")
+        };
+        let (admin_user, password) = target_admin(root);
+        let admin_password_hint = if secrets::read(root, secrets::ADMIN_PASSWORD).is_some() {
+            trf(
+                "connection.stored_in",
+                &[(
+                    "path",
+                    &secrets::path(root, secrets::ADMIN_PASSWORD)
+                        .display()
+                        .to_string(),
+                )],
+            )
+        } else if password == TARGET_ADMIN.1 {
+            password
+        } else {
+            tr("connection.in_env").to_string()
+        };
+        let database = match ExternalDb::from_env(&env) {
+            Some(db) => trf("connection.external_db", &[("address", &db.address())]),
+            None => tr("connection.bundled_db").to_string(),
+        };
+        Self {
+            admin_console: format!("{url}/admin/master/console/"),
+            database,
+            admin_user,
+            admin_password_hint,
+            qr: qr_code(&url),
+            url,
+        }
+    }
+}
+
+/// Render `text` as a QR code for the terminal. Colors are swapped so the
+/// code reads correctly as light-on-dark.
+fn qr_code(text: &str) -> Option<String> {
+    let code = QrCode::new(text).ok()?;
+    Some(
+        code.render::<Dense1x2>()
+            .dark_color(Dense1x2::Light)
+            .light_color(Dense1x2::Dark)
+            .quiet_zone(true)
+            .build(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_details_from_env() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(".env"), "SERVER_IP=10.0.0.5\n").unwrap();
+        let details = ConnectionDetails::load(dir.path(), &Settings::default());
+        assert_eq!(details.url, "https://10.0.0.5:8008");
+        assert_eq!(
+            details.admin_console,
+            "https://10.0.0.5:8008/admin/master/console/"
+        );
+        assert_eq!(details.admin_password_hint, "admin");
+        assert!(details.qr.is_some_and(|qr| qr.lines().count() > 10));
+
+        std::fs::write(
+            dir.path().join(".env"),
+            "KC_BOOTSTRAP_ADMIN_USERNAME=ops\nKC_BOOTSTRAP_ADMIN_PASSWORD=s3cret\n",
+        )
+        .unwrap();
+        let details = ConnectionDetails::load(dir.path(), &Settings::default());
+        assert_eq!(details.admin_user, "ops");
+        assert_eq!(
+            details.admin_password_hint,
+            "KC_BOOTSTRAP_ADMIN_PASSWORD in .env"
+        );
+    }
+}
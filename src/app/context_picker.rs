@@ -0,0 +1,91 @@
+// context_picker.rs - choosing the Docker daemon from `docker context ls`
+
+use crate::docker_context::DockerContext;
+
+#[derive(Debug, Clone, Default)]
+pub struct ContextPicker {
+    pub contexts: Vec<DockerContext>,
+    pub selected: usize,
+    /// Context picked in the installer, `None` when docker decides.
+    pub chosen: Option<String>,
+    /// Why the contexts could not be listed.
+    pub error: Option<String>,
+    /// Result of reaching the daemon after the last change: its version or
+    /// the error.
+    pub check: Option<Result<String, String>>,
+}
+
+impl ContextPicker {
+    /// Starts on the context in use.
+    pub fn new(contexts: Result<Vec<DockerContext>, String>, chosen: Option<String>) -> Self {
+        let (contexts, error) = match contexts {
+            Ok(contexts) => (contexts, None),
+            Err(e) => (Vec::new(), Some(e)),
+        };
+        let mut picker = Self {
+            contexts,
+            chosen,
+            error,
+            ..Self::default()
+        };
+        picker.selected = picker
+            .contexts
+            .iter()
+            .position(|c| picker.in_use(c))
+            .unwrap_or_default();
+        picker
+    }
+
+    pub fn select_prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.contexts.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn highlighted(&self) -> Option<&DockerContext> {
+        self.contexts.get(self.selected)
+    }
+
+    /// Whether docker and compose commands go to `context` now.
+    pub fn in_use(&self, context: &DockerContext) -> bool {
+        match &self.chosen {
+            Some(name) => *name == context.name,
+            None => context.current,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(name: &str, current: bool) -> DockerContext {
+        DockerContext {
+            name: name.to_string(),
+            description: String::new(),
+            docker_endpoint: format!("unix:///{name}.sock"),
+            current,
+            error: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_starts_on_context_in_use() {
+        let contexts = vec![context("default", true), context("rootless", false)];
+        let picker = ContextPicker::new(Ok(contexts.clone()), None);
+        assert_eq!(picker.highlighted().unwrap().name, "default");
+
+        let mut picker = ContextPicker::new(Ok(contexts), Some("rootless".into()));
+        assert_eq!(picker.selected, 1);
+        assert!(!picker.in_use(&context("default", true)));
+        picker.select_next();
+        assert_eq!(picker.selected, 1);
+
+        let failed = ContextPicker::new(Err("no docker".into()), None);
+        assert!(failed.highlighted().is_none());
+    }
+}
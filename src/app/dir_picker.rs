@@ -0,0 +1,132 @@
+// dir_picker.rs - choosing the project directory from the TUI
+// Browses the filesystem one directory at a time. Directories that already
+// hold the stack's compose file are marked, since that is usually the one
+// wanted when the current directory was the wrong guess.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::i18n::trf;
+use crate::stack;
+
+#[derive(Debug)]
+pub struct DirPicker {
+    /// Directory being listed; choosing takes this one.
+    pub dir: PathBuf,
+    /// Names of its subdirectories, hidden ones left out.
+    pub entries: Vec<String>,
+    pub selected: usize,
+    /// Why the directory could not be listed.
+    pub error: Option<String>,
+}
+
+impl DirPicker {
+    pub fn new(dir: PathBuf) -> Self {
+        let mut picker = Self {
+            dir,
+            entries: Vec::new(),
+            selected: 0,
+            error: None,
+        };
+        picker.load();
+        picker
+    }
+
+    fn load(&mut self) {
+        self.selected = 0;
+        match fs::read_dir(&self.dir) {
+            Ok(read) => {
+                self.entries = read
+                    .flatten()
+                    .filter(|entry| entry.path().is_dir())
+                    .filter_map(|entry| entry.file_name().into_string().ok())
+                    .filter(|name| !name.starts_with('.'))
+                    .collect();
+                self.entries.sort();
+                self.error = None;
+            }
+            Err(e) => {
+                self.entries.clear();
+                self.error = Some(trf(
+                    "dir_picker.cannot_list",
+                    &[
+                        ("dir", &self.dir.display().to_string()),
+                        ("error", &e.to_string()),
+                    ],
+                ));
+            }
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1).min(self.entries.len().saturating_sub(1));
+    }
+
+    /// Descend into the highlighted subdirectory.
+    pub fn open(&mut self) {
+        if let Some(name) = self.entries.get(self.selected) {
+            self.dir = self.dir.join(name);
+            self.load();
+        }
+    }
+
+    /// Go to the parent, highlighting the directory just left.
+    pub fn parent(&mut self) {
+        let Some(parent) = self.dir.parent().map(Path::to_path_buf) else {
+            return;
+        };
+        let left = self
+            .dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned());
+        self.dir = parent;
+        self.load();
+        if let Some(idx) = left.and_then(|left| self.entries.iter().position(|e| *e == left)) {
+            self.selected = idx;
+        }
+    }
+
+    /// Whether `name` (a subdirectory, or the listed directory for None) holds an install.
+    pub fn has_install(&self, name: Option<&str>) -> bool {
+        let dir = match name {
+            Some(name) => self.dir.join(name),
+            None => self.dir.clone(),
+        };
+        dir.join(stack::current().compose_file()).is_file()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_browse_and_mark_installs() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("identity")).unwrap();
+        fs::create_dir_all(root.path().join(".cache")).unwrap();
+        fs::create_dir_all(root.path().join("apps/web")).unwrap();
+        fs::write(
+            root.path()
+                .join("identity")
+                .join(stack::current().compose_file()),
+            "",
+        )
+        .unwrap();
+
+        let mut picker = DirPicker::new(root.path().to_path_buf());
+        assert_eq!(picker.entries, ["apps", "identity"]);
+        assert!(picker.has_install(Some("identity")));
+        assert!(!picker.has_install(None));
+
+        picker.open();
+        assert_eq!(picker.dir, root.path().join("apps"));
+        assert_eq!(picker.entries, ["web"]);
+        picker.parent();
+        assert_eq!(picker.entries[picker.selected], "apps");
+    }
+}
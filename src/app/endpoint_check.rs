@@ -0,0 +1,184 @@
+// endpoint_check.rs - LLM endpoints and API keys checked before saving
+// Template fields marked `probe` (LLM and embedding endpoints) or `verify`
+// (vendor API keys) are checked before the file is written, so a mistyped URL
+// or a revoked key shows up in the form instead of at the first query after a
+// restart.
+
+use std::time::{Duration, Instant};
+
+use reqwest::Client;
+
+use crate::i18n::{tr, trf};
+use crate::templates::KeyProvider;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// One check run before a template is saved.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Check {
+    /// Base URL of an OpenAI-compatible API.
+    Endpoint(String),
+    Key(KeyProvider, String),
+}
+
+impl Check {
+    /// Shown next to the result; never includes the key itself.
+    pub fn label(&self) -> String {
+        match self {
+            Check::Endpoint(url) => url.clone(),
+            Check::Key(provider, _) => {
+                trf("endpoint_check.api_key", &[("provider", provider.name())])
+            }
+        }
+    }
+
+    pub async fn run(&self, client: &Client) -> Result<String, String> {
+        match self {
+            Check::Endpoint(url) => check(client, url).await,
+            Check::Key(provider, key) => verify_key(client, *provider, key).await,
+        }
+    }
+}
+
+/// List the models at `base`/models. Ok describes what answered and how fast;
+/// Err says why the endpoint is unusable.
+pub async fn check(client: &Client, base: &str) -> Result<String, String> {
+    let url = format!("{}/models", base.trim_end_matches('/'));
+    let started = Instant::now();
+    let response = client
+        .get(&url)
+        .timeout(TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| {
+            if e.is_timeout() {
+                trf(
+                    "endpoint_check.timeout",
+                    &[("seconds", &TIMEOUT.as_secs().to_string())],
+                )
+            } else if e.is_connect() {
+                tr("endpoint_check.connect_failed").to_string()
+            } else {
+                e.to_string()
+            }
+        })?;
+    let status = response.status().as_u16();
+    let body = response.text().await.unwrap_or_default();
+    evaluate(status, &body, started.elapsed())
+}
+
+/// List models with `key`, which costs no tokens on any of the vendors.
+pub async fn verify_key(
+    client: &Client,
+    provider: KeyProvider,
+    key: &str,
+) -> Result<String, String> {
+    let request = match provider {
+        KeyProvider::Openai => client
+            .get("https://api.openai.com/v1/models")
+            .bearer_auth(key),
+        KeyProvider::Anthropic => client
+            .get("https://api.anthropic.com/v1/models")
+            .header("x-api-key", key)
+            .header("anthropic-version", "2023-06-01"),
+        KeyProvider::Gemini => client
+            .get("https://generativelanguage.googleapis.com/v1beta/models")
+            .header("x-goog-api-key", key),
+    };
+    let response = request.timeout(TIMEOUT).send().await.map_err(|e| {
+        trf(
+            "endpoint_check.unreachable",
+            &[("provider", provider.name()), ("error", &e.to_string())],
+        )
+    })?;
+    let status = response.status().as_u16();
+    let body = response.text().await.unwrap_or_default();
+    evaluate_key(status, &body)
+}
+
+fn evaluate_key(status: u16, body: &str) -> Result<String, String> {
+    // Vendors put the reason in error.message (Gemini also in error.status).
+    let reason = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|json| json.pointer("/error/message")?.as_str().map(str::to_string))
+        .unwrap_or_default();
+    match status {
+        200..=299 => Ok(tr("endpoint_check.accepted").to_string()),
+        // Gemini answers 400 API_KEY_INVALID for unknown keys.
+        400 | 401 => Err(trf(
+            "endpoint_check.rejected",
+            &[("status", &status.to_string()), ("reason", &reason)],
+        )
+        .trim()
+        .to_string()),
+        403 => Err(trf("endpoint_check.forbidden", &[("reason", &reason)])
+            .trim()
+            .to_string()),
+        429 if reason.contains("quota") => Err(trf("endpoint_check.quota", &[("reason", &reason)])),
+        429 => Ok(tr("endpoint_check.rate_limited").to_string()),
+        _ => Err(format!("HTTP {status} {reason}").trim().to_string()),
+    }
+}
+
+fn evaluate(status: u16, body: &str, elapsed: Duration) -> Result<String, String> {
+    let ms = elapsed.as_millis();
+    match status {
+        200..=299 => {
+            let models = serde_json::from_str::<serde_json::Value>(body)
+                .ok()
+                .and_then(|json| json.get("data")?.as_array().map(Vec::len))
+                .ok_or(tr("endpoint_check.not_openai"))?;
+            Ok(trf(
+                "endpoint_check.models",
+                &[("models", &models.to_string()), ("ms", &ms.to_string())],
+            ))
+        }
+        401 | 403 => Ok(trf(
+            "endpoint_check.needs_key",
+            &[("status", &status.to_string()), ("ms", &ms.to_string())],
+        )),
+        404 => Err(tr("endpoint_check.not_found").to_string()),
+        _ => Err(format!("HTTP {status}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_model_list() {
+        let elapsed = Duration::from_millis(42);
+        assert_eq!(
+            evaluate(
+                200,
+                r#"{"object":"list","data":[{"id":"llama3"},{"id":"nomic"}]}"#,
+                elapsed
+            ),
+            Ok("2 models, 42 ms".to_string())
+        );
+        assert!(evaluate(200, "<html>welcome</html>", elapsed).is_err());
+        assert!(evaluate(401, "", elapsed).unwrap().contains("API key"));
+        assert!(evaluate(404, "", elapsed).unwrap_err().contains("/v1"));
+    }
+
+    #[test]
+    fn test_evaluate_key() {
+        assert_eq!(evaluate_key(200, "{}"), Ok("accepted".to_string()));
+        assert_eq!(
+            evaluate_key(401, r#"{"error":{"message":"Incorrect API key provided"}}"#),
+            Err("rejected (HTTP 401) Incorrect API key provided".to_string())
+        );
+        let quota = r#"{"error":{"message":"You exceeded your current quota","type":"insufficient_quota"}}"#;
+        assert!(
+            evaluate_key(429, quota)
+                .unwrap_err()
+                .starts_with("out of quota")
+        );
+        assert!(evaluate_key(429, "{}").is_ok());
+        assert_eq!(
+            Check::Key(KeyProvider::Anthropic, "sk-ant-secret".to_string()).label(),
+            "Anthropic API key"
+        );
+    }
+}
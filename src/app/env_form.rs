@@ -0,0 +1,46 @@
+use std::path::{Path, PathBuf};
+
+use color_eyre::Result;
+
+use crate::app::form::{Field, Form};
+use crate::env_file::{self, EnvFile};
+
+/// Structured editor for an existing `.env`: one field per key, in file order.
+#[derive(Debug)]
+pub struct EnvForm {
+    path: PathBuf,
+    env: EnvFile,
+    /// Labelled with the key; secret keys are masked.
+    pub form: Form,
+}
+
+impl EnvForm {
+    pub fn load(path: &Path) -> Result<Self> {
+        let env = EnvFile::load(path)?;
+        let fields = env
+            .entries()
+            .into_iter()
+            .map(|(key, value)| {
+                let field = if env_file::is_secret_key(&key) {
+                    Field::masked(key.clone(), value)
+                } else {
+                    Field::new(key.clone(), value)
+                };
+                field.validator(move |value| env_file::validate(&key, value))
+            })
+            .collect();
+        Ok(Self {
+            path: path.to_path_buf(),
+            env,
+            form: Form::new(fields),
+        })
+    }
+
+    /// Write edited values back, keeping comments and key order.
+    pub fn save(&mut self) -> Result<()> {
+        for field in &self.form.fields {
+            self.env.set(&field.label, field.value());
+        }
+        self.env.save(&self.path)
+    }
+}
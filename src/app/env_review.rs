@@ -0,0 +1,74 @@
+use std::path::PathBuf;
+
+use color_eyre::Result;
+
+use crate::env_file::{EnvChange, EnvFile};
+
+/// Pending `.env` regeneration, reviewed key by key before it is written.
+#[derive(Debug)]
+pub struct EnvReview {
+    path: PathBuf,
+    proposed: EnvFile,
+    pub changes: Vec<EnvChange>,
+    /// Per change: true keeps the existing value instead of the generated one.
+    pub keep_existing: Vec<bool>,
+    pub selected: usize,
+}
+
+impl EnvReview {
+    /// None when the regeneration only adds keys, so nothing the user set
+    /// by hand can be lost and no review is needed.
+    pub fn new(path: PathBuf, current: EnvFile, proposed: EnvFile) -> Option<Self> {
+        let changes = current.diff(&proposed);
+        if !changes.iter().any(EnvChange::overwrites) {
+            return None;
+        }
+        Some(Self {
+            path,
+            proposed,
+            keep_existing: vec![false; changes.len()],
+            changes,
+            selected: 0,
+        })
+    }
+
+    pub fn toggle_selected(&mut self) {
+        if let Some(change) = self.changes.get(self.selected)
+            && change.overwrites()
+            && let Some(keep) = self.keep_existing.get_mut(self.selected)
+        {
+            *keep = !*keep;
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.changes.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// The generated file with kept values restored.
+    pub fn merged(&self) -> EnvFile {
+        let mut merged = self.proposed.clone();
+        for (change, keep) in self.changes.iter().zip(&self.keep_existing) {
+            if !keep {
+                continue;
+            }
+            match &change.old {
+                Some(old) => merged.set(&change.key, old),
+                None => {
+                    merged.remove(&change.key);
+                }
+            }
+        }
+        merged
+    }
+
+    pub fn apply(&self) -> Result<()> {
+        self.merged().save(&self.path)
+    }
+}
@@ -0,0 +1,189 @@
+// events.rs - the single source of everything the TUI reacts to
+// A reader thread turns terminal input into `AppEvent::Input` and sends a
+// `Tick` whenever `TICK` passes without one, so screens redraw (spinners,
+// streamed logs) without each handler polling the terminal itself. Background
+// tasks post their progress into the same channel. Keys are remapped per
+// keybindings.toml on the way out, so every reader sees the same keys.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use tokio::sync::mpsc;
+
+use crate::app::keymap::Keymap;
+
+/// Redraw interval when nothing else happens.
+pub const TICK: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone)]
+pub enum AppEvent {
+    /// Terminal input: keys, paste, resize.
+    Input(Event),
+    Tick,
+    /// Progress note from a background task.
+    Progress(String),
+}
+
+#[derive(Debug)]
+pub struct EventLoop {
+    tx: mpsc::UnboundedSender<AppEvent>,
+    rx: mpsc::UnboundedReceiver<AppEvent>,
+    /// Set while a tick sits unread, so steps that do not read the channel
+    /// for minutes (an install) leave one tick behind rather than thousands.
+    tick_queued: Arc<AtomicBool>,
+    reading: bool,
+    pub keymap: Keymap,
+}
+
+impl EventLoop {
+    pub fn new(keymap: Keymap) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            tx,
+            rx,
+            tick_queued: Arc::new(AtomicBool::new(false)),
+            reading: false,
+            keymap,
+        }
+    }
+
+    /// Start reading the terminal. Only the interactive TUI calls this, so
+    /// headless runs and tests never touch stdin.
+    pub fn start_input(&mut self) {
+        if self.reading {
+            return;
+        }
+        self.reading = true;
+        let tx = self.tx.clone();
+        let tick_queued = Arc::clone(&self.tick_queued);
+        thread::spawn(move || {
+            let mut last_tick = Instant::now();
+            loop {
+                let timeout = TICK.saturating_sub(last_tick.elapsed());
+                let next = match event::poll(timeout) {
+                    Ok(true) => match event::read() {
+                        Ok(event) => AppEvent::Input(event),
+                        Err(_) => break,
+                    },
+                    Ok(false) => {
+                        last_tick = Instant::now();
+                        if tick_queued.swap(true, Ordering::Relaxed) {
+                            continue;
+                        }
+                        AppEvent::Tick
+                    }
+                    Err(_) => break,
+                };
+                // The receiver is gone once the app has exited.
+                if tx.send(next).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Sender for background tasks' progress notes.
+    pub fn sender(&self) -> mpsc::UnboundedSender<AppEvent> {
+        self.tx.clone()
+    }
+
+    pub async fn next(&mut self) -> AppEvent {
+        // `self` holds a sender, so the channel never closes.
+        match self.rx.recv().await.unwrap_or(AppEvent::Tick) {
+            AppEvent::Input(event) => AppEvent::Input(self.keymap.translate(event)),
+            AppEvent::Tick => {
+                self.tick_queued.store(false, Ordering::Relaxed);
+                AppEvent::Tick
+            }
+            event => event,
+        }
+    }
+
+    /// Next key press already queued; never waits. Ticks and progress notes
+    /// queued before it are dropped, which suits the loops that call this:
+    /// they redraw on their own schedule.
+    pub fn try_key(&mut self) -> Option<KeyEvent> {
+        while let Ok(event) = self.rx.try_recv() {
+            if matches!(event, AppEvent::Tick) {
+                self.tick_queued.store(false, Ordering::Relaxed);
+            }
+            if let AppEvent::Input(event) = event
+                && let Some(key) = key_press(&self.keymap.translate(event))
+            {
+                return Some(key);
+            }
+        }
+        None
+    }
+
+    /// Esc or Ctrl+C pressed while an install step runs; never waits.
+    pub fn interrupt(&mut self) -> Option<Interrupt> {
+        while let Some(key) = self.try_key() {
+            if is_ctrl_c(&key) {
+                return Some(Interrupt::Quit);
+            }
+            if key.code == KeyCode::Esc {
+                return Some(Interrupt::Cancel);
+            }
+        }
+        None
+    }
+}
+
+/// What a key pressed during an install step asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interrupt {
+    /// Esc: cancel the step and go back to the menu.
+    Cancel,
+    /// Ctrl+C: leave the installer, which asks how first.
+    Quit,
+}
+
+/// The key of a press event; releases and repeats are ignored everywhere.
+pub fn key_press(event: &Event) -> Option<KeyEvent> {
+    match event {
+        Event::Key(key) if key.kind == KeyEventKind::Press => Some(*key),
+        _ => None,
+    }
+}
+
+pub fn is_ctrl_c(key: &KeyEvent) -> bool {
+    key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyEventState;
+
+    fn key(code: KeyCode, kind: KeyEventKind) -> AppEvent {
+        AppEvent::Input(Event::Key(KeyEvent {
+            code,
+            modifiers: KeyModifiers::NONE,
+            kind,
+            state: KeyEventState::NONE,
+        }))
+    }
+
+    #[test]
+    fn test_try_key_skips_ticks_progress_and_releases() {
+        let mut events = EventLoop::new(Keymap::default());
+        let tx = events.sender();
+        tx.send(AppEvent::Tick).unwrap();
+        tx.send(AppEvent::Progress("Downloading".to_string()))
+            .unwrap();
+        tx.send(key(KeyCode::Enter, KeyEventKind::Release)).unwrap();
+        tx.send(key(KeyCode::Esc, KeyEventKind::Press)).unwrap();
+        assert_eq!(events.try_key().map(|k| k.code), Some(KeyCode::Esc));
+        assert_eq!(events.try_key(), None);
+
+        tx.send(key(KeyCode::Char('x'), KeyEventKind::Press))
+            .unwrap();
+        tx.send(key(KeyCode::Esc, KeyEventKind::Press)).unwrap();
+        assert_eq!(events.interrupt(), Some(Interrupt::Cancel));
+        assert_eq!(events.interrupt(), None);
+    }
+}
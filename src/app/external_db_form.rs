@@ -0,0 +1,90 @@
+use crate::app::form::{Field, Form};
+use crate::external_db::{ExternalDb, SslMode};
+use crate::i18n::{tr, trf};
+
+/// PostgreSQL server to use instead of the bundled identity-db.
+#[derive(Debug)]
+pub struct ExternalDbForm {
+    /// Host, port, database, username, password, TLS mode.
+    pub form: Form,
+}
+
+impl ExternalDbForm {
+    /// Starts from the server saved in .env, if any.
+    pub fn new(current: Option<&ExternalDb>) -> Self {
+        let value = |get: fn(&ExternalDb) -> String| current.map(get).unwrap_or_default();
+        Self {
+            form: Form::new(vec![
+                Field::new(tr("external_db.host"), value(|db| db.host.clone()))
+                    .placeholder(tr("external_db.host_placeholder"))
+                    .validator(|host| match host {
+                        "localhost" | "127.0.0.1" | "::1" => {
+                            Err(tr("external_db.localhost").to_string())
+                        }
+                        _ if host.contains(char::is_whitespace) => {
+                            Err(trf("external_db.host_invalid", &[("host", host)]))
+                        }
+                        _ => Ok(()),
+                    }),
+                Field::new(
+                    tr("form.port"),
+                    current.map_or("5432".to_string(), |db| db.port.to_string()),
+                )
+                .validator(|port| {
+                    if port.parse::<u16>().is_ok_and(|p| p > 0) {
+                        Ok(())
+                    } else {
+                        Err(tr("form.port_invalid").to_string())
+                    }
+                }),
+                Field::new(tr("external_db.database"), value(|db| db.database.clone()))
+                    .placeholder(tr("external_db.database_placeholder")),
+                Field::new(tr("form.username"), value(|db| db.user.clone())),
+                Field::masked(tr("form.password"), value(|db| db.password.clone())),
+                Field::new(
+                    tr("external_db.tls"),
+                    current
+                        .map_or(SslMode::default(), |db| db.ssl_mode)
+                        .as_str(),
+                )
+                .placeholder(tr("external_db.tls_placeholder"))
+                .validator(|value| SslMode::parse(value).map(drop)),
+            ])
+            .buttons(tr("form.save"), tr("form.cancel")),
+        }
+    }
+
+    /// `None` when the host is empty: back to the bundled database.
+    pub fn to_settings(&self) -> Option<ExternalDb> {
+        let host = self.form.value(0);
+        if host.is_empty() {
+            return None;
+        }
+        Some(ExternalDb {
+            host: host.to_string(),
+            port: self.form.value(1).parse().unwrap_or(5432),
+            database: self.form.value(2).to_string(),
+            user: self.form.value(3).to_string(),
+            // Untrimmed: passwords may end in whitespace.
+            password: self.form.fields[4].input.value().to_string(),
+            ssl_mode: SslMode::parse(self.form.value(5)).unwrap_or_default(),
+        })
+    }
+
+    pub fn validate(&mut self) -> bool {
+        if !self.form.validate() {
+            return false;
+        }
+        if self.form.value(0).is_empty() {
+            return true;
+        }
+        match (2..=3).find(|&idx| self.form.value(idx).is_empty()) {
+            Some(idx) => {
+                let label = self.form.fields[idx].label.clone();
+                self.form
+                    .fail(idx, trf("external_db.required", &[("label", &label)]))
+            }
+            None => true,
+        }
+    }
+}
@@ -0,0 +1,291 @@
+// form.rs - fields, focus and keyboard handling shared by every input screen
+// A screen's form type (proxy, .env editor, template values, ...) wraps a
+// `Form` and adds what is specific to it: where values come from and what
+// saving does. Navigation, paste and editing keys live here once.
+
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+
+use crate::app::paste;
+use crate::app::registry_form::FocusState;
+use crate::app::text_input::TextInput;
+use crate::i18n::tr;
+
+type Validator = Box<dyn Fn(&str) -> Result<(), String> + Send + Sync>;
+
+pub struct Field {
+    pub label: String,
+    pub input: TextInput,
+    /// Shown while the input is empty.
+    pub placeholder: String,
+    /// What the validator said about the value, shown next to the field.
+    /// Set as the field is edited and for every field by `Form::validate`,
+    /// so an untouched field is not flagged before the first save.
+    pub error: Option<String>,
+    validator: Option<Validator>,
+}
+
+impl std::fmt::Debug for Field {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Field")
+            .field("label", &self.label)
+            .field("input", &self.input)
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Field {
+    pub fn new(label: impl Into<String>, value: impl Into<String>) -> Self {
+        Self::with_input(label, TextInput::new(value))
+    }
+
+    /// Field whose value is hidden until Ctrl+R.
+    pub fn masked(label: impl Into<String>, value: impl Into<String>) -> Self {
+        Self::with_input(label, TextInput::masked(value))
+    }
+
+    fn with_input(label: impl Into<String>, input: TextInput) -> Self {
+        Self {
+            label: label.into(),
+            input,
+            placeholder: String::new(),
+            error: None,
+            validator: None,
+        }
+    }
+
+    pub fn placeholder(mut self, text: impl Into<String>) -> Self {
+        self.placeholder = text.into();
+        self
+    }
+
+    /// Check run on the trimmed value by `Form::validate`.
+    pub fn validator(
+        mut self,
+        check: impl Fn(&str) -> Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        self.validator = Some(Box::new(check));
+        self
+    }
+
+    pub fn value(&self) -> &str {
+        self.input.value().trim()
+    }
+
+    /// Run the validator on the current value and keep its verdict in `error`.
+    pub fn check(&mut self) -> bool {
+        self.error = self
+            .validator
+            .as_ref()
+            .and_then(|check| check(self.value()).err());
+        self.error.is_none()
+    }
+}
+
+/// What a key press asks of the screen owning the form.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FormEvent {
+    /// Enter on the first button, or Ctrl+S.
+    Submit,
+    /// Enter on the second button, or Esc.
+    Cancel,
+}
+
+#[derive(Debug)]
+pub struct Form {
+    pub fields: Vec<Field>,
+    pub focus_state: FocusState,
+    pub error_message: String,
+    /// Labels of the submit and cancel buttons.
+    pub buttons: (&'static str, &'static str),
+    /// Enter on a field submits instead of moving to the next one.
+    enter_submits: bool,
+}
+
+impl Form {
+    pub fn new(fields: Vec<Field>) -> Self {
+        Self {
+            fields,
+            focus_state: FocusState::Field(0),
+            error_message: String::new(),
+            buttons: (tr("form.save"), tr("form.cancel")),
+            enter_submits: false,
+        }
+    }
+
+    pub fn buttons(mut self, submit: &'static str, cancel: &'static str) -> Self {
+        self.buttons = (submit, cancel);
+        self
+    }
+
+    /// For single-field prompts, where Enter should not need a second press.
+    pub fn enter_submits(mut self) -> Self {
+        self.enter_submits = true;
+        self
+    }
+
+    /// Trimmed value of field `idx`.
+    pub fn value(&self, idx: usize) -> &str {
+        self.fields.get(idx).map_or("", Field::value)
+    }
+
+    pub fn current_input_mut(&mut self) -> Option<&mut TextInput> {
+        match self.focus_state {
+            FocusState::Field(idx) => self.fields.get_mut(idx).map(|field| &mut field.input),
+            _ => None,
+        }
+    }
+
+    /// Cycle Field(0) → … → Field(n-1) → Save → Cancel → Field(0).
+    pub fn focus_next(&mut self) {
+        self.focus_state = match self.focus_state {
+            FocusState::Field(idx) if idx + 1 < self.fields.len() => FocusState::Field(idx + 1),
+            FocusState::Field(_) => FocusState::SaveButton,
+            FocusState::SaveButton => FocusState::CancelButton,
+            FocusState::CancelButton if self.fields.is_empty() => FocusState::SaveButton,
+            FocusState::CancelButton => FocusState::Field(0),
+        };
+    }
+
+    pub fn focus_prev(&mut self) {
+        self.focus_state = match self.focus_state {
+            FocusState::Field(0) => FocusState::CancelButton,
+            FocusState::Field(idx) => FocusState::Field(idx - 1),
+            FocusState::SaveButton if self.fields.is_empty() => FocusState::CancelButton,
+            FocusState::SaveButton => FocusState::Field(self.fields.len() - 1),
+            FocusState::CancelButton => FocusState::SaveButton,
+        };
+    }
+
+    /// Show `message` against field `idx`; returns false for use in validators.
+    pub fn fail(&mut self, idx: usize, message: impl Into<String>) -> bool {
+        self.error_message = message.into();
+        if let Some(field) = self.fields.get_mut(idx) {
+            field.error = Some(self.error_message.clone());
+        }
+        self.focus_state = FocusState::Field(idx);
+        false
+    }
+
+    /// Run every field's validator, marking each invalid field; focuses the
+    /// first one.
+    pub fn validate(&mut self) -> bool {
+        let mut first = None;
+        for (idx, field) in self.fields.iter_mut().enumerate() {
+            if !field.check() && first.is_none() {
+                first = Some((idx, field.error.clone().unwrap_or_default()));
+            }
+        }
+        match first {
+            Some((idx, message)) => self.fail(idx, message),
+            None => {
+                self.error_message.clear();
+                true
+            }
+        }
+    }
+
+    /// Re-check the focused field after an edit. The form-wide message goes
+    /// once no field is flagged any more.
+    fn check_focused(&mut self) {
+        if let FocusState::Field(idx) = self.focus_state
+            && let Some(field) = self.fields.get_mut(idx)
+        {
+            field.check();
+        }
+        if self.fields.iter().all(|field| field.error.is_none()) {
+            self.error_message.clear();
+        }
+    }
+
+    /// Apply one terminal event: Tab/↑↓ move focus, Enter advances or presses
+    /// the focused button, Ctrl+S submits, Esc cancels, and anything else
+    /// (including a paste) edits the focused field. Ctrl+C is the app's.
+    pub fn handle_event(&mut self, event: &Event) -> Option<FormEvent> {
+        if let Some(pasted) = paste::text(event) {
+            match pasted {
+                Ok(text) => {
+                    if let Some(input) = self.current_input_mut() {
+                        input.insert_str(&text);
+                        self.check_focused();
+                    }
+                }
+                Err(message) => self.error_message = message,
+            }
+            return None;
+        }
+        let Event::Key(key) = event else {
+            return None;
+        };
+        if key.kind != KeyEventKind::Press {
+            return None;
+        }
+        match key.code {
+            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Some(FormEvent::Submit);
+            }
+            KeyCode::Esc => return Some(FormEvent::Cancel),
+            KeyCode::Tab | KeyCode::Down => self.focus_next(),
+            KeyCode::BackTab | KeyCode::Up => self.focus_prev(),
+            KeyCode::Enter => match self.focus_state {
+                FocusState::Field(_) if self.enter_submits => return Some(FormEvent::Submit),
+                FocusState::Field(_) => self.focus_next(),
+                FocusState::SaveButton => return Some(FormEvent::Submit),
+                FocusState::CancelButton => return Some(FormEvent::Cancel),
+            },
+            _ => {
+                if let Some(input) = self.current_input_mut() {
+                    let before = input.value().to_string();
+                    input.handle_key(key);
+                    if input.value() != before {
+                        self.check_focused();
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyEvent;
+
+    fn press(form: &mut Form, code: KeyCode) -> Option<FormEvent> {
+        form.handle_event(&Event::Key(KeyEvent::new(code, KeyModifiers::NONE)))
+    }
+
+    #[test]
+    fn test_navigation_editing_and_validation() {
+        let mut form = Form::new(vec![
+            Field::new("Port", "80").validator(|v| {
+                v.parse::<u16>()
+                    .map(drop)
+                    .map_err(|_| "Port must be a number".to_string())
+            }),
+            Field::masked("Password", ""),
+        ]);
+        press(&mut form, KeyCode::Char('x'));
+        assert_eq!(form.value(0), "80x");
+        assert_eq!(
+            form.fields[0].error.as_deref(),
+            Some("Port must be a number")
+        );
+        assert!(form.error_message.is_empty());
+        assert!(!form.validate());
+        assert_eq!(form.error_message, "Port must be a number");
+
+        press(&mut form, KeyCode::Backspace);
+        assert_eq!(form.fields[0].error, None);
+        assert!(form.error_message.is_empty());
+        assert!(form.validate());
+        assert_eq!(press(&mut form, KeyCode::Enter), None);
+        assert_eq!(form.focus_state, FocusState::Field(1));
+        press(&mut form, KeyCode::Down);
+        assert_eq!(press(&mut form, KeyCode::Enter), Some(FormEvent::Submit));
+        press(&mut form, KeyCode::Tab);
+        assert_eq!(press(&mut form, KeyCode::Enter), Some(FormEvent::Cancel));
+        assert_eq!(press(&mut form, KeyCode::Esc), Some(FormEvent::Cancel));
+    }
+}
@@ -1,12 +0,0 @@
-// form_data.rs - stub for NQRust Identity installer
-// Not used; kept to satisfy mod declaration.
-
-#[allow(dead_code)]
-pub struct FormData {}
-
-#[allow(dead_code)]
-impl FormData {
-    pub fn new() -> Self {
-        Self {}
-    }
-}
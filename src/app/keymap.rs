@@ -0,0 +1,517 @@
+// keymap.rs - key remaps from keybindings.toml and the help overlay's contents
+// Some terminals swallow Tab/BackTab or F-keys. `keybindings.toml` in the
+// config dir (`$XDG_CONFIG_HOME/nqrust-installer`), or else in the project
+// directory, maps keys a user can press onto the ones the screens listen
+// for, and every event is translated before a screen sees it:
+//
+//   [remap]
+//   "ctrl+n" = "tab"
+//   "ctrl+p" = "backtab"
+//   "f2" = "ctrl+s"
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+use crate::app::state::AppState;
+use crate::xdg;
+
+pub const KEYBINDINGS_FILE: &str = "keybindings.toml";
+
+/// A key plus modifiers, written like `ctrl+n`, `shift+tab` or `f2`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    /// Terminals disagree on whether Shift is reported with an uppercase
+    /// letter or BackTab; drop it there so both spellings match.
+    fn normalized(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let modifiers = match code {
+            KeyCode::Char(_) | KeyCode::BackTab => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        };
+        Self { code, modifiers }
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let lower = text.trim().to_ascii_lowercase();
+        let mut parts: Vec<&str> = lower.split('+').collect();
+        let name = parts.pop().unwrap_or_default();
+        let mut modifiers = KeyModifiers::NONE;
+        for part in parts {
+            modifiers |= match part {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier '{part}' in '{text}'")),
+            };
+        }
+        let code = match name {
+            "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "space" => KeyCode::Char(' '),
+            f if f.len() > 1 && f.starts_with('f') => match f[1..].parse::<u8>() {
+                Ok(n @ 1..=12) => KeyCode::F(n),
+                _ => return Err(format!("unknown key '{text}'")),
+            },
+            c if c.chars().count() == 1 => KeyCode::Char(c.chars().next().unwrap_or(' ')),
+            _ => return Err(format!("unknown key '{text}'")),
+        };
+        Ok(Self::normalized(code, modifiers))
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "Ctrl+"),
+            (KeyModifiers::ALT, "Alt+"),
+            (KeyModifiers::SHIFT, "Shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("Space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct KeybindingsFile {
+    #[serde(default)]
+    remap: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default)]
+pub struct Keymap {
+    remap: HashMap<KeyChord, KeyChord>,
+    /// Problems found in keybindings.toml, shown in the help overlay.
+    pub warnings: Vec<String>,
+}
+
+impl Keymap {
+    pub fn parse(content: &str) -> Self {
+        let mut keymap = Self::default();
+        let file: KeybindingsFile = match toml::from_str(content) {
+            Ok(file) => file,
+            Err(e) => {
+                keymap.warnings.push(format!("{KEYBINDINGS_FILE}: {e}"));
+                return keymap;
+            }
+        };
+        for (from, to) in file.remap {
+            match (from.parse::<KeyChord>(), to.parse::<KeyChord>()) {
+                (Ok(from), Ok(to)) => {
+                    keymap.remap.insert(from, to);
+                }
+                (Err(e), _) | (_, Err(e)) => {
+                    keymap.warnings.push(format!("{KEYBINDINGS_FILE}: {e}"));
+                }
+            }
+        }
+        keymap
+    }
+
+    /// keybindings.toml from the config dir, falling back to the project's;
+    /// no remaps when neither exists.
+    pub fn load(root: &Path) -> Self {
+        Self::load_first(&[xdg::config_dir(), root.to_path_buf()])
+    }
+
+    /// The first keybindings.toml found in `dirs`.
+    fn load_first(dirs: &[PathBuf]) -> Self {
+        dirs.iter()
+            .find_map(|dir| fs::read_to_string(dir.join(KEYBINDINGS_FILE)).ok())
+            .map(|content| Self::parse(&content))
+            .unwrap_or_default()
+    }
+
+    /// `event` with a remapped key replaced by its target.
+    pub fn translate(&self, event: Event) -> Event {
+        let Event::Key(key) = event else {
+            return event;
+        };
+        match self
+            .remap
+            .get(&KeyChord::normalized(key.code, key.modifiers))
+        {
+            Some(to) => Event::Key(KeyEvent {
+                code: to.code,
+                modifiers: to.modifiers,
+                ..key
+            }),
+            None => event,
+        }
+    }
+
+    /// Remaps as "from → to" pairs, sorted for display.
+    pub fn remaps(&self) -> Vec<(String, String)> {
+        let mut pairs: Vec<(String, String)> = self
+            .remap
+            .iter()
+            .map(|(from, to)| (from.to_string(), to.to_string()))
+            .collect();
+        pairs.sort();
+        pairs
+    }
+}
+
+/// Screens where typed characters go into a text field, so `?` is text
+/// there and only F1 opens the help.
+pub fn takes_text(state: &AppState) -> bool {
+    matches!(
+        state,
+        AppState::RegistrySetup
+            | AppState::ProxySetup
+            | AppState::AdvancedOptions
+            | AppState::EnvEditor
+            | AppState::YamlEditor
+            | AppState::TemplateForm
+            | AppState::MigrationSetup
+            | AppState::SmtpSetup
+            | AppState::LdapSetup
+            | AppState::ExternalDbSetup
+            | AppState::OidcClientSetup
+            | AppState::TemplateSelection
+    )
+}
+
+const FORM_KEYS: &[(&str, &str)] = &[
+    ("Tab / ↓", "Next field or button"),
+    ("Shift+Tab / ↑", "Previous field or button"),
+    ("Enter", "Next field, or press the focused button"),
+    ("Ctrl+S", "Save"),
+    ("Esc", "Cancel"),
+    ("← → Home End", "Move the cursor"),
+    ("Ctrl+V", "Paste from the clipboard"),
+    ("Ctrl+R", "Show or hide a masked value"),
+];
+
+const OUTPUT_KEYS: &[(&str, &str)] = &[
+    ("↑ ↓ PgUp PgDn", "Scroll the output"),
+    ("r", "Run again"),
+    ("Esc / b / q", "Back to the menu"),
+];
+
+/// Title and key list of the help overlay for `state`.
+pub fn help_for(state: &AppState) -> (&'static str, &'static [(&'static str, &'static str)]) {
+    match state {
+        AppState::AirgappedSetup => (
+            "Offline image setup",
+            &[
+                ("Enter", "Retry after a failure"),
+                ("q", "Quit after a failure"),
+            ],
+        ),
+        AppState::LegacyFound => (
+            "Older deployment found",
+            &[("Enter / m", "Migrate it"), ("Esc / s", "Skip for now")],
+        ),
+        AppState::LegacyMigrating => (
+            "Legacy migration",
+            &[("↑ ↓", "Scroll the output"), ("Enter / Esc", "Continue")],
+        ),
+        AppState::SslSetup => (
+            "SSL setup",
+            &[("↑ ↓", "Choose"), ("Enter", "Confirm"), ("Esc", "Skip")],
+        ),
+        AppState::RegistrySetup => ("Registry login", FORM_KEYS),
+        AppState::ProxySetup => ("Proxy settings", FORM_KEYS),
+        AppState::AdvancedOptions => ("Advanced options", FORM_KEYS),
+        AppState::ProjectDirPicker => (
+            "Project directory",
+            &[
+                ("↑ ↓", "Choose a subdirectory"),
+                ("Enter / →", "Open it"),
+                ("Backspace / ←", "Parent directory"),
+                ("s / Space", "Use the directory shown"),
+                ("Esc", "Back without changing"),
+            ],
+        ),
+        AppState::ServiceSelection => (
+            "Services",
+            &[
+                ("↑ ↓", "Choose a service"),
+                ("Space", "Start it or leave it out (optional services only)"),
+                ("Enter", "Save and regenerate the override file"),
+                ("Esc", "Back without changing"),
+            ],
+        ),
+        AppState::ResourcePresets => (
+            "Resource presets",
+            &[
+                ("↑ ↓", "Choose a preset"),
+                ("Enter", "Write its limits to the advanced options"),
+                ("Esc", "Back"),
+            ],
+        ),
+        AppState::DockerContextPicker => (
+            "Docker daemon",
+            &[
+                ("↑ ↓", "Choose a context"),
+                ("Enter", "Send docker and compose commands to it"),
+                ("r", "Let DOCKER_HOST / docker's current context decide"),
+                ("Esc", "Back"),
+            ],
+        ),
+        AppState::EnvEditor => ("Edit .env", FORM_KEYS),
+        AppState::YamlEditor => ("Edit config.yaml", FORM_KEYS),
+        AppState::TemplateForm => ("Template values", FORM_KEYS),
+        AppState::MigrationSetup => ("Keycloak migration", FORM_KEYS),
+        AppState::SmtpSetup => ("Email (SMTP)", FORM_KEYS),
+        AppState::LdapSetup => ("User federation", FORM_KEYS),
+        AppState::ExternalDbSetup => ("External database", FORM_KEYS),
+        AppState::OidcClientSetup => ("Application client", FORM_KEYS),
+        AppState::Registries => (
+            "Registries & mirror",
+            &[
+                ("↑ ↓", "Choose a registry"),
+                ("Enter", "Log in"),
+                ("m", "Use or stop using it as the mirror"),
+                ("f", "Forget its saved credentials"),
+                ("Esc", "Back"),
+            ],
+        ),
+        AppState::TemplateSelection => (
+            "Config templates",
+            &[
+                ("← → ↑ ↓", "Choose a template; the grid pages as you move"),
+                ("Home / End", "First / last template"),
+                ("Letters", "Filter by name or description"),
+                ("Backspace", "Edit the filter"),
+                ("Enter", "Fill it in"),
+                ("Esc", "Clear the filter, then back"),
+            ],
+        ),
+        AppState::EnvReview => (
+            "Review .env changes",
+            &[
+                ("↑ ↓", "Choose a key"),
+                ("Space", "Keep the current value or take the new one"),
+                ("Enter", "Write .env"),
+                ("Esc", "Leave .env untouched"),
+            ],
+        ),
+        AppState::Confirmation => (
+            "Main menu",
+            &[
+                ("↑ ↓", "Choose"),
+                ("Enter", "Select"),
+                ("i a l s d e g f", "Quick actions shown under the menu"),
+                ("PgUp PgDn", "Scroll the files summary"),
+                ("Esc", "Quit"),
+            ],
+        ),
+        AppState::UpdateList => (
+            "Updates",
+            &[
+                ("↑ ↓", "Choose an image"),
+                ("Enter", "Pull the selected update, or the marked ones"),
+                ("Space", "Mark / unmark for a batch pull"),
+                ("a", "Mark every pending update (again: clear)"),
+                ("p", "Pull the marked images, downloading in parallel"),
+                ("t", "Pin a tag"),
+                ("u", "Roll back the last update"),
+                ("r", "Check again"),
+                ("PgUp PgDn", "Scroll release notes"),
+                ("Esc / b", "Back"),
+            ],
+        ),
+        AppState::Migrating | AppState::ActionOutput(_) | AppState::Database(_) => {
+            ("Output", OUTPUT_KEYS)
+        }
+        AppState::SmtpApplying => (
+            "Output",
+            &[
+                ("↑ ↓ PgUp PgDn", "Scroll the output"),
+                ("r", "Apply and send the test mail again"),
+                ("Esc / b / q", "Back to the menu"),
+            ],
+        ),
+        AppState::OidcClientRegistering => (
+            "Output",
+            &[
+                ("↑ ↓ PgUp PgDn", "Scroll the output"),
+                ("r", "Register another client"),
+                ("Esc / b / q", "Back to the menu"),
+            ],
+        ),
+        AppState::BundleRewrite => (
+            "Compose bundle",
+            &[
+                ("y", "Write the built-in files"),
+                ("↑ ↓ PgUp PgDn", "Scroll the diff"),
+                ("Esc / n", "Back to the menu"),
+            ],
+        ),
+        AppState::ComposeCheck => (
+            "Compose file check",
+            &[
+                ("↑ ↓ PgUp PgDn", "Scroll the report"),
+                ("r", "Check again and install"),
+                ("Esc / b / q", "Back to the menu"),
+            ],
+        ),
+        AppState::RotateSecrets => (
+            "Rotate secrets",
+            &[
+                ("y", "Rotate the signing keys"),
+                ("d", "Also rotate the database password"),
+                ("↑ ↓ PgUp PgDn", "Scroll the output"),
+                ("Esc / n", "Back to the menu"),
+            ],
+        ),
+        AppState::ExternalDbTesting => (
+            "Output",
+            &[
+                ("↑ ↓ PgUp PgDn", "Scroll the output"),
+                ("r", "Edit the settings again"),
+                ("Esc / b / q", "Back to the menu"),
+            ],
+        ),
+        AppState::LdapApplying => (
+            "Output",
+            &[
+                ("↑ ↓ PgUp PgDn", "Scroll the output"),
+                ("r", "Test and apply again"),
+                ("Esc / b / q", "Back to the menu"),
+            ],
+        ),
+        AppState::SystemdSetup => (
+            "Output",
+            &[
+                ("↑ ↓ PgUp PgDn", "Scroll the output"),
+                ("Esc / b / q", "Back to the menu"),
+            ],
+        ),
+        AppState::RestorePicker => (
+            "Restore a backup",
+            &[
+                ("↑ ↓", "Choose a backup"),
+                ("Enter", "Restore (press twice to confirm)"),
+                ("Esc / b / q", "Back"),
+            ],
+        ),
+        AppState::UpdatePulling => ("In progress", &[("Esc", "Cancel the running step")]),
+        AppState::Installing => (
+            "Installing",
+            &[
+                ("Esc", "Cancel the running step"),
+                ("Ctrl+C", "Quit: wait, stop services, or detach"),
+            ],
+        ),
+        AppState::FirewallSetup => (
+            "Firewall",
+            &[
+                ("y", "Run the commands shown"),
+                ("↑ ↓ PgUp PgDn", "Scroll the output"),
+                ("Esc / n", "Back"),
+            ],
+        ),
+        AppState::Success => (
+            "Done",
+            &[("f", "Open the ports in the host firewall"), ("q", "Quit")],
+        ),
+        AppState::Error(_) => (
+            "Error",
+            &[
+                ("r", "Retry the step that failed, when it can run again"),
+                ("m Esc", "Back to the menu"),
+                ("v", "Show the whole install log, or the recent lines again"),
+                ("↑ ↓ PgUp PgDn", "Scroll the log"),
+                ("c", "Save the error, hints and log to a file"),
+                ("q", "Quit"),
+            ],
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Event {
+        Event::Key(KeyEvent::new(code, modifiers))
+    }
+
+    #[test]
+    fn test_remaps_translate_keys_and_report_bad_entries() {
+        let keymap = Keymap::parse(
+            r#"
+[remap]
+"ctrl+n" = "tab"
+"Shift+Tab" = "up"
+"f2" = "ctrl+s"
+"hyper+x" = "tab"
+"#,
+        );
+        assert_eq!(keymap.warnings.len(), 1);
+        assert!(keymap.warnings[0].contains("hyper"));
+
+        assert_eq!(
+            keymap.translate(key(KeyCode::Char('n'), KeyModifiers::CONTROL)),
+            key(KeyCode::Tab, KeyModifiers::NONE)
+        );
+        // BackTab arrives with or without Shift depending on the terminal.
+        assert_eq!(
+            keymap.translate(key(KeyCode::BackTab, KeyModifiers::SHIFT)),
+            key(KeyCode::Up, KeyModifiers::NONE)
+        );
+        assert_eq!(
+            keymap.translate(key(KeyCode::F(2), KeyModifiers::NONE)),
+            key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        );
+        let untouched = key(KeyCode::Char('n'), KeyModifiers::NONE);
+        assert_eq!(keymap.translate(untouched.clone()), untouched);
+        assert_eq!(
+            keymap.remaps()[0],
+            ("BackTab".to_string(), "Up".to_string())
+        );
+    }
+
+    #[test]
+    fn test_config_dir_file_wins_over_project() {
+        let config = tempfile::tempdir().unwrap();
+        let project = tempfile::tempdir().unwrap();
+        fs::write(
+            project.path().join(KEYBINDINGS_FILE),
+            "[remap]\n\"f2\" = \"tab\"\n",
+        )
+        .unwrap();
+        let dirs = [config.path().to_path_buf(), project.path().to_path_buf()];
+        assert_eq!(Keymap::load_first(&dirs).remaps().len(), 1);
+
+        fs::write(config.path().join(KEYBINDINGS_FILE), "[remap]\n").unwrap();
+        assert!(Keymap::load_first(&dirs).remaps().is_empty());
+    }
+}
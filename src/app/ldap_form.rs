@@ -0,0 +1,86 @@
+use crate::app::form::{Field, Form};
+use crate::i18n::{tr, trf};
+use crate::ldap::{LdapSettings, Vendor};
+
+/// Directory to federate users from.
+#[derive(Debug)]
+pub struct LdapForm {
+    /// URL, vendor, bind DN, bind password, users DN, groups DN, realm.
+    pub form: Form,
+}
+
+impl LdapForm {
+    /// Starts from the directory saved in .env, if any.
+    pub fn new(current: Option<&LdapSettings>) -> Self {
+        let value = |get: fn(&LdapSettings) -> String| current.map(get).unwrap_or_default();
+        let dn = |value: &str| {
+            if value.contains('=') {
+                Ok(())
+            } else {
+                Err(trf("ldap.dn_invalid", &[("value", value)]))
+            }
+        };
+        Self {
+            form: Form::new(vec![
+                Field::new(tr("ldap.url"), value(|s| s.url.clone()))
+                    .placeholder(tr("ldap.url_placeholder"))
+                    .validator(|url| {
+                        if url.starts_with("ldap://") || url.starts_with("ldaps://") {
+                            Ok(())
+                        } else {
+                            Err(tr("ldap.url_invalid").to_string())
+                        }
+                    }),
+                Field::new(
+                    tr("ldap.vendor"),
+                    current.map_or(Vendor::default(), |s| s.vendor).as_str(),
+                )
+                .placeholder(tr("ldap.vendor_placeholder"))
+                .validator(|value| Vendor::parse(value).map(drop)),
+                Field::new(tr("ldap.bind_dn"), value(|s| s.bind_dn.clone()))
+                    .placeholder(tr("ldap.bind_dn_placeholder"))
+                    .validator(dn),
+                Field::masked(
+                    tr("ldap.bind_password"),
+                    value(|s| s.bind_credential.clone()),
+                )
+                .validator(|password| {
+                    if password.is_empty() {
+                        Err(tr("ldap.bind_password_required").to_string())
+                    } else {
+                        Ok(())
+                    }
+                }),
+                Field::new(tr("ldap.users_dn"), value(|s| s.users_dn.clone()))
+                    .placeholder(tr("ldap.users_dn_placeholder"))
+                    .validator(dn),
+                Field::new(tr("ldap.groups_dn"), value(|s| s.groups_dn.clone()))
+                    .placeholder(tr("ldap.groups_dn_placeholder"))
+                    .validator(move |value| if value.is_empty() { Ok(()) } else { dn(value) }),
+                Field::new(
+                    tr("ldap.realm"),
+                    current.map_or("master".to_string(), |s| s.realm.clone()),
+                ),
+            ])
+            .buttons(tr("form.save"), tr("form.cancel")),
+        }
+    }
+
+    pub fn to_settings(&self) -> LdapSettings {
+        let realm = self.form.value(6);
+        LdapSettings {
+            url: self.form.value(0).to_string(),
+            vendor: Vendor::parse(self.form.value(1)).unwrap_or_default(),
+            bind_dn: self.form.value(2).to_string(),
+            // Untrimmed: passwords may end in whitespace.
+            bind_credential: self.form.fields[3].input.value().to_string(),
+            users_dn: self.form.value(4).to_string(),
+            groups_dn: self.form.value(5).to_string(),
+            realm: if realm.is_empty() { "master" } else { realm }.to_string(),
+        }
+    }
+
+    pub fn validate(&mut self) -> bool {
+        self.form.validate()
+    }
+}
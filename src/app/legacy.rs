@@ -0,0 +1,220 @@
+// legacy.rs - finding and migrating an older Keycloak deployment
+// Hosts set up before this installer ran the same Postgres database (same
+// user and password) under compose project `keycloak`: containers `keycloak`
+// and `keycloak-db`, data in `keycloak_pgdata`, and the admin login in
+// KEYCLOAK_ADMIN / KEYCLOAK_ADMIN_PASSWORD, the names Keycloak read before
+// version 26. Migrating dumps the old database first, removes the old
+// containers, copies the data volume under the name compose now expects and
+// renames the .env keys. The old volume is left in place.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::registry;
+use crate::runner::{Cmd, CommandRunner};
+use crate::stack;
+
+const APP_CONTAINERS: &[&str] = &["keycloak"];
+const DB_CONTAINERS: &[&str] = &["keycloak-db", "keycloak-postgres"];
+const VOLUMES: &[&str] = &["keycloak_pgdata", "keycloak_postgres_data"];
+/// `identity_pgdata` of compose project `identity`.
+const DATA_VOLUME: &str = "identity_identity_pgdata";
+const DATA_VOLUME_LABELS: &[&str] = &[
+    "com.docker.compose.project=identity",
+    "com.docker.compose.volume=identity_pgdata",
+];
+/// Old .env key → the key the compose file reads now.
+pub const ENV_RENAMES: &[(&str, &str)] = &[
+    ("KEYCLOAK_ADMIN", "KC_BOOTSTRAP_ADMIN_USERNAME"),
+    ("KEYCLOAK_ADMIN_PASSWORD", "KC_BOOTSTRAP_ADMIN_PASSWORD"),
+];
+
+/// What is left of an older deployment on this host.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LegacyInstall {
+    /// Old containers, running or not.
+    pub containers: Vec<String>,
+    /// The old database container, when it is running and can be dumped.
+    pub running_db: Option<String>,
+    /// Old data volume, when the new one does not exist yet.
+    pub volume: Option<String>,
+    /// .env keys still using their old names.
+    pub env_renames: Vec<(&'static str, &'static str)>,
+}
+
+impl LegacyInstall {
+    pub fn is_empty(&self) -> bool {
+        self.containers.is_empty() && self.volume.is_none() && self.env_renames.is_empty()
+    }
+}
+
+/// Look for old containers, volumes and .env keys. Docker being unreachable
+/// counts as finding nothing.
+pub async fn detect(runner: &dyn CommandRunner, root: &Path) -> LegacyInstall {
+    let mut found = LegacyInstall::default();
+
+    let ps = Cmd::new("docker").args(["ps", "-a", "--format", "{{.Names}}\t{{.State}}"]);
+    if let Ok(output) = runner.output(&ps).await.map(|o| o.value()) {
+        for line in output.unwrap_or_default().lines() {
+            let (name, state) = line.split_once('\t').unwrap_or((line, ""));
+            if DB_CONTAINERS.contains(&name) && state == "running" {
+                found.running_db = Some(name.to_string());
+            }
+            if APP_CONTAINERS.contains(&name) || DB_CONTAINERS.contains(&name) {
+                found.containers.push(name.to_string());
+            }
+        }
+    }
+
+    let ls = Cmd::new("docker").args(["volume", "ls", "--format", "{{.Name}}"]);
+    if let Ok(Some(volumes)) = runner.output(&ls).await.map(|o| o.value()) {
+        let volumes: Vec<&str> = volumes.lines().collect();
+        if !volumes.contains(&DATA_VOLUME) {
+            found.volume = VOLUMES
+                .iter()
+                .find(|v| volumes.contains(v))
+                .map(|v| v.to_string());
+        }
+    }
+
+    if let Ok(env) = fs::read_to_string(root.join(".env")) {
+        found.env_renames = ENV_RENAMES
+            .iter()
+            .filter(|(old, _)| env_key(&env, old))
+            .copied()
+            .collect();
+    }
+    found
+}
+
+/// `pg_dump` of the old database in the format Restore database reads. The
+/// container's own POSTGRES_USER / POSTGRES_DB name the database.
+pub fn dump_cmd(container: &str, path: &Path) -> Cmd {
+    Cmd::new("docker")
+        .args(["exec", container, "sh", "-c"])
+        .arg(r#"pg_dump --format=custom -U "$POSTGRES_USER" -d "${POSTGRES_DB:-$POSTGRES_USER}""#)
+        .stdout_file(path)
+}
+
+pub fn remove_cmd(containers: &[String]) -> Cmd {
+    Cmd::new("docker").args(["rm", "-f"]).args(containers)
+}
+
+/// Postgres image of the stack, also used to read and copy the old volume.
+fn postgres_image() -> &'static str {
+    stack::current()
+        .services
+        .iter()
+        .find(|s| s.service == "postgres")
+        .map_or("postgres:16-alpine", |s| s.image)
+}
+
+/// Major version the old data directory was created by, e.g. `16`.
+pub async fn data_version(runner: &dyn CommandRunner, volume: &str) -> Option<String> {
+    let cmd = Cmd::new("docker")
+        .args(["run", "--rm", "-v", &format!("{volume}:/from:ro")])
+        .args([postgres_image(), "cat", "/from/PG_VERSION"]);
+    runner.output(&cmd).await.ok()?.value()
+}
+
+/// Major version of the stack's Postgres image tag.
+pub fn stack_version() -> &'static str {
+    let tag = postgres_image().rsplit(':').next().unwrap_or_default();
+    tag.split(['.', '-']).next().unwrap_or_default()
+}
+
+/// Create the volume compose expects, labelled as its own, and copy `from` into it.
+pub async fn copy_volume(runner: &dyn CommandRunner, from: &str) -> Result<(), String> {
+    let mut create = Cmd::new("docker").args(["volume", "create"]);
+    for label in DATA_VOLUME_LABELS {
+        create = create.args(["--label", label]);
+    }
+    let copy = Cmd::new("docker")
+        .args(["run", "--rm", "-v", &format!("{from}:/from:ro")])
+        .args(["-v", &format!("{DATA_VOLUME}:/to"), postgres_image()])
+        .args(["cp", "-a", "/from/.", "/to/"]);
+    for cmd in [create.arg(DATA_VOLUME), copy] {
+        let output = runner.output(&cmd).await.map_err(|e| e.to_string())?;
+        if !output.success {
+            return Err(format!("{} failed: {}", cmd.line(), output.stderr.trim()));
+        }
+    }
+    Ok(())
+}
+
+/// Rename old keys in `.env`, keeping the previous file as `.env.bak`.
+pub fn migrate_env(root: &Path, renames: &[(&str, &str)]) -> io::Result<PathBuf> {
+    let path = root.join(".env");
+    let content = fs::read_to_string(&path)?;
+    fs::copy(&path, path.with_extension("bak"))?;
+    registry::write_private(&path, &rename_env_keys(&content, renames))
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    Ok(path)
+}
+
+/// `content` with each old key renamed, unless the new key is already set.
+fn rename_env_keys(content: &str, renames: &[(&str, &str)]) -> String {
+    let mut out = String::new();
+    for line in content.lines() {
+        let renamed = renames.iter().find_map(|(old, new)| {
+            let value = line.trim_start().strip_prefix(old)?.strip_prefix('=')?;
+            (!env_key(content, new)).then(|| format!("{new}={value}"))
+        });
+        out.push_str(renamed.as_deref().unwrap_or(line));
+        out.push('\n');
+    }
+    out
+}
+
+fn env_key(content: &str, key: &str) -> bool {
+    content.lines().any(|line| {
+        line.trim_start()
+            .strip_prefix(key)
+            .is_some_and(|rest| rest.trim_start().starts_with('='))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::{CmdOutput, MockRunner};
+
+    #[tokio::test]
+    async fn test_detect_old_containers_volume_and_env() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join(".env"),
+            "SERVER_IP=10.0.0.5\nKEYCLOAK_ADMIN=admin\nKEYCLOAK_ADMIN_PASSWORD=s3cret\n",
+        )
+        .unwrap();
+        let runner = MockRunner::new()
+            .on(
+                "docker ps",
+                CmdOutput::ok("keycloak\texited\nkeycloak-db\trunning\nportainer\trunning\n"),
+            )
+            .on("volume ls", CmdOutput::ok("keycloak_pgdata\nother\n"));
+
+        let found = detect(&runner, root).await;
+        assert_eq!(found.containers, ["keycloak", "keycloak-db"]);
+        assert_eq!(found.running_db.as_deref(), Some("keycloak-db"));
+        assert_eq!(found.volume.as_deref(), Some("keycloak_pgdata"));
+        assert_eq!(found.env_renames, ENV_RENAMES);
+
+        migrate_env(root, &found.env_renames).unwrap();
+        let env = fs::read_to_string(root.join(".env")).unwrap();
+        assert_eq!(
+            env,
+            "SERVER_IP=10.0.0.5\nKC_BOOTSTRAP_ADMIN_USERNAME=admin\nKC_BOOTSTRAP_ADMIN_PASSWORD=s3cret\n"
+        );
+        assert!(detect(&MockRunner::new(), root).await.is_empty());
+    }
+
+    #[test]
+    fn test_rename_keeps_existing_new_key() {
+        let env = "KEYCLOAK_ADMIN=old\nKC_BOOTSTRAP_ADMIN_USERNAME=new\n";
+        assert_eq!(rename_env_keys(env, ENV_RENAMES), env);
+        assert_eq!(stack_version(), "16");
+    }
+}
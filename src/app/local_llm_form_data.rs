@@ -1,12 +0,0 @@
-// local_llm_form_data.rs - stub for NQRust Identity installer
-// Not used; kept to satisfy mod declaration.
-
-#[allow(dead_code)]
-pub struct LocalLlmFormData {}
-
-#[allow(dead_code)]
-impl LocalLlmFormData {
-    pub fn new() -> Self {
-        Self {}
-    }
-}
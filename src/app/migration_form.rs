@@ -0,0 +1,60 @@
+use crate::app::form::{Field, Form};
+use crate::i18n::tr;
+
+/// Source Keycloak to import realms and users from.
+#[derive(Debug)]
+pub struct MigrationForm {
+    /// URL, admin username, admin password.
+    pub form: Form,
+}
+
+impl Default for MigrationForm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MigrationForm {
+    pub fn new() -> Self {
+        let required = |value: &str| {
+            if value.is_empty() {
+                Err(tr("migration.admin_required").to_string())
+            } else {
+                Ok(())
+            }
+        };
+        Self {
+            form: Form::new(vec![
+                Field::new(tr("migration.url"), "")
+                    .placeholder(tr("migration.url_placeholder"))
+                    .validator(|url| {
+                        if url.starts_with("http://") || url.starts_with("https://") {
+                            Ok(())
+                        } else {
+                            Err(tr("migration.url_invalid").to_string())
+                        }
+                    }),
+                Field::new(tr("migration.admin_user"), "admin").validator(required),
+                Field::masked(tr("migration.admin_password"), "").validator(required),
+            ])
+            .buttons(tr("form.migrate"), tr("form.cancel")),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        self.form.value(0)
+    }
+
+    pub fn username(&self) -> &str {
+        self.form.value(1)
+    }
+
+    /// Untrimmed: passwords may end in whitespace.
+    pub fn password(&self) -> &str {
+        self.form.fields[2].input.value()
+    }
+
+    pub fn validate(&mut self) -> bool {
+        self.form.validate()
+    }
+}
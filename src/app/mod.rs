@@ -1,131 +1,466 @@
+use chrono::{DateTime, Utc};
 use color_eyre::{Result, eyre::eyre};
-use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
-use ratatui::{DefaultTerminal, Frame};
-use rcgen::{Certificate, CertificateParams, SanType};
-use reqwest::Client;
-use std::net::IpAddr as StdIpAddr;
-use std::process::Stdio;
+use crossterm::event::{Event, KeyCode, KeyModifiers};
+use futures::stream::{self, StreamExt};
+use ratatui::{DefaultTerminal, Frame, layout::Rect};
+use std::collections::BTreeSet;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
 use std::{env, fs};
-use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::Command;
+use unicode_segmentation::UnicodeSegmentation;
 
 use crate::app::state::SslSetupMenuSelection;
+use crate::cli::Cli;
+use crate::compose_override;
+use crate::db_tuning::{self, Host, Tuning};
+use crate::env_file::EnvFile;
+use crate::external_db::{self, ExternalDb};
+use crate::firewall::FirewallOffer;
+use crate::i18n::{tr, trf};
+use crate::install_log::{self, Level};
+use crate::keycloak_migrate::Endpoint;
+use crate::ldap::LdapSettings;
+use crate::manifest::InstallManifest;
+use crate::progress::{ComposeEvent, InstallProgress, ServiceState};
+use crate::registry::{self, Registry, RegistryKind};
+use crate::remote::{self, RemoteTarget};
+use crate::rollback::{HealthOutcome, RollbackRecord};
+use crate::runner::{Cmd, CommandRunner, Running};
+use crate::self_update::PackageFormat;
+use crate::settings::{HookEvent, LogLevel, PinnedTag, Settings};
+use crate::sizing::Preset;
+use crate::smtp::SmtpSettings;
+use crate::templates::{self, ConfigTemplate};
 use crate::ui::{
-    self, ConfirmationView, ErrorView, InstallingView, RegistrySetupView, SslSetupView,
-    SuccessView, UpdateListView,
+    self, ActionOutputView, AdvancedOptionsView, AirgappedSetupView, ConfigSelectionView,
+    ConfirmationView, ContextPickerView, DirPickerView, EnvEditorView, EnvReviewView, ErrorView,
+    ExternalDbSetupView, InstallingView, LdapSetupView, LegacyFoundView, MigrationSetupView,
+    ONBOARDING_STEPS, OidcClientSetupView, OnboardingView, PresetPickerView, ProxySetupView,
+    RegistriesView, RegistrySetupView, RestorePickerView, ServicePickerView, SmtpSetupView,
+    SslSetupView, SuccessView, TagPickerView, TemplateFormView, UpdateListView, YamlEditorView,
 };
+use crate::update_check::UpdateStatus;
+pub use crate::updates::UpdateInfo;
+use crate::updates::{collect_update_infos, fetch_latest_identity_tag};
 use crate::utils;
+use crate::xdg;
+use crate::{
+    airgapped, artifacts, build_info, bundle, compose_lint, daemon, disk, docker_context, doctor,
+    env_template, file_security, firewall, gpu, hooks, http, keycloak_migrate, ldap, platform,
+    progress, rollback, secrets, self_update, selinux, smtp, systemd, throttle, token_check,
+    updates, verify,
+};
 
-pub mod form_data;
-pub mod local_llm_form_data;
+pub mod advanced_form;
+mod airgapped_setup;
+pub mod answers;
+mod backup;
+pub mod connection;
+pub mod context_picker;
+pub mod dir_picker;
+pub mod endpoint_check;
+pub mod env_form;
+pub mod env_review;
+pub mod events;
+pub mod external_db_form;
+pub mod form;
+pub mod keymap;
+pub mod ldap_form;
+pub mod legacy;
+pub mod migration_form;
+mod oidc_client;
+pub mod oidc_client_form;
+mod paste;
+pub mod preset_picker;
+pub mod proxy_form;
+pub mod recovery;
 pub mod registry_form;
+pub mod rotation;
+pub mod service_picker;
+pub mod smtp_form;
 pub mod state;
-mod updates;
+pub mod task;
+pub mod template_form;
+pub mod text_input;
+pub mod yaml_form;
 
+use advanced_form::AdvancedForm;
+pub use airgapped_setup::AirgappedSetup;
+use answers::Answers;
+use connection::ConnectionDetails;
+use context_picker::ContextPicker;
+use dir_picker::DirPicker;
+use env_form::EnvForm;
+use env_review::EnvReview;
+use events::{AppEvent, EventLoop, Interrupt, is_ctrl_c, key_press};
+use external_db_form::ExternalDbForm;
+use form::{Form, FormEvent};
+use keymap::Keymap;
+use ldap_form::LdapForm;
+use legacy::LegacyInstall;
+use migration_form::MigrationForm;
+use oidc_client_form::OidcClientForm;
+use preset_picker::PresetPicker;
+use proxy_form::ProxyForm;
+use recovery::{Recovery, Retry};
 use registry_form::RegistryForm;
-pub use state::{AppState, MenuSelection};
-pub use updates::UpdateInfo;
-use updates::{collect_update_infos, fetch_latest_identity_tag, get_local_image_created};
+use service_picker::ServicePicker;
+use smtp_form::SmtpForm;
+pub use state::{
+    AppState, CleanupChoice, DatabaseTask, DiskChoice, MenuSelection, QuickAction, QuitChoice,
+    StallChoice, StallPrompt, StatusMessage,
+};
+use task::{Busy, Task};
+use template_form::TemplateForm;
+use yaml_form::YamlForm;
+
+/// Upper bound for post-cancel cleanup (`compose stop` / `down -v`).
+const CLEANUP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(120);
+
+enum ConfirmationAction {
+    Menu(MenuSelection),
+    Quick(QuickAction),
+}
 
 enum UpdateListAction {
     Pull,
+    /// Pull every marked row; `true` downloads them all at once first.
+    PullMarked(bool),
     Refresh,
+    Rollback,
+    PickTag,
     Back,
 }
 
+/// Tag picker popup state for the selected update-list row.
+/// Index 0 is the "follow latest release" entry that removes the pin.
+#[derive(Debug)]
+struct TagPicker {
+    tags: Vec<String>,
+    selected: usize,
+}
+
 enum RegistryAction {
     Submit,
     Skip,
 }
 
+enum RegistriesAction {
+    Login,
+    ToggleMirror,
+    Forget,
+    Back,
+}
+
+/// Save/Cancel outcome shared by the settings-style forms.
+enum FormAction {
+    Save,
+    Cancel,
+}
+
+enum MigrationAction {
+    Start,
+    Cancel,
+}
+
+enum ComposeStepOutcome {
+    Completed,
+    Cancelled,
+    Failed { daemon_lost: bool },
+}
+
 #[derive(Debug)]
 pub struct App {
     running: bool,
     pub(crate) state: AppState,
     logs: Vec<String>,
-    progress: f64,
+    /// Per-service state of the running install.
+    install_progress: InstallProgress,
+    /// Status line under the progress bar.
     current_service: String,
-    total_services: usize,
-    completed_services: usize,
     pub(crate) cert_exists: bool,
     pub(crate) env_has_ip: bool,
+    /// Permissions of .env and the registry token files, for the main screen.
+    security: file_security::Report,
+    /// Run the security check again before the next draw.
+    recheck_security: bool,
     pub(crate) menu_selection: MenuSelection,
     update_infos: Vec<UpdateInfo>,
     update_selection_index: usize,
+    /// Rows marked with Space / `a` for a batch pull.
+    update_marked: BTreeSet<usize>,
+    /// Aggregate progress of a batch pull, in percent.
+    update_progress: Option<f64>,
     update_message: Option<String>,
     registry_form: RegistryForm,
-    registry_status: Option<String>,
+    registry_status: Option<StatusMessage>,
+    /// Highlighted row of the registries list.
+    registry_selection: usize,
+    /// Whether each configured registry has a saved credential. Refreshed when
+    /// the list is shown, since keyring lookups are too slow to repeat per frame.
+    registry_saved: Vec<bool>,
+    proxy_form: ProxyForm,
+    /// Loaded when Advanced options is opened.
+    advanced_form: Option<AdvancedForm>,
+    /// Open while the project directory is being chosen.
+    dir_picker: Option<DirPicker>,
+    /// Open while the Docker daemon is being chosen.
+    context_picker: Option<ContextPicker>,
+    service_picker: Option<ServicePicker>,
+    preset_picker: Option<PresetPicker>,
+    migration_form: MigrationForm,
+    /// Open while the SMTP screen is shown.
+    smtp_form: Option<SmtpForm>,
+    /// Open while the LDAP screen is shown.
+    ldap_form: Option<LdapForm>,
+    /// Open while the external database screen is shown.
+    external_db_form: Option<ExternalDbForm>,
+    oidc_client_form: OidcClientForm,
+    /// The rotation shown on the Rotate secrets screen has run.
+    rotation_ran: bool,
+    /// Compared when the bundle rewrite screen opens; cleared once written.
+    bundle_files: Vec<bundle::BundleFile>,
+    /// Loaded when the .env editor is opened.
+    env_form: Option<EnvForm>,
+    /// Loaded when the config.yaml editor is opened.
+    yaml_form: Option<YamlForm>,
+    /// Pending .env regeneration awaiting per-key review.
+    env_review: Option<EnvReview>,
+    /// Config templates, loaded when the template grid is opened.
+    templates: Vec<ConfigTemplate>,
+    /// Template files that failed to parse.
+    template_warnings: Vec<String>,
+    /// Index into the templates matching `template_filter`.
+    template_selection: usize,
+    template_filter: String,
+    template_form: Option<TemplateForm>,
+    /// Files summary shown in place of the checklist, read when opened.
+    artifacts: Option<artifacts::Summary>,
+    artifacts_scroll: u16,
+    /// Retry, log and report state of the error screen.
+    recovery: Recovery,
     ghcr_token: Option<String>,
+    /// When the saved GHCR token expires, as GitHub reported it at login.
+    token_expires: Option<DateTime<Utc>>,
     /// True when running as nqrust-identity-airgapped (offline mode, no image pull)
     pub(crate) airgapped: bool,
     // SSL setup screen state
     pub(crate) ssl_detected_ip: String,
     pub(crate) ssl_menu_selection: SslSetupMenuSelection,
     pub(crate) ssl_status: Option<String>,
+    settings: Settings,
+    tag_picker: Option<TagPicker>,
+    notes_scroll: u16,
+    /// `--no-root`: self-update installs into ~/.local/bin without sudo.
+    no_root: bool,
+    /// How this installer was packaged; picks the self-update asset.
+    package_format: PackageFormat,
+    /// Current page of the first-run tour; None once dismissed.
+    onboarding_step: Option<usize>,
+    /// Terminal input, redraw ticks and background task progress.
+    events: EventLoop,
+    /// Whether the key help overlay is open.
+    show_help: bool,
+    /// Spinner popup of the background task being waited on.
+    busy: Option<Busy>,
+    /// Highlighted entry of the cleanup prompt shown after cancelling `up`.
+    cleanup_selection: Option<usize>,
+    /// Highlighted entry of the low-disk prompt, when it is open.
+    disk_selection: Option<usize>,
+    /// Open when a compose step went quiet or ran past its time limit.
+    stall_prompt: Option<StallPrompt>,
+    /// Highlighted entry of the prompt Ctrl+C opens during an install.
+    quit_selection: Option<usize>,
+    /// How the user asked to quit the running install.
+    quit: Option<QuitChoice>,
+    /// Printed once the terminal is restored, after quitting mid-install.
+    exit_message: Option<String>,
+    /// URLs and admin login shown on the Success screen.
+    connection: ConnectionDetails,
+    /// Raw stderr of the running compose step, for the stall prompt.
+    compose_output: Vec<String>,
+    /// Scroll position (from the bottom) of the quick-action output screen.
+    output_scroll: u16,
+    /// Log file of the current install run, shown on the Success/Error screens.
+    install_log: Option<PathBuf>,
+    /// Archives listed in the restore picker, newest first.
+    backups: Vec<PathBuf>,
+    backup_selection: usize,
+    /// Enter was pressed once in the restore picker.
+    restore_confirm: bool,
+    /// Ports the host firewall still blocks after an install.
+    firewall_offer: Option<FirewallOffer>,
+    /// Commands and results of the firewall screen.
+    firewall_output: Vec<String>,
+    /// The firewall commands were confirmed and run.
+    firewall_ran: bool,
+    /// Last result written by `--check-updates-daemon` or the update list.
+    update_status: Option<UpdateStatus>,
+    /// Offline image setup shown before the menu; `None` for online runs.
+    airgapped_setup: Option<AirgappedSetup>,
+    /// Starts docker, compose and sudo; a `MockRunner` in tests.
+    runner: Arc<dyn CommandRunner>,
+    /// `--answers`: drives the screens until they are used up.
+    answers: Option<Answers>,
+    /// What the startup check found of an older deployment; `None` until it ran.
+    legacy: Option<LegacyInstall>,
+    /// Screen to go on to from the legacy migration offer.
+    legacy_resume: AppState,
+    /// `--remote`: docker and compose run on this host instead.
+    remote: Option<RemoteTarget>,
 }
 
 impl App {
-    pub fn new() -> Self {
-        let token_from_env = env::var("GHCR_TOKEN")
-            .or_else(|_| env::var("GITHUB_TOKEN"))
-            .or_else(|_| env::var("GH_TOKEN"))
-            .ok();
-        let token_from_disk = App::load_token_from_disk();
-        let initial_token = token_from_env.clone().or(token_from_disk.clone());
+    pub fn new(cli: &Cli) -> Self {
+        Self::with_runner(cli, remote::runner(cli.remote().as_ref()))
+    }
 
-        let mut registry_form = RegistryForm::new();
-        if let Some(token) = initial_token.clone() {
-            registry_form.token = token;
-        }
+    /// `new`, with external commands going through `runner`.
+    pub fn with_runner(cli: &Cli, runner: Arc<dyn CommandRunner>) -> Self {
+        let initial_token = Registry::ghcr().credential();
+        let registry_form = RegistryForm::new();
 
-        let airgapped = crate::airgapped::is_airgapped_binary().unwrap_or(false);
+        let airgapped = cli.airgapped();
+        let no_root = cli.no_root;
 
         // Detect IP for SSL setup
-        let ssl_detected_ip = App::detect_ip();
+        let ssl_detected_ip = cli
+            .remote()
+            .as_ref()
+            .and_then(RemoteTarget::address)
+            .unwrap_or_else(utils::detect_ip);
 
         // Check file status for checklist
         let root = utils::project_root();
         let cert_exists =
             root.join("certs/server.crt").exists() && root.join("certs/server.key").exists();
-        let env_has_ip = fs::read_to_string(root.join(".env"))
-            .map(|c| c.lines().any(|l| l.starts_with("SERVER_IP=")))
-            .unwrap_or(false);
+        let env_has_ip = env_template::has_server_ip(&root);
+
+        let airgapped_setup = airgapped::Source::from_cli(cli)
+            .ok()
+            .flatten()
+            .map(|source| AirgappedSetup::new(source, cli.workdir(), runner.clone()));
 
-        // Always start at Confirmation (or RegistrySetup if no token)
-        let initial_state = if initial_token.is_some() || airgapped {
+        // Always start at Confirmation (or RegistrySetup if no token), after
+        // loading the bundled images on offline runs
+        let initial_state = if airgapped_setup.is_some() {
+            AppState::AirgappedSetup
+        } else if initial_token.is_some() || airgapped {
             AppState::Confirmation
         } else {
             AppState::RegistrySetup
         };
 
+        let settings = Settings::load();
+        // First launch: no settings file yet, or the tour was never dismissed.
+        let onboarding_step = (!settings.onboarding_seen).then_some(0);
+
         let mut app = Self {
             running: true,
             state: initial_state,
             logs: Vec::new(),
-            progress: 0.0,
+            install_progress: InstallProgress::from_stack(),
             current_service: String::new(),
-            // Identity stack: identity-db + identity + identity-caddy
-            total_services: 3,
-            completed_services: 0,
             cert_exists,
             env_has_ip,
+            security: file_security::Report::default(),
+            recheck_security: true,
             menu_selection: MenuSelection::Proceed,
             update_infos: Vec::new(),
             update_selection_index: 0,
+            update_marked: BTreeSet::new(),
+            update_progress: None,
             update_message: None,
             registry_form,
             registry_status: None,
+            registry_selection: 0,
+            registry_saved: Vec::new(),
+            proxy_form: ProxyForm::from_settings(&settings.proxy),
+            advanced_form: None,
+            dir_picker: None,
+            context_picker: None,
+            service_picker: None,
+            preset_picker: None,
+            migration_form: MigrationForm::new(),
+            smtp_form: None,
+            ldap_form: None,
+            external_db_form: None,
+            oidc_client_form: OidcClientForm::new(),
+            rotation_ran: false,
+            bundle_files: Vec::new(),
+            env_form: None,
+            yaml_form: None,
+            env_review: None,
+            templates: Vec::new(),
+            template_warnings: Vec::new(),
+            template_selection: 0,
+            template_filter: String::new(),
+            template_form: None,
+            artifacts: None,
+            artifacts_scroll: 0,
+            recovery: Recovery::default(),
+            token_expires: initial_token
+                .as_deref()
+                .and_then(|token| Registry::ghcr().expiry(token)),
             ghcr_token: initial_token,
             airgapped,
             ssl_detected_ip,
             ssl_menu_selection: SslSetupMenuSelection::Generate,
             ssl_status: None,
+            tag_picker: None,
+            notes_scroll: 0,
+            no_root,
+            // Detected when `run` starts; asking dpkg and rpm is async.
+            package_format: PackageFormat::Tarball,
+            onboarding_step,
+            events: EventLoop::new(Keymap::load(&utils::project_root())),
+            show_help: false,
+            busy: None,
+            cleanup_selection: None,
+            disk_selection: None,
+            stall_prompt: None,
+            quit_selection: None,
+            quit: None,
+            exit_message: None,
+            connection: ConnectionDetails::load(&root, &settings),
+            settings,
+            compose_output: Vec::new(),
+            output_scroll: 0,
+            install_log: install_log::path(),
+            backups: Vec::new(),
+            backup_selection: 0,
+            restore_confirm: false,
+            firewall_offer: None,
+            firewall_output: Vec::new(),
+            firewall_ran: false,
+            update_status: UpdateStatus::load(&utils::project_root()),
+            airgapped_setup,
+            runner,
+            answers: None,
+            legacy: None,
+            legacy_resume: AppState::Confirmation,
+            remote: cli.remote(),
         };
 
+        if app.state == AppState::Confirmation {
+            app.route_expired_token();
+        }
         app.ensure_menu_selection();
         app
     }
 
+    /// Walk the screens with `answers` instead of waiting for keys. The tour
+    /// is skipped.
+    pub fn use_answers(&mut self, answers: Answers) {
+        if let Some(ip) = &answers.ssl.server_ip {
+            self.ssl_detected_ip = ip.clone();
+        }
+        self.onboarding_step = None;
+        self.answers = Some(answers);
+    }
+
     /// Build the adaptive menu based on current file status.
     fn menu_options(&self) -> Vec<MenuSelection> {
         let mut options = Vec::new();
@@ -140,17 +475,76 @@ impl App {
                 options.push(MenuSelection::UpdateToken);
             }
             options.push(MenuSelection::CheckUpdates);
+            options.push(MenuSelection::ProxySettings);
+            if !self.settings.registries.is_empty() {
+                options.push(MenuSelection::Registries);
+            }
+            if self.ghcr_token.is_some() || !self.settings.registries.is_empty() {
+                options.push(MenuSelection::ForgetCredentials);
+            }
+        }
+
+        if utils::project_root().join(".env").is_file() {
+            options.push(MenuSelection::EditConfig);
+        }
+        if utils::project_root().join(yaml_form::CONFIG_YAML).is_file() {
+            options.push(MenuSelection::EditYamlConfig);
+        }
+        options.push(MenuSelection::GenerateFromTemplate);
+        options.push(MenuSelection::RewriteBundle);
+        options.push(MenuSelection::Services);
+        options.push(MenuSelection::ResourcePresets);
+        options.push(MenuSelection::AdvancedOptions);
+        options.push(MenuSelection::ExternalDatabase);
+        options.push(MenuSelection::ConfigureLdap);
+        options.push(MenuSelection::ProjectDir);
+        // With --remote the daemon is fixed by the SSH target.
+        if self.remote.is_none() {
+            options.push(MenuSelection::DockerContext);
         }
 
         // Proceed only available when cert + SERVER_IP are both ready
         if self.cert_exists && self.env_has_ip {
             options.push(MenuSelection::Proceed);
+            options.push(MenuSelection::MigrateKeycloak);
+        }
+
+        // Backups need an installed stack and the bundled database;
+        // restoring also needs a backup.
+        let root = utils::project_root();
+        if InstallManifest::path(&root).is_file() {
+            options.push(MenuSelection::RegisterClient);
+            options.push(MenuSelection::ConfigureSmtp);
+            options.push(MenuSelection::RotateSecrets);
+            if ExternalDb::load(&root).is_none() {
+                options.push(MenuSelection::BackupDatabase);
+                if !backup::list(&self.settings.backups.dir()).is_empty() {
+                    options.push(MenuSelection::RestoreDatabase);
+                }
+            }
+            // The unit is written on this machine, not the --remote host.
+            if systemd::available() && self.remote.is_none() {
+                options.push(MenuSelection::InstallSystemd);
+            }
         }
 
         options.push(MenuSelection::Cancel);
         options
     }
 
+    /// Quick actions that make sense right now; the bar hides the rest.
+    fn quick_actions(&self) -> Vec<QuickAction> {
+        QuickAction::ALL
+            .into_iter()
+            .filter(|action| match action {
+                QuickAction::Install => self.cert_exists && self.env_has_ip,
+                QuickAction::UpdateAll => !self.airgapped,
+                QuickAction::DebugLogging => self.env_has_ip,
+                _ => true,
+            })
+            .collect()
+    }
+
     /// Ensure current menu_selection is valid for current state.
     /// Prefers Proceed if available (so after cert generation the cursor lands there),
     /// otherwise falls back to the first available option.
@@ -168,128 +562,138 @@ impl App {
         }
     }
 
-    /// Detect the VM's outbound IP by opening a UDP-like socket toward 8.8.8.8.
-    /// Falls back to 127.0.0.1 if detection fails.
-    fn detect_ip() -> String {
-        use std::net::UdpSocket;
-        UdpSocket::bind("0.0.0.0:0")
-            .and_then(|s| {
-                s.connect("8.8.8.8:80")?;
-                s.local_addr()
-            })
-            .map(|addr| addr.ip().to_string())
-            .unwrap_or_else(|_| "127.0.0.1".to_string())
-    }
-
-    /// Generate a self-signed TLS cert using rcgen (no openssl required).
-    /// Writes certs/server.crt and certs/server.key, then updates SERVER_IP in .env.
-    fn generate_ssl_cert(ip: &str) -> Result<()> {
-        let root = utils::project_root();
-        let certs_dir = root.join("certs");
-        fs::create_dir_all(&certs_dir)?;
-
-        let ip_addr: StdIpAddr = ip
-            .parse()
-            .map_err(|_| eyre!("Invalid IP address: {}", ip))?;
-
-        let mut params = CertificateParams::default();
-        params.subject_alt_names = vec![SanType::IpAddress(ip_addr)];
-        // Valid for ~100 years
-        params.not_before = rcgen::date_time_ymd(2024, 1, 1);
-        params.not_after = rcgen::date_time_ymd(2124, 1, 1);
-
-        let cert = Certificate::from_params(params).map_err(|e| eyre!("rcgen cert error: {e}"))?;
-
-        let cert_pem = cert
-            .serialize_pem()
-            .map_err(|e| eyre!("cert serialize error: {e}"))?;
-        let key_pem = cert.serialize_private_key_pem();
-
-        fs::write(certs_dir.join("server.crt"), cert_pem)?;
-        fs::write(certs_dir.join("server.key"), key_pem)?;
-
-        // Write SERVER_IP to .env
-        App::write_server_ip_to_env(ip)?;
+    /// Regenerate .env for `ip` from the stack's template, with Postgres
+    /// sized to the host where .env has no settings. Writes directly when
+    /// only new keys appear; otherwise opens the review screen so changed and
+    /// removed values can be kept.
+    fn regenerate_env(&mut self, ip: &str, tuning: Option<&Tuning>) -> Result<()> {
+        let env_path = utils::project_root().join(".env");
+        let current = EnvFile::load(&env_path).unwrap_or_default();
+        let proposed =
+            env_template::generate(&current, ip, tuning, self.settings.advanced.log_level);
 
+        match EnvReview::new(env_path.clone(), current, proposed.clone()) {
+            Some(review) => {
+                self.env_review = Some(review);
+                self.state = AppState::EnvReview;
+            }
+            None => {
+                proposed.save(&env_path)?;
+                self.env_has_ip = true;
+                self.recheck_security = true;
+                self.state = AppState::Confirmation;
+                self.ensure_menu_selection();
+            }
+        }
         Ok(())
     }
 
-    /// Upsert SERVER_IP=<ip> in .env (create file if missing).
-    fn write_server_ip_to_env(ip: &str) -> Result<()> {
-        let root = utils::project_root();
+    /// Write the log level variables into an existing .env under `root`.
+    fn write_log_level(root: &Path, level: Option<LogLevel>) -> Result<()> {
         let env_path = root.join(".env");
-        let entry = format!("SERVER_IP={}", ip);
-
-        let existing = fs::read_to_string(&env_path).unwrap_or_default();
-        let has_entry = existing.lines().any(|l| l.starts_with("SERVER_IP="));
-
-        let new_content = if has_entry {
-            existing
-                .lines()
-                .map(|l| {
-                    if l.starts_with("SERVER_IP=") {
-                        entry.as_str()
-                    } else {
-                        l
-                    }
-                })
-                .collect::<Vec<_>>()
-                .join("\n")
-                + "\n"
-        } else {
-            format!("{}{}", existing, format!("{}\n", entry))
+        let Ok(mut env) = EnvFile::load(&env_path) else {
+            return Ok(());
         };
-
-        fs::write(&env_path, new_content)?;
-        Ok(())
+        LogLevel::apply(level, &mut env);
+        env.save(&env_path)
     }
 
-    fn load_token_from_disk() -> Option<String> {
-        let token_path = utils::project_root().join(".ghcr_token");
-        fs::read_to_string(&token_path)
-            .ok()
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
+    fn add_log(&mut self, message: &str) {
+        install_log::record(message);
+        self.logs.push(message.to_string());
     }
 
-    fn save_token_to_disk(token: &str) {
-        let token_path = utils::project_root().join(".ghcr_token");
-        let _ = fs::write(&token_path, token);
+    fn http_client(&self) -> reqwest::Client {
+        http::client(self.airgapped, &self.settings.proxy)
     }
 
-    fn add_log(&mut self, message: &str) {
-        self.logs.push(message.to_string());
+    /// Notify the hooks configured in settings; runs in the background.
+    fn fire_hooks(&self, event: HookEvent, detail: &str) {
+        hooks::fire(
+            self.runner.clone(),
+            &self.settings.hooks,
+            &self.http_client(),
+            event,
+            detail,
+        );
     }
 
+    /// One loop for the whole session: draw, wait for the next input or tick,
+    /// and hand input to the current screen. Ctrl+C quits from any screen;
+    /// long-running steps (install, migrations) read their own cancel keys.
     pub async fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        self.events.start_input();
+        self.package_format = PackageFormat::detect(self.runner.as_ref(), self.no_root).await;
         while self.running {
+            self.prepare_error_screen();
+            if self.recheck_security {
+                self.recheck_security = false;
+                self.security = file_security::check(
+                    self.runner.as_ref(),
+                    &utils::project_root(),
+                    &self.settings,
+                )
+                .await;
+            }
             terminal.draw(|frame| self.render(frame))?;
+            // Offline runs check once the images are loaded.
+            if self.legacy.is_none() && self.state != AppState::AirgappedSetup {
+                self.check_legacy().await;
+                continue;
+            }
+            if self.follow_answers(terminal).await? {
+                continue;
+            }
+
+            let event = match self.events.next().await {
+                AppEvent::Input(event) => event,
+                AppEvent::Tick | AppEvent::Progress(_) => {
+                    if self.state == AppState::AirgappedSetup {
+                        self.advance_airgapped_setup();
+                    }
+                    continue;
+                }
+            };
+            // The next draw lays the current screen out for the new size.
+            if let Event::Resize(..) = event {
+                continue;
+            }
+            if let Some(key) = key_press(&event) {
+                if is_ctrl_c(&key) {
+                    self.running = false;
+                    continue;
+                }
+                // Keys would act on a screen that is not shown.
+                let size = terminal.size()?;
+                if !ui::fits(Rect::new(0, 0, size.width, size.height)) {
+                    continue;
+                }
+                // Any key closes the help; `?` is text on screens with fields.
+                if self.show_help {
+                    self.show_help = false;
+                    continue;
+                }
+                if key.code == KeyCode::F(1)
+                    || (key.code == KeyCode::Char('?') && !keymap::takes_text(&self.state))
+                {
+                    self.show_help = true;
+                    continue;
+                }
+            }
+
+            // The tour waits until the offline images are loaded.
+            if self.onboarding_step.is_some() && self.state != AppState::AirgappedSetup {
+                self.handle_onboarding_events(&event)?;
+                continue;
+            }
 
             match &self.state.clone() {
+                AppState::AirgappedSetup => self.handle_airgapped_setup_events(&event)?,
+
                 AppState::SslSetup => {
-                    if let Some(action) = self.handle_ssl_setup_events()? {
+                    if let Some(action) = self.handle_ssl_setup_events(&event)? {
                         match action {
-                            SslSetupMenuSelection::Generate => {
-                                self.ssl_status = Some("⏳ Generating SSL cert...".to_string());
-                                terminal.draw(|frame| self.render(frame))?;
-                                let ip = self.ssl_detected_ip.clone();
-                                match App::generate_ssl_cert(&ip) {
-                                    Ok(()) => {
-                                        self.ssl_status = None;
-                                        // Update checklist state
-                                        self.cert_exists = true;
-                                        self.env_has_ip = true;
-                                        self.state = AppState::Confirmation;
-                                        self.ensure_menu_selection();
-                                    }
-                                    Err(e) => {
-                                        self.ssl_status = None;
-                                        self.state = AppState::Error(format!(
-                                            "SSL cert generation failed: {e}"
-                                        ));
-                                    }
-                                }
-                            }
+                            SslSetupMenuSelection::Generate => self.generate_ssl(terminal).await?,
                             SslSetupMenuSelection::Skip => {
                                 self.state = AppState::Confirmation;
                                 self.ensure_menu_selection();
@@ -302,108 +706,456 @@ impl App {
                 }
 
                 AppState::RegistrySetup => {
-                    if let Some(action) = self.handle_registry_events()? {
+                    if let Some(action) = self.handle_registry_events(&event)? {
                         match action {
                             RegistryAction::Submit => {
-                                let token = self.registry_form.token.trim().to_string();
+                                let token = self.registry_form.token().to_string();
                                 if token.is_empty() {
-                                    self.registry_form.error_message =
-                                        "Token cannot be empty. Press Esc to skip.".to_string();
+                                    self.registry_form.form.error_message =
+                                        tr("log.token_empty").to_string();
                                 } else {
-                                    // Validate token by running docker login
-                                    self.registry_status =
-                                        Some("🔐 Validating token...".to_string());
-                                    terminal.draw(|frame| self.render(frame))?;
-                                    match self.login_to_ghcr(&token).await {
-                                        Ok(()) => {
-                                            Self::save_token_to_disk(&token);
-                                            self.ghcr_token = Some(token);
-                                            self.registry_status = None;
-                                            self.registry_form.error_message.clear();
-                                            self.state = AppState::Confirmation;
-                                            self.ensure_menu_selection();
-                                        }
-                                        Err(e) => {
-                                            self.registry_form.error_message = format!(
-                                                "❌ Login failed: {}",
-                                                e.to_string()
-                                                    .lines()
-                                                    .next()
-                                                    .unwrap_or("unknown error")
-                                            );
-                                            self.registry_status = None;
-                                            // Stay on RegistrySetup
-                                        }
+                                    self.submit_registry_token(terminal, token).await?;
+                                }
+                            }
+                            RegistryAction::Skip
+                                if self.registry_form.registry.kind != RegistryKind::Ghcr =>
+                            {
+                                self.registry_status = None;
+                                self.refresh_registry_saved();
+                                self.state = AppState::Registries;
+                            }
+                            RegistryAction::Skip => self.skip_registry_setup(),
+                        }
+                    }
+                }
+
+                AppState::Registries => {
+                    if let Some(action) = self.handle_registries_events(&event)? {
+                        let registries = registry::configured(&self.settings);
+                        let selected = registries[self.registry_selection].clone();
+                        match action {
+                            RegistriesAction::Login if selected.needs_secret() => {
+                                self.registry_form = RegistryForm::for_registry(selected);
+                                self.registry_status = None;
+                                self.state = AppState::RegistrySetup;
+                            }
+                            RegistriesAction::Login => {
+                                let host = selected.host().to_string();
+                                let runner = self.runner.clone();
+                                let task = Task::spawn(
+                                    &self.events,
+                                    trf("registry.logging_in", &[("host", &host)]),
+                                    |_| async move { selected.login(runner.as_ref(), None).await },
+                                );
+                                self.registry_status =
+                                    Some(match self.wait_for(terminal, task).await? {
+                                        Some(Ok(())) => StatusMessage::Success(trf(
+                                            "registry.logged_in_to",
+                                            &[("host", &host)],
+                                        )),
+                                        Some(Err(e)) => StatusMessage::Failure(format!("❌ {e}")),
+                                        None => StatusMessage::Info(
+                                            tr("registry.login_cancelled").to_string(),
+                                        ),
+                                    });
+                            }
+                            RegistriesAction::ToggleMirror => {
+                                self.settings.mirror =
+                                    if self.settings.mirror.as_deref() == Some(&selected.name) {
+                                        None
+                                    } else {
+                                        Some(selected.name.clone())
+                                    };
+                                self.registry_status = Some(match self.settings.save() {
+                                    Ok(()) => StatusMessage::Info(match &self.settings.mirror {
+                                        Some(name) => trf("registry.mirror_on", &[("name", name)]),
+                                        None => tr("registry.mirror_off").to_string(),
+                                    }),
+                                    Err(e) => StatusMessage::Failure(trf(
+                                        "registry.settings_not_saved",
+                                        &[("error", &e.to_string())],
+                                    )),
+                                });
+                            }
+                            RegistriesAction::Forget => {
+                                let _ = selected.logout(self.runner.as_ref()).await;
+                                self.registry_status = Some(match selected.forget_credential() {
+                                    Ok(()) => StatusMessage::Success(trf(
+                                        "registry.forgot",
+                                        &[("host", selected.host())],
+                                    )),
+                                    Err(e) => StatusMessage::Failure(format!("❌ {e}")),
+                                });
+                                if selected.name == registry::GHCR {
+                                    self.ghcr_token = selected.credential();
+                                }
+                                self.refresh_registry_saved();
+                            }
+                            RegistriesAction::Back => {
+                                self.registry_status = None;
+                                self.state = AppState::Confirmation;
+                            }
+                        }
+                    }
+                }
+
+                AppState::ProxySetup => {
+                    if let Some(action) = self.handle_proxy_events(&event)? {
+                        match action {
+                            FormAction::Save => {
+                                if self.proxy_form.validate() {
+                                    self.settings.proxy = self.proxy_form.to_settings();
+                                    if let Err(e) = self.settings.save() {
+                                        self.proxy_form.form.error_message = trf(
+                                            "log.settings_not_saved",
+                                            &[("error", &e.to_string())],
+                                        );
+                                    } else {
+                                        self.state = AppState::Confirmation;
                                     }
                                 }
                             }
-                            RegistryAction::Skip => {
+                            FormAction::Cancel => self.state = AppState::Confirmation,
+                        }
+                    }
+                }
+
+                AppState::ProjectDirPicker => self.handle_dir_picker_events(&event),
+                AppState::DockerContextPicker => self.handle_context_picker_events(&event).await,
+                AppState::ServiceSelection => self.handle_service_picker_events(&event),
+                AppState::ResourcePresets => self.handle_preset_picker_events(&event),
+
+                AppState::AdvancedOptions => {
+                    if let Some(action) = self.handle_advanced_events(&event)? {
+                        match action {
+                            FormAction::Save => self.save_advanced_form(),
+                            FormAction::Cancel => {
+                                self.advanced_form = None;
                                 self.state = AppState::Confirmation;
-                                // Refresh checklist status after returning from registry
-                                let root = utils::project_root();
-                                self.cert_exists = root.join("certs/server.crt").exists()
-                                    && root.join("certs/server.key").exists();
-                                self.env_has_ip = fs::read_to_string(root.join(".env"))
-                                    .map(|c| c.lines().any(|l| l.starts_with("SERVER_IP=")))
-                                    .unwrap_or(false);
-                                self.ensure_menu_selection();
                             }
                         }
                     }
                 }
 
-                AppState::Confirmation => {
-                    if let Some(action) = self.handle_confirmation_events()? {
-                        let options = self.menu_options();
+                AppState::EnvEditor => {
+                    if let Some(action) = self.handle_env_editor_events(&event)? {
+                        match action {
+                            FormAction::Save => self.save_env_form(),
+                            FormAction::Cancel => {
+                                self.env_form = None;
+                                self.state = AppState::Confirmation;
+                            }
+                        }
+                    }
+                }
+
+                AppState::YamlEditor => {
+                    if let Some(action) = self.handle_yaml_editor_events(&event)? {
+                        match action {
+                            FormAction::Save => self.save_yaml_form(),
+                            FormAction::Cancel => {
+                                self.yaml_form = None;
+                                self.state = AppState::Confirmation;
+                            }
+                        }
+                    }
+                }
+
+                AppState::TemplateSelection => {
+                    self.handle_template_selection_events(&event).await?
+                }
+
+                AppState::TemplateForm => {
+                    if let Some(action) = self.handle_template_form_events(&event)? {
+                        match action {
+                            FormAction::Save => self.save_template_form(terminal).await?,
+                            FormAction::Cancel => {
+                                self.template_form = None;
+                                self.state = AppState::TemplateSelection;
+                            }
+                        }
+                    }
+                }
+
+                AppState::EnvReview => self.handle_env_review_events(&event)?,
+
+                AppState::MigrationSetup => {
+                    if let Some(action) = self.handle_migration_events(&event)? {
                         match action {
+                            MigrationAction::Start => {
+                                if self.migration_form.validate() {
+                                    self.run_migration(terminal).await?;
+                                }
+                            }
+                            MigrationAction::Cancel => self.state = AppState::Confirmation,
+                        }
+                    }
+                }
+
+                AppState::Migrating => {
+                    self.handle_migration_output_events(&event, terminal)
+                        .await?
+                }
+
+                AppState::SmtpSetup => {
+                    let action =
+                        self.form_event(&event, |app| app.smtp_form.as_mut().map(|f| &mut f.form))?;
+                    match action {
+                        Some(FormAction::Save)
+                            if self.smtp_form.as_mut().is_some_and(SmtpForm::validate) =>
+                        {
+                            self.run_smtp_setup(terminal).await?
+                        }
+                        Some(FormAction::Cancel) => {
+                            self.smtp_form = None;
+                            self.state = AppState::Confirmation;
+                        }
+                        _ => {}
+                    }
+                }
+
+                AppState::SmtpApplying => self.handle_smtp_output_events(&event, terminal).await?,
+
+                AppState::LdapSetup => {
+                    let action =
+                        self.form_event(&event, |app| app.ldap_form.as_mut().map(|f| &mut f.form))?;
+                    match action {
+                        Some(FormAction::Save)
+                            if self.ldap_form.as_mut().is_some_and(LdapForm::validate) =>
+                        {
+                            self.run_ldap_setup(terminal).await?
+                        }
+                        Some(FormAction::Cancel) => {
+                            self.ldap_form = None;
+                            self.state = AppState::Confirmation;
+                        }
+                        _ => {}
+                    }
+                }
+
+                AppState::LdapApplying => self.handle_ldap_output_events(&event, terminal).await?,
+
+                AppState::ExternalDbSetup => {
+                    let action = self.form_event(&event, |app| {
+                        app.external_db_form.as_mut().map(|f| &mut f.form)
+                    })?;
+                    match action {
+                        Some(FormAction::Save)
+                            if self
+                                .external_db_form
+                                .as_mut()
+                                .is_some_and(ExternalDbForm::validate) =>
+                        {
+                            self.run_external_db_setup(terminal).await?
+                        }
+                        Some(FormAction::Cancel) => {
+                            self.external_db_form = None;
+                            self.state = AppState::Confirmation;
+                        }
+                        _ => {}
+                    }
+                }
+
+                AppState::ExternalDbTesting => self.handle_external_db_output_events(&event),
+
+                AppState::OidcClientSetup => {
+                    match self.form_event(&event, |app| Some(&mut app.oidc_client_form.form))? {
+                        Some(FormAction::Save) if self.oidc_client_form.validate() => {
+                            self.run_client_registration(terminal).await?
+                        }
+                        Some(FormAction::Cancel) => self.state = AppState::Confirmation,
+                        _ => {}
+                    }
+                }
+
+                AppState::OidcClientRegistering => self.handle_client_output_events(&event)?,
+
+                AppState::ComposeCheck => {
+                    self.handle_compose_check_events(&event, terminal).await?
+                }
+
+                AppState::BundleRewrite => self.handle_bundle_events(&event)?,
+
+                AppState::RotateSecrets => self.handle_rotation_events(&event, terminal).await?,
+
+                AppState::LegacyFound => {
+                    if let Some(key) = key_press(&event) {
+                        match key.code {
+                            KeyCode::Enter | KeyCode::Char('m') => {
+                                self.migrate_legacy(terminal).await?
+                            }
+                            KeyCode::Esc | KeyCode::Char('s') => self.leave_legacy(),
+                            _ => {}
+                        }
+                    }
+                }
+
+                AppState::LegacyMigrating => {
+                    if let Some(key) = key_press(&event) {
+                        match key.code {
+                            KeyCode::Enter | KeyCode::Esc | KeyCode::Char('b') => {
+                                self.leave_legacy()
+                            }
+                            KeyCode::Up => {
+                                self.output_scroll = self.output_scroll.saturating_add(1)
+                            }
+                            KeyCode::Down => {
+                                self.output_scroll = self.output_scroll.saturating_sub(1)
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+
+                AppState::Confirmation => {
+                    match self.handle_confirmation_events(&event)? {
+                        Some(ConfirmationAction::Quick(action)) => {
+                            self.run_quick_action(action, terminal).await?;
+                        }
+                        Some(ConfirmationAction::Menu(action)) => match action {
                             MenuSelection::GenerateSsl => {
                                 self.ssl_menu_selection = SslSetupMenuSelection::Generate;
                                 self.ssl_status = None;
                                 self.state = AppState::SslSetup;
                             }
-                            MenuSelection::Proceed => {
-                                // Only reachable when cert_exists && env_has_ip
-                                let root = utils::project_root();
-                                if let Err(e) = utils::ensure_compose_bundle(&root) {
-                                    self.state = AppState::Error(format!(
-                                        "Failed to write compose file: {e}"
-                                    ));
-                                } else {
-                                    self.state = AppState::Installing;
-                                    self.logs.clear();
-                                    terminal.draw(|frame| self.render(frame))?;
-                                    if let Err(e) = self.run_docker_compose(terminal).await {
-                                        self.state =
-                                            AppState::Error(format!("Installation failed: {e}"));
+                            MenuSelection::Proceed => self.start_install(terminal).await?,
+                            MenuSelection::CheckUpdates => {
+                                self.state = AppState::UpdateList;
+                                self.refresh_update_infos(terminal).await?;
+                            }
+                            MenuSelection::ProxySettings => {
+                                self.proxy_form = ProxyForm::from_settings(&self.settings.proxy);
+                                self.state = AppState::ProxySetup;
+                            }
+                            MenuSelection::ProjectDir => {
+                                self.dir_picker = Some(DirPicker::new(utils::project_root()));
+                                self.state = AppState::ProjectDirPicker;
+                            }
+                            MenuSelection::DockerContext => {
+                                let contexts = docker_context::list(self.runner.as_ref()).await;
+                                self.context_picker =
+                                    Some(ContextPicker::new(contexts, docker_context::selected()));
+                                self.state = AppState::DockerContextPicker;
+                            }
+                            MenuSelection::Services => {
+                                self.service_picker =
+                                    Some(ServicePicker::new(&self.settings.disabled_services));
+                                self.state = AppState::ServiceSelection;
+                            }
+                            MenuSelection::ResourcePresets => {
+                                let host = db_tuning::detect(self.runner.as_ref()).await;
+                                self.preset_picker = Some(PresetPicker::new(host));
+                                self.state = AppState::ResourcePresets;
+                            }
+                            MenuSelection::AdvancedOptions => {
+                                self.advanced_form =
+                                    Some(AdvancedForm::from_settings(&self.settings.advanced));
+                                self.state = AppState::AdvancedOptions;
+                            }
+                            MenuSelection::EditConfig => {
+                                let path = utils::project_root().join(".env");
+                                match EnvForm::load(&path) {
+                                    Ok(form) => {
+                                        self.env_form = Some(form);
+                                        self.state = AppState::EnvEditor;
+                                    }
+                                    Err(e) => {
+                                        self.state = AppState::Error(trf(
+                                            "log.cannot_read_env",
+                                            &[("error", &e.to_string())],
+                                        ));
                                     }
                                 }
                             }
-                            MenuSelection::CheckUpdates => {
-                                self.state = AppState::UpdateList;
-                                self.update_message = Some("Fetching update info...".to_string());
-                                let client = Client::new();
-                                match collect_update_infos(&client, self.ghcr_token.as_deref())
-                                    .await
-                                {
-                                    Ok(infos) => {
-                                        for mut info in infos {
-                                            match get_local_image_created(
-                                                &info.image,
-                                                &info.current_tag,
-                                            )
-                                            .await
-                                            {
-                                                Ok(created) => info.apply_local_created(created),
-                                                Err(_) => info.apply_local_created(None),
-                                            }
-                                            self.update_infos.push(info);
-                                        }
-                                        self.update_message = None;
+                            MenuSelection::EditYamlConfig => {
+                                let path = utils::project_root().join(yaml_form::CONFIG_YAML);
+                                match YamlForm::load(&path) {
+                                    Ok(form) => {
+                                        self.yaml_form = Some(form);
+                                        self.state = AppState::YamlEditor;
                                     }
                                     Err(e) => {
-                                        self.update_message = Some(format!("Error: {e}"));
+                                        self.state = AppState::Error(trf(
+                                            "log.cannot_read",
+                                            &[
+                                                ("file", yaml_form::CONFIG_YAML),
+                                                ("error", &e.to_string()),
+                                            ],
+                                        ));
+                                    }
+                                }
+                            }
+                            MenuSelection::GenerateFromTemplate => {
+                                let (loaded, warnings) =
+                                    templates::load_all(&utils::project_root());
+                                self.templates = loaded;
+                                self.template_warnings = warnings;
+                                self.template_selection = 0;
+                                self.template_filter.clear();
+                                self.state = AppState::TemplateSelection;
+                            }
+                            MenuSelection::MigrateKeycloak => {
+                                self.migration_form = MigrationForm::new();
+                                self.state = AppState::MigrationSetup;
+                            }
+                            MenuSelection::RegisterClient => {
+                                self.oidc_client_form = OidcClientForm::new();
+                                self.state = AppState::OidcClientSetup;
+                            }
+                            MenuSelection::RotateSecrets => self.show_rotation_plan(),
+                            MenuSelection::RewriteBundle => self.show_bundle_diff(),
+                            MenuSelection::ExternalDatabase => {
+                                let current = ExternalDb::load(&utils::project_root());
+                                self.external_db_form = Some(ExternalDbForm::new(current.as_ref()));
+                                self.state = AppState::ExternalDbSetup;
+                            }
+                            MenuSelection::ConfigureLdap => {
+                                let env = EnvFile::load(&utils::project_root().join(".env"))
+                                    .unwrap_or_default();
+                                self.ldap_form =
+                                    Some(LdapForm::new(LdapSettings::from_env(&env).as_ref()));
+                                self.state = AppState::LdapSetup;
+                            }
+                            MenuSelection::ConfigureSmtp => {
+                                let env = EnvFile::load(&utils::project_root().join(".env"))
+                                    .unwrap_or_default();
+                                self.smtp_form =
+                                    Some(SmtpForm::new(SmtpSettings::from_env(&env).as_ref()));
+                                self.state = AppState::SmtpSetup;
+                            }
+                            MenuSelection::BackupDatabase => self.run_backup(terminal).await?,
+                            MenuSelection::InstallSystemd => {
+                                self.install_systemd_unit(terminal).await?
+                            }
+                            MenuSelection::RestoreDatabase => {
+                                self.backups = backup::list(&self.settings.backups.dir());
+                                self.backup_selection = 0;
+                                self.restore_confirm = false;
+                                self.state = AppState::RestorePicker;
+                            }
+                            MenuSelection::Registries => {
+                                self.registry_selection = 0;
+                                self.registry_status = None;
+                                self.refresh_registry_saved();
+                                self.state = AppState::Registries;
+                            }
+                            MenuSelection::ForgetCredentials => {
+                                let mut failures = Vec::new();
+                                for registry in registry::configured(&self.settings) {
+                                    if let Err(e) = registry.forget_credential() {
+                                        failures.push(e.to_string());
                                     }
+                                    // Not logged in is fine; only the saved copy matters.
+                                    let _ = registry.logout(self.runner.as_ref()).await;
                                 }
+                                self.ghcr_token = Registry::ghcr().credential();
+                                self.registry_form = RegistryForm::new();
+                                self.registry_status = Some(if failures.is_empty() {
+                                    StatusMessage::Success(tr("registry.forgot_all").to_string())
+                                } else {
+                                    StatusMessage::Failure(format!("❌ {}", failures.join("; ")))
+                                });
+                                self.state = AppState::RegistrySetup;
                             }
                             MenuSelection::UpdateToken => {
                                 self.registry_form = RegistryForm::new();
@@ -413,37 +1165,47 @@ impl App {
                             MenuSelection::Cancel => {
                                 self.running = false;
                             }
-                        }
-                        let _ = options;
+                        },
+                        None => {}
                     }
                 }
 
                 AppState::UpdateList => {
-                    if let Some(action) = self.handle_update_list_events()? {
+                    if let Some(action) = self.handle_update_list_events(&event)? {
                         match action {
                             UpdateListAction::Back => {
                                 self.state = AppState::Confirmation;
                             }
                             UpdateListAction::Refresh => {
-                                self.update_infos.clear();
-                                self.update_message = Some("Fetching update info...".to_string());
-                                let client = Client::new();
-                                match collect_update_infos(&client, self.ghcr_token.as_deref())
-                                    .await
-                                {
-                                    Ok(infos) => {
-                                        self.update_infos = infos;
-                                        self.update_message = None;
-                                    }
-                                    Err(e) => {
-                                        self.update_message = Some(format!("Error: {e}"));
-                                    }
+                                self.refresh_update_infos(terminal).await?
+                            }
+                            UpdateListAction::PickTag => self.open_tag_picker(),
+                            UpdateListAction::Rollback => {
+                                self.state = AppState::UpdatePulling;
+                                terminal.draw(|frame| self.render(frame))?;
+                                if let Err(e) = self.rollback_last_update().await {
+                                    self.add_log(&trf(
+                                        "log.rollback_failed",
+                                        &[("error", &e.to_string())],
+                                    ));
+                                }
+                                self.state = AppState::UpdateList;
+                            }
+                            UpdateListAction::PullMarked(parallel) => {
+                                if self.route_expired_token() {
+                                    return Ok(());
                                 }
+                                self.state = AppState::UpdatePulling;
+                                self.pull_marked_updates(terminal, parallel).await?;
+                                self.state = AppState::UpdateList;
                             }
                             UpdateListAction::Pull => {
+                                if self.route_expired_token() {
+                                    return Ok(());
+                                }
                                 self.state = AppState::UpdatePulling;
-                                if let Err(e) = self.pull_selected_update().await {
-                                    self.add_log(&format!("❌ Error: {e}"));
+                                if let Err(e) = self.pull_selected_update(terminal).await {
+                                    self.add_log(&trf("log.error", &[("error", &e.to_string())]));
                                     self.state = AppState::UpdateList;
                                 } else {
                                     self.state = AppState::UpdateList;
@@ -453,37 +1215,65 @@ impl App {
                     }
                 }
 
-                AppState::UpdatePulling => {
-                    // Rendering only; handled in UpdateList branch above
-                    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                AppState::ActionOutput(action) => {
+                    let action = *action;
+                    self.handle_action_output_events(&event, action, terminal)
+                        .await?;
                 }
 
-                AppState::Installing => {
-                    // Installing is driven via run_docker_compose above;
-                    // just keep rendering while we wait.
-                    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                AppState::RestorePicker => {
+                    self.handle_restore_picker_events(&event, terminal).await?
                 }
 
-                AppState::Success | AppState::Error(_) => {
-                    if event::poll(std::time::Duration::from_millis(200))? {
-                        if let Event::Key(key) = event::read()? {
-                            if key.kind == KeyEventKind::Press
-                                && (key.code == KeyCode::Char('q')
-                                    || (key.code == KeyCode::Char('c')
-                                        && key.modifiers.contains(KeyModifiers::CONTROL)))
-                            {
-                                self.running = false;
-                            }
-                        }
-                    }
+                AppState::SystemdSetup => self.handle_systemd_output_events(&event)?,
+
+                AppState::Database(task) => {
+                    let task = *task;
+                    self.handle_database_output_events(&event, task, terminal)
+                        .await?;
                 }
-            }
-        }
-        Ok(())
+
+                // Driven by the UpdateList and install steps above; the loop
+                // only keeps redrawing here.
+                AppState::UpdatePulling | AppState::Installing => {}
+
+                AppState::FirewallSetup => self.handle_firewall_events(&event, terminal).await?,
+
+                AppState::Success
+                    if self.firewall_offer.is_some()
+                        && key_press(&event).is_some_and(|key| key.code == KeyCode::Char('f')) =>
+                {
+                    self.show_firewall_offer()
+                }
+
+                AppState::Error(_) => self.handle_error_events(&event, terminal).await?,
+
+                AppState::Success => {
+                    if key_press(&event).is_some_and(|key| key.code == KeyCode::Char('q')) {
+                        self.running = false;
+                    }
+                }
+            }
+        }
+        Ok(())
     }
 
     fn render(&self, frame: &mut Frame) {
+        if !ui::fits(frame.area()) {
+            ui::render_too_small(frame);
+            return;
+        }
         match &self.state {
+            AppState::AirgappedSetup => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                if let Some(setup) = &self.airgapped_setup {
+                    let view = AirgappedSetupView {
+                        setup,
+                        logs: &self.logs,
+                    };
+                    ui::render_airgapped_setup(frame, &view);
+                }
+            }
             AppState::SslSetup => {
                 frame.render_widget(ratatui::widgets::Clear, frame.area());
                 let view = SslSetupView {
@@ -499,19 +1289,252 @@ impl App {
                 frame.render_widget(ratatui::widgets::Clear, frame.area());
                 let view = RegistrySetupView {
                     form: &self.registry_form,
-                    status: self.registry_status.as_deref(),
+                    status: self.registry_status.as_ref(),
                 };
                 ui::render_registry_setup(frame, &view);
             }
+            AppState::Registries => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                let registries = registry::configured(&self.settings);
+                let view = RegistriesView {
+                    registries: &registries,
+                    saved: &self.registry_saved,
+                    mirror: self.settings.mirror.as_deref(),
+                    selected: self.registry_selection,
+                    status: self.registry_status.as_ref().map(StatusMessage::text),
+                };
+                ui::render_registries(frame, &view);
+            }
+            AppState::ProxySetup => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                let env_hint = env_proxy_hint();
+                let view = ProxySetupView {
+                    form: &self.proxy_form,
+                    env_hint: env_hint.as_deref(),
+                };
+                ui::render_proxy_setup(frame, &view);
+            }
+            AppState::ProjectDirPicker => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                if let Some(picker) = &self.dir_picker {
+                    ui::render_dir_picker(frame, &DirPickerView { picker });
+                }
+            }
+            AppState::ServiceSelection => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                if let Some(picker) = &self.service_picker {
+                    ui::render_service_picker(frame, &ServicePickerView { picker });
+                }
+            }
+            AppState::ResourcePresets => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                if let Some(picker) = &self.preset_picker {
+                    ui::render_preset_picker(
+                        frame,
+                        &PresetPickerView {
+                            picker,
+                            advanced: &self.settings.advanced,
+                        },
+                    );
+                }
+            }
+            AppState::DockerContextPicker => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                if let Some(picker) = &self.context_picker {
+                    ui::render_context_picker(frame, &ContextPickerView { picker });
+                }
+            }
+            AppState::AdvancedOptions => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                if let Some(form) = &self.advanced_form {
+                    ui::render_advanced_options(frame, &AdvancedOptionsView { form });
+                }
+            }
+            AppState::EnvEditor => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                if let Some(form) = &self.env_form {
+                    ui::render_env_editor(frame, &EnvEditorView { form });
+                }
+            }
+            AppState::YamlEditor => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                if let Some(form) = &self.yaml_form {
+                    ui::render_yaml_editor(frame, &YamlEditorView { form });
+                }
+            }
+            AppState::TemplateSelection => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                let view = ConfigSelectionView {
+                    templates: &self.shown_templates(),
+                    total: self.templates.len(),
+                    filter: &self.template_filter,
+                    selected_index: self.template_selection,
+                    warnings: &self.template_warnings,
+                };
+                ui::render_config_selection(frame, &view);
+            }
+            AppState::TemplateForm => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                if let Some(form) = &self.template_form {
+                    ui::render_template_form(frame, &TemplateFormView { form });
+                }
+            }
+            AppState::EnvReview => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                if let Some(review) = &self.env_review {
+                    ui::render_env_review(frame, &EnvReviewView { review });
+                }
+            }
+            AppState::MigrationSetup => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                let view = MigrationSetupView {
+                    form: &self.migration_form,
+                };
+                ui::render_migration_setup(frame, &view);
+            }
+            AppState::LegacyFound => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                if let Some(found) = &self.legacy {
+                    ui::render_legacy_found(frame, &LegacyFoundView { found });
+                }
+            }
+            AppState::LegacyMigrating => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                let view = ActionOutputView {
+                    title: tr("log.title_legacy"),
+                    lines: &self.logs,
+                    scroll_back: self.output_scroll,
+                };
+                ui::render_action_output(frame, &view);
+            }
+            AppState::SmtpSetup => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                if let Some(form) = &self.smtp_form {
+                    ui::render_smtp_setup(frame, &SmtpSetupView { form });
+                }
+            }
+            AppState::OidcClientSetup => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                let view = OidcClientSetupView {
+                    form: &self.oidc_client_form,
+                };
+                ui::render_oidc_client_setup(frame, &view);
+            }
+            AppState::OidcClientRegistering => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                let view = ActionOutputView {
+                    title: tr("log.title_oidc_client"),
+                    lines: &self.logs,
+                    scroll_back: self.output_scroll,
+                };
+                ui::render_action_output(frame, &view);
+            }
+            AppState::BundleRewrite => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                let view = ActionOutputView {
+                    title: tr("log.title_bundle"),
+                    lines: &self.logs,
+                    scroll_back: self.output_scroll,
+                };
+                ui::render_action_output(frame, &view);
+            }
+            AppState::ComposeCheck => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                let view = ActionOutputView {
+                    title: tr("log.title_compose_check"),
+                    lines: &self.logs,
+                    scroll_back: self.output_scroll,
+                };
+                ui::render_action_output(frame, &view);
+            }
+            AppState::RotateSecrets => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                let view = ActionOutputView {
+                    title: tr("log.title_rotation"),
+                    lines: &self.logs,
+                    scroll_back: self.output_scroll,
+                };
+                ui::render_action_output(frame, &view);
+            }
+            AppState::ExternalDbSetup => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                if let Some(form) = &self.external_db_form {
+                    let view = ExternalDbSetupView {
+                        form,
+                        installed: InstallManifest::path(&utils::project_root()).is_file(),
+                    };
+                    ui::render_external_db_setup(frame, &view);
+                }
+            }
+            AppState::ExternalDbTesting => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                let view = ActionOutputView {
+                    title: tr("log.title_external_db"),
+                    lines: &self.logs,
+                    scroll_back: self.output_scroll,
+                };
+                ui::render_action_output(frame, &view);
+            }
+            AppState::LdapSetup => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                if let Some(form) = &self.ldap_form {
+                    let view = LdapSetupView {
+                        form,
+                        installed: InstallManifest::path(&utils::project_root()).is_file(),
+                    };
+                    ui::render_ldap_setup(frame, &view);
+                }
+            }
+            AppState::LdapApplying => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                let view = ActionOutputView {
+                    title: tr("log.title_ldap"),
+                    lines: &self.logs,
+                    scroll_back: self.output_scroll,
+                };
+                ui::render_action_output(frame, &view);
+            }
+            AppState::SmtpApplying => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                let view = ActionOutputView {
+                    title: tr("log.title_smtp"),
+                    lines: &self.logs,
+                    scroll_back: self.output_scroll,
+                };
+                ui::render_action_output(frame, &view);
+            }
+            AppState::Migrating => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                let view = ActionOutputView {
+                    title: tr("log.title_migration"),
+                    lines: &self.logs,
+                    scroll_back: self.output_scroll,
+                };
+                ui::render_action_output(frame, &view);
+            }
             AppState::Confirmation => {
                 frame.render_widget(ratatui::widgets::Clear, frame.area());
                 let options = self.menu_options();
+                let quick_actions = self.quick_actions();
+                let project_dir = utils::project_root();
+                let docker_daemon = docker_context::describe();
                 let view = ConfirmationView {
                     cert_exists: self.cert_exists,
                     env_has_ip: self.env_has_ip,
+                    security: &self.security,
                     menu_selection: &self.menu_selection,
                     menu_options: &options,
+                    quick_actions: &quick_actions,
                     airgapped: self.airgapped,
+                    update_status: self.update_status.as_ref(),
+                    token_expires: self.ghcr_token.as_ref().and(self.token_expires),
+                    project_dir: &project_dir,
+                    project_dir_explicit: utils::project_root_is_explicit(),
+                    remote: self.remote.as_ref().map(|r| r.destination.as_str()),
+                    docker_daemon: docker_daemon.as_deref(),
+                    services: &external_db::services(&project_dir, &self.settings),
+                    artifacts: self.artifacts.as_ref(),
+                    artifacts_scroll: self.artifacts_scroll,
                 };
                 ui::render_confirmation(frame, &view);
             }
@@ -522,49 +1545,170 @@ impl App {
                     selected_index: self.update_selection_index,
                     message: self.update_message.as_deref(),
                     logs: &self.logs,
+                    marked: &self.update_marked,
                     pulling: matches!(self.state, AppState::UpdatePulling),
-                    progress: None,
+                    progress: self.update_progress,
+                    release_notes: self
+                        .update_infos
+                        .get(self.update_selection_index)
+                        .and_then(|info| info.release_notes.as_deref()),
+                    notes_scroll: self.notes_scroll,
+                    tag_picker: self.tag_picker.as_ref().map(|picker| TagPickerView {
+                        tags: &picker.tags,
+                        selected: picker.selected,
+                    }),
                 };
                 ui::render_update_list(frame, &view);
             }
             AppState::Installing => {
                 frame.render_widget(ratatui::widgets::Clear, frame.area());
                 let view = InstallingView {
-                    progress: self.progress,
+                    progress: &self.install_progress,
                     current_service: &self.current_service,
-                    completed_services: self.completed_services,
-                    total_services: self.total_services,
                     logs: &self.logs,
                     airgapped: self.airgapped,
+                    cleanup_selection: self.cleanup_selection,
+                    disk_selection: self.disk_selection,
+                    stall_prompt: self.stall_prompt.as_ref(),
+                    compose_output: &self.compose_output,
+                    quit_selection: self.quit_selection,
                 };
                 ui::render_installing(frame, &view);
             }
+            AppState::SystemdSetup => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                let view = ActionOutputView {
+                    title: tr("log.title_systemd"),
+                    lines: &self.logs,
+                    scroll_back: self.output_scroll,
+                };
+                ui::render_action_output(frame, &view);
+            }
+            AppState::FirewallSetup => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                let view = ActionOutputView {
+                    title: tr("log.title_firewall"),
+                    lines: &self.firewall_output,
+                    scroll_back: self.output_scroll,
+                };
+                ui::render_action_output(frame, &view);
+            }
+            AppState::RestorePicker => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                let view = RestorePickerView {
+                    backups: &self.backups,
+                    selected: self.backup_selection,
+                    confirm: self.restore_confirm,
+                };
+                ui::render_restore_picker(frame, &view);
+            }
+            AppState::Database(task) => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                let view = ActionOutputView {
+                    title: task.label(),
+                    lines: &self.logs,
+                    scroll_back: self.output_scroll,
+                };
+                ui::render_action_output(frame, &view);
+            }
+            AppState::ActionOutput(action) => {
+                frame.render_widget(ratatui::widgets::Clear, frame.area());
+                let view = ActionOutputView {
+                    title: action.label(),
+                    lines: &self.logs,
+                    scroll_back: self.output_scroll,
+                };
+                ui::render_action_output(frame, &view);
+            }
             AppState::Success => {
                 frame.render_widget(ratatui::widgets::Clear, frame.area());
-                let view = SuccessView { logs: &self.logs };
+                let view = SuccessView {
+                    logs: &self.logs,
+                    log_path: self.install_log.as_deref(),
+                    connection: &self.connection,
+                    firewall: self.firewall_offer.as_ref(),
+                };
                 ui::render_success(frame, &view);
             }
             AppState::Error(msg) => {
                 frame.render_widget(ratatui::widgets::Clear, frame.area());
                 let view = ErrorView {
                     error: msg,
-                    logs: &self.logs,
+                    logs: self.recovery.full_log.as_deref().unwrap_or(&self.logs),
+                    full_log: self.recovery.full_log.is_some(),
+                    log_path: self.install_log.as_deref(),
+                    hints: self.recovery.hints.as_deref().unwrap_or_default(),
+                    retry: self.recovery.retry,
+                    scroll: self.recovery.scroll,
+                    status: self.recovery.status.as_ref(),
                 };
                 ui::render_error(frame, &view);
             }
         }
+
+        if let Some(step) = self.onboarding_step {
+            let root = utils::project_root();
+            let config_dir = xdg::config_dir();
+            let view = OnboardingView {
+                step,
+                project_root: &root.to_string_lossy(),
+                config_dir: &config_dir.to_string_lossy(),
+            };
+            ui::render_onboarding(frame, &view);
+        }
+        if self.show_help {
+            let (title, bindings) = keymap::help_for(&self.state);
+            let view = ui::HelpView {
+                title,
+                bindings,
+                remaps: &self.events.keymap.remaps(),
+                warnings: &self.events.keymap.warnings,
+            };
+            ui::render_help(frame, &view);
+        }
+        if let Some(busy) = &self.busy {
+            ui::render_busy(frame, &ui::BusyView { busy });
+        }
+        if let Some(at) = ui::theme::current().apply(frame.buffer_mut()) {
+            frame.set_cursor_position(at);
+        }
     }
 
-    fn handle_ssl_setup_events(&mut self) -> Result<Option<SslSetupMenuSelection>> {
-        if !event::poll(std::time::Duration::from_millis(200))? {
-            return Ok(None);
+    fn handle_onboarding_events(&mut self, event: &Event) -> Result<()> {
+        let Some(key) = key_press(event) else {
+            return Ok(());
+        };
+        let Some(step) = self.onboarding_step else {
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Enter | KeyCode::Right if step + 1 < ONBOARDING_STEPS => {
+                self.onboarding_step = Some(step + 1);
+            }
+            KeyCode::Left => self.onboarding_step = Some(step.saturating_sub(1)),
+            KeyCode::Enter | KeyCode::Esc | KeyCode::Char('q') => self.finish_onboarding(),
+            _ => {}
         }
-        let Event::Key(key) = event::read()? else {
+        Ok(())
+    }
+
+    /// Dismiss the tour and remember it, so it never shows again.
+    fn finish_onboarding(&mut self) {
+        self.onboarding_step = None;
+        self.settings.onboarding_seen = true;
+        if let Err(e) = self.settings.save() {
+            self.add_log(&trf(
+                "log.settings_not_saved_warning",
+                &[("error", &e.to_string())],
+            ));
+        }
+    }
+
+    fn handle_ssl_setup_events(&mut self, event: &Event) -> Result<Option<SslSetupMenuSelection>> {
+        let Some(key) = key_press(event) else {
             return Ok(None);
         };
-        if key.kind != KeyEventKind::Press {
-            return Ok(None);
-        }
 
         let options = [
             SslSetupMenuSelection::Generate,
@@ -577,15 +1721,11 @@ impl App {
             .unwrap_or(0);
 
         match key.code {
-            KeyCode::Up => {
-                if current_idx > 0 {
-                    self.ssl_menu_selection = options[current_idx - 1].clone();
-                }
+            KeyCode::Up if current_idx > 0 => {
+                self.ssl_menu_selection = options[current_idx - 1].clone();
             }
-            KeyCode::Down => {
-                if current_idx + 1 < options.len() {
-                    self.ssl_menu_selection = options[current_idx + 1].clone();
-                }
+            KeyCode::Down if current_idx + 1 < options.len() => {
+                self.ssl_menu_selection = options[current_idx + 1].clone();
             }
             KeyCode::Enter => {
                 return Ok(Some(self.ssl_menu_selection.clone()));
@@ -593,298 +1733,3089 @@ impl App {
             KeyCode::Esc => {
                 return Ok(Some(SslSetupMenuSelection::Skip));
             }
-            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.running = false;
-            }
             _ => {}
         }
         Ok(None)
     }
 
-    fn handle_registry_events(&mut self) -> Result<Option<RegistryAction>> {
-        if !event::poll(std::time::Duration::from_millis(200))? {
-            return Ok(None);
-        }
-        let Event::Key(key) = event::read()? else {
-            return Ok(None);
+    /// Apply `event` to the form `pick` returns. A form that is gone reads
+    /// as Cancel.
+    fn form_event(
+        &mut self,
+        event: &Event,
+        pick: impl FnOnce(&mut Self) -> Option<&mut Form>,
+    ) -> Result<Option<FormAction>> {
+        let Some(form) = pick(self) else {
+            return Ok(Some(FormAction::Cancel));
         };
-        if key.kind != KeyEventKind::Press {
-            return Ok(None);
-        }
-
-        use crate::app::registry_form::FocusState;
-
-        match key.code {
-            // Ctrl+C → exit
-            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.running = false;
-            }
-
-            // Esc → skip (always)
-            KeyCode::Esc => {
-                return Ok(Some(RegistryAction::Skip));
-            }
+        Ok(form.handle_event(event).map(|event| match event {
+            FormEvent::Submit => FormAction::Save,
+            FormEvent::Cancel => FormAction::Cancel,
+        }))
+    }
 
-            // Tab / Down → move focus forward (Field → Submit → Cancel → Field)
-            KeyCode::Tab | KeyCode::Down => {
-                self.registry_form.focus_state = match &self.registry_form.focus_state {
-                    FocusState::Field(_) => FocusState::SaveButton,
-                    FocusState::SaveButton => FocusState::CancelButton,
-                    FocusState::CancelButton => FocusState::Field(0),
-                };
+    /// Keep drawing (with a spinner popup) and reading keys until `task`
+    /// finishes. Esc aborts the task and returns None; so does Ctrl+C, which
+    /// also stops the app.
+    async fn wait_for<T: Send + 'static>(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        task: Task<T>,
+    ) -> Result<Option<T>> {
+        self.busy = Some(Busy::new(task.label.clone()));
+        let outcome = loop {
+            if task.is_finished() {
+                break Some(task.join().await);
             }
-
-            // Shift+Tab / Up → move focus backward
-            KeyCode::BackTab | KeyCode::Up => {
-                self.registry_form.focus_state = match &self.registry_form.focus_state {
-                    FocusState::Field(_) => FocusState::CancelButton,
-                    FocusState::SaveButton => FocusState::Field(0),
-                    FocusState::CancelButton => FocusState::SaveButton,
-                };
+            terminal.draw(|frame| self.render(frame))?;
+            match self.events.next().await {
+                AppEvent::Progress(note) => {
+                    if let Some(busy) = self.busy.as_mut() {
+                        busy.label = note;
+                    }
+                }
+                AppEvent::Input(event) => match key_press(&event) {
+                    Some(key) if is_ctrl_c(&key) => {
+                        self.running = false;
+                        break None;
+                    }
+                    Some(key) if key.code == KeyCode::Esc => break None,
+                    _ => {}
+                },
+                AppEvent::Tick => {}
             }
+        };
+        self.busy = None;
+        outcome.transpose()
+    }
 
-            // Enter → action depends on current focus
-            KeyCode::Enter => {
-                match &self.registry_form.focus_state {
-                    FocusState::Field(_) | FocusState::SaveButton => {
-                        // Save token and proceed
-                        return Ok(Some(RegistryAction::Submit));
-                    }
-                    FocusState::CancelButton => {
-                        // Skip without saving
-                        return Ok(Some(RegistryAction::Skip));
+    /// Take the step the answers file decides on the current screen. False
+    /// when the screen needs the user; the answers are then dropped.
+    async fn follow_answers(&mut self, terminal: &mut DefaultTerminal) -> Result<bool> {
+        let Some(answers) = self.answers.as_mut() else {
+            return Ok(false);
+        };
+        match self.state.clone() {
+            // Loads on its own; the menu follows.
+            AppState::AirgappedSetup => return Ok(false),
+            AppState::RegistrySetup => match answers.registry_token() {
+                Some(Ok(token)) => {
+                    self.submit_registry_token(terminal, token).await?;
+                    // Still here: logged in with a warning, or the form shows
+                    // why the login failed.
+                    if self.state == AppState::RegistrySetup {
+                        if self.registry_form.form.error_message.is_empty() {
+                            if let Some(status) = self.registry_status.take() {
+                                install_log::record(status.text());
+                            }
+                            self.skip_registry_setup();
+                        } else {
+                            self.answers = None;
+                        }
                     }
                 }
+                Some(Err(e)) => {
+                    self.registry_form.form.error_message = format!("❌ {e}");
+                    self.answers = None;
+                }
+                None => self.skip_registry_setup(),
+            },
+            AppState::SslSetup | AppState::Confirmation
+                if !(self.cert_exists && self.env_has_ip) =>
+            {
+                if !answers.ssl.generate {
+                    self.answers = None;
+                    return Ok(false);
+                }
+                // Once only: a second miss is for the user to look at.
+                answers.ssl.generate = false;
+                self.generate_ssl(terminal).await?;
             }
-
-            // Typing only works when field is focused
-            KeyCode::Char(c) => {
-                if matches!(self.registry_form.focus_state, FocusState::Field(_)) {
-                    self.registry_form.token.push(c);
+            AppState::EnvReview => {
+                if let Some(review) = self.env_review.as_mut()
+                    && let Err(e) = review.apply()
+                {
+                    self.state =
+                        AppState::Error(trf("log.env_write_failed", &[("error", &e.to_string())]));
+                    return Ok(true);
                 }
+                self.finish_env_review();
             }
-            KeyCode::Backspace => {
-                if matches!(self.registry_form.focus_state, FocusState::Field(_)) {
-                    self.registry_form.token.pop();
+            AppState::Confirmation if answers.install => {
+                answers.install = false;
+                match answers.apply(&utils::project_root(), &mut self.settings) {
+                    Ok(written) => {
+                        written.iter().for_each(|line| install_log::record(line));
+                        if let Err(e) = self.settings.save() {
+                            install_log::record(&trf(
+                                "log.settings_not_saved_plain",
+                                &[("error", &e.to_string())],
+                            ));
+                        }
+                    }
+                    Err(e) => {
+                        self.state =
+                            AppState::Error(trf("log.answers_file", &[("error", &e.to_string())]));
+                        self.answers = None;
+                        return Ok(true);
+                    }
                 }
+                self.start_install(terminal).await?;
+            }
+            AppState::Success if answers.exit_when_done => self.running = false,
+            _ => {
+                self.answers = None;
+                return Ok(false);
             }
-
-            _ => {}
         }
-        Ok(None)
+        Ok(true)
     }
 
-    fn handle_confirmation_events(&mut self) -> Result<Option<MenuSelection>> {
-        if !event::poll(std::time::Duration::from_millis(200))? {
-            return Ok(None);
-        }
-        let Event::Key(key) = event::read()? else {
-            return Ok(None);
+    /// Log in with the token entered on the registry form and save it. A
+    /// failed login stays on the form with the error.
+    async fn submit_registry_token(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        token: String,
+    ) -> Result<()> {
+        let registry = self.registry_form.registry.clone();
+        let is_ghcr = registry.kind == RegistryKind::Ghcr;
+        self.registry_status = None;
+        let task = self.login_task(registry.clone(), token.clone());
+        let Some(outcome) = self.wait_for(terminal, task).await? else {
+            self.registry_form.form.error_message = tr("registry.login_cancelled").to_string();
+            return Ok(());
         };
-        if key.kind != KeyEventKind::Press {
-            return Ok(None);
-        }
-
-        let options = self.menu_options();
-        let current_idx = options
-            .iter()
-            .position(|o| o == &self.menu_selection)
-            .unwrap_or(0);
-
-        match key.code {
-            KeyCode::Up => {
-                if current_idx > 0 {
-                    self.menu_selection = options[current_idx - 1].clone();
+        match outcome {
+            Ok(checked) => {
+                let mut warning = checked.warning;
+                if let Err(e) = registry
+                    .save_credential(&token)
+                    .and_then(|_| registry.save_expiry(&token, checked.expires))
+                {
+                    warning = Some(trf(
+                        "registry.credentials_not_saved",
+                        &[("error", &e.to_string())],
+                    ));
                 }
-            }
-            KeyCode::Down => {
-                if current_idx + 1 < options.len() {
-                    self.menu_selection = options[current_idx + 1].clone();
+                if registry.name == registry::GHCR {
+                    self.ghcr_token = Some(token);
+                    self.token_expires = checked.expires;
+                }
+                self.registry_form.form.error_message.clear();
+                if let Some(warning) = warning {
+                    // Stay so the warning is seen; Esc continues.
+                    self.registry_status = Some(StatusMessage::Info(trf(
+                        "registry.logged_in_warning",
+                        &[("warning", &warning)],
+                    )));
+                } else if is_ghcr {
+                    self.registry_status = None;
+                    self.state = AppState::Confirmation;
+                    self.ensure_menu_selection();
+                } else {
+                    self.registry_status = Some(StatusMessage::Success(trf(
+                        "registry.logged_in_to",
+                        &[("host", registry.host())],
+                    )));
+                    self.refresh_registry_saved();
+                    self.state = AppState::Registries;
                 }
             }
-            KeyCode::Enter => {
-                return Ok(Some(self.menu_selection.clone()));
-            }
-            KeyCode::Esc => {
-                return Ok(Some(MenuSelection::Cancel));
-            }
-            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.running = false;
+            Err(message) => {
+                self.registry_form.form.error_message = message;
+                // Stay on RegistrySetup
             }
-            _ => {}
         }
-        Ok(None)
+        Ok(())
     }
 
-    fn handle_update_list_events(&mut self) -> Result<Option<UpdateListAction>> {
-        if !event::poll(std::time::Duration::from_millis(200))? {
-            return Ok(None);
-        }
-        let Event::Key(key) = event::read()? else {
-            return Ok(None);
-        };
-        if key.kind != KeyEventKind::Press {
-            return Ok(None);
+    /// Work out the hints for an error screen about to be drawn for the first time.
+    fn prepare_error_screen(&mut self) {
+        if let AppState::Error(message) = &self.state
+            && self.recovery.hints.is_none()
+        {
+            let text = format!("{message}\n{}", self.logs.join("\n"));
+            self.recovery.hints = Some(recovery::hints(&text, platform::DockerRuntime::detect()));
         }
+    }
 
+    async fn handle_error_events(
+        &mut self,
+        event: &Event,
+        terminal: &mut DefaultTerminal,
+    ) -> Result<()> {
+        let Some(key) = key_press(event) else {
+            return Ok(());
+        };
+        let AppState::Error(message) = self.state.clone() else {
+            return Ok(());
+        };
         match key.code {
-            KeyCode::Esc | KeyCode::Char('b') => return Ok(Some(UpdateListAction::Back)),
-            KeyCode::Char('r') => return Ok(Some(UpdateListAction::Refresh)),
-            KeyCode::Enter => {
-                // Pull the selected image update
-                if !self.update_infos.is_empty() {
-                    return Ok(Some(UpdateListAction::Pull));
+            KeyCode::Char('q') => self.running = false,
+            KeyCode::Char('r') if self.recovery.retry.is_some() => {
+                let retry = self.recovery.retry;
+                self.recovery = Recovery::default();
+                match retry {
+                    Some(Retry::Install) => self.start_install(terminal).await?,
+                    Some(Retry::SslCert) => {
+                        self.state = AppState::SslSetup;
+                        self.generate_ssl(terminal).await?;
+                    }
+                    None => {}
                 }
             }
-            KeyCode::Up => {
-                if self.update_selection_index > 0 {
-                    self.update_selection_index -= 1;
-                }
+            KeyCode::Char('m') | KeyCode::Esc => {
+                self.recovery = Recovery::default();
+                self.logs.clear();
+                self.state = AppState::Confirmation;
+                let root = utils::project_root();
+                self.cert_exists = root.join("certs/server.crt").exists()
+                    && root.join("certs/server.key").exists();
+                self.env_has_ip = env_template::has_server_ip(&root);
+                self.recheck_security = true;
+                self.ensure_menu_selection();
             }
-            KeyCode::Down => {
-                if self.update_selection_index + 1 < self.update_infos.len() {
-                    self.update_selection_index += 1;
-                }
+            KeyCode::Char('v') => {
+                self.recovery.full_log = match self.recovery.full_log {
+                    Some(_) => None,
+                    None => Some(recovery::full_log(&self.logs)),
+                };
+                self.recovery.scroll = 0;
             }
-            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.running = false;
+            KeyCode::Char('c') => {
+                let hints = self.recovery.hints.clone().unwrap_or_default();
+                let saved = recovery::save_report(
+                    &install_log::dir(),
+                    &message,
+                    &hints,
+                    &recovery::full_log(&self.logs),
+                );
+                self.recovery.status = Some(match saved {
+                    Ok(path) => Ok(trf(
+                        "log.error_saved",
+                        &[("path", &path.display().to_string())],
+                    )),
+                    Err(e) => Err(trf("log.error_not_saved", &[("error", &e.to_string())])),
+                });
             }
+            KeyCode::Up => self.recovery.scroll = self.recovery.scroll.saturating_add(1),
+            KeyCode::Down => self.recovery.scroll = self.recovery.scroll.saturating_sub(1),
+            KeyCode::PageUp => self.recovery.scroll = self.recovery.scroll.saturating_add(10),
+            KeyCode::PageDown => self.recovery.scroll = self.recovery.scroll.saturating_sub(10),
             _ => {}
         }
-        Ok(None)
+        Ok(())
     }
 
-    async fn pull_selected_update(&mut self) -> Result<()> {
-        let Some(info) = self.update_infos.get(self.update_selection_index).cloned() else {
-            return Ok(());
-        };
-
-        if info.is_self {
-            self.add_log("ℹ️  Self-update: please download the new installer from:");
-            self.add_log("    https://github.com/NexusQuantum/installer-NQRust-Identity/releases");
-            return Ok(());
+    /// Leave the GHCR token screen for the menu without logging in.
+    fn skip_registry_setup(&mut self) {
+        self.state = AppState::Confirmation;
+        // Skipping past an expired token means going on without it.
+        if self.token_expired() {
+            self.ghcr_token = None;
+            self.token_expires = None;
         }
+        // Refresh checklist status after returning from registry
+        let root = utils::project_root();
+        self.cert_exists =
+            root.join("certs/server.crt").exists() && root.join("certs/server.key").exists();
+        self.env_has_ip = env_template::has_server_ip(&root);
+        self.recheck_security = true;
+        self.ensure_menu_selection();
+    }
 
-        // Use the latest release tag if available (e.g. "v0.0.1"), otherwise fall back
-        let tag = info
-            .latest_release_tag
-            .as_deref()
-            .unwrap_or(info.current_tag.as_str());
-        let reference = format!("{}:{}", info.image, tag);
-
-        self.add_log(&format!("⬇️  Pulling {}...", reference));
-
-        // Login first if token is available
-        if let Some(token) = self.ghcr_token.clone() {
-            self.add_log("🔐 Logging into GHCR...");
-            if let Err(e) = self.login_to_ghcr(&token).await {
-                self.add_log(&format!("⚠️  GHCR login warning: {e}"));
-            }
-        }
+    fn token_expired(&self) -> bool {
+        self.ghcr_token.is_some()
+            && self
+                .token_expires
+                .is_some_and(|expires| expires <= Utc::now())
+    }
 
-        // Run docker pull and stream output to logs
-        let mut child = Command::new("docker")
-            .arg("pull")
-            .arg(&reference)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()?;
-
-        // Stream stderr (docker pull progress goes to stderr)
-        if let Some(stderr) = child.stderr.take() {
-            let mut reader = BufReader::new(stderr).lines();
-            while let Ok(Some(line)) = reader.next_line().await {
-                self.add_log(&line);
-            }
+    /// Send the user to the token screen when the saved GHCR token has
+    /// expired, rather than letting a pull fail on it. Returns true if it did.
+    fn route_expired_token(&mut self) -> bool {
+        if self.airgapped || !self.token_expired() {
+            return false;
         }
+        self.registry_form = RegistryForm::new();
+        self.registry_form.form.error_message = trf(
+            "registry.token_expired",
+            &[(
+                "date",
+                &self
+                    .token_expires
+                    .unwrap_or_default()
+                    .format("%Y-%m-%d")
+                    .to_string(),
+            )],
+        );
+        self.registry_status = None;
+        self.state = AppState::RegistrySetup;
+        true
+    }
 
-        let status = child.wait().await?;
-        if status.success() {
-            self.add_log(&format!("✅ Successfully pulled {}", reference));
-            // Update local_created timestamp in the stored info
-            if let Some(stored) = self.update_infos.get_mut(self.update_selection_index) {
-                use updates::get_local_image_created;
-                if let Ok(created) = get_local_image_created(&info.image, tag).await {
-                    stored.apply_local_created(created);
-                }
+    /// Write the certificate for `ssl_detected_ip` and put the address in .env.
+    async fn generate_ssl(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        self.ssl_status = Some(tr("log.generating_cert").to_string());
+        terminal.draw(|frame| self.render(frame))?;
+        let ip = self.ssl_detected_ip.clone();
+        let tuning = db_tuning::detect(self.runner.as_ref())
+            .await
+            .map(Tuning::for_host);
+        let result =
+            utils::generate_ssl_cert(&ip).and_then(|()| self.regenerate_env(&ip, tuning.as_ref()));
+        self.ssl_status = None;
+        match result {
+            // Update checklist state
+            Ok(()) => self.cert_exists = true,
+            Err(e) => {
+                self.recovery.retry = Some(Retry::SslCert);
+                self.state = AppState::Error(trf("log.cert_failed", &[("error", &e.to_string())]));
             }
-        } else {
-            self.add_log(&format!(
-                "❌ Failed to pull {} — check token and image name",
-                reference
-            ));
         }
-
         Ok(())
     }
 
-    // ─── Docker Compose ────────────────────────────────────────────────────────
+    /// Proceed from the menu; only offered when cert_exists && env_has_ip.
+    async fn start_install(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        if self.route_expired_token() {
+            return Ok(());
+        }
+        let root = utils::project_root();
+        if let Err(e) = utils::ensure_compose_bundle(&root) {
+            self.recovery.retry = Some(Retry::Install);
+            self.state = AppState::Error(trf(
+                "log.compose_write_failed",
+                &[("error", &e.to_string())],
+            ));
+            return Ok(());
+        }
+        self.state = AppState::Installing;
+        self.logs.clear();
+        self.quit = None;
+        self.install_log = install_log::start();
+        terminal.draw(|frame| self.render(frame))?;
+        if let Err(e) = self.run_docker_compose(terminal).await {
+            install_log::write(Level::Error, &format!("{e:#}"));
+            self.fire_hooks(HookEvent::InstallFailure, &e.to_string());
+            self.recovery.retry = Some(Retry::Install);
+            self.state = AppState::Error(trf("log.install_failed", &[("error", &e.to_string())]));
+        }
+        // A failed or cancelled install stays on screen rather than exiting.
+        if self.quit.take() == Some(QuitChoice::Wait) && self.state == AppState::Success {
+            self.exit_message = Some(trf(
+                "log.install_finished",
+                &[("url", &self.connection.url)],
+            ));
+            self.running = false;
+        }
+        Ok(())
+    }
+
+    /// What to tell the user on the plain terminal after the TUI has closed.
+    pub fn exit_message(&self) -> Option<&str> {
+        self.exit_message.as_deref()
+    }
+
+    /// Log of the last operation, as shown on its screen.
+    pub fn logs(&self) -> &[String] {
+        &self.logs
+    }
+
+    /// Check a GHCR token's scopes, then `docker login`. Ok carries a warning
+    /// worth showing; Err the message for the form.
+    fn login_task(
+        &self,
+        registry: Registry,
+        token: String,
+    ) -> Task<std::result::Result<token_check::Checked, String>> {
+        let client = self.http_client();
+        let runner = self.runner.clone();
+        Task::spawn(
+            &self.events,
+            tr("log.validating_credentials"),
+            move |progress| async move {
+                // Check scopes with the GitHub API first: docker login only says "denied".
+                let mut checked = token_check::Checked::default();
+                if registry.kind == RegistryKind::Ghcr {
+                    progress.note(tr("log.checking_scopes"));
+                    checked = token_check::check(&client, &token).await?;
+                    progress.note(tr("log.validating_credentials"));
+                }
+                registry
+                    .login(runner.as_ref(), Some(&token))
+                    .await
+                    .map_err(|e| {
+                        trf(
+                            "log.login_failed",
+                            &[(
+                                "error",
+                                e.to_string()
+                                    .lines()
+                                    .next()
+                                    .unwrap_or(tr("log.unknown_error")),
+                            )],
+                        )
+                    })?;
+                Ok(checked)
+            },
+        )
+    }
+
+    fn handle_registry_events(&mut self, event: &Event) -> Result<Option<RegistryAction>> {
+        Ok(self
+            .form_event(event, |app| Some(&mut app.registry_form.form))?
+            .map(|action| match action {
+                FormAction::Save => RegistryAction::Submit,
+                FormAction::Cancel => RegistryAction::Skip,
+            }))
+    }
+
+    fn refresh_registry_saved(&mut self) {
+        self.registry_saved = registry::configured(&self.settings)
+            .iter()
+            .map(|r| r.credential().is_some())
+            .collect();
+    }
+
+    fn handle_registries_events(&mut self, event: &Event) -> Result<Option<RegistriesAction>> {
+        let Some(key) = key_press(event) else {
+            return Ok(None);
+        };
+
+        let count = registry::configured(&self.settings).len();
+        match key.code {
+            KeyCode::Up => self.registry_selection = self.registry_selection.saturating_sub(1),
+            KeyCode::Down => {
+                self.registry_selection = (self.registry_selection + 1).min(count - 1);
+            }
+            KeyCode::Enter => return Ok(Some(RegistriesAction::Login)),
+            KeyCode::Char('m') | KeyCode::Char('M') => {
+                return Ok(Some(RegistriesAction::ToggleMirror));
+            }
+            KeyCode::Char('f') | KeyCode::Char('F') => return Ok(Some(RegistriesAction::Forget)),
+            KeyCode::Esc => return Ok(Some(RegistriesAction::Back)),
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn handle_proxy_events(&mut self, event: &Event) -> Result<Option<FormAction>> {
+        self.form_event(event, |app| Some(&mut app.proxy_form.form))
+    }
+
+    fn handle_advanced_events(&mut self, event: &Event) -> Result<Option<FormAction>> {
+        self.form_event(event, |app| app.advanced_form.as_mut().map(|f| &mut f.form))
+    }
+
+    fn handle_env_editor_events(&mut self, event: &Event) -> Result<Option<FormAction>> {
+        self.form_event(event, |app| app.env_form.as_mut().map(|f| &mut f.form))
+    }
+
+    fn handle_yaml_editor_events(&mut self, event: &Event) -> Result<Option<FormAction>> {
+        self.form_event(event, |app| app.yaml_form.as_mut().map(|f| &mut f.form))
+    }
+
+    fn save_yaml_form(&mut self) {
+        let Some(form) = self.yaml_form.as_mut() else {
+            return;
+        };
+        if !form.validate() {
+            return;
+        }
+        if let Err(e) = form.save() {
+            form.form.error_message = trf(
+                "log.cannot_write_output",
+                &[("file", yaml_form::CONFIG_YAML), ("error", &e.to_string())],
+            );
+            return;
+        }
+        self.yaml_form = None;
+        self.state = AppState::Confirmation;
+    }
+
+    /// Templates matching the filter typed on the selection screen.
+    fn shown_templates(&self) -> Vec<&ConfigTemplate> {
+        self.templates
+            .iter()
+            .filter(|template| template.matches(&self.template_filter))
+            .collect()
+    }
+
+    async fn handle_template_selection_events(&mut self, event: &Event) -> Result<()> {
+        let Some(key) = key_press(event) else {
+            return Ok(());
+        };
+
+        let last = self.shown_templates().len().saturating_sub(1);
+        let cols = ui::GRID_COLUMNS;
+        match key.code {
+            // The first Esc clears the filter.
+            KeyCode::Esc if !self.template_filter.is_empty() => {
+                self.template_filter.clear();
+                self.template_selection = 0;
+            }
+            KeyCode::Esc => self.state = AppState::Confirmation,
+            KeyCode::Left => self.template_selection = self.template_selection.saturating_sub(1),
+            KeyCode::Right => self.template_selection = (self.template_selection + 1).min(last),
+            KeyCode::Up => {
+                self.template_selection = self.template_selection.saturating_sub(cols);
+            }
+            KeyCode::Down => {
+                self.template_selection = (self.template_selection + cols).min(last);
+            }
+            KeyCode::Home => self.template_selection = 0,
+            KeyCode::End => self.template_selection = last,
+            KeyCode::Backspace => {
+                if let Some((at, _)) = self.template_filter.grapheme_indices(true).next_back() {
+                    self.template_filter.truncate(at);
+                }
+                self.template_selection = 0;
+            }
+            KeyCode::Char(c)
+                if key.modifiers.is_empty() || key.modifiers == KeyModifiers::SHIFT =>
+            {
+                self.template_filter.push(c);
+                self.template_selection = 0;
+            }
+            KeyCode::Enter => {
+                if let Some(template) = self
+                    .shown_templates()
+                    .get(self.template_selection)
+                    .map(|template| (*template).clone())
+                {
+                    let mut form = TemplateForm::new(template.clone());
+                    if !template.gpu_services.is_empty() {
+                        form.gpu = Some(gpu::detect(self.runner.as_ref()).await);
+                    }
+                    self.template_form = Some(form);
+                    self.state = AppState::TemplateForm;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_template_form_events(&mut self, event: &Event) -> Result<Option<FormAction>> {
+        self.form_event(event, |app| app.template_form.as_mut().map(|f| &mut f.form))
+    }
+
+    async fn save_template_form(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        let Some(form) = self.template_form.as_mut() else {
+            return Ok(());
+        };
+        if !form.form.validate() {
+            return Ok(());
+        }
+        let checks = form.checks_to_run();
+        if !checks.is_empty() {
+            let labels: Vec<String> = checks.iter().map(|c| c.label()).collect();
+            form.checks = vec![trf("log.checking", &[("checks", &labels.join(", "))])];
+            terminal.draw(|frame| self.render(frame))?;
+            let client = self.http_client();
+            let mut results = Vec::new();
+            for check in checks {
+                let result = check.run(&client).await;
+                results.push((check, result));
+            }
+            let Some(form) = self.template_form.as_mut() else {
+                return Ok(());
+            };
+            if !form.record_checks(results) {
+                return Ok(());
+            }
+        }
+        let Some(form) = self.template_form.as_mut() else {
+            return Ok(());
+        };
+        let root = utils::project_root();
+        match form.save(&root) {
+            Ok(path) => {
+                install_log::record(&trf(
+                    "log.wrote_template",
+                    &[("path", &path.display().to_string())],
+                ));
+                let mut status = trf("log.wrote", &[("path", &path.display().to_string())]);
+                self.settings.generated_files.insert(
+                    form.template.output.display().to_string(),
+                    form.template.name.clone(),
+                );
+                if let Some(gpu) = &form.gpu {
+                    let services = &form.template.gpu_services;
+                    self.settings.gpu_services.retain(|s| !services.contains(s));
+                    if gpu.usable() {
+                        self.settings.gpu_services.extend(services.iter().cloned());
+                    }
+                    let synced = self.settings.save().and_then(|()| {
+                        compose_override::sync(&root, &self.settings, self.airgapped)
+                    });
+                    match synced {
+                        Err(e) => {
+                            status.push_str(&trf("log.gpu_not_saved", &[("error", &e.to_string())]))
+                        }
+                        Ok(()) if gpu.usable() => status.push_str(&trf(
+                            "log.gpu_reserved",
+                            &[
+                                ("services", &services.join(", ")),
+                                ("file", compose_override::OVERRIDE_FILE),
+                            ],
+                        )),
+                        Ok(()) => status
+                            .push_str(&trf("log.gpu_cpu", &[("services", &services.join(", "))])),
+                    }
+                } else if let Err(e) = self.settings.save() {
+                    status.push_str(&trf(
+                        "log.settings_not_saved_suffix",
+                        &[("error", &e.to_string())],
+                    ));
+                }
+                status.push_str(tr("log.restart_to_apply"));
+                form.status = Some(status);
+            }
+            Err(e) => {
+                form.form.error_message = trf(
+                    "log.cannot_write_output",
+                    &[
+                        ("file", &form.template.output.display().to_string()),
+                        ("error", &e.to_string()),
+                    ],
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_env_review_events(&mut self, event: &Event) -> Result<()> {
+        let Some(key) = key_press(event) else {
+            return Ok(());
+        };
+        let Some(review) = self.env_review.as_mut() else {
+            self.state = AppState::Confirmation;
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Up => review.select_prev(),
+            KeyCode::Down => review.select_next(),
+            KeyCode::Char(' ') => review.toggle_selected(),
+            KeyCode::Enter => {
+                if let Err(e) = review.apply() {
+                    self.state =
+                        AppState::Error(trf("log.env_write_failed", &[("error", &e.to_string())]));
+                    return Ok(());
+                }
+                self.finish_env_review();
+            }
+            // Esc leaves .env untouched.
+            KeyCode::Esc => self.finish_env_review(),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn finish_env_review(&mut self) {
+        self.env_review = None;
+        self.env_has_ip = env_template::has_server_ip(&utils::project_root());
+        self.recheck_security = true;
+        self.state = AppState::Confirmation;
+        self.ensure_menu_selection();
+    }
+
+    fn save_env_form(&mut self) {
+        let Some(form) = self.env_form.as_mut() else {
+            return;
+        };
+        if !form.form.validate() {
+            return;
+        }
+        if let Err(e) = form.save() {
+            form.form.error_message =
+                trf("log.env_write_failed_icon", &[("error", &e.to_string())]);
+            return;
+        }
+        self.env_has_ip = env_template::has_server_ip(&utils::project_root());
+        self.recheck_security = true;
+        self.env_form = None;
+        self.ensure_menu_selection();
+        self.state = AppState::Confirmation;
+    }
+
+    fn handle_dir_picker_events(&mut self, event: &Event) {
+        let (Some(key), Some(picker)) = (key_press(event), self.dir_picker.as_mut()) else {
+            return;
+        };
+        match key.code {
+            KeyCode::Up => picker.select_prev(),
+            KeyCode::Down => picker.select_next(),
+            KeyCode::Enter | KeyCode::Right => picker.open(),
+            KeyCode::Backspace | KeyCode::Left => picker.parent(),
+            KeyCode::Char('s') | KeyCode::Char(' ') => {
+                let dir = picker.dir.clone();
+                self.dir_picker = None;
+                self.switch_project(dir);
+            }
+            KeyCode::Esc => {
+                self.dir_picker = None;
+                self.state = AppState::Confirmation;
+            }
+            _ => {}
+        }
+    }
+
+    async fn handle_context_picker_events(&mut self, event: &Event) {
+        let (Some(key), Some(picker)) = (key_press(event), self.context_picker.as_mut()) else {
+            return;
+        };
+        let chosen = match key.code {
+            KeyCode::Up => return picker.select_prev(),
+            KeyCode::Down => return picker.select_next(),
+            KeyCode::Enter => match picker.highlighted() {
+                Some(context) => Some(context.name.clone()),
+                None => return,
+            },
+            KeyCode::Char('r') => None,
+            KeyCode::Esc => {
+                self.context_picker = None;
+                self.state = AppState::Confirmation;
+                return;
+            }
+            _ => return,
+        };
+        docker_context::select(chosen.clone());
+        install_log::record(&trf(
+            "log.docker_daemon",
+            &[(
+                "daemon",
+                &docker_context::describe().unwrap_or_else(|| tr("log.docker_default").to_string()),
+            )],
+        ));
+        self.settings.docker_context = chosen.clone();
+        let saved = self.settings.save();
+        let check = docker_context::check(self.runner.as_ref()).await;
+        if let Some(picker) = self.context_picker.as_mut() {
+            picker.chosen = chosen;
+            picker.check = Some(match saved {
+                Ok(()) => check,
+                Err(e) => Err(trf(
+                    "log.settings_not_saved_lower",
+                    &[("error", &e.to_string())],
+                )),
+            });
+        }
+    }
+
+    fn handle_service_picker_events(&mut self, event: &Event) {
+        let (Some(key), Some(picker)) = (key_press(event), self.service_picker.as_mut()) else {
+            return;
+        };
+        match key.code {
+            KeyCode::Up => picker.select_prev(),
+            KeyCode::Down => picker.select_next(),
+            KeyCode::Char(' ') => picker.toggle(),
+            KeyCode::Enter => {
+                self.settings.disabled_services = picker.disabled();
+                self.service_picker = None;
+                let root = utils::project_root();
+                let saved = self
+                    .settings
+                    .save()
+                    .and_then(|()| compose_override::sync(&root, &self.settings, self.airgapped));
+                self.connection = ConnectionDetails::load(&root, &self.settings);
+                self.state = match saved {
+                    Ok(_) => AppState::Confirmation,
+                    Err(e) => {
+                        AppState::Error(trf("log.services_not_saved", &[("error", &e.to_string())]))
+                    }
+                };
+            }
+            KeyCode::Esc => {
+                self.service_picker = None;
+                self.state = AppState::Confirmation;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_preset_picker_events(&mut self, event: &Event) {
+        let (Some(key), Some(picker)) = (key_press(event), self.preset_picker.as_mut()) else {
+            return;
+        };
+        match key.code {
+            KeyCode::Up => return picker.select_prev(),
+            KeyCode::Down => return picker.select_next(),
+            KeyCode::Esc => {
+                self.preset_picker = None;
+                self.state = AppState::Confirmation;
+                return;
+            }
+            KeyCode::Enter => {}
+            _ => return,
+        }
+        let (preset, host) = (picker.highlighted(), picker.host);
+        let status = self.apply_preset(preset, host);
+        if self.state == AppState::ResourcePresets
+            && let Some(picker) = self.preset_picker.as_mut()
+        {
+            picker.status = Some(status);
+        }
+    }
+
+    /// Write the limits of `preset` and resize Postgres to its own limit;
+    /// moves to the .env review when that replaces values already in .env.
+    fn apply_preset(&mut self, preset: Preset, host: Option<Host>) -> Result<String, String> {
+        preset.apply(host, &mut self.settings.advanced);
+        let root = utils::project_root();
+        self.settings
+            .save()
+            .and_then(|()| compose_override::sync(&root, &self.settings, self.airgapped))
+            .map_err(|e| trf("log.limits_not_saved", &[("error", &e.to_string())]))?;
+        let env_path = root.join(".env");
+        if ExternalDb::load(&root).is_none()
+            && let Ok(current) = EnvFile::load(&env_path)
+        {
+            let proposed = preset.postgres_env(host, &current);
+            if let Some(review) = EnvReview::new(env_path.clone(), current, proposed.clone()) {
+                self.env_review = Some(review);
+                self.preset_picker = None;
+                self.state = AppState::EnvReview;
+                return Ok(String::new());
+            }
+            proposed
+                .save(&env_path)
+                .map_err(|e| trf("log.env_not_written", &[("error", &e.to_string())]))?;
+        }
+        Ok(trf(
+            "log.preset_written",
+            &[
+                ("preset", preset.name()),
+                ("file", compose_override::OVERRIDE_FILE),
+            ],
+        ))
+    }
+
+    /// Make `dir` the project directory and reload everything read from it.
+    fn switch_project(&mut self, dir: PathBuf) {
+        utils::set_project_root(dir);
+        let root = utils::project_root();
+        install_log::record(&trf(
+            "log.project_dir",
+            &[("path", &root.display().to_string())],
+        ));
+        self.cert_exists =
+            root.join("certs/server.crt").exists() && root.join("certs/server.key").exists();
+        self.env_has_ip = env_template::has_server_ip(&root);
+        self.recheck_security = true;
+        self.settings = Settings::load();
+        docker_context::select(
+            self.settings
+                .docker_context
+                .clone()
+                .filter(|_| self.remote.is_none()),
+        );
+        self.proxy_form = ProxyForm::from_settings(&self.settings.proxy);
+        self.connection = ConnectionDetails::load(&root, &self.settings);
+        self.update_status = UpdateStatus::load(&root);
+        self.legacy = None;
+        self.state = AppState::Confirmation;
+        self.ensure_menu_selection();
+    }
+
+    /// Save the advanced options and regenerate the compose override; the
+    /// stack picks them up on the next install or update.
+    fn save_advanced_form(&mut self) {
+        let Some(form) = self.advanced_form.as_mut() else {
+            return;
+        };
+        if !form.validate() {
+            return;
+        }
+        let previous_level = self.settings.advanced.log_level;
+        self.settings.advanced = form.to_settings();
+        let level = self.settings.advanced.log_level;
+        let root = utils::project_root();
+        let saved = self
+            .settings
+            .save()
+            .and_then(|()| compose_override::sync(&root, &self.settings, self.airgapped))
+            // Only a changed level touches .env, so one set there by hand stays.
+            .and_then(|_| {
+                if level == previous_level {
+                    return Ok(());
+                }
+                App::write_log_level(&root, level)
+            });
+        if let Err(e) = saved {
+            form.form.error_message = trf("log.advanced_not_saved", &[("error", &e.to_string())]);
+            return;
+        }
+        self.advanced_form = None;
+        self.state = AppState::Confirmation;
+    }
+
+    fn handle_migration_events(&mut self, event: &Event) -> Result<Option<MigrationAction>> {
+        Ok(self
+            .form_event(event, |app| Some(&mut app.migration_form.form))?
+            .map(|action| match action {
+                FormAction::Save => MigrationAction::Start,
+                FormAction::Cancel => MigrationAction::Cancel,
+            }))
+    }
+
+    async fn run_migration(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        self.logs.clear();
+        self.output_scroll = 0;
+        self.state = AppState::Migrating;
+        if let Err(e) = self.migrate_keycloak(terminal).await {
+            self.add_log(&format!("❌ {e}"));
+        }
+        Ok(())
+    }
+
+    /// Copy every non-master realm and its users from the source Keycloak.
+    /// A failing realm is reported and skipped so the others still migrate.
+    async fn migrate_keycloak(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        let url = self.migration_form.url().to_string();
+        self.add_log(&trf("log.connecting", &[("url", &url)]));
+        terminal.draw(|frame| self.render(frame))?;
+        let source = Endpoint::login(
+            self.http_client(),
+            &url,
+            self.migration_form.username(),
+            self.migration_form.password(),
+        )
+        .await?;
+        self.add_log(tr("log.logged_in_source"));
+
+        self.add_log(&trf(
+            "log.connecting_target",
+            &[("url", &keycloak_migrate::target_url(self.runner.as_ref()))],
+        ));
+        terminal.draw(|frame| self.render(frame))?;
+        let target = Endpoint::login_target(self.runner.as_ref()).await?;
+        self.add_log(tr("log.logged_in_target"));
+
+        let realms = source.realm_names().await?;
+        if realms.is_empty() {
+            self.add_log(tr("log.nothing_to_migrate"));
+            return Ok(());
+        }
+
+        let mut failed = 0;
+        for realm in &realms {
+            self.add_log(&trf("log.realm_exporting", &[("realm", realm)]));
+            terminal.draw(|frame| self.render(frame))?;
+            let result = async {
+                let rep = source.export_realm(realm).await?;
+                let created = target.import_realm(&rep).await?;
+                let users = source.export_users(realm).await?;
+                let (added, skipped) = target.import_users(realm, users).await?;
+                Ok::<_, color_eyre::Report>((created, added, skipped))
+            }
+            .await;
+            match result {
+                Ok((created, added, skipped)) => self.add_log(&trf(
+                    "log.realm_done",
+                    &[
+                        ("realm", realm),
+                        (
+                            "result",
+                            if created {
+                                tr("log.created")
+                            } else {
+                                tr("log.merged")
+                            },
+                        ),
+                        ("added", &added.to_string()),
+                        ("skipped", &skipped.to_string()),
+                    ],
+                )),
+                Err(e) => {
+                    failed += 1;
+                    self.add_log(&trf(
+                        "log.realm_failed",
+                        &[("realm", realm), ("error", &e.to_string())],
+                    ));
+                }
+            }
+        }
+
+        self.add_log("");
+        self.add_log(&trf(
+            "log.migrated",
+            &[
+                ("migrated", &(realms.len() - failed).to_string()),
+                ("total", &realms.len().to_string()),
+            ],
+        ));
+        self.add_log(tr("log.reset_passwords"));
+        Ok(())
+    }
+
+    async fn handle_migration_output_events(
+        &mut self,
+        event: &Event,
+        terminal: &mut DefaultTerminal,
+    ) -> Result<()> {
+        let Some(key) = key_press(event) else {
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('b') | KeyCode::Char('q') => {
+                self.logs.clear();
+                self.state = AppState::Confirmation;
+            }
+            // Re-running is safe: existing realms and users are skipped.
+            KeyCode::Char('r') => self.run_migration(terminal).await?,
+            KeyCode::Up => self.output_scroll = self.output_scroll.saturating_add(1),
+            KeyCode::Down => self.output_scroll = self.output_scroll.saturating_sub(1),
+            KeyCode::PageUp => self.output_scroll = self.output_scroll.saturating_add(10),
+            KeyCode::PageDown => self.output_scroll = self.output_scroll.saturating_sub(10),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Save the SMTP form to .env, then apply it to the stack and send the
+    /// test mail.
+    async fn run_smtp_setup(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        let Some(form) = &self.smtp_form else {
+            return Ok(());
+        };
+        let settings = form.to_settings();
+        let to = form.test_recipient().map(str::to_string);
+        self.logs.clear();
+        self.output_scroll = 0;
+        self.state = AppState::SmtpApplying;
+
+        let env_path = utils::project_root().join(".env");
+        let saved = EnvFile::load(&env_path).and_then(|mut env| {
+            settings.write_env(&mut env);
+            env.save(&env_path)
+        });
+        match saved {
+            Ok(()) => self.add_log(&trf(
+                "log.saved_host_port",
+                &[
+                    ("host", &settings.host),
+                    ("port", &settings.port.to_string()),
+                ],
+            )),
+            Err(e) => {
+                self.add_log(&trf("log.env_not_updated", &[("error", &e.to_string())]));
+                return Ok(());
+            }
+        }
+        if let Err(e) = self.apply_smtp(terminal, &settings, to.as_deref()).await {
+            self.add_log(&format!("❌ {e}"));
+        }
+        Ok(())
+    }
+
+    /// Write `settings` into every realm, then have Keycloak send its test
+    /// mail.
+    async fn apply_smtp(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        settings: &SmtpSettings,
+        to: Option<&str>,
+    ) -> Result<()> {
+        self.add_log(&trf(
+            "log.connecting_target",
+            &[("url", &keycloak_migrate::target_url(self.runner.as_ref()))],
+        ));
+        terminal.draw(|frame| self.render(frame))?;
+        let endpoint = Endpoint::login_target(self.runner.as_ref()).await?;
+        let realms = smtp::apply(&endpoint, settings).await?;
+        self.add_log(&trf("log.smtp_set", &[("realms", &realms.join(", "))]));
+
+        self.add_log(tr("log.sending_test"));
+        terminal.draw(|frame| self.render(frame))?;
+        let recipient = smtp::send_test(&endpoint, settings, to).await?;
+        self.add_log(&trf("log.test_sent", &[("recipient", &recipient)]));
+        Ok(())
+    }
+
+    async fn run_client_registration(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        let request = self.oidc_client_form.to_request();
+        self.logs.clear();
+        self.output_scroll = 0;
+        self.state = AppState::OidcClientRegistering;
+        self.add_log(&trf(
+            "log.connecting_target",
+            &[("url", &keycloak_migrate::target_url(self.runner.as_ref()))],
+        ));
+        terminal.draw(|frame| self.render(frame))?;
+
+        let root = utils::project_root();
+        let base_url = ConnectionDetails::load(&root, &self.settings).url;
+        let result = async {
+            let endpoint = Endpoint::login_target(self.runner.as_ref()).await?;
+            oidc_client::register(&endpoint, &request, &base_url).await
+        }
+        .await;
+        let client = match result {
+            Ok(client) => client,
+            Err(e) => {
+                self.add_log(&format!("❌ {e}"));
+                return Ok(());
+            }
+        };
+        self.add_log(&trf(
+            "log.client_done",
+            &[
+                ("client_id", &client.client_id),
+                (
+                    "result",
+                    if client.created {
+                        tr("log.created")
+                    } else {
+                        tr("log.updated")
+                    },
+                ),
+                ("realm", &request.realm),
+            ],
+        ));
+        // Shown here but kept out of the install log, which gets attached to
+        // support tickets.
+        self.logs.push(String::new());
+        self.logs
+            .push(trf("log.issuer", &[("issuer", &client.issuer)]));
+        self.logs
+            .push(trf("log.client_id", &[("client_id", &client.client_id)]));
+        self.logs
+            .push(trf("log.client_secret", &[("secret", &client.secret)]));
+        self.logs.push(String::new());
+        match oidc_client::export(&root, &client, &request.redirect_uris) {
+            Ok(path) => self.add_log(&trf(
+                "log.exported",
+                &[("path", &path.display().to_string())],
+            )),
+            Err(e) => self.add_log(&trf("log.export_failed", &[("error", &e.to_string())])),
+        }
+        Ok(())
+    }
+
+    fn handle_client_output_events(&mut self, event: &Event) -> Result<()> {
+        let Some(key) = key_press(event) else {
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('b') | KeyCode::Char('q') => {
+                self.logs.clear();
+                self.state = AppState::Confirmation;
+            }
+            KeyCode::Char('r') => {
+                self.logs.clear();
+                self.oidc_client_form = OidcClientForm::new();
+                self.state = AppState::OidcClientSetup;
+            }
+            KeyCode::Up => self.output_scroll = self.output_scroll.saturating_add(1),
+            KeyCode::Down => self.output_scroll = self.output_scroll.saturating_sub(1),
+            KeyCode::PageUp => self.output_scroll = self.output_scroll.saturating_add(10),
+            KeyCode::PageDown => self.output_scroll = self.output_scroll.saturating_sub(10),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn handle_compose_check_events(
+        &mut self,
+        event: &Event,
+        terminal: &mut DefaultTerminal,
+    ) -> Result<()> {
+        let Some(key) = key_press(event) else {
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('b') | KeyCode::Char('q') => {
+                self.logs.clear();
+                self.state = AppState::Confirmation;
+            }
+            KeyCode::Char('r') => self.start_install(terminal).await?,
+            KeyCode::Up => self.output_scroll = self.output_scroll.saturating_add(1),
+            KeyCode::Down => self.output_scroll = self.output_scroll.saturating_sub(1),
+            KeyCode::PageUp => self.output_scroll = self.output_scroll.saturating_add(10),
+            KeyCode::PageDown => self.output_scroll = self.output_scroll.saturating_sub(10),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Compare the bundle files with the embedded ones and show the diff;
+    /// nothing is written until the user confirms.
+    fn show_bundle_diff(&mut self) {
+        let root = utils::project_root();
+        self.output_scroll = 0;
+        self.state = AppState::BundleRewrite;
+        self.bundle_files = match bundle::compare(&root) {
+            Ok(files) => files,
+            Err(e) => {
+                self.logs = vec![trf(
+                    "log.bundle_compare_failed",
+                    &[("error", &e.to_string())],
+                )];
+                self.bundle_files.clear();
+                return;
+            }
+        };
+        self.logs = vec![trf(
+            "log.bundle_header",
+            &[("path", &root.display().to_string())],
+        )];
+        for file in &self.bundle_files {
+            match &file.state {
+                bundle::FileState::Current => {
+                    self.logs
+                        .push(trf("log.bundle_same", &[("file", file.name)]));
+                }
+                bundle::FileState::Missing => {
+                    self.logs
+                        .push(trf("log.bundle_missing", &[("file", file.name)]));
+                }
+                bundle::FileState::Differs(diff) if diff.is_empty() => self
+                    .logs
+                    .push(trf("log.bundle_line_endings", &[("file", file.name)])),
+                bundle::FileState::Differs(diff) => {
+                    self.logs
+                        .push(trf("log.bundle_differs", &[("file", file.name)]));
+                    self.logs
+                        .extend(diff.iter().map(|line| format!("   {line}")));
+                }
+            }
+        }
+        self.logs.push(String::new());
+        if self
+            .bundle_files
+            .iter()
+            .all(|file| file.state == bundle::FileState::Current)
+        {
+            self.bundle_files.clear();
+            self.logs.push(tr("log.bundle_nothing").to_string());
+        } else {
+            self.logs.push(tr("log.bundle_prompt").to_string());
+        }
+    }
+
+    fn handle_bundle_events(&mut self, event: &Event) -> Result<()> {
+        let Some(key) = key_press(event) else {
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Char('y') if !self.bundle_files.is_empty() => {
+                let files = std::mem::take(&mut self.bundle_files);
+                self.logs.push(String::new());
+                match bundle::rewrite(&utils::project_root(), &files) {
+                    Ok(written) => self.logs.extend(written),
+                    Err(e) => self.logs.push(format!("❌ {e}")),
+                }
+                self.output_scroll = 0;
+            }
+            KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('b') | KeyCode::Char('q') => {
+                self.logs.clear();
+                self.bundle_files.clear();
+                self.state = AppState::Confirmation;
+            }
+            KeyCode::Up => self.output_scroll = self.output_scroll.saturating_add(1),
+            KeyCode::Down => self.output_scroll = self.output_scroll.saturating_sub(1),
+            KeyCode::PageUp => self.output_scroll = self.output_scroll.saturating_add(10),
+            KeyCode::PageDown => self.output_scroll = self.output_scroll.saturating_sub(10),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// List what a rotation changes and what it breaks; nothing runs until
+    /// the user confirms.
+    fn show_rotation_plan(&mut self) {
+        let root = utils::project_root();
+        let blocker = rotation::db_password_blocker(&root, &self.settings.secrets);
+        self.logs = vec![
+            tr("log.rotation_header").to_string(),
+            tr("log.rotation_keys").to_string(),
+            match blocker {
+                None => tr("log.rotation_db").to_string(),
+                Some(reason) => trf("log.rotation_db_stays", &[("reason", reason)]),
+            },
+            String::new(),
+            tr("log.rotation_sessions").to_string(),
+            String::new(),
+            if blocker.is_none() {
+                tr("log.rotation_prompt_db")
+            } else {
+                tr("log.rotation_prompt")
+            }
+            .to_string(),
+        ];
+        self.rotation_ran = false;
+        self.output_scroll = 0;
+        self.state = AppState::RotateSecrets;
+    }
+
+    async fn handle_rotation_events(
+        &mut self,
+        event: &Event,
+        terminal: &mut DefaultTerminal,
+    ) -> Result<()> {
+        let Some(key) = key_press(event) else {
+            return Ok(());
+        };
+        let root = utils::project_root();
+        let db_password = match key.code {
+            KeyCode::Char('y') => false,
+            KeyCode::Char('d')
+                if rotation::db_password_blocker(&root, &self.settings.secrets).is_none() =>
+            {
+                true
+            }
+            KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('b') | KeyCode::Char('q') => {
+                self.logs.clear();
+                self.state = AppState::Confirmation;
+                return Ok(());
+            }
+            KeyCode::Up => {
+                self.output_scroll = self.output_scroll.saturating_add(1);
+                return Ok(());
+            }
+            KeyCode::Down => {
+                self.output_scroll = self.output_scroll.saturating_sub(1);
+                return Ok(());
+            }
+            KeyCode::PageUp => {
+                self.output_scroll = self.output_scroll.saturating_add(10);
+                return Ok(());
+            }
+            KeyCode::PageDown => {
+                self.output_scroll = self.output_scroll.saturating_sub(10);
+                return Ok(());
+            }
+            _ => return Ok(()),
+        };
+        if self.rotation_ran {
+            return Ok(());
+        }
+        self.rotation_ran = true;
+        self.logs.push(String::new());
+
+        // The password first: it restarts Keycloak, which then comes up before
+        // the keys are rotated through it.
+        if db_password {
+            self.add_log(tr("log.changing_db_password"));
+            terminal.draw(|frame| self.render(frame))?;
+            if let Err(e) = rotation::rotate_db_password(self.runner.as_ref(), &root).await {
+                self.add_log(&format!("❌ {e}"));
+                return Ok(());
+            }
+            self.add_log(tr("log.db_password_changed"));
+            terminal.draw(|frame| self.render(frame))?;
+            let identity = external_db::services(&root, &self.settings)
+                .into_iter()
+                .find(|service| service.container == backup::APP_CONTAINER);
+            if let Some(identity) = identity {
+                let deadline = Instant::now() + verify::VERIFY_TIMEOUT;
+                while let Err(reason) = verify::check(self.runner.as_ref(), identity).await {
+                    if Instant::now() >= deadline {
+                        self.add_log(&trf("log.keycloak_not_back", &[("reason", &reason)]));
+                        return Ok(());
+                    }
+                    tokio::time::sleep(verify::RETRY_INTERVAL).await;
+                }
+            }
+        }
+
+        self.add_log(tr("log.rotating_keys"));
+        terminal.draw(|frame| self.render(frame))?;
+        match rotation::rotate_keys(self.runner.as_ref()).await {
+            Ok(realms) => self.add_log(&trf("log.keys_rotated", &[("realms", &realms.join(", "))])),
+            Err(e) => self.add_log(&format!("❌ {e}")),
+        }
+        Ok(())
+    }
+
+    /// Test the server from the form and save it to .env, or go back to the
+    /// bundled database when the host was left empty. Either way the compose
+    /// file and override are regenerated to match.
+    async fn run_external_db_setup(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        let Some(form) = &self.external_db_form else {
+            return Ok(());
+        };
+        let db = form.to_settings();
+        self.logs.clear();
+        self.output_scroll = 0;
+        self.state = AppState::ExternalDbTesting;
+
+        let root = utils::project_root();
+        let installed = InstallManifest::path(&root).is_file();
+        if let Some(db) = &db {
+            self.add_log(&trf("log.db_testing", &[("address", &db.address())]));
+            terminal.draw(|frame| self.render(frame))?;
+            match external_db::test(self.runner.as_ref(), db).await {
+                Ok(version) => self.add_log(&trf("log.db_connected", &[("version", &version)])),
+                Err(e) => {
+                    self.add_log(&format!("❌ {e}"));
+                    self.add_log(tr("log.nothing_saved"));
+                    return Ok(());
+                }
+            }
+        }
+
+        let env_path = root.join(".env");
+        let saved = EnvFile::load(&env_path)
+            .and_then(|mut env| {
+                match &db {
+                    Some(db) => db.write_env(&mut env),
+                    None => external_db::disable(&mut env),
+                }
+                env.save(&env_path)
+            })
+            .and_then(|()| utils::ensure_compose_bundle(&root))
+            .and_then(|()| compose_override::sync(&root, &self.settings, self.airgapped));
+        if let Err(e) = saved {
+            self.add_log(&trf(
+                "log.project_not_updated",
+                &[("error", &e.to_string())],
+            ));
+            return Ok(());
+        }
+        match &db {
+            Some(db) => self.add_log(&trf("log.external_db_saved", &[("address", &db.address())])),
+            None => self.add_log(tr("log.bundled_db")),
+        }
+        if installed {
+            self.add_log(tr("log.db_switch_hint"));
+        }
+        self.external_db_form = None;
+        Ok(())
+    }
+
+    fn handle_external_db_output_events(&mut self, event: &Event) {
+        let Some(key) = key_press(event) else {
+            return;
+        };
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('b') | KeyCode::Char('q') => {
+                self.logs.clear();
+                self.external_db_form = None;
+                self.state = AppState::Confirmation;
+            }
+            // Back to the form, with what was entered when the test failed.
+            KeyCode::Char('r') => {
+                if self.external_db_form.is_none() {
+                    let current = ExternalDb::load(&utils::project_root());
+                    self.external_db_form = Some(ExternalDbForm::new(current.as_ref()));
+                }
+                self.state = AppState::ExternalDbSetup;
+            }
+            KeyCode::Up => self.output_scroll = self.output_scroll.saturating_add(1),
+            KeyCode::Down => self.output_scroll = self.output_scroll.saturating_sub(1),
+            KeyCode::PageUp => self.output_scroll = self.output_scroll.saturating_add(10),
+            KeyCode::PageDown => self.output_scroll = self.output_scroll.saturating_sub(10),
+            _ => {}
+        }
+    }
+
+    /// Save the LDAP form to .env; with the stack installed, also test the
+    /// directory and create the federation provider.
+    async fn run_ldap_setup(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        let Some(form) = &self.ldap_form else {
+            return Ok(());
+        };
+        let settings = form.to_settings();
+        self.logs.clear();
+        self.output_scroll = 0;
+        self.state = AppState::LdapApplying;
+
+        let root = utils::project_root();
+        let env_path = root.join(".env");
+        let saved = EnvFile::load(&env_path).and_then(|mut env| {
+            settings.write_env(&mut env);
+            env.save(&env_path)
+        });
+        match saved {
+            Ok(()) => self.add_log(&trf("log.saved_url", &[("url", &settings.url)])),
+            Err(e) => {
+                self.add_log(&trf("log.env_not_updated", &[("error", &e.to_string())]));
+                return Ok(());
+            }
+        }
+        if !InstallManifest::path(&root).is_file() {
+            self.add_log(tr("log.ldap_not_installed"));
+            return Ok(());
+        }
+        if let Err(e) = self.apply_ldap(terminal, &settings).await {
+            self.add_log(&format!("❌ {e}"));
+        }
+        Ok(())
+    }
+
+    /// Have Keycloak test the directory, then create or update the provider.
+    async fn apply_ldap(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        settings: &LdapSettings,
+    ) -> Result<()> {
+        self.add_log(&trf(
+            "log.connecting_target",
+            &[("url", &keycloak_migrate::target_url(self.runner.as_ref()))],
+        ));
+        terminal.draw(|frame| self.render(frame))?;
+        let endpoint = Endpoint::login_target(self.runner.as_ref()).await?;
+        self.add_log(&trf("log.ldap_testing", &[("url", &settings.url)]));
+        terminal.draw(|frame| self.render(frame))?;
+        ldap::test(&endpoint, settings).await?;
+        self.add_log(&trf("log.ldap_bound", &[("bind_dn", &settings.bind_dn)]));
+        let created = ldap::apply(&endpoint, settings).await?;
+        self.add_log(&trf(
+            "log.ldap_done",
+            &[
+                (
+                    "result",
+                    if created {
+                        tr("log.created")
+                    } else {
+                        tr("log.updated")
+                    },
+                ),
+                ("realm", &settings.realm),
+            ],
+        ));
+        Ok(())
+    }
+
+    async fn handle_ldap_output_events(
+        &mut self,
+        event: &Event,
+        terminal: &mut DefaultTerminal,
+    ) -> Result<()> {
+        let Some(key) = key_press(event) else {
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('b') | KeyCode::Char('q') => {
+                self.logs.clear();
+                self.ldap_form = None;
+                self.state = AppState::Confirmation;
+            }
+            KeyCode::Char('r') => self.run_ldap_setup(terminal).await?,
+            KeyCode::Up => self.output_scroll = self.output_scroll.saturating_add(1),
+            KeyCode::Down => self.output_scroll = self.output_scroll.saturating_sub(1),
+            KeyCode::PageUp => self.output_scroll = self.output_scroll.saturating_add(10),
+            KeyCode::PageDown => self.output_scroll = self.output_scroll.saturating_sub(10),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn handle_smtp_output_events(
+        &mut self,
+        event: &Event,
+        terminal: &mut DefaultTerminal,
+    ) -> Result<()> {
+        let Some(key) = key_press(event) else {
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('b') | KeyCode::Char('q') => {
+                self.logs.clear();
+                self.smtp_form = None;
+                self.state = AppState::Confirmation;
+            }
+            KeyCode::Char('r') => self.run_smtp_setup(terminal).await?,
+            KeyCode::Up => self.output_scroll = self.output_scroll.saturating_add(1),
+            KeyCode::Down => self.output_scroll = self.output_scroll.saturating_sub(1),
+            KeyCode::PageUp => self.output_scroll = self.output_scroll.saturating_add(10),
+            KeyCode::PageDown => self.output_scroll = self.output_scroll.saturating_sub(10),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_confirmation_events(&mut self, event: &Event) -> Result<Option<ConfirmationAction>> {
+        let Some(key) = key_press(event) else {
+            return Ok(None);
+        };
+
+        let options = self.menu_options();
+        let current_idx = options
+            .iter()
+            .position(|o| o == &self.menu_selection)
+            .unwrap_or(0);
+
+        match key.code {
+            KeyCode::Up if current_idx > 0 => {
+                self.menu_selection = options[current_idx - 1].clone();
+            }
+            KeyCode::Down if current_idx + 1 < options.len() => {
+                self.menu_selection = options[current_idx + 1].clone();
+            }
+            KeyCode::Enter => {
+                // Whatever the entry does may change the files; read them again.
+                self.artifacts = None;
+                return Ok(Some(ConfirmationAction::Menu(self.menu_selection.clone())));
+            }
+            KeyCode::Esc if self.artifacts.is_some() => self.artifacts = None,
+            KeyCode::Esc => {
+                return Ok(Some(ConfirmationAction::Menu(MenuSelection::Cancel)));
+            }
+            KeyCode::PageUp if self.artifacts.is_some() => {
+                self.artifacts_scroll = self.artifacts_scroll.saturating_sub(10);
+            }
+            KeyCode::PageDown if self.artifacts.is_some() => {
+                self.artifacts_scroll = self.artifacts_scroll.saturating_add(10);
+            }
+            KeyCode::Char(c)
+                if key.modifiers.is_empty() || key.modifiers == KeyModifiers::SHIFT =>
+            {
+                let c = c.to_ascii_lowercase();
+                match self.quick_actions().into_iter().find(|a| a.key() == c) {
+                    // Install is the same flow as the Proceed menu entry.
+                    Some(QuickAction::Install) => {
+                        self.artifacts = None;
+                        return Ok(Some(ConfirmationAction::Menu(MenuSelection::Proceed)));
+                    }
+                    // Only changes what the screen shows.
+                    Some(QuickAction::Files) => {
+                        self.artifacts = match self.artifacts {
+                            Some(_) => None,
+                            None => Some(artifacts::Summary::collect(
+                                &utils::project_root(),
+                                &self.settings,
+                            )),
+                        };
+                        self.artifacts_scroll = 0;
+                    }
+                    Some(action) => {
+                        self.artifacts = None;
+                        return Ok(Some(ConfirmationAction::Quick(action)));
+                    }
+                    None => {}
+                }
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    async fn run_quick_action(
+        &mut self,
+        action: QuickAction,
+        terminal: &mut DefaultTerminal,
+    ) -> Result<()> {
+        self.logs.clear();
+        self.output_scroll = 0;
+
+        if action == QuickAction::UpdateAll {
+            if self.route_expired_token() {
+                return Ok(());
+            }
+            self.state = AppState::UpdatePulling;
+            terminal.draw(|frame| self.render(frame))?;
+            self.update_all(terminal).await?;
+            self.state = AppState::UpdateList;
+            return Ok(());
+        }
+
+        self.state = AppState::ActionOutput(action);
+        self.add_log(&trf("log.running", &[("action", action.label())]));
+        terminal.draw(|frame| self.render(frame))?;
+        self.logs.clear();
+
+        let root = utils::project_root();
+        match action {
+            QuickAction::Logs => {
+                self.capture_compose(&root, &["logs", "--no-color", "--tail", "200"])
+                    .await
+            }
+            QuickAction::Status => self.capture_compose(&root, &["ps", "--all"]).await,
+            QuickAction::Doctor => {
+                let compose_cmd = utils::detect_compose_command(self.runner.as_ref())
+                    .await
+                    .ok();
+                let checks = doctor::run_checks(
+                    self.runner.as_ref(),
+                    &root,
+                    compose_cmd.as_deref(),
+                    self.airgapped,
+                    self.ghcr_token.is_some(),
+                    self.settings.proxy.is_configured() || env_proxy_hint().is_some(),
+                )
+                .await;
+                let failed = checks.iter().filter(|c| !c.ok).count();
+                for check in &checks {
+                    self.add_log(&check.line());
+                }
+                self.add_log("");
+                self.add_log(&if failed == 0 {
+                    tr("log.checks_passed").to_string()
+                } else {
+                    trf("log.checks_failed", &[("failed", &failed.to_string())])
+                });
+            }
+            QuickAction::ExportAnswers => {
+                let answers =
+                    Answers::from_install(&root, self.ghcr_token.is_some() && !self.airgapped);
+                match answers.export(&root) {
+                    Ok(path) => {
+                        self.add_log(&trf(
+                            "log.wrote_file",
+                            &[("path", &path.display().to_string())],
+                        ));
+                        self.add_log("");
+                        for line in fs::read_to_string(&path).unwrap_or_default().lines() {
+                            self.add_log(&format!("   {line}"));
+                        }
+                        self.add_log("");
+                        self.add_log(&trf("log.answers_hint", &[("file", answers::EXPORT_FILE)]));
+                    }
+                    Err(e) => {
+                        self.add_log(&trf("log.answers_failed", &[("error", &e.to_string())]))
+                    }
+                }
+            }
+            QuickAction::DebugLogging => self.toggle_debug_logging(&root).await,
+            QuickAction::Install | QuickAction::UpdateAll | QuickAction::Files => {}
+        }
+        Ok(())
+    }
+
+    /// Switch every service to debug logging, or back to their defaults when
+    /// it is on, and recreate the services whose environment changed.
+    async fn toggle_debug_logging(&mut self, root: &Path) {
+        let level = match self.settings.advanced.log_level {
+            Some(LogLevel::Debug) => None,
+            _ => Some(LogLevel::Debug),
+        };
+        self.settings.advanced.log_level = level;
+        if let Err(e) = self
+            .settings
+            .save()
+            .and_then(|()| App::write_log_level(root, level))
+        {
+            self.add_log(&trf(
+                "log.log_level_not_saved",
+                &[("error", &e.to_string())],
+            ));
+            return;
+        }
+        self.add_log(match level {
+            Some(_) => tr("log.debug_on"),
+            None => tr("log.debug_off"),
+        });
+
+        let compose_cmd = match utils::detect_compose_command(self.runner.as_ref()).await {
+            Ok(cmd) => cmd,
+            Err(e) => {
+                self.add_log(&format!("❌ {e}"));
+                return;
+            }
+        };
+        let mut command = Cmd::compose(&compose_cmd)
+            .args(compose_override::compose_file_args(root))
+            .args(["up", "-d", "--pull", "never"])
+            .dir(root);
+        // The tag of the last install, so this does not move to latest.
+        if let Some(manifest) = InstallManifest::load(root) {
+            command = command.env("IDENTITY_TAG", &manifest.identity_tag);
+        }
+        match self.runner.output(&command).await {
+            Ok(output) => {
+                for line in output.stderr.lines() {
+                    self.add_log(line);
+                }
+                self.add_log(if output.success {
+                    tr("log.services_restarted")
+                } else {
+                    tr("log.restart_failed")
+                });
+            }
+            Err(e) => self.add_log(&trf("log.compose_failed", &[("error", &e.to_string())])),
+        }
+    }
+
+    /// Run a read-only compose subcommand against the stack and show its output.
+    async fn capture_compose(&mut self, root: &Path, args: &[&str]) {
+        let compose_cmd = match utils::detect_compose_command(self.runner.as_ref()).await {
+            Ok(cmd) => cmd,
+            Err(e) => {
+                self.add_log(&format!("❌ {e}"));
+                return;
+            }
+        };
+
+        let command = Cmd::compose(&compose_cmd)
+            .args(compose_override::compose_file_args(root))
+            .args(args)
+            .dir(root);
+        match self.runner.output(&command).await {
+            Ok(output) => {
+                for line in output.stdout.lines().chain(output.stderr.lines()) {
+                    self.add_log(line);
+                }
+                if self.logs.is_empty() {
+                    self.add_log(tr("log.no_output"));
+                }
+            }
+            Err(e) => self.add_log(&trf("log.compose_failed", &[("error", &e.to_string())])),
+        }
+    }
+
+    async fn handle_action_output_events(
+        &mut self,
+        event: &Event,
+        action: QuickAction,
+        terminal: &mut DefaultTerminal,
+    ) -> Result<()> {
+        let Some(key) = key_press(event) else {
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('b') | KeyCode::Char('q') => {
+                self.logs.clear();
+                self.state = AppState::Confirmation;
+            }
+            KeyCode::Char('r') => self.run_quick_action(action, terminal).await?,
+            KeyCode::Up => self.output_scroll = self.output_scroll.saturating_add(1),
+            KeyCode::Down => self.output_scroll = self.output_scroll.saturating_sub(1),
+            KeyCode::PageUp => self.output_scroll = self.output_scroll.saturating_add(10),
+            KeyCode::PageDown => self.output_scroll = self.output_scroll.saturating_sub(10),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Write the boot-time unit for the installed stack and enable it.
+    async fn install_systemd_unit(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        self.logs.clear();
+        self.output_scroll = 0;
+        self.state = AppState::SystemdSetup;
+        self.add_log(&trf(
+            "log.writing",
+            &[("path", &systemd::unit_path().display().to_string())],
+        ));
+        terminal.draw(|frame| self.render(frame))?;
+
+        let root = utils::project_root();
+        let compose_cmd = match utils::detect_compose_command(self.runner.as_ref()).await {
+            Ok(cmd) => cmd,
+            Err(e) => {
+                self.add_log(&format!("❌ {e}"));
+                return Ok(());
+            }
+        };
+        let tag = InstallManifest::load(&root)
+            .map(|manifest| manifest.identity_tag)
+            .unwrap_or_else(|| "latest".to_string());
+        let unit = systemd::render(
+            &root,
+            &compose_cmd,
+            &compose_override::compose_file_args(&root),
+            &tag,
+        );
+        for line in unit.lines() {
+            self.add_log(&format!("   {line}"));
+        }
+
+        match systemd::install(self.runner.as_ref(), &unit, &root).await {
+            Ok(steps) => {
+                for step in steps {
+                    self.add_log(&trf("log.sudo_step", &[("step", &step)]));
+                }
+                self.add_log(&trf("log.systemd_enabled", &[("unit", systemd::UNIT_NAME)]));
+            }
+            Err(e) => {
+                self.add_log(&format!("❌ {e}"));
+                self.add_log(tr("log.run_yourself"));
+                for command in systemd::manual_commands(&root) {
+                    self.add_log(&format!("   {command}"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Drive the offline image setup on every tick: start it, collect its
+    /// progress, and move on to the menu once the images are loaded.
+    fn advance_airgapped_setup(&mut self) {
+        let Some(setup) = self.airgapped_setup.as_mut() else {
+            self.state = AppState::Confirmation;
+            return;
+        };
+        if !setup.is_running() && !setup.done && setup.error.is_none() {
+            setup.start();
+        }
+        let lines = setup.poll();
+        let done = setup.done;
+        // Lines are already in the install log; the setup recorded them.
+        self.logs.extend(lines);
+        if done {
+            self.install_log = install_log::path();
+            self.airgapped_setup = None;
+            self.logs.clear();
+            self.state = AppState::Confirmation;
+            self.ensure_menu_selection();
+        }
+    }
+
+    fn handle_airgapped_setup_events(&mut self, event: &Event) -> Result<()> {
+        let Some(key) = key_press(event) else {
+            return Ok(());
+        };
+        let failed = self
+            .airgapped_setup
+            .as_ref()
+            .is_some_and(|setup| setup.error.is_some());
+        match key.code {
+            KeyCode::Char('q') if failed => self.running = false,
+            KeyCode::Enter if failed => {
+                self.logs.clear();
+                if let Some(setup) = self.airgapped_setup.as_mut() {
+                    setup.start();
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_systemd_output_events(&mut self, event: &Event) -> Result<()> {
+        let Some(key) = key_press(event) else {
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('b') | KeyCode::Char('q') => {
+                self.logs.clear();
+                self.state = AppState::Confirmation;
+            }
+            KeyCode::Up => self.output_scroll = self.output_scroll.saturating_add(1),
+            KeyCode::Down => self.output_scroll = self.output_scroll.saturating_sub(1),
+            KeyCode::PageUp => self.output_scroll = self.output_scroll.saturating_add(10),
+            KeyCode::PageDown => self.output_scroll = self.output_scroll.saturating_sub(10),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// List the commands that open the firewall and wait for confirmation.
+    fn show_firewall_offer(&mut self) {
+        let Some(offer) = &self.firewall_offer else {
+            return;
+        };
+        self.firewall_output = vec![
+            trf(
+                "log.firewall_blocks",
+                &[
+                    ("firewall", offer.firewall.label()),
+                    ("ports", &offer.port_list()),
+                ],
+            ),
+            String::new(),
+            tr("log.commands_will_run").to_string(),
+        ];
+        self.firewall_output.extend(
+            offer
+                .command_lines()
+                .into_iter()
+                .map(|line| format!("   {line}")),
+        );
+        self.firewall_output.push(String::new());
+        self.firewall_output
+            .push(tr("log.firewall_prompt").to_string());
+        self.firewall_ran = false;
+        self.output_scroll = 0;
+        self.state = AppState::FirewallSetup;
+    }
+
+    async fn handle_firewall_events(
+        &mut self,
+        event: &Event,
+        terminal: &mut DefaultTerminal,
+    ) -> Result<()> {
+        let Some(key) = key_press(event) else {
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Char('y') if !self.firewall_ran => {
+                let Some(offer) = self.firewall_offer.clone() else {
+                    return Ok(());
+                };
+                self.firewall_ran = true;
+                self.firewall_output.push(String::new());
+                self.firewall_output
+                    .push(tr("log.running_plain").to_string());
+                terminal.draw(|frame| self.render(frame))?;
+                self.firewall_output.pop();
+                match firewall::open(self.runner.as_ref(), &offer).await {
+                    Ok(steps) => {
+                        for step in steps {
+                            self.firewall_output
+                                .push(trf("log.sudo_step", &[("step", &step)]));
+                        }
+                        self.firewall_output.push(trf(
+                            "log.firewall_opened",
+                            &[
+                                ("firewall", offer.firewall.label()),
+                                ("ports", &offer.port_list()),
+                            ],
+                        ));
+                        self.firewall_offer = None;
+                    }
+                    Err(e) => {
+                        self.firewall_output.push(format!("❌ {e}"));
+                        self.firewall_output
+                            .push(tr("log.run_yourself").to_string());
+                        for line in offer.command_lines() {
+                            self.firewall_output.push(format!("   {line}"));
+                        }
+                    }
+                }
+                for line in self.firewall_output.clone() {
+                    install_log::record(&line);
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('b') | KeyCode::Char('q') => {
+                self.state = AppState::Success;
+            }
+            KeyCode::Up => self.output_scroll = self.output_scroll.saturating_add(1),
+            KeyCode::Down => self.output_scroll = self.output_scroll.saturating_sub(1),
+            KeyCode::PageUp => self.output_scroll = self.output_scroll.saturating_add(10),
+            KeyCode::PageDown => self.output_scroll = self.output_scroll.saturating_sub(10),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Look for an older deployment once at startup and offer to migrate it.
+    /// Unattended (`--answers`) runs leave it alone.
+    async fn check_legacy(&mut self) {
+        let found = if self.answers.is_some() {
+            LegacyInstall::default()
+        } else {
+            legacy::detect(self.runner.as_ref(), &utils::project_root()).await
+        };
+        if !found.is_empty() {
+            self.legacy_resume = std::mem::replace(&mut self.state, AppState::LegacyFound);
+        }
+        self.legacy = Some(found);
+    }
+
+    fn leave_legacy(&mut self) {
+        self.logs.clear();
+        self.env_has_ip = env_template::has_server_ip(&utils::project_root());
+        self.recheck_security = true;
+        self.state = self.legacy_resume.clone();
+        self.ensure_menu_selection();
+    }
+
+    async fn migrate_legacy(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        let Some(found) = self.legacy.clone() else {
+            return Ok(());
+        };
+        self.logs.clear();
+        self.output_scroll = 0;
+        self.state = AppState::LegacyMigrating;
+        match self.run_legacy_migration(terminal, &found).await {
+            Ok(()) => {
+                self.add_log(tr("log.migration_finished"));
+                self.legacy = Some(LegacyInstall::default());
+            }
+            Err(e) => {
+                self.add_log(&format!("❌ {e}"));
+                self.add_log(tr("log.migration_stopped"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Dump, remove containers, copy the volume, rename .env keys. Stops at
+    /// the first failure so the old data is never removed without a copy.
+    async fn run_legacy_migration(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        found: &LegacyInstall,
+    ) -> Result<()> {
+        let runner = self.runner.clone();
+        let mut dumped = None;
+        if let Some(db) = &found.running_db {
+            let dir = self.settings.backups.dir();
+            xdg::create_private_dir(&dir).map_err(|e| {
+                eyre!(trf(
+                    "log.cannot_create",
+                    &[
+                        ("dir", &dir.display().to_string()),
+                        ("error", &e.to_string())
+                    ]
+                ))
+            })?;
+            let path = backup::new_path(&dir);
+            self.add_log(&trf(
+                "log.dumping",
+                &[("db", db), ("path", &path.display().to_string())],
+            ));
+            terminal.draw(|frame| self.render(frame))?;
+            let output = runner.output(&legacy::dump_cmd(db, &path)).await?;
+            if !output.success {
+                let _ = fs::remove_file(&path);
+                return Err(eyre!(trf(
+                    "log.pg_dump_failed",
+                    &[("stderr", output.stderr.trim())]
+                )));
+            }
+            dumped = Some(path);
+        }
+
+        if !found.containers.is_empty() {
+            self.add_log(&trf(
+                "log.removing_containers",
+                &[("containers", &found.containers.join(", "))],
+            ));
+            terminal.draw(|frame| self.render(frame))?;
+            let output = runner
+                .output(&legacy::remove_cmd(&found.containers))
+                .await?;
+            if !output.success {
+                return Err(eyre!(trf(
+                    "log.docker_rm_failed",
+                    &[("stderr", output.stderr.trim())]
+                )));
+            }
+        }
+
+        if let Some(volume) = &found.volume {
+            let version = legacy::data_version(runner.as_ref(), volume).await;
+            if version.as_deref() == Some(legacy::stack_version()) {
+                self.add_log(&trf("log.copying_volume", &[("volume", volume)]));
+                terminal.draw(|frame| self.render(frame))?;
+                legacy::copy_volume(runner.as_ref(), volume)
+                    .await
+                    .map_err(|e| eyre!(e))?;
+            } else {
+                self.add_log(&trf(
+                    "log.volume_version",
+                    &[
+                        ("volume", volume),
+                        ("version", version.as_deref().unwrap_or(tr("log.unknown"))),
+                        ("stack_version", legacy::stack_version()),
+                    ],
+                ));
+                match &dumped {
+                    Some(path) => self.add_log(&trf(
+                        "log.restore_hint",
+                        &[("path", &path.display().to_string())],
+                    )),
+                    None => self.add_log(tr("log.dump_yourself")),
+                }
+            }
+        }
+
+        if !found.env_renames.is_empty() {
+            let path = legacy::migrate_env(&utils::project_root(), &found.env_renames)?;
+            for (old, new) in &found.env_renames {
+                self.add_log(&trf(
+                    "log.env_renamed",
+                    &[
+                        ("old", old),
+                        ("new", new),
+                        ("path", &path.display().to_string()),
+                    ],
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// `pg_dump` into the backup directory, then prune old backups.
+    async fn run_backup(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        self.logs.clear();
+        self.output_scroll = 0;
+        self.state = AppState::Database(DatabaseTask::Backup);
+
+        let dir = self.settings.backups.dir();
+        if let Err(e) = xdg::create_private_dir(&dir) {
+            self.add_log(&trf(
+                "log.cannot_create_icon",
+                &[
+                    ("dir", &dir.display().to_string()),
+                    ("error", &e.to_string()),
+                ],
+            ));
+            return Ok(());
+        }
+        let path = backup::new_path(&dir);
+        self.add_log(&trf(
+            "log.backing_up",
+            &[("path", &path.display().to_string())],
+        ));
+        terminal.draw(|frame| self.render(frame))?;
+
+        let ok = match self.runner.spawn(&backup::dump_cmd(&path)) {
+            Ok(child) => self.stream_database_task(terminal, child).await?,
+            Err(e) => {
+                self.add_log(&trf("log.pg_dump_run_failed", &[("error", &e.to_string())]));
+                false
+            }
+        };
+        if !ok {
+            // A partial dump would be offered for restore later.
+            let _ = fs::remove_file(&path);
+            self.add_log(tr("log.backup_failed"));
+            return Ok(());
+        }
+
+        let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        self.add_log(&trf(
+            "log.backup_written",
+            &[("size", &format!("{:.1}", size as f64 / 1_048_576.0))],
+        ));
+        for removed in backup::prune(&dir, self.settings.backups.keep) {
+            self.add_log(&trf(
+                "log.backup_removed",
+                &[("path", &removed.display().to_string())],
+            ));
+        }
+        Ok(())
+    }
+
+    /// Stop identity, `pg_restore` the chosen archive, and start identity again.
+    async fn run_restore(&mut self, terminal: &mut DefaultTerminal, path: PathBuf) -> Result<()> {
+        self.logs.clear();
+        self.output_scroll = 0;
+        self.state = AppState::Database(DatabaseTask::Restore);
+        self.add_log(&trf(
+            "log.restoring",
+            &[("path", &path.display().to_string())],
+        ));
+        self.add_log(tr("log.stopping_identity"));
+        terminal.draw(|frame| self.render(frame))?;
+
+        if !backup::set_running(self.runner.as_ref(), backup::APP_CONTAINER, false).await {
+            self.add_log(tr("log.stop_failed"));
+        }
+        let ok = match self.runner.spawn(&backup::restore_cmd(&path)) {
+            Ok(child) => self.stream_database_task(terminal, child).await?,
+            Err(e) => {
+                self.add_log(&trf(
+                    "log.pg_restore_run_failed",
+                    &[("error", &e.to_string())],
+                ));
+                false
+            }
+        };
+        self.add_log(tr("log.starting_identity"));
+        terminal.draw(|frame| self.render(frame))?;
+        if !backup::set_running(self.runner.as_ref(), backup::APP_CONTAINER, true).await {
+            self.add_log(tr("log.start_failed"));
+        }
+        self.add_log(if ok {
+            tr("log.restored")
+        } else {
+            tr("log.restore_failed")
+        });
+        Ok(())
+    }
+
+    /// Show a pg_dump / pg_restore child's stderr as it runs; true on success.
+    async fn stream_database_task(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        mut child: Running,
+    ) -> Result<bool> {
+        while let Some(line) = child.next_line().await {
+            self.add_log(&line);
+            terminal.draw(|frame| self.render(frame))?;
+        }
+        Ok(child.wait().await)
+    }
+
+    async fn handle_restore_picker_events(
+        &mut self,
+        event: &Event,
+        terminal: &mut DefaultTerminal,
+    ) -> Result<()> {
+        let Some(key) = key_press(event) else {
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Esc if self.restore_confirm => self.restore_confirm = false,
+            KeyCode::Esc | KeyCode::Char('b') | KeyCode::Char('q') => {
+                self.state = AppState::Confirmation;
+            }
+            KeyCode::Up => {
+                self.backup_selection = self.backup_selection.saturating_sub(1);
+                self.restore_confirm = false;
+            }
+            KeyCode::Down => {
+                self.backup_selection =
+                    (self.backup_selection + 1).min(self.backups.len().saturating_sub(1));
+                self.restore_confirm = false;
+            }
+            KeyCode::Enter if !self.restore_confirm => self.restore_confirm = true,
+            KeyCode::Enter => {
+                if let Some(path) = self.backups.get(self.backup_selection).cloned() {
+                    self.run_restore(terminal, path).await?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn handle_database_output_events(
+        &mut self,
+        event: &Event,
+        task: DatabaseTask,
+        terminal: &mut DefaultTerminal,
+    ) -> Result<()> {
+        let Some(key) = key_press(event) else {
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('b') | KeyCode::Char('q') => {
+                self.logs.clear();
+                self.state = AppState::Confirmation;
+            }
+            KeyCode::Char('r') => match task {
+                DatabaseTask::Backup => self.run_backup(terminal).await?,
+                // Back to the picker rather than silently restoring again.
+                DatabaseTask::Restore => {
+                    self.backups = backup::list(&self.settings.backups.dir());
+                    self.restore_confirm = false;
+                    self.state = AppState::RestorePicker;
+                }
+            },
+            KeyCode::Up => self.output_scroll = self.output_scroll.saturating_add(1),
+            KeyCode::Down => self.output_scroll = self.output_scroll.saturating_sub(1),
+            KeyCode::PageUp => self.output_scroll = self.output_scroll.saturating_add(10),
+            KeyCode::PageDown => self.output_scroll = self.output_scroll.saturating_sub(10),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Re-fetch the update list from scratch.
+    async fn refresh_update_infos(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        self.update_infos.clear();
+        self.update_selection_index = 0;
+        self.update_marked.clear();
+        self.update_message = None;
+        let client = self.http_client();
+        let token = self.ghcr_token.clone();
+        let pinned = self.settings.pinned_tags.clone();
+        let sources = self.settings.update_sources.clone();
+        let format = self.package_format;
+        let runner = self.runner.clone();
+        let task = Task::spawn(
+            &self.events,
+            tr("log.fetching_updates"),
+            move |_| async move {
+                collect_update_infos(
+                    runner.as_ref(),
+                    &client,
+                    token.as_deref(),
+                    &pinned,
+                    &sources,
+                    format,
+                )
+                .await
+            },
+        );
+        let Some(result) = self.wait_for(terminal, task).await? else {
+            self.update_message = Some(tr("log.update_cancelled").to_string());
+            return Ok(());
+        };
+        match result {
+            Ok(infos) => {
+                // Keep the Confirmation badge in step with what was just fetched.
+                let status = UpdateStatus::from_infos(&infos);
+                let _ = status.save(&utils::project_root());
+                self.update_status = Some(status);
+                self.update_infos = infos;
+                self.update_message = None;
+            }
+            Err(e) => {
+                self.update_message = Some(trf("log.error_plain", &[("error", &e.to_string())]));
+            }
+        }
+        Ok(())
+    }
+
+    /// Pull every service image that has an update. The installer itself is
+    /// left out: replacing the running binary mid-batch is not what "update
+    /// all" should mean, and it stays one Enter away in the list.
+    async fn update_all(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        self.refresh_update_infos(terminal).await?;
+        let pending: Vec<usize> = self
+            .update_infos
+            .iter()
+            .enumerate()
+            .filter(|(_, info)| info.has_update && !info.is_self && info.status_note.is_none())
+            .map(|(idx, _)| idx)
+            .collect();
+
+        if pending.is_empty() {
+            self.update_message = Some(tr("log.images_up_to_date").to_string());
+            return Ok(());
+        }
+
+        for idx in pending {
+            self.update_selection_index = idx;
+            if let Err(e) = self.pull_selected_update(terminal).await {
+                self.add_log(&trf("log.error", &[("error", &e.to_string())]));
+            }
+        }
+        self.update_selection_index = 0;
+        Ok(())
+    }
+
+    fn handle_update_list_events(&mut self, event: &Event) -> Result<Option<UpdateListAction>> {
+        let Some(key) = key_press(event) else {
+            return Ok(None);
+        };
+
+        if self.tag_picker.is_some() {
+            self.handle_tag_picker_key(key.code)?;
+            return Ok(None);
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('b') => return Ok(Some(UpdateListAction::Back)),
+            KeyCode::Char('t') => return Ok(Some(UpdateListAction::PickTag)),
+            KeyCode::Char('r') => return Ok(Some(UpdateListAction::Refresh)),
+            KeyCode::Char('u') => return Ok(Some(UpdateListAction::Rollback)),
+            KeyCode::Enter if !self.update_marked.is_empty() => {
+                return Ok(Some(UpdateListAction::PullMarked(false)));
+            }
+            KeyCode::Char('p') if !self.update_marked.is_empty() => {
+                return Ok(Some(UpdateListAction::PullMarked(true)));
+            }
+            // Pull the selected image update
+            KeyCode::Enter if !self.update_infos.is_empty() => {
+                return Ok(Some(UpdateListAction::Pull));
+            }
+            KeyCode::Char(' ') => {
+                let idx = self.update_selection_index;
+                if !self
+                    .update_infos
+                    .get(idx)
+                    .is_some_and(UpdateInfo::batchable)
+                {
+                    self.update_message = Some(tr("log.installer_self_updates").to_string());
+                } else if !self.update_marked.remove(&idx) {
+                    self.update_marked.insert(idx);
+                }
+            }
+            // Mark every pending update, or clear the marks when all are marked
+            KeyCode::Char('a') => {
+                let pending: BTreeSet<usize> = (self.update_infos.iter().enumerate())
+                    .filter(|(_, info)| info.batchable() && info.has_update)
+                    .map(|(idx, _)| idx)
+                    .collect();
+                self.update_marked = if !pending.is_empty() && self.update_marked == pending {
+                    BTreeSet::new()
+                } else {
+                    pending
+                };
+            }
+            KeyCode::Up if self.update_selection_index > 0 => {
+                self.update_selection_index -= 1;
+                self.notes_scroll = 0;
+            }
+            KeyCode::Down if self.update_selection_index + 1 < self.update_infos.len() => {
+                self.update_selection_index += 1;
+                self.notes_scroll = 0;
+            }
+            KeyCode::PageDown => self.notes_scroll = self.notes_scroll.saturating_add(5),
+            KeyCode::PageUp => self.notes_scroll = self.notes_scroll.saturating_sub(5),
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    /// Pull the marked images one after another, or with `parallel` download
+    /// them all at once and then apply them in turn, which only has to
+    /// recreate the containers since the layers are already there.
+    async fn pull_marked_updates(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        parallel: bool,
+    ) -> Result<()> {
+        let marked: Vec<usize> = std::mem::take(&mut self.update_marked)
+            .into_iter()
+            .collect();
+        let total = marked.len();
+        // With `parallel` the downloads are the first half of the work.
+        let steps = if parallel { total * 2 } else { total };
+        let mut done = 0;
+        self.update_progress = Some(0.0);
+
+        if parallel {
+            if let Some(token) = self.ghcr_token.clone() {
+                self.add_log(tr("log.ghcr_login"));
+                if let Err(e) = Registry::ghcr()
+                    .login(self.runner.as_ref(), Some(&token))
+                    .await
+                {
+                    self.add_log(&trf("log.ghcr_login_warning", &[("error", &e.to_string())]));
+                }
+            }
+            let references: Vec<String> = marked
+                .iter()
+                .filter_map(|&idx| self.update_infos.get(idx))
+                .map(|info| format!("{}:{}", info.image, info.target_tag()))
+                .collect();
+            self.add_log(&trf(
+                "log.downloading_parallel",
+                &[("total", &total.to_string())],
+            ));
+            terminal.draw(|frame| self.render(frame))?;
+
+            let runner = self.runner.clone();
+            let at_once = throttle::limits()
+                .pull_parallelism
+                .map_or(total, NonZeroUsize::get);
+            let mut pulls = stream::iter(references.iter().map(|reference| {
+                let runner = runner.clone();
+                async move {
+                    let pull = Cmd::new("docker").arg("pull").arg(reference);
+                    (reference, runner.output(&pull).await)
+                }
+            }))
+            .buffer_unordered(at_once);
+            while let Some((reference, output)) = pulls.next().await {
+                done += 1;
+                match output {
+                    Ok(output) if output.success => {
+                        self.add_log(&trf(
+                            "log.downloaded",
+                            &[
+                                ("done", &done.to_string()),
+                                ("total", &total.to_string()),
+                                ("reference", reference),
+                            ],
+                        ));
+                    }
+                    Ok(output) => self.add_log(&format!(
+                        "❌ [{done}/{total}] {reference}: {}",
+                        output.stderr.trim()
+                    )),
+                    Err(e) => self.add_log(&format!("❌ [{done}/{total}] {reference}: {e}")),
+                }
+                self.update_progress = Some(done as f64 / steps as f64 * 100.0);
+                terminal.draw(|frame| self.render(frame))?;
+            }
+        }
+
+        for (position, idx) in marked.into_iter().enumerate() {
+            self.update_selection_index = idx;
+            self.add_log(&format!("📦 [{}/{total}]", position + 1));
+            terminal.draw(|frame| self.render(frame))?;
+            if let Err(e) = self.pull_selected_update(terminal).await {
+                self.add_log(&trf("log.error", &[("error", &e.to_string())]));
+            }
+            done += 1;
+            self.update_progress = Some(done as f64 / steps as f64 * 100.0);
+            terminal.draw(|frame| self.render(frame))?;
+        }
+        self.update_progress = None;
+        self.add_log(&trf("log.batch_finished", &[("total", &total.to_string())]));
+        Ok(())
+    }
+
+    fn open_tag_picker(&mut self) {
+        let Some(info) = self.update_infos.get(self.update_selection_index) else {
+            return;
+        };
+        if info.is_self || info.service.is_none() {
+            self.update_message = Some(tr("log.installer_not_pinnable").to_string());
+            return;
+        }
+        let tags = info.picker_tags();
+        if tags.is_empty() {
+            self.update_message = Some(tr("log.no_tags").to_string());
+            return;
+        }
+        let selected = info
+            .pinned_tag
+            .as_ref()
+            .and_then(|pin| tags.iter().position(|t| t == pin))
+            .map(|idx| idx + 1)
+            .unwrap_or(0);
+        self.tag_picker = Some(TagPicker { tags, selected });
+    }
+
+    fn handle_tag_picker_key(&mut self, code: KeyCode) -> Result<()> {
+        let Some(picker) = self.tag_picker.as_mut() else {
+            return Ok(());
+        };
+        match code {
+            KeyCode::Up => picker.selected = picker.selected.saturating_sub(1),
+            // tags.len() is the last index because of the leading "follow latest" entry
+            KeyCode::Down if picker.selected < picker.tags.len() => picker.selected += 1,
+            KeyCode::Esc => self.tag_picker = None,
+            KeyCode::Enter => {
+                let choice = match picker.selected {
+                    0 => None,
+                    idx => picker.tags.get(idx - 1).cloned(),
+                };
+                self.tag_picker = None;
+                self.pin_selected_tag(choice)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Pin (or unpin with `None`) the selected service and rewrite the compose override.
+    fn pin_selected_tag(&mut self, tag: Option<String>) -> Result<()> {
+        let Some(info) = self.update_infos.get_mut(self.update_selection_index) else {
+            return Ok(());
+        };
+        let Some(service) = info.service.clone() else {
+            return Ok(());
+        };
+
+        match &tag {
+            Some(tag) => {
+                self.settings.pinned_tags.insert(
+                    service.clone(),
+                    PinnedTag {
+                        image: info.image.clone(),
+                        tag: tag.clone(),
+                    },
+                );
+            }
+            None => {
+                self.settings.pinned_tags.remove(&service);
+            }
+        }
+        info.pinned_tag = tag.clone();
+        // Digests were checked for the previous target tag; re-check on refresh.
+        info.remote_digest = None;
+        info.local_digests.clear();
+        info.recompute_status();
 
-    async fn detect_compose_command(&self) -> Result<Vec<String>> {
-        // Try `docker compose` (plugin, Docker 20.10+)
-        let result = Command::new("docker")
-            .args(["compose", "version"])
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .status()
-            .await;
+        self.settings.save()?;
+        compose_override::sync(&utils::project_root(), &self.settings, self.airgapped)?;
+        match tag {
+            Some(tag) => self.add_log(&trf(
+                "log.pinned",
+                &[
+                    ("service", &service),
+                    ("tag", &tag),
+                    ("file", compose_override::OVERRIDE_FILE),
+                ],
+            )),
+            None => self.add_log(&trf("log.unpinned", &[("service", &service)])),
+        }
+        Ok(())
+    }
+
+    async fn pull_selected_update(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        let Some(info) = self.update_infos.get(self.update_selection_index).cloned() else {
+            return Ok(());
+        };
 
-        if result.map(|s| s.success()).unwrap_or(false) {
-            return Ok(vec!["docker".to_string(), "compose".to_string()]);
+        if info.is_self {
+            return self.self_update(terminal, &info).await;
         }
 
-        // Fallback to standalone docker-compose
-        let result = Command::new("docker-compose")
-            .arg("version")
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .status()
-            .await;
+        // Pinned tag first, then the latest release tag (e.g. "v0.0.1"), then current
+        let tag = info.target_tag();
+        let reference = format!("{}:{}", info.image, tag);
+
+        // Remember what the service runs right now so a bad image can be rolled back.
+        let previous = match &info.container {
+            Some(container) => rollback::running_image_id(self.runner.as_ref(), container).await,
+            None => None,
+        };
 
-        if result.map(|s| s.success()).unwrap_or(false) {
-            return Ok(vec!["docker-compose".to_string()]);
+        self.add_log(&trf("log.pulling", &[("image", &reference)]));
+
+        // Login first if token is available
+        if let Some(token) = self.ghcr_token.clone() {
+            self.add_log(tr("log.ghcr_login"));
+            if let Err(e) = Registry::ghcr()
+                .login(self.runner.as_ref(), Some(&token))
+                .await
+            {
+                self.add_log(&trf("log.ghcr_login_warning", &[("error", &e.to_string())]));
+            }
         }
 
-        Err(eyre!(
-            "Neither 'docker compose' nor 'docker-compose' found.\n\
-             Please install Docker 20.10+ (includes Compose plugin)\n\
-             or install docker-compose separately."
-        ))
+        // Run docker pull and stream output to logs
+        let mut child = self
+            .runner
+            .spawn(&Cmd::new("docker").arg("pull").arg(&reference))?;
+        while let Some(line) = child.next_line().await {
+            self.add_log(&line);
+        }
+
+        if child.wait().await {
+            self.add_log(&trf("log.pulled", &[("image", &reference)]));
+            // Update local_created timestamp in the stored info
+            if let Some(stored) = self.update_infos.get_mut(self.update_selection_index) {
+                use updates::{get_local_image_created, inspect_local_repo_digests};
+                let runner = self.runner.as_ref();
+                stored.local_digests = inspect_local_repo_digests(runner, &info.image, tag).await;
+                if let Ok(created) = get_local_image_created(runner, &info.image, tag).await {
+                    stored.apply_local_created(created);
+                }
+            }
+
+            // Only a running stack gets recreated; otherwise the next install picks it up.
+            if let (Some(previous_image_id), Some(service), Some(container)) =
+                (previous, info.service.clone(), info.container.clone())
+            {
+                let record = RollbackRecord {
+                    service,
+                    container,
+                    image: info.image.clone(),
+                    tag: tag.to_string(),
+                    previous_digest: rollback::image_repo_digest(
+                        self.runner.as_ref(),
+                        &previous_image_id,
+                    )
+                    .await,
+                    previous_image_id,
+                    recorded_at: chrono::Utc::now(),
+                };
+                if let Err(e) = record.save() {
+                    self.add_log(&trf(
+                        "log.rollback_not_recorded",
+                        &[("error", &e.to_string())],
+                    ));
+                }
+                self.apply_service_image(&record, false).await?;
+                self.fire_hooks(
+                    HookEvent::UpdateApplied,
+                    &trf(
+                        "log.service_updated",
+                        &[("service", &record.service), ("image", &reference)],
+                    ),
+                );
+            }
+        } else {
+            self.add_log(&trf("log.pull_failed", &[("image", &reference)]));
+        }
+
+        Ok(())
+    }
+
+    async fn self_update(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        info: &UpdateInfo,
+    ) -> Result<()> {
+        let Some(download_url) = info.download_url.as_deref() else {
+            if let Some(note) = &info.status_note {
+                self.add_log(&format!("❌ {note}"));
+            }
+            self.add_log(tr("log.self_update_manual"));
+            self.add_log("    https://github.com/NexusQuantum/installer-NQRust-Identity/releases");
+            return Ok(());
+        };
+        let Some(checksum_url) = info.checksum_url.as_deref() else {
+            return Err(eyre!(tr("log.no_checksums")));
+        };
+
+        let client = self.http_client();
+        let asset = self_update::asset_name(download_url).to_string();
+        let dir = self_update::download_dir()?;
+        let dest = dir.join(&asset);
+        self.add_log(&trf("log.downloading", &[("asset", &asset)]));
+        let task = {
+            let (download_url, checksum_url) = (download_url.to_string(), checksum_url.to_string());
+            let (asset, dest) = (asset.clone(), dest.clone());
+            Task::spawn(
+                &self.events,
+                trf("log.fetching_checksum", &[("asset", &asset)]),
+                move |progress| async move {
+                    let expected =
+                        self_update::fetch_expected_checksum(&client, &checksum_url, &asset)
+                            .await?;
+                    progress.note(trf("log.downloading", &[("asset", &asset)]));
+                    self_update::download_verified(&client, &download_url, &dest, &expected).await
+                },
+            )
+        };
+        let Some(size) = self.wait_for(terminal, task).await?.transpose()? else {
+            let _ = fs::remove_dir_all(&dir);
+            self.add_log(tr("log.self_update_cancelled"));
+            return Ok(());
+        };
+        self.add_log(&trf(
+            "log.downloaded_verified",
+            &[("size", &format!("{:.1}", size as f64 / 1_048_576.0))],
+        ));
+
+        let format = PackageFormat::from_asset_name(&asset).unwrap_or(self.package_format);
+        self.add_log(tr("log.installing_package"));
+        match self_update::install(self.runner.as_ref(), &dest, format, self.no_root).await {
+            Ok(installed) => {
+                let _ = fs::remove_dir_all(&dir);
+                self.add_log(&trf(
+                    "log.installer_updated",
+                    &[
+                        (
+                            "version",
+                            info.latest_release_tag.as_deref().unwrap_or("latest"),
+                        ),
+                        ("path", &installed.display().to_string()),
+                    ],
+                ));
+                self.fire_hooks(
+                    HookEvent::UpdateApplied,
+                    &format!(
+                        "installer updated to {}",
+                        info.latest_release_tag.as_deref().unwrap_or("latest")
+                    ),
+                );
+                if self.no_root
+                    && let Some(bin_dir) = installed.parent()
+                    && !env::var("PATH")
+                        .unwrap_or_default()
+                        .split(':')
+                        .any(|p| Path::new(p) == bin_dir)
+                {
+                    self.add_log(&trf(
+                        "log.add_to_path",
+                        &[("dir", &bin_dir.display().to_string())],
+                    ));
+                }
+            }
+            Err(e) => {
+                self.add_log(&trf(
+                    "log.auto_install_failed",
+                    &[("error", &e.to_string())],
+                ));
+                self.add_log(&trf(
+                    "log.run_manually",
+                    &[("command", &self_update::manual_command(&dest, format))],
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Recreate the recorded service on `image:tag` and wait for its healthcheck.
+    /// `pull_never` keeps compose from re-pulling when restoring a retagged image.
+    async fn apply_service_image(
+        &mut self,
+        record: &RollbackRecord,
+        pull_never: bool,
+    ) -> Result<()> {
+        let root = utils::project_root();
+        let compose_cmd = utils::detect_compose_command(self.runner.as_ref()).await?;
+
+        self.add_log(&trf("log.recreating", &[("service", &record.service)]));
+        let mut cmd = Cmd::compose(&compose_cmd)
+            .args(compose_override::compose_file_args(&root))
+            .args(["up", "-d", "--no-deps"]);
+        if pull_never {
+            cmd = cmd.args(["--pull", "never"]);
+        }
+        let cmd = cmd
+            .arg(&record.service)
+            .env("IDENTITY_TAG", &record.tag)
+            .dir(&root);
+        let output = self.runner.output(&cmd).await?;
+        for line in output.stderr.lines() {
+            self.add_log(line);
+        }
+        if !output.success {
+            return Err(eyre!(trf(
+                "log.compose_up_service_failed",
+                &[("service", &record.service)]
+            )));
+        }
+
+        self.add_log(&trf(
+            "log.waiting_healthcheck",
+            &[("container", &record.container)],
+        ));
+        match rollback::wait_for_health(
+            self.runner.as_ref(),
+            &record.container,
+            std::time::Duration::from_secs(180),
+        )
+        .await
+        {
+            HealthOutcome::Healthy => {
+                self.add_log(&trf(
+                    "log.service_healthy",
+                    &[("service", &record.container)],
+                ));
+            }
+            HealthOutcome::NoHealthcheck => {
+                self.add_log(&trf(
+                    "log.container_running",
+                    &[("container", &record.container)],
+                ));
+            }
+            outcome @ (HealthOutcome::Unhealthy | HealthOutcome::TimedOut) => {
+                let reason = if outcome == HealthOutcome::Unhealthy {
+                    tr("log.healthcheck_failed")
+                } else {
+                    tr("log.healthcheck_timeout")
+                };
+                self.add_log(&format!("❌ {} {}", record.container, reason));
+                if !pull_never {
+                    let previous = record
+                        .previous_digest
+                        .as_deref()
+                        .unwrap_or(&record.previous_image_id);
+                    self.add_log(&trf("log.press_rollback", &[("previous", previous)]));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn rollback_last_update(&mut self) -> Result<()> {
+        let Some(record) = RollbackRecord::load() else {
+            self.add_log(tr("log.no_previous"));
+            return Ok(());
+        };
+
+        let previous = record
+            .previous_digest
+            .clone()
+            .unwrap_or_else(|| record.previous_image_id.clone());
+        self.add_log(&trf(
+            "log.rolling_back",
+            &[
+                ("service", &record.service),
+                ("previous", &previous),
+                (
+                    "recorded",
+                    &record.recorded_at.format("%Y-%m-%d %H:%M UTC").to_string(),
+                ),
+            ],
+        ));
+        rollback::retag_previous(self.runner.as_ref(), &record).await?;
+        self.apply_service_image(&record, true).await?;
+        RollbackRecord::clear();
+        self.add_log(tr("log.rollback_complete"));
+        Ok(())
     }
 
+    // ─── Docker Compose ────────────────────────────────────────────────────────
+
     async fn run_docker_compose(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
         let root = utils::project_root();
         let compose_file = root.join("docker-compose.yaml");
 
         if !compose_file.exists() {
-            return Err(eyre!("docker-compose.yaml not found in {}", root.display()));
+            return Err(eyre!(trf(
+                "log.compose_not_found",
+                &[("path", &root.display().to_string())]
+            )));
+        }
+
+        self.install_progress = InstallProgress::from_stack();
+        self.current_service.clear();
+
+        // Secret files must exist before the override mounts them.
+        secrets::materialize(
+            self.runner.as_ref(),
+            &root,
+            &self.settings.secrets,
+            &self.http_client(),
+        )
+        .await?;
+        for line in selinux::check(self.runner.as_ref(), &mut self.settings).await {
+            self.add_log(&line);
+        }
+        // Regenerate the override so log caps and pins match the current settings.
+        compose_override::sync(&root, &self.settings, self.airgapped)?;
+        let compose_args = compose_override::compose_file_args(&root);
+        let compose_cmd = utils::detect_compose_command(self.runner.as_ref()).await?;
+        let lint =
+            compose_lint::check(self.runner.as_ref(), &compose_cmd, &compose_args, &root).await;
+        if !lint.is_ok() {
+            self.add_log(tr("log.compose_problems"));
+            for line in lint.lines() {
+                self.add_log(&line);
+            }
+            self.add_log(tr("log.compose_problems_hint"));
+            self.output_scroll = 0;
+            self.state = AppState::ComposeCheck;
+            return Ok(());
+        }
+        for line in lint.lines() {
+            self.add_log(&line);
         }
+        self.install_progress =
+            InstallProgress::load(self.runner.as_ref(), &compose_cmd, &compose_args, &root).await;
+        // Structured events where compose supports them, plain status lines otherwise.
+        let progress_args: Vec<String> =
+            if progress::json_progress_supported(self.runner.as_ref(), &compose_cmd).await {
+                vec!["--progress".to_string(), "json".to_string()]
+            } else {
+                Vec::new()
+            };
 
-        let compose_file_str = compose_file.to_string_lossy().to_string();
-        let compose_cmd = self.detect_compose_command().await?;
+        self.add_log(&format!(
+            "ℹ️  {} {} (git {}, {}, {})",
+            build_info::NAME,
+            build_info::VERSION,
+            build_info::GIT_SHA,
+            build_info::mode(self.airgapped),
+            build_info::platform()
+        ));
 
-        // --- Registry login (if token available) ---
+        // --- Registry logins (where credentials are available) ---
         // Non-fatal: Docker may already be authenticated via credentials helper
-        if let Some(token) = self.ghcr_token.clone() {
-            self.add_log("🔐 Logging into GHCR...");
-            if let Err(e) = self.login_to_ghcr(&token).await {
-                self.add_log(&format!(
-                    "⚠️  GHCR login warning (will try pull anyway): {e}"
-                ));
+        for registry in registry::configured(&self.settings) {
+            if !registry.has_credentials() {
+                continue;
+            }
+            self.add_log(&trf("log.logging_in", &[("host", registry.host())]));
+            if let Err(e) = registry
+                .login(self.runner.as_ref(), registry.credential().as_deref())
+                .await
+            {
+                self.add_log(&trf("log.login_warning", &[("error", &e.to_string())]));
             }
         }
 
         // --- Resolve latest image tag from GitHub Releases ---
-        let identity_tag = if !self.airgapped {
-            let client = Client::new();
-            self.add_log("🔍 Checking latest nqrust-identity release tag...");
-            match fetch_latest_identity_tag(&client, self.ghcr_token.as_deref()).await {
+        let pinned = self
+            .settings
+            .pinned_tags
+            .get("identity")
+            .map(|p| p.tag.clone());
+        let identity_tag = if let Some(tag) = pinned {
+            self.add_log(&trf("log.pinned_tag", &[("tag", &tag)]));
+            tag
+        } else if !self.airgapped {
+            let client = self.http_client();
+            self.add_log(tr("log.checking_release"));
+            match fetch_latest_identity_tag(
+                &client,
+                &self.settings.update_sources,
+                self.ghcr_token.as_deref(),
+            )
+            .await
+            {
                 Some(tag) => {
-                    self.add_log(&format!("✅ Using image tag: {tag}"));
+                    self.add_log(&trf("log.using_tag", &[("tag", &tag)]));
                     tag
                 }
                 None => {
-                    self.add_log("⚠️ Could not resolve latest tag, falling back to 'latest'");
+                    self.add_log(tr("log.latest_fallback"));
                     "latest".to_string()
                 }
             }
@@ -894,147 +4825,807 @@ impl App {
 
         // --- Step 1: Pull images (skip in airgapped mode) ---
         if !self.airgapped {
-            self.add_log("⬇️  Step 1/2: Pulling images...");
-            self.progress = 10.0;
-
-            let mut cmd = Command::new(&compose_cmd[0]);
-            for arg in compose_cmd.iter().skip(1) {
-                cmd.arg(arg);
-            }
-            cmd.args(["-f", &compose_file_str, "pull"])
-                .env("IDENTITY_TAG", &identity_tag)
-                .current_dir(&root)
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped());
-
-            let mut child = cmd.spawn()?;
-
-            // Stream stderr with Ctrl+C support
-            if let Some(stderr) = child.stderr.take() {
-                let mut reader = BufReader::new(stderr).lines();
-                while let Ok(Some(line)) = reader.next_line().await {
-                    self.process_log_line(&line);
-                    let _ = terminal.draw(|frame| self.render(frame));
-                    // Allow Ctrl+C to cancel during streaming
-                    if event::poll(std::time::Duration::ZERO)? {
-                        if let Event::Key(key) = event::read()? {
-                            if key.kind == KeyEventKind::Press
-                                && key.code == KeyCode::Char('c')
-                                && key.modifiers.contains(KeyModifiers::CONTROL)
-                            {
-                                self.running = false;
-                                return Ok(());
-                            }
-                        }
-                    }
-                }
+            if !self.ensure_disk_space(terminal, &identity_tag).await? {
+                self.add_log(tr("log.cancelled_disk"));
+                self.state = AppState::Confirmation;
+                return Ok(());
             }
+            self.add_log(tr("log.step_pull"));
 
-            let status = child.wait().await?;
-            if !status.success() {
-                return Err(eyre!("docker compose pull failed"));
+            let args = [
+                progress_args.as_slice(),
+                &compose_args,
+                &["pull".to_string()],
+            ]
+            .concat();
+            match self
+                .run_compose_step_resilient(terminal, &compose_cmd, &args, &identity_tag)
+                .await?
+            {
+                ComposeStepOutcome::Completed => {}
+                ComposeStepOutcome::Cancelled => {
+                    // Nothing has been started yet, so there is nothing to clean up.
+                    if self
+                        .quit_install(terminal, &compose_cmd, &compose_args, false)
+                        .await
+                    {
+                        return Ok(());
+                    }
+                    self.add_log(tr("log.cancelled_pull"));
+                    self.state = AppState::Confirmation;
+                    return Ok(());
+                }
+                ComposeStepOutcome::Failed { .. } => {
+                    return Err(eyre!(tr("log.compose_pull_failed")));
+                }
             }
-            self.add_log("✅ Images pulled successfully");
-            self.progress = 50.0;
+            self.add_log(tr("log.images_pulled"));
         } else {
-            self.add_log("🔒 Airgapped mode — skipping pull (using local images)");
-            self.progress = 50.0;
+            self.add_log(tr("log.airgapped_skip"));
+        }
+
+        if let Some(db) = ExternalDb::load(&root) {
+            let version = external_db::test(self.runner.as_ref(), &db).await?;
+            self.add_log(&trf(
+                "log.external_db_version",
+                &[("address", &db.address()), ("version", &version)],
+            ));
         }
 
         // --- Step 2: Start services ---
-        self.add_log("🚀 Step 2/2: Starting services...");
+        self.add_log(tr("log.step_start"));
+
+        let args = [
+            progress_args.as_slice(),
+            &compose_args,
+            &compose_override::up_args(&self.settings, self.airgapped),
+        ]
+        .concat();
+        match self
+            .run_compose_step_resilient(terminal, &compose_cmd, &args, &identity_tag)
+            .await?
+        {
+            ComposeStepOutcome::Completed => {}
+            ComposeStepOutcome::Cancelled => {
+                if self
+                    .quit_install(terminal, &compose_cmd, &compose_args, true)
+                    .await
+                {
+                    return Ok(());
+                }
+                let choice = self.prompt_cleanup(terminal).await?;
+                self.cleanup_partial_install(terminal, &compose_cmd, &compose_args, choice)
+                    .await;
+                self.state = AppState::Confirmation;
+                return Ok(());
+            }
+            ComposeStepOutcome::Failed { .. } => return Err(eyre!(tr("log.compose_up_failed"))),
+        }
+        for container in
+            compose_override::remove_skipped(self.runner.as_ref(), &self.settings).await
+        {
+            self.add_log(&trf("log.container_removed", &[("container", container)]));
+        }
+
+        // --- Step 3: Verify every service answers ---
+        if !self.verify_services(terminal).await? {
+            if self
+                .quit_install(terminal, &compose_cmd, &compose_args, true)
+                .await
+            {
+                return Ok(());
+            }
+            let choice = self.prompt_cleanup(terminal).await?;
+            self.cleanup_partial_install(terminal, &compose_cmd, &compose_args, choice)
+                .await;
+            self.state = AppState::Confirmation;
+            return Ok(());
+        }
+
+        let compose_files = compose_args
+            .iter()
+            .filter(|arg| arg.as_str() != "-f")
+            .cloned()
+            .collect();
+        let manifest = InstallManifest::new(self.airgapped, &identity_tag, compose_files);
+        if let Err(e) = manifest.save(&root) {
+            self.add_log(&trf("log.manifest_failed", &[("error", &e.to_string())]));
+        }
 
-        let mut cmd = Command::new(&compose_cmd[0]);
-        for arg in compose_cmd.iter().skip(1) {
-            cmd.arg(arg);
+        self.add_log(tr("log.all_started"));
+        self.connection = ConnectionDetails::load(&root, &self.settings);
+        self.add_log(&trf("log.access", &[("url", &self.connection.url)]));
+        if let Some(line) = smtp::apply_from_env(self.runner.as_ref(), &root).await {
+            self.add_log(&line);
+        }
+        for line in ldap::apply_from_env(self.runner.as_ref(), &root).await {
+            self.add_log(&line);
+        }
+        // With --remote the firewall is on the other host; its commands would
+        // run here. Without the proxy nothing listens on a public port.
+        if self.remote.is_none() && !self.settings.skips_entry() {
+            self.firewall_offer = firewall::detect(self.runner.as_ref(), &root).await;
+        }
+        if let Some(offer) = &self.firewall_offer {
+            self.add_log(&trf(
+                "log.firewall_closed",
+                &[
+                    ("firewall", offer.firewall.label()),
+                    ("ports", &offer.port_list()),
+                ],
+            ));
         }
-        cmd.args(["-f", &compose_file_str, "up", "-d"])
-            .env("IDENTITY_TAG", &identity_tag)
-            .current_dir(&root)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
+        self.state = AppState::Success;
+        self.fire_hooks(
+            HookEvent::InstallSuccess,
+            &format!("identity {identity_tag} started"),
+        );
 
-        let mut child = cmd.spawn()?;
+        Ok(())
+    }
 
-        // Stream stderr with Ctrl+C support
-        if let Some(stderr) = child.stderr.take() {
-            let mut reader = BufReader::new(stderr).lines();
-            while let Ok(Some(line)) = reader.next_line().await {
-                self.process_log_line(&line);
+    /// Probe each stack service until it answers, redrawing between attempts.
+    /// Returns false if the user cancelled; an unhealthy service is an error,
+    /// with its last log lines added to the install log.
+    async fn verify_services(&mut self, terminal: &mut DefaultTerminal) -> Result<bool> {
+        self.add_log(tr("log.step_verify"));
+        for service in external_db::services(&utils::project_root(), &self.settings) {
+            self.current_service = service.service.to_string();
+            let deadline = Instant::now() + verify::VERIFY_TIMEOUT;
+            let mut next_attempt = Instant::now();
+            let result = loop {
+                if Instant::now() >= next_attempt {
+                    match verify::check(self.runner.as_ref(), service).await {
+                        Ok(()) => break Ok(()),
+                        Err(reason) if Instant::now() >= deadline => break Err(reason),
+                        Err(_) => next_attempt = Instant::now() + verify::RETRY_INTERVAL,
+                    }
+                }
                 let _ = terminal.draw(|frame| self.render(frame));
-                // Allow Ctrl+C to cancel during streaming
-                if event::poll(std::time::Duration::ZERO)? {
-                    if let Event::Key(key) = event::read()? {
-                        if key.kind == KeyEventKind::Press
-                            && key.code == KeyCode::Char('c')
-                            && key.modifiers.contains(KeyModifiers::CONTROL)
-                        {
-                            self.running = false;
-                            return Ok(());
+                if self.install_interrupted(terminal).await? {
+                    self.add_log(tr("log.cancelled_verify"));
+                    return Ok(false);
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            };
+
+            let state = if result.is_ok() {
+                ServiceState::Healthy
+            } else {
+                ServiceState::Failed
+            };
+            if let Some(progress) = self
+                .install_progress
+                .services
+                .iter_mut()
+                .find(|s| s.name == service.service)
+            {
+                progress.state = state;
+            }
+            if let Err(reason) = result {
+                self.add_log(&trf(
+                    "log.service_unhealthy",
+                    &[("service", service.service), ("reason", &reason)],
+                ));
+                self.add_log(&trf(
+                    "log.last_log_lines",
+                    &[("container", service.container)],
+                ));
+                for line in verify::last_logs(self.runner.as_ref(), service.container).await {
+                    self.add_log(&format!("   {line}"));
+                }
+                return Err(eyre!(trf(
+                    "log.verification_failed",
+                    &[("service", service.service), ("reason", &reason)]
+                )));
+            }
+            self.add_log(&trf("log.service_healthy", &[("service", service.service)]));
+        }
+        Ok(true)
+    }
+
+    /// Run a compose step, and if the Docker daemon drops out mid-step, wait up
+    /// to the configured grace period for it to come back and re-run the step,
+    /// at most `daemon::MAX_RETRIES` times. Compose pull/up are idempotent, so
+    /// re-running resumes where it left off.
+    async fn run_compose_step_resilient(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        compose_cmd: &[String],
+        args: &[String],
+        identity_tag: &str,
+    ) -> Result<ComposeStepOutcome> {
+        let mut retries = 0;
+        loop {
+            let outcome = self
+                .run_compose_step(terminal, compose_cmd, args, identity_tag)
+                .await?;
+            let ComposeStepOutcome::Failed { daemon_lost } = outcome else {
+                return Ok(outcome);
+            };
+            if !daemon_lost && daemon::daemon_is_up(self.runner.as_ref()).await {
+                return Ok(outcome);
+            }
+            if retries == daemon::MAX_RETRIES {
+                return Err(eyre!(trf(
+                    "log.daemon_gave_up",
+                    &[
+                        ("retries", &retries.to_string()),
+                        ("logs", platform::DockerRuntime::detect().logs_hint())
+                    ]
+                )));
+            }
+            retries += 1;
+
+            let grace = self.settings.daemon_grace_period_secs;
+            self.add_log(&trf("log.daemon_lost", &[("grace", &grace.to_string())]));
+            match self.wait_for_daemon(terminal, grace).await? {
+                Some(true) => {}
+                None => return Ok(ComposeStepOutcome::Cancelled),
+                Some(false) => {
+                    return Err(eyre!(trf(
+                        "log.daemon_gone",
+                        &[
+                            ("grace", &grace.to_string()),
+                            ("start", &platform::docker_start_hint())
+                        ]
+                    )));
+                }
+            }
+            self.add_log(tr("log.daemon_back"));
+        }
+    }
+
+    async fn run_compose_step(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        compose_cmd: &[String],
+        args: &[String],
+        identity_tag: &str,
+    ) -> Result<ComposeStepOutcome> {
+        let root = utils::project_root();
+        let cmd = Cmd::compose(compose_cmd)
+            .args(args)
+            .env("IDENTITY_TAG", identity_tag)
+            .dir(&root);
+        let mut child = self.runner.spawn(&cmd)?;
+        let mut daemon_lost = false;
+
+        let action = args
+            .iter()
+            .find(|arg| matches!(arg.as_str(), "pull" | "up" | "build"))
+            .map_or("", String::as_str);
+        let limit = self.settings.compose_timeouts.for_action(action);
+        let stall = self.settings.compose_timeouts.stall();
+        let mut deadline = limit.map(|limit| Instant::now() + limit);
+        let mut last_output = Instant::now();
+        self.compose_output.clear();
+
+        // Stream output, watching for cancel keys and hangs even while compose is quiet.
+        loop {
+            tokio::select! {
+                line = child.next_line() => match line {
+                    Some(line) => {
+                        last_output = Instant::now();
+                        daemon_lost |= daemon::is_daemon_lost(&line);
+                        self.compose_output.push(line.clone());
+                        self.process_log_line(&line);
+                    }
+                    None => break,
+                },
+                _ = tokio::time::sleep(std::time::Duration::from_millis(100)) => {}
+            }
+
+            if self.stall_prompt.is_none() {
+                let reason = if stall.is_some_and(|stall| last_output.elapsed() >= stall) {
+                    Some(trf(
+                        "log.compose_stalled",
+                        &[
+                            ("action", action),
+                            ("seconds", &last_output.elapsed().as_secs().to_string()),
+                        ],
+                    ))
+                } else if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                    Some(trf(
+                        "log.compose_slow",
+                        &[
+                            ("action", action),
+                            ("seconds", &limit.unwrap_or_default().as_secs().to_string()),
+                        ],
+                    ))
+                } else {
+                    None
+                };
+                if let Some(reason) = reason {
+                    self.add_log(&format!("⚠️  {reason}"));
+                    self.stall_prompt = Some(StallPrompt {
+                        reason,
+                        selected: 0,
+                        show_output: false,
+                    });
+                }
+            }
+
+            while let Some(key) = self.events.try_key() {
+                // Compose keeps running while the quit prompt is open.
+                if self.quit_selection.is_some() {
+                    if let Some(choice) = self.handle_quit_key(key.code) {
+                        self.quit = Some(choice);
+                        if choice != QuitChoice::Wait {
+                            child.kill();
+                            self.stall_prompt = None;
+                            self.add_log(tr("log.stopped_to_quit"));
+                            return Ok(ComposeStepOutcome::Cancelled);
                         }
+                        self.add_log(tr("log.exit_after_install"));
+                    }
+                    continue;
+                }
+                if is_ctrl_c(&key) {
+                    self.quit_selection = Some(0);
+                    continue;
+                }
+                let choice = if self.stall_prompt.is_some() {
+                    self.handle_stall_key(key.code)
+                } else if key.code == KeyCode::Esc {
+                    Some(StallChoice::Abort)
+                } else {
+                    None
+                };
+                match choice {
+                    Some(StallChoice::KeepWaiting) => {
+                        self.stall_prompt = None;
+                        last_output = Instant::now();
+                        deadline = limit.map(|limit| Instant::now() + limit);
+                        self.add_log(tr("log.still_waiting"));
                     }
+                    Some(StallChoice::Abort) => {
+                        // Stop compose and its plugin process now rather than
+                        // letting them finish in the background.
+                        child.kill();
+                        let stalled = self.stall_prompt.take().is_some();
+                        self.add_log(if stalled {
+                            tr("log.aborted_stalled")
+                        } else {
+                            tr("log.cancelled")
+                        });
+                        return Ok(ComposeStepOutcome::Cancelled);
+                    }
+                    Some(StallChoice::ViewOutput) | None => {}
                 }
             }
+            let _ = terminal.draw(|frame| self.render(frame));
         }
 
-        let status = child.wait().await?;
-        if !status.success() {
-            return Err(eyre!("docker compose up failed"));
+        if child.wait().await {
+            Ok(ComposeStepOutcome::Completed)
+        } else {
+            Ok(ComposeStepOutcome::Failed { daemon_lost })
         }
+    }
 
-        self.add_log("✅ All services started!");
-        self.add_log("ℹ️  Keycloak warms up in ~30-60s. Access: https://localhost:8008");
-        self.progress = 100.0;
-        self.completed_services = self.total_services;
-        self.state = AppState::Success;
+    /// Navigate the quit prompt; closes it and returns the choice once made.
+    fn handle_quit_key(&mut self, code: KeyCode) -> Option<QuitChoice> {
+        let selected = self.quit_selection?;
+        let choice = match code {
+            KeyCode::Up => {
+                self.quit_selection = Some(selected.saturating_sub(1));
+                return None;
+            }
+            KeyCode::Down => {
+                self.quit_selection = Some((selected + 1).min(QuitChoice::ALL.len() - 1));
+                return None;
+            }
+            KeyCode::Enter => QuitChoice::ALL[selected],
+            KeyCode::Esc => QuitChoice::Wait,
+            _ => return None,
+        };
+        self.quit_selection = None;
+        Some(choice)
+    }
 
-        Ok(())
+    /// Keys pressed while a step other than compose runs (verification,
+    /// waiting for the daemon). True when the step should stop: Esc, or
+    /// Ctrl+C answered with anything but waiting.
+    async fn install_interrupted(&mut self, terminal: &mut DefaultTerminal) -> Result<bool> {
+        match self.events.interrupt() {
+            Some(Interrupt::Cancel) => Ok(true),
+            Some(Interrupt::Quit) => {
+                self.quit_selection = Some(0);
+                let choice = loop {
+                    terminal.draw(|frame| self.render(frame))?;
+                    let AppEvent::Input(event) = self.events.next().await else {
+                        continue;
+                    };
+                    if let Some(key) = key_press(&event)
+                        && let Some(choice) = self.handle_quit_key(key.code)
+                    {
+                        break choice;
+                    }
+                };
+                self.quit = Some(choice);
+                if choice == QuitChoice::Wait {
+                    self.add_log(tr("log.exit_after_install"));
+                }
+                Ok(choice != QuitChoice::Wait)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Finish quitting after Ctrl+C stopped the install: stop what compose
+    /// started if asked to, then leave the app. False when the step was
+    /// cancelled with Esc instead.
+    async fn quit_install(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        compose_cmd: &[String],
+        compose_args: &[String],
+        started: bool,
+    ) -> bool {
+        let choice = match self.quit {
+            Some(choice @ (QuitChoice::StopAndExit | QuitChoice::Detach)) => choice,
+            _ => return false,
+        };
+        let root = utils::project_root();
+        let message = if !started {
+            tr("log.stopped_pull").to_string()
+        } else if choice == QuitChoice::StopAndExit {
+            self.cleanup_partial_install(
+                terminal,
+                compose_cmd,
+                compose_args,
+                CleanupChoice::StopServices,
+            )
+            .await;
+            trf(
+                "log.stopped_services",
+                &[("path", &root.display().to_string())],
+            )
+        } else {
+            trf("log.detached", &[("path", &root.display().to_string())])
+        };
+        self.add_log(&format!("ℹ️  {message}"));
+        self.exit_message = Some(message);
+        self.running = false;
+        true
+    }
+
+    /// Navigate the stall prompt; returns a choice that needs the caller
+    /// (viewing output is handled here).
+    fn handle_stall_key(&mut self, code: KeyCode) -> Option<StallChoice> {
+        let prompt = self.stall_prompt.as_mut()?;
+        if prompt.show_output {
+            if matches!(code, KeyCode::Esc | KeyCode::Enter) {
+                prompt.show_output = false;
+            }
+            return None;
+        }
+        match code {
+            KeyCode::Up => prompt.selected = prompt.selected.saturating_sub(1),
+            KeyCode::Down => {
+                prompt.selected = (prompt.selected + 1).min(StallChoice::ALL.len() - 1)
+            }
+            KeyCode::Esc => return Some(StallChoice::KeepWaiting),
+            KeyCode::Enter => match StallChoice::ALL[prompt.selected] {
+                StallChoice::ViewOutput => prompt.show_output = true,
+                choice => return Some(choice),
+            },
+            _ => {}
+        }
+        None
     }
 
-    async fn login_to_ghcr(&self, token: &str) -> Result<()> {
-        let mut child = Command::new("docker")
-            .args(["login", "ghcr.io", "-u", "token", "--password-stdin"])
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()?;
+    /// Compare the estimated pull size with the free space under the Docker
+    /// root, offering a prune while it does not fit. False if the user cancels.
+    async fn ensure_disk_space(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        identity_tag: &str,
+    ) -> Result<bool> {
+        loop {
+            self.add_log(tr("log.estimating_disk"));
+            terminal.draw(|frame| self.render(frame))?;
+            let requirement = disk::pull_requirement(
+                self.runner.as_ref(),
+                &self.http_client(),
+                identity_tag,
+                self.ghcr_token.as_deref(),
+            )
+            .await;
+            self.add_log(&format!("   {}", requirement.line()));
+            if !requirement.is_short() {
+                return Ok(true);
+            }
 
-        if let Some(mut stdin) = child.stdin.take() {
-            use tokio::io::AsyncWriteExt;
-            stdin.write_all(token.as_bytes()).await?;
+            match self.prompt_disk_choice(terminal).await? {
+                DiskChoice::Prune => {
+                    self.add_log(tr("log.pruning"));
+                    terminal.draw(|frame| self.render(frame))?;
+                    match disk::prune(self.runner.as_ref()).await {
+                        Ok(summary) => {
+                            for line in summary.lines() {
+                                self.add_log(&format!("   {line}"));
+                            }
+                        }
+                        Err(e) => self.add_log(&trf("log.prune_failed", &[("error", &e)])),
+                    }
+                }
+                DiskChoice::ContinueAnyway => {
+                    self.add_log(tr("log.low_disk"));
+                    return Ok(true);
+                }
+                DiskChoice::Cancel => return Ok(false),
+            }
         }
+    }
+
+    async fn prompt_disk_choice(&mut self, terminal: &mut DefaultTerminal) -> Result<DiskChoice> {
+        self.disk_selection = Some(0);
+        let choice = loop {
+            terminal.draw(|frame| self.render(frame))?;
+            let AppEvent::Input(event) = self.events.next().await else {
+                continue;
+            };
+            let Some(key) = key_press(&event) else {
+                continue;
+            };
+            let selected = self.disk_selection.unwrap_or(0);
+            match key.code {
+                KeyCode::Up if selected > 0 => self.disk_selection = Some(selected - 1),
+                KeyCode::Down if selected + 1 < DiskChoice::ALL.len() => {
+                    self.disk_selection = Some(selected + 1);
+                }
+                KeyCode::Enter => break DiskChoice::ALL[selected],
+                KeyCode::Esc => break DiskChoice::Cancel,
+                _ => {}
+            }
+        };
+        self.disk_selection = None;
+        Ok(choice)
+    }
 
-        let output = child.wait_with_output().await?;
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(eyre!("GHCR login failed: {}", stderr.trim()));
+    /// Ask what to do with the containers `compose up` managed to start.
+    async fn prompt_cleanup(&mut self, terminal: &mut DefaultTerminal) -> Result<CleanupChoice> {
+        self.cleanup_selection = Some(0);
+        let choice = loop {
+            terminal.draw(|frame| self.render(frame))?;
+            let AppEvent::Input(event) = self.events.next().await else {
+                continue;
+            };
+            let Some(key) = key_press(&event) else {
+                continue;
+            };
+            let selected = self.cleanup_selection.unwrap_or(0);
+            match key.code {
+                KeyCode::Up if selected > 0 => self.cleanup_selection = Some(selected - 1),
+                KeyCode::Down if selected + 1 < CleanupChoice::ALL.len() => {
+                    self.cleanup_selection = Some(selected + 1);
+                }
+                KeyCode::Enter => break CleanupChoice::ALL[selected],
+                KeyCode::Esc => break CleanupChoice::LeaveAsIs,
+                _ => {}
+            }
+        };
+        self.cleanup_selection = None;
+        Ok(choice)
+    }
+
+    /// Apply the chosen cleanup, bounded by `CLEANUP_TIMEOUT` so a wedged
+    /// daemon cannot keep the user from getting back to the menu.
+    async fn cleanup_partial_install(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        compose_cmd: &[String],
+        compose_args: &[String],
+        choice: CleanupChoice,
+    ) {
+        let action: &[&str] = match choice {
+            CleanupChoice::LeaveAsIs => {
+                self.add_log(tr("log.leaving_services"));
+                return;
+            }
+            CleanupChoice::Down => &["down", "--remove-orphans"],
+            CleanupChoice::StopServices => &["stop"],
+            CleanupChoice::DownVolumes => &["down", "-v", "--remove-orphans"],
+        };
+
+        self.add_log(&trf(
+            "log.running_compose",
+            &[("action", &action.join(" "))],
+        ));
+        let _ = terminal.draw(|frame| self.render(frame));
+
+        let root = utils::project_root();
+        let cmd = Cmd::compose(compose_cmd)
+            .args(compose_args)
+            .args(action)
+            .dir(&root);
+
+        match tokio::time::timeout(CLEANUP_TIMEOUT, self.runner.output(&cmd)).await {
+            Ok(Ok(output)) if output.success => {
+                self.add_log(tr("log.cleanup_finished"));
+            }
+            Ok(Ok(output)) => {
+                self.add_log(&trf(
+                    "log.cleanup_failed_stderr",
+                    &[("stderr", output.stderr.trim())],
+                ));
+            }
+            Ok(Err(e)) => self.add_log(&trf("log.cleanup_failed", &[("error", &e.to_string())])),
+            Err(_) => self.add_log(&trf(
+                "log.cleanup_timeout",
+                &[("seconds", &CLEANUP_TIMEOUT.as_secs().to_string())],
+            )),
         }
-        Ok(())
+    }
+
+    /// Poll `docker info` every two seconds until the daemon answers or the
+    /// grace period runs out. Keeps redrawing so the user sees the countdown.
+    /// `None` when the user cancelled.
+    async fn wait_for_daemon(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        grace_secs: u64,
+    ) -> Result<Option<bool>> {
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(grace_secs);
+        while std::time::Instant::now() < deadline {
+            if daemon::daemon_is_up(self.runner.as_ref()).await {
+                return Ok(Some(true));
+            }
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            self.current_service = trf(
+                "log.waiting_daemon",
+                &[("seconds", &remaining.as_secs().to_string())],
+            );
+            let _ = terminal.draw(|frame| self.render(frame));
+            for _ in 0..20 {
+                if self.install_interrupted(terminal).await? {
+                    return Ok(None);
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            }
+        }
+        Ok(Some(daemon::daemon_is_up(self.runner.as_ref()).await))
     }
 
     fn process_log_line(&mut self, line: &str) {
+        if let Some(event) = ComposeEvent::parse(line) {
+            self.process_compose_event(&event);
+            return;
+        }
         self.add_log(line);
 
-        // Track service start events for progress
-        let service_name = self.extract_service_name(line);
-        if let Some(name) = service_name {
-            if line.contains("Started") || line.contains("Running") || line.contains("Created") {
-                self.current_service = name;
-                self.completed_services = (self.completed_services + 1).min(self.total_services);
-                self.progress =
-                    (self.completed_services as f64 / self.total_services as f64) * 50.0 + 50.0;
-            }
+        if let Some(service) = self.install_progress.update(line) {
+            self.current_service = format!("{} {}", service.name, service.state.display_label());
         }
     }
 
-    fn extract_service_name(&self, line: &str) -> Option<String> {
-        // Matches lines like: " ✔ Container identity-db  Started"
-        let service_names = ["identity-db", "identity-caddy", "identity"];
-        for name in &service_names {
-            if line.contains(name) {
-                return Some(name.to_string());
+    /// Log state changes and errors only; layer download ticks just move the
+    /// progress table.
+    fn process_compose_event(&mut self, event: &ComposeEvent) {
+        if event.tail {
+            self.add_log(&event.text);
+            return;
+        }
+        let Some(service) = self.install_progress.apply(event) else {
+            if event.is_error() {
+                self.add_log(&format!("❌ {}: {}", event.id, event.status));
             }
+            return;
+        };
+        self.current_service = format!("{} {}", service.name, service.state.display_label());
+        if event.is_error() {
+            self.add_log(&format!("❌ {}: {}", event.id, event.status));
+        } else {
+            self.add_log(&format!(" ✔ {} {}", event.id, event.text));
         }
-        None
+    }
+}
+
+/// Proxy variables already set in the environment, for the proxy form hint.
+fn env_proxy_hint() -> Option<String> {
+    let found: Vec<String> = ["HTTPS_PROXY", "HTTP_PROXY", "NO_PROXY"]
+        .iter()
+        .filter_map(|name| {
+            env::var(name)
+                .or_else(|_| env::var(name.to_ascii_lowercase()))
+                .ok()
+                .map(|value| format!("{name}={value}"))
+        })
+        .collect();
+    (!found.is_empty()).then(|| found.join("  "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::{CmdOutput, MockRunner};
+
+    fn test_app(runner: &Arc<MockRunner>) -> App {
+        // A bundle directory spares scanning the test binary for a payload.
+        let cli = Cli {
+            no_root: true,
+            payload_dir: Some(PathBuf::from("bundle")),
+            ..Cli::default()
+        };
+        App::with_runner(&cli, runner.clone())
+    }
+
+    #[tokio::test]
+    async fn test_compose_detection_falls_back_to_standalone() {
+        let runner = MockRunner::new().on("docker compose version", CmdOutput::failed(""));
+        assert_eq!(
+            utils::detect_compose_command(&runner).await.unwrap(),
+            vec!["docker-compose".to_string()]
+        );
+
+        let runner = runner.on("docker-compose version", CmdOutput::failed(""));
+        assert!(utils::detect_compose_command(&runner).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rollback_recreates_without_pull_and_waits_for_health() {
+        let runner = Arc::new(
+            MockRunner::new()
+                .on(
+                    "up -d --no-deps",
+                    CmdOutput::failed("Container identity  Recreated"),
+                )
+                .on("docker inspect", CmdOutput::ok("healthy\n")),
+        );
+        let mut app = test_app(&runner);
+        let record = RollbackRecord {
+            service: "identity".to_string(),
+            container: "identity".to_string(),
+            image: "ghcr.io/nexusquantum/nqrust-identity".to_string(),
+            tag: "v1.2.0".to_string(),
+            previous_image_id: "sha256:abc".to_string(),
+            previous_digest: None,
+            recorded_at: chrono::Utc::now(),
+        };
+
+        let err = app.apply_service_image(&record, true).await.unwrap_err();
+        assert_eq!(err.to_string(), "docker compose up identity failed");
+        assert!(
+            app.logs
+                .iter()
+                .any(|l| l == "Container identity  Recreated")
+        );
+
+        let runner = Arc::new(MockRunner::new().on("docker inspect", CmdOutput::ok("healthy\n")));
+        let mut app = test_app(&runner);
+        app.apply_service_image(&record, true).await.unwrap();
+        let up = runner
+            .calls()
+            .into_iter()
+            .find(|cmd| cmd.args.contains(&"up".to_string()))
+            .unwrap();
+        assert!(up.line().ends_with("up -d --no-deps --pull never identity"));
+        assert_eq!(
+            up.env,
+            vec![("IDENTITY_TAG".to_string(), "v1.2.0".to_string())]
+        );
+        assert!(app.logs.iter().any(|l| l == "✅ identity is healthy"));
+    }
+
+    #[test]
+    fn test_quit_prompt_waits_unless_told_otherwise() {
+        let mut app = test_app(&Arc::new(MockRunner::new()));
+        assert_eq!(app.handle_quit_key(KeyCode::Enter), None);
+
+        app.quit_selection = Some(0);
+        assert_eq!(app.handle_quit_key(KeyCode::Char('y')), None);
+        assert_eq!(app.handle_quit_key(KeyCode::Esc), Some(QuitChoice::Wait));
+        assert_eq!(app.quit_selection, None);
+
+        app.quit_selection = Some(0);
+        for _ in 0..5 {
+            app.handle_quit_key(KeyCode::Down);
+        }
+        assert_eq!(
+            app.handle_quit_key(KeyCode::Enter),
+            Some(QuitChoice::Detach)
+        );
     }
 }
@@ -0,0 +1,182 @@
+// oidc_client.rs - registering downstream applications as OIDC clients
+// Hooking the analytics UI or another service up to Keycloak needs a
+// confidential client with the app's redirect URIs, and its secret. The
+// installer creates it through the admin API (or updates the redirect URIs
+// and scopes of an existing client of that ID), reads the generated secret
+// and exports everything the app needs to `clients/<client-id>.env` in the
+// project directory, readable only by its owner.
+
+use std::path::{Path, PathBuf};
+
+use color_eyre::{Result, eyre::eyre};
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+
+use crate::keycloak_migrate::Endpoint;
+use crate::{registry, xdg};
+
+pub const CLIENTS_DIR: &str = "clients";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientRequest {
+    pub realm: String,
+    pub client_id: String,
+    pub redirect_uris: Vec<String>,
+    /// Client scopes added to the token by default, e.g. `profile email`;
+    /// empty for the realm's defaults.
+    pub scopes: Vec<String>,
+}
+
+impl ClientRequest {
+    fn representation(&self) -> Value {
+        let mut rep = json!({
+            "clientId": self.client_id,
+            "protocol": "openid-connect",
+            "publicClient": false,
+            "clientAuthenticatorType": "client-secret",
+            "standardFlowEnabled": true,
+            "directAccessGrantsEnabled": false,
+            "redirectUris": self.redirect_uris,
+            // "+" allows CORS from the redirect URIs' origins.
+            "webOrigins": ["+"],
+        });
+        if !self.scopes.is_empty() {
+            rep["defaultClientScopes"] = json!(self.scopes);
+        }
+        rep
+    }
+}
+
+/// A registered client and what the application needs to use it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegisteredClient {
+    pub client_id: String,
+    pub secret: String,
+    /// `https://SERVER_IP:8008/realms/<realm>`
+    pub issuer: String,
+    /// False when a client of that ID existed and was updated.
+    pub created: bool,
+}
+
+impl RegisteredClient {
+    /// The exported file: the values most OIDC libraries ask for.
+    pub fn env(&self, redirect_uris: &[String]) -> String {
+        format!(
+            "# OIDC client registered by nqrust-identity\n\
+             OIDC_ISSUER={}\n\
+             OIDC_DISCOVERY_URL={}/.well-known/openid-configuration\n\
+             OIDC_CLIENT_ID={}\n\
+             OIDC_CLIENT_SECRET={}\n\
+             OIDC_REDIRECT_URIS={}\n",
+            self.issuer,
+            self.issuer,
+            self.client_id,
+            self.secret,
+            redirect_uris.join(",")
+        )
+    }
+}
+
+/// Create the client, or update the one with the same client ID, and read
+/// its secret. `base_url` is the stack's public address for the issuer.
+pub async fn register(
+    endpoint: &Endpoint,
+    request: &ClientRequest,
+    base_url: &str,
+) -> Result<RegisteredClient> {
+    let realm = &request.realm;
+    let rep = request.representation();
+    let (status, response) = endpoint
+        .post(&format!("/realms/{realm}/clients"), &rep)
+        .await?;
+    let created = match status {
+        s if s.is_success() => true,
+        StatusCode::CONFLICT => false,
+        _ => {
+            return Err(eyre!(
+                "Creating client {} in realm {realm} failed (HTTP {status}): {response}",
+                request.client_id
+            ));
+        }
+    };
+    let id = find(endpoint, realm, &request.client_id).await?;
+    if !created {
+        let (status, response) = endpoint
+            .put(&format!("/realms/{realm}/clients/{id}"), &rep)
+            .await?;
+        if !status.is_success() {
+            return Err(eyre!(
+                "Updating client {} failed (HTTP {status}): {response}",
+                request.client_id
+            ));
+        }
+    }
+    let secret = endpoint
+        .get(&format!("/realms/{realm}/clients/{id}/client-secret"))
+        .await?["value"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| eyre!("Keycloak returned no secret for {}", request.client_id))?;
+    Ok(RegisteredClient {
+        client_id: request.client_id.clone(),
+        secret,
+        issuer: format!("{}/realms/{realm}", base_url.trim_end_matches('/')),
+        created,
+    })
+}
+
+/// Internal id of the client with `client_id`.
+async fn find(endpoint: &Endpoint, realm: &str, client_id: &str) -> Result<String> {
+    let found = endpoint
+        .get(&format!("/realms/{realm}/clients?clientId={client_id}"))
+        .await?;
+    found
+        .as_array()
+        .and_then(|clients| clients.first())
+        .and_then(|client| client["id"].as_str())
+        .map(str::to_string)
+        .ok_or_else(|| eyre!("Client {client_id} not found in realm {realm}"))
+}
+
+/// Write the client's env file under `root`; returns its path.
+pub fn export(root: &Path, client: &RegisteredClient, redirect_uris: &[String]) -> Result<PathBuf> {
+    let dir = root.join(CLIENTS_DIR);
+    xdg::create_private_dir(&dir)?;
+    let path = dir.join(format!("{}.env", client.client_id));
+    registry::write_private(&path, &client.env(redirect_uris))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_representation_and_export() {
+        let request = ClientRequest {
+            realm: "corp".into(),
+            client_id: "analytics-ui".into(),
+            redirect_uris: vec!["https://analytics.corp.local/*".into()],
+            scopes: vec!["profile".into(), "email".into()],
+        };
+        let rep = request.representation();
+        assert_eq!(rep["publicClient"], false);
+        assert_eq!(rep["redirectUris"][0], "https://analytics.corp.local/*");
+        assert_eq!(rep["defaultClientScopes"][1], "email");
+
+        let client = RegisteredClient {
+            client_id: "analytics-ui".into(),
+            secret: "s3cret".into(),
+            issuer: "https://10.0.0.5:8008/realms/corp".into(),
+            created: true,
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let path = export(dir.path(), &client, &request.redirect_uris).unwrap();
+        assert_eq!(path, dir.path().join("clients/analytics-ui.env"));
+        let env = std::fs::read_to_string(&path).unwrap();
+        assert!(env.contains("OIDC_CLIENT_SECRET=s3cret\n"));
+        assert!(env.contains(
+            "OIDC_DISCOVERY_URL=https://10.0.0.5:8008/realms/corp/.well-known/openid-configuration\n"
+        ));
+    }
+}
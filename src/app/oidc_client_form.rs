@@ -0,0 +1,82 @@
+use crate::app::form::{Field, Form};
+use crate::app::oidc_client::ClientRequest;
+use crate::i18n::{tr, trf};
+
+/// Application to register as a confidential OIDC client.
+#[derive(Debug)]
+pub struct OidcClientForm {
+    /// Realm, client ID, redirect URIs, default scopes.
+    pub form: Form,
+}
+
+impl Default for OidcClientForm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OidcClientForm {
+    pub fn new() -> Self {
+        Self {
+            form: Form::new(vec![
+                Field::new(tr("oidc_client.realm"), "master").validator(|realm| {
+                    if realm.is_empty() {
+                        Err(tr("oidc_client.realm_required").to_string())
+                    } else {
+                        Ok(())
+                    }
+                }),
+                Field::new(tr("oidc_client.client_id"), "")
+                    .placeholder(tr("oidc_client.client_id_placeholder"))
+                    .validator(|id| {
+                        let valid = !id.is_empty()
+                            && id
+                                .chars()
+                                .all(|c| c.is_ascii_alphanumeric() || "._-".contains(c));
+                        if valid {
+                            Ok(())
+                        } else {
+                            Err(tr("oidc_client.client_id_invalid").to_string())
+                        }
+                    }),
+                Field::new(tr("oidc_client.redirect_uris"), "")
+                    .placeholder(tr("oidc_client.redirect_uris_placeholder"))
+                    .validator(|uris| {
+                        let uris = split(uris);
+                        if uris.is_empty() {
+                            return Err(tr("oidc_client.redirect_uris_required").to_string());
+                        }
+                        match uris.iter().find(|uri| !uri.contains("://")) {
+                            Some(uri) => Err(trf("oidc_client.uri_invalid", &[("uri", uri)])),
+                            None => Ok(()),
+                        }
+                    }),
+                Field::new(tr("oidc_client.scopes"), "")
+                    .placeholder(tr("oidc_client.scopes_placeholder")),
+            ])
+            .buttons(tr("form.register"), tr("form.cancel")),
+        }
+    }
+
+    pub fn to_request(&self) -> ClientRequest {
+        ClientRequest {
+            realm: self.form.value(0).to_string(),
+            client_id: self.form.value(1).to_string(),
+            redirect_uris: split(self.form.value(2)),
+            scopes: split(self.form.value(3)),
+        }
+    }
+
+    pub fn validate(&mut self) -> bool {
+        self.form.validate()
+    }
+}
+
+/// Words separated by commas or whitespace.
+fn split(value: &str) -> Vec<String> {
+    value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
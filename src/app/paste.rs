@@ -0,0 +1,43 @@
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+
+use crate::i18n::tr;
+
+/// Text to insert into the focused field when `event` is a paste: a
+/// bracketed-paste event from the terminal, or Ctrl+V read from the system
+/// clipboard. `None` for any other event.
+pub fn text(event: &Event) -> Option<Result<String, String>> {
+    match event {
+        Event::Paste(text) => Some(Ok(single_line(text))),
+        Event::Key(key)
+            if key.kind == KeyEventKind::Press
+                && key.code == KeyCode::Char('v')
+                && key.modifiers.contains(KeyModifiers::CONTROL) =>
+        {
+            Some(clipboard().map(|text| single_line(&text)))
+        }
+        _ => None,
+    }
+}
+
+fn clipboard() -> Result<String, String> {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .map_err(|_| tr("form.no_clipboard").to_string())
+}
+
+/// Fields are single-line: drop the trailing newline copied along with a
+/// token and any other line breaks or control characters.
+fn single_line(text: &str) -> String {
+    text.trim().chars().filter(|c| !c.is_control()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paste_is_single_line() {
+        let pasted = text(&Event::Paste("ghp_abc\r\ndef\n".to_string()));
+        assert_eq!(pasted, Some(Ok("ghp_abcdef".to_string())));
+    }
+}
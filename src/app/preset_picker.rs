@@ -0,0 +1,46 @@
+// preset_picker.rs - choosing a resource limit preset for the Docker host
+
+use crate::db_tuning::Host;
+use crate::sizing::Preset;
+
+#[derive(Debug, Clone)]
+pub struct PresetPicker {
+    /// `None` when `docker info` could not be read; nothing is recommended.
+    pub host: Option<Host>,
+    pub selected: usize,
+    /// Outcome of the last apply: what changed or the error.
+    pub status: Option<Result<String, String>>,
+}
+
+impl PresetPicker {
+    /// Starts on the preset recommended for `host`.
+    pub fn new(host: Option<Host>) -> Self {
+        let selected = host
+            .map(Preset::recommended)
+            .and_then(|preset| Preset::ALL.iter().position(|p| *p == preset))
+            .unwrap_or_default();
+        Self {
+            host,
+            selected,
+            status: None,
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < Preset::ALL.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn highlighted(&self) -> Preset {
+        Preset::ALL[self.selected]
+    }
+
+    pub fn recommended(&self) -> Option<Preset> {
+        self.host.map(Preset::recommended)
+    }
+}
@@ -0,0 +1,53 @@
+use crate::app::form::{Field, Form};
+use crate::http;
+use crate::i18n::tr;
+use crate::settings::ProxySettings;
+
+#[derive(Debug)]
+pub struct ProxyForm {
+    /// HTTPS proxy, HTTP proxy, no-proxy list.
+    pub form: Form,
+}
+
+impl ProxyForm {
+    pub fn from_settings(proxy: &ProxySettings) -> Self {
+        let url = |label: &str, value: &Option<String>| {
+            Field::new(label, value.clone().unwrap_or_default())
+                .placeholder(tr("proxy.url_placeholder"))
+                .validator(|url| {
+                    if url.is_empty() {
+                        Ok(())
+                    } else {
+                        http::validate_proxy_url(url)
+                    }
+                })
+        };
+        Self {
+            form: Form::new(vec![
+                url(tr("proxy.https"), &proxy.https_proxy),
+                url(tr("proxy.http"), &proxy.http_proxy),
+                Field::new(
+                    tr("proxy.no_proxy"),
+                    proxy.no_proxy.clone().unwrap_or_default(),
+                )
+                .placeholder(tr("proxy.no_proxy_placeholder")),
+            ]),
+        }
+    }
+
+    pub fn to_settings(&self) -> ProxySettings {
+        let value = |idx: usize| {
+            let v = self.form.value(idx);
+            (!v.is_empty()).then(|| v.to_string())
+        };
+        ProxySettings {
+            https_proxy: value(0),
+            http_proxy: value(1),
+            no_proxy: value(2),
+        }
+    }
+
+    pub fn validate(&mut self) -> bool {
+        self.form.validate()
+    }
+}
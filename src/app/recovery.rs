@@ -0,0 +1,170 @@
+// recovery.rs - ways out of the error screen
+// A failed step used to leave the installer on a screen only Ctrl+C could
+// leave. The error screen now offers: retry the step that failed (the
+// install or the certificate, the two steps that can simply run again), go
+// back to the menu, page through the whole install log, or save the error
+// with its hints and log lines to a file for a support ticket. The error and
+// the log lines are also matched against common docker / compose failures so
+// the screen can say what to do about them.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use color_eyre::Result;
+
+use crate::i18n::{tr, trf};
+use crate::install_log;
+use crate::platform::DockerRuntime;
+
+/// A step that failed and can be run again as it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Retry {
+    Install,
+    SslCert,
+}
+
+/// What the error screen shows besides the error itself.
+#[derive(Debug, Clone, Default)]
+pub struct Recovery {
+    pub retry: Option<Retry>,
+    /// `hints` for the error and the log, worked out when the screen is
+    /// first drawn.
+    pub hints: Option<Vec<String>>,
+    /// Lines of the install log, while it is shown in place of the recent ones.
+    pub full_log: Option<Vec<String>>,
+    /// Lines scrolled up from the end of the log pane.
+    pub scroll: u16,
+    /// Outcome of saving the error to a file.
+    pub status: Option<std::result::Result<String, String>>,
+}
+
+/// Remediation for the docker / compose failures seen in `text`, most
+/// specific first. Empty when nothing is recognised.
+pub fn hints(text: &str, runtime: DockerRuntime) -> Vec<String> {
+    let text = text.to_ascii_lowercase();
+    let has = |needles: &[&str]| needles.iter().any(|needle| text.contains(needle));
+    let mut hints = Vec::new();
+    if has(&["permission denied while trying to connect to the docker daemon"]) {
+        hints.push(tr("hint.docker_group").to_string());
+    } else if has(&[
+        "cannot connect to the docker daemon",
+        "is the docker daemon running",
+        "docker daemon is not running",
+    ]) {
+        hints.push(trf(
+            "hint.daemon_down",
+            &[
+                ("runtime", runtime.label()),
+                ("help", &runtime.daemon_help()),
+            ],
+        ));
+    }
+    if has(&["port is already allocated", "address already in use"]) {
+        hints.push(tr("hint.port_taken").to_string());
+    }
+    if has(&[
+        "unauthorized",
+        "authentication required",
+        "denied: denied",
+        "requested access to the resource is denied",
+    ]) {
+        hints.push(tr("hint.pull_denied").to_string());
+    }
+    if has(&["manifest unknown", "manifest for", "not found: manifest"]) {
+        hints.push(tr("hint.unknown_tag").to_string());
+    }
+    if has(&["no space left on device"]) {
+        hints.push(tr("hint.disk_full").to_string());
+    }
+    if has(&[
+        "tls handshake timeout",
+        "i/o timeout",
+        "no such host",
+        "network is unreachable",
+        "connection reset by peer",
+    ]) {
+        hints.push(trf(
+            "hint.registry_unreachable",
+            &[("proxy", runtime.proxy_hint())],
+        ));
+    }
+    if has(&[
+        "is already in use by container",
+        "conflict. the container name",
+    ]) {
+        hints.push(tr("hint.name_conflict").to_string());
+    }
+    if has(&["yaml:", "additional property", "invalid compose project"]) {
+        hints.push(tr("hint.compose_invalid").to_string());
+    }
+    if has(&["unhealthy", "dependency failed to start"]) {
+        hints.push(trf("hint.unhealthy", &[("logs", runtime.logs_hint())]));
+    }
+    if has(&["permission denied (os error 13)", "operation not permitted"]) {
+        hints.push(tr("hint.file_permission").to_string());
+    }
+    hints
+}
+
+/// The install log, or `recent` when this run has none.
+pub fn full_log(recent: &[String]) -> Vec<String> {
+    install_log::path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|log| log.lines().map(str::to_string).collect())
+        .unwrap_or_else(|| recent.to_vec())
+}
+
+/// Write the error, its hints and the log lines to `error-<time>.txt` in
+/// `dir`. Returns the path written.
+pub fn save_report(dir: &Path, error: &str, hints: &[String], logs: &[String]) -> Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let path = dir.join(format!("error-{stamp}.txt"));
+    let mut report = format!("Error: {error}\n");
+    if !hints.is_empty() {
+        report.push_str("\nWhat to try:\n");
+        for hint in hints {
+            report.push_str(&format!("- {hint}\n"));
+        }
+    }
+    report.push_str("\nLog:\n");
+    for line in logs {
+        report.push_str(line);
+        report.push('\n');
+    }
+    fs::write(&path, report)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hints_for_common_failures() {
+        let pull = "Error response from daemon: Head \"https://ghcr.io/v2/x/manifests/1\": \
+                    unauthorized";
+        let pull_hints = hints(pull, DockerRuntime::Engine);
+        assert_eq!(pull_hints.len(), 1);
+        assert!(pull_hints[0].contains("Update GHCR token"));
+
+        let port = "Bind for 0.0.0.0:8008 failed: port is already allocated";
+        let port_hints = hints(port, DockerRuntime::Engine);
+        assert!(port_hints[0].contains("ss -ltnp"));
+        let daemon = "Cannot connect to the Docker daemon at unix:///var/run/docker.sock";
+        assert!(hints(daemon, DockerRuntime::Engine)[0].contains("systemctl start docker"));
+        assert!(hints("SSL cert generation failed", DockerRuntime::Engine).is_empty());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = save_report(
+            dir.path(),
+            port,
+            &port_hints,
+            &["⏳ Starting caddy".to_string()],
+        )
+        .unwrap();
+        let report = fs::read_to_string(path).unwrap();
+        assert!(report.starts_with(&format!("Error: {port}\n\nWhat to try:\n- A port")));
+        assert!(report.ends_with("Log:\n⏳ Starting caddy\n"));
+    }
+}
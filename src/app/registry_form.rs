@@ -1,3 +1,7 @@
+use crate::app::form::{Field, Form};
+use crate::i18n::tr;
+use crate::registry::{Registry, RegistryKind};
+
 #[allow(dead_code)]
 #[derive(Debug, Clone, PartialEq)]
 pub enum FocusState {
@@ -8,34 +12,48 @@ pub enum FocusState {
 
 #[derive(Debug)]
 pub struct RegistryForm {
-    pub token: String,
-    pub focus_state: FocusState,
-    pub error_message: String,
+    /// Registry the credential is for.
+    pub registry: Registry,
+    /// The token or password; Enter submits straight from the field.
+    pub form: Form,
 }
 
 impl RegistryForm {
     pub fn new() -> Self {
+        Self::for_registry(Registry::ghcr())
+    }
+
+    /// Form for `registry`, pre-filled with its saved credential.
+    pub fn for_registry(registry: Registry) -> Self {
+        let (label, missing) = if registry.kind == RegistryKind::Ghcr {
+            (tr("registry.pat"), tr("registry.pat_required"))
+        } else {
+            (tr("registry.password"), tr("registry.password_required"))
+        };
+        let token = Field::masked(label, registry.credential().unwrap_or_default())
+            .placeholder(tr("registry.token_placeholder"))
+            .validator(move |value| {
+                if value.is_empty() {
+                    Err(missing.to_string())
+                } else {
+                    Ok(())
+                }
+            });
         Self {
-            token: String::new(),
-            focus_state: FocusState::Field(0),
-            error_message: String::new(),
+            registry,
+            form: Form::new(vec![token])
+                .buttons(tr("form.submit"), tr("form.skip"))
+                .enter_submits(),
         }
     }
 
-    #[allow(dead_code)]
-    pub fn get_current_value_mut(&mut self) -> &mut String {
-        &mut self.token
+    pub fn token(&self) -> &str {
+        self.form.value(0)
     }
 
     #[allow(dead_code)]
     pub fn validate(&mut self) -> bool {
-        if self.token.trim().is_empty() {
-            self.error_message = "Personal access token is required".to_string();
-            return false;
-        }
-
-        self.error_message.clear();
-        true
+        self.form.validate()
     }
 }
 
@@ -0,0 +1,227 @@
+// rotation.rs - rotating the stack's secrets
+// The stack has no shared JWT secret: Keycloak signs tokens with per-realm key
+// providers. Rotating means adding fresh RSA and HMAC providers with a higher
+// priority, disabling the generated ones they replace and ending every
+// session, so tokens signed with the old keys stop validating. Master goes
+// last, with a fresh login once its new keys exist, since disabling its old
+// keys invalidates the installer's own admin token.
+//
+// The database password can only be rotated when the installer owns it (the
+// docker-secrets backend with the bundled identity-db): the role's password is
+// changed first while Keycloak keeps its open connections, the secret file is
+// replaced, then Keycloak is restarted to read it.
+
+use std::fs;
+use std::path::Path;
+
+use chrono::Utc;
+use color_eyre::{Result, eyre::eyre};
+use serde_json::{Value, json};
+
+use crate::app::backup::APP_CONTAINER;
+use crate::external_db::ExternalDb;
+use crate::i18n::tr;
+use crate::keycloak_migrate::Endpoint;
+use crate::runner::{Cmd, CommandRunner};
+use crate::secrets::{self, SecretBackend, SecretsSettings};
+
+const KEY_PROVIDER: &str = "org.keycloak.keys.KeyProvider";
+/// Generated key providers replaced by a rotation: (provider id, algorithm).
+const ROTATED: [(&str, &str); 2] = [("rsa-generated", "RS256"), ("hmac-generated", "HS512")];
+const DB_CONTAINER: &str = "identity-db";
+const DB_USER: &str = "identity";
+
+/// Why the database password cannot be rotated here, or `None` when it can.
+pub fn db_password_blocker(root: &Path, settings: &SecretsSettings) -> Option<&'static str> {
+    if ExternalDb::load(root).is_some() {
+        return Some(tr("rotation.external_db"));
+    }
+    match settings.backend {
+        SecretBackend::DockerSecrets => None,
+        SecretBackend::Env => Some(tr("rotation.env_backend")),
+        SecretBackend::Vault | SecretBackend::AwsSsm => Some(tr("rotation.external_store")),
+    }
+}
+
+/// Give every realm new signing keys, disable the old ones and end all
+/// sessions. Returns the realms rotated, master last.
+pub async fn rotate_keys(runner: &dyn CommandRunner) -> Result<Vec<String>> {
+    let mut realms = Endpoint::login_target(runner).await?.realm_names().await?;
+    realms.push("master".to_string());
+    for realm in &realms {
+        rotate_realm(runner, realm).await?;
+    }
+    Ok(realms)
+}
+
+async fn rotate_realm(runner: &dyn CommandRunner, realm: &str) -> Result<()> {
+    let endpoint = Endpoint::login_target(runner).await?;
+    let realm_id = endpoint.get(&format!("/realms/{realm}")).await?["id"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| eyre!("Realm {realm} has no id"))?;
+    let stamp = Utc::now();
+    let mut created = Vec::new();
+    for (provider, algorithm) in ROTATED {
+        let name = format!("{provider}-{}", stamp.format("%Y%m%d%H%M%S"));
+        let component = json!({
+            "name": name,
+            "providerId": provider,
+            "providerType": KEY_PROVIDER,
+            "parentId": realm_id,
+            "config": {
+                // Above the defaults (100) and every earlier rotation.
+                "priority": [stamp.timestamp_millis().to_string()],
+                "enabled": ["true"],
+                "active": ["true"],
+                "algorithm": [algorithm],
+            },
+        });
+        let (status, response) = endpoint
+            .post(&format!("/realms/{realm}/components"), &component)
+            .await?;
+        if !status.is_success() {
+            return Err(eyre!(
+                "Adding {provider} keys to realm {realm} failed (HTTP {status}): {response}"
+            ));
+        }
+        created.push(name);
+    }
+
+    // A new token, signed with the new keys when this is master.
+    let endpoint = Endpoint::login_target(runner).await?;
+    let providers = endpoint
+        .get(&format!(
+            "/realms/{realm}/components?parent={realm_id}&type={KEY_PROVIDER}"
+        ))
+        .await?;
+    for mut provider in providers.as_array().cloned().unwrap_or_default() {
+        if !retired(&provider, &created) {
+            continue;
+        }
+        provider["config"]["enabled"] = json!(["false"]);
+        provider["config"]["active"] = json!(["false"]);
+        let id = provider["id"].as_str().unwrap_or_default().to_string();
+        let (status, response) = endpoint
+            .put(&format!("/realms/{realm}/components/{id}"), &provider)
+            .await?;
+        if !status.is_success() {
+            return Err(eyre!(
+                "Disabling key {} in realm {realm} failed (HTTP {status}): {response}",
+                provider["name"]
+            ));
+        }
+    }
+
+    let (status, response) = endpoint
+        .post(&format!("/realms/{realm}/logout-all"), &json!({}))
+        .await?;
+    if !status.is_success() {
+        return Err(eyre!(
+            "Ending the sessions of realm {realm} failed (HTTP {status}): {response}"
+        ));
+    }
+    Ok(())
+}
+
+/// A generated provider of a rotated kind that is not one of `created`.
+fn retired(provider: &Value, created: &[String]) -> bool {
+    let id = provider["providerId"].as_str().unwrap_or_default();
+    let name = provider["name"].as_str().unwrap_or_default();
+    ROTATED.iter().any(|(rotated, _)| *rotated == id) && !created.iter().any(|n| n == name)
+}
+
+/// Set a new password on the identity role, store it in the secret file and
+/// restart Keycloak with it.
+pub async fn rotate_db_password(runner: &dyn CommandRunner, root: &Path) -> Result<()> {
+    let password = uuid::Uuid::new_v4().simple().to_string();
+    let path = secrets::path(root, secrets::DB_PASSWORD);
+    // Staged first so the password is on disk before the database uses it.
+    let staged = path.with_extension("new");
+    fs::write(&staged, &password)?;
+
+    // On stdin, so the password is not on a command line.
+    let alter = Cmd::new("docker")
+        .args([
+            "exec",
+            "-i",
+            DB_CONTAINER,
+            "psql",
+            "-q",
+            "-v",
+            "ON_ERROR_STOP=1",
+        ])
+        .args(["-U", DB_USER, "-d", DB_USER])
+        .stdin(format!(
+            "ALTER ROLE {DB_USER} WITH PASSWORD '{password}';\n"
+        ));
+    let output = runner.output(&alter).await?;
+    if !output.success {
+        let _ = fs::remove_file(&staged);
+        return Err(eyre!(
+            "Changing the database password failed: {}",
+            output.stderr.trim()
+        ));
+    }
+    fs::rename(&staged, &path).map_err(|e| {
+        eyre!(
+            "The database now uses the password in {}, but it could not replace {}: {e}",
+            staged.display(),
+            path.display()
+        )
+    })?;
+
+    let restart = runner
+        .output(&Cmd::new("docker").args(["restart", APP_CONTAINER]))
+        .await?;
+    if !restart.success {
+        return Err(eyre!(
+            "Restarting {APP_CONTAINER} failed: {}",
+            restart.stderr.trim()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::{CmdOutput, MockRunner};
+
+    #[tokio::test]
+    async fn test_db_password_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = SecretsSettings {
+            backend: SecretBackend::DockerSecrets,
+            ..Default::default()
+        };
+        assert_eq!(db_password_blocker(dir.path(), &settings), None);
+        assert!(db_password_blocker(dir.path(), &SecretsSettings::default()).is_some());
+
+        fs::create_dir(dir.path().join(secrets::SECRETS_DIR)).unwrap();
+        fs::write(secrets::path(dir.path(), secrets::DB_PASSWORD), "old").unwrap();
+        let runner = MockRunner::new()
+            .on("docker exec", CmdOutput::ok(""))
+            .on("docker restart", CmdOutput::ok("identity"));
+        rotate_db_password(&runner, dir.path()).await.unwrap();
+        let new = secrets::read(dir.path(), secrets::DB_PASSWORD).unwrap();
+        assert_ne!(new, "old");
+        let calls = runner.calls();
+        assert!(!calls[0].line().contains(&new));
+        assert_eq!(calls[1].line(), "docker restart identity");
+
+        let runner = MockRunner::new().on("docker exec", CmdOutput::failed("role missing"));
+        assert!(rotate_db_password(&runner, dir.path()).await.is_err());
+        assert_eq!(secrets::read(dir.path(), secrets::DB_PASSWORD), Some(new));
+    }
+
+    #[test]
+    fn test_only_generated_keys_are_retired() {
+        let created = vec!["rsa-generated-20260101000000".to_string()];
+        let key = |provider: &str, name: &str| json!({ "providerId": provider, "name": name });
+        assert!(retired(&key("rsa-generated", "rsa-generated"), &created));
+        assert!(!retired(&key("rsa-generated", &created[0]), &created));
+        assert!(!retired(&key("aes-generated", "aes-generated"), &created));
+        assert!(!retired(&key("rsa", "imported"), &created));
+    }
+}
@@ -0,0 +1,72 @@
+// service_picker.rs - choosing which optional services the install starts
+
+use crate::stack::{self, StackService};
+
+#[derive(Debug, Clone)]
+pub struct ServicePicker {
+    pub services: &'static [StackService],
+    pub selected: usize,
+    /// Parallel to `services`; required services are always on.
+    pub enabled: Vec<bool>,
+}
+
+impl ServicePicker {
+    /// Starts from the services left out in `disabled`.
+    pub fn new(disabled: &[String]) -> Self {
+        let services = stack::current().services;
+        let enabled = services
+            .iter()
+            .map(|s| !s.optional || !disabled.iter().any(|d| d == s.service))
+            .collect();
+        Self {
+            services,
+            selected: 0,
+            enabled,
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.services.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Flip the highlighted service; required ones do not change.
+    pub fn toggle(&mut self) {
+        if self.services[self.selected].optional {
+            self.enabled[self.selected] = !self.enabled[self.selected];
+        }
+    }
+
+    /// Service names for `Settings::disabled_services`.
+    pub fn disabled(&self) -> Vec<String> {
+        self.services
+            .iter()
+            .zip(&self.enabled)
+            .filter(|(_, enabled)| !**enabled)
+            .map(|(s, _)| s.service.to_string())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_optional_services_toggle() {
+        let mut picker = ServicePicker::new(&["identity".to_string()]);
+        assert!(picker.enabled.iter().all(|e| *e));
+        picker.toggle();
+        assert!(picker.disabled().is_empty());
+
+        picker.selected = picker.services.len() - 1;
+        picker.toggle();
+        assert_eq!(picker.disabled(), ["caddy"]);
+        assert_eq!(ServicePicker::new(&picker.disabled()).disabled(), ["caddy"]);
+    }
+}
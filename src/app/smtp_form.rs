@@ -0,0 +1,93 @@
+use crate::app::form::{Field, Form};
+use crate::i18n::{tr, trf};
+use crate::smtp::{self, Security, SmtpSettings};
+
+/// SMTP server for Keycloak's mails, and where the test mail goes.
+#[derive(Debug)]
+pub struct SmtpForm {
+    /// Host, port, security, user, password, from address, test recipient.
+    pub form: Form,
+}
+
+impl SmtpForm {
+    /// Starts from the server saved in .env, if any.
+    pub fn new(current: Option<&SmtpSettings>) -> Self {
+        let value = |get: fn(&SmtpSettings) -> String| current.map(get).unwrap_or_default();
+        let email = |value: &str| {
+            if value.is_empty() || value.contains('@') {
+                Ok(())
+            } else {
+                Err(trf("smtp.email_invalid", &[("value", value)]))
+            }
+        };
+        Self {
+            form: Form::new(vec![
+                Field::new(tr("smtp.host"), value(|s| s.host.clone()))
+                    .placeholder(tr("smtp.host_placeholder"))
+                    .validator(|host| {
+                        if host.is_empty() || host.contains(char::is_whitespace) {
+                            Err(tr("smtp.host_required").to_string())
+                        } else {
+                            Ok(())
+                        }
+                    }),
+                Field::new(tr("form.port"), value(|s| s.port.to_string()))
+                    .placeholder(tr("smtp.port_placeholder"))
+                    .validator(|port| {
+                        if port.is_empty() || port.parse::<u16>().is_ok_and(|p| p > 0) {
+                            Ok(())
+                        } else {
+                            Err(tr("form.port_invalid").to_string())
+                        }
+                    }),
+                Field::new(
+                    tr("smtp.security"),
+                    current.map_or(Security::default(), |s| s.security).as_str(),
+                )
+                .placeholder(tr("smtp.security_placeholder"))
+                .validator(|value| Security::parse(value).map(drop)),
+                Field::new(tr("form.username"), value(|s| s.user.clone()))
+                    .placeholder(tr("smtp.user_placeholder")),
+                Field::masked(tr("form.password"), value(|s| s.password.clone())),
+                Field::new(tr("smtp.from"), value(|s| s.from.clone()))
+                    .placeholder(tr("smtp.from_placeholder"))
+                    .validator(move |from| {
+                        if from.is_empty() {
+                            Err(tr("smtp.from_required").to_string())
+                        } else {
+                            email(from)
+                        }
+                    }),
+                Field::new(tr("smtp.test_to"), "")
+                    .placeholder(tr("smtp.test_to_placeholder"))
+                    .validator(email),
+            ])
+            .buttons(tr("form.save"), tr("form.cancel")),
+        }
+    }
+
+    pub fn to_settings(&self) -> SmtpSettings {
+        let security = Security::parse(self.form.value(2)).unwrap_or_default();
+        SmtpSettings {
+            host: self.form.value(0).to_string(),
+            port: self
+                .form
+                .value(1)
+                .parse()
+                .unwrap_or_else(|_| smtp::default_port(security)),
+            security,
+            user: self.form.value(3).to_string(),
+            // Untrimmed: passwords may end in whitespace.
+            password: self.form.fields[4].input.value().to_string(),
+            from: self.form.value(5).to_string(),
+        }
+    }
+
+    pub fn test_recipient(&self) -> Option<&str> {
+        Some(self.form.value(6)).filter(|to| !to.is_empty())
+    }
+
+    pub fn validate(&mut self) -> bool {
+        self.form.validate()
+    }
+}
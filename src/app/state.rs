@@ -1,13 +1,95 @@
+use crate::i18n::tr;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum AppState {
+    /// Offline runs: extracting and loading the bundled images.
+    AirgappedSetup,
+    /// Leftovers of an older deployment were found; offers to migrate them.
+    LegacyFound,
+    /// Progress and result of the legacy migration.
+    LegacyMigrating,
     SslSetup,
     RegistrySetup,
+    /// Configured registries, their login state and the mirror choice.
+    Registries,
+    ProxySetup,
+    /// Browsing for the project directory.
+    ProjectDirPicker,
+    /// Choosing the Docker daemon from `docker context ls`.
+    DockerContextPicker,
+    /// Resource limits, restart policy, network and extra environment for the override file.
+    AdvancedOptions,
+    /// Checkboxes for the optional services.
+    ServiceSelection,
+    /// Small / Medium / Large resource limits for the Docker host.
+    ResourcePresets,
+    /// Structured editor for the existing .env.
+    EnvEditor,
+    /// Structured editor for the project's config.yaml.
+    YamlEditor,
+    /// Grid of config templates (built-in and from templates/).
+    TemplateSelection,
+    /// Field values for the chosen template.
+    TemplateForm,
+    /// Per-key review of a regenerated .env before it overwrites the current one.
+    EnvReview,
+    MigrationSetup,
+    /// Progress and result of a Keycloak migration.
+    Migrating,
+    /// SMTP server form.
+    SmtpSetup,
+    /// Applying the SMTP server to the realms and sending the test mail.
+    SmtpApplying,
+    /// External PostgreSQL form.
+    ExternalDbSetup,
+    /// Testing the external database and saving it.
+    ExternalDbTesting,
+    /// LDAP / Active Directory form.
+    LdapSetup,
+    /// Testing the directory and creating the federation provider.
+    LdapApplying,
+    /// What a rotation changes; runs it once confirmed and shows the result.
+    RotateSecrets,
+    /// Application client form.
+    OidcClientSetup,
+    /// Registering the client; shows its ID, secret and exported file.
+    OidcClientRegistering,
     Confirmation,
     UpdateList,
     UpdatePulling,
     Installing,
     Success,
     Error(String),
+    /// Output of a quick action run from the Confirmation screen.
+    ActionOutput(QuickAction),
+    /// Choosing the backup to restore.
+    RestorePicker,
+    /// Progress of a database backup or restore.
+    Database(DatabaseTask),
+    /// Result of writing and enabling the systemd unit.
+    SystemdSetup,
+    /// Problems found in the compose files before an install; nothing ran.
+    ComposeCheck,
+    /// Diff of the bundle files against the embedded ones; written once confirmed.
+    BundleRewrite,
+    /// Commands that open the published ports in the host firewall; they run
+    /// only once confirmed.
+    FirewallSetup,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DatabaseTask {
+    Backup,
+    Restore,
+}
+
+impl DatabaseTask {
+    pub fn label(self) -> &'static str {
+        match self {
+            DatabaseTask::Backup => tr("database.backup"),
+            DatabaseTask::Restore => tr("database.restore"),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -15,7 +97,39 @@ pub enum MenuSelection {
     GenerateSsl,
     Proceed,
     UpdateToken,
+    Registries,
+    /// Remove saved registry credentials from the keyring / disk and docker.
+    ForgetCredentials,
     CheckUpdates,
+    ProxySettings,
+    EditConfig,
+    EditYamlConfig,
+    GenerateFromTemplate,
+    /// Write the embedded compose file and Caddyfile back, after a diff.
+    RewriteBundle,
+    /// Leave optional services out of the install.
+    Services,
+    /// CPU and memory limits sized to the Docker host.
+    ResourcePresets,
+    AdvancedOptions,
+    /// Choose the directory holding the compose files, .env and certs.
+    ProjectDir,
+    /// Choose the docker context commands go to.
+    DockerContext,
+    MigrateKeycloak,
+    /// SMTP server for Keycloak's mails.
+    ConfigureSmtp,
+    /// Use an existing PostgreSQL instead of identity-db.
+    ExternalDatabase,
+    /// LDAP / Active Directory user federation.
+    ConfigureLdap,
+    /// Create a confidential OIDC client for a downstream application.
+    RegisterClient,
+    /// New realm signing keys and, when the installer owns it, database password.
+    RotateSecrets,
+    BackupDatabase,
+    RestoreDatabase,
+    InstallSystemd,
     Cancel,
 }
 
@@ -25,3 +139,193 @@ pub enum SslSetupMenuSelection {
     Skip,
     Cancel,
 }
+
+/// What to do with a partially started stack after the user cancels `compose up`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CleanupChoice {
+    /// `down`: removes the started containers and network, keeps volumes.
+    Down,
+    LeaveAsIs,
+    StopServices,
+    /// `down -v`: removes containers, network and the database volume.
+    DownVolumes,
+}
+
+impl CleanupChoice {
+    pub const ALL: [CleanupChoice; 4] = [
+        CleanupChoice::Down,
+        CleanupChoice::LeaveAsIs,
+        CleanupChoice::StopServices,
+        CleanupChoice::DownVolumes,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CleanupChoice::Down => tr("cleanup.down"),
+            CleanupChoice::LeaveAsIs => tr("cleanup.leave"),
+            CleanupChoice::StopServices => tr("cleanup.stop"),
+            CleanupChoice::DownVolumes => tr("cleanup.down_volumes"),
+        }
+    }
+}
+
+/// A status line; whether it reports a success or a failure picks its colour.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatusMessage {
+    Info(String),
+    Success(String),
+    Failure(String),
+}
+
+impl StatusMessage {
+    pub fn text(&self) -> &str {
+        match self {
+            StatusMessage::Info(text)
+            | StatusMessage::Success(text)
+            | StatusMessage::Failure(text) => text,
+        }
+    }
+}
+
+/// Answers to Ctrl+C while an install is running. Quitting outright would
+/// leave compose half way through a deployment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuitChoice {
+    /// Close the prompt and exit once the install has finished.
+    Wait,
+    /// Stop compose and the services it started, then exit.
+    StopAndExit,
+    /// Stop compose, leave started services running and exit.
+    Detach,
+}
+
+impl QuitChoice {
+    pub const ALL: [QuitChoice; 3] = [
+        QuitChoice::Wait,
+        QuitChoice::StopAndExit,
+        QuitChoice::Detach,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            QuitChoice::Wait => tr("quit.wait"),
+            QuitChoice::StopAndExit => tr("quit.stop_and_exit"),
+            QuitChoice::Detach => tr("quit.detach"),
+        }
+    }
+}
+
+/// Options offered when the estimated pull does not fit on disk.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiskChoice {
+    /// `docker system prune -f`, then check again.
+    Prune,
+    ContinueAnyway,
+    Cancel,
+}
+
+impl DiskChoice {
+    pub const ALL: [DiskChoice; 3] = [
+        DiskChoice::Prune,
+        DiskChoice::ContinueAnyway,
+        DiskChoice::Cancel,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DiskChoice::Prune => tr("disk.prune"),
+            DiskChoice::ContinueAnyway => tr("disk.continue"),
+            DiskChoice::Cancel => tr("disk.cancel"),
+        }
+    }
+}
+
+/// Options offered when a compose step stops producing output or overruns
+/// its time limit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StallChoice {
+    KeepWaiting,
+    ViewOutput,
+    Abort,
+}
+
+impl StallChoice {
+    pub const ALL: [StallChoice; 3] = [
+        StallChoice::KeepWaiting,
+        StallChoice::ViewOutput,
+        StallChoice::Abort,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            StallChoice::KeepWaiting => tr("stall.keep_waiting"),
+            StallChoice::ViewOutput => tr("stall.view_output"),
+            StallChoice::Abort => tr("stall.abort"),
+        }
+    }
+}
+
+/// The open stall prompt.
+#[derive(Debug, Clone)]
+pub struct StallPrompt {
+    pub reason: String,
+    pub selected: usize,
+    /// Showing the raw output instead of the choices.
+    pub show_output: bool,
+}
+
+/// Hotkeys shown in the quick-actions bar on the Confirmation screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuickAction {
+    Install,
+    UpdateAll,
+    Logs,
+    Status,
+    Doctor,
+    /// Save the current setup as an `--answers` file.
+    ExportAnswers,
+    /// Turn debug logging on (or off again) and recreate the services.
+    DebugLogging,
+    /// Show or hide the summary of the project's files in place of the
+    /// checklist.
+    Files,
+}
+
+impl QuickAction {
+    pub const ALL: [QuickAction; 8] = [
+        QuickAction::Install,
+        QuickAction::UpdateAll,
+        QuickAction::Logs,
+        QuickAction::Status,
+        QuickAction::Doctor,
+        QuickAction::ExportAnswers,
+        QuickAction::DebugLogging,
+        QuickAction::Files,
+    ];
+
+    pub fn key(self) -> char {
+        match self {
+            QuickAction::Install => 'i',
+            QuickAction::UpdateAll => 'a',
+            QuickAction::Logs => 'l',
+            QuickAction::Status => 's',
+            QuickAction::Doctor => 'd',
+            QuickAction::ExportAnswers => 'e',
+            QuickAction::DebugLogging => 'g',
+            QuickAction::Files => 'f',
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            QuickAction::Install => tr("quick.install"),
+            QuickAction::UpdateAll => tr("quick.update_all"),
+            QuickAction::Logs => tr("quick.logs"),
+            QuickAction::Status => tr("quick.status"),
+            QuickAction::Doctor => tr("quick.doctor"),
+            QuickAction::ExportAnswers => tr("quick.export_answers"),
+            QuickAction::DebugLogging => tr("quick.debug_logging"),
+            QuickAction::Files => tr("quick.files"),
+        }
+    }
+}
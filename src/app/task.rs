@@ -0,0 +1,114 @@
+// task.rs - network work run off the render loop
+// A `Task` runs a future on the tokio runtime while the UI keeps drawing and
+// reading keys. The future reports progress through the app's event channel
+// (shown next to the spinner), and dropping the task — Esc while it runs —
+// aborts it.
+
+use std::future::Future;
+use std::time::Instant;
+
+use color_eyre::{Result, eyre::eyre};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::app::events::{AppEvent, EventLoop};
+use crate::http;
+
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// Sender half handed to the task's future.
+#[derive(Debug, Clone)]
+pub struct Progress(mpsc::UnboundedSender<AppEvent>);
+
+impl Progress {
+    /// Replace the label shown next to the spinner.
+    pub fn note(&self, text: impl Into<String>) {
+        let _ = self.0.send(AppEvent::Progress(text.into()));
+    }
+}
+
+#[derive(Debug)]
+pub struct Task<T> {
+    handle: JoinHandle<T>,
+    /// Shown until the task sends its first progress note.
+    pub label: String,
+}
+
+impl<T: Send + 'static> Task<T> {
+    pub fn spawn<F, Fut>(events: &EventLoop, label: impl Into<String>, work: F) -> Self
+    where
+        F: FnOnce(Progress) -> Fut,
+        Fut: Future<Output = T> + Send + 'static,
+    {
+        let progress = Progress(events.sender());
+        let retries = progress.clone();
+        let work = http::with_retry_status(move |note| retries.note(note), work(progress));
+        Self {
+            handle: tokio::spawn(work),
+            label: label.into(),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    /// Result of a finished task; waits if it is still running.
+    pub async fn join(mut self) -> Result<T> {
+        (&mut self.handle)
+            .await
+            .map_err(|e| eyre!("background task failed: {e}"))
+    }
+}
+
+impl<T> Drop for Task<T> {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// What the busy popup shows while a task runs.
+#[derive(Debug, Clone)]
+pub struct Busy {
+    pub label: String,
+    pub started: Instant,
+}
+
+impl Busy {
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            started: Instant::now(),
+        }
+    }
+
+    /// Spinner frame for the time elapsed, one step per 100 ms.
+    pub fn spinner(&self) -> char {
+        SPINNER[(self.started.elapsed().as_millis() / 100) as usize % SPINNER.len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_notes_reach_the_event_loop_and_drop_aborts() {
+        let mut events = EventLoop::new(Default::default());
+        let task = Task::spawn(&events, "Starting", |progress| async move {
+            progress.note("Downloading");
+            42
+        });
+        assert!(matches!(events.next().await, AppEvent::Progress(note) if note == "Downloading"));
+        assert_eq!(task.join().await.unwrap(), 42);
+
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        let task = Task::spawn(&events, "Waiting", |_| async move {
+            let _tx = tx;
+            std::future::pending::<()>().await
+        });
+        drop(task);
+        // The aborted future drops its sender, closing the channel.
+        assert!(rx.await.is_err());
+    }
+}
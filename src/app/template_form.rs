@@ -0,0 +1,169 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use color_eyre::{Result, eyre::eyre};
+
+use crate::app::endpoint_check::Check;
+use crate::app::form::{Field, Form};
+use crate::gpu::Gpu;
+use crate::i18n::tr;
+use crate::templates::ConfigTemplate;
+
+/// Values for one config template, one field each, prefilled with defaults.
+#[derive(Debug)]
+pub struct TemplateForm {
+    pub template: ConfigTemplate,
+    pub form: Form,
+    /// Set after the file was written.
+    pub status: Option<String>,
+    /// What the host offers, for templates with `gpu_services`.
+    pub gpu: Option<Gpu>,
+    /// Results of the last checks, one line per `probe` or `verify` field.
+    pub checks: Vec<String>,
+    /// Values that failed a check; saving them again writes anyway.
+    pub checked_values: Option<BTreeMap<String, String>>,
+}
+
+impl TemplateForm {
+    pub fn new(template: ConfigTemplate) -> Self {
+        let fields = template
+            .fields
+            .iter()
+            .map(|field| {
+                let label = if field.required {
+                    format!("{}*", field.label())
+                } else {
+                    field.label().to_string()
+                };
+                let input = if field.secret {
+                    Field::masked(label, field.default.clone())
+                } else {
+                    Field::new(label, field.default.clone())
+                };
+                let rules = field.clone();
+                input
+                    .placeholder(if field.options.is_empty() {
+                        String::new()
+                    } else {
+                        format!("({})", field.options.join("|"))
+                    })
+                    .validator(move |value| rules.validate(value))
+            })
+            .collect();
+        Self {
+            template,
+            form: Form::new(fields),
+            status: None,
+            gpu: None,
+            checks: Vec::new(),
+            checked_values: None,
+        }
+    }
+
+    /// Endpoints and keys in non-empty `probe` / `verify` fields, to check
+    /// before saving. Empty when these values already failed a check and the
+    /// user saves anyway.
+    pub fn checks_to_run(&self) -> Vec<Check> {
+        if self.checked_values.as_ref() == Some(&self.values()) {
+            return Vec::new();
+        }
+        self.template
+            .fields
+            .iter()
+            .zip(&self.form.fields)
+            .filter(|(_, input)| !input.value().is_empty())
+            .filter_map(|(field, input)| {
+                let value = input.value().to_string();
+                match field.verify {
+                    Some(provider) => Some(Check::Key(provider, value)),
+                    None => field.probe.then_some(Check::Endpoint(value)),
+                }
+            })
+            .collect()
+    }
+
+    /// Record the check results; true when every check passed.
+    pub fn record_checks(&mut self, results: Vec<(Check, Result<String, String>)>) -> bool {
+        let passed = results.iter().all(|(_, result)| result.is_ok());
+        self.checks = results
+            .into_iter()
+            .map(|(check, result)| match result {
+                Ok(message) => format!("✓ {}: {message}", check.label()),
+                Err(message) => format!("✗ {}: {message}", check.label()),
+            })
+            .collect();
+        if passed {
+            self.checked_values = None;
+        } else {
+            self.checked_values = Some(self.values());
+            self.form.error_message = tr("template_form.check_failed").to_string();
+        }
+        passed
+    }
+
+    fn values(&self) -> BTreeMap<String, String> {
+        self.template
+            .fields
+            .iter()
+            .zip(&self.form.fields)
+            .map(|(field, input)| (field.key.clone(), input.value().to_string()))
+            .collect()
+    }
+
+    /// Render the template into its output under `root`. Returns the path written.
+    pub fn save(&self, root: &Path) -> Result<PathBuf> {
+        write(&self.template, &self.values(), root)
+    }
+}
+
+/// Render `template` with `values` into its output under `root`. An existing
+/// file is kept as `<output>.bak`. Returns the path written.
+pub fn write(
+    template: &ConfigTemplate,
+    values: &BTreeMap<String, String>,
+    root: &Path,
+) -> Result<PathBuf> {
+    let rendered = template.render(values).map_err(|e| eyre!(e))?;
+    let path = template.output_path(root);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if path.is_file() {
+        let mut backup = path.clone().into_os_string();
+        backup.push(".bak");
+        fs::copy(&path, backup)?;
+    }
+    fs::write(&path, rendered)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failed_endpoint_check_allows_saving_again() {
+        let yaml = "\
+name: Local LLM
+output: config.yaml
+body: \"llm_api_base: {{ llm_api_base }}\\n\"
+fields:
+  - key: llm_api_base
+    default: http://localhost:11434/v1
+    validate: url
+    probe: true
+";
+        let mut form = TemplateForm::new(ConfigTemplate::parse("llm.yaml", yaml).unwrap());
+        let url = "http://localhost:11434/v1".to_string();
+        assert_eq!(form.checks_to_run(), [Check::Endpoint(url.clone())]);
+
+        assert!(!form.record_checks(vec![(Check::Endpoint(url), Err("HTTP 404".to_string()))]));
+        assert_eq!(form.checks, ["✗ http://localhost:11434/v1: HTTP 404"]);
+        assert!(form.checks_to_run().is_empty());
+
+        assert!(
+            ConfigTemplate::parse("llm.yaml", &yaml.replace("    validate: url\n", "")).is_err()
+        );
+    }
+}
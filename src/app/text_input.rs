@@ -0,0 +1,155 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Single-line text field with a cursor. The cursor moves and edits by
+/// grapheme, so an accented letter typed as two code points or an emoji
+/// with a modifier is one step and one bullet. Masked fields render as
+/// bullets until Ctrl+R reveals them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextInput {
+    value: String,
+    /// Cursor position in graphemes, 0..=len.
+    cursor: usize,
+    masked: bool,
+    revealed: bool,
+}
+
+impl TextInput {
+    pub fn new(value: impl Into<String>) -> Self {
+        let value = value.into();
+        Self {
+            cursor: value.graphemes(true).count(),
+            value,
+            ..Self::default()
+        }
+    }
+
+    pub fn masked(value: impl Into<String>) -> Self {
+        Self {
+            masked: true,
+            ..Self::new(value)
+        }
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Whether the value is currently hidden behind bullets.
+    pub fn is_hidden(&self) -> bool {
+        self.masked && !self.revealed
+    }
+
+    /// The value as it should be shown, one bullet per grapheme when hidden.
+    pub fn display(&self) -> String {
+        if self.is_hidden() {
+            "•".repeat(self.value.graphemes(true).count())
+        } else {
+            self.value.clone()
+        }
+    }
+
+    pub fn insert_str(&mut self, text: &str) {
+        let at = self.byte_index(self.cursor);
+        self.value.insert_str(at, text);
+        // A combining mark joins the grapheme before it, so count again
+        // rather than adding the graphemes of `text`.
+        self.cursor = self.value[..at + text.len()].graphemes(true).count();
+    }
+
+    /// Remove the grapheme starting at grapheme `at`.
+    fn remove(&mut self, at: usize) {
+        let range = self.byte_index(at)..self.byte_index(at + 1);
+        self.value.replace_range(range, "");
+    }
+
+    /// Apply an editing key. Returns false for keys the field doesn't use,
+    /// so the caller can treat them as navigation.
+    pub fn handle_key(&mut self, key: &KeyEvent) -> bool {
+        let len = self.value.graphemes(true).count();
+        match key.code {
+            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if !self.masked {
+                    return false;
+                }
+                self.revealed = !self.revealed;
+            }
+            KeyCode::Char(_) if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Char(c) => self.insert_str(c.encode_utf8(&mut [0; 4])),
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.remove(self.cursor);
+            }
+            KeyCode::Delete if self.cursor < len => self.remove(self.cursor),
+            KeyCode::Backspace | KeyCode::Delete => {}
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(len),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = len,
+            _ => return false,
+        }
+        true
+    }
+
+    fn byte_index(&self, graphemes: usize) -> usize {
+        self.value
+            .grapheme_indices(true)
+            .nth(graphemes)
+            .map_or(self.value.len(), |(i, _)| i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(input: &mut TextInput, code: KeyCode) {
+        input.handle_key(&KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    #[test]
+    fn test_edits_at_cursor() {
+        let mut input = TextInput::masked("héllo");
+        press(&mut input, KeyCode::Home);
+        press(&mut input, KeyCode::Right);
+        press(&mut input, KeyCode::Delete);
+        press(&mut input, KeyCode::Char('e'));
+        press(&mut input, KeyCode::End);
+        press(&mut input, KeyCode::Backspace);
+        assert_eq!(input.value(), "hell");
+        assert_eq!(input.display(), "••••");
+
+        input.handle_key(&KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        assert_eq!(input.display(), "hell");
+    }
+
+    #[test]
+    fn test_edits_whole_graphemes() {
+        // "e" + combining acute, a family emoji joined by ZWJs, then "ü".
+        let mut input = TextInput::masked("ne\u{301}e 👨\u{200d}👩\u{200d}👧 ü");
+        assert_eq!(input.display(), "•".repeat(7));
+        press(&mut input, KeyCode::Left);
+        press(&mut input, KeyCode::Left);
+        press(&mut input, KeyCode::Backspace);
+        assert_eq!(input.value(), "ne\u{301}e  ü");
+
+        press(&mut input, KeyCode::Home);
+        press(&mut input, KeyCode::Right);
+        press(&mut input, KeyCode::Delete);
+        assert_eq!(input.value(), "ne  ü");
+        input.insert_str("s\u{327}");
+        press(&mut input, KeyCode::Char('\u{301}'));
+        assert_eq!(input.cursor(), 2);
+        assert_eq!(input.value(), "ns\u{327}\u{301}e  ü");
+        press(&mut input, KeyCode::Backspace);
+        assert_eq!(input.value(), "ne  ü");
+    }
+}
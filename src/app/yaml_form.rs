@@ -0,0 +1,77 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use color_eyre::{Result, eyre::eyre};
+
+use crate::app::form::{Field, Form};
+use crate::env_file;
+use crate::yaml_file::{YamlEntry, YamlFile};
+
+/// Project config edited by the YAML editor, when present.
+pub const CONFIG_YAML: &str = "config.yaml";
+
+/// Structured editor for an existing YAML config: one field per scalar,
+/// labelled with its dotted path.
+#[derive(Debug)]
+pub struct YamlForm {
+    pub path: PathBuf,
+    file: YamlFile,
+    entries: Vec<YamlEntry>,
+    /// Secret-looking keys are masked.
+    pub form: Form,
+}
+
+impl YamlForm {
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = YamlFile::load(path)?;
+        let entries = file.entries();
+        let fields = entries
+            .iter()
+            .map(|entry| {
+                if env_file::is_secret_key(&entry.key) {
+                    Field::masked(entry.key.clone(), entry.value.clone())
+                } else {
+                    Field::new(entry.key.clone(), entry.value.clone())
+                }
+            })
+            .collect();
+        Ok(Self {
+            path: path.to_path_buf(),
+            form: Form::new(fields),
+            entries,
+            file,
+        })
+    }
+
+    /// The document with every field applied; on a type mismatch, focuses
+    /// the field and returns `None`.
+    fn edited(&mut self) -> Option<YamlFile> {
+        let mut file = self.file.clone();
+        for (idx, entry) in self.entries.iter().enumerate() {
+            if let Err(e) = file.set(entry, self.form.value(idx)) {
+                self.form.fail(idx, e);
+                return None;
+            }
+        }
+        self.form.error_message.clear();
+        Some(file)
+    }
+
+    /// Check every field parses as the type it had in the file.
+    pub fn validate(&mut self) -> bool {
+        self.edited().is_some()
+    }
+
+    /// Write the edited document back; the previous file is kept as `.bak`.
+    pub fn save(&mut self) -> Result<()> {
+        let file = self
+            .edited()
+            .ok_or_else(|| eyre!(self.form.error_message.clone()))?;
+        let mut backup = self.path.clone().into_os_string();
+        backup.push(".bak");
+        fs::copy(&self.path, backup)?;
+        file.save(&self.path)?;
+        self.file = file;
+        Ok(())
+    }
+}
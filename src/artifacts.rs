@@ -0,0 +1,162 @@
+// artifacts.rs - the files an install will use, for the confirmation screen
+// Before "Proceed with installation" the operator can audit the project
+// directory without leaving the TUI: the compose bundle and whether it still
+// matches the embedded files, .env, the compose override, the certificate,
+// and every file rendered from a config template, with the template's name.
+// Each file that exists shows its size and the start of its SHA-256, and
+// .env lists its keys with the values of secret keys masked. The summary is
+// read once when the panel is opened, not on every frame.
+
+use std::fs;
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+
+use crate::bundle::{self, FileState};
+use crate::compose_override;
+use crate::env_file::{self, EnvFile};
+use crate::settings::Settings;
+use crate::stack;
+
+/// Hex digits of the SHA-256 shown, enough to tell two versions apart.
+const CHECKSUM_LEN: usize = 12;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Origin {
+    /// Part of the embedded compose bundle; false once edited on disk.
+    Bundle { current: bool },
+    /// Rendered from the config template with this name.
+    Template(String),
+    /// Managed by the installer itself: .env, the override, the certificate.
+    Installer,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Artifact {
+    /// Relative to the project directory.
+    pub path: String,
+    pub origin: Origin,
+    /// Size in bytes and the start of the SHA-256, when the file exists.
+    pub file: Option<(u64, String)>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Summary {
+    pub files: Vec<Artifact>,
+    /// .env keys in file order, secret values masked.
+    pub env: Vec<(String, String)>,
+}
+
+impl Summary {
+    pub fn collect(root: &Path, settings: &Settings) -> Self {
+        let compared = bundle::compare(root).unwrap_or_default();
+        let mut files: Vec<Artifact> = stack::current()
+            .bundle
+            .iter()
+            .map(|(name, _)| {
+                let current = compared
+                    .iter()
+                    .any(|file| file.name == *name && file.state == FileState::Current);
+                artifact(root, name, Origin::Bundle { current })
+            })
+            .collect();
+        for path in [
+            ".env",
+            compose_override::OVERRIDE_FILE,
+            "certs/server.crt",
+            "certs/server.key",
+        ] {
+            files.push(artifact(root, path, Origin::Installer));
+        }
+        for (path, template) in &settings.generated_files {
+            let origin = Origin::Template(template.clone());
+            match files.iter_mut().find(|file| file.path == *path) {
+                Some(file) => file.origin = origin,
+                None => files.push(artifact(root, path, origin)),
+            }
+        }
+
+        let env = EnvFile::load(&root.join(".env"))
+            .map(|env| {
+                env.entries()
+                    .into_iter()
+                    .map(|(key, value)| {
+                        let value = mask(&key, &value);
+                        (key, value)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { files, env }
+    }
+}
+
+fn artifact(root: &Path, path: &str, origin: Origin) -> Artifact {
+    let file = fs::read(root.join(path)).ok().map(|contents| {
+        let digest = format!("{:x}", Sha256::digest(&contents));
+        (contents.len() as u64, digest[..CHECKSUM_LEN].to_string())
+    });
+    Artifact {
+        path: path.to_string(),
+        origin,
+        file,
+    }
+}
+
+/// The value as the summary shows it: secret keys hidden, empty ones quoted.
+pub fn mask(key: &str, value: &str) -> String {
+    if value.is_empty() {
+        "\"\"".to_string()
+    } else if env_file::is_secret_key(key) {
+        "••••••".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_lists_files_and_masks_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        let (compose, contents) = stack::IDENTITY.bundle[0];
+        fs::write(dir.path().join(compose), contents).unwrap();
+        fs::write(
+            dir.path().join(".env"),
+            "SERVER_IP=10.0.0.5\nPOSTGRES_PASSWORD=hunter2\nKC_LOG_LEVEL=\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("realm.json"), "{}").unwrap();
+        let mut settings = Settings::default();
+        settings
+            .generated_files
+            .insert(".env".to_string(), "Production".to_string());
+        settings
+            .generated_files
+            .insert("realm.json".to_string(), "Realm import".to_string());
+
+        let summary = Summary::collect(dir.path(), &settings);
+        let find = |path: &str| summary.files.iter().find(|f| f.path == path).unwrap();
+        assert_eq!(find(compose).origin, Origin::Bundle { current: true });
+        assert_eq!(find(stack::IDENTITY.bundle[1].0).file, None);
+        assert_eq!(
+            find(".env").origin,
+            Origin::Template("Production".to_string())
+        );
+        assert_eq!(
+            find("realm.json").file,
+            Some((2, "44136fa355b3".to_string()))
+        );
+        assert_eq!(find("certs/server.key").origin, Origin::Installer);
+        assert_eq!(
+            summary.env,
+            [
+                ("SERVER_IP".to_string(), "10.0.0.5".to_string()),
+                ("POSTGRES_PASSWORD".to_string(), "••••••".to_string()),
+                ("KC_LOG_LEVEL".to_string(), "\"\"".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,28 @@
+// build_info.rs - identifies the exact installer build
+// Shared by HTTP headers, logs and the install manifest so bug reports and
+// server-side logs can be matched to a specific binary.
+
+pub const NAME: &str = "nqrust-identity";
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const GIT_SHA: &str = env!("NQRUST_GIT_SHA");
+pub const TARGET: &str = env!("NQRUST_BUILD_TARGET");
+pub const PROFILE: &str = env!("NQRUST_BUILD_PROFILE");
+/// Comma-separated cargo features the binary was built with.
+pub const FEATURES: &str = env!("NQRUST_BUILD_FEATURES");
+
+pub fn mode(airgapped: bool) -> &'static str {
+    if airgapped { "airgapped" } else { "online" }
+}
+
+pub fn platform() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+/// e.g. `nqrust-identity/0.0.1 (git 1a2b3c4d5e6f; online; linux-x86_64)`
+pub fn user_agent(airgapped: bool) -> String {
+    format!(
+        "{NAME}/{VERSION} (git {GIT_SHA}; {}; {})",
+        mode(airgapped),
+        platform()
+    )
+}
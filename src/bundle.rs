@@ -0,0 +1,196 @@
+// bundle.rs - the embedded compose bundle against the files on disk
+// The selected stack's compose file and Caddyfile are compiled into the binary
+// (`Stack::bundle`). `utils::ensure_compose_bundle` only fills in files that
+// are missing, so hand edits survive a re-run. "(Re)write compose bundle"
+// compares every file with the embedded version, shows a line diff of the
+// ones that differ, and once confirmed writes the embedded versions back,
+// keeping each replaced file as `<name>.bak`.
+
+use std::fs;
+use std::path::Path;
+
+use color_eyre::Result;
+
+use crate::external_db::{self, ExternalDb};
+use crate::stack;
+
+/// Unchanged lines shown around each change.
+const CONTEXT: usize = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileState {
+    Missing,
+    Current,
+    /// `line_diff` from the file on disk to the embedded version.
+    Differs(Vec<String>),
+}
+
+#[derive(Debug, Clone)]
+pub struct BundleFile {
+    pub name: &'static str,
+    /// What the installer would write: the embedded file, or with an
+    /// external database the compose file generated from it.
+    pub expected: String,
+    pub state: FileState,
+}
+
+/// Every bundle file of the selected stack under `root`.
+pub fn compare(root: &Path) -> Result<Vec<BundleFile>> {
+    let external = ExternalDb::load(root).is_some();
+    stack::current()
+        .bundle
+        .iter()
+        .map(|(name, contents)| {
+            let expected = if external && *name == stack::current().compose_file() {
+                external_db::compose(contents)?
+            } else {
+                contents.to_string()
+            };
+            let state = match fs::read_to_string(root.join(name)) {
+                Ok(current) if current == expected => FileState::Current,
+                Ok(current) if !current.trim().is_empty() => {
+                    FileState::Differs(line_diff(&current, &expected))
+                }
+                _ => FileState::Missing,
+            };
+            Ok(BundleFile {
+                name,
+                expected,
+                state,
+            })
+        })
+        .collect()
+}
+
+/// Write every file that is missing or differs; a file that differs is kept
+/// as `<name>.bak` first. Returns one line per file written.
+pub fn rewrite(root: &Path, files: &[BundleFile]) -> Result<Vec<String>> {
+    let mut written = Vec::new();
+    for file in files {
+        let path = root.join(file.name);
+        match file.state {
+            FileState::Current => continue,
+            FileState::Missing => {
+                // A bind mount of a missing file leaves a directory behind.
+                if path.is_dir() {
+                    fs::remove_dir_all(&path)?;
+                }
+                fs::write(&path, &file.expected)?;
+                written.push(format!("✅ Wrote {}", file.name));
+            }
+            FileState::Differs(_) => {
+                fs::copy(&path, root.join(format!("{}.bak", file.name)))?;
+                fs::write(&path, &file.expected)?;
+                written.push(format!(
+                    "✅ Wrote {0} (the previous one is kept as {0}.bak)",
+                    file.name
+                ));
+            }
+        }
+    }
+    Ok(written)
+}
+
+/// `- ` / `+ ` / `  ` lines turning `old` into `new`, with `CONTEXT`
+/// unchanged lines around each change and `…` where lines are left out.
+pub fn line_diff(old: &str, new: &str) -> Vec<String> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    // lcs[i][j]: length of the longest common subsequence of old[i..] and new[j..].
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            ops.push((' ', old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            ops.push(('-', old[i]));
+            i += 1;
+        } else {
+            ops.push(('+', new[j]));
+            j += 1;
+        }
+    }
+
+    let changed: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, (tag, _))| *tag != ' ')
+        .map(|(k, _)| k)
+        .collect();
+    let mut lines = Vec::new();
+    let mut skipped = false;
+    for (k, (tag, line)) in ops.iter().enumerate() {
+        if changed.iter().any(|&c| c.abs_diff(k) <= CONTEXT) {
+            if skipped {
+                lines.push("  …".to_string());
+                skipped = false;
+            }
+            lines.push(format!("{tag} {line}"));
+        } else {
+            skipped = true;
+        }
+    }
+    if skipped && !lines.is_empty() {
+        lines.push("  …".to_string());
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_diff_keeps_context_around_changes() {
+        let old = "a\nb\nc\nd\ne\nf\ng\n";
+        let new = "a\nb\nc\nD\ne\nf\ng\nh\n";
+        assert_eq!(
+            line_diff(old, new),
+            [
+                "  …", "  b", "  c", "- d", "+ D", "  e", "  f", "  g", "+ h"
+            ]
+        );
+        assert!(line_diff(old, old).is_empty());
+    }
+
+    #[test]
+    fn test_rewrite_keeps_edited_files_as_bak() {
+        let dir = tempfile::tempdir().unwrap();
+        let (compose, contents) = stack::IDENTITY.bundle[0];
+        fs::write(dir.path().join(compose), "services: {}\n").unwrap();
+
+        let files = compare(dir.path()).unwrap();
+        assert!(matches!(files[0].state, FileState::Differs(_)));
+        assert_eq!(files[1].state, FileState::Missing);
+
+        let written = rewrite(dir.path(), &files).unwrap();
+        assert_eq!(written.len(), 2);
+        assert_eq!(
+            fs::read_to_string(dir.path().join(format!("{compose}.bak"))).unwrap(),
+            "services: {}\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join(compose)).unwrap(),
+            contents
+        );
+        assert!(
+            compare(dir.path())
+                .unwrap()
+                .iter()
+                .all(|file| file.state == FileState::Current)
+        );
+    }
+}
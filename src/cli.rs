@@ -0,0 +1,181 @@
+// cli.rs - command-line flags
+// Without flags the installer starts the interactive TUI.
+
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+
+use crate::i18n::Lang;
+use crate::output::OutputFormat;
+use crate::remote::RemoteTarget;
+use crate::settings::BandwidthSettings;
+use crate::throttle::Limits;
+
+#[derive(Debug, Default, Parser)]
+#[command(name = "nqrust-identity", version, about)]
+pub struct Cli {
+    /// Install without the TUI: create certs/.env if missing, pull, start
+    /// and wait for the identity healthcheck. Exits non-zero on failure.
+    #[arg(long)]
+    pub headless: bool,
+
+    /// Stop and remove the installed stack, then exit.
+    #[arg(long, conflicts_with = "headless")]
+    pub uninstall: bool,
+
+    /// With --uninstall, also delete volumes (the identity database).
+    #[arg(long, requires = "uninstall")]
+    pub purge: bool,
+
+    /// SERVER_IP for the certificate and .env in headless mode (default: auto-detect).
+    #[arg(long, value_name = "IP")]
+    pub server_ip: Option<String>,
+
+    /// Print payload, template and build details of this binary, then exit.
+    #[arg(long, hide = true)]
+    pub diagnostics: bool,
+
+    /// Product stack to install.
+    #[arg(long, default_value = "identity", value_parser = clap::builder::PossibleValuesParser::new(crate::stack::ids()))]
+    pub stack: String,
+
+    /// Self-update into ~/.local/bin without sudo.
+    #[arg(long)]
+    pub no_root: bool,
+
+    /// Pull and save all images into a bundle for airgapped hosts, then exit.
+    /// A path ending in .tar.gz writes a single archive instead of a directory.
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["headless", "uninstall"])]
+    pub export_bundle: Option<PathBuf>,
+
+    /// With --export-bundle: only include images changed since the release
+    /// described by this manifest.json (or bundle directory), e.g. the
+    /// .nqrust-payload-manifest.json left on the airgapped host.
+    #[arg(long, value_name = "MANIFEST", requires = "export_bundle")]
+    pub since: Option<PathBuf>,
+
+    /// Run offline, loading images from an exported bundle directory instead
+    /// of a payload embedded in the binary.
+    #[arg(long, value_name = "DIR")]
+    pub payload_dir: Option<PathBuf>,
+
+    /// Run offline, extracting images from a separate payload.tar.gz instead
+    /// of a payload embedded in the binary.
+    #[arg(long, value_name = "FILE", conflicts_with = "payload_dir")]
+    pub payload: Option<PathBuf>,
+
+    /// Check for image and installer updates on an interval (settings:
+    /// `update_check_interval_hours`) and record the result for the TUI.
+    #[arg(long, conflicts_with_all = ["headless", "uninstall", "export_bundle"])]
+    pub check_updates_daemon: bool,
+
+    /// Directory to extract an airgapped payload into (default: whichever of
+    /// the temp dir, /var/tmp, the project or home directory has the most
+    /// free space). Also read from NQRUST_WORKDIR.
+    #[arg(long, value_name = "DIR")]
+    pub workdir: Option<PathBuf>,
+
+    /// Directory holding the compose files, .env and certificates (default:
+    /// the nearest directory from here upwards with a compose file, else the
+    /// current one). Also read from NQRUST_PROJECT_DIR.
+    #[arg(long, value_name = "DIR")]
+    pub project_dir: Option<PathBuf>,
+
+    /// Install onto another host over SSH (`user@host[:port]` or
+    /// `ssh://…`): docker and compose talk to its daemon, and the project
+    /// directory is copied to the same path there before containers start.
+    /// An `ssh://` DOCKER_HOST does the same.
+    #[arg(long, value_name = "USER@HOST", value_parser = RemoteTarget::parse, conflicts_with_all = ["export_bundle", "check_updates_daemon"])]
+    pub remote: Option<RemoteTarget>,
+
+    /// Progress format for --headless and --uninstall: readable text, or one
+    /// JSON event per line on stdout.
+    #[arg(long, value_enum, default_value_t)]
+    pub output: OutputFormat,
+
+    /// TUI colours. `auto` uses mono when NO_COLOR is set, otherwise light
+    /// or dark from the terminal background (COLORFGBG).
+    #[arg(long, value_enum, default_value_t)]
+    pub theme: ThemeChoice,
+
+    /// Screen-reader friendly TUI: no colours, frames or emoji, and the
+    /// terminal cursor follows the selection.
+    #[arg(long, conflicts_with = "theme")]
+    pub plain: bool,
+
+    /// YAML answers for the TUI forms (GHCR token file, SSL address, admin
+    /// credentials, .env values, config templates); the screens then advance
+    /// on their own. See README "Unattended installs".
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["headless", "uninstall", "export_bundle"])]
+    pub answers: Option<PathBuf>,
+
+    /// Cap the installer's own downloads (the self-update) at this many
+    /// bytes per second: `500K`, `2M`. Overrides `bandwidth.download_limit`
+    /// in settings.
+    #[arg(long, value_name = "RATE", value_parser = crate::throttle::parse_rate)]
+    pub limit_rate: Option<u64>,
+
+    /// Images docker pulls at the same time (COMPOSE_PARALLEL_LIMIT).
+    /// Overrides `bandwidth.pull_parallelism` in settings.
+    #[arg(long, value_name = "N")]
+    pub pull_parallelism: Option<NonZeroUsize>,
+
+    /// TUI language. Defaults to the one named by LC_ALL, LC_MESSAGES or
+    /// LANG, falling back to English.
+    #[arg(long, value_enum)]
+    pub lang: Option<Lang>,
+}
+
+impl Cli {
+    /// `--workdir`, or `NQRUST_WORKDIR` when the flag is absent.
+    pub fn workdir(&self) -> Option<PathBuf> {
+        self.workdir
+            .clone()
+            .or_else(|| std::env::var_os("NQRUST_WORKDIR").map(PathBuf::from))
+    }
+
+    /// `--project-dir`, or `NQRUST_PROJECT_DIR` when the flag is absent.
+    pub fn project_dir(&self) -> Option<PathBuf> {
+        self.project_dir
+            .clone()
+            .or_else(|| std::env::var_os("NQRUST_PROJECT_DIR").map(PathBuf::from))
+    }
+
+    /// `--remote`, or `DOCKER_HOST` when it is an `ssh://` address.
+    pub fn remote(&self) -> Option<RemoteTarget> {
+        self.remote.clone().or_else(|| {
+            let host = std::env::var("DOCKER_HOST").ok()?;
+            host.starts_with("ssh://")
+                .then(|| RemoteTarget::parse(&host).ok())
+                .flatten()
+        })
+    }
+
+    /// Bandwidth limits from settings, with the flags taking precedence.
+    pub fn limits(&self, settings: &BandwidthSettings) -> Limits {
+        let configured = Limits::from_settings(settings);
+        Limits {
+            download_rate: self.limit_rate.or(configured.download_rate),
+            pull_parallelism: self.pull_parallelism.or(configured.pull_parallelism),
+        }
+    }
+
+    /// Offline mode: a bundle or payload file was given or the binary carries a payload.
+    pub fn airgapped(&self) -> bool {
+        self.payload_dir.is_some()
+            || self.payload.is_some()
+            || crate::airgapped::is_airgapped_binary().unwrap_or(false)
+    }
+}
+
+/// `--theme`; the TUI resolves it to a palette.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ThemeChoice {
+    /// `NO_COLOR` picks mono; otherwise the terminal background decides.
+    #[default]
+    Auto,
+    Dark,
+    Light,
+    Mono,
+}
@@ -0,0 +1,176 @@
+// compose_lint.rs - checking the compose files before anything is pulled
+// A typo in docker-compose.yaml or the override, or a variable .env does not
+// set, otherwise surfaces as one terse compose error halfway through the pull
+// or `up`. Each file is parsed first, so YAML syntax errors come with the file
+// and line, then `docker compose config --quiet` renders the merged project:
+// that catches schema errors and `${VAR:?}` variables without a value (which
+// stop the install), and variables compose would replace with an empty string
+// (which are reported and let through).
+
+use std::fs;
+use std::path::Path;
+
+use crate::runner::{Cmd, CommandRunner};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Report {
+    /// Problems compose would fail on; the install does not start.
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl Report {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// One line per finding, errors first, for the log and the report screen.
+    pub fn lines(&self) -> Vec<String> {
+        let errors = self.errors.iter().map(|e| format!("❌ {e}"));
+        let warnings = self.warnings.iter().map(|w| format!("⚠️  {w}"));
+        errors.chain(warnings).collect()
+    }
+}
+
+/// Check the files named in `compose_args` (`-f <path>` pairs) and the
+/// project compose renders from them.
+pub async fn check(
+    runner: &dyn CommandRunner,
+    compose_cmd: &[String],
+    compose_args: &[String],
+    root: &Path,
+) -> Report {
+    let mut report = Report::default();
+    for path in compose_args.iter().filter(|arg| arg.as_str() != "-f") {
+        if let Some(error) = parse_error(Path::new(path)) {
+            report.errors.push(error);
+        }
+    }
+    // Compose would only repeat the parse error, less precisely.
+    if !report.is_ok() {
+        return report;
+    }
+
+    let config = Cmd::compose(compose_cmd)
+        .args(compose_args)
+        .args(["config", "--quiet"])
+        .dir(root);
+    match runner.output(&config).await {
+        Ok(output) => {
+            for line in output.stderr.lines() {
+                let Some((message, warning)) = message(line) else {
+                    continue;
+                };
+                if warning {
+                    report.warnings.push(message);
+                } else if !output.success {
+                    report.errors.push(message);
+                }
+            }
+            if !output.success && report.errors.is_empty() {
+                report
+                    .errors
+                    .push("docker compose config failed without saying why".to_string());
+            }
+        }
+        Err(e) => report
+            .warnings
+            .push(format!("Could not run docker compose config: {e}")),
+    }
+    report
+}
+
+/// `file:line:column: message` when `path` is not valid YAML.
+fn parse_error(path: &Path) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    let error = serde_yaml::from_str::<serde_yaml::Value>(&content).err()?;
+    Some(match error.location() {
+        Some(at) => format!("{}:{}:{}: {error}", path.display(), at.line(), at.column()),
+        None => format!("{}: {error}", path.display()),
+    })
+}
+
+/// The message of one line of compose's stderr and whether it is a warning.
+/// Compose v2 logs `time=... level=warning msg="..."`, v1 `WARNING: ...`.
+fn message(line: &str) -> Option<(String, bool)> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+    let (text, warning) = match line.find("msg=") {
+        Some(at) => {
+            let text = &line[at + 4..];
+            let text = text
+                .strip_prefix('"')
+                .and_then(|text| text.strip_suffix('"'))
+                .unwrap_or(text);
+            (text.replace("\\\"", "\""), line.contains("level=warning"))
+        }
+        None => match line.strip_prefix("WARNING: ") {
+            Some(text) => (text.to_string(), true),
+            None => (line.to_string(), false),
+        },
+    };
+    Some((unset_variable(&text).unwrap_or(text), warning))
+}
+
+/// A readable form of compose's "The \"X\" variable is not set" warning.
+fn unset_variable(text: &str) -> Option<String> {
+    let rest = text.strip_prefix("The ")?;
+    let (name, rest) = rest.split_once(' ')?;
+    rest.starts_with("variable is not set").then(|| {
+        format!(
+            "{} is not set in .env or the environment; compose uses an empty string",
+            name.trim_matches('"')
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::{CmdOutput, MockRunner};
+
+    #[tokio::test]
+    async fn test_reports_yaml_compose_and_variable_problems() {
+        let dir = tempfile::tempdir().unwrap();
+        let compose = dir.path().join("docker-compose.yaml");
+        let args = vec!["-f".to_string(), compose.to_string_lossy().to_string()];
+        let cmd = vec!["docker".to_string(), "compose".to_string()];
+
+        fs::write(&compose, "services:\n  identity:\n    image: [\n").unwrap();
+        let runner = MockRunner::new();
+        let report = check(&runner, &cmd, &args, dir.path()).await;
+        assert!(report.errors[0].contains("docker-compose.yaml:"));
+        assert!(runner.calls().is_empty());
+
+        fs::write(&compose, "services:\n  identity:\n    image: ${IMAGE}\n").unwrap();
+        let runner = MockRunner::new().on(
+            "config",
+            CmdOutput {
+                success: true,
+                stdout: String::new(),
+                stderr: "time=\"2026-01-01T00:00:00Z\" level=warning msg=\"The \\\"SMTP_HOST\\\" \
+                         variable is not set. Defaulting to a blank string.\"\n"
+                    .to_string(),
+            },
+        );
+        let report = check(&runner, &cmd, &args, dir.path()).await;
+        assert!(report.is_ok());
+        assert_eq!(
+            report.warnings,
+            ["SMTP_HOST is not set in .env or the environment; compose uses an empty string"]
+        );
+
+        let runner = MockRunner::new().on(
+            "config",
+            CmdOutput::failed(
+                "validating docker-compose.yaml: services.identity Additional property \
+                 imagee is not allowed\n",
+            ),
+        );
+        let report = check(&runner, &cmd, &args, dir.path()).await;
+        assert_eq!(report.lines().len(), 1);
+        assert!(report.errors[0].contains("imagee is not allowed"));
+    }
+}
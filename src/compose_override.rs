@@ -0,0 +1,454 @@
+// compose_override.rs - generated docker-compose.override.yml
+// The base compose file is embedded and rewritten by the installer, so every
+// user-tunable setting lands in this override instead.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use color_eyre::eyre::Result;
+
+use crate::external_db::{self, ExternalDb};
+use crate::runner::{Cmd, CommandRunner};
+use crate::settings::{LogCaps, PullPolicy, RestartPolicy, Settings};
+use crate::{registry, secrets, selinux, stack};
+
+pub const OVERRIDE_FILE: &str = "docker-compose.override.yml";
+/// Compose profile of the services left out of the install; never enabled.
+const DISABLED_PROFILE: &str = "disabled";
+
+/// Gives a service every NVIDIA GPU of the host.
+const GPU_RESERVATION: &str = "    deploy:
+      resources:
+        reservations:
+          devices:
+            - driver: nvidia
+              count: all
+              capabilities: [gpu]
+";
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ServiceOverride {
+    pub image: Option<String>,
+    pub logging: Option<LogCaps>,
+    pub pull_policy: Option<PullPolicy>,
+    pub restart: Option<RestartPolicy>,
+    pub cpus: Option<String>,
+    pub mem_limit: Option<String>,
+    /// Reserve every NVIDIA GPU for the service.
+    pub gpu: bool,
+    /// Bind mounts repeated from the base file, e.g. with an SELinux label.
+    pub volumes: Vec<String>,
+    pub environment: BTreeMap<String, String>,
+    /// Port mappings added to the base file's, e.g. `127.0.0.1:8080:8080`.
+    pub ports: Vec<String>,
+    /// Left out of the install: only `--profile disabled` would start it.
+    pub disabled: bool,
+    /// Names of top-level secrets mounted under /run/secrets.
+    pub secrets: Vec<String>,
+    pub entrypoint: Option<Vec<String>>,
+}
+
+impl ServiceOverride {
+    fn is_empty(&self) -> bool {
+        self.image.is_none()
+            && self.logging.is_none()
+            && self.pull_policy.is_none()
+            && self.restart.is_none()
+            && self.cpus.is_none()
+            && self.mem_limit.is_none()
+            && !self.gpu
+            && self.volumes.is_empty()
+            && self.environment.is_empty()
+            && self.ports.is_empty()
+            && !self.disabled
+            && self.secrets.is_empty()
+            && self.entrypoint.is_none()
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ComposeOverride {
+    pub services: BTreeMap<String, ServiceOverride>,
+    /// Top-level file secrets: name → path relative to the project dir.
+    pub secrets: BTreeMap<String, String>,
+    /// Docker name given to the stack network.
+    pub network_name: Option<String>,
+}
+
+impl ComposeOverride {
+    /// `airgapped` picks the default pull policy when none is configured.
+    pub fn from_settings(settings: &Settings, airgapped: bool) -> Self {
+        let mut doc = Self::default();
+        let pull_policy = settings.pull_policy(airgapped);
+        for service in stack::current().service_names() {
+            let entry = doc.service(service);
+            entry.logging = Some(settings.log_caps_for(service).clone());
+            // Replaces the base file's `pull_policy: always` on identity.
+            entry.pull_policy = Some(pull_policy);
+        }
+        for (service, pin) in &settings.pinned_tags {
+            doc.service(service).image = Some(format!("{}:{}", pin.image, pin.tag));
+        }
+        if let Some(mirror) = registry::mirror(settings) {
+            for service in stack::current().services {
+                let entry = doc.service(service.service);
+                let source = entry.image.as_deref().unwrap_or(service.image);
+                entry.image = Some(registry::mirrored(source, &mirror));
+            }
+        }
+        for (service, tuning) in &settings.advanced.services {
+            let entry = doc.service(service);
+            entry.restart = tuning.restart;
+            entry.cpus = tuning.cpus.clone();
+            entry.mem_limit = tuning.memory.clone();
+            entry.environment.extend(tuning.environment.clone());
+        }
+        for service in &settings.gpu_services {
+            doc.service(service).gpu = true;
+        }
+        let stack = stack::current();
+        for service in stack.service_names().filter(|s| settings.skips(s)) {
+            doc.service(service).disabled = true;
+        }
+        if settings.skips_entry() {
+            let (service, address, port) = stack.direct;
+            doc.service(service).ports.push(format!("{address}:{port}"));
+        }
+        if settings.selinux_relabel {
+            for (service, mounts) in selinux::relabelled_mounts() {
+                doc.service(&service).volumes = mounts;
+            }
+        }
+        doc.network_name = settings.advanced.network_name.clone();
+        // Last, so installer-managed secret variables override extra environment.
+        secrets::apply(&mut doc, &settings.secrets);
+        doc
+    }
+
+    pub fn service(&mut self, name: &str) -> &mut ServiceOverride {
+        self.services.entry(name.to_string()).or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.secrets.is_empty()
+            && self.network_name.is_none()
+            && self.services.values().all(ServiceOverride::is_empty)
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::from(
+            "# Generated by nqrust-identity installer — changes are overwritten.\n\
+             # Adjust settings from the installer instead of editing this file.\n\
+             services:\n",
+        );
+        for (name, service) in &self.services {
+            if service.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("  {name}:\n"));
+            if service.disabled {
+                out.push_str(&format!("    profiles: [{DISABLED_PROFILE}]\n"));
+            }
+            if let Some(image) = &service.image {
+                out.push_str(&format!("    image: {}\n", yaml_quote(image)));
+            }
+            if let Some(policy) = service.pull_policy {
+                out.push_str(&format!("    pull_policy: {}\n", policy.as_str()));
+            }
+            if let Some(restart) = service.restart {
+                out.push_str(&format!("    restart: {}\n", yaml_quote(restart.as_str())));
+            }
+            if let Some(cpus) = &service.cpus {
+                out.push_str(&format!("    cpus: {cpus}\n"));
+            }
+            if let Some(mem_limit) = &service.mem_limit {
+                out.push_str(&format!("    mem_limit: {}\n", yaml_quote(mem_limit)));
+            }
+            if service.gpu {
+                out.push_str(GPU_RESERVATION);
+            }
+            if let Some(entrypoint) = &service.entrypoint {
+                let items: Vec<String> = entrypoint.iter().map(|s| yaml_quote(s)).collect();
+                out.push_str(&format!("    entrypoint: [{}]\n", items.join(", ")));
+            }
+            if !service.ports.is_empty() {
+                out.push_str("    ports:\n");
+                for port in &service.ports {
+                    out.push_str(&format!("      - {}\n", yaml_quote(port)));
+                }
+            }
+            if !service.volumes.is_empty() {
+                out.push_str("    volumes:\n");
+                for volume in &service.volumes {
+                    out.push_str(&format!("      - {}\n", yaml_quote(volume)));
+                }
+            }
+            if !service.environment.is_empty() {
+                out.push_str("    environment:\n");
+                for (key, value) in &service.environment {
+                    out.push_str(&format!("      {key}: {}\n", yaml_quote(value)));
+                }
+            }
+            if !service.secrets.is_empty() {
+                out.push_str("    secrets:\n");
+                for name in &service.secrets {
+                    out.push_str(&format!("      - {name}\n"));
+                }
+            }
+            if let Some(caps) = &service.logging {
+                out.push_str("    logging:\n      driver: json-file\n      options:\n");
+                out.push_str(&format!(
+                    "        max-size: {}\n        max-file: \"{}\"\n",
+                    yaml_quote(&caps.max_size),
+                    caps.max_file
+                ));
+            }
+        }
+        if let Some(name) = &self.network_name {
+            out.push_str(&format!(
+                "networks:\n  {}:\n    name: {}\n",
+                stack::current().network,
+                yaml_quote(name)
+            ));
+        }
+        if !self.secrets.is_empty() {
+            out.push_str("secrets:\n");
+            for (name, file) in &self.secrets {
+                out.push_str(&format!("  {name}:\n    file: {}\n", yaml_quote(file)));
+            }
+        }
+        out
+    }
+
+    /// Write the override next to the base compose file, or remove a stale
+    /// one when there is nothing left to override.
+    pub fn write(&self, root: &Path) -> Result<()> {
+        let path = root.join(OVERRIDE_FILE);
+        if self.is_empty() {
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+            return Ok(());
+        }
+        fs::write(path, self.render())?;
+        Ok(())
+    }
+}
+
+/// Regenerate the override file from the current settings and the
+/// project's database choice.
+pub fn sync(root: &Path, settings: &Settings, airgapped: bool) -> Result<()> {
+    let mut doc = ComposeOverride::from_settings(settings, airgapped);
+    if ExternalDb::load(root).is_some() {
+        external_db::detach(&mut doc);
+    }
+    doc.write(root)
+}
+
+/// Remove the containers of services left out of the install: once in the
+/// disabled profile `up` no longer touches them. Returns the ones removed.
+pub async fn remove_skipped(runner: &dyn CommandRunner, settings: &Settings) -> Vec<&'static str> {
+    let mut removed = Vec::new();
+    for service in stack::current()
+        .services
+        .iter()
+        .filter(|s| settings.skips(s.service))
+    {
+        let filter = format!("name=^{}$", service.container);
+        let existing = runner
+            .output(&Cmd::new("docker").args(["ps", "-aq", "-f", &filter]))
+            .await;
+        if !existing.is_ok_and(|output| output.value().is_some()) {
+            continue;
+        }
+        let rm = Cmd::new("docker").args(["rm", "-f", service.container]);
+        if runner.output(&rm).await.is_ok_and(|output| output.success) {
+            removed.push(service.container);
+        }
+    }
+    removed
+}
+
+/// `up` arguments carrying the effective pull policy.
+pub fn up_args(settings: &Settings, airgapped: bool) -> Vec<String> {
+    [
+        "up",
+        "-d",
+        "--pull",
+        settings.pull_policy(airgapped).as_str(),
+    ]
+    .map(String::from)
+    .to_vec()
+}
+
+/// `-f` arguments for compose: the base file plus the override when present.
+/// Passing `-f` disables compose's implicit override lookup, so it must be explicit.
+pub fn compose_file_args(root: &Path) -> Vec<String> {
+    let mut args = vec![
+        "-f".to_string(),
+        root.join(stack::current().compose_file())
+            .to_string_lossy()
+            .to_string(),
+    ];
+    let override_path = root.join(OVERRIDE_FILE);
+    if override_path.exists() {
+        args.push("-f".to_string());
+        args.push(override_path.to_string_lossy().to_string());
+    }
+    args
+}
+
+fn yaml_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::{CmdOutput, MockRunner};
+    use crate::settings::{PinnedTag, ServiceTuning};
+
+    #[tokio::test]
+    async fn test_skipped_entry_service_publishes_direct_port() {
+        let settings = Settings {
+            // Required services cannot be left out.
+            disabled_services: vec!["caddy".to_string(), "identity".to_string()],
+            ..Settings::default()
+        };
+        let rendered = ComposeOverride::from_settings(&settings, false).render();
+        assert!(rendered.contains("  caddy:\n    profiles: [disabled]\n"));
+        assert!(!rendered.contains("  identity:\n    profiles"));
+        assert!(rendered.contains("    ports:\n      - \"127.0.0.1:8080:8080\"\n"));
+
+        let runner = MockRunner::new().on("docker ps", CmdOutput::ok("3f2a\n"));
+        assert_eq!(remove_skipped(&runner, &settings).await, ["identity-caddy"]);
+        assert_eq!(runner.lines()[1], "docker rm -f identity-caddy");
+    }
+
+    #[test]
+    fn test_render_pinned_image() {
+        let mut settings = Settings::default();
+        settings.pinned_tags.insert(
+            "identity".to_string(),
+            PinnedTag {
+                image: "ghcr.io/nexusquantum/nqrust-identity".to_string(),
+                tag: "v0.2.0".to_string(),
+            },
+        );
+        let rendered = ComposeOverride::from_settings(&settings, false).render();
+        assert!(
+            rendered.contains(
+                "  identity:\n    image: \"ghcr.io/nexusquantum/nqrust-identity:v0.2.0\"\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_mirror_rewrites_every_image() {
+        let mut settings = Settings::default();
+        settings.registries.push(registry::Registry {
+            name: "corp".to_string(),
+            kind: registry::RegistryKind::Harbor,
+            host: Some("harbor.corp".to_string()),
+            project: Some("mirror".to_string()),
+            ..registry::Registry::default()
+        });
+        settings.mirror = Some("corp".to_string());
+        let rendered = ComposeOverride::from_settings(&settings, false).render();
+        assert!(rendered.contains("image: \"harbor.corp/mirror/library/postgres:16-alpine\""));
+        assert!(rendered.contains(
+            "image: \"harbor.corp/mirror/nexusquantum/nqrust-identity:${IDENTITY_TAG:-latest}\""
+        ));
+        assert_eq!(
+            rendered.matches("image: \"harbor.corp/").count(),
+            stack::current().services.len()
+        );
+    }
+
+    #[test]
+    fn test_pull_policy_defaults_to_never_offline() {
+        let mut settings = Settings::default();
+        let offline = ComposeOverride::from_settings(&settings, true).render();
+        assert_eq!(
+            offline.matches("pull_policy: never").count(),
+            stack::current().services.len()
+        );
+        assert!(
+            ComposeOverride::from_settings(&settings, false)
+                .render()
+                .contains("pull_policy: missing")
+        );
+
+        settings.pull_policy = Some(PullPolicy::Always);
+        assert!(
+            ComposeOverride::from_settings(&settings, true)
+                .render()
+                .contains("pull_policy: always")
+        );
+    }
+
+    #[test]
+    fn test_docker_secrets_mounted() {
+        let mut settings = Settings::default();
+        settings.secrets.backend = secrets::SecretBackend::DockerSecrets;
+        let rendered = ComposeOverride::from_settings(&settings, false).render();
+        assert!(
+            rendered
+                .contains("secrets:\n  admin_password:\n    file: \"./secrets/admin_password\"\n")
+        );
+        assert!(rendered.contains("POSTGRES_PASSWORD_FILE: \"/run/secrets/db_password\""));
+        assert!(rendered.contains("$$(cat /run/secrets/db_password)"));
+    }
+
+    #[test]
+    fn test_default_log_caps_for_every_service() {
+        let mut settings = Settings::default();
+        settings.service_log_caps.insert(
+            "identity".to_string(),
+            LogCaps {
+                max_size: "50m".to_string(),
+                max_file: 5,
+            },
+        );
+        let rendered = ComposeOverride::from_settings(&settings, false).render();
+        assert_eq!(
+            rendered.matches("driver: json-file").count(),
+            stack::current().services.len()
+        );
+        assert!(rendered.contains("max-size: \"50m\"\n        max-file: \"5\""));
+        assert!(rendered.contains("max-size: \"10m\"\n        max-file: \"3\""));
+    }
+
+    #[test]
+    fn test_advanced_tuning_rendered_and_secrets_win() {
+        let mut settings = Settings::default();
+        settings.secrets.backend = secrets::SecretBackend::DockerSecrets;
+        settings.advanced.network_name = Some("corp-identity".to_string());
+        settings.advanced.services.insert(
+            "identity".to_string(),
+            ServiceTuning {
+                cpus: Some("1.5".to_string()),
+                memory: Some("2g".to_string()),
+                restart: Some(RestartPolicy::OnFailure),
+                environment: BTreeMap::from([
+                    ("JAVA_OPTS_APPEND".to_string(), "-Xmx1g".to_string()),
+                    ("KC_DB_PASSWORD".to_string(), "plain".to_string()),
+                ]),
+            },
+        );
+        let rendered = ComposeOverride::from_settings(&settings, false).render();
+        assert!(
+            rendered
+                .contains("    restart: \"on-failure\"\n    cpus: 1.5\n    mem_limit: \"2g\"\n")
+        );
+        assert!(rendered.contains("      JAVA_OPTS_APPEND: \"-Xmx1g\"\n"));
+        assert!(rendered.contains("      KC_DB_PASSWORD: \"\"\n"));
+        assert!(rendered.contains("networks:\n  identity-net:\n    name: \"corp-identity\"\n"));
+
+        settings.gpu_services = vec!["identity".to_string()];
+        let rendered = ComposeOverride::from_settings(&settings, false).render();
+        assert_eq!(rendered.matches("driver: nvidia").count(), 1);
+        assert!(rendered.contains("count: all\n              capabilities: [gpu]\n"));
+    }
+}
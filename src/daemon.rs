@@ -0,0 +1,40 @@
+use crate::runner::{Cmd, CommandRunner};
+
+/// Messages the docker CLI prints when it loses its connection to the daemon.
+const DAEMON_LOST_MARKERS: &[&str] = &[
+    "Cannot connect to the Docker daemon",
+    "Is the docker daemon running?",
+    "error during connect",
+];
+
+/// Times a compose step is re-run after losing the daemon before the install
+/// fails; a marker that keeps matching must not re-run compose forever.
+pub const MAX_RETRIES: usize = 3;
+
+/// True if a docker/compose output line indicates the daemon went away.
+pub fn is_daemon_lost(line: &str) -> bool {
+    DAEMON_LOST_MARKERS
+        .iter()
+        .any(|marker| line.contains(marker))
+}
+
+/// Check whether the Docker daemon currently answers `docker info`.
+pub async fn daemon_is_up(runner: &dyn CommandRunner) -> bool {
+    runner
+        .output(&Cmd::new("docker").arg("info"))
+        .await
+        .is_ok_and(|output| output.success)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_daemon_lost_lines() {
+        assert!(is_daemon_lost(
+            "Cannot connect to the Docker daemon at unix:///var/run/docker.sock. Is the docker daemon running?"
+        ));
+        assert!(!is_daemon_lost(" ✔ Container identity-db  Started"));
+    }
+}
@@ -0,0 +1,168 @@
+// db_tuning.rs - Postgres settings sized to the Docker host
+// The postgres image starts with settings meant for a small shared machine
+// (128MB of shared buffers, 1GB of WAL between checkpoints) whatever the host
+// has. When .env is generated the installer reads the memory and CPUs of the
+// Docker host from `docker info`, which is the remote host with `--remote`,
+// derives the usual starting values from them and writes them as
+// `POSTGRES_*` variables. The compose file hands them to postgres as `-c`
+// options. Keys already in .env are kept, so hand tuning survives.
+
+use crate::env_file::EnvFile;
+use crate::runner::{Cmd, CommandRunner};
+
+const MB: u64 = 1 << 20;
+const GB: u64 = 1 << 30;
+
+/// Memory and CPUs the Docker daemon reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Host {
+    pub memory: u64,
+    pub cpus: u64,
+}
+
+/// `None` when `docker info` fails or reports nothing usable.
+pub async fn detect(runner: &dyn CommandRunner) -> Option<Host> {
+    let info = Cmd::new("docker").args(["info", "--format", "{{.MemTotal}} {{.NCPU}}"]);
+    let output = runner.output(&info).await.ok()?.value()?;
+    let (memory, cpus) = output.split_once(' ')?;
+    let host = Host {
+        memory: memory.trim().parse().ok()?,
+        cpus: cpus.trim().parse().ok()?,
+    };
+    (host.memory > 0 && host.cpus > 0).then_some(host)
+}
+
+/// The values written to .env, as Postgres setting strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tuning {
+    pub shared_buffers: String,
+    pub effective_cache_size: String,
+    pub work_mem: String,
+    pub maintenance_work_mem: String,
+    pub max_connections: u32,
+    pub wal_buffers: String,
+    pub min_wal_size: String,
+    pub max_wal_size: String,
+    pub max_worker_processes: u64,
+    pub max_parallel_workers: u64,
+}
+
+impl Tuning {
+    /// A quarter of the memory for shared buffers and three quarters assumed
+    /// to be page cache, the rest split between connections; larger WAL so
+    /// checkpoints are driven by time rather than by bursts of writes.
+    pub fn for_host(host: Host) -> Self {
+        let memory = host.memory;
+        let shared_buffers = (memory / 4).clamp(128 * MB, 8 * GB);
+        let max_connections = if memory < 4 * GB { 100 } else { 200 };
+        let work_mem = (memory.saturating_sub(shared_buffers) / (u64::from(max_connections) * 3))
+            .clamp(4 * MB, 64 * MB);
+        let (min_wal, max_wal) = if memory < 4 * GB {
+            (512 * MB, 2 * GB)
+        } else {
+            (GB, 4 * GB)
+        };
+        Self {
+            shared_buffers: size(shared_buffers),
+            effective_cache_size: size(memory / 4 * 3),
+            work_mem: size(work_mem),
+            maintenance_work_mem: size((memory / 16).clamp(64 * MB, 2 * GB)),
+            max_connections,
+            wal_buffers: size((shared_buffers / 32).min(16 * MB)),
+            min_wal_size: size(min_wal),
+            max_wal_size: size(max_wal),
+            max_worker_processes: host.cpus.max(8),
+            max_parallel_workers: host.cpus.clamp(2, 8),
+        }
+    }
+
+    pub fn env_pairs(&self) -> [(&'static str, String); 10] {
+        [
+            ("POSTGRES_SHARED_BUFFERS", self.shared_buffers.clone()),
+            (
+                "POSTGRES_EFFECTIVE_CACHE_SIZE",
+                self.effective_cache_size.clone(),
+            ),
+            ("POSTGRES_WORK_MEM", self.work_mem.clone()),
+            (
+                "POSTGRES_MAINTENANCE_WORK_MEM",
+                self.maintenance_work_mem.clone(),
+            ),
+            ("POSTGRES_MAX_CONNECTIONS", self.max_connections.to_string()),
+            ("POSTGRES_WAL_BUFFERS", self.wal_buffers.clone()),
+            ("POSTGRES_MIN_WAL_SIZE", self.min_wal_size.clone()),
+            ("POSTGRES_MAX_WAL_SIZE", self.max_wal_size.clone()),
+            (
+                "POSTGRES_MAX_WORKER_PROCESSES",
+                self.max_worker_processes.to_string(),
+            ),
+            (
+                "POSTGRES_MAX_PARALLEL_WORKERS",
+                self.max_parallel_workers.to_string(),
+            ),
+        ]
+    }
+
+    /// Set the keys .env does not have yet. Returns how many were added.
+    pub fn fill(&self, env: &mut EnvFile) -> usize {
+        let mut added = 0;
+        for (key, value) in self.env_pairs() {
+            if env.get(key).is_none() {
+                env.set(key, &value);
+                added += 1;
+            }
+        }
+        added
+    }
+}
+
+/// Whole megabytes, or gigabytes when exact.
+fn size(bytes: u64) -> String {
+    let mb = bytes / MB;
+    if mb >= 1024 && mb.is_multiple_of(1024) {
+        format!("{}GB", mb / 1024)
+    } else {
+        format!("{mb}MB")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::{CmdOutput, MockRunner};
+
+    #[tokio::test]
+    async fn test_detect_and_tune() {
+        let runner = MockRunner::new().on("docker info", CmdOutput::ok("16765456384 8\n"));
+        let host = detect(&runner).await.unwrap();
+        assert_eq!(host.cpus, 8);
+
+        let tuning = Tuning::for_host(Host {
+            memory: 16 * GB,
+            cpus: 8,
+        });
+        assert_eq!(tuning.shared_buffers, "4GB");
+        assert_eq!(tuning.effective_cache_size, "12GB");
+        assert_eq!(tuning.work_mem, "20MB");
+        assert_eq!(tuning.maintenance_work_mem, "1GB");
+        assert_eq!(tuning.max_connections, 200);
+        assert_eq!(tuning.wal_buffers, "16MB");
+        assert_eq!(tuning.max_wal_size, "4GB");
+
+        let small = Tuning::for_host(Host {
+            memory: GB,
+            cpus: 1,
+        });
+        assert_eq!(small.shared_buffers, "256MB");
+        assert_eq!(small.work_mem, "4MB");
+        assert_eq!(small.max_parallel_workers, 2);
+
+        let mut env = EnvFile::parse("POSTGRES_SHARED_BUFFERS=1GB\n");
+        assert_eq!(tuning.fill(&mut env), 9);
+        assert_eq!(env.get("POSTGRES_SHARED_BUFFERS"), Some("1GB"));
+        assert_eq!(env.get("POSTGRES_MAX_CONNECTIONS"), Some("200"));
+
+        let failing = MockRunner::new().on("docker info", CmdOutput::failed("no daemon"));
+        assert_eq!(detect(&failing).await, None);
+    }
+}
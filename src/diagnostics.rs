@@ -0,0 +1,81 @@
+// diagnostics.rs - hidden `--diagnostics` report
+// Answers "which binary does the customer actually have?" without running
+// the installer: payload presence and layout, embedded templates, build info.
+
+use std::time::Instant;
+
+use color_eyre::Result;
+
+use crate::airgapped::{PAYLOAD_MARKER, extractor};
+use crate::{build_info, stack};
+
+pub fn report(started: Instant) -> Result<()> {
+    let exe = std::env::current_exe()?;
+    let binary_size = std::fs::metadata(&exe)?.len();
+
+    println!("{} {}", build_info::NAME, build_info::VERSION);
+    println!("  git:          {}", build_info::GIT_SHA);
+    println!("  target:       {}", build_info::TARGET);
+    println!("  profile:      {}", build_info::PROFILE);
+    println!(
+        "  features:     {}",
+        if build_info::FEATURES.is_empty() {
+            "(none)"
+        } else {
+            build_info::FEATURES
+        }
+    );
+    println!("  executable:   {}", exe.display());
+    println!("  file size:    {}", human_size(binary_size));
+
+    println!();
+    println!("Payload");
+    let scan_started = Instant::now();
+    let marker = extractor::marker_offset(&exe)?;
+    let scan_time = scan_started.elapsed();
+    match marker {
+        Some(offset) => {
+            let payload_start = offset + PAYLOAD_MARKER.len() as u64;
+            println!("  attached:     yes");
+            println!("  marker at:    byte {offset}");
+            println!("  binary part:  {}", human_size(offset));
+            println!(
+                "  payload size: {}",
+                human_size(binary_size - payload_start)
+            );
+        }
+        None => println!("  attached:     no"),
+    }
+    println!("  marker scan:  {} ms", scan_time.as_millis());
+
+    println!();
+    println!("Embedded templates");
+    for stack in stack::STACKS {
+        println!("  {} ({})", stack.display_name, stack.id);
+        for (name, contents) in stack.bundle {
+            println!("    {name:<24} {}", human_size(contents.len() as u64));
+        }
+        for (image, file) in stack.required_images {
+            println!("    image {image} → {file}");
+        }
+    }
+
+    println!();
+    println!("Startup to report: {} ms", started.elapsed().as_millis());
+    Ok(())
+}
+
+fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {} ({bytes} B)", UNITS[unit])
+    }
+}
@@ -0,0 +1,407 @@
+// disk.rs - free space checks before images are pulled or extracted
+// Running out of space halfway through a pull or a payload extraction leaves
+// half-written layers behind and a confusing error. Estimating up front lets
+// the installer stop early and offer `docker system prune` instead.
+
+use std::path::{Path, PathBuf};
+
+use reqwest::Client;
+use serde::Deserialize;
+
+use crate::runner::{Cmd, CommandRunner, blocking_output};
+use crate::{http, stack};
+
+/// Unpacked layers take roughly this many times their compressed size, on top
+/// of the compressed download docker keeps while extracting.
+const EXTRACT_FACTOR: u64 = 3;
+/// Slack on top of every estimate, for containers, logs and the database.
+const HEADROOM: u64 = 1 << 30;
+
+/// Space needed on one filesystem.
+#[derive(Debug, Clone)]
+pub struct Requirement {
+    /// What the space is for, e.g. "Docker data".
+    pub label: &'static str,
+    pub path: PathBuf,
+    pub needed: u64,
+    /// `None` when the free space could not be determined.
+    pub free: Option<u64>,
+}
+
+impl Requirement {
+    fn new(label: &'static str, path: PathBuf, needed: u64) -> Self {
+        let free = free_bytes(&path);
+        Self {
+            label,
+            path,
+            needed,
+            free,
+        }
+    }
+
+    pub fn is_short(&self) -> bool {
+        self.free.is_some_and(|free| free < self.needed)
+    }
+
+    pub fn line(&self) -> String {
+        let free = self.free.map_or_else(|| "unknown".to_string(), gib);
+        format!(
+            "{} {} ({}): need ~{}, {free} free",
+            if self.is_short() { "❌" } else { "✓" },
+            self.label,
+            self.path.display(),
+            gib(self.needed)
+        )
+    }
+}
+
+pub fn gib(bytes: u64) -> String {
+    format!("{:.1} GB", bytes as f64 / 1_073_741_824.0)
+}
+
+/// Free bytes on the filesystem holding `path` (or its nearest existing parent).
+pub fn free_bytes(path: &Path) -> Option<u64> {
+    let existing = path.ancestors().find(|p| p.exists())?;
+    statvfs_free(existing)
+}
+
+#[cfg(unix)]
+fn statvfs_free(path: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    // SAFETY: statvfs(3) only writes into the zeroed struct we pass.
+    unsafe {
+        let mut stat: libc::statvfs = std::mem::zeroed();
+        if libc::statvfs(c_path.as_ptr(), &mut stat) != 0 {
+            return None;
+        }
+        Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+    }
+}
+
+#[cfg(not(unix))]
+fn statvfs_free(_path: &Path) -> Option<u64> {
+    // Docker Desktop keeps images inside its VM disk; nothing useful to check.
+    None
+}
+
+/// Space a pull of the stack needs under the Docker root. Images already
+/// present locally are skipped; images whose size the registry won't report
+/// are left out, so this is a lower bound. `ghcr_token` reads private GHCR
+/// manifests.
+pub async fn pull_requirement(
+    runner: &dyn CommandRunner,
+    client: &Client,
+    identity_tag: &str,
+    ghcr_token: Option<&str>,
+) -> Requirement {
+    let mut compressed = 0;
+    for service in stack::current().services {
+        let image = service
+            .image
+            .replace("${IDENTITY_TAG:-latest}", identity_tag);
+        if image_present(runner, &image).await {
+            continue;
+        }
+        compressed += compressed_size(client, &image, ghcr_token)
+            .await
+            .unwrap_or(0);
+    }
+    let needed = if compressed == 0 {
+        0
+    } else {
+        compressed * (EXTRACT_FACTOR + 1) + HEADROOM
+    };
+    let docker_root = runner
+        .output(&Cmd::new("docker").args(DOCKER_ROOT_ARGS))
+        .await
+        .ok()
+        .and_then(|output| output.value());
+    Requirement::new("Docker data", root_or_default(docker_root), needed)
+}
+
+/// Space the extracted image archives of a `payload_size` payload take.
+pub fn extraction_size(payload_size: u64) -> u64 {
+    payload_size + HEADROOM
+}
+
+/// Space extracting and loading an airgapped payload of `payload_size` bytes
+/// needs: the extracted image archives in `work_dir`, and the unpacked images
+/// under the Docker root.
+pub fn payload_requirements(
+    payload_size: u64,
+    work_dir: PathBuf,
+    docker_root: PathBuf,
+) -> [Requirement; 2] {
+    [
+        Requirement::new(
+            "Extraction directory",
+            work_dir,
+            extraction_size(payload_size),
+        ),
+        Requirement::new(
+            "Docker data",
+            docker_root,
+            payload_size * EXTRACT_FACTOR + HEADROOM,
+        ),
+    ]
+}
+
+/// Where to extract a payload when no `--workdir` was given: of the system
+/// temp dir (often a small tmpfs), `/var/tmp`, the project directory and the
+/// home directory, the one with the most free space. Candidates that already
+/// have `needed` bytes free win over ones that don't.
+pub fn pick_workdir(needed: u64) -> PathBuf {
+    let mut candidates = vec![std::env::temp_dir()];
+    if cfg!(unix) {
+        candidates.push(PathBuf::from("/var/tmp"));
+    }
+    candidates.push(crate::utils::project_root());
+    if let Some(home) = std::env::var_os("HOME") {
+        candidates.push(PathBuf::from(home));
+    }
+    candidates
+        .into_iter()
+        .filter(|dir| dir.is_dir())
+        .map(|dir| (free_bytes(&dir), dir))
+        .max_by_key(|(free, _)| (free.is_some_and(|free| free >= needed), *free))
+        .map_or_else(std::env::temp_dir, |(_, dir)| dir)
+}
+
+/// Docker's data directory (`/var/lib/docker` unless the daemon moved it).
+/// Blocks, for the offline payload loader.
+pub fn docker_root(runner: &dyn CommandRunner) -> PathBuf {
+    let dir = blocking_output(runner, &Cmd::new("docker").args(DOCKER_ROOT_ARGS))
+        .ok()
+        .and_then(|output| output.value());
+    root_or_default(dir)
+}
+
+const DOCKER_ROOT_ARGS: [&str; 3] = ["info", "--format", "{{.DockerRootDir}}"];
+
+fn root_or_default(dir: Option<String>) -> PathBuf {
+    dir.map_or_else(|| PathBuf::from("/var/lib/docker"), PathBuf::from)
+}
+
+async fn image_present(runner: &dyn CommandRunner, image: &str) -> bool {
+    runner
+        .output(&Cmd::new("docker").args(["image", "inspect", image]))
+        .await
+        .is_ok_and(|output| output.success)
+}
+
+/// `docker system prune -f`: stopped containers, unused networks, dangling
+/// images and build cache. Volumes are never touched. Returns its summary.
+pub async fn prune(runner: &dyn CommandRunner) -> Result<String, String> {
+    let output = runner
+        .output(&Cmd::new("docker").args(["system", "prune", "-f"]))
+        .await
+        .map_err(|e| e.to_string())?;
+    if !output.success {
+        return Err(output.stderr.trim().to_string());
+    }
+    Ok(output.stdout.trim().to_string())
+}
+
+#[derive(Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    manifests: Vec<PlatformManifest>,
+    #[serde(default)]
+    layers: Vec<Blob>,
+    config: Option<Blob>,
+}
+
+#[derive(Deserialize)]
+struct PlatformManifest {
+    digest: String,
+    platform: Option<Platform>,
+}
+
+#[derive(Deserialize)]
+struct Platform {
+    architecture: String,
+    os: String,
+}
+
+#[derive(Deserialize)]
+struct Blob {
+    size: u64,
+}
+
+#[derive(Deserialize)]
+struct Token {
+    #[serde(alias = "access_token")]
+    token: String,
+}
+
+/// Split `image` into registry, repository and tag the way docker does:
+/// no host means Docker Hub, and official images live under `library/`.
+fn parse_reference(image: &str) -> (String, String, String) {
+    let (name, tag) = match image.rsplit_once(':') {
+        Some((name, tag)) if !tag.contains('/') => (name, tag),
+        _ => (image, "latest"),
+    };
+    let (registry, repository) = match name.split_once('/') {
+        Some((host, rest)) if host.contains('.') || host.contains(':') => {
+            (host.to_string(), rest.to_string())
+        }
+        Some(_) => ("registry-1.docker.io".to_string(), name.to_string()),
+        None => (
+            "registry-1.docker.io".to_string(),
+            format!("library/{name}"),
+        ),
+    };
+    (registry, repository, tag.to_string())
+}
+
+/// Sum of the compressed layers of `image` for this host's platform.
+async fn compressed_size(client: &Client, image: &str, ghcr_token: Option<&str>) -> Option<u64> {
+    let (registry, repository, tag) = parse_reference(image);
+    let credential = ghcr_token.filter(|_| registry == "ghcr.io");
+    let token = pull_token(
+        client,
+        &format!("https://{registry}"),
+        &repository,
+        credential,
+    )
+    .await;
+    let manifest = fetch_manifest(client, &registry, &repository, &tag, token.as_deref()).await?;
+
+    let manifest = if manifest.manifests.is_empty() {
+        manifest
+    } else {
+        let arch = match std::env::consts::ARCH {
+            "x86_64" => "amd64",
+            "aarch64" => "arm64",
+            other => other,
+        };
+        let digest = manifest
+            .manifests
+            .iter()
+            .find(|m| {
+                m.platform
+                    .as_ref()
+                    .is_some_and(|p| p.os == "linux" && p.architecture == arch)
+            })?
+            .digest
+            .clone();
+        fetch_manifest(client, &registry, &repository, &digest, token.as_deref()).await?
+    };
+    Some(
+        manifest.layers.iter().map(|l| l.size).sum::<u64>() + manifest.config.map_or(0, |c| c.size),
+    )
+}
+
+async fn fetch_manifest(
+    client: &Client,
+    registry: &str,
+    repository: &str,
+    reference: &str,
+    token: Option<&str>,
+) -> Option<Manifest> {
+    let mut request = client
+        .get(format!(
+            "https://{registry}/v2/{repository}/manifests/{reference}"
+        ))
+        .header(
+            "Accept",
+            "application/vnd.oci.image.index.v1+json, \
+             application/vnd.docker.distribution.manifest.list.v2+json, \
+             application/vnd.oci.image.manifest.v1+json, \
+             application/vnd.docker.distribution.manifest.v2+json",
+        );
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    http::send(request)
+        .await
+        .ok()?
+        .error_for_status()
+        .ok()?
+        .json()
+        .await
+        .ok()
+}
+
+/// Pull token from the registry's auth service, found through the
+/// `WWW-Authenticate` challenge of `/v2/`; anonymous without `credential`.
+/// `base` is the registry's URL, e.g. `https://ghcr.io`.
+pub async fn pull_token(
+    client: &Client,
+    base: &str,
+    repository: &str,
+    credential: Option<&str>,
+) -> Option<String> {
+    let challenge = http::send(client.get(format!("{base}/v2/"))).await.ok()?;
+    let header = challenge
+        .headers()
+        .get("WWW-Authenticate")?
+        .to_str()
+        .ok()?
+        .to_string();
+    let param = |key: &str| {
+        header
+            .split([',', ' '])
+            .find_map(|part| part.strip_prefix(&format!("{key}=")))
+            .map(|value| value.trim_matches('"').to_string())
+    };
+    let realm = param("realm")?;
+    let mut query = vec![("scope", format!("repository:{repository}:pull"))];
+    if let Some(service) = param("service") {
+        query.push(("service", service));
+    }
+    let mut request = client.get(realm).query(&query);
+    if let Some(credential) = credential {
+        request = request.basic_auth("token", Some(credential));
+    }
+    let token: Token = http::send(request)
+        .await
+        .ok()?
+        .error_for_status()
+        .ok()?
+        .json()
+        .await
+        .ok()?;
+    Some(token.token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::{CmdOutput, MockRunner};
+
+    #[test]
+    fn test_docker_root_falls_back_when_docker_fails() {
+        let runner = MockRunner::new().on("docker info", CmdOutput::ok("/data/docker\n"));
+        assert_eq!(docker_root(&runner), PathBuf::from("/data/docker"));
+        let runner = MockRunner::new().on("docker info", CmdOutput::failed("Cannot connect"));
+        assert_eq!(docker_root(&runner), PathBuf::from("/var/lib/docker"));
+    }
+
+    #[test]
+    fn test_parse_reference() {
+        assert_eq!(
+            parse_reference("postgres:16-alpine"),
+            (
+                "registry-1.docker.io".to_string(),
+                "library/postgres".to_string(),
+                "16-alpine".to_string()
+            )
+        );
+        assert_eq!(
+            parse_reference("ghcr.io/nexusquantum/nqrust-identity:v1.0.0"),
+            (
+                "ghcr.io".to_string(),
+                "nexusquantum/nqrust-identity".to_string(),
+                "v1.0.0".to_string()
+            )
+        );
+        assert_eq!(
+            parse_reference("localhost:5000/caddy").2,
+            "latest".to_string()
+        );
+    }
+}
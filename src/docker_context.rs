@@ -0,0 +1,111 @@
+// docker_context.rs - which Docker daemon the installer talks to
+// Docker itself honours DOCKER_HOST, DOCKER_TLS_VERIFY / DOCKER_CERT_PATH and
+// the current context from ~/.docker/config.json, and the installer passes
+// its environment on unchanged. A context picked in the TUI (saved as
+// `docker_context` in settings) is handed to every docker and compose
+// command as DOCKER_CONTEXT instead, with the DOCKER_HOST variables removed
+// because docker lets those win over any context.
+
+use std::sync::RwLock;
+
+use serde::Deserialize;
+
+use crate::runner::{Cmd, CommandRunner};
+
+/// Variables that point docker at a daemon without a context.
+pub const HOST_VARS: &[&str] = &["DOCKER_HOST", "DOCKER_TLS_VERIFY", "DOCKER_CERT_PATH"];
+
+static SELECTED: RwLock<Option<String>> = RwLock::new(None);
+
+/// Use context `name` for docker commands from now on; `None` leaves the
+/// choice to docker.
+pub fn select(name: Option<String>) {
+    if let Ok(mut selected) = SELECTED.write() {
+        *selected = name.filter(|name| !name.is_empty());
+    }
+}
+
+/// The context picked in the installer, if any.
+pub fn selected() -> Option<String> {
+    SELECTED.read().ok().and_then(|selected| selected.clone())
+}
+
+/// The daemon commands go to, for the main screen: the selected context,
+/// or DOCKER_HOST when set. `None` for docker's own default.
+pub fn describe() -> Option<String> {
+    if let Some(name) = selected() {
+        return Some(format!("context {name}"));
+    }
+    std::env::var("DOCKER_HOST")
+        .ok()
+        .filter(|host| !host.is_empty())
+}
+
+/// One entry of `docker context ls`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DockerContext {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub docker_endpoint: String,
+    /// The one docker uses when the installer does not choose.
+    #[serde(default)]
+    pub current: bool,
+    /// Set when the context's metadata could not be read.
+    #[serde(default)]
+    pub error: String,
+}
+
+pub async fn list(runner: &dyn CommandRunner) -> Result<Vec<DockerContext>, String> {
+    let cmd = Cmd::new("docker").args(["context", "ls", "--format", "{{json .}}"]);
+    let output = runner.output(&cmd).await.map_err(|e| e.to_string())?;
+    if !output.success {
+        return Err(format!(
+            "docker context ls failed: {}",
+            output.stderr.trim()
+        ));
+    }
+    Ok(parse(&output.stdout))
+}
+
+fn parse(output: &str) -> Vec<DockerContext> {
+    output
+        .lines()
+        .filter_map(|line| serde_json::from_str(line.trim()).ok())
+        .collect()
+}
+
+/// Server version of the daemon the current selection reaches.
+pub async fn check(runner: &dyn CommandRunner) -> Result<String, String> {
+    let cmd = Cmd::new("docker").args(["info", "--format", "{{.ServerVersion}}"]);
+    let output = runner.output(&cmd).await.map_err(|e| e.to_string())?;
+    output.value().ok_or_else(|| {
+        let stderr = output.stderr.trim();
+        stderr.lines().next().unwrap_or("no answer").to_string()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_context_ls() {
+        let output = concat!(
+            r#"{"Current":true,"Description":"Current DOCKER_HOST based configuration","DockerEndpoint":"unix:///var/run/docker.sock","Error":"","Name":"default"}"#,
+            "\n",
+            r#"{"Current":false,"Description":"","DockerEndpoint":"unix:///run/user/1000/docker.sock","Error":"","Name":"rootless"}"#,
+            "\nWARNING: not json\n",
+        );
+        let contexts = parse(output);
+        assert_eq!(contexts.len(), 2);
+        assert!(contexts[0].current);
+        assert_eq!(contexts[1].name, "rootless");
+        assert_eq!(
+            contexts[1].docker_endpoint,
+            "unix:///run/user/1000/docker.sock"
+        );
+    }
+}
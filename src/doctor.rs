@@ -0,0 +1,219 @@
+use std::fs;
+use std::path::Path;
+
+use crate::daemon;
+use crate::platform::{self, DockerRuntime};
+use crate::runner::{Cmd, CommandRunner};
+use crate::stack;
+
+/// One line of the Doctor report.
+pub struct Check {
+    pub name: &'static str,
+    pub ok: bool,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, ok: bool, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            ok,
+            detail: detail.into(),
+        }
+    }
+
+    pub fn line(&self) -> String {
+        let icon = if self.ok { "✅" } else { "❌" };
+        format!("{icon} {:<18} {}", self.name, self.detail)
+    }
+}
+
+/// Quick environment checks covering the usual reasons an install or update fails.
+pub async fn run_checks(
+    runner: &dyn CommandRunner,
+    root: &Path,
+    compose_cmd: Option<&[String]>,
+    airgapped: bool,
+    has_token: bool,
+    uses_proxy: bool,
+) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    let daemon_up = daemon::daemon_is_up(runner).await;
+    let runtime = DockerRuntime::detect();
+    checks.push(Check::new(
+        "Docker daemon",
+        daemon_up,
+        match (daemon_up, runtime) {
+            (true, _) => format!("{} responding to `docker info`", runtime.label()),
+            (false, DockerRuntime::Engine) => "not reachable — is the docker service running \
+                 and are you in the docker group?"
+                .to_string(),
+            (false, _) => format!(
+                "{} not reachable — {}",
+                runtime.label(),
+                platform::docker_start_hint()
+            ),
+        },
+    ));
+
+    let lowest = runtime.lowest_publishable_port();
+    if lowest > 0 {
+        let blocked: Vec<String> =
+            published_ports(&root.join(stack::current().compose_file()), None)
+                .into_iter()
+                .filter(|port| *port < lowest)
+                .map(|port| port.to_string())
+                .collect();
+        checks.push(Check::new(
+            "Published ports",
+            blocked.is_empty(),
+            if blocked.is_empty() {
+                format!("all at or above {lowest}, which a rootless daemon can bind")
+            } else {
+                format!(
+                    "{} below {lowest} can't be bound by a rootless daemon — publish \
+                     them higher or lower net.ipv4.ip_unprivileged_port_start",
+                    blocked.join(", ")
+                )
+            },
+        ));
+    }
+
+    checks.push(match compose_cmd {
+        Some(cmd) => Check::new("Docker Compose", true, cmd.join(" ")),
+        None => Check::new(
+            "Docker Compose",
+            false,
+            "neither `docker compose` nor `docker-compose` found",
+        ),
+    });
+
+    let cert_ok = root.join("certs/server.crt").exists() && root.join("certs/server.key").exists();
+    checks.push(Check::new(
+        "SSL certificate",
+        cert_ok,
+        if cert_ok {
+            "certs/server.crt + server.key"
+        } else {
+            "missing — use \"Generate SSL Cert & write .env\""
+        },
+    ));
+
+    let server_ip = fs::read_to_string(root.join(".env")).ok().and_then(|env| {
+        env.lines()
+            .find_map(|l| l.strip_prefix("SERVER_IP=").map(str::to_string))
+    });
+    checks.push(match server_ip {
+        Some(ip) => Check::new("SERVER_IP", true, ip),
+        None => Check::new("SERVER_IP", false, "not set in .env"),
+    });
+
+    let compose_ok = root.join("docker-compose.yaml").is_file();
+    let caddy_ok = root.join("Caddyfile").is_file();
+    checks.push(Check::new(
+        "Compose bundle",
+        compose_ok && caddy_ok,
+        match (compose_ok, caddy_ok) {
+            (true, true) => "docker-compose.yaml + Caddyfile",
+            (false, _) => "docker-compose.yaml missing — written on install",
+            (true, false) => "Caddyfile missing or not a file — rewritten on install",
+        },
+    ));
+
+    // Image pulls are made by the daemon, which ignores the installer's proxy.
+    if uses_proxy && daemon_up {
+        let daemon_proxy = runner
+            .output(&Cmd::new("docker").args(["info", "--format", "{{.HTTPSProxy}}{{.HTTPProxy}}"]))
+            .await
+            .is_ok_and(|out| !out.stdout.trim().is_empty());
+        checks.push(Check::new(
+            "Docker proxy",
+            daemon_proxy,
+            if daemon_proxy {
+                "daemon has a proxy configured".to_string()
+            } else {
+                format!(
+                    "installer uses a proxy but the daemon does not — pulls will fail \
+                     (see {})",
+                    runtime.proxy_hint()
+                )
+            },
+        ));
+    }
+
+    if !airgapped {
+        checks.push(Check::new(
+            "GHCR token",
+            has_token,
+            if has_token {
+                "configured"
+            } else {
+                "not set — private images cannot be pulled"
+            },
+        ));
+    }
+
+    checks
+}
+
+/// Host ports the compose file publishes, from short (`"8008:443"`,
+/// `"127.0.0.1:8008:443"`) and long (`published: 8008`) syntax, for every
+/// service or only `service`. Ports left to docker to pick are skipped.
+pub fn published_ports(compose_file: &Path, service: Option<&str>) -> Vec<u16> {
+    let Some(compose) = fs::read_to_string(compose_file)
+        .ok()
+        .and_then(|raw| serde_yaml::from_str::<serde_yaml::Value>(&raw).ok())
+    else {
+        return Vec::new();
+    };
+    let Some(services) = compose["services"].as_mapping() else {
+        return Vec::new();
+    };
+    let mut ports = Vec::new();
+    let services = services
+        .iter()
+        .filter(|(name, _)| service.is_none() || name.as_str() == service)
+        .map(|(_, definition)| definition);
+    for service in services {
+        for port in service["ports"].as_sequence().into_iter().flatten() {
+            let published = match port {
+                serde_yaml::Value::String(short) => {
+                    let mut parts: Vec<&str> = short.split(':').collect();
+                    parts.pop();
+                    parts.pop().map(str::to_string)
+                }
+                serde_yaml::Value::Mapping(_) => match &port["published"] {
+                    serde_yaml::Value::Number(n) => Some(n.to_string()),
+                    serde_yaml::Value::String(s) => Some(s.clone()),
+                    _ => None,
+                },
+                _ => None,
+            };
+            // A range such as "8000-8010" counts by its first port.
+            if let Some(port) = published.and_then(|p| p.split('-').next()?.parse().ok()) {
+                ports.push(port);
+            }
+        }
+    }
+    ports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_published_ports() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("docker-compose.yaml");
+        fs::write(
+            &file,
+            "services:\n  db:\n    ports:\n      - \"5436:5432\"\n  caddy:\n    ports:\n      \
+             - \"127.0.0.1:443:443\"\n      - \"9000\"\n      - target: 80\n        published: 80\n",
+        )
+        .unwrap();
+        assert_eq!(published_ports(&file, None), [5436, 443, 80]);
+        assert_eq!(published_ports(&file, Some("caddy")), [443, 80]);
+    }
+}
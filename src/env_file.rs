@@ -0,0 +1,374 @@
+// env_file.rs - line-preserving .env parser
+// Edits go through `EnvFile` so comments, blank lines and key order survive
+// a round trip; only the values that changed are rewritten.
+
+use std::fs;
+use std::net::IpAddr;
+use std::path::Path;
+
+use color_eyre::Result;
+
+use crate::registry;
+
+#[derive(Debug, Clone, PartialEq)]
+enum EnvLine {
+    Entry {
+        key: String,
+        value: String,
+        /// Quote character the value was written with, reused on save.
+        quote: Option<char>,
+        export: bool,
+    },
+    /// Comments, blank lines and anything else kept verbatim.
+    Other(String),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EnvFile {
+    lines: Vec<EnvLine>,
+}
+
+impl EnvFile {
+    pub fn parse(content: &str) -> Self {
+        let lines = content
+            .lines()
+            .map(|line| {
+                let trimmed = line.trim_start();
+                let body = trimmed.strip_prefix("export ");
+                let export = body.is_some();
+                match body.unwrap_or(trimmed).split_once('=') {
+                    Some((key, raw)) if !trimmed.starts_with('#') && is_key(key.trim()) => {
+                        let (value, quote) = unquote(raw.trim());
+                        EnvLine::Entry {
+                            key: key.trim().to_string(),
+                            value,
+                            quote,
+                            export,
+                        }
+                    }
+                    _ => EnvLine::Other(line.to_string()),
+                }
+            })
+            .collect();
+        Self { lines }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(Self::parse(&fs::read_to_string(path)?))
+    }
+
+    /// Written with mode 0600: the file holds the stack's passwords.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        registry::write_private(path, &self.render())
+    }
+
+    /// Key/value pairs in file order.
+    pub fn entries(&self) -> Vec<(String, String)> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                EnvLine::Entry { key, value, .. } => Some((key.clone(), value.clone())),
+                EnvLine::Other(_) => None,
+            })
+            .collect()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.lines.iter().find_map(|line| match line {
+            EnvLine::Entry { key: k, value, .. } if k == key => Some(value.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Update `key` in place, or append it when missing.
+    pub fn set(&mut self, key: &str, new_value: &str) {
+        for line in &mut self.lines {
+            if let EnvLine::Entry { key: k, value, .. } = line
+                && k == key
+            {
+                *value = new_value.to_string();
+                return;
+            }
+        }
+        self.lines.push(EnvLine::Entry {
+            key: key.to_string(),
+            value: new_value.to_string(),
+            quote: None,
+            export: false,
+        });
+    }
+
+    /// Remove `key`; returns true when it was present.
+    pub fn remove(&mut self, key: &str) -> bool {
+        let before = self.lines.len();
+        self.lines
+            .retain(|line| !matches!(line, EnvLine::Entry { key: k, .. } if k == key));
+        self.lines.len() != before
+    }
+
+    /// Keys that differ between this file and a regenerated one, in file order
+    /// (removed keys follow their original position).
+    pub fn diff(&self, proposed: &EnvFile) -> Vec<EnvChange> {
+        let mut changes = Vec::new();
+        for (key, old) in self.entries() {
+            match proposed.get(&key) {
+                Some(new) if new != old => changes.push(EnvChange {
+                    key,
+                    kind: ChangeKind::Changed,
+                    old: Some(old),
+                    new: Some(new.to_string()),
+                }),
+                Some(_) => {}
+                None => changes.push(EnvChange {
+                    key,
+                    kind: ChangeKind::Removed,
+                    old: Some(old),
+                    new: None,
+                }),
+            }
+        }
+        for (key, new) in proposed.entries() {
+            if self.get(&key).is_none() {
+                changes.push(EnvChange {
+                    key,
+                    kind: ChangeKind::Added,
+                    old: None,
+                    new: Some(new),
+                });
+            }
+        }
+        changes
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for line in &self.lines {
+            match line {
+                EnvLine::Entry {
+                    key,
+                    value,
+                    quote,
+                    export,
+                } => {
+                    if *export {
+                        out.push_str("export ");
+                    }
+                    let quote = quote.or_else(|| needs_quotes(value).then_some('"'));
+                    match quote {
+                        Some(q) => out.push_str(&format!("{key}={q}{value}{q}\n")),
+                        None => out.push_str(&format!("{key}={value}\n")),
+                    }
+                }
+                EnvLine::Other(raw) => {
+                    out.push_str(raw);
+                    out.push('\n');
+                }
+            }
+        }
+        out
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChangeKind {
+    Added,
+    Changed,
+    Removed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvChange {
+    pub key: String,
+    pub kind: ChangeKind,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+impl EnvChange {
+    /// Only changed and removed keys can lose a value the user set by hand.
+    pub fn overwrites(&self) -> bool {
+        self.kind != ChangeKind::Added
+    }
+}
+
+/// Keys whose values are masked in the editor.
+pub fn is_secret_key(key: &str) -> bool {
+    let key = key.to_ascii_uppercase();
+    ["PASSWORD", "SECRET", "TOKEN", "API_KEY", "PRIVATE"]
+        .iter()
+        .any(|marker| key.contains(marker))
+}
+
+/// Suffixes of keys holding a count that must be above 0, such as
+/// `POSTGRES_MAX_CONNECTIONS`.
+const COUNT_SUFFIXES: [&str; 3] = ["_CONNECTIONS", "_TOKENS", "_DIMENSIONS"];
+
+/// Suffixes of counts where 0 turns the feature off, such as
+/// `POSTGRES_MAX_PARALLEL_WORKERS=0` disabling parallel query.
+const ZERO_COUNT_SUFFIXES: [&str; 2] = ["_WORKERS", "_PROCESSES"];
+
+/// Type checks inferred from the key name: ports, counts, URLs and IP
+/// addresses.
+pub fn validate(key: &str, value: &str) -> std::result::Result<(), String> {
+    let upper = key.to_ascii_uppercase();
+    if value.is_empty() {
+        return Ok(());
+    }
+    if upper.ends_with("PORT") && value.parse::<u16>().map_or(true, |p| p == 0) {
+        return Err(format!("{key} must be a port number (1-65535)"));
+    }
+    if COUNT_SUFFIXES.iter().any(|suffix| upper.ends_with(suffix))
+        && value.parse::<u32>().map_or(true, |n| n == 0)
+    {
+        return Err(format!("{key} must be a whole number above 0"));
+    }
+    if ZERO_COUNT_SUFFIXES
+        .iter()
+        .any(|suffix| upper.ends_with(suffix))
+        && value.parse::<u32>().is_err()
+    {
+        return Err(format!("{key} must be a whole number"));
+    }
+    if (upper.ends_with("_URL") || upper.ends_with("_URI")) && reqwest::Url::parse(value).is_err() {
+        return Err(format!("{key} must be a valid URL"));
+    }
+    if upper.ends_with("_IP") && value.parse::<IpAddr>().is_err() {
+        return Err(format!("{key} must be an IP address"));
+    }
+    Ok(())
+}
+
+/// Check a whole generated .env before it is written: every line is blank, a
+/// comment or `KEY=VALUE`, no key repeats, and values pass `validate`. With
+/// `allowed`, other keys are refused too (compose's own `COMPOSE_*` settings
+/// are always fine). Errors name the line.
+pub fn check(content: &str, allowed: Option<&[&str]>) -> std::result::Result<(), String> {
+    let mut seen: Vec<String> = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let number = number + 1;
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let body = trimmed.strip_prefix("export ").unwrap_or(trimmed);
+        let Some((key, raw)) = body.split_once('=') else {
+            return Err(format!("line {number}: expected KEY=VALUE, got '{line}'"));
+        };
+        let key = key.trim();
+        if !is_key(key) {
+            return Err(format!(
+                "line {number}: '{key}' is not a valid variable name"
+            ));
+        }
+        if seen.iter().any(|k| k == key) {
+            return Err(format!("line {number}: {key} is set twice"));
+        }
+        if let Some(allowed) = allowed
+            && !allowed.contains(&key)
+            && !key.starts_with("COMPOSE_")
+        {
+            return Err(format!(
+                "line {number}: {key} is not read by the stack (known: {})",
+                allowed.join(", ")
+            ));
+        }
+        validate(key, &unquote(raw.trim()).0).map_err(|e| format!("line {number}: {e}"))?;
+        seen.push(key.to_string());
+    }
+    Ok(())
+}
+
+/// Letters, digits, `_` and `.`, as accepted for .env keys.
+pub fn is_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+fn unquote(raw: &str) -> (String, Option<char>) {
+    for q in ['"', '\''] {
+        if raw.len() >= 2 && raw.starts_with(q) && raw.ends_with(q) {
+            return (raw[1..raw.len() - 1].to_string(), Some(q));
+        }
+    }
+    (raw.to_string(), None)
+}
+
+fn needs_quotes(value: &str) -> bool {
+    value.chars().any(|c| c.is_whitespace() || c == '#')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_preserves_comments_and_order() {
+        let content =
+            "# Server\nSERVER_IP=10.0.0.5\n\nDB_PASSWORD=\"p@ss word\"\nexport APP_PORT=8080\n";
+        let mut env = EnvFile::parse(content);
+        assert_eq!(env.get("DB_PASSWORD"), Some("p@ss word"));
+        env.set("SERVER_IP", "10.0.0.6");
+        env.set("NEW_KEY", "x");
+        assert_eq!(
+            env.render(),
+            "# Server\nSERVER_IP=10.0.0.6\n\nDB_PASSWORD=\"p@ss word\"\nexport APP_PORT=8080\nNEW_KEY=x\n"
+        );
+    }
+
+    #[test]
+    fn test_diff_reports_added_changed_removed() {
+        let current = EnvFile::parse("SERVER_IP=10.0.0.5\nAPP_PORT=9000\nCUSTOM=1\n");
+        let proposed = EnvFile::parse("SERVER_IP=10.0.0.6\nAPP_PORT=9000\nNEW=x\n");
+        let kinds: Vec<(String, ChangeKind)> = current
+            .diff(&proposed)
+            .into_iter()
+            .map(|c| (c.key, c.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("SERVER_IP".to_string(), ChangeKind::Changed),
+                ("CUSTOM".to_string(), ChangeKind::Removed),
+                ("NEW".to_string(), ChangeKind::Added),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_names_the_failing_line() {
+        let allowed = ["SERVER_IP", "APP_PORT"];
+        assert!(
+            check(
+                "# c\nSERVER_IP=10.0.0.5\nCOMPOSE_PROJECT_NAME=x\n",
+                Some(&allowed)
+            )
+            .is_ok()
+        );
+        assert_eq!(
+            check("SERVER_IP=10.0.0.5\nAPP_PORT=http\n", None).unwrap_err(),
+            "line 2: APP_PORT must be a port number (1-65535)"
+        );
+        assert_eq!(
+            check("SERVER_IP=1.2.3.4\nSERVER_IP=1.2.3.5\n", None).unwrap_err(),
+            "line 2: SERVER_IP is set twice"
+        );
+        assert!(check("OTHER=1\n", Some(&allowed)).is_err());
+        assert!(check("not a line\n", None).is_err());
+    }
+
+    #[test]
+    fn test_validate_by_key_name() {
+        assert!(validate("APP_PORT", "8080").is_ok());
+        assert!(validate("APP_PORT", "80a").is_err());
+        assert!(validate("APP_PORT", "70000").is_err());
+        assert!(validate("POSTGRES_MAX_CONNECTIONS", "100").is_ok());
+        assert!(validate("POSTGRES_MAX_CONNECTIONS", "0").is_err());
+        assert!(validate("POSTGRES_MAX_PARALLEL_WORKERS", "-2").is_err());
+        assert!(validate("POSTGRES_MAX_PARALLEL_WORKERS", "0").is_ok());
+        assert!(validate("SERVER_IP", "not-an-ip").is_err());
+        assert!(validate("PUBLIC_URL", "https://id.example").is_ok());
+        assert!(validate("PUBLIC_URL", "id.example").is_err());
+    }
+}
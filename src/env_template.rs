@@ -0,0 +1,104 @@
+// env_template.rs - the .env a stack starts from
+// Each stack embeds a template of its .env (`Stack::env_template`): the keys
+// the installer fills in for the host, SERVER_IP and the Postgres sizes, and
+// the ones it only gives a default, with comments saying what they are for.
+// Writing .env, for a new install or over an existing file, renders the
+// template and carries the current file's values over: template keys keep
+// what was set by hand, and keys the stack reads that other screens write
+// (SMTP, LDAP, an external database) stay. Keys the stack does not know are
+// left out, so the review screen shows them as removed.
+
+use std::path::Path;
+
+use color_eyre::Result;
+
+use crate::db_tuning::Tuning;
+use crate::env_file::EnvFile;
+use crate::settings::LogLevel;
+use crate::stack;
+
+/// The selected stack's .env for `ip`, with `current`'s values kept. Postgres
+/// is sized by `tuning` where `current` has no value; `log_level` replaces
+/// the log level keys when set.
+pub fn generate(
+    current: &EnvFile,
+    ip: &str,
+    tuning: Option<&Tuning>,
+    log_level: Option<LogLevel>,
+) -> EnvFile {
+    let stack = stack::current();
+    let mut env = EnvFile::parse(stack.env_template);
+    if let Some(tuning) = tuning {
+        for (key, value) in tuning.env_pairs() {
+            env.set(key, &value);
+        }
+    }
+    for (key, value) in current.entries() {
+        let known = env.get(&key).is_some()
+            || stack.env_keys.contains(&key.as_str())
+            || key.starts_with("COMPOSE_");
+        if known {
+            env.set(&key, &value);
+        }
+    }
+    env.set("SERVER_IP", ip);
+    if log_level.is_some() {
+        LogLevel::apply(log_level, &mut env);
+    }
+    env
+}
+
+/// Generate .env under `root` for `ip` and write it, keeping the values of
+/// the file already there.
+pub fn write(
+    root: &Path,
+    ip: &str,
+    tuning: Option<&Tuning>,
+    log_level: Option<LogLevel>,
+) -> Result<()> {
+    let path = root.join(".env");
+    let current = EnvFile::load(&path).unwrap_or_default();
+    generate(&current, ip, tuning, log_level).save(&path)
+}
+
+/// True when .env under `root` has a non-empty SERVER_IP.
+pub fn has_server_ip(root: &Path) -> bool {
+    EnvFile::load(&root.join(".env"))
+        .is_ok_and(|env| env.get("SERVER_IP").is_some_and(|ip| !ip.is_empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db_tuning::Host;
+    use crate::env_file::{self, ChangeKind};
+
+    #[test]
+    fn test_generate_keeps_known_values_and_drops_unknown_keys() {
+        let tuning = Tuning::for_host(Host {
+            memory: 8 << 30,
+            cpus: 4,
+        });
+        let fresh = generate(&EnvFile::default(), "10.0.0.5", Some(&tuning), None);
+        assert_eq!(fresh.get("SERVER_IP"), Some("10.0.0.5"));
+        assert_eq!(fresh.get("POSTGRES_SHARED_BUFFERS"), Some("2GB"));
+        assert_eq!(fresh.get("KC_THEME_WELCOME"), Some("keycloak"));
+        assert!(fresh.render().starts_with("# .env — NQRust Identity\n"));
+        assert!(env_file::check(&fresh.render(), Some(stack::current().env_keys)).is_ok());
+
+        let current = EnvFile::parse(
+            "SERVER_IP=10.0.0.4\nPOSTGRES_WORK_MEM=16MB\nSMTP_HOST=mail.example\n\
+             OPENAI_API_KEY=sk-old\n",
+        );
+        let proposed = generate(&current, "10.0.0.5", Some(&tuning), Some(LogLevel::Debug));
+        assert_eq!(proposed.get("POSTGRES_WORK_MEM"), Some("16MB"));
+        assert_eq!(proposed.get("SMTP_HOST"), Some("mail.example"));
+        assert_eq!(proposed.get("LOG_LEVEL"), Some("debug"));
+        let changes = current.diff(&proposed);
+        let kind = |key: &str| changes.iter().find(|c| c.key == key).map(|c| c.kind);
+        assert_eq!(kind("SERVER_IP"), Some(ChangeKind::Changed));
+        assert_eq!(kind("OPENAI_API_KEY"), Some(ChangeKind::Removed));
+        assert_eq!(kind("POSTGRES_WORK_MEM"), None);
+        assert_eq!(kind("KC_THEME_DEFAULT"), Some(ChangeKind::Added));
+    }
+}
@@ -0,0 +1,286 @@
+// external_db.rs - running Keycloak against an existing PostgreSQL
+// With EXTERNAL_DB_HOST set in .env the installer writes a compose file
+// without the bundled identity-db service and its volume, pointing Keycloak's
+// JDBC URL at the configured server instead. The generated file starts with
+// `MARKER`, so switching back to the bundled database restores the embedded
+// one. The connection is checked with a `SELECT version()` from a throwaway
+// postgres container on the Docker host, which is where Keycloak will connect
+// from, so no Postgres client is needed on this machine.
+
+use std::path::Path;
+
+use color_eyre::{Result, eyre::eyre};
+use serde_yaml::Value;
+
+use crate::compose_override::ComposeOverride;
+use crate::env_file::EnvFile;
+use crate::runner::{Cmd, CommandRunner};
+use crate::secrets;
+use crate::settings::Settings;
+use crate::stack::{self, StackService};
+
+pub const HOST: &str = "EXTERNAL_DB_HOST";
+pub const PORT: &str = "EXTERNAL_DB_PORT";
+pub const NAME: &str = "EXTERNAL_DB_NAME";
+pub const USER: &str = "EXTERNAL_DB_USER";
+pub const PASSWORD: &str = "EXTERNAL_DB_PASSWORD";
+pub const SSL_MODE: &str = "EXTERNAL_DB_SSLMODE";
+
+/// First line of a compose file generated for an external database.
+pub const MARKER: &str = "# Generated by nqrust-identity installer for an external database";
+/// Compose service and named volume of the bundled database.
+pub const DB_SERVICE: &str = "postgres";
+const DB_VOLUME: &str = "identity_pgdata";
+/// Runs `psql` for the connection test; the image the stack already uses.
+const CLIENT_IMAGE: &str = "postgres:16-alpine";
+
+/// libpq / JDBC `sslmode`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SslMode {
+    Disable,
+    /// TLS when the server offers it.
+    #[default]
+    Prefer,
+    /// TLS or no connection; the certificate is not verified.
+    Require,
+}
+
+impl SslMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SslMode::Disable => "disable",
+            SslMode::Prefer => "prefer",
+            SslMode::Require => "require",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_ascii_lowercase().as_str() {
+            "disable" | "off" => Ok(SslMode::Disable),
+            "" | "prefer" => Ok(SslMode::Prefer),
+            "require" | "on" => Ok(SslMode::Require),
+            other => Err(format!(
+                "'{other}' is not one of disable, prefer or require"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalDb {
+    pub host: String,
+    pub port: u16,
+    pub database: String,
+    pub user: String,
+    pub password: String,
+    pub ssl_mode: SslMode,
+}
+
+impl ExternalDb {
+    /// The server configured in .env; `None` without EXTERNAL_DB_HOST, which
+    /// means the bundled database.
+    pub fn from_env(env: &EnvFile) -> Option<Self> {
+        let get = |key: &str| env.get(key).unwrap_or_default().to_string();
+        let host = get(HOST);
+        if host.is_empty() {
+            return None;
+        }
+        Some(Self {
+            host,
+            port: get(PORT).parse().unwrap_or(5432),
+            database: get(NAME),
+            user: get(USER),
+            password: get(PASSWORD),
+            ssl_mode: SslMode::parse(&get(SSL_MODE)).unwrap_or_default(),
+        })
+    }
+
+    pub fn load(root: &Path) -> Option<Self> {
+        Self::from_env(&EnvFile::load(&root.join(".env")).unwrap_or_default())
+    }
+
+    pub fn write_env(&self, env: &mut EnvFile) {
+        env.set(HOST, &self.host);
+        env.set(PORT, &self.port.to_string());
+        env.set(NAME, &self.database);
+        env.set(USER, &self.user);
+        env.set(PASSWORD, &self.password);
+        env.set(SSL_MODE, self.ssl_mode.as_str());
+    }
+
+    /// `host:port/database`, for logs and the Success screen.
+    pub fn address(&self) -> String {
+        format!("{}:{}/{}", self.host, self.port, self.database)
+    }
+}
+
+/// Go back to the bundled database; the other keys are kept for next time.
+pub fn disable(env: &mut EnvFile) {
+    env.set(HOST, "");
+}
+
+/// Run `SELECT version()` against the server from the Docker host. Returns
+/// the server's version, e.g. `PostgreSQL 15.6`.
+pub async fn test(runner: &dyn CommandRunner, db: &ExternalDb) -> Result<String> {
+    // Passed by name so the password stays out of the process list.
+    let vars = [
+        ("PGHOST", db.host.clone()),
+        ("PGPORT", db.port.to_string()),
+        ("PGDATABASE", db.database.clone()),
+        ("PGUSER", db.user.clone()),
+        ("PGPASSWORD", db.password.clone()),
+        ("PGSSLMODE", db.ssl_mode.as_str().to_string()),
+        ("PGCONNECT_TIMEOUT", "10".to_string()),
+    ];
+    let mut cmd = Cmd::new("docker").args(["run", "--rm"]);
+    for (key, value) in &vars {
+        cmd = cmd.args(["-e", key]).env(key, value);
+    }
+    let cmd = cmd.args([CLIENT_IMAGE, "psql", "-tAc", "SELECT version()"]);
+    let output = runner.output(&cmd).await?;
+    match output.value() {
+        Some(version) => Ok(version.split(" on ").next().unwrap_or(&version).to_string()),
+        None => Err(eyre!(
+            "Could not query {}: {}",
+            db.address(),
+            output.stderr.trim()
+        )),
+    }
+}
+
+/// The bundled compose file without identity-db, Keycloak pointed at the
+/// EXTERNAL_DB_* variables.
+pub fn compose(base: &str) -> Result<String> {
+    let mut doc: Value = serde_yaml::from_str(base)?;
+    let services = doc["services"]
+        .as_mapping_mut()
+        .ok_or_else(|| eyre!("The compose file has no services"))?;
+    services.shift_remove(DB_SERVICE);
+    for (_, service) in services.iter_mut() {
+        if let Some(depends_on) = service
+            .get_mut("depends_on")
+            .and_then(Value::as_mapping_mut)
+        {
+            depends_on.shift_remove(DB_SERVICE);
+            if depends_on.is_empty()
+                && let Some(service) = service.as_mapping_mut()
+            {
+                service.shift_remove("depends_on");
+            }
+        }
+    }
+    let identity = doc["services"]["identity"]["environment"]
+        .as_mapping_mut()
+        .ok_or_else(|| eyre!("The identity service has no environment"))?;
+    for (key, value) in [
+        (
+            "KC_DB_URL",
+            format!(
+                "jdbc:postgresql://${{{HOST}}}:${{{PORT}:-5432}}/${{{NAME}}}?sslmode=${{{SSL_MODE}:-prefer}}"
+            ),
+        ),
+        ("KC_DB_USERNAME", format!("${{{USER}}}")),
+        ("KC_DB_PASSWORD", format!("${{{PASSWORD}}}")),
+    ] {
+        identity.insert(key.into(), value.into());
+    }
+    if let Some(volumes) = doc.get_mut("volumes").and_then(Value::as_mapping_mut) {
+        volumes.shift_remove(DB_VOLUME);
+        if volumes.is_empty()
+            && let Some(doc) = doc.as_mapping_mut()
+        {
+            doc.shift_remove("volumes");
+        }
+    }
+    Ok(format!("{MARKER}\n{}", serde_yaml::to_string(&doc)?))
+}
+
+/// Drop what the override would add for the bundled database: its service
+/// and the database password secret, which Keycloak must not read over the
+/// external server's password.
+pub fn detach(doc: &mut ComposeOverride) {
+    doc.services.remove(DB_SERVICE);
+    doc.secrets.remove(secrets::DB_PASSWORD);
+    let identity = doc.service("identity");
+    identity.secrets.retain(|name| name != secrets::DB_PASSWORD);
+    identity.environment.remove("KC_DB_PASSWORD");
+    if identity.entrypoint.is_some() {
+        identity.entrypoint = Some(secrets::kc_entrypoint(&[(
+            "KC_BOOTSTRAP_ADMIN_PASSWORD",
+            secrets::ADMIN_PASSWORD,
+        )]));
+    }
+}
+
+/// The stack's services that run under `root`, excluding identity-db when an
+/// external database is configured and any services disabled on the Services
+/// screen.
+pub fn services(root: &Path, settings: &Settings) -> Vec<&'static StackService> {
+    let external = ExternalDb::load(root).is_some();
+    stack::current()
+        .services
+        .iter()
+        .filter(|service| !(external && service.service == DB_SERVICE))
+        .filter(|service| !settings.skips(service.service))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::{CmdOutput, MockRunner};
+
+    #[test]
+    fn test_compose_omits_bundled_database() {
+        let rendered = compose(stack::IDENTITY.bundle[0].1).unwrap();
+        assert!(rendered.starts_with(MARKER));
+        let doc: Value = serde_yaml::from_str(&rendered).unwrap();
+        assert!(doc["services"]["postgres"].is_null());
+        assert!(doc["services"]["identity"]["depends_on"].is_null());
+        assert!(doc["volumes"].is_null());
+        assert_eq!(
+            doc["services"]["identity"]["environment"]["KC_DB_USERNAME"],
+            "${EXTERNAL_DB_USER}"
+        );
+        assert!(
+            doc["services"]["identity"]["environment"]["KC_DB_URL"]
+                .as_str()
+                .unwrap()
+                .starts_with("jdbc:postgresql://${EXTERNAL_DB_HOST}:${EXTERNAL_DB_PORT:-5432}/")
+        );
+        assert!(!doc["services"]["caddy"]["depends_on"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_env_round_trip_and_connection_test() {
+        let mut env = EnvFile::parse("SERVER_IP=10.0.0.5\n");
+        assert_eq!(ExternalDb::from_env(&env), None);
+        let db = ExternalDb {
+            host: "db.corp.local".into(),
+            port: 5433,
+            database: "keycloak".into(),
+            user: "keycloak".into(),
+            password: "s3cret".into(),
+            ssl_mode: SslMode::Require,
+        };
+        db.write_env(&mut env);
+        assert_eq!(ExternalDb::from_env(&env), Some(db.clone()));
+        disable(&mut env);
+        assert_eq!(ExternalDb::from_env(&env), None);
+
+        let runner = MockRunner::new().on(
+            "docker run",
+            CmdOutput::ok("PostgreSQL 15.6 on x86_64-pc-linux-musl, compiled by gcc\n"),
+        );
+        assert_eq!(test(&runner, &db).await.unwrap(), "PostgreSQL 15.6");
+        assert!(!runner.lines()[0].contains("s3cret"));
+
+        let runner = MockRunner::new().on(
+            "docker run",
+            CmdOutput::failed("psql: error: password authentication failed"),
+        );
+        let err = test(&runner, &db).await.unwrap_err().to_string();
+        assert!(err.contains("db.corp.local:5433/keycloak"));
+        assert!(err.contains("password authentication failed"));
+    }
+}
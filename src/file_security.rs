@@ -0,0 +1,162 @@
+// file_security.rs - who can read and replace the installer's secret files
+// `.env` holds the admin and database passwords, a registry token file
+// holds a GitHub/registry credential, certs/server.key the TLS private key
+// and each `pg_dump` archive a copy of the database with its credential
+// hashes and client secrets, so all of them are written with mode 0600.
+// That does not cover a file created by hand or by an older version, a
+// world-writable directory where anyone can swap the file out, or a project
+// directory that is a git checkout where `git add .` would commit it. The
+// Confirmation screen shows what this finds as its "Security check".
+// `git` runs locally: the files live on this machine even with `--remote`,
+// and `RemoteRunner` only sends docker and compose to the server.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::registry;
+use crate::runner::{Cmd, CommandRunner};
+use crate::settings::Settings;
+
+/// The secret files that exist and what is wrong with them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Report {
+    pub checked: Vec<PathBuf>,
+    /// One sentence per problem, with the fix.
+    pub findings: Vec<String>,
+}
+
+/// Check `.env` and the TLS key under `root`, the registry token files
+/// saved in place of a keyring and the database backups.
+pub async fn check(runner: &dyn CommandRunner, root: &Path, settings: &Settings) -> Report {
+    let mut files = vec![root.join(".env"), root.join("certs/server.key")];
+    for registry in registry::configured(settings) {
+        files.extend(registry.credential_files());
+    }
+    let backups = settings
+        .backups
+        .dir
+        .clone()
+        .unwrap_or_else(|| root.join("backups"));
+    if let Ok(entries) = fs::read_dir(backups) {
+        let mut dumps: Vec<PathBuf> = entries.filter_map(|e| e.ok().map(|e| e.path())).collect();
+        dumps.sort();
+        files.extend(dumps);
+    }
+    let mut report = Report::default();
+    for path in files.into_iter().filter(|path| path.is_file()) {
+        report.findings.extend(inspect(&path));
+        report.findings.extend(git_exposure(runner, &path).await);
+        report.checked.push(path);
+    }
+    report
+}
+
+/// Mode, owner and parent directory of one file.
+#[cfg(unix)]
+fn inspect(path: &Path) -> Vec<String> {
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+    let mut findings = Vec::new();
+    let Ok(meta) = path.metadata() else {
+        return findings;
+    };
+    let mode = meta.permissions().mode() & 0o777;
+    if mode & 0o077 != 0 {
+        findings.push(format!(
+            "{} is readable by other users (mode {mode:o}): chmod 600 {0}",
+            path.display()
+        ));
+    }
+    // SAFETY: geteuid(2) cannot fail and has no side effects.
+    let uid = unsafe { libc::geteuid() };
+    if meta.uid() != uid {
+        findings.push(format!(
+            "{} is owned by uid {}, not the user running the installer (uid {uid}): \
+             chown it to this user",
+            path.display(),
+            meta.uid()
+        ));
+    }
+    if let Some(dir) = path.parent()
+        && let Ok(dir_meta) = dir.metadata()
+        && dir_meta.permissions().mode() & 0o002 != 0
+    {
+        findings.push(format!(
+            "{} is world-writable, so anyone can replace {}: chmod o-w {0}",
+            dir.display(),
+            path.file_name().unwrap_or_default().to_string_lossy()
+        ));
+    }
+    findings
+}
+
+#[cfg(not(unix))]
+fn inspect(_path: &Path) -> Vec<String> {
+    Vec::new()
+}
+
+/// A finding when `path` is inside a git working tree and not ignored.
+async fn git_exposure(runner: &dyn CommandRunner, path: &Path) -> Option<String> {
+    let dir = path.parent()?;
+    let name = path.file_name()?.to_str()?;
+    let git = |args: &[&str]| runner.output(&Cmd::new("git").arg("-C").arg(dir).args(args));
+    let inside = git(&["rev-parse", "--is-inside-work-tree"]).await.ok()?;
+    if inside.value().as_deref() != Some("true") {
+        return None;
+    }
+    // Exit status 0 means ignored; 1 means git would pick the file up.
+    let ignored = git(&["check-ignore", "-q", name]).await.ok()?;
+    (!ignored.success).then(|| {
+        format!(
+            "{} is inside a git working tree and not in .gitignore: add {name} to .gitignore",
+            path.display()
+        )
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::runner::{CmdOutput, MockRunner};
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_flags_open_modes_and_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = dir.path().join(".env");
+        registry::write_private(&env, "SERVER_IP=10.0.0.5\n").unwrap();
+        assert!(inspect(&env).is_empty());
+
+        fs::set_permissions(&env, fs::Permissions::from_mode(0o644)).unwrap();
+        fs::set_permissions(dir.path(), fs::Permissions::from_mode(0o777)).unwrap();
+        let findings = inspect(&env);
+        assert_eq!(findings.len(), 2);
+        assert!(findings[0].contains("mode 644"));
+        assert!(findings[1].contains("world-writable"));
+    }
+
+    #[tokio::test]
+    async fn test_flags_files_git_would_commit() {
+        let dir = tempfile::tempdir().unwrap();
+        registry::write_private(&dir.path().join(".env"), "SERVER_IP=10.0.0.5\n").unwrap();
+        let runner = MockRunner::new()
+            .on("rev-parse", CmdOutput::ok("true\n"))
+            .on("check-ignore", CmdOutput::failed(""));
+        let report = check(&runner, dir.path(), &Settings::default()).await;
+        assert_eq!(report.checked[0], dir.path().join(".env"));
+        assert!(report.findings[0].contains("not in .gitignore"));
+        assert!(runner.lines()[1].ends_with("check-ignore -q .env"));
+    }
+
+    #[tokio::test]
+    async fn test_checks_database_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let dump = dir.path().join("backups/identity-20260101-000000.dump");
+        fs::create_dir(dir.path().join("backups")).unwrap();
+        fs::write(&dump, "PGDMP").unwrap();
+        fs::set_permissions(&dump, fs::Permissions::from_mode(0o644)).unwrap();
+        let report = check(&MockRunner::new(), dir.path(), &Settings::default()).await;
+        assert!(report.checked.contains(&dump));
+        assert!(report.findings.iter().any(|f| f.contains("mode 644")));
+    }
+}
@@ -0,0 +1,213 @@
+// firewall.rs - opening the published ports in the host firewall
+// Many "can't reach Keycloak" reports come down to a host firewall that drops
+// port 8008. After an install the installer looks for an active firewalld or
+// ufw and, when the entry service's ports are not open yet, offers the exact
+// commands that open them. Nothing runs until the user confirms; the commands
+// go through `sudo -n` like the systemd unit, and are printed for pasting
+// when sudo wants a password.
+
+use std::fs;
+use std::path::Path;
+
+use color_eyre::eyre::Result;
+
+use crate::runner::{Cmd, CommandRunner};
+use crate::stack;
+use crate::{doctor, systemd};
+
+const UFW_CONF: &str = "/etc/ufw/ufw.conf";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Firewall {
+    Firewalld,
+    Ufw,
+}
+
+impl Firewall {
+    pub fn label(self) -> &'static str {
+        match self {
+            Firewall::Firewalld => "firewalld",
+            Firewall::Ufw => "ufw",
+        }
+    }
+}
+
+/// Ports an active firewall does not let through yet, and how to open them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FirewallOffer {
+    pub firewall: Firewall,
+    pub ports: Vec<u16>,
+    /// Commands to run as root, in order.
+    pub commands: Vec<Vec<String>>,
+}
+
+impl FirewallOffer {
+    /// `8008/tcp, 8443/tcp`
+    pub fn port_list(&self) -> String {
+        self.ports
+            .iter()
+            .map(|port| format!("{port}/tcp"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// The commands as typed at a shell.
+    pub fn command_lines(&self) -> Vec<String> {
+        self.commands
+            .iter()
+            .map(|command| format!("sudo {}", command.join(" ")))
+            .collect()
+    }
+}
+
+/// Look for an active firewall that would block the entry service's ports.
+/// `None` when there is none, or every port is already open.
+pub async fn detect(runner: &dyn CommandRunner, root: &Path) -> Option<FirewallOffer> {
+    let stack = stack::current();
+    let ports =
+        doctor::published_ports(&root.join(stack.compose_file()), Some(stack.entry_service));
+    if ports.is_empty() {
+        return None;
+    }
+
+    let state = runner
+        .output(&Cmd::new("firewall-cmd").arg("--state"))
+        .await
+        .ok();
+    let firewall = if state.is_some_and(|state| state.value().as_deref() == Some("running")) {
+        Firewall::Firewalld
+    } else if ufw_enabled(&fs::read_to_string(UFW_CONF).unwrap_or_default()) {
+        Firewall::Ufw
+    } else {
+        return None;
+    };
+
+    let mut closed = Vec::new();
+    for port in ports {
+        if !is_open(runner, firewall, port).await {
+            closed.push(port);
+        }
+    }
+    (!closed.is_empty()).then(|| offer(firewall, closed))
+}
+
+/// Whether `port` is let through. Unknown counts as closed: opening an open
+/// port is harmless with both firewalls.
+async fn is_open(runner: &dyn CommandRunner, firewall: Firewall, port: u16) -> bool {
+    let cmd = match firewall {
+        Firewall::Firewalld => Cmd::new("firewall-cmd").arg(format!("--query-port={port}/tcp")),
+        // `ufw status` needs root; without passwordless sudo nothing is known.
+        Firewall::Ufw => Cmd::new("sudo").args(["-n", "ufw", "status"]),
+    };
+    let Ok(output) = runner.output(&cmd).await else {
+        return false;
+    };
+    match firewall {
+        Firewall::Firewalld => output.value().as_deref() == Some("yes"),
+        Firewall::Ufw => output.success && ufw_allows(&output.stdout, port),
+    }
+}
+
+fn offer(firewall: Firewall, ports: Vec<u16>) -> FirewallOffer {
+    let owned = |words: &[&str]| words.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+    let mut commands: Vec<Vec<String>> = ports
+        .iter()
+        .map(|port| match firewall {
+            Firewall::Firewalld => owned(&[
+                "firewall-cmd",
+                "--permanent",
+                &format!("--add-port={port}/tcp"),
+            ]),
+            Firewall::Ufw => owned(&["ufw", "allow", &format!("{port}/tcp")]),
+        })
+        .collect();
+    // Permanent firewalld rules only apply after a reload.
+    if firewall == Firewall::Firewalld {
+        commands.push(owned(&["firewall-cmd", "--reload"]));
+    }
+    FirewallOffer {
+        firewall,
+        ports,
+        commands,
+    }
+}
+
+/// Run the offer's commands. Returns the ones that ran; stops at the first
+/// failure.
+pub async fn open(runner: &dyn CommandRunner, offer: &FirewallOffer) -> Result<Vec<String>> {
+    let mut done = Vec::new();
+    for command in &offer.commands {
+        let words: Vec<&str> = command.iter().map(String::as_str).collect();
+        systemd::run_privileged(runner, &words).await?;
+        done.push(command.join(" "));
+    }
+    Ok(done)
+}
+
+/// `ENABLED=yes` in ufw.conf.
+fn ufw_enabled(conf: &str) -> bool {
+    conf.lines()
+        .any(|line| line.trim().replace('"', "") == "ENABLED=yes")
+}
+
+/// A rule in `ufw status` output that allows `port` over TCP.
+fn ufw_allows(status: &str, port: u16) -> bool {
+    status.lines().any(|line| {
+        let mut words = line.split_whitespace();
+        let rule = words.next().unwrap_or_default();
+        let allowed = words.next() == Some("ALLOW");
+        allowed && (rule == port.to_string() || rule == format!("{port}/tcp"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::{CmdOutput, MockRunner};
+
+    #[tokio::test]
+    async fn test_offers_closed_ports_for_firewalld() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join(stack::current().compose_file()),
+            "services:\n  postgres:\n    ports:\n      - \"5436:5432\"\n  caddy:\n    ports:\n      \
+             - \"8008:443\"\n      - \"8009:80\"\n",
+        )
+        .unwrap();
+        let runner = MockRunner::new()
+            .on("firewall-cmd --state", CmdOutput::ok("running\n"))
+            .on("--query-port=8009/tcp", CmdOutput::ok("yes\n"))
+            .on("--query-port", CmdOutput::failed(""));
+
+        let offer = detect(&runner, dir.path()).await.unwrap();
+        assert_eq!(offer.firewall, Firewall::Firewalld);
+        assert_eq!(offer.port_list(), "8008/tcp");
+        assert_eq!(
+            offer.command_lines(),
+            [
+                "sudo firewall-cmd --permanent --add-port=8008/tcp",
+                "sudo firewall-cmd --reload"
+            ]
+        );
+
+        assert_eq!(open(&runner, &offer).await.unwrap().len(), 2);
+        assert_eq!(
+            runner.lines().last().unwrap(),
+            "sudo -n firewall-cmd --reload"
+        );
+    }
+
+    #[test]
+    fn test_reads_ufw_config_and_status() {
+        assert!(ufw_enabled("# comment\nENABLED=yes\nLOGLEVEL=low\n"));
+        assert!(!ufw_enabled("ENABLED=no\n"));
+
+        let status = "Status: active\n\nTo                         Action      From\n\
+                      --                         ------      ----\n\
+                      22/tcp                     ALLOW       Anywhere\n\
+                      8008                       ALLOW       Anywhere\n";
+        assert!(ufw_allows(status, 8008));
+        assert!(ufw_allows(status, 22));
+        assert!(!ufw_allows(status, 443));
+    }
+}
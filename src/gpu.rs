@@ -0,0 +1,83 @@
+// gpu.rs - NVIDIA GPU availability for services that run inference
+// Containers only see a GPU when the host has the driver (`nvidia-smi`
+// works) and Docker has the NVIDIA runtime; without either, compose still
+// starts the service and inference silently runs on the CPU.
+
+use crate::runner::{Cmd, CommandRunner};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Gpu {
+    /// Names reported by `nvidia-smi`; empty without a driver or GPU.
+    pub names: Vec<String>,
+    /// `nvidia` is among the runtimes `docker info` lists.
+    pub docker_runtime: bool,
+}
+
+impl Gpu {
+    /// Containers can be given the GPU.
+    pub fn usable(&self) -> bool {
+        !self.names.is_empty() && self.docker_runtime
+    }
+
+    /// Why containers cannot use a GPU, or None when they can.
+    pub fn problem(&self) -> Option<&'static str> {
+        match (self.names.is_empty(), self.docker_runtime) {
+            (true, _) => Some("no NVIDIA GPU found (nvidia-smi failed or listed none)"),
+            (false, false) => {
+                Some("Docker has no nvidia runtime (install nvidia-container-toolkit)")
+            }
+            (false, true) => None,
+        }
+    }
+}
+
+pub async fn detect(runner: &dyn CommandRunner) -> Gpu {
+    let smi = Cmd::new("nvidia-smi").args(["--query-gpu=name", "--format=csv,noheader"]);
+    let names = match runner.output(&smi).await {
+        Ok(output) => output
+            .value()
+            .map(|names| names.lines().map(|n| n.trim().to_string()).collect())
+            .unwrap_or_default(),
+        Err(_) => Vec::new(),
+    };
+    let info = Cmd::new("docker").args(["info", "--format", "{{json .Runtimes}}"]);
+    let docker_runtime = runner
+        .output(&info)
+        .await
+        .ok()
+        .and_then(|output| output.value())
+        .is_some_and(|runtimes| runtimes.contains("\"nvidia\""));
+    Gpu {
+        names,
+        docker_runtime,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::{CmdOutput, MockRunner};
+
+    #[tokio::test]
+    async fn test_gpu_needs_driver_and_docker_runtime() {
+        let runner = MockRunner::new()
+            .on("nvidia-smi", CmdOutput::ok("NVIDIA L4\nNVIDIA L4\n"))
+            .on(
+                "docker info",
+                CmdOutput::ok(r#"{"io.containerd.runc.v2":{},"nvidia":{"path":"nvidia-container-runtime"},"runc":{}}"#),
+            );
+        let gpu = detect(&runner).await;
+        assert_eq!(gpu.names, ["NVIDIA L4", "NVIDIA L4"]);
+        assert!(gpu.usable());
+
+        let runner = MockRunner::new()
+            .on("nvidia-smi", CmdOutput::ok("NVIDIA L4\n"))
+            .on("docker info", CmdOutput::ok(r#"{"runc":{}}"#));
+        let gpu = detect(&runner).await;
+        assert!(!gpu.usable());
+        assert!(gpu.problem().unwrap().contains("nvidia-container-toolkit"));
+
+        let gpu = detect(&MockRunner::new().on("nvidia-smi", CmdOutput::failed("not found"))).await;
+        assert!(gpu.problem().unwrap().contains("no NVIDIA GPU"));
+    }
+}
@@ -0,0 +1,541 @@
+// Non-interactive install / uninstall, used by `--headless`, `--uninstall`
+// and the end-to-end test harness. Mirrors the TUI flow step for step but
+// prints progress to stdout (text, or JSON events with `--output json`) and
+// fails with a non-zero exit instead of showing the error screen.
+
+use std::time::{Duration, Instant};
+
+use color_eyre::{Result, eyre::eyre};
+
+use crate::cli::Cli;
+use crate::db_tuning::{self, Tuning};
+use crate::external_db::{self, ExternalDb};
+use crate::install_log::{self, Level, logln};
+use crate::manifest::InstallManifest;
+use crate::output::{self, Event};
+use crate::progress::InstallProgress;
+use crate::registry::{self, Registry};
+use crate::remote::{self, RemoteTarget};
+use crate::rollback::{self, HealthOutcome};
+use crate::runner::{Cmd, CommandRunner};
+use crate::settings::{ComposeTimeouts, HookEvent, Settings};
+use crate::updates::fetch_latest_identity_tag;
+use crate::{
+    build_info, compose_lint, compose_override, disk, env_template, firewall, hooks, http, ldap,
+    secrets, selinux, smtp, stack, token_check, utils, verify,
+};
+
+/// How long `--headless` waits for Keycloak to report healthy.
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// The step currently running, for `step_*` events and error codes.
+struct Steps {
+    current: Option<(&'static str, Instant)>,
+    index: usize,
+    total: usize,
+    /// Replaces the generic `<step>_failed` code for the error being returned.
+    code: Option<&'static str>,
+}
+
+impl Steps {
+    fn new(total: usize) -> Self {
+        Self {
+            current: None,
+            index: 0,
+            total,
+            code: None,
+        }
+    }
+
+    fn begin(&mut self, step: &'static str) {
+        self.finish();
+        self.index += 1;
+        output::emit(&Event::StepStarted {
+            step,
+            index: self.index,
+            total: self.total,
+        });
+        self.current = Some((step, Instant::now()));
+    }
+
+    fn finish(&mut self) {
+        if let Some((step, started)) = self.current.take() {
+            output::emit(&Event::StepFinished {
+                step,
+                duration_ms: started.elapsed().as_millis() as u64,
+            });
+        }
+    }
+
+    fn error_code(&self) -> String {
+        match (self.code, self.current) {
+            (Some(code), _) => code.to_string(),
+            (None, Some((step, _))) => format!("{step}_failed"),
+            (None, None) => "install_failed".to_string(),
+        }
+    }
+}
+
+pub async fn install(cli: &Cli) -> Result<()> {
+    let mut settings = Settings::load();
+    let log_path = install_log::start();
+    // prepare, login, [pull], start, health
+    let mut steps = Steps::new(if cli.airgapped() { 4 } else { 5 });
+    let result = run_install(cli, &mut settings, &mut steps).await;
+    match &result {
+        Ok(_) => steps.finish(),
+        Err(e) => {
+            let message = format!("{e:#}");
+            install_log::write(Level::Error, &message);
+            output::emit(&Event::Error {
+                code: &steps.error_code(),
+                message: &message,
+            });
+        }
+    }
+    if let Some(path) = &log_path
+        && !output::is_json()
+    {
+        println!("📄 Install log: {}", path.display());
+    }
+    output::emit(&Event::Finished {
+        success: result.is_ok(),
+        tag: result.as_ref().ok().map(|(tag, _)| tag.as_str()),
+        url: result.as_ref().ok().map(|(_, url)| url.as_str()),
+        log_path: log_path.as_deref(),
+    });
+    let client = http::client(cli.airgapped(), &settings.proxy);
+    let runner = remote::runner(cli.remote().as_ref());
+    match &result {
+        Ok((tag, _)) => hooks::fire(
+            runner,
+            &settings.hooks,
+            &client,
+            HookEvent::InstallSuccess,
+            &format!("identity {tag} started"),
+        ),
+        Err(e) => hooks::fire(
+            runner,
+            &settings.hooks,
+            &client,
+            HookEvent::InstallFailure,
+            &e.to_string(),
+        ),
+    }
+    result.map(|_| ())
+}
+
+/// Returns the image tag and URL of the started stack.
+async fn run_install(
+    cli: &Cli,
+    settings: &mut Settings,
+    steps: &mut Steps,
+) -> Result<(String, String)> {
+    let root = utils::project_root();
+    let airgapped = cli.airgapped();
+    let runner = remote::runner(cli.remote().as_ref());
+    let runner = runner.as_ref();
+    logln!(
+        "ℹ️  {} {} (git {}, {}, {})",
+        build_info::NAME,
+        build_info::VERSION,
+        build_info::GIT_SHA,
+        build_info::mode(airgapped),
+        build_info::platform()
+    );
+    logln!("📦 Stack: {}", crate::stack::current().display_name);
+    logln!("📁 Project directory: {}", root.display());
+
+    steps.begin("prepare");
+    utils::ensure_compose_bundle(&root)?;
+
+    let ip = cli
+        .server_ip
+        .clone()
+        .or_else(|| cli.remote().as_ref().and_then(RemoteTarget::address))
+        .unwrap_or_else(utils::detect_ip);
+    if !(root.join("certs/server.crt").exists() && root.join("certs/server.key").exists()) {
+        logln!("🔐 Generating SSL certificate for {ip}...");
+        utils::generate_ssl_cert(&ip)?;
+    }
+    if !env_template::has_server_ip(&root) {
+        logln!("📝 Writing SERVER_IP={ip} to .env");
+        let host = db_tuning::detect(runner).await;
+        if let Some(host) = host {
+            logln!(
+                "🐘 Sizing Postgres for {} MiB of memory and {} CPUs",
+                host.memory >> 20,
+                host.cpus
+            );
+        }
+        env_template::write(
+            &root,
+            &ip,
+            host.map(Tuning::for_host).as_ref(),
+            settings.advanced.log_level,
+        )?;
+    }
+
+    secrets::materialize(
+        runner,
+        &root,
+        &settings.secrets,
+        &http::client(airgapped, &settings.proxy),
+    )
+    .await?;
+    for line in selinux::check(runner, settings).await {
+        logln!("{line}");
+    }
+    compose_override::sync(&root, settings, airgapped)?;
+    let compose_args = compose_override::compose_file_args(&root);
+    let compose_cmd = utils::detect_compose_command(runner).await?;
+    let lint = compose_lint::check(runner, &compose_cmd, &compose_args, &root).await;
+    for line in lint.lines() {
+        logln!("{line}");
+    }
+    if !lint.is_ok() {
+        steps.code = Some("compose_invalid");
+        return Err(eyre!(
+            "The compose files have {} problem(s); nothing was pulled or started",
+            lint.errors.len()
+        ));
+    }
+    let mut progress = InstallProgress::load(runner, &compose_cmd, &compose_args, &root).await;
+    let compose = Cmd::compose(&compose_cmd).args(&compose_args).dir(&root);
+
+    steps.begin("login");
+    let token = Registry::ghcr().credential();
+    if let Some(token) = &token {
+        match token_check::check(&http::client(airgapped, &settings.proxy), token).await {
+            Ok(checked) => {
+                if let Some(warning) = checked.warning {
+                    logln!("{warning}");
+                }
+                if checked.expires.is_some() {
+                    let _ = Registry::ghcr().save_expiry(token, checked.expires);
+                }
+            }
+            Err(message) => logln!("{message} (will try pull anyway)"),
+        }
+    }
+    for registry in registry::configured(settings) {
+        if !registry.has_credentials() {
+            continue;
+        }
+        logln!("🔐 Logging into {}...", registry.host());
+        if let Err(e) = registry
+            .login(runner, registry.credential().as_deref())
+            .await
+        {
+            logln!("⚠️  Login warning (will try pull anyway): {e}");
+        }
+    }
+
+    let identity_tag = match settings.pinned_tags.get("identity") {
+        Some(pin) => pin.tag.clone(),
+        None if !airgapped => {
+            let client = http::client(airgapped, &settings.proxy);
+            fetch_latest_identity_tag(&client, &settings.update_sources, token.as_deref())
+                .await
+                .unwrap_or_else(|| "latest".to_string())
+        }
+        None => "latest".to_string(),
+    };
+    logln!("🏷️  Image tag: {identity_tag}");
+
+    if !airgapped {
+        steps.begin("pull");
+        let client = http::client(airgapped, &settings.proxy);
+        let requirement =
+            disk::pull_requirement(runner, &client, &identity_tag, token.as_deref()).await;
+        logln!("💽 {}", requirement.line());
+        if requirement.is_short() {
+            steps.code = Some("disk_insufficient");
+            return Err(eyre!(
+                "Not enough free disk space for the pull. Free some with \
+                 `docker system prune -f` (volumes are kept) and run the installer again."
+            ));
+        }
+        logln!("⬇️  Step 1/3: Pulling images...");
+        run_compose(
+            runner,
+            &compose,
+            &["pull"],
+            &identity_tag,
+            &mut progress,
+            &settings.compose_timeouts,
+        )
+        .await?;
+    }
+
+    if let Some(db) = ExternalDb::load(&root) {
+        match external_db::test(runner, &db).await {
+            Ok(version) => logln!("🗄️  External database {}: {version}", db.address()),
+            Err(e) => {
+                steps.code = Some("database_unreachable");
+                return Err(e);
+            }
+        }
+    }
+
+    steps.begin("start");
+    logln!("🚀 Step 2/3: Starting services...");
+    let up_args = compose_override::up_args(settings, airgapped);
+    let up_args: Vec<&str> = up_args.iter().map(String::as_str).collect();
+    run_compose(
+        runner,
+        &compose,
+        &up_args,
+        &identity_tag,
+        &mut progress,
+        &settings.compose_timeouts,
+    )
+    .await?;
+    for container in compose_override::remove_skipped(runner, settings).await {
+        logln!("🧹 Removed {container} (left out of the install)");
+    }
+
+    steps.begin("health");
+    logln!("🩺 Step 3/3: Verifying services...");
+    match rollback::wait_for_health(runner, "identity", HEALTH_TIMEOUT).await {
+        HealthOutcome::Healthy | HealthOutcome::NoHealthcheck => {}
+        HealthOutcome::Unhealthy => {
+            steps.code = Some("health_unhealthy");
+            return Err(eyre!("identity failed its healthcheck"));
+        }
+        HealthOutcome::TimedOut => {
+            steps.code = Some("health_timeout");
+            return Err(eyre!(
+                "identity did not become healthy within {}s",
+                HEALTH_TIMEOUT.as_secs()
+            ));
+        }
+    }
+
+    for service in external_db::services(&root, settings) {
+        if let Err(reason) = verify::wait(runner, service, verify::VERIFY_TIMEOUT).await {
+            logln!("❌ {} is not healthy: {reason}", service.service);
+            logln!("📋 Last log lines from {}:", service.container);
+            for line in verify::last_logs(runner, service.container).await {
+                logln!("   {line}");
+            }
+            steps.code = Some("health_unhealthy");
+            return Err(eyre!(
+                "{} did not pass verification: {reason}",
+                service.service
+            ));
+        }
+        logln!("✅ {} is healthy", service.service);
+    }
+
+    let compose_files = compose_args
+        .iter()
+        .filter(|arg| arg.as_str() != "-f")
+        .cloned()
+        .collect();
+    if let Err(e) = InstallManifest::new(airgapped, &identity_tag, compose_files).save(&root) {
+        logln!("⚠️  Could not write install manifest: {e}");
+    }
+
+    let url = if settings.skips_entry() {
+        format!("http://{}", stack::current().direct.1)
+    } else {
+        format!("https://{ip}:8008")
+    };
+    logln!("✅ All services started! Access: {url}");
+    if let Some(line) = smtp::apply_from_env(runner, &root).await {
+        logln!("{line}");
+    }
+    for line in ldap::apply_from_env(runner, &root).await {
+        logln!("{line}");
+    }
+    // Unattended runs never change the firewall; they print what to run.
+    if cli.remote().is_none()
+        && !settings.skips_entry()
+        && let Some(offer) = firewall::detect(runner, &root).await
+    {
+        logln!(
+            "🔥 {} does not let {} through yet; to open it run:",
+            offer.firewall.label(),
+            offer.port_list()
+        );
+        for line in offer.command_lines() {
+            logln!("   {line}");
+        }
+    }
+    Ok((identity_tag, url))
+}
+
+/// Stop and remove the stack. `purge` also deletes volumes, i.e. the database.
+pub async fn uninstall(cli: &Cli, purge: bool) -> Result<()> {
+    let result = run_uninstall(cli, purge).await;
+    if let Err(e) = &result {
+        output::emit(&Event::Error {
+            code: "uninstall_failed",
+            message: &format!("{e:#}"),
+        });
+    }
+    output::emit(&Event::Finished {
+        success: result.is_ok(),
+        tag: None,
+        url: None,
+        log_path: None,
+    });
+    result
+}
+
+async fn run_uninstall(cli: &Cli, purge: bool) -> Result<()> {
+    let root = utils::project_root();
+    if !root.join("docker-compose.yaml").exists() {
+        return Err(eyre!("docker-compose.yaml not found in {}", root.display()));
+    }
+    let runner = remote::runner(cli.remote().as_ref());
+    let runner = runner.as_ref();
+    let compose_args = compose_override::compose_file_args(&root);
+    let compose_cmd = utils::detect_compose_command(runner).await?;
+    let compose = Cmd::compose(&compose_cmd).args(&compose_args).dir(&root);
+
+    let mut action = vec!["down", "--remove-orphans"];
+    if purge {
+        action.push("-v");
+        logln!("🧹 Removing services and volumes...");
+    } else {
+        logln!("🧹 Removing services (volumes kept)...");
+    }
+    let mut progress = InstallProgress::from_stack();
+    run_compose(
+        runner,
+        &compose,
+        &action,
+        "latest",
+        &mut progress,
+        &Settings::load().compose_timeouts,
+    )
+    .await?;
+    logln!("✅ Identity stack removed");
+    Ok(())
+}
+
+/// Run `compose` (the compose command with its `-f` files, in the project
+/// directory) with `action` appended.
+async fn run_compose(
+    runner: &dyn CommandRunner,
+    compose: &Cmd,
+    action: &[&str],
+    identity_tag: &str,
+    progress: &mut InstallProgress,
+    timeouts: &ComposeTimeouts,
+) -> Result<()> {
+    let mut child = runner.spawn(
+        &compose
+            .clone()
+            .args(action)
+            .env("IDENTITY_TAG", identity_tag),
+    )?;
+    let limit = timeouts.for_action(action[0]);
+    let stall = timeouts.stall();
+    let started = Instant::now();
+    // Compose reports progress on stderr; pass it through and keep a copy.
+    loop {
+        // Wait for the next line no longer than the stall limit or what
+        // is left of the step's overall limit, whichever is shorter.
+        let remaining = limit.map(|limit| limit.saturating_sub(started.elapsed()));
+        let wait = match (stall, remaining) {
+            (Some(stall), Some(remaining)) => Some(stall.min(remaining)),
+            (stall, remaining) => stall.or(remaining),
+        };
+        let next = match wait {
+            Some(wait) => match tokio::time::timeout(wait, child.next_line()).await {
+                Ok(next) => next,
+                Err(_) => {
+                    child.kill();
+                    return Err(match limit {
+                        Some(limit) if started.elapsed() >= limit => eyre!(
+                            "docker compose {} did not finish within {}s",
+                            action[0],
+                            limit.as_secs()
+                        ),
+                        _ => eyre!(
+                            "docker compose {} stalled: no output for {}s",
+                            action[0],
+                            wait.as_secs()
+                        ),
+                    });
+                }
+            },
+            None => child.next_line().await,
+        };
+        let Some(line) = next else { break };
+        install_log::record(&line);
+        if !output::is_json() {
+            eprintln!("{line}");
+            continue;
+        }
+        match progress.update(&line) {
+            Some(service) => output::emit(&Event::Progress {
+                service: &service.name,
+                status: service.state.label(),
+            }),
+            None => output::line(&line),
+        }
+    }
+    if !child.wait().await {
+        return Err(eyre!("docker compose {} failed", action.join(" ")));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::runner::{CmdOutput, MockRunner};
+
+    #[tokio::test]
+    async fn test_run_compose_passes_tag_and_fails_on_exit_status() {
+        let runner = MockRunner::new()
+            .on(
+                " pull",
+                CmdOutput::failed("pull access denied for identity\n"),
+            )
+            .on(" up", CmdOutput::ok("Container identity  Started\n"));
+        let compose_cmd = vec!["docker".to_string(), "compose".to_string()];
+        let root = Path::new("/srv/identity");
+        let compose = Cmd::compose(&compose_cmd)
+            .args(["-f", "docker-compose.yaml"])
+            .dir(root);
+        let timeouts = ComposeTimeouts::default();
+        let mut progress = InstallProgress::from_stack();
+
+        run_compose(
+            &runner,
+            &compose,
+            &["up", "-d"],
+            "v1.2.0",
+            &mut progress,
+            &timeouts,
+        )
+        .await
+        .unwrap();
+        let err = run_compose(
+            &runner,
+            &compose,
+            &["pull"],
+            "v1.2.0",
+            &mut progress,
+            &timeouts,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.to_string(), "docker compose pull failed");
+
+        let up = &runner.calls()[0];
+        assert_eq!(up.line(), "docker compose -f docker-compose.yaml up -d");
+        assert_eq!(up.dir.as_deref(), Some(root));
+        assert_eq!(
+            up.env,
+            vec![("IDENTITY_TAG".to_string(), "v1.2.0".to_string())]
+        );
+    }
+}
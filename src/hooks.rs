@@ -0,0 +1,60 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use reqwest::Client;
+use serde_json::json;
+
+use crate::runner::CommandRunner;
+use crate::settings::{Hook, HookEvent};
+use crate::{build_info, platform};
+
+/// Hooks must never hold up or break the installer, so each one gets a
+/// bounded run time and failures are dropped.
+const HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Run every hook registered for `event` in the background.
+pub fn fire(
+    runner: Arc<dyn CommandRunner>,
+    hooks: &[Hook],
+    client: &Client,
+    event: HookEvent,
+    detail: &str,
+) {
+    for hook in hooks.iter().filter(|hook| hook.event == event) {
+        if let Some(command) = &hook.command {
+            // Detached: a hook fired just before exit should still finish.
+            let cmd = platform::shell_command(command)
+                .env("NQRUST_EVENT", event.as_str())
+                .env("NQRUST_DETAIL", detail)
+                .env("NQRUST_INSTALLER_VERSION", build_info::VERSION)
+                .detached();
+            let runner = runner.clone();
+            tokio::spawn(async move {
+                let Ok(mut running) = runner.spawn(&cmd) else {
+                    return;
+                };
+                // Output is dropped; only the exit matters.
+                let finished = async { while running.next_line().await.is_some() {} };
+                if tokio::time::timeout(HOOK_TIMEOUT, finished).await.is_err() {
+                    running.kill();
+                }
+            });
+        }
+
+        if let Some(url) = hook.webhook.clone() {
+            let body = json!({
+                "event": event.as_str(),
+                "detail": detail,
+                "installer": build_info::NAME,
+                "version": build_info::VERSION,
+                "git_sha": build_info::GIT_SHA,
+                "timestamp": Utc::now(),
+            });
+            let request = client.post(url).timeout(HOOK_TIMEOUT).json(&body);
+            tokio::spawn(async move {
+                let _ = request.send().await;
+            });
+        }
+    }
+}
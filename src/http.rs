@@ -0,0 +1,150 @@
+// http.rs - shared reqwest client construction
+// Every outbound request goes through `client()` so headers and proxy
+// settings stay consistent. Requests to GitHub and GHCR are sent with `send`,
+// which retries the failures a flaky corporate network produces (timeouts,
+// dropped connections, 429 and 5xx answers) with exponential backoff and
+// jitter. Inside a TUI task each retry shows as "attempt 2/5" next to the
+// spinner.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT};
+use reqwest::{Client, NoProxy, Proxy, RequestBuilder, Response, StatusCode};
+
+use crate::build_info;
+use crate::settings::ProxySettings;
+
+/// Build the shared client. Proxies saved in settings win; otherwise reqwest
+/// picks up `HTTPS_PROXY` / `HTTP_PROXY` / `NO_PROXY` from the environment.
+pub fn client(airgapped: bool, proxy: &ProxySettings) -> Client {
+    let mut headers = HeaderMap::new();
+    if let Ok(value) = HeaderValue::from_str(&build_info::user_agent(airgapped)) {
+        headers.insert(USER_AGENT, value);
+    }
+    headers.insert(
+        "X-Installer-Version",
+        HeaderValue::from_static(build_info::VERSION),
+    );
+    if let Ok(value) = HeaderValue::from_str(build_info::GIT_SHA) {
+        headers.insert("X-Installer-Build", value);
+    }
+    headers.insert(
+        "X-Installer-Mode",
+        HeaderValue::from_static(build_info::mode(airgapped)),
+    );
+
+    let mut builder = Client::builder().default_headers(headers);
+    if proxy.is_configured() {
+        let no_proxy = || proxy.no_proxy.as_deref().and_then(NoProxy::from_string);
+        // Configured proxies replace the environment ones entirely.
+        builder = builder.no_proxy();
+        if let Some(url) = &proxy.https_proxy
+            && let Ok(p) = Proxy::https(url)
+        {
+            builder = builder.proxy(p.no_proxy(no_proxy()));
+        }
+        if let Some(url) = &proxy.http_proxy
+            && let Ok(p) = Proxy::http(url)
+        {
+            builder = builder.proxy(p.no_proxy(no_proxy()));
+        }
+    }
+
+    builder.build().unwrap_or_else(|_| Client::new())
+}
+
+/// Tries `send` makes, the first included.
+pub const ATTEMPTS: u32 = 5;
+const FIRST_DELAY: Duration = Duration::from_millis(500);
+const MAX_DELAY: Duration = Duration::from_secs(8);
+
+tokio::task_local! {
+    static RETRY_STATUS: Arc<dyn Fn(String) + Send + Sync>;
+}
+
+/// Run `future` with `send`'s retry notices passed to `status`.
+pub async fn with_retry_status<F: Future>(
+    status: impl Fn(String) + Send + Sync + 'static,
+    future: F,
+) -> F::Output {
+    RETRY_STATUS.scope(Arc::new(status), future).await
+}
+
+/// Send `request`, retrying timeouts, connection failures, 429 and 5xx up to
+/// `ATTEMPTS` times. The last response or error is returned as it came.
+pub async fn send(request: RequestBuilder) -> reqwest::Result<Response> {
+    let (client, request) = request.build_split();
+    let request = request?;
+    let mut attempt = 1;
+    loop {
+        // Only streamed bodies cannot be cloned, and none are sent this way.
+        let Some(this_try) = request.try_clone().filter(|_| attempt < ATTEMPTS) else {
+            return client.execute(request).await;
+        };
+        let result = client.execute(this_try).await;
+        let transient = match &result {
+            Ok(response) => {
+                response.status() == StatusCode::TOO_MANY_REQUESTS
+                    || response.status().is_server_error()
+            }
+            Err(e) => e.is_timeout() || e.is_connect() || e.is_request(),
+        };
+        if !transient {
+            return result;
+        }
+        attempt += 1;
+        let host = request.url().host_str().unwrap_or_default().to_string();
+        let _ = RETRY_STATUS.try_with(|status| {
+            status(format!(
+                "🔁 {host} did not answer, attempt {attempt}/{ATTEMPTS}..."
+            ))
+        });
+        tokio::time::sleep(backoff(attempt - 1, jitter())).await;
+    }
+}
+
+/// Delay before retry `retry` (1-based): doubling from `FIRST_DELAY` up to
+/// `MAX_DELAY`, plus up to half of that again, `jitter` being 0.0..1.0.
+fn backoff(retry: u32, jitter: f64) -> Duration {
+    let base = FIRST_DELAY
+        .saturating_mul(1 << retry.saturating_sub(1).min(16))
+        .min(MAX_DELAY);
+    base + base.mul_f64(jitter / 2.0)
+}
+
+/// 0.0..1.0 from the clock; spreading retries out needs no better.
+fn jitter() -> f64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.subsec_nanos());
+    f64::from(nanos % 1000) / 1000.0
+}
+
+/// Check a proxy URL the way reqwest will use it.
+pub fn validate_proxy_url(url: &str) -> Result<(), String> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("{url}: {e}"))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!(
+            "{url}: only http:// and https:// proxies are supported"
+        ));
+    }
+    if parsed.host_str().is_none() {
+        return Err(format!("{url}: missing host"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        assert_eq!(backoff(1, 0.0), Duration::from_millis(500));
+        assert_eq!(backoff(3, 0.0), Duration::from_secs(2));
+        assert_eq!(backoff(3, 1.0), Duration::from_secs(3));
+        assert_eq!(backoff(10, 0.0), MAX_DELAY);
+    }
+}
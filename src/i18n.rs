@@ -0,0 +1,152 @@
+// i18n.rs - translated UI strings
+// Catalogs live in locales/<lang>.toml and are embedded at compile time.
+// Keys are dotted paths into the file (`menu.proceed`); placeholders are
+// written `{name}`. A key missing from a catalog falls back to English, so a
+// partial translation never leaves a blank on screen.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use clap::ValueEnum;
+
+static LANG: OnceLock<Lang> = OnceLock::new();
+static EN: OnceLock<HashMap<String, String>> = OnceLock::new();
+static ID: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Lang {
+    #[default]
+    En,
+    /// Bahasa Indonesia.
+    Id,
+}
+
+impl Lang {
+    /// Language named by a locale such as `id_ID.UTF-8`; English otherwise.
+    pub fn from_locale(locale: &str) -> Self {
+        let code = locale
+            .split(['_', '.', '-', '@'])
+            .next()
+            .unwrap_or_default();
+        match code.to_ascii_lowercase().as_str() {
+            "id" | "in" => Lang::Id,
+            _ => Lang::En,
+        }
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Lang::En => include_str!("../locales/en.toml"),
+            Lang::Id => include_str!("../locales/id.toml"),
+        }
+    }
+
+    fn catalog(self) -> &'static HashMap<String, String> {
+        let cell = match self {
+            Lang::En => &EN,
+            Lang::Id => &ID,
+        };
+        cell.get_or_init(|| parse(self.source()))
+    }
+}
+
+/// Pick the language for the rest of the run: `--lang`, else the first of
+/// LC_ALL, LC_MESSAGES and LANG that is set. Only the first call counts.
+pub fn init(choice: Option<Lang>) {
+    let lang = choice.unwrap_or_else(|| {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .find_map(|name| std::env::var(name).ok().filter(|v| !v.is_empty()))
+            .map(|locale| Lang::from_locale(&locale))
+            .unwrap_or_default()
+    });
+    let _ = LANG.set(lang);
+}
+
+pub fn current() -> Lang {
+    LANG.get().copied().unwrap_or_default()
+}
+
+/// Text for `key` in the current language.
+pub fn tr(key: &'static str) -> &'static str {
+    lookup(current(), key)
+}
+
+/// `tr` with each `{name}` replaced by its value from `args`.
+pub fn trf(key: &'static str, args: &[(&str, &str)]) -> String {
+    args.iter()
+        .fold(tr(key).to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{name}}}"), value)
+        })
+}
+
+fn lookup(lang: Lang, key: &'static str) -> &'static str {
+    lang.catalog()
+        .get(key)
+        .or_else(|| Lang::En.catalog().get(key))
+        .map_or(key, String::as_str)
+}
+
+/// Flatten nested tables into dotted keys.
+fn parse(source: &str) -> HashMap<String, String> {
+    fn walk(prefix: &str, table: &toml::Table, out: &mut HashMap<String, String>) {
+        for (key, value) in table {
+            let path = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{prefix}.{key}")
+            };
+            match value {
+                toml::Value::String(text) => {
+                    out.insert(path, text.clone());
+                }
+                toml::Value::Table(table) => walk(&path, table, out),
+                _ => {}
+            }
+        }
+    }
+    let mut out = HashMap::new();
+    // The catalogs are checked by the tests below; a broken one shows keys.
+    if let Ok(table) = source.parse::<toml::Table>() {
+        walk("", &table, &mut out);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholders(text: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = text
+            .split('{')
+            .skip(1)
+            .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_catalogs_have_the_same_keys_and_placeholders() {
+        let en = Lang::En.catalog();
+        let id = Lang::Id.catalog();
+        assert!(!en.is_empty());
+        for (key, text) in en {
+            let translated = id.get(key).unwrap_or_else(|| panic!("id.toml lacks {key}"));
+            assert_eq!(placeholders(text), placeholders(translated), "{key}");
+        }
+        for key in id.keys() {
+            assert!(en.contains_key(key), "en.toml lacks {key}");
+        }
+    }
+
+    #[test]
+    fn test_locale_and_lookup() {
+        assert_eq!(Lang::from_locale("id_ID.UTF-8"), Lang::Id);
+        assert_eq!(Lang::from_locale("en_US.UTF-8"), Lang::En);
+        assert_eq!(Lang::from_locale("C"), Lang::En);
+        assert_eq!(lookup(Lang::Id, "menu.cancel"), "Batal");
+        assert_eq!(lookup(Lang::Id, "no.such.key"), "no.such.key");
+    }
+}
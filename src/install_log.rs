@@ -0,0 +1,144 @@
+// install_log.rs - timestamped log file for an install run
+// Everything the installer shows during an install (TUI log lines, compose
+// output, airgapped extraction) is also appended to
+// `logs/install-YYYYMMDD-HHMMSS.log` in the project's state dir (see xdg.rs),
+// so a failed run can be attached to a support ticket after the terminal is
+// gone.
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+use crate::{build_info, utils, xdg};
+
+static LOG: Mutex<Option<InstallLog>> = Mutex::new(None);
+
+struct InstallLog {
+    path: PathBuf,
+    file: File,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    /// Guess the level of a display line from its marker emoji or wording.
+    pub fn of(line: &str) -> Self {
+        let line = line.trim_start();
+        let lower = line.to_ascii_lowercase();
+        if line.starts_with('❌') {
+            Level::Error
+        } else if line.starts_with('⚠') {
+            Level::Warn
+        } else if lower.contains("error") || lower.contains("failed") {
+            Level::Error
+        } else if lower.contains("warn") {
+            Level::Warn
+        } else {
+            Level::Info
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Level::Info => "INFO ",
+            Level::Warn => "WARN ",
+            Level::Error => "ERROR",
+        }
+    }
+}
+
+/// Open the log file for this run. Later calls return the same file, so the
+/// airgapped setup and the install that follows share one log. `None` when
+/// the file can't be created; logging never stops an install.
+pub fn start() -> Option<PathBuf> {
+    let mut log = LOG.lock().ok()?;
+    if let Some(log) = log.as_ref() {
+        return Some(log.path.clone());
+    }
+    let dir = dir();
+    fs::create_dir_all(&dir).ok()?;
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let path = dir.join(format!("install-{stamp}.log"));
+    let file = File::create(&path).ok()?;
+    *log = Some(InstallLog {
+        path: path.clone(),
+        file,
+    });
+    drop(log);
+    write(
+        Level::Info,
+        &format!(
+            "{} {} (git {}, {})",
+            build_info::NAME,
+            build_info::VERSION,
+            build_info::GIT_SHA,
+            build_info::platform()
+        ),
+    );
+    Some(path)
+}
+
+/// Where the logs of the current project go.
+pub fn dir() -> PathBuf {
+    let root = utils::project_root();
+    xdg::adopt(&root.join("logs"), &xdg::project_state(&root).join("logs"))
+}
+
+/// Path of the current log, if one was started.
+pub fn path() -> Option<PathBuf> {
+    LOG.lock().ok()?.as_ref().map(|log| log.path.clone())
+}
+
+/// Append a display line, with the level guessed by `Level::of`.
+pub fn record(line: &str) {
+    write(Level::of(line), line);
+}
+
+pub fn write(level: Level, message: &str) {
+    let Ok(mut log) = LOG.lock() else {
+        return;
+    };
+    let Some(log) = log.as_mut() else {
+        return;
+    };
+    let stamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
+    for line in message.lines().filter(|l| !l.trim().is_empty()) {
+        let _ = writeln!(log.file, "{stamp} {} {}", level.as_str(), line.trim_end());
+    }
+}
+
+/// `println!` that also records the line in the install log. Becomes a JSON
+/// `log` event under `--output json`.
+macro_rules! logln {
+    ($($arg:tt)*) => {{
+        let line = format!($($arg)*);
+        $crate::output::line(&line);
+        $crate::install_log::record(&line);
+    }};
+}
+pub(crate) use logln;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_from_display_line() {
+        assert_eq!(Level::of("❌ Rollback failed: timeout"), Level::Error);
+        assert_eq!(Level::of("Error response from daemon: ..."), Level::Error);
+        assert_eq!(
+            Level::of("⚠️  Login warning (will try pull anyway): login failed"),
+            Level::Warn
+        );
+        assert_eq!(Level::of(" ✔ Container identity-db  Started"), Level::Info);
+    }
+}
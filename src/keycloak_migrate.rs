@@ -0,0 +1,254 @@
+// keycloak_migrate.rs - copy realms and users from an existing Keycloak
+// into the freshly installed stack via the admin REST API.
+
+use std::path::Path;
+
+use color_eyre::{Result, eyre::eyre};
+use reqwest::{Client, StatusCode};
+use serde_json::{Value, json};
+
+use crate::env_file::EnvFile;
+use crate::runner::CommandRunner;
+use crate::{secrets, utils};
+
+/// Caddy's self-signed HTTPS port, through which the installed stack is reached.
+const TARGET_PORT: u16 = 8008;
+/// Bootstrap admin defaults from docker-compose.yaml (`KC_BOOTSTRAP_ADMIN_*`).
+pub const TARGET_ADMIN: (&str, &str) = ("admin", "admin");
+/// Keycloak replaces exported client secrets with this mask.
+const MASKED_SECRET: &str = "**********";
+const USERS_PAGE: usize = 100;
+
+/// Bootstrap admin of the stack under `root`: `KC_BOOTSTRAP_ADMIN_*` from
+/// .env over the compose defaults. The admin secret replaces the password
+/// when a secrets backend is used.
+/// The installed stack's base URL, on the host `runner` deploys to.
+pub fn target_url(runner: &dyn CommandRunner) -> String {
+    format!("https://{}:{TARGET_PORT}", runner.host())
+}
+
+pub fn target_admin(root: &Path) -> (String, String) {
+    let env = EnvFile::load(&root.join(".env")).unwrap_or_default();
+    let from_env = |key: &str, default: &str| {
+        env.get(key)
+            .filter(|value| !value.is_empty())
+            .unwrap_or(default)
+            .to_string()
+    };
+    let username = from_env("KC_BOOTSTRAP_ADMIN_USERNAME", TARGET_ADMIN.0);
+    let password = secrets::read(root, secrets::ADMIN_PASSWORD)
+        .unwrap_or_else(|| from_env("KC_BOOTSTRAP_ADMIN_PASSWORD", TARGET_ADMIN.1));
+    (username, password)
+}
+
+/// One side of the migration: base URL plus a master-realm admin token.
+pub struct Endpoint {
+    client: Client,
+    base: String,
+    token: String,
+}
+
+impl Endpoint {
+    /// Log in to the master realm with the `admin-cli` password grant.
+    pub async fn login(client: Client, url: &str, username: &str, password: &str) -> Result<Self> {
+        let base = url.trim_end_matches('/').to_string();
+        let response = client
+            .post(format!(
+                "{base}/realms/master/protocol/openid-connect/token"
+            ))
+            .form(&[
+                ("grant_type", "password"),
+                ("client_id", "admin-cli"),
+                ("username", username),
+                ("password", password),
+            ])
+            .send()
+            .await
+            .map_err(|e| eyre!("Cannot reach {base}: {e}"))?;
+        if !response.status().is_success() {
+            return Err(eyre!(
+                "Admin login to {base} failed (HTTP {})",
+                response.status()
+            ));
+        }
+        let body: Value = response.json().await?;
+        let token = body["access_token"]
+            .as_str()
+            .ok_or_else(|| eyre!("No access_token in login response from {base}"))?
+            .to_string();
+        Ok(Self {
+            client,
+            base,
+            token,
+        })
+    }
+
+    /// Log in to the installed stack with the compose bootstrap admin.
+    pub async fn login_target(runner: &dyn CommandRunner) -> Result<Self> {
+        let client = Client::builder()
+            .danger_accept_invalid_certs(true)
+            .no_proxy()
+            .build()?;
+        let (username, password) = target_admin(&utils::project_root());
+        Self::login(client, &target_url(runner), &username, &password)
+            .await
+            .map_err(|e| eyre!("{e}\nIs the stack installed and healthy?"))
+    }
+
+    pub async fn get(&self, path: &str) -> Result<Value> {
+        let response = self
+            .client
+            .get(format!("{}/admin{path}", self.base))
+            .bearer_auth(&self.token)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(eyre!("GET {path} failed (HTTP {})", response.status()));
+        }
+        Ok(response.json().await?)
+    }
+
+    pub async fn post(&self, path: &str, body: &Value) -> Result<(StatusCode, Value)> {
+        let response = self
+            .client
+            .post(format!("{}/admin{path}", self.base))
+            .bearer_auth(&self.token)
+            .json(body)
+            .send()
+            .await?;
+        let status = response.status();
+        let body = response.json().await.unwrap_or(Value::Null);
+        Ok((status, body))
+    }
+
+    pub async fn put(&self, path: &str, body: &Value) -> Result<(StatusCode, Value)> {
+        let response = self
+            .client
+            .put(format!("{}/admin{path}", self.base))
+            .bearer_auth(&self.token)
+            .json(body)
+            .send()
+            .await?;
+        let status = response.status();
+        let body = response.json().await.unwrap_or(Value::Null);
+        Ok((status, body))
+    }
+
+    /// Names of all realms except `master`, which both sides already have.
+    pub async fn realm_names(&self) -> Result<Vec<String>> {
+        let realms = self.get("/realms").await?;
+        Ok(realms
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|r| r["realm"].as_str())
+            .filter(|name| *name != "master")
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Realm settings, clients, roles and groups (no users).
+    pub async fn export_realm(&self, realm: &str) -> Result<Value> {
+        let (status, mut rep) = self
+            .post(
+                &format!(
+                    "/realms/{realm}/partial-export?exportClients=true&exportGroupsAndRoles=true"
+                ),
+                &Value::Null,
+            )
+            .await?;
+        if !status.is_success() {
+            return Err(eyre!("Export of realm {realm} failed (HTTP {status})"));
+        }
+        strip_masked_secrets(&mut rep);
+        Ok(rep)
+    }
+
+    /// All users of a realm. Passwords are never exported by Keycloak.
+    pub async fn export_users(&self, realm: &str) -> Result<Vec<Value>> {
+        let mut users = Vec::new();
+        loop {
+            let page = self
+                .get(&format!(
+                    "/realms/{realm}/users?briefRepresentation=false&first={}&max={USERS_PAGE}",
+                    users.len()
+                ))
+                .await?;
+            let page = page.as_array().cloned().unwrap_or_default();
+            let done = page.len() < USERS_PAGE;
+            users.extend(page);
+            if done {
+                return Ok(users);
+            }
+        }
+    }
+
+    /// Create the realm, or merge into it when it already exists.
+    /// Returns true when the realm was newly created.
+    pub async fn import_realm(&self, rep: &Value) -> Result<bool> {
+        let realm = rep["realm"].as_str().unwrap_or_default();
+        let (status, body) = self.post("/realms", rep).await?;
+        if status.is_success() {
+            return Ok(true);
+        }
+        if status != StatusCode::CONFLICT {
+            return Err(eyre!(
+                "Creating realm {realm} failed (HTTP {status}): {body}"
+            ));
+        }
+        self.partial_import(realm, rep.clone()).await?;
+        Ok(false)
+    }
+
+    /// Import users, skipping any that already exist. Returns (added, skipped).
+    pub async fn import_users(&self, realm: &str, users: Vec<Value>) -> Result<(u64, u64)> {
+        self.partial_import(realm, json!({ "users": users })).await
+    }
+
+    async fn partial_import(&self, realm: &str, mut rep: Value) -> Result<(u64, u64)> {
+        rep["ifResourceExists"] = json!("SKIP");
+        let (status, body) = self
+            .post(&format!("/realms/{realm}/partialImport"), &rep)
+            .await?;
+        if !status.is_success() {
+            return Err(eyre!("Import into {realm} failed (HTTP {status}): {body}"));
+        }
+        Ok((
+            body["added"].as_u64().unwrap_or(0),
+            body["skipped"].as_u64().unwrap_or(0),
+        ))
+    }
+}
+
+/// Drop masked client secrets so the target generates fresh ones instead of
+/// storing the literal mask.
+fn strip_masked_secrets(rep: &mut Value) {
+    for client in rep["clients"].as_array_mut().into_iter().flatten() {
+        if client["secret"].as_str() == Some(MASKED_SECRET)
+            && let Some(obj) = client.as_object_mut()
+        {
+            obj.remove("secret");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_masked_secrets() {
+        let mut rep = json!({
+            "realm": "acme",
+            "clients": [
+                { "clientId": "web", "secret": "**********" },
+                { "clientId": "api", "secret": "kept" },
+                { "clientId": "spa", "publicClient": true }
+            ]
+        });
+        strip_masked_secrets(&mut rep);
+        assert!(rep["clients"][0].get("secret").is_none());
+        assert_eq!(rep["clients"][1]["secret"], "kept");
+        assert!(rep["clients"][2].get("secret").is_none());
+    }
+}
@@ -0,0 +1,344 @@
+// ldap.rs - LDAP / Active Directory user federation
+// The directory is kept in .env (`LDAP_*`) like the SMTP server, so templates
+// and answers files can set it. Once the stack answers, the installer has
+// Keycloak test the connection and the bind (`testLDAPConnection`, run from
+// inside the Keycloak container, which is where the real logins come from)
+// and then creates the realm's user-federation provider, or updates the one it
+// created before. With a groups DN a group mapper is added under it. The
+// provider is read-only: users and groups stay managed in the directory.
+
+use std::path::Path;
+
+use color_eyre::{Result, eyre::eyre};
+use serde_json::{Value, json};
+
+use crate::env_file::EnvFile;
+use crate::keycloak_migrate::Endpoint;
+use crate::runner::CommandRunner;
+
+pub const URL: &str = "LDAP_URL";
+pub const VENDOR: &str = "LDAP_VENDOR";
+pub const BIND_DN: &str = "LDAP_BIND_DN";
+pub const BIND_CREDENTIAL: &str = "LDAP_BIND_CREDENTIAL";
+pub const USERS_DN: &str = "LDAP_USERS_DN";
+pub const GROUPS_DN: &str = "LDAP_GROUPS_DN";
+pub const REALM: &str = "LDAP_REALM";
+
+/// Name of the provider the installer manages; others are left alone.
+const PROVIDER_NAME: &str = "ldap";
+const GROUP_MAPPER_NAME: &str = "groups";
+const STORAGE_PROVIDER: &str = "org.keycloak.storage.UserStorageProvider";
+const LDAP_MAPPER: &str = "org.keycloak.storage.ldap.mappers.LDAPStorageMapper";
+
+/// Directory flavour; decides the attribute names Keycloak looks users up by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Vendor {
+    /// Active Directory: sAMAccountName, objectGUID, memberOf.
+    Ad,
+    /// OpenLDAP and other RFC 4519 directories: uid, entryUUID.
+    #[default]
+    Other,
+}
+
+impl Vendor {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Vendor::Ad => "ad",
+            Vendor::Other => "other",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_ascii_lowercase().as_str() {
+            "ad" | "activedirectory" | "active directory" => Ok(Vendor::Ad),
+            "" | "other" | "openldap" => Ok(Vendor::Other),
+            other => Err(format!("'{other}' is not one of ad or other")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LdapSettings {
+    /// `ldap://host:389` or `ldaps://host:636`.
+    pub url: String,
+    pub vendor: Vendor,
+    pub bind_dn: String,
+    pub bind_credential: String,
+    pub users_dn: String,
+    /// Empty for no group mapper.
+    pub groups_dn: String,
+    pub realm: String,
+}
+
+impl LdapSettings {
+    /// The directory configured in .env; `None` without LDAP_URL.
+    pub fn from_env(env: &EnvFile) -> Option<Self> {
+        let get = |key: &str| env.get(key).unwrap_or_default().to_string();
+        let url = get(URL);
+        if url.is_empty() {
+            return None;
+        }
+        let realm = get(REALM);
+        Some(Self {
+            url,
+            vendor: Vendor::parse(&get(VENDOR)).unwrap_or_default(),
+            bind_dn: get(BIND_DN),
+            bind_credential: get(BIND_CREDENTIAL),
+            users_dn: get(USERS_DN),
+            groups_dn: get(GROUPS_DN),
+            realm: if realm.is_empty() {
+                "master".to_string()
+            } else {
+                realm
+            },
+        })
+    }
+
+    pub fn write_env(&self, env: &mut EnvFile) {
+        env.set(URL, &self.url);
+        env.set(VENDOR, self.vendor.as_str());
+        env.set(BIND_DN, &self.bind_dn);
+        env.set(BIND_CREDENTIAL, &self.bind_credential);
+        env.set(USERS_DN, &self.users_dn);
+        env.set(GROUPS_DN, &self.groups_dn);
+        env.set(REALM, &self.realm);
+    }
+
+    /// Body of `testLDAPConnection` for `action` (`testConnection` or
+    /// `testAuthentication`).
+    fn test_request(&self, action: &str) -> Value {
+        json!({
+            "action": action,
+            "connectionUrl": self.url,
+            "authType": "simple",
+            "bindDn": self.bind_dn,
+            "bindCredential": self.bind_credential,
+            "useTruststoreSpi": "always",
+            "startTls": "false",
+        })
+    }
+
+    /// The provider's config, in Keycloak's list-per-key form.
+    fn provider_config(&self) -> Value {
+        let (username, rdn, uuid, classes) = match self.vendor {
+            Vendor::Ad => (
+                "sAMAccountName",
+                "cn",
+                "objectGUID",
+                "person, organizationalPerson, user",
+            ),
+            Vendor::Other => (
+                "uid",
+                "uid",
+                "entryUUID",
+                "inetOrgPerson, organizationalPerson",
+            ),
+        };
+        json!({
+            "vendor": [self.vendor.as_str()],
+            "connectionUrl": [self.url],
+            "authType": ["simple"],
+            "bindDn": [self.bind_dn],
+            "bindCredential": [self.bind_credential],
+            "usersDn": [self.users_dn],
+            "usernameLDAPAttribute": [username],
+            "rdnLDAPAttribute": [rdn],
+            "uuidLDAPAttribute": [uuid],
+            "userObjectClasses": [classes],
+            "searchScope": ["2"],
+            "editMode": ["READ_ONLY"],
+            "enabled": ["true"],
+            "importEnabled": ["true"],
+            "syncRegistrations": ["false"],
+            "pagination": ["true"],
+            "useTruststoreSpi": ["always"],
+            "priority": ["0"],
+        })
+    }
+
+    fn group_mapper_config(&self) -> Value {
+        let (classes, strategy) = match self.vendor {
+            Vendor::Ad => ("group", "GET_GROUPS_FROM_USER_MEMBEROF_ATTRIBUTE"),
+            Vendor::Other => ("groupOfNames", "LOAD_GROUPS_BY_MEMBER_ATTRIBUTE"),
+        };
+        json!({
+            "groups.dn": [self.groups_dn],
+            "group.name.ldap.attribute": ["cn"],
+            "group.object.classes": [classes],
+            "membership.ldap.attribute": ["member"],
+            "membership.attribute.type": ["DN"],
+            "membership.user.ldap.attribute": ["uid"],
+            "memberof.ldap.attribute": ["memberOf"],
+            "user.roles.retrieve.strategy": [strategy],
+            "mode": ["READ_ONLY"],
+            "preserve.group.inheritance": ["true"],
+            "ignore.missing.groups": ["false"],
+            "drop.non.existing.groups.during.sync": ["false"],
+        })
+    }
+}
+
+/// Have Keycloak reach the directory and bind with the credentials.
+pub async fn test(endpoint: &Endpoint, settings: &LdapSettings) -> Result<()> {
+    for (action, what) in [
+        ("testConnection", "connect to"),
+        ("testAuthentication", "bind to"),
+    ] {
+        let (status, response) = endpoint
+            .post(
+                &format!("/realms/{}/testLDAPConnection", settings.realm),
+                &settings.test_request(action),
+            )
+            .await?;
+        if !status.is_success() {
+            let reason = response["errorMessage"]
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("HTTP {status}"));
+            return Err(eyre!(
+                "Keycloak could not {what} {}: {reason}",
+                settings.url
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Create the provider (and group mapper), or update the ones created
+/// before. Returns true when the provider was new.
+pub async fn apply(endpoint: &Endpoint, settings: &LdapSettings) -> Result<bool> {
+    let realm = &settings.realm;
+    let realm_id = endpoint.get(&format!("/realms/{realm}")).await?["id"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| eyre!("Realm {realm} has no id"))?;
+
+    let provider = json!({
+        "name": PROVIDER_NAME,
+        "providerId": "ldap",
+        "providerType": STORAGE_PROVIDER,
+        "parentId": realm_id,
+        "config": settings.provider_config(),
+    });
+    let (provider_id, created) =
+        upsert(endpoint, realm, &realm_id, STORAGE_PROVIDER, provider).await?;
+
+    if !settings.groups_dn.is_empty() {
+        let mapper = json!({
+            "name": GROUP_MAPPER_NAME,
+            "providerId": "group-ldap-mapper",
+            "providerType": LDAP_MAPPER,
+            "parentId": provider_id,
+            "config": settings.group_mapper_config(),
+        });
+        upsert(endpoint, realm, &provider_id, LDAP_MAPPER, mapper).await?;
+    }
+    Ok(created)
+}
+
+/// Create `component` under `parent`, or replace the one of the same name.
+/// Returns its id and whether it was created.
+async fn upsert(
+    endpoint: &Endpoint,
+    realm: &str,
+    parent: &str,
+    kind: &str,
+    mut component: Value,
+) -> Result<(String, bool)> {
+    let name = component["name"].as_str().unwrap_or_default().to_string();
+    let find = format!("/realms/{realm}/components?parent={parent}&type={kind}&name={name}");
+    let existing = endpoint.get(&find).await?;
+    let existing_id = existing
+        .as_array()
+        .and_then(|found| found.first())
+        .and_then(|found| found["id"].as_str())
+        .map(str::to_string);
+
+    let (status, response) = match &existing_id {
+        Some(id) => {
+            component["id"] = json!(id);
+            endpoint
+                .put(&format!("/realms/{realm}/components/{id}"), &component)
+                .await?
+        }
+        None => {
+            endpoint
+                .post(&format!("/realms/{realm}/components"), &component)
+                .await?
+        }
+    };
+    if !status.is_success() {
+        return Err(eyre!(
+            "Saving {name} in realm {realm} failed (HTTP {status}): {response}"
+        ));
+    }
+    if let Some(id) = existing_id {
+        return Ok((id, false));
+    }
+    let created = endpoint.get(&find).await?;
+    let id = created
+        .as_array()
+        .and_then(|found| found.first())
+        .and_then(|found| found["id"].as_str())
+        .ok_or_else(|| eyre!("{name} was created but can't be found in realm {realm}"))?;
+    Ok((id.to_string(), true))
+}
+
+/// After an install: test the directory from .env and create its provider.
+/// Returns lines for the install log; empty when .env sets no LDAP_URL.
+pub async fn apply_from_env(runner: &dyn CommandRunner, root: &Path) -> Vec<String> {
+    let env = EnvFile::load(&root.join(".env")).unwrap_or_default();
+    let Some(settings) = LdapSettings::from_env(&env) else {
+        return Vec::new();
+    };
+    let result = async {
+        let endpoint = Endpoint::login_target(runner).await?;
+        test(&endpoint, &settings).await?;
+        apply(&endpoint, &settings).await
+    }
+    .await;
+    vec![match result {
+        Ok(created) => format!(
+            "👥 LDAP federation with {} {} in realm {}",
+            settings.url,
+            if created { "created" } else { "updated" },
+            settings.realm
+        ),
+        Err(e) => format!("⚠️  LDAP federation from .env not set up: {e}"),
+    }]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_round_trip_and_vendor_attributes() {
+        let mut env = EnvFile::parse("");
+        assert_eq!(LdapSettings::from_env(&env), None);
+
+        let settings = LdapSettings {
+            url: "ldaps://dc1.corp.local:636".into(),
+            vendor: Vendor::Ad,
+            bind_dn: "CN=svc-sso,OU=Service,DC=corp,DC=local".into(),
+            bind_credential: "s3cret".into(),
+            users_dn: "OU=Staff,DC=corp,DC=local".into(),
+            groups_dn: String::new(),
+            realm: "corp".into(),
+        };
+        settings.write_env(&mut env);
+        assert_eq!(LdapSettings::from_env(&env), Some(settings.clone()));
+
+        let config = settings.provider_config();
+        assert_eq!(config["usernameLDAPAttribute"][0], "sAMAccountName");
+        assert_eq!(config["editMode"][0], "READ_ONLY");
+        assert_eq!(
+            settings.test_request("testAuthentication")["bindDn"],
+            settings.bind_dn
+        );
+
+        env.set(REALM, "");
+        assert_eq!(LdapSettings::from_env(&env).unwrap().realm, "master");
+        assert_eq!(Vendor::parse("OpenLDAP"), Ok(Vendor::Other));
+    }
+}
@@ -0,0 +1,60 @@
+//! Installer for the NexusQuantum Identity stack.
+//!
+//! The `nqrust-identity` binary is a thin TUI frontend over this crate; the
+//! screens themselves live in the binary. To drive an install from code,
+//! start with [`api::Installer`]; the other modules are the building blocks
+//! it and the TUI share (compose orchestration, registry auth, updates,
+//! airgapped payloads, .env and certificate generation).
+
+pub mod airgapped;
+pub mod api;
+pub mod artifacts;
+pub mod build_info;
+pub mod bundle;
+pub mod cli;
+pub mod compose_lint;
+pub mod compose_override;
+pub mod daemon;
+pub mod db_tuning;
+pub mod diagnostics;
+pub mod disk;
+pub mod docker_context;
+pub mod doctor;
+pub mod env_file;
+pub mod env_template;
+pub mod external_db;
+pub mod file_security;
+pub mod firewall;
+pub mod gpu;
+pub mod headless;
+pub mod hooks;
+pub mod http;
+pub mod i18n;
+pub mod install_log;
+pub mod keycloak_migrate;
+pub mod ldap;
+pub mod manifest;
+pub mod output;
+pub mod platform;
+pub mod progress;
+pub mod registry;
+pub mod remote;
+pub mod rollback;
+pub mod runner;
+pub mod secrets;
+pub mod self_update;
+pub mod selinux;
+pub mod settings;
+pub mod sizing;
+pub mod smtp;
+pub mod stack;
+pub mod systemd;
+pub mod templates;
+pub mod throttle;
+pub mod token_check;
+pub mod update_check;
+pub mod updates;
+pub mod utils;
+pub mod verify;
+pub mod xdg;
+pub mod yaml_file;
@@ -1,26 +1,141 @@
-mod airgapped;
+// main.rs - the `nqrust-identity` binary
+// The TUI (`app` for state and flows, `ui` for drawing) lives here; the
+// library crate holds everything a headless or embedded install needs, and
+// is brought into scope below so both halves name it as `crate::…`.
+
 mod app;
-mod templates;
 mod ui;
-mod utils;
 
-use app::App;
+use clap::Parser;
+use crossterm::event::{DisableBracketedPaste, EnableBracketedPaste};
+use nqrust_installer::{
+    airgapped, artifacts, build_info, bundle, cli, compose_lint, compose_override, daemon,
+    db_tuning, diagnostics, disk, docker_context, doctor, env_file, env_template, external_db,
+    file_security, firewall, gpu, headless, hooks, http, i18n, install_log, keycloak_migrate, ldap,
+    manifest, output, platform, progress, registry, remote, rollback, runner, secrets, self_update,
+    selinux, settings, sizing, smtp, stack, systemd, templates, throttle, token_check,
+    update_check, updates, utils, verify, xdg, yaml_file,
+};
 
+use app::App;
+use app::answers::Answers;
+use cli::Cli;
+use runner::SystemRunner;
+use settings::Settings;
 #[tokio::main]
 async fn main() -> color_eyre::Result<()> {
+    let started = std::time::Instant::now();
+    platform::configure_docker_host();
     color_eyre::install()?;
+    let cli = Cli::parse();
+    stack::select(&cli.stack)?;
+    if let Some(dir) = cli.project_dir() {
+        utils::set_project_root(dir);
+    }
+    let settings = Settings::load();
+    // With --remote the SSH target fixes the daemon, not a context.
+    docker_context::select(settings.docker_context.filter(|_| cli.remote().is_none()));
+    throttle::select(cli.limits(&settings.bandwidth));
+    // The TUI owns the terminal; JSON events only make sense without it.
+    if cli.headless || cli.uninstall {
+        output::set_format(cli.output);
+    }
+
+    if cli.diagnostics {
+        return diagnostics::report(started);
+    }
+
+    if cli.uninstall {
+        return headless::uninstall(&cli, cli.purge).await;
+    }
+    if cli.check_updates_daemon {
+        return update_check::run_daemon(&cli).await;
+    }
+    if let Some(dest) = cli.export_bundle.clone() {
+        let since = cli.since.clone();
+        let identity_tag = match settings.pinned_tags.get("identity") {
+            Some(pin) => pin.tag.clone(),
+            None => {
+                let client = http::client(false, &settings.proxy);
+                let token = registry::Registry::ghcr().credential();
+                updates::fetch_latest_identity_tag(
+                    &client,
+                    &settings.update_sources,
+                    token.as_deref(),
+                )
+                .await
+                .unwrap_or_else(|| "latest".to_string())
+            }
+        };
+        return tokio::task::spawn_blocking(move || {
+            airgapped::bundle::export(&SystemRunner, &dest, since.as_deref(), &identity_tag)
+        })
+        .await?;
+    }
 
-    // Check if running as airgapped binary and setup if needed
-    if airgapped::is_airgapped_binary()? {
-        airgapped::setup().await?;
-        println!(
-            "Installer running in offline mode (images from embedded payload only, no pull from internet)."
-        );
+    if cli.headless {
+        // Offline setup: from a bundle directory, or the payload embedded in
+        // this binary. The TUI runs the same setup on its own screen.
+        if let Some(source) = airgapped::Source::from_cli(&cli)? {
+            let workdir = cli.workdir();
+            let runner = remote::runner(cli.remote().as_ref());
+            tokio::task::spawn_blocking(move || {
+                airgapped::setup(
+                    &source,
+                    workdir.as_deref(),
+                    runner.as_ref(),
+                    &airgapped::progress::Reporter::default(),
+                )
+            })
+            .await?
+            .inspect_err(log_error)?;
+            println!(
+                "Installer running in offline mode (images from embedded payload only, no pull from internet)."
+            );
+        }
+        return headless::install(&cli).await;
     }
 
+    // A bad answers file fails here, before the terminal is taken over.
+    let answers = cli.answers.as_deref().map(Answers::load).transpose()?;
+    ui::theme::init(cli.theme, cli.plain);
+    i18n::init(cli.lang);
     let mut terminal = ratatui::init();
-    let mut app = App::new();
+    // Pasted tokens arrive as one event instead of a burst of key presses.
+    let _ = crossterm::execute!(std::io::stdout(), EnableBracketedPaste);
+    // ratatui's hook leaves raw mode and the alternate screen on a panic;
+    // paste mode has to be turned off as well or the shell receives the
+    // bracket markers with every paste.
+    let restore = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = crossterm::execute!(std::io::stdout(), DisableBracketedPaste);
+        restore(info);
+    }));
+    let mut app = App::new(&cli);
+    if let Some(answers) = answers {
+        app.use_answers(answers);
+    }
     let result = app.run(&mut terminal).await;
+    let _ = crossterm::execute!(std::io::stdout(), DisableBracketedPaste);
     ratatui::restore();
+    // A screen reader can't scroll back through the alternate screen; in
+    // plain mode the last log is left in the terminal, one line at a time.
+    if ui::theme::current() == ui::theme::Theme::Plain {
+        for line in app.logs() {
+            println!("{line}");
+        }
+    }
+    if let Some(message) = app.exit_message() {
+        println!("{message}");
+    }
     result
 }
+
+fn log_error(e: &color_eyre::Report) {
+    let message = format!("{e:#}");
+    install_log::write(install_log::Level::Error, &message);
+    output::emit(&output::Event::Error {
+        code: "airgapped_setup_failed",
+        message: &message,
+    });
+}
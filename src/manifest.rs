@@ -0,0 +1,53 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::build_info;
+
+const MANIFEST_FILE: &str = ".nqrust-install-manifest.json";
+
+/// Record of the last successful install, written next to the compose bundle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallManifest {
+    pub installer: String,
+    pub installer_version: String,
+    pub installer_git_sha: String,
+    pub mode: String,
+    pub platform: String,
+    pub identity_tag: String,
+    pub compose_files: Vec<String>,
+    pub installed_at: DateTime<Utc>,
+}
+
+impl InstallManifest {
+    pub fn new(airgapped: bool, identity_tag: &str, compose_files: Vec<String>) -> Self {
+        Self {
+            installer: build_info::NAME.to_string(),
+            installer_version: build_info::VERSION.to_string(),
+            installer_git_sha: build_info::GIT_SHA.to_string(),
+            mode: build_info::mode(airgapped).to_string(),
+            platform: build_info::platform(),
+            identity_tag: identity_tag.to_string(),
+            compose_files,
+            installed_at: Utc::now(),
+        }
+    }
+
+    pub fn path(root: &Path) -> PathBuf {
+        root.join(MANIFEST_FILE)
+    }
+
+    pub fn load(root: &Path) -> Option<Self> {
+        fs::read_to_string(Self::path(root))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+    }
+
+    pub fn save(&self, root: &Path) -> Result<()> {
+        fs::write(Self::path(root), serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+// output.rs - how headless runs report progress
+// Text mode prints the same lines the TUI shows. `--output json` prints one
+// JSON event per line on stdout instead, for Ansible and other orchestration
+// tools: step boundaries, per-service progress, log lines, errors with a
+// stable code, and a final result. Library callers (`api`) receive the same
+// events through a listener instead of stdout.
+
+use std::cell::Cell;
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::install_log::Level;
+
+static FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+
+type Listener = Arc<dyn Fn(&Event<'_>) + Send + Sync>;
+static LISTENER: Mutex<Option<Listener>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// Select the format for the rest of the run. Only the first call counts.
+pub fn set_format(format: OutputFormat) {
+    let _ = FORMAT.set(format);
+}
+
+pub fn is_json() -> bool {
+    FORMAT.get() == Some(&OutputFormat::Json)
+}
+
+/// Deliver events, and progress lines as `Log` events, to `listener`
+/// instead of stdout until the returned guard is dropped.
+pub fn listen(listener: impl Fn(&Event<'_>) + Send + Sync + 'static) -> ListenGuard {
+    if let Ok(mut slot) = LISTENER.lock() {
+        *slot = Some(Arc::new(listener));
+    }
+    ListenGuard(())
+}
+
+/// Removes the listener installed by `listen` when dropped.
+pub struct ListenGuard(());
+
+impl Drop for ListenGuard {
+    fn drop(&mut self) {
+        if let Ok(mut slot) = LISTENER.lock() {
+            *slot = None;
+        }
+    }
+}
+
+thread_local! {
+    /// Set while this thread runs the listener.
+    static NOTIFYING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Hand `event` to the listener; false when there is none. The lock is
+/// released before the call, so the listener may replace itself; what it
+/// logs through this module goes to stdout rather than back to it.
+fn notify(event: &Event<'_>) -> bool {
+    if NOTIFYING.get() {
+        return false;
+    }
+    let listener = match LISTENER.lock() {
+        Ok(slot) => slot.clone(),
+        Err(_) => return false,
+    };
+    match listener {
+        Some(listener) => {
+            NOTIFYING.set(true);
+            listener(event);
+            NOTIFYING.set(false);
+            true
+        }
+        None => false,
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    Log {
+        level: Level,
+        message: &'a str,
+    },
+    StepStarted {
+        step: &'a str,
+        index: usize,
+        total: usize,
+    },
+    StepFinished {
+        step: &'a str,
+        duration_ms: u64,
+    },
+    /// A compose status line for one service of the running step, e.g.
+    /// `Pulled` or `Started`.
+    Progress {
+        service: &'a str,
+        status: &'a str,
+    },
+    Error {
+        /// Stable identifier such as `pull_failed` or `health_timeout`.
+        code: &'a str,
+        message: &'a str,
+    },
+    Finished {
+        success: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        tag: Option<&'a str>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        url: Option<&'a str>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        log_path: Option<&'a Path>,
+    },
+}
+
+/// Print `event` as one JSON line; nothing in text mode.
+pub fn emit(event: &Event<'_>) {
+    if notify(event) {
+        return;
+    }
+    if is_json()
+        && let Ok(json) = serde_json::to_string(event)
+    {
+        println!("{json}");
+    }
+}
+
+/// A human-readable progress line: printed as is, or as a `log` event.
+pub fn line(message: &str) {
+    let event = Event::Log {
+        level: Level::of(message),
+        message: message.trim(),
+    };
+    if notify(&event) {
+        return;
+    }
+    if is_json() {
+        emit(&Event::Log {
+            level: Level::of(message),
+            message: message.trim(),
+        });
+    } else {
+        println!("{message}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_shape() {
+        let json = serde_json::to_string(&Event::Error {
+            code: "pull_failed",
+            message: "docker compose pull failed",
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            r#"{"event":"error","code":"pull_failed","message":"docker compose pull failed"}"#
+        );
+    }
+
+    #[test]
+    fn test_listener_may_log_and_remove_itself() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&calls);
+        let guard = Arc::new(Mutex::new(None));
+        let own = Arc::clone(&guard);
+        *guard.lock().unwrap() = Some(listen(move |event| {
+            if let Event::Log { message, .. } = event {
+                seen.lock().unwrap().push(message.to_string());
+                line("from the listener");
+                own.lock().unwrap().take();
+            }
+        }));
+        line("⏳ Pulling images");
+        assert!(
+            calls
+                .lock()
+                .unwrap()
+                .contains(&"⏳ Pulling images".to_string())
+        );
+        assert!(guard.lock().unwrap().is_none());
+    }
+}
@@ -0,0 +1,320 @@
+// platform.rs - OS differences the installer has to care about
+// Linux is the primary target; Windows Server hosts run the same TUI against
+// Docker Desktop, so anything that shells out or touches the filesystem in an
+// OS-specific way goes through here.
+
+use std::path::PathBuf;
+
+use tokio::process::{Child, Command};
+
+use crate::build_info;
+use crate::runner::Cmd;
+
+/// File name of the installer binary on this OS.
+pub fn exe_name() -> String {
+    format!("{}{}", build_info::NAME, std::env::consts::EXE_SUFFIX)
+}
+
+/// Where `--no-root` self-updates install: `~/.local/bin`, or
+/// `%LOCALAPPDATA%\Programs\nqrust-identity` on Windows.
+pub fn user_bin_dir() -> Option<PathBuf> {
+    #[cfg(windows)]
+    let dir = std::env::var_os("LOCALAPPDATA")
+        .map(|dir| PathBuf::from(dir).join("Programs").join(build_info::NAME));
+    #[cfg(not(windows))]
+    let dir = home_path(".local/bin");
+    dir
+}
+
+/// A command line run through the system shell (`sh -c` / `cmd /C`).
+pub fn shell_command(command: &str) -> Cmd {
+    #[cfg(windows)]
+    let (shell, flag) = ("cmd", "/C");
+    #[cfg(not(windows))]
+    let (shell, flag) = ("sh", "-c");
+    Cmd::new(shell).arg(flag).arg(command)
+}
+
+/// Start `cmd` in its own process group, so `kill_tree` also reaches what it
+/// spawns: `docker compose` runs the compose plugin as a separate process.
+pub fn own_process_group(cmd: &mut Command) {
+    #[cfg(unix)]
+    cmd.process_group(0);
+    #[cfg(not(unix))]
+    let _ = cmd;
+}
+
+/// Kill `child` and every process it started.
+pub async fn kill_tree(child: &mut Child) {
+    if let Some(pid) = child.id() {
+        #[cfg(unix)]
+        // SAFETY: plain kill(2); the negative pid addresses the process group
+        // `own_process_group` created for this child.
+        unsafe {
+            libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+        }
+        // Not through a `CommandRunner`: this is `SystemRunner` cleaning up
+        // a process it started itself, and only that runner has a pid here.
+        #[cfg(windows)]
+        let _ = Command::new("taskkill")
+            .args(["/T", "/F", "/PID", &pid.to_string()])
+            .output()
+            .await;
+    }
+    let _ = child.kill().await;
+}
+
+/// Which Docker daemon the host runs; decides sockets and troubleshooting text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DockerRuntime {
+    /// dockerd managed by systemd (Linux).
+    Engine,
+    DockerDesktop,
+    /// Colima VM on macOS.
+    Colima,
+    /// dockerd run by the user (`dockerd-rootless-setuptool.sh`), socket
+    /// under $XDG_RUNTIME_DIR. Managed with `systemctl --user`, no sudo.
+    Rootless,
+}
+
+impl DockerRuntime {
+    /// Judged from what is installed, so it also works while the daemon is down.
+    pub fn detect() -> Self {
+        if cfg!(windows) {
+            return DockerRuntime::DockerDesktop;
+        }
+        if !cfg!(target_os = "macos") {
+            let runtime_dir = std::env::var("XDG_RUNTIME_DIR").ok();
+            let rootless = is_rootless(
+                std::env::var("DOCKER_HOST").ok().as_deref(),
+                runtime_dir.as_deref(),
+                current_docker_context().as_deref(),
+                PathBuf::from("/var/run/docker.sock").exists(),
+            );
+            return if rootless {
+                DockerRuntime::Rootless
+            } else {
+                DockerRuntime::Engine
+            };
+        }
+        let colima_host = std::env::var("DOCKER_HOST").is_ok_and(|h| h.contains(".colima"));
+        let desktop = PathBuf::from("/Applications/Docker.app").exists();
+        let colima = home_path(".colima").is_some_and(|p| p.exists());
+        if colima_host || (colima && !desktop) {
+            DockerRuntime::Colima
+        } else {
+            DockerRuntime::DockerDesktop
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DockerRuntime::Engine => "Docker Engine",
+            DockerRuntime::DockerDesktop => "Docker Desktop",
+            DockerRuntime::Colima => "Colima",
+            DockerRuntime::Rootless => "Rootless Docker",
+        }
+    }
+
+    /// Troubleshooting lines for a daemon that does not answer.
+    pub fn daemon_help(self) -> String {
+        match self {
+            DockerRuntime::Engine => "- Start Docker daemon: sudo systemctl start docker\n\
+                 - Enable Docker on boot: sudo systemctl enable docker\n\
+                 - Check Docker status: sudo systemctl status docker\n\
+                 - Ensure your user is in docker group: sudo usermod -aG docker $USER"
+                .to_string(),
+            DockerRuntime::DockerDesktop => format!(
+                "- Start Docker: {}\n\
+                 - Wait until Docker Desktop reports \"Engine running\"\n\
+                 - Check the active context: docker context ls (desktop-linux or default)",
+                docker_start_hint()
+            ),
+            DockerRuntime::Colima => "- Start the VM: colima start\n\
+                 - Check it: colima status\n\
+                 - Check the active context: docker context use colima"
+                .to_string(),
+            DockerRuntime::Rootless => "- Start Docker daemon: systemctl --user start docker\n\
+                 - Enable it on boot: systemctl --user enable docker && loginctl enable-linger $USER\n\
+                 - Check Docker status: systemctl --user status docker\n\
+                 - Point docker at it: export DOCKER_HOST=unix://$XDG_RUNTIME_DIR/docker.sock"
+                .to_string(),
+        }
+    }
+
+    /// Where the daemon's own logs are.
+    pub fn logs_hint(self) -> &'static str {
+        match self {
+            DockerRuntime::Engine => "sudo journalctl -u docker -n 50",
+            DockerRuntime::DockerDesktop => "Docker Desktop → Troubleshoot → Diagnose",
+            DockerRuntime::Colima => "~/.colima/_lima/colima/ha.stderr.log",
+            DockerRuntime::Rootless => "journalctl --user -u docker -n 50",
+        }
+    }
+
+    /// Where image pulls get their proxy from.
+    pub fn proxy_hint(self) -> &'static str {
+        match self {
+            DockerRuntime::Engine => "/etc/systemd/system/docker.service.d/http-proxy.conf",
+            DockerRuntime::DockerDesktop => "Docker Desktop → Settings → Resources → Proxies",
+            DockerRuntime::Colima => {
+                "colima start --env HTTPS_PROXY=... (or ~/.colima/default/colima.yaml)"
+            }
+            DockerRuntime::Rootless => "~/.config/systemd/user/docker.service.d/http-proxy.conf",
+        }
+    }
+
+    /// Lowest host port the daemon can publish. A rootless daemon can't bind
+    /// below `net.ipv4.ip_unprivileged_port_start` (1024 unless lowered).
+    pub fn lowest_publishable_port(self) -> u16 {
+        if self != DockerRuntime::Rootless {
+            return 0;
+        }
+        std::fs::read_to_string("/proc/sys/net/ipv4/ip_unprivileged_port_start")
+            .ok()
+            .and_then(|start| start.trim().parse().ok())
+            .unwrap_or(1024)
+    }
+}
+
+/// How to start the Docker daemon, for error messages.
+pub fn docker_start_hint() -> String {
+    #[cfg(windows)]
+    let hint = match docker_desktop_exe() {
+        Some(exe) => format!("start Docker Desktop ({})", exe.display()),
+        None => "install and start Docker Desktop \
+                 (https://docs.docker.com/desktop/setup/install/windows-install/)"
+            .to_string(),
+    };
+    #[cfg(not(windows))]
+    let hint = match DockerRuntime::detect() {
+        DockerRuntime::Engine => "sudo systemctl start docker",
+        DockerRuntime::DockerDesktop => "open -a Docker",
+        DockerRuntime::Colima => "colima start",
+        DockerRuntime::Rootless => "systemctl --user start docker",
+    }
+    .to_string();
+    hint
+}
+
+/// Point docker at a Docker Desktop / Colima socket on macOS when nothing
+/// else tells it where the daemon is: no `DOCKER_HOST`, no context selected
+/// and no `/var/run/docker.sock` (Colima and newer Desktop installs don't
+/// create it). Called once at startup, before any docker command runs.
+pub fn configure_docker_host() {
+    if !cfg!(target_os = "macos")
+        || std::env::var_os("DOCKER_HOST").is_some()
+        || std::env::var_os("DOCKER_CONTEXT").is_some()
+        || PathBuf::from("/var/run/docker.sock").exists()
+        || has_docker_context()
+    {
+        return;
+    }
+    let candidates = [
+        ".docker/run/docker.sock",
+        ".colima/default/docker.sock",
+        ".colima/docker.sock",
+    ];
+    if let Some(socket) = candidates
+        .iter()
+        .filter_map(|rel| home_path(rel))
+        .find(|p| p.exists())
+    {
+        // SAFETY: runs first thing in main, before the installer starts any
+        // task or thread that reads the environment.
+        unsafe { std::env::set_var("DOCKER_HOST", format!("unix://{}", socket.display())) };
+    }
+}
+
+/// True if `~/.docker/config.json` selects a non-default context.
+fn has_docker_context() -> bool {
+    current_docker_context().is_some_and(|context| context != "default")
+}
+
+/// Context named by DOCKER_CONTEXT, the installer's pick or
+/// `~/.docker/config.json`.
+fn current_docker_context() -> Option<String> {
+    if let Some(context) = crate::docker_context::selected() {
+        return Some(context);
+    }
+    if let Ok(context) = std::env::var("DOCKER_CONTEXT") {
+        return Some(context);
+    }
+    home_path(".docker/config.json")
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|raw| serde_json::from_str::<serde_json::Value>(&raw).ok())
+        .and_then(|config| config["currentContext"].as_str().map(str::to_string))
+}
+
+/// Whether docker talks to a rootless daemon: DOCKER_HOST is a socket under
+/// the user's runtime dir, or, without one, the `rootless` context that
+/// `dockerd-rootless-setuptool.sh` creates is in use, or only the user's
+/// socket exists.
+fn is_rootless(
+    docker_host: Option<&str>,
+    runtime_dir: Option<&str>,
+    context: Option<&str>,
+    system_socket: bool,
+) -> bool {
+    let user_socket = |path: &str| {
+        path.starts_with("/run/user/") || runtime_dir.is_some_and(|dir| path.starts_with(dir))
+    };
+    if let Some(host) = docker_host.filter(|host| !host.is_empty()) {
+        return host.strip_prefix("unix://").is_some_and(user_socket);
+    }
+    if let Some(context) = context.filter(|context| *context != "default") {
+        return context == "rootless";
+    }
+    !system_socket && runtime_dir.is_some_and(|dir| PathBuf::from(dir).join("docker.sock").exists())
+}
+
+fn home_path(rel: &str) -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(rel))
+}
+
+/// Docker Desktop's executable, if it is installed.
+#[cfg(windows)]
+pub fn docker_desktop_exe() -> Option<PathBuf> {
+    let program_files =
+        std::env::var_os("ProgramFiles").unwrap_or_else(|| r"C:\Program Files".into());
+    let exe = PathBuf::from(program_files)
+        .join("Docker")
+        .join("Docker")
+        .join("Docker Desktop.exe");
+    exe.is_file().then_some(exe)
+}
+
+/// Command that prints a file's SHA256, for manual verification hints.
+pub fn sha256_command(file: &str) -> String {
+    #[cfg(windows)]
+    let command = format!("certutil -hashfile {file} SHA256");
+    #[cfg(not(windows))]
+    let command = format!("sha256sum {file}");
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_rootless() {
+        let run = Some("/run/user/1000");
+        assert!(is_rootless(
+            Some("unix:///run/user/1000/docker.sock"),
+            run,
+            None,
+            true
+        ));
+        assert!(!is_rootless(
+            Some("unix:///var/run/docker.sock"),
+            run,
+            Some("rootless"),
+            true
+        ));
+        assert!(!is_rootless(Some("tcp://10.0.0.5:2376"), run, None, false));
+        assert!(is_rootless(None, run, Some("rootless"), true));
+        assert!(!is_rootless(None, run, Some("colima"), false));
+        assert!(!is_rootless(None, None, None, true));
+    }
+}
@@ -0,0 +1,344 @@
+// progress.rs - per-service install progress
+// The service list comes from `compose config` (so overrides and extra
+// services count), falling back to the stack definition. Compose events move
+// each service along pull → build → create → start: JSON events from
+// `--progress json` where compose supports it, plain status lines otherwise.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::i18n::tr;
+use crate::runner::{Cmd, CommandRunner};
+use crate::stack;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ServiceState {
+    #[default]
+    Waiting,
+    Pulling,
+    Pulled,
+    Building,
+    Built,
+    Creating,
+    Created,
+    Starting,
+    Started,
+    Healthy,
+    Failed,
+}
+
+impl ServiceState {
+    pub fn label(self) -> &'static str {
+        match self {
+            ServiceState::Waiting => "waiting",
+            ServiceState::Pulling => "pulling",
+            ServiceState::Pulled => "pulled",
+            ServiceState::Building => "building",
+            ServiceState::Built => "built",
+            ServiceState::Creating => "creating",
+            ServiceState::Created => "created",
+            ServiceState::Starting => "starting",
+            ServiceState::Started => "started",
+            ServiceState::Healthy => "healthy",
+            ServiceState::Failed => "failed",
+        }
+    }
+
+    /// `label` in the UI language.
+    pub fn display_label(self) -> &'static str {
+        match self {
+            ServiceState::Waiting => tr("service_state.waiting"),
+            ServiceState::Pulling => tr("service_state.pulling"),
+            ServiceState::Pulled => tr("service_state.pulled"),
+            ServiceState::Building => tr("service_state.building"),
+            ServiceState::Built => tr("service_state.built"),
+            ServiceState::Creating => tr("service_state.creating"),
+            ServiceState::Created => tr("service_state.created"),
+            ServiceState::Starting => tr("service_state.starting"),
+            ServiceState::Started => tr("service_state.started"),
+            ServiceState::Healthy => tr("service_state.healthy"),
+            ServiceState::Failed => tr("service_state.failed"),
+        }
+    }
+
+    /// How far along a service is, 0.0..=1.0, not counting layer progress.
+    fn weight(self) -> f64 {
+        match self {
+            ServiceState::Waiting | ServiceState::Failed => 0.0,
+            ServiceState::Pulling | ServiceState::Building => 0.2,
+            ServiceState::Pulled | ServiceState::Built => 0.4,
+            ServiceState::Creating => 0.5,
+            ServiceState::Created => 0.6,
+            ServiceState::Starting => 0.8,
+            ServiceState::Started | ServiceState::Healthy => 1.0,
+        }
+    }
+
+    /// Compose's status word (`Pulled`, `Started`, ...), if it is a state change.
+    fn from_status(status: &str) -> Option<Self> {
+        Some(match status {
+            "Pulling" => ServiceState::Pulling,
+            "Pulled" | "Skipped" => ServiceState::Pulled,
+            "Building" => ServiceState::Building,
+            "Built" => ServiceState::Built,
+            "Creating" | "Recreate" => ServiceState::Creating,
+            "Created" | "Recreated" => ServiceState::Created,
+            "Starting" => ServiceState::Starting,
+            "Started" | "Running" => ServiceState::Started,
+            "Healthy" => ServiceState::Healthy,
+            "Error" => ServiceState::Failed,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ServiceProgress {
+    /// Compose service name.
+    pub name: String,
+    pub container: Option<String>,
+    pub state: ServiceState,
+    /// Per image layer, 0..=100: download is the first half, extraction the second.
+    layers: BTreeMap<String, f64>,
+}
+
+impl ServiceProgress {
+    fn new(name: String, container: Option<String>) -> Self {
+        Self {
+            name,
+            container,
+            state: ServiceState::Waiting,
+            layers: BTreeMap::new(),
+        }
+    }
+
+    /// Share of the image pulled, known only from JSON layer events.
+    pub fn pull_percent(&self) -> Option<f64> {
+        if self.layers.is_empty() {
+            return None;
+        }
+        Some(self.layers.values().sum::<f64>() / self.layers.len() as f64)
+    }
+
+    fn weight(&self) -> f64 {
+        match (self.state, self.pull_percent()) {
+            (ServiceState::Pulling, Some(percent)) => 0.2 + 0.2 * percent / 100.0,
+            (state, _) => state.weight(),
+        }
+    }
+
+    /// Compose prints the service name while pulling and building, and the
+    /// container name (`container_name`, or `<project>-<service>-1`) after.
+    fn is_named(&self, name: &str) -> bool {
+        name == self.name
+            || self.container.as_deref() == Some(name)
+            || name
+                .strip_suffix("-1")
+                .is_some_and(|rest| rest.ends_with(&format!("-{}", self.name)))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct InstallProgress {
+    pub services: Vec<ServiceProgress>,
+}
+
+impl InstallProgress {
+    /// Services of the selected stack, before the compose file is read.
+    pub fn from_stack() -> Self {
+        Self {
+            services: stack::current()
+                .services
+                .iter()
+                .map(|s| ServiceProgress::new(s.service.to_string(), Some(s.container.to_string())))
+                .collect(),
+        }
+    }
+
+    /// Service list from `compose config --format json`; the stack definition
+    /// when compose can't render it (docker-compose v1, broken override).
+    pub async fn load(
+        runner: &dyn CommandRunner,
+        compose_cmd: &[String],
+        compose_args: &[String],
+        root: &Path,
+    ) -> Self {
+        let output = runner
+            .output(
+                &Cmd::compose(compose_cmd)
+                    .args(compose_args)
+                    .args(["config", "--format", "json"])
+                    .dir(root),
+            )
+            .await;
+        match output {
+            Ok(output) if output.success => {
+                Self::from_config_json(&output.stdout).unwrap_or_else(Self::from_stack)
+            }
+            _ => Self::from_stack(),
+        }
+    }
+
+    fn from_config_json(json: &str) -> Option<Self> {
+        let config: serde_json::Value = serde_json::from_str(json).ok()?;
+        let services = config["services"].as_object()?;
+        Some(Self {
+            services: services
+                .iter()
+                .map(|(name, service)| {
+                    ServiceProgress::new(
+                        name.clone(),
+                        service["container_name"].as_str().map(str::to_string),
+                    )
+                })
+                .collect(),
+        })
+    }
+
+    /// Apply one line of compose output. Returns the service it moved.
+    /// Lines look like ` ✔ Container identity-db  Started` or ` postgres Pulled`.
+    pub fn update(&mut self, line: &str) -> Option<&ServiceProgress> {
+        let mut words = line
+            .split_whitespace()
+            .filter(|w| !matches!(*w, "✔" | "✘" | "⠿" | "Container" | "Image" | "Service"));
+        let name = words.next()?;
+        let state = ServiceState::from_status(words.next()?)?;
+        let service = self.services.iter_mut().find(|s| s.is_named(name))?;
+        service.state = state;
+        Some(service)
+    }
+
+    /// Apply one `--progress json` event. Returns the service whose state
+    /// changed; layer updates only move its pull percentage.
+    pub fn apply(&mut self, event: &ComposeEvent) -> Option<&ServiceProgress> {
+        if !event.parent_id.is_empty() {
+            let service = self
+                .services
+                .iter_mut()
+                .find(|s| s.is_named(&event.parent_id))?;
+            let percent = f64::from(event.percent.min(100));
+            let layer = match event.text.as_str() {
+                "Downloading" | "Verifying Checksum" => percent / 2.0,
+                "Download complete" => 50.0,
+                "Extracting" => 50.0 + percent / 2.0,
+                "Pull complete" | "Already exists" => 100.0,
+                _ => 0.0,
+            };
+            service.layers.insert(event.id.clone(), layer);
+            return None;
+        }
+        // "Container identity-db", "Image postgres:16-alpine" or just "postgres".
+        let name = event.id.rsplit(' ').next()?;
+        let state = ServiceState::from_status(&event.text)?;
+        let service = self.services.iter_mut().find(|s| s.is_named(name))?;
+        if service.state == state {
+            return None;
+        }
+        service.state = state;
+        Some(service)
+    }
+
+    pub fn percent(&self) -> f64 {
+        if self.services.is_empty() {
+            return 0.0;
+        }
+        let done: f64 = self.services.iter().map(ServiceProgress::weight).sum();
+        done / self.services.len() as f64 * 100.0
+    }
+
+    pub fn started(&self) -> usize {
+        self.services
+            .iter()
+            .filter(|s| s.state >= ServiceState::Started && s.state != ServiceState::Failed)
+            .count()
+    }
+}
+
+/// One line of `docker compose --progress json` output.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ComposeEvent {
+    /// `Container identity-db`, a service name, or an image layer id.
+    pub id: String,
+    /// Service a layer event belongs to.
+    pub parent_id: String,
+    /// Short state: `Pulling`, `Downloading`, `Started`, `Error`, ...
+    pub text: String,
+    /// Detail: a progress bar, or the error message.
+    pub status: String,
+    pub percent: u32,
+    /// Free-form output compose passes through (warnings, build logs).
+    pub tail: bool,
+}
+
+impl ComposeEvent {
+    /// `None` for lines that aren't JSON events, e.g. from an older compose.
+    pub fn parse(line: &str) -> Option<Self> {
+        if !line.trim_start().starts_with('{') {
+            return None;
+        }
+        serde_json::from_str(line).ok()
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.text == "Error"
+    }
+}
+
+/// Whether this compose accepts `--progress json` (Compose v2.2x+).
+pub async fn json_progress_supported(runner: &dyn CommandRunner, compose_cmd: &[String]) -> bool {
+    runner
+        .output(&Cmd::compose(compose_cmd).args(["--progress", "json", "version"]))
+        .await
+        .is_ok_and(|output| output.success)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compose_lines_move_services() {
+        let mut progress = InstallProgress::from_config_json(
+            r#"{"services":{"postgres":{"container_name":"identity-db"},"worker":{}}}"#,
+        )
+        .unwrap();
+        progress.update(" postgres Pulled");
+        assert_eq!(progress.services[0].state, ServiceState::Pulled);
+        progress.update(" ✔ Container identity-db  Started");
+        progress.update(" Container identity-worker-1  Creating");
+        assert_eq!(progress.services[0].state, ServiceState::Started);
+        assert_eq!(progress.services[1].state, ServiceState::Creating);
+        assert!(progress.update("a1b2c3 Pulling fs layer").is_none());
+        assert_eq!(progress.started(), 1);
+    }
+
+    #[test]
+    fn test_json_events_track_layers() {
+        let mut progress = InstallProgress::from_stack();
+        let events = [
+            r#"{"id":"postgres","text":"Pulling","status":"Pulling"}"#,
+            r#"{"id":"a1b2","parent_id":"postgres","text":"Downloading","status":"[==>  ] 1MB/4MB","current":1048576,"total":4194304,"percent":25}"#,
+            r#"{"id":"c3d4","parent_id":"postgres","text":"Pull complete","percent":100}"#,
+        ];
+        for line in events {
+            progress.apply(&ComposeEvent::parse(line).unwrap());
+        }
+        let postgres = &progress.services[0];
+        assert_eq!(postgres.state, ServiceState::Pulling);
+        assert_eq!(postgres.pull_percent(), Some(56.25));
+
+        let error = ComposeEvent::parse(
+            r#"{"id":"Container identity-db","text":"Error","status":"port is already allocated"}"#,
+        )
+        .unwrap();
+        assert!(error.is_error());
+        assert_eq!(
+            progress.apply(&error).map(|s| s.state),
+            Some(ServiceState::Failed)
+        );
+        assert!(ComposeEvent::parse(" Container identity-db  Started").is_none());
+    }
+}
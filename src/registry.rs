@@ -0,0 +1,452 @@
+// registry.rs - container registries the installer logs into
+// GHCR is always configured; Docker Hub, Harbor and ECR entries come from
+// settings. One of them can be picked as a mirror, in which case the compose
+// override rewrites every stack image to pull through it.
+
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use color_eyre::{
+    Result,
+    eyre::{bail, eyre},
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::runner::{Cmd, CommandRunner};
+use crate::settings::Settings;
+use crate::{utils, xdg};
+
+pub const GHCR: &str = "ghcr";
+/// Keyring service name; the account is the registry name.
+const KEYRING_SERVICE: &str = "nqrust-identity";
+
+/// Where `save_credential` put the secret.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CredentialStore {
+    Keyring,
+    /// Plaintext file readable only by the current user.
+    File,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RegistryKind {
+    /// GitHub Container Registry, logged into with a personal access token.
+    #[default]
+    Ghcr,
+    DockerHub,
+    /// Any registry speaking the plain `docker login` protocol (Harbor, Nexus, ...).
+    Harbor,
+    /// AWS ECR; the password comes from `aws ecr get-login-password`.
+    Ecr,
+}
+
+impl RegistryKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            RegistryKind::Ghcr => "GitHub Container Registry",
+            RegistryKind::DockerHub => "Docker Hub",
+            RegistryKind::Harbor => "Harbor",
+            RegistryKind::Ecr => "AWS ECR",
+        }
+    }
+
+    fn default_host(self) -> &'static str {
+        match self {
+            RegistryKind::Ghcr => "ghcr.io",
+            RegistryKind::DockerHub => "docker.io",
+            RegistryKind::Harbor | RegistryKind::Ecr => "",
+        }
+    }
+}
+
+/// One registry entry in settings:
+///
+/// ```json
+/// { "name": "corp", "kind": "harbor", "host": "harbor.corp", "username": "robot$ci", "project": "mirror" }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Registry {
+    /// Short id used for the saved credential and the `mirror` setting.
+    pub name: String,
+    pub kind: RegistryKind,
+    /// Registry host; defaults to ghcr.io / docker.io, required for Harbor and ECR.
+    pub host: Option<String>,
+    pub username: Option<String>,
+    /// Project (namespace) mirrored images live under, e.g. `harbor.corp/<project>/...`.
+    pub project: Option<String>,
+    /// AWS region for ECR; read from the host when unset.
+    pub region: Option<String>,
+}
+
+impl Registry {
+    pub fn ghcr() -> Self {
+        Self {
+            name: GHCR.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn host(&self) -> &str {
+        self.host
+            .as_deref()
+            .unwrap_or_else(|| self.kind.default_host())
+    }
+
+    /// ECR gets its password from the aws CLI, everything else needs a saved one.
+    pub fn needs_secret(&self) -> bool {
+        self.kind != RegistryKind::Ecr
+    }
+
+    /// Whether an install should try to log in: a secret is available, or none is needed.
+    pub fn has_credentials(&self) -> bool {
+        !self.needs_secret() || self.credential().is_some()
+    }
+
+    /// Password / token from the environment, else the one saved by the installer.
+    /// GHCR honours `GHCR_TOKEN` / `GITHUB_TOKEN` / `GH_TOKEN`; other registries
+    /// read `NQRUST_REGISTRY_<NAME>_PASSWORD`.
+    pub fn credential(&self) -> Option<String> {
+        if let Some(secret) = self
+            .env_vars()
+            .iter()
+            .find_map(|var| env::var(var).ok())
+            .filter(|s| !s.is_empty())
+        {
+            return Some(secret);
+        }
+        let entry = self.keyring_entry();
+        if let Some(entry) = &entry {
+            match entry.get_password() {
+                Ok(secret) => return Some(secret),
+                Err(keyring::Error::NoEntry) => {}
+                // No usable keyring: only the file fallback can hold it.
+                Err(_) => return self.read_credential_file(),
+            }
+        }
+        let secret = self.read_credential_file()?;
+        // Saved by an older version (or while the keyring was unavailable): move it.
+        if entry.is_some_and(|e| e.set_password(&secret).is_ok()) {
+            let _ = fs::remove_file(self.credential_path());
+        }
+        Some(secret)
+    }
+
+    /// Store in the OS keyring, or in a 0600 file when no keyring is available.
+    pub fn save_credential(&self, secret: &str) -> Result<CredentialStore> {
+        if self
+            .keyring_entry()
+            .is_some_and(|e| e.set_password(secret).is_ok())
+        {
+            let path = self.credential_path();
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+            return Ok(CredentialStore::Keyring);
+        }
+        write_private(&self.credential_path(), secret)?;
+        Ok(CredentialStore::File)
+    }
+
+    /// Remove the saved credential from the keyring and the file fallback.
+    pub fn forget_credential(&self) -> Result<()> {
+        if let Some(entry) = self.keyring_entry() {
+            match entry.delete_credential() {
+                Ok(())
+                | Err(
+                    keyring::Error::NoEntry
+                    | keyring::Error::NoStorageAccess(_)
+                    | keyring::Error::PlatformFailure(_),
+                ) => {}
+                Err(e) => return Err(eyre!("Could not remove keyring entry: {e}")),
+            }
+        }
+        let path = self.credential_path();
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        self.save_expiry("", None)
+    }
+
+    /// Remember when `secret` expires, as GitHub reported it at login; `None`
+    /// forgets it (a token without an expiry date).
+    pub fn save_expiry(&self, secret: &str, expires: Option<DateTime<Utc>>) -> Result<()> {
+        let path = self.expiry_path();
+        let Some(expires) = expires else {
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let saved = SavedExpiry {
+            fingerprint: fingerprint(secret),
+            expires,
+        };
+        fs::write(path, serde_json::to_string_pretty(&saved)? + "\n")?;
+        Ok(())
+    }
+
+    /// When `secret` expires, if it is the token the expiry was saved for.
+    pub fn expiry(&self, secret: &str) -> Option<DateTime<Utc>> {
+        let saved: SavedExpiry =
+            serde_json::from_str(&fs::read_to_string(self.expiry_path()).ok()?).ok()?;
+        (saved.fingerprint == fingerprint(secret)).then_some(saved.expires)
+    }
+
+    fn expiry_path(&self) -> PathBuf {
+        xdg::config_dir().join(format!("{}_token_expiry.json", self.name))
+    }
+
+    fn keyring_entry(&self) -> Option<keyring::Entry> {
+        keyring::Entry::new(KEYRING_SERVICE, &self.name).ok()
+    }
+
+    fn read_credential_file(&self) -> Option<String> {
+        fs::read_to_string(self.credential_path())
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    }
+
+    fn env_vars(&self) -> Vec<String> {
+        if self.name == GHCR {
+            return ["GHCR_TOKEN", "GITHUB_TOKEN", "GH_TOKEN"]
+                .map(String::from)
+                .to_vec();
+        }
+        let name = self.name.to_ascii_uppercase().replace(['-', '.'], "_");
+        vec![format!("NQRUST_REGISTRY_{name}_PASSWORD")]
+    }
+
+    /// File fallback in the config dir. Earlier versions kept it as
+    /// `.ghcr_token` (etc.) in the project directory; that file is moved over.
+    fn credential_path(&self) -> PathBuf {
+        let [old, new] = self.credential_files();
+        xdg::adopt(&old, &new)
+    }
+
+    /// Where the file fallback may be: the project directory of earlier
+    /// versions, then the config dir.
+    pub fn credential_files(&self) -> [PathBuf; 2] {
+        [
+            utils::project_root().join(format!(".{}_token", self.name)),
+            xdg::config_dir().join(format!("{}_token", self.name)),
+        ]
+    }
+
+    /// `docker login` with this registry's verb.
+    pub async fn login(&self, runner: &dyn CommandRunner, secret: Option<&str>) -> Result<()> {
+        let host = self.host();
+        if host.is_empty() {
+            bail!("Registry '{}' has no host configured", self.name);
+        }
+        let (username, password) = match self.kind {
+            RegistryKind::Ecr => ("AWS".to_string(), self.ecr_password(runner).await?),
+            RegistryKind::Ghcr => (
+                self.username.clone().unwrap_or_else(|| "token".to_string()),
+                secret
+                    .ok_or_else(|| eyre!("No token for {}", host))?
+                    .to_string(),
+            ),
+            RegistryKind::DockerHub | RegistryKind::Harbor => (
+                self.username
+                    .clone()
+                    .ok_or_else(|| eyre!("Registry '{}' has no username configured", self.name))?,
+                secret
+                    .ok_or_else(|| eyre!("No password for {}", host))?
+                    .to_string(),
+            ),
+        };
+
+        let mut cmd = Cmd::new("docker").arg("login");
+        // Docker Hub is the implicit default server.
+        if self.kind != RegistryKind::DockerHub {
+            cmd = cmd.arg(host);
+        }
+        let cmd = cmd
+            .args(["-u", &username, "--password-stdin"])
+            .stdin(password);
+
+        let output = runner.output(&cmd).await?;
+        if !output.success {
+            return Err(eyre!("{} login failed: {}", host, output.stderr.trim()));
+        }
+        Ok(())
+    }
+
+    /// Drop the login docker keeps in its own config / credential helper.
+    pub async fn logout(&self, runner: &dyn CommandRunner) -> Result<()> {
+        let mut cmd = Cmd::new("docker").arg("logout");
+        if self.kind != RegistryKind::DockerHub {
+            cmd = cmd.arg(self.host());
+        }
+        let output = runner.output(&cmd).await?;
+        if !output.success {
+            bail!("{} logout failed: {}", self.host(), output.stderr.trim());
+        }
+        Ok(())
+    }
+
+    async fn ecr_password(&self, runner: &dyn CommandRunner) -> Result<String> {
+        // <account>.dkr.ecr.<region>.amazonaws.com
+        let region = self
+            .region
+            .clone()
+            .or_else(|| self.host().split('.').nth(3).map(String::from))
+            .ok_or_else(|| eyre!("Registry '{}' has no AWS region", self.name))?;
+        let output = runner
+            .output(&Cmd::new("aws").args(["ecr", "get-login-password", "--region", &region]))
+            .await
+            .map_err(|e| eyre!("Could not run the aws CLI: {e}"))?;
+        if !output.success {
+            bail!(
+                "aws ecr get-login-password failed: {}",
+                output.stderr.trim()
+            );
+        }
+        Ok(output.stdout.trim().to_string())
+    }
+}
+
+/// Expiry saved next to a credential. The fingerprint ties it to one token,
+/// so a token from `GHCR_TOKEN` or a replaced one is not judged by it.
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedExpiry {
+    fingerprint: String,
+    expires: DateTime<Utc>,
+}
+
+/// First 16 hex digits of the secret's SHA-256.
+fn fingerprint(secret: &str) -> String {
+    let digest = format!("{:x}", Sha256::digest(secret.as_bytes()));
+    digest[..16].to_string()
+}
+
+/// Write `contents` to `path` with mode 0600.
+pub fn write_private(path: &Path, contents: &str) -> Result<()> {
+    // Recreate so an older, wider-permission file doesn't keep its mode.
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    file.write_all(contents.as_bytes())?;
+    Ok(())
+}
+
+/// GHCR followed by the registries from settings. An entry named `ghcr`
+/// replaces the built-in one (e.g. to set a username).
+pub fn configured(settings: &Settings) -> Vec<Registry> {
+    let mut registries = vec![Registry::ghcr()];
+    for registry in &settings.registries {
+        if registry.name == GHCR {
+            registries[0] = registry.clone();
+        } else {
+            registries.push(registry.clone());
+        }
+    }
+    registries
+}
+
+/// The registry selected as mirror, if it is still configured.
+pub fn mirror(settings: &Settings) -> Option<Registry> {
+    let name = settings.mirror.as_deref()?;
+    configured(settings).into_iter().find(|r| r.name == name)
+}
+
+/// `image` as pulled through `mirror`: the source registry host is replaced by
+/// the mirror host and project, keeping repository path and tag.
+/// `postgres:16-alpine` → `harbor.corp/mirror/library/postgres:16-alpine`.
+pub fn mirrored(image: &str, mirror: &Registry) -> String {
+    let path = match image.split_once('/') {
+        Some((first, rest)) if first.contains(['.', ':']) || first == "localhost" => {
+            rest.to_string()
+        }
+        Some(_) => image.to_string(),
+        None => format!("library/{image}"),
+    };
+    match mirror.project.as_deref().map(|p| p.trim_matches('/')) {
+        Some(project) if !project.is_empty() => format!("{}/{project}/{path}", mirror.host()),
+        _ => format!("{}/{path}", mirror.host()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::{CmdOutput, MockRunner};
+
+    #[tokio::test]
+    async fn test_login_sends_secret_on_stdin_and_reports_stderr() {
+        let runner = MockRunner::new().on(
+            "docker login harbor.corp",
+            CmdOutput::failed("Error response from daemon: unauthorized\n"),
+        );
+        let ghcr = Registry::ghcr();
+        ghcr.login(&runner, Some("ghp_secret")).await.unwrap();
+        let login = &runner.calls()[0];
+        assert_eq!(
+            login.line(),
+            "docker login ghcr.io -u token --password-stdin"
+        );
+        assert_eq!(login.stdin.as_deref(), Some("ghp_secret"));
+
+        let harbor = Registry {
+            name: "corp".to_string(),
+            kind: RegistryKind::Harbor,
+            host: Some("harbor.corp".to_string()),
+            username: Some("robot".to_string()),
+            ..Registry::default()
+        };
+        let err = harbor.login(&runner, Some("pw")).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "harbor.corp login failed: Error response from daemon: unauthorized"
+        );
+        assert!(harbor.login(&runner, None).await.is_err());
+        assert_eq!(runner.calls().len(), 2);
+    }
+
+    #[test]
+    fn test_mirrored_image_references() {
+        let harbor = Registry {
+            name: "corp".to_string(),
+            kind: RegistryKind::Harbor,
+            host: Some("harbor.corp".to_string()),
+            project: Some("mirror".to_string()),
+            ..Registry::default()
+        };
+        assert_eq!(
+            mirrored("postgres:16-alpine", &harbor),
+            "harbor.corp/mirror/library/postgres:16-alpine"
+        );
+        assert_eq!(
+            mirrored("ghcr.io/nexusquantum/nqrust-identity:v1", &harbor),
+            "harbor.corp/mirror/nexusquantum/nqrust-identity:v1"
+        );
+        assert_eq!(
+            mirrored(
+                "localhost:5000/caddy:2",
+                &Registry {
+                    project: None,
+                    ..harbor
+                }
+            ),
+            "harbor.corp/caddy:2"
+        );
+    }
+}
@@ -0,0 +1,259 @@
+// remote.rs - installing onto another host over SSH
+// With `--remote user@host` the TUI and forms stay on this machine and every
+// file (.env, certs, compose files) is generated in the local project
+// directory as usual. `RemoteRunner` then points docker and compose at the
+// remote daemon through `DOCKER_HOST=ssh://…`, and before compose creates
+// containers it copies the project directory to the same absolute path on
+// the remote host, because bind mounts are resolved there. Registry logins
+// stay in the local docker config; the client hands them to the remote
+// daemon when pulling.
+
+use std::io;
+use std::net::{IpAddr, ToSocketAddrs};
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+
+use crate::runner::{Cmd, CmdOutput, CommandRunner, Running, SystemRunner};
+
+/// Compose subcommands that create containers and so need the bind-mounted
+/// files on the remote host first.
+const SYNC_BEFORE: &[&str] = &["up", "create", "run", "start", "restart"];
+/// Programs that run on the remote host over plain `ssh`.
+const OVER_SSH: &[&str] = &["nvidia-smi"];
+/// Left out when copying the project directory.
+const SYNC_EXCLUDE: &[&str] = &["./backups"];
+
+/// `user@host` with an optional port, from `--remote`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteTarget {
+    pub destination: String,
+    pub port: Option<u16>,
+}
+
+impl RemoteTarget {
+    /// `user@host`, `user@host:2222` or `ssh://user@host[:port]`.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        let value = value.strip_prefix("ssh://").unwrap_or(value);
+        let value = value.trim_end_matches('/');
+        let (destination, port) = match value.rsplit_once(':') {
+            Some((destination, port)) if !port.is_empty() && !port.contains(']') => {
+                let port = port
+                    .parse()
+                    .map_err(|_| format!("'{port}' is not a port number"))?;
+                (destination, Some(port))
+            }
+            _ => (value, None),
+        };
+        let host = destination.rsplit('@').next().unwrap_or_default();
+        if host.is_empty() || destination.contains(['/', ' ']) {
+            return Err(format!("'{value}' is not a user@host SSH destination"));
+        }
+        Ok(Self {
+            destination: destination.to_string(),
+            port,
+        })
+    }
+
+    pub fn host(&self) -> &str {
+        self.destination.rsplit('@').next().unwrap_or_default()
+    }
+
+    /// `DOCKER_HOST` value for the remote daemon.
+    pub fn docker_host(&self) -> String {
+        match self.port {
+            Some(port) => format!("ssh://{}:{port}", self.destination),
+            None => format!("ssh://{}", self.destination),
+        }
+    }
+
+    /// The host's address for SERVER_IP and the certificate: the host itself
+    /// when it is an IP, else the first IPv4 address its name resolves to.
+    pub fn address(&self) -> Option<String> {
+        let host = self.host().trim_start_matches('[').trim_end_matches(']');
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            return Some(ip.to_string());
+        }
+        let addrs: Vec<_> = (host, 22).to_socket_addrs().ok()?.collect();
+        addrs
+            .iter()
+            .find(|addr| addr.is_ipv4())
+            .or(addrs.first())
+            .map(|addr| addr.ip().to_string())
+    }
+
+    /// `ssh [-p port] -- user@host`, to which the remote command is appended.
+    fn ssh(&self) -> Cmd {
+        let mut ssh = Cmd::new("ssh").args(["-o", "BatchMode=yes"]);
+        if let Some(port) = self.port {
+            ssh = ssh.args(["-p", &port.to_string()]);
+        }
+        ssh.args(["--", &self.destination])
+    }
+}
+
+/// Runs docker and compose against the remote daemon and everything else
+/// locally, except the few probes in `OVER_SSH`.
+#[derive(Debug, Clone)]
+pub struct RemoteRunner {
+    target: RemoteTarget,
+}
+
+impl RemoteRunner {
+    pub fn new(target: RemoteTarget) -> Self {
+        Self { target }
+    }
+
+    /// The command that actually runs for `cmd`.
+    fn map(&self, cmd: &Cmd) -> Cmd {
+        if OVER_SSH.contains(&cmd.program.as_str()) {
+            let remote = std::iter::once(&cmd.program)
+                .chain(&cmd.args)
+                .map(|word| quote(word))
+                .collect::<Vec<_>>()
+                .join(" ");
+            return Cmd {
+                program: "ssh".to_string(),
+                args: self.target.ssh().args.into_iter().chain([remote]).collect(),
+                ..cmd.clone()
+            };
+        }
+        if cmd.program != "docker" && cmd.program != "docker-compose" {
+            return cmd.clone();
+        }
+        let mapped = cmd.clone().env("DOCKER_HOST", &self.target.docker_host());
+        match (&cmd.dir, needs_sync(cmd)) {
+            (Some(dir), true) => self.with_sync(mapped, &dir.to_string_lossy()),
+            _ => mapped,
+        }
+    }
+
+    /// `cmd`, preceded by copying `dir` to the same path on the remote host.
+    fn with_sync(&self, cmd: Cmd, dir: &str) -> Cmd {
+        let excludes: String = SYNC_EXCLUDE
+            .iter()
+            .map(|path| format!(" --exclude={path}"))
+            .collect();
+        let unpack = format!("mkdir -p {0} && tar -C {0} -xf -", quote(dir));
+        let ssh = self
+            .target
+            .ssh()
+            .args
+            .iter()
+            .map(|word| quote(word))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let script = format!(
+            "tar -C {} -cf -{excludes} . | ssh {ssh} {} && exec \"$@\"",
+            quote(dir),
+            quote(&unpack)
+        );
+        Cmd {
+            program: "sh".to_string(),
+            args: ["-c", &script, "sh", &cmd.program]
+                .into_iter()
+                .map(String::from)
+                .chain(cmd.args)
+                .collect(),
+            ..cmd
+        }
+    }
+}
+
+impl CommandRunner for RemoteRunner {
+    fn output(&self, cmd: &Cmd) -> BoxFuture<'static, io::Result<CmdOutput>> {
+        SystemRunner.output(&self.map(cmd))
+    }
+
+    fn spawn(&self, cmd: &Cmd) -> io::Result<Running> {
+        SystemRunner.spawn(&self.map(cmd))
+    }
+
+    fn resolve(&self, cmd: &Cmd) -> Cmd {
+        self.map(cmd)
+    }
+
+    fn host(&self) -> String {
+        self.target.host().to_string()
+    }
+}
+
+/// The runner for this run: remote when `--remote` was given.
+pub fn runner(target: Option<&RemoteTarget>) -> Arc<dyn CommandRunner> {
+    match target {
+        Some(target) => Arc::new(RemoteRunner::new(target.clone())),
+        None => Arc::new(SystemRunner),
+    }
+}
+
+/// A compose command whose subcommand creates or starts containers.
+fn needs_sync(cmd: &Cmd) -> bool {
+    let is_compose =
+        cmd.program == "docker-compose" || cmd.args.first().is_some_and(|a| a == "compose");
+    is_compose
+        && cmd
+            .args
+            .iter()
+            .any(|arg| SYNC_BEFORE.contains(&arg.as_str()))
+}
+
+/// One word for a POSIX shell.
+fn quote(word: &str) -> String {
+    if !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "@%+=:,./_-".contains(c))
+    {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_targets() {
+        let target = RemoteTarget::parse("ssh://admin@10.0.0.5:2222").unwrap();
+        assert_eq!(target.destination, "admin@10.0.0.5");
+        assert_eq!(target.port, Some(2222));
+        assert_eq!(target.docker_host(), "ssh://admin@10.0.0.5:2222");
+        assert_eq!(target.address().as_deref(), Some("10.0.0.5"));
+        assert_eq!(RemoteTarget::parse("srv").unwrap().port, None);
+        assert!(RemoteTarget::parse("admin@").is_err());
+        assert!(RemoteTarget::parse("admin@srv:ssh").is_err());
+    }
+
+    #[test]
+    fn test_map_points_docker_at_remote_and_syncs_before_up() {
+        let runner = RemoteRunner::new(RemoteTarget::parse("admin@srv").unwrap());
+
+        let ps = runner.map(&Cmd::new("docker").args(["ps", "-a"]));
+        assert_eq!(ps.line(), "docker ps -a");
+        assert_eq!(ps.env, [("DOCKER_HOST".into(), "ssh://admin@srv".into())]);
+
+        let up = Cmd::new("docker")
+            .args(["compose", "-f", "docker-compose.yaml", "up", "-d"])
+            .dir("/opt/my identity");
+        let up = runner.map(&up);
+        assert_eq!(up.program, "sh");
+        assert_eq!(
+            up.args[1],
+            r#"tar -C '/opt/my identity' -cf - --exclude=./backups . | ssh -o BatchMode=yes -- admin@srv 'mkdir -p '\''/opt/my identity'\'' && tar -C '\''/opt/my identity'\'' -xf -' && exec "$@""#
+        );
+        assert_eq!(
+            up.args[3..].join(" "),
+            "docker compose -f docker-compose.yaml up -d"
+        );
+
+        let smi = runner.map(&Cmd::new("nvidia-smi").arg("--query-gpu=name"));
+        assert_eq!(
+            smi.line(),
+            "ssh -o BatchMode=yes -- admin@srv nvidia-smi --query-gpu=name"
+        );
+        assert_eq!(runner.map(&Cmd::new("aws").arg("ecr")).line(), "aws ecr");
+    }
+}
@@ -0,0 +1,123 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::{Result, eyre};
+use serde::{Deserialize, Serialize};
+
+use crate::runner::{Cmd, CommandRunner};
+use crate::{utils, xdg};
+
+/// Where earlier versions kept it, in the project directory.
+const LEGACY_STATE_FILE: &str = ".nqrust-update-state.json";
+
+/// Image that was running before the last update, so it can be restored if
+/// the freshly pulled image turns out to be broken.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollbackRecord {
+    pub service: String,
+    pub container: String,
+    pub image: String,
+    pub tag: String,
+    /// Local image ID (`sha256:...`) the container was running before the pull.
+    pub previous_image_id: String,
+    /// Repo digest of that image, if it came from a registry.
+    pub previous_digest: Option<String>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl RollbackRecord {
+    fn path() -> PathBuf {
+        let root = utils::project_root();
+        xdg::adopt(
+            &root.join(LEGACY_STATE_FILE),
+            &xdg::project_state(&root).join("update-state.json"),
+        )
+    }
+
+    pub fn load() -> Option<Self> {
+        fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        fs::write(Self::path(), serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(())
+    }
+
+    pub fn clear() {
+        let _ = fs::remove_file(Self::path());
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HealthOutcome {
+    Healthy,
+    Unhealthy,
+    /// Container has no healthcheck; we only know it is running.
+    NoHealthcheck,
+    TimedOut,
+}
+
+async fn docker_inspect(runner: &dyn CommandRunner, target: &str, format: &str) -> Option<String> {
+    let value = runner
+        .output(&Cmd::new("docker").args(["inspect", "--format", format, target]))
+        .await
+        .ok()?
+        .value()?;
+    (value != "<no value>").then_some(value)
+}
+
+/// Image ID the given container is currently running, or None if it does not exist.
+pub async fn running_image_id(runner: &dyn CommandRunner, container: &str) -> Option<String> {
+    docker_inspect(runner, container, "{{.Image}}").await
+}
+
+pub async fn image_repo_digest(runner: &dyn CommandRunner, image_id: &str) -> Option<String> {
+    docker_inspect(runner, image_id, "{{index .RepoDigests 0}}").await
+}
+
+/// Poll the container health status until it settles or `timeout` elapses.
+pub async fn wait_for_health(
+    runner: &dyn CommandRunner,
+    container: &str,
+    timeout: Duration,
+) -> HealthOutcome {
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        match docker_inspect(
+            runner,
+            container,
+            "{{if .State.Health}}{{.State.Health.Status}}{{else}}none{{end}}",
+        )
+        .await
+        .as_deref()
+        {
+            Some("healthy") => return HealthOutcome::Healthy,
+            Some("unhealthy") => return HealthOutcome::Unhealthy,
+            Some("none") => return HealthOutcome::NoHealthcheck,
+            _ => tokio::time::sleep(Duration::from_secs(3)).await,
+        }
+    }
+    HealthOutcome::TimedOut
+}
+
+/// Point `image:tag` back at the recorded image ID so compose recreates the
+/// service on the old image without pulling.
+pub async fn retag_previous(runner: &dyn CommandRunner, record: &RollbackRecord) -> Result<()> {
+    let reference = format!("{}:{}", record.image, record.tag);
+    let output = runner
+        .output(&Cmd::new("docker").args(["tag", &record.previous_image_id, &reference]))
+        .await?;
+    if !output.success {
+        return Err(eyre!(
+            "docker tag {} {} failed: {}",
+            record.previous_image_id,
+            reference,
+            output.stderr.trim()
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,440 @@
+// runner.rs - the one door external programs go through
+// Install, update and login flows start docker, docker compose, the aws CLI
+// and `sudo` (dpkg, rpm, systemctl). Each run is described as a `Cmd` and
+// handed to a `CommandRunner`: `SystemRunner` starts real processes, and
+// `MockRunner` answers from a script so those flows can be tested without
+// Docker. The offline payload loader and bundle export are synchronous and
+// run on a blocking thread, so they wait on the runner with
+// `blocking_output`; the two commands that stream an image through a pipe
+// (`docker load`, `docker save`) get their `std::process::Command` from
+// `CommandRunner::resolve` and `Cmd::std_command`.
+
+use std::ffi::OsStr;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Mutex;
+
+use futures::future::{self, BoxFuture};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader};
+use tokio::process::Command;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::{docker_context, platform, throttle};
+
+/// A program, its arguments and where its input and output go.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Cmd {
+    pub program: String,
+    pub args: Vec<String>,
+    pub dir: Option<PathBuf>,
+    pub env: Vec<(String, String)>,
+    /// Written to stdin, which is then closed; stdin is empty otherwise.
+    pub stdin: Option<String>,
+    pub stdin_file: Option<PathBuf>,
+    /// Send stdout to this file instead of capturing or streaming it. The
+    /// file is created with mode 0600: it holds database dumps.
+    pub stdout_file: Option<PathBuf>,
+    /// Let the command finish when the installer exits; only `Running::kill`
+    /// stops it.
+    pub detached: bool,
+}
+
+impl Cmd {
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+            ..Self::default()
+        }
+    }
+
+    /// `docker compose` or `docker-compose`, as found by
+    /// `utils::detect_compose_command`.
+    /// Pulls are capped at `--pull-parallelism` images at once.
+    pub fn compose(compose_cmd: &[String]) -> Self {
+        let cmd = Self::new(compose_cmd[0].clone()).args(&compose_cmd[1..]);
+        match throttle::limits().pull_parallelism {
+            Some(limit) => cmd.env("COMPOSE_PARALLEL_LIMIT", &limit.to_string()),
+            None => cmd,
+        }
+    }
+
+    pub fn arg(mut self, arg: impl AsRef<OsStr>) -> Self {
+        self.args.push(arg.as_ref().to_string_lossy().into_owned());
+        self
+    }
+
+    pub fn args<I, S>(self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        args.into_iter().fold(self, Self::arg)
+    }
+
+    pub fn dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    pub fn env(mut self, key: &str, value: &str) -> Self {
+        self.env.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn stdin(mut self, input: impl Into<String>) -> Self {
+        self.stdin = Some(input.into());
+        self
+    }
+
+    pub fn stdin_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.stdin_file = Some(path.into());
+        self
+    }
+
+    pub fn stdout_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.stdout_file = Some(path.into());
+        self
+    }
+
+    pub fn detached(mut self) -> Self {
+        self.detached = true;
+        self
+    }
+
+    /// `program arg…`, as logged and as matched by `MockRunner`.
+    pub fn line(&self) -> String {
+        std::iter::once(self.program.as_str())
+            .chain(self.args.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// The process with its arguments, directory and environment, for
+    /// synchronous code that streams through its stdin or stdout.
+    pub fn std_command(&self) -> std::process::Command {
+        let mut command = std::process::Command::new(&self.program);
+        command.args(&self.args);
+        if let Some(dir) = &self.dir {
+            command.current_dir(dir);
+        }
+        // A command given its own DOCKER_HOST (`--remote`) keeps it: docker
+        // refuses DOCKER_CONTEXT alongside it.
+        if let Some(context) = docker_context::selected()
+            && (self.program == "docker" || self.program == "docker-compose")
+            && !self.env.iter().any(|(key, _)| key == "DOCKER_HOST")
+        {
+            for var in docker_context::HOST_VARS {
+                command.env_remove(var);
+            }
+            command.env("DOCKER_CONTEXT", context);
+        }
+        for (key, value) in &self.env {
+            command.env(key, value);
+        }
+        command
+    }
+
+    fn command(&self) -> io::Result<Command> {
+        let mut command = Command::from(self.std_command());
+        command.kill_on_drop(!self.detached);
+        command.stdin(match (&self.stdin, &self.stdin_file) {
+            (Some(_), _) => Stdio::piped(),
+            (None, Some(path)) => File::open(path)?.into(),
+            (None, None) => Stdio::null(),
+        });
+        command.stdout(match &self.stdout_file {
+            Some(path) => create_private(path)?.into(),
+            None => Stdio::piped(),
+        });
+        command.stderr(Stdio::piped());
+        Ok(command)
+    }
+}
+
+/// `File::create`, readable and writable by the owner only.
+fn create_private(path: &Path) -> io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)
+}
+
+/// What a finished command printed, and whether it exited with status 0.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CmdOutput {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl CmdOutput {
+    pub fn ok(stdout: &str) -> Self {
+        Self {
+            success: true,
+            stdout: stdout.to_string(),
+            stderr: String::new(),
+        }
+    }
+
+    pub fn failed(stderr: &str) -> Self {
+        Self {
+            success: false,
+            stdout: String::new(),
+            stderr: stderr.to_string(),
+        }
+    }
+
+    /// Trimmed stdout, or None when the command failed or printed nothing.
+    pub fn value(&self) -> Option<String> {
+        let value = self.stdout.trim();
+        (self.success && !value.is_empty()).then(|| value.to_string())
+    }
+}
+
+/// A started command. Lines of stdout and stderr arrive as they are printed
+/// (docker and compose report progress on stderr). Dropping it kills the
+/// command and everything it started.
+#[derive(Debug)]
+pub struct Running {
+    lines: mpsc::UnboundedReceiver<String>,
+    status: oneshot::Receiver<bool>,
+    kill: Option<oneshot::Sender<()>>,
+}
+
+impl Running {
+    /// Next line of output; None once the command closed both streams.
+    pub async fn next_line(&mut self) -> Option<String> {
+        self.lines.recv().await
+    }
+
+    /// Stop the command and anything it started.
+    pub fn kill(&mut self) {
+        if let Some(kill) = self.kill.take() {
+            let _ = kill.send(());
+        }
+    }
+
+    /// Wait for the command to exit; true if it succeeded.
+    pub async fn wait(mut self) -> bool {
+        (&mut self.status).await.unwrap_or(false)
+    }
+}
+
+pub trait CommandRunner: Send + Sync + std::fmt::Debug {
+    /// Run `cmd` to completion and capture what it printed.
+    fn output(&self, cmd: &Cmd) -> BoxFuture<'static, io::Result<CmdOutput>>;
+
+    /// Start `cmd` and stream its output.
+    fn spawn(&self, cmd: &Cmd) -> io::Result<Running>;
+
+    /// The command that actually runs for `cmd`, for callers that have to
+    /// start it themselves.
+    fn resolve(&self, cmd: &Cmd) -> Cmd {
+        cmd.clone()
+    }
+
+    /// Host the stack's published ports are reached at: where the docker
+    /// daemon runs.
+    fn host(&self) -> String {
+        "127.0.0.1".to_string()
+    }
+}
+
+/// `runner.output(cmd)` for synchronous code on a blocking thread, such as
+/// the offline payload loader. Never call it from a task on the runtime.
+pub fn blocking_output(runner: &dyn CommandRunner, cmd: &Cmd) -> io::Result<CmdOutput> {
+    futures::executor::block_on(runner.output(cmd))
+}
+
+/// Starts real processes.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemRunner;
+
+impl CommandRunner for SystemRunner {
+    fn output(&self, cmd: &Cmd) -> BoxFuture<'static, io::Result<CmdOutput>> {
+        let command = cmd.command();
+        let input = cmd.stdin.clone();
+        Box::pin(async move {
+            let mut child = command?.spawn()?;
+            if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+                stdin.write_all(input.as_bytes()).await?;
+            }
+            let output = child.wait_with_output().await?;
+            Ok(CmdOutput {
+                success: output.status.success(),
+                stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            })
+        })
+    }
+
+    fn spawn(&self, cmd: &Cmd) -> io::Result<Running> {
+        let mut command = cmd.command()?;
+        platform::own_process_group(&mut command);
+        let mut child = command.spawn()?;
+        let input = cmd.stdin.clone();
+        let detached = cmd.detached;
+        let (line_tx, lines) = mpsc::unbounded_channel();
+        let (status_tx, status) = oneshot::channel();
+        let (kill, mut killed) = oneshot::channel::<()>();
+
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+        let stdin = child.stdin.take();
+        tokio::spawn(async move {
+            let run = async {
+                if let (Some(input), Some(mut stdin)) = (input, stdin) {
+                    let _ = stdin.write_all(input.as_bytes()).await;
+                }
+                tokio::join!(
+                    forward_lines(stdout, line_tx.clone()),
+                    forward_lines(stderr, line_tx)
+                );
+                child.wait().await.is_ok_and(|status| status.success())
+            };
+            // `killed` also fires when the `Running` is dropped; a detached
+            // command only stops for `kill`.
+            let success = {
+                tokio::pin!(run);
+                tokio::select! {
+                    success = &mut run => success,
+                    killed = &mut killed => match killed {
+                        Err(_) if detached => run.await,
+                        _ => false,
+                    },
+                }
+            };
+            if !success {
+                platform::kill_tree(&mut child).await;
+            }
+            let _ = status_tx.send(success);
+        });
+        Ok(Running {
+            lines,
+            status,
+            kill: Some(kill),
+        })
+    }
+}
+
+async fn forward_lines(stream: Option<impl AsyncRead + Unpin>, tx: mpsc::UnboundedSender<String>) {
+    let Some(stream) = stream else {
+        return;
+    };
+    let mut reader = BufReader::new(stream).lines();
+    while let Ok(Some(line)) = reader.next_line().await {
+        if tx.send(line).is_err() {
+            break;
+        }
+    }
+}
+
+/// Answers commands from a script and records them. The first rule whose
+/// pattern occurs in the command line decides the reply; anything else
+/// succeeds without output.
+#[derive(Debug, Default)]
+pub struct MockRunner {
+    rules: Vec<(String, CmdOutput)>,
+    calls: Mutex<Vec<Cmd>>,
+    host: Option<String>,
+}
+
+impl MockRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn on(mut self, pattern: &str, reply: CmdOutput) -> Self {
+        self.rules.push((pattern.to_string(), reply));
+        self
+    }
+
+    /// Act as a runner for a daemon on `host`, as with `--remote`.
+    pub fn remote(mut self, host: &str) -> Self {
+        self.host = Some(host.to_string());
+        self
+    }
+
+    /// Every command run so far, oldest first.
+    pub fn calls(&self) -> Vec<Cmd> {
+        self.calls.lock().map(|c| c.clone()).unwrap_or_default()
+    }
+
+    /// Command lines run so far, oldest first.
+    pub fn lines(&self) -> Vec<String> {
+        self.calls().iter().map(Cmd::line).collect()
+    }
+
+    fn reply(&self, cmd: &Cmd) -> CmdOutput {
+        if let Ok(mut calls) = self.calls.lock() {
+            calls.push(cmd.clone());
+        }
+        let line = cmd.line();
+        self.rules
+            .iter()
+            .find(|(pattern, _)| line.contains(pattern.as_str()))
+            .map_or_else(|| CmdOutput::ok(""), |(_, reply)| reply.clone())
+    }
+}
+
+impl CommandRunner for MockRunner {
+    fn output(&self, cmd: &Cmd) -> BoxFuture<'static, io::Result<CmdOutput>> {
+        Box::pin(future::ready(Ok(self.reply(cmd))))
+    }
+
+    fn spawn(&self, cmd: &Cmd) -> io::Result<Running> {
+        let reply = self.reply(cmd);
+        let (line_tx, lines) = mpsc::unbounded_channel();
+        for line in reply.stdout.lines().chain(reply.stderr.lines()) {
+            let _ = line_tx.send(line.to_string());
+        }
+        let (status_tx, status) = oneshot::channel();
+        let _ = status_tx.send(reply.success);
+        Ok(Running {
+            lines,
+            status,
+            kill: None,
+        })
+    }
+
+    fn host(&self) -> String {
+        self.host.clone().unwrap_or_else(|| SystemRunner.host())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mock_replies_by_pattern_and_records_calls() {
+        let runner = MockRunner::new()
+            .on("compose version", CmdOutput::failed("unknown command"))
+            .on("docker info", CmdOutput::ok("  24.0.7\n"));
+
+        let version = runner
+            .output(&Cmd::new("docker").args(["compose", "version"]))
+            .await
+            .unwrap();
+        assert!(!version.success);
+        let info = Cmd::new("docker").args(["info", "--format", "{{.ServerVersion}}"]);
+        assert_eq!(
+            runner.output(&info).await.unwrap().value().as_deref(),
+            Some("24.0.7")
+        );
+
+        let mut pull = runner
+            .spawn(&Cmd::new("docker").arg("pull").stdin("secret"))
+            .unwrap();
+        assert_eq!(pull.next_line().await, None);
+        assert!(pull.wait().await);
+        assert_eq!(runner.lines()[2], "docker pull");
+        assert_eq!(runner.calls()[2].stdin.as_deref(), Some("secret"));
+    }
+}
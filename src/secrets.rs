@@ -0,0 +1,220 @@
+// secrets.rs - sensitive stack values kept out of .env and the compose file
+// With a non-`env` backend the installer writes each secret to
+// `secrets/<name>` (directory mode 0700) and the compose override mounts them
+// as compose file secrets, so no password appears in plaintext config.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use color_eyre::{Result, eyre::eyre};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::compose_override::ComposeOverride;
+use crate::external_db::ExternalDb;
+use crate::runner::{Cmd, CommandRunner};
+
+pub const SECRETS_DIR: &str = "secrets";
+pub const DB_PASSWORD: &str = "db_password";
+pub const ADMIN_PASSWORD: &str = "admin_password";
+const ALL: [&str; 2] = [DB_PASSWORD, ADMIN_PASSWORD];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SecretBackend {
+    /// Plaintext values from docker-compose.yaml (the historical behaviour).
+    #[default]
+    Env,
+    /// Random values generated once and kept under `secrets/`.
+    DockerSecrets,
+    /// HashiCorp Vault KV v2, read with `VAULT_ADDR` / `VAULT_TOKEN`.
+    Vault,
+    /// AWS SSM Parameter Store, read with the `aws` CLI and its credentials.
+    AwsSsm,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecretsSettings {
+    pub backend: SecretBackend,
+    /// Vault KV v2 path holding `db_password` / `admin_password`, e.g. `secret/data/identity`.
+    pub vault_path: Option<String>,
+    /// SSM parameter prefix; values are read from `<prefix>/<name>`.
+    pub ssm_prefix: Option<String>,
+}
+
+pub fn path(root: &Path, name: &str) -> PathBuf {
+    root.join(SECRETS_DIR).join(name)
+}
+
+/// Current value of a materialized secret.
+pub fn read(root: &Path, name: &str) -> Option<String> {
+    fs::read_to_string(path(root, name))
+        .ok()
+        .map(|v| v.trim_end().to_string())
+}
+
+/// Write every secret file for the configured backend. Generated secrets are
+/// created once and reused, since the database keeps the password it was
+/// initialized with.
+pub async fn materialize(
+    runner: &dyn CommandRunner,
+    root: &Path,
+    settings: &SecretsSettings,
+    client: &Client,
+) -> Result<()> {
+    if settings.backend == SecretBackend::Env {
+        return Ok(());
+    }
+    let dir = root.join(SECRETS_DIR);
+    fs::create_dir_all(&dir)?;
+    restrict_dir(&dir)?;
+    // An external database has its own password, set with EXTERNAL_DB_PASSWORD.
+    let external_db = ExternalDb::load(root).is_some();
+    for name in ALL
+        .into_iter()
+        .filter(|name| !(external_db && *name == DB_PASSWORD))
+    {
+        let value = match settings.backend {
+            SecretBackend::Env => unreachable!(),
+            SecretBackend::DockerSecrets => match read(root, name) {
+                Some(existing) => existing,
+                None => uuid::Uuid::new_v4().simple().to_string(),
+            },
+            SecretBackend::Vault => fetch_vault(client, settings, name).await?,
+            SecretBackend::AwsSsm => fetch_ssm(runner, settings, name).await?,
+        };
+        fs::write(path(root, name), value)?;
+    }
+    Ok(())
+}
+
+/// Mount the secrets and point the services at them.
+pub fn apply(doc: &mut ComposeOverride, settings: &SecretsSettings) {
+    if settings.backend == SecretBackend::Env {
+        return;
+    }
+    for name in ALL {
+        doc.secrets
+            .insert(name.to_string(), format!("./{SECRETS_DIR}/{name}"));
+    }
+
+    // The postgres image reads *_FILE natively; the plain variable must be
+    // blanked because the entrypoint rejects both being set.
+    let postgres = doc.service("postgres");
+    postgres.secrets.push(DB_PASSWORD.to_string());
+    postgres
+        .environment
+        .insert("POSTGRES_PASSWORD".to_string(), String::new());
+    postgres.environment.insert(
+        "POSTGRES_PASSWORD_FILE".to_string(),
+        format!("/run/secrets/{DB_PASSWORD}"),
+    );
+
+    // Keycloak has no *_FILE support, so export from the mounted files
+    // before handing the compose `command` arguments to kc.sh. `$$` keeps
+    // compose from interpolating the shell expansions.
+    let identity = doc.service("identity");
+    identity.secrets.extend(ALL.map(String::from));
+    for var in ["KC_DB_PASSWORD", "KC_BOOTSTRAP_ADMIN_PASSWORD"] {
+        identity.environment.insert(var.to_string(), String::new());
+    }
+    identity.entrypoint = Some(kc_entrypoint(&[
+        ("KC_DB_PASSWORD", DB_PASSWORD),
+        ("KC_BOOTSTRAP_ADMIN_PASSWORD", ADMIN_PASSWORD),
+    ]));
+}
+
+/// Keycloak entrypoint exporting each (variable, secret) pair from the
+/// mounted secret files.
+pub fn kc_entrypoint(exports: &[(&str, &str)]) -> Vec<String> {
+    let exports: Vec<String> = exports
+        .iter()
+        .map(|(var, name)| format!("{var}=\"$$(cat /run/secrets/{name})\""))
+        .collect();
+    vec![
+        "/bin/bash".to_string(),
+        "-c".to_string(),
+        format!(
+            "export {}; exec /opt/keycloak/bin/kc.sh \"$$@\"",
+            exports.join(" ")
+        ),
+        "kc.sh".to_string(),
+    ]
+}
+
+async fn fetch_vault(client: &Client, settings: &SecretsSettings, name: &str) -> Result<String> {
+    let addr = std::env::var("VAULT_ADDR").map_err(|_| eyre!("VAULT_ADDR is not set"))?;
+    let token = std::env::var("VAULT_TOKEN").map_err(|_| eyre!("VAULT_TOKEN is not set"))?;
+    let vault_path = settings
+        .vault_path
+        .as_deref()
+        .ok_or_else(|| eyre!("secrets.vault_path is not set in settings"))?;
+    let url = format!(
+        "{}/v1/{}",
+        addr.trim_end_matches('/'),
+        vault_path.trim_start_matches('/')
+    );
+    let response = client
+        .get(&url)
+        .header("X-Vault-Token", token)
+        .send()
+        .await
+        .map_err(|e| eyre!("Vault request failed: {e}"))?;
+    if !response.status().is_success() {
+        return Err(eyre!("Vault returned HTTP {} for {url}", response.status()));
+    }
+    let body: Value = response.json().await?;
+    body["data"]["data"][name]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| eyre!("Vault secret {vault_path} has no '{name}' key"))
+}
+
+async fn fetch_ssm(
+    runner: &dyn CommandRunner,
+    settings: &SecretsSettings,
+    name: &str,
+) -> Result<String> {
+    let prefix = settings
+        .ssm_prefix
+        .as_deref()
+        .ok_or_else(|| eyre!("secrets.ssm_prefix is not set in settings"))?;
+    let parameter = format!("{}/{name}", prefix.trim_end_matches('/'));
+    let output = runner
+        .output(&Cmd::new("aws").args([
+            "ssm",
+            "get-parameter",
+            "--with-decryption",
+            "--name",
+            &parameter,
+            "--query",
+            "Parameter.Value",
+            "--output",
+            "text",
+        ]))
+        .await
+        .map_err(|e| eyre!("Failed to run aws CLI: {e}"))?;
+    if !output.success {
+        return Err(eyre!(
+            "aws ssm get-parameter {parameter} failed: {}",
+            output.stderr.trim()
+        ));
+    }
+    Ok(output.stdout.trim().to_string())
+}
+
+/// Only the directory is locked down: non-swarm compose secrets are plain
+/// bind mounts, and the files must stay readable by the container users
+/// (postgres, keycloak) that are not the installing user.
+fn restrict_dir(dir: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o700))?;
+    }
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
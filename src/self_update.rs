@@ -0,0 +1,413 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::{Result, eyre};
+use flate2::read::GzDecoder;
+use reqwest::Client;
+use sha2::{Digest, Sha256};
+
+use crate::runner::{Cmd, CommandRunner};
+use crate::throttle::Pacer;
+use crate::{build_info, http, platform};
+
+/// How the installer binary is packaged, which decides both the release
+/// asset to download and how it gets installed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PackageFormat {
+    Deb,
+    Rpm,
+    /// Plain `.tar.gz` holding the binary; replaced in place or in `~/.local/bin`.
+    Tarball,
+}
+
+impl PackageFormat {
+    /// Work out how the running binary was installed. Anything not owned by
+    /// dpkg or rpm is treated as a tarball install, as is `--no-root` since
+    /// neither package manager works without root.
+    pub async fn detect(runner: &dyn CommandRunner, no_root: bool) -> Self {
+        // No package managers on Windows either.
+        if no_root || cfg!(windows) {
+            return PackageFormat::Tarball;
+        }
+        let Ok(exe) = std::env::current_exe() else {
+            return PackageFormat::Tarball;
+        };
+        let owned_by = |program: &str, flag: &str| {
+            let owns = runner.output(&Cmd::new(program).arg(flag).arg(&exe));
+            async { owns.await.is_ok_and(|output| output.success) }
+        };
+        if owned_by("dpkg", "-S").await {
+            PackageFormat::Deb
+        } else if owned_by("rpm", "-qf").await {
+            PackageFormat::Rpm
+        } else {
+            PackageFormat::Tarball
+        }
+    }
+
+    pub fn from_asset_name(name: &str) -> Option<Self> {
+        if name.ends_with(".deb") {
+            Some(PackageFormat::Deb)
+        } else if name.ends_with(".rpm") {
+            Some(PackageFormat::Rpm)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(PackageFormat::Tarball)
+        } else {
+            None
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            PackageFormat::Deb => ".deb",
+            PackageFormat::Rpm => ".rpm",
+            PackageFormat::Tarball => ".tar.gz",
+        }
+    }
+
+    /// True if `name` is this format's release asset for `target`.
+    /// Debian and RPM spell architectures differently (`amd64` vs `x86_64`).
+    pub fn matches_asset(self, name: &str, target: &Target) -> bool {
+        let name = name.to_ascii_lowercase();
+        if Self::from_asset_name(&name) != Some(self) {
+            return false;
+        }
+        let aliases = target.arch_aliases();
+        match self {
+            PackageFormat::Deb => aliases.iter().any(|a| name.ends_with(&format!("_{a}.deb"))),
+            PackageFormat::Rpm => aliases.iter().any(|a| name.ends_with(&format!(".{a}.rpm"))),
+            PackageFormat::Tarball => {
+                name.contains(target.os) && aliases.iter().any(|a| name.contains(a))
+            }
+        }
+    }
+}
+
+/// OS/architecture the installer was compiled for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Target {
+    pub os: &'static str,
+    pub arch: &'static str,
+}
+
+impl Target {
+    pub fn current() -> Self {
+        Self {
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+        }
+    }
+
+    fn arch_aliases(&self) -> Vec<&'static str> {
+        match self.arch {
+            "x86_64" => vec!["x86_64", "amd64"],
+            "aarch64" => vec!["aarch64", "arm64"],
+            other => vec![other],
+        }
+    }
+}
+
+/// Pick the release asset for `format` on `target`, failing with the list of
+/// what the release does ship so the user can download by hand.
+pub fn select_asset<'a>(
+    names: &[&'a str],
+    format: PackageFormat,
+    target: &Target,
+) -> Result<&'a str> {
+    if target.os != "linux" && !(target.os == "windows" && format == PackageFormat::Tarball) {
+        return Err(eyre!(
+            "Self-update is only available on Linux and Windows (running on {})",
+            target.os
+        ));
+    }
+    names
+        .iter()
+        .copied()
+        .find(|name| format.matches_asset(name, target))
+        .ok_or_else(|| {
+            let shipped: Vec<&str> = names
+                .iter()
+                .copied()
+                .filter(|name| PackageFormat::from_asset_name(name).is_some())
+                .collect();
+            eyre!(
+                "No {} asset for {}-{} in this release (has: {})",
+                format.label(),
+                target.os,
+                target.arch,
+                if shipped.is_empty() {
+                    "none".to_string()
+                } else {
+                    shipped.join(", ")
+                }
+            )
+        })
+}
+
+/// Look up the expected SHA256 for `asset_name` in a `SHA256SUMS` body.
+/// Accepts both `<hash>  <name>` and binary-mode `<hash> *<name>` lines.
+pub fn parse_checksum(sums: &str, asset_name: &str) -> Option<String> {
+    sums.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        let hash = parts.next()?;
+        let name = parts.next()?.trim_start_matches('*');
+        (name == asset_name && hash.len() == 64).then(|| hash.to_ascii_lowercase())
+    })
+}
+
+/// File name of a release asset, taken from the last segment of its download URL.
+pub fn asset_name(url: &str) -> &str {
+    url.rsplit('/').next().unwrap_or(url)
+}
+
+/// Fetch SHA256SUMS and return the hash for `asset_name` without touching disk.
+pub async fn fetch_expected_checksum(
+    client: &Client,
+    checksum_url: &str,
+    asset_name: &str,
+) -> Result<String> {
+    let sums = http::send(client.get(checksum_url))
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    parse_checksum(&sums, asset_name)
+        .ok_or_else(|| eyre!("{asset_name} is not listed in SHA256SUMS"))
+}
+
+/// Stream `url` to `dest`, hashing each chunk as it is written so the package
+/// is never held in memory. The file is removed if the hash does not match.
+pub async fn download_verified(
+    client: &Client,
+    url: &str,
+    dest: &Path,
+    expected_sha256: &str,
+) -> Result<u64> {
+    let mut response = http::send(client.get(url)).await?.error_for_status()?;
+
+    let mut file = File::create(dest)?;
+    let mut hasher = Sha256::new();
+    let mut written = 0u64;
+    let mut pacer = Pacer::new();
+    while let Some(chunk) = response.chunk().await? {
+        hasher.update(&chunk);
+        file.write_all(&chunk)?;
+        written += chunk.len() as u64;
+        pacer.consume(chunk.len()).await;
+    }
+    file.flush()?;
+
+    let actual = format!("{:x}", hasher.finalize());
+    if actual != expected_sha256 {
+        let _ = std::fs::remove_file(dest);
+        return Err(eyre!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            dest.display(),
+            expected_sha256,
+            actual
+        ));
+    }
+    Ok(written)
+}
+
+/// Install a downloaded package and return where the binary ended up.
+/// Package managers run under `sudo -n` so a password prompt can never
+/// hijack the TUI; callers fall back to printing the manual command.
+pub async fn install(
+    runner: &dyn CommandRunner,
+    path: &Path,
+    format: PackageFormat,
+    no_root: bool,
+) -> Result<PathBuf> {
+    match format {
+        PackageFormat::Deb => {
+            run_privileged(runner, &["dpkg", "-i"], path).await?;
+            Ok(PathBuf::from("/usr/bin").join(build_info::NAME))
+        }
+        PackageFormat::Rpm => {
+            run_privileged(runner, &["rpm", "-U"], path).await?;
+            Ok(PathBuf::from("/usr/bin").join(build_info::NAME))
+        }
+        PackageFormat::Tarball => {
+            let target = if no_root {
+                user_bin_dir()?.join(platform::exe_name())
+            } else {
+                std::env::current_exe()?
+            };
+            replace_binary(path, &target)?;
+            Ok(target)
+        }
+    }
+}
+
+/// Command a user can run by hand when the automatic install fails.
+pub fn manual_command(path: &Path, format: PackageFormat) -> String {
+    match format {
+        PackageFormat::Deb => format!("sudo dpkg -i {}", path.display()),
+        PackageFormat::Rpm => format!("sudo rpm -U {}", path.display()),
+        #[cfg(windows)]
+        PackageFormat::Tarball => format!(
+            "tar -xzf {} && move {} \"{}\"",
+            path.display(),
+            platform::exe_name(),
+            platform::user_bin_dir().unwrap_or_default().display()
+        ),
+        #[cfg(not(windows))]
+        PackageFormat::Tarball => format!(
+            "tar -xzf {} && install -m 755 {} ~/.local/bin/",
+            path.display(),
+            build_info::NAME
+        ),
+    }
+}
+
+async fn run_privileged(runner: &dyn CommandRunner, command: &[&str], path: &Path) -> Result<()> {
+    let output = runner
+        .output(&Cmd::new("sudo").arg("-n").args(command).arg(path))
+        .await?;
+    if !output.success {
+        return Err(eyre!("{}", output.stderr.trim()));
+    }
+    Ok(())
+}
+
+/// `~/.local/bin` (`%LOCALAPPDATA%\Programs\nqrust-identity` on Windows), created if needed.
+pub fn user_bin_dir() -> Result<PathBuf> {
+    let dir = platform::user_bin_dir().ok_or_else(|| eyre!("Home directory is not set"))?;
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Extract the installer binary from `archive` next to `target`, then rename
+/// it over `target`. The rename is atomic, so an interrupted update never
+/// leaves a half-written binary, and replacing a running executable is safe.
+fn replace_binary(archive: &Path, target: &Path) -> Result<()> {
+    let dir = target
+        .parent()
+        .ok_or_else(|| eyre!("{} has no parent directory", target.display()))?;
+    let staged = dir.join(format!(".{}.new", build_info::NAME));
+    let exe_name = platform::exe_name();
+
+    let mut tar = tar::Archive::new(GzDecoder::new(File::open(archive)?));
+    let mut found = false;
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        if entry.path()?.file_name().and_then(|n| n.to_str()) == Some(exe_name.as_str()) {
+            let mut out = File::create(&staged).map_err(|e| {
+                eyre!(
+                    "cannot write to {} ({e}); re-run with --no-root to install into ~/.local/bin",
+                    dir.display()
+                )
+            })?;
+            std::io::copy(&mut entry, &mut out)?;
+            out.flush()?;
+            found = true;
+            break;
+        }
+    }
+    if !found {
+        return Err(eyre!(
+            "{} does not contain a {} binary",
+            archive.display(),
+            exe_name
+        ));
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o755))?;
+    }
+    // Windows cannot overwrite a running executable, but it can rename it away.
+    #[cfg(windows)]
+    if target.exists() {
+        let old = target.with_extension("old.exe");
+        let _ = fs::remove_file(&old);
+        fs::rename(target, &old)?;
+    }
+    if let Err(e) = fs::rename(&staged, target) {
+        let _ = fs::remove_file(&staged);
+        return Err(eyre!("failed to replace {}: {e}", target.display()));
+    }
+    Ok(())
+}
+
+/// Directory that holds a downloaded package until it has been installed.
+pub fn download_dir() -> Result<PathBuf> {
+    Ok(tempfile::Builder::new()
+        .prefix("nqrust-identity-update")
+        .tempdir()?
+        .keep())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::{CmdOutput, MockRunner};
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_detect_asks_dpkg_then_rpm() {
+        let runner = MockRunner::new()
+            .on("dpkg -S", CmdOutput::failed("no path found"))
+            .on("rpm -qf", CmdOutput::ok("nqrust-identity-0.1.0-1.x86_64\n"));
+        assert_eq!(
+            PackageFormat::detect(&runner, false).await,
+            PackageFormat::Rpm
+        );
+        assert_eq!(runner.calls().len(), 2);
+        assert_eq!(
+            PackageFormat::detect(&runner, true).await,
+            PackageFormat::Tarball
+        );
+        assert_eq!(runner.calls().len(), 2);
+    }
+
+    #[test]
+    fn test_parse_checksum_matches_asset() {
+        let hash = "a".repeat(64);
+        let sums = format!(
+            "{}  other.deb\n{} *nqrust-identity_0.1.0_amd64.deb\n",
+            "b".repeat(64),
+            hash
+        );
+        assert_eq!(
+            parse_checksum(&sums, "nqrust-identity_0.1.0_amd64.deb"),
+            Some(hash)
+        );
+        assert_eq!(parse_checksum(&sums, "missing.deb"), None);
+    }
+
+    #[test]
+    fn test_format_from_asset_name() {
+        assert_eq!(
+            PackageFormat::from_asset_name("nqrust-identity-0.1.0-1.x86_64.rpm"),
+            Some(PackageFormat::Rpm)
+        );
+        assert_eq!(
+            PackageFormat::from_asset_name("nqrust-identity-x86_64-linux.tar.gz"),
+            Some(PackageFormat::Tarball)
+        );
+        assert_eq!(PackageFormat::from_asset_name("SHA256SUMS"), None);
+    }
+
+    #[test]
+    fn test_select_asset_by_arch() {
+        let names = [
+            "nqrust-identity_0.2.0_amd64.deb",
+            "nqrust-identity_0.2.0_arm64.deb",
+            "nqrust-identity-0.2.0-1.x86_64.rpm",
+            "SHA256SUMS",
+        ];
+        let arm = Target {
+            os: "linux",
+            arch: "aarch64",
+        };
+        assert_eq!(
+            select_asset(&names, PackageFormat::Deb, &arm).unwrap(),
+            "nqrust-identity_0.2.0_arm64.deb"
+        );
+        assert!(select_asset(&names, PackageFormat::Rpm, &arm).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+// selinux.rs - bind mounts on SELinux hosts
+// When SELinux enforces and the daemon runs with selinux-enabled, a container
+// can only read bind-mounted host paths that carry a container label; without
+// one Caddy fails to read certs/ and the Caddyfile with EACCES. Before
+// starting the stack the install flows ask the daemon which security modules
+// it uses. With SELinux among them the compose override repeats each
+// project-relative bind mount of the base file with `z`, so docker relabels
+// it as shared container content. Absolute host paths are never relabelled.
+// AppArmor needs nothing for bind mounts and is only reported.
+
+use std::collections::BTreeMap;
+
+use crate::runner::{Cmd, CommandRunner};
+use crate::settings::Settings;
+use crate::{secrets, stack, utils};
+
+/// Security modules the daemon confines containers with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DaemonSecurity {
+    pub selinux: bool,
+    pub apparmor: bool,
+}
+
+/// `None` when the daemon does not answer.
+pub async fn detect(runner: &dyn CommandRunner) -> Option<DaemonSecurity> {
+    let cmd = Cmd::new("docker").args(["info", "--format", "{{json .SecurityOptions}}"]);
+    let output = runner.output(&cmd).await.ok()?;
+    output.success.then(|| parse(&output.stdout))
+}
+
+/// `["name=seccomp,profile=builtin","name=selinux"]` as printed by `docker info`.
+fn parse(options: &str) -> DaemonSecurity {
+    let options: Vec<String> = serde_json::from_str(options.trim()).unwrap_or_default();
+    let has = |module: &str| {
+        options.iter().any(|option| {
+            option
+                .split(',')
+                .any(|part| part == format!("name={module}"))
+        })
+    };
+    DaemonSecurity {
+        selinux: has("selinux"),
+        apparmor: has("apparmor"),
+    }
+}
+
+/// Record in `settings` whether bind mounts need relabelling, saving when it
+/// changed. Returns lines for the install log.
+pub async fn check(runner: &dyn CommandRunner, settings: &mut Settings) -> Vec<String> {
+    let Some(security) = detect(runner).await else {
+        return Vec::new();
+    };
+    let mut lines = Vec::new();
+    if settings.selinux_relabel != security.selinux {
+        settings.selinux_relabel = security.selinux;
+        if let Err(e) = settings.save() {
+            lines.push(format!("⚠️  Could not save settings: {e}"));
+        }
+    }
+    if security.selinux {
+        lines.push("🛡️  SELinux is enforced by the Docker daemon — bind mounts get :z in the compose override".to_string());
+        if settings.secrets.backend != secrets::SecretBackend::Env {
+            lines.push(format!(
+                "⚠️  Secret files can't be relabelled by compose; if the database can't read \
+                 them, run: chcon -R -t container_file_t {}",
+                utils::project_root().join(secrets::SECRETS_DIR).display()
+            ));
+        }
+    }
+    if security.apparmor {
+        lines.push("🛡️  AppArmor is active on the Docker daemon (no changes needed)".to_string());
+    }
+    lines
+}
+
+/// Project-relative bind mounts of the base compose file with `z` added,
+/// per service.
+pub fn relabelled_mounts() -> BTreeMap<String, Vec<String>> {
+    let stack = stack::current();
+    let compose = stack
+        .bundle
+        .iter()
+        .find(|(name, _)| *name == stack.compose_file())
+        .and_then(|(_, content)| serde_yaml::from_str::<serde_yaml::Value>(content).ok());
+    let mut mounts = BTreeMap::new();
+    let Some(services) = compose.as_ref().and_then(|c| c["services"].as_mapping()) else {
+        return mounts;
+    };
+    for (name, service) in services {
+        let relabelled: Vec<String> = service["volumes"]
+            .as_sequence()
+            .into_iter()
+            .flatten()
+            .filter_map(|volume| relabel(volume.as_str()?))
+            .collect();
+        if let (Some(name), false) = (name.as_str(), relabelled.is_empty()) {
+            mounts.insert(name.to_string(), relabelled);
+        }
+    }
+    mounts
+}
+
+/// `./src:/target[:opts]` with `z` among the options; `None` for named
+/// volumes, absolute paths and mounts that already carry a label option.
+fn relabel(volume: &str) -> Option<String> {
+    let mut parts = volume.splitn(3, ':');
+    let (source, target) = (parts.next()?, parts.next()?);
+    if !source.starts_with("./") && !source.starts_with("../") && source != "." {
+        return None;
+    }
+    match parts.next() {
+        Some(options) if options.split(',').any(|o| o == "z" || o == "Z") => None,
+        Some(options) => Some(format!("{source}:{target}:{options},z")),
+        None => Some(format!("{source}:{target}:z")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_relabel() {
+        let security = parse(r#"["name=seccomp,profile=builtin","name=selinux"]"#);
+        assert!(security.selinux && !security.apparmor);
+        assert!(parse("[\"name=apparmor\"]").apparmor);
+        assert_eq!(parse("not json"), DaemonSecurity::default());
+
+        assert_eq!(
+            relabel("./certs:/etc/certs:ro").as_deref(),
+            Some("./certs:/etc/certs:ro,z")
+        );
+        assert_eq!(relabel("./data:/data").as_deref(), Some("./data:/data:z"));
+        assert_eq!(relabel("./data:/data:Z"), None);
+        assert_eq!(relabel("identity_pgdata:/var/lib/postgresql/data"), None);
+        assert_eq!(relabel("/var/run/docker.sock:/var/run/docker.sock"), None);
+
+        let mounts = relabelled_mounts();
+        assert_eq!(
+            mounts["caddy"],
+            [
+                "./Caddyfile:/etc/caddy/Caddyfile:ro,z",
+                "./certs:/etc/certs:ro,z"
+            ]
+        );
+        assert!(!mounts.contains_key("postgres"));
+    }
+}
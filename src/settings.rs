@@ -0,0 +1,532 @@
+// settings.rs - persisted installer preferences
+// Stored as JSON in the per-project config dir (see xdg.rs) so it survives
+// re-runs without sitting next to the compose bundle.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use color_eyre::eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::env_file::EnvFile;
+use crate::registry::Registry;
+use crate::secrets::SecretsSettings;
+use crate::{stack, utils, xdg};
+
+/// Where earlier versions kept it, in the project directory.
+const LEGACY_SETTINGS_FILE: &str = ".nqrust-installer.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Seconds to wait for the Docker daemon to come back after it drops mid-operation.
+    pub daemon_grace_period_secs: u64,
+    /// Image tags pinned from the update list, keyed by compose service name.
+    pub pinned_tags: BTreeMap<String, PinnedTag>,
+    /// Set once the first-run tour has been dismissed.
+    pub onboarding_seen: bool,
+    /// json-file log rotation applied to every stack service.
+    pub log_caps: LogCaps,
+    /// Per-service log caps that replace `log_caps`, keyed by compose service name.
+    pub service_log_caps: BTreeMap<String, LogCaps>,
+    /// Commands / webhooks run when the installer reaches certain states.
+    pub hooks: Vec<Hook>,
+    /// Proxy for GitHub / GHCR requests; unset fields fall back to the environment.
+    pub proxy: ProxySettings,
+    /// Compose pull policy for `up`; unset means `never` offline, `missing` online.
+    pub pull_policy: Option<PullPolicy>,
+    /// Where database / admin passwords come from instead of plaintext compose values.
+    pub secrets: SecretsSettings,
+    /// Registries logged into besides GHCR (Docker Hub, Harbor, ECR).
+    pub registries: Vec<Registry>,
+    /// Name of the registry every stack image is pulled through, if any.
+    pub mirror: Option<String>,
+    /// Limits on compose pull / up before the installer calls them stalled.
+    pub compose_timeouts: ComposeTimeouts,
+    /// Where database backups go and how many are kept.
+    pub backups: BackupSettings,
+    /// Hours between checks made by `--check-updates-daemon`.
+    pub update_check_interval_hours: u64,
+    /// Resource limits, restart policy, network name and extra environment
+    /// written to the compose override (Advanced options).
+    pub advanced: AdvancedSettings,
+    /// Services given the host's NVIDIA GPUs, set from templates with `gpu_services`.
+    pub gpu_services: Vec<String>,
+    /// Docker context picked under "Docker daemon"; docker's own choice
+    /// (DOCKER_HOST or its current context) when unset.
+    pub docker_context: Option<String>,
+    /// Set by the install preflight when the daemon enforces SELinux; the
+    /// compose override then relabels bind mounts with `:z`.
+    pub selinux_relabel: bool,
+    /// Caps on how much of the link downloads and pulls may take.
+    pub bandwidth: BandwidthSettings,
+    /// Internal mirrors update checks read from instead of GitHub and GHCR.
+    pub update_sources: UpdateSources,
+    /// Optional services left out of the install, by compose service name.
+    pub disabled_services: Vec<String>,
+    /// Name of the config template each generated file was rendered from,
+    /// keyed by its path under the project directory.
+    pub generated_files: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PullPolicy {
+    Always,
+    Missing,
+    Never,
+}
+
+impl PullPolicy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PullPolicy::Always => "always",
+            PullPolicy::Missing => "missing",
+            PullPolicy::Never => "never",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartPolicy {
+    No,
+    Always,
+    OnFailure,
+    UnlessStopped,
+}
+
+impl RestartPolicy {
+    pub const ALL: [RestartPolicy; 4] = [
+        RestartPolicy::No,
+        RestartPolicy::Always,
+        RestartPolicy::OnFailure,
+        RestartPolicy::UnlessStopped,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RestartPolicy::No => "no",
+            RestartPolicy::Always => "always",
+            RestartPolicy::OnFailure => "on-failure",
+            RestartPolicy::UnlessStopped => "unless-stopped",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.as_str() == value)
+    }
+}
+
+/// How much the services log, written to .env as the variables each of them
+/// reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 4] = [
+        LogLevel::Error,
+        LogLevel::Warn,
+        LogLevel::Info,
+        LogLevel::Debug,
+    ];
+    /// .env keys `apply` writes: the generic one, Keycloak's and Rust services'.
+    pub const ENV_KEYS: [&'static str; 3] = ["LOG_LEVEL", "KC_LOG_LEVEL", "RUST_LOG"];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|l| l.as_str().eq_ignore_ascii_case(value))
+    }
+
+    /// Set the `ENV_KEYS` in `env`, or with `None` remove them so each
+    /// service falls back to its default.
+    pub fn apply(level: Option<Self>, env: &mut EnvFile) {
+        let Some(level) = level else {
+            for key in Self::ENV_KEYS {
+                env.remove(key);
+            }
+            return;
+        };
+        // Keycloak's root category at debug is mostly Hibernate and Vert.x.
+        let keycloak = match level {
+            LogLevel::Debug => "info,org.keycloak:debug",
+            level => level.as_str(),
+        };
+        env.set("LOG_LEVEL", level.as_str());
+        env.set("KC_LOG_LEVEL", keycloak);
+        env.set("RUST_LOG", level.as_str());
+    }
+}
+
+/// Compose tuning that would otherwise mean hand-editing a compose file the
+/// installer rewrites.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdvancedSettings {
+    /// Docker name for the stack network; compose prefixes the project name otherwise.
+    pub network_name: Option<String>,
+    /// Log level of every service; their own defaults when unset.
+    pub log_level: Option<LogLevel>,
+    /// Keyed by compose service name.
+    pub services: BTreeMap<String, ServiceTuning>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServiceTuning {
+    /// CPU limit, e.g. `1.5`.
+    pub cpus: Option<String>,
+    /// Memory limit in docker notation (`512m`, `2g`).
+    pub memory: Option<String>,
+    pub restart: Option<RestartPolicy>,
+    /// Added to the service environment; installer-managed secrets still win.
+    pub environment: BTreeMap<String, String>,
+}
+
+impl ServiceTuning {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProxySettings {
+    pub https_proxy: Option<String>,
+    pub http_proxy: Option<String>,
+    /// Comma-separated hosts/CIDRs that bypass the proxy, as in `NO_PROXY`.
+    pub no_proxy: Option<String>,
+}
+
+/// `"bandwidth": { "download_limit": "2M", "pull_parallelism": 2 }`; the
+/// `--limit-rate` and `--pull-parallelism` flags win over these.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BandwidthSettings {
+    /// Bytes per second for the installer's own downloads, with an optional
+    /// K / M / G suffix as in curl's `--limit-rate`.
+    pub download_limit: Option<String>,
+    /// Images docker pulls at the same time.
+    pub pull_parallelism: Option<usize>,
+}
+
+/// `"update_sources": { "github_api": "https://artifactory.corp/api/github",
+/// "registry": "https://artifactory.corp/api/docker/ghcr" }`: base URLs that
+/// answer the same paths as api.github.com and an OCI registry's `/v2/`.
+/// No GitHub token is sent to either.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UpdateSources {
+    /// Release and package metadata, in place of `https://api.github.com`.
+    pub github_api: Option<String>,
+    /// Tags and manifest digests, in place of each image's own registry.
+    pub registry: Option<String>,
+}
+
+impl UpdateSources {
+    /// Base URL of the GitHub REST API to use.
+    pub fn github_api(&self) -> String {
+        mirror_base(self.github_api.as_deref()).unwrap_or_else(|| "https://api.github.com".into())
+    }
+
+    /// Base URL serving `/v2/` for images hosted on `host`.
+    pub fn registry(&self, host: &str) -> String {
+        mirror_base(self.registry.as_deref()).unwrap_or_else(|| format!("https://{host}"))
+    }
+
+    pub fn github_mirrored(&self) -> bool {
+        mirror_base(self.github_api.as_deref()).is_some()
+    }
+
+    pub fn registry_mirrored(&self) -> bool {
+        mirror_base(self.registry.as_deref()).is_some()
+    }
+}
+
+/// A configured base URL without its trailing slash; blank means unset.
+fn mirror_base(url: Option<&str>) -> Option<String> {
+    let url = url?.trim().trim_end_matches('/');
+    (!url.is_empty()).then(|| url.to_string())
+}
+
+impl ProxySettings {
+    pub fn is_configured(&self) -> bool {
+        self.https_proxy.is_some() || self.http_proxy.is_some()
+    }
+}
+
+/// One notification hook. Set `command`, `webhook`, or both:
+///
+/// ```json
+/// { "event": "install-failure", "webhook": "https://chat.example/hook" }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Hook {
+    pub event: HookEvent,
+    /// Run with `sh -c`; event details are passed as `NQRUST_*` env vars.
+    #[serde(default)]
+    pub command: Option<String>,
+    /// Receives a JSON POST describing the event.
+    #[serde(default)]
+    pub webhook: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HookEvent {
+    InstallSuccess,
+    InstallFailure,
+    UpdateApplied,
+}
+
+impl HookEvent {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HookEvent::InstallSuccess => "install-success",
+            HookEvent::InstallFailure => "install-failure",
+            HookEvent::UpdateApplied => "update-applied",
+        }
+    }
+}
+
+/// Docker json-file `max-size` / `max-file` options. Unbounded container
+/// logs are the usual reason long-lived hosts run out of disk.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogCaps {
+    /// Size per log file, in docker notation (`10m`, `512k`, `1g`).
+    pub max_size: String,
+    /// Number of rotated files kept per container.
+    pub max_file: u32,
+}
+
+impl Default for LogCaps {
+    fn default() -> Self {
+        Self {
+            max_size: "10m".to_string(),
+            max_file: 3,
+        }
+    }
+}
+
+/// How long compose steps may run. A daemon that stops answering otherwise
+/// leaves `pull` / `up` waiting forever. `0` disables a limit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ComposeTimeouts {
+    pub pull_secs: u64,
+    /// `up`, including any image builds it runs.
+    pub up_secs: u64,
+    /// Longest stretch without any compose output.
+    pub stall_secs: u64,
+}
+
+impl Default for ComposeTimeouts {
+    fn default() -> Self {
+        Self {
+            pull_secs: 1800,
+            up_secs: 900,
+            stall_secs: 300,
+        }
+    }
+}
+
+impl ComposeTimeouts {
+    /// Overall limit for a compose subcommand (`pull`, `up`, ...).
+    pub fn for_action(&self, action: &str) -> Option<Duration> {
+        let secs = match action {
+            "pull" => self.pull_secs,
+            "up" | "build" => self.up_secs,
+            _ => 0,
+        };
+        (secs > 0).then(|| Duration::from_secs(secs))
+    }
+
+    pub fn stall(&self) -> Option<Duration> {
+        (self.stall_secs > 0).then(|| Duration::from_secs(self.stall_secs))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupSettings {
+    /// Directory for `pg_dump` archives; `backups/` in the project directory when unset.
+    pub dir: Option<PathBuf>,
+    /// Newest backups kept after each new one; `0` keeps them all.
+    pub keep: usize,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self { dir: None, keep: 7 }
+    }
+}
+
+impl BackupSettings {
+    pub fn dir(&self) -> PathBuf {
+        self.dir
+            .clone()
+            .unwrap_or_else(|| utils::project_root().join("backups"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PinnedTag {
+    pub image: String,
+    pub tag: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            daemon_grace_period_secs: 60,
+            pinned_tags: BTreeMap::new(),
+            onboarding_seen: false,
+            log_caps: LogCaps::default(),
+            service_log_caps: BTreeMap::new(),
+            hooks: Vec::new(),
+            proxy: ProxySettings::default(),
+            pull_policy: None,
+            secrets: SecretsSettings::default(),
+            registries: Vec::new(),
+            mirror: None,
+            compose_timeouts: ComposeTimeouts::default(),
+            backups: BackupSettings::default(),
+            update_check_interval_hours: 24,
+            advanced: AdvancedSettings::default(),
+            gpu_services: Vec::new(),
+            docker_context: None,
+            selinux_relabel: false,
+            bandwidth: BandwidthSettings::default(),
+            update_sources: UpdateSources::default(),
+            disabled_services: Vec::new(),
+            generated_files: BTreeMap::new(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn path() -> PathBuf {
+        let root = utils::project_root();
+        xdg::adopt(
+            &root.join(LEGACY_SETTINGS_FILE),
+            &xdg::project_config(&root).join("settings.json"),
+        )
+    }
+
+    /// Load settings from disk, falling back to defaults when the file is
+    /// missing or unreadable (a broken settings file must never block install).
+    pub fn load() -> Self {
+        fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Pull policy in effect. Airgapped installs default to `never` so a tag
+    /// missing locally fails fast instead of attempting a network pull.
+    pub fn pull_policy(&self, airgapped: bool) -> PullPolicy {
+        self.pull_policy.unwrap_or(if airgapped {
+            PullPolicy::Never
+        } else {
+            PullPolicy::Missing
+        })
+    }
+
+    /// Log caps in effect for `service`.
+    pub fn log_caps_for(&self, service: &str) -> &LogCaps {
+        self.service_log_caps.get(service).unwrap_or(&self.log_caps)
+    }
+
+    /// Whether `service` is left out of the install; only optional services can be.
+    pub fn skips(&self, service: &str) -> bool {
+        self.disabled_services.iter().any(|s| s == service)
+            && stack::current()
+                .services
+                .iter()
+                .any(|s| s.service == service && s.optional)
+    }
+
+    /// The entry service is left out, so a reverse proxy on the host reaches
+    /// `Stack::direct` instead.
+    pub fn skips_entry(&self) -> bool {
+        self.skips(stack::current().entry_service)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(Self::path(), content + "\n")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_fields_use_defaults() {
+        let settings: Settings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings, Settings::default());
+    }
+
+    #[test]
+    fn test_hook_event_names() {
+        let settings: Settings = serde_json::from_str(
+            r#"{"hooks": [{"event": "update-applied", "command": "logger updated"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(settings.hooks[0].event, HookEvent::UpdateApplied);
+        assert_eq!(settings.hooks[0].webhook, None);
+    }
+
+    #[test]
+    fn test_update_sources_fall_back_to_github() {
+        let sources = UpdateSources::default();
+        assert_eq!(sources.github_api(), "https://api.github.com");
+        assert_eq!(sources.registry("ghcr.io"), "https://ghcr.io");
+
+        let settings: Settings = serde_json::from_str(
+            r#"{"update_sources": {"github_api": "https://art.corp/api/github/", "registry": " "}}"#,
+        )
+        .unwrap();
+        let sources = settings.update_sources;
+        assert_eq!(sources.github_api(), "https://art.corp/api/github");
+        assert!(sources.github_mirrored());
+        assert!(!sources.registry_mirrored());
+        assert_eq!(sources.registry("ghcr.io"), "https://ghcr.io");
+    }
+
+    #[test]
+    fn test_log_level_env() {
+        let mut env = EnvFile::parse("SERVER_IP=10.0.0.5\n");
+        LogLevel::apply(LogLevel::parse("DEBUG"), &mut env);
+        assert_eq!(env.get("LOG_LEVEL"), Some("debug"));
+        assert_eq!(env.get("KC_LOG_LEVEL"), Some("info,org.keycloak:debug"));
+        assert_eq!(env.get("RUST_LOG"), Some("debug"));
+
+        LogLevel::apply(Some(LogLevel::Warn), &mut env);
+        assert_eq!(env.get("KC_LOG_LEVEL"), Some("warn"));
+        LogLevel::apply(None, &mut env);
+        assert_eq!(env.get("LOG_LEVEL"), None);
+        assert_eq!(env.get("SERVER_IP"), Some("10.0.0.5"));
+    }
+}
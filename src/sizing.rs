@@ -0,0 +1,180 @@
+// sizing.rs - resource limit presets sized to the Docker host
+// Without limits every container may take the whole host, and Keycloak sizes
+// its heap from the host's memory rather than from what it can really use, so
+// a 2 GB VPS runs out of memory once Postgres, Keycloak and Caddy are all
+// busy. Small, Medium and Large give each service a CPU and memory limit
+// (`StackService::sizes`) plus what has to fit under it, such as Keycloak's
+// heap; the preset the host's memory suits is recommended. Applying one writes
+// the limits into the advanced settings the compose override is generated
+// from, and re-derives the Postgres settings in .env from Postgres's new
+// memory limit instead of the host's.
+
+use crate::db_tuning::{Host, Tuning};
+use crate::env_file::EnvFile;
+use crate::external_db;
+use crate::settings::AdvancedSettings;
+use crate::stack::{self, Size};
+
+const GB: u64 = 1 << 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    Small,
+    Medium,
+    Large,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 3] = [Preset::Small, Preset::Medium, Preset::Large];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Preset::Small => "Small",
+            Preset::Medium => "Medium",
+            Preset::Large => "Large",
+        }
+    }
+
+    /// What the limits of the whole stack add up to leaves room for the OS
+    /// on a host of this size.
+    pub fn recommended(host: Host) -> Self {
+        if host.memory < 4 * GB {
+            Preset::Small
+        } else if host.memory < 12 * GB {
+            Preset::Medium
+        } else {
+            Preset::Large
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Limits of every service of the stack under this preset. CPUs are
+    /// capped at what the host has, since docker refuses more.
+    pub fn sizes(self, host: Option<Host>) -> Vec<(&'static str, Size)> {
+        stack::current()
+            .services
+            .iter()
+            .map(|service| {
+                let mut size = service.sizes[self.index()];
+                if let Some(host) = host {
+                    size.cpus = size.cpus.min(host.cpus as f64);
+                }
+                (service.service, size)
+            })
+            .collect()
+    }
+
+    /// Set the limits in `advanced`; restart policies and other variables
+    /// stay as they are.
+    pub fn apply(self, host: Option<Host>, advanced: &mut AdvancedSettings) {
+        for (service, size) in self.sizes(host) {
+            let tuning = advanced.services.entry(service.to_string()).or_default();
+            tuning.cpus = Some(size.cpus.to_string());
+            tuning.memory = Some(size.memory.to_string());
+            for (key, value) in size.environment {
+                tuning
+                    .environment
+                    .insert(key.to_string(), value.to_string());
+            }
+        }
+    }
+
+    /// Whether `advanced` has this preset's limits.
+    pub fn in_use(self, host: Option<Host>, advanced: &AdvancedSettings) -> bool {
+        self.sizes(host).iter().all(|(service, size)| {
+            advanced.services.get(*service).is_some_and(|tuning| {
+                tuning.cpus.as_deref() == Some(size.cpus.to_string().as_str())
+                    && tuning.memory.as_deref() == Some(size.memory)
+                    && size.environment.iter().all(|(key, value)| {
+                        tuning.environment.get(*key).map(String::as_str) == Some(value)
+                    })
+            })
+        })
+    }
+
+    /// `env` with the Postgres settings derived from the bundled database's
+    /// limit under this preset, replacing the ones sized to the host.
+    pub fn postgres_env(self, host: Option<Host>, env: &EnvFile) -> EnvFile {
+        let mut env = env.clone();
+        let Some((_, size)) = self
+            .sizes(host)
+            .into_iter()
+            .find(|(service, _)| *service == external_db::DB_SERVICE)
+        else {
+            return env;
+        };
+        let Some(memory) = parse_memory(size.memory) else {
+            return env;
+        };
+        let tuning = Tuning::for_host(Host {
+            memory: host.map_or(memory, |host| memory.min(host.memory)),
+            cpus: size.cpus.ceil() as u64,
+        });
+        for (key, value) in tuning.env_pairs() {
+            env.set(key, &value);
+        }
+        env
+    }
+}
+
+/// Bytes in a docker memory size such as `512m` or `4g`.
+fn parse_memory(value: &str) -> Option<u64> {
+    let unit_at = value.char_indices().last()?.0;
+    let (number, unit) = value.split_at(unit_at);
+    let factor = match unit.to_ascii_lowercase().as_str() {
+        "k" => 1 << 10,
+        "m" => 1 << 20,
+        "g" => GB,
+        _ => return value.parse().ok(),
+    };
+    number.parse::<u64>().ok().map(|n| n * factor)
+}
+
+/// `1 CPU, 512m` plus the variables set with it, for the picker.
+pub fn describe(size: &Size) -> String {
+    let cpus = if size.cpus == 1.0 { "CPU" } else { "CPUs" };
+    let mut text = format!("{} {cpus}, {}", size.cpus, size.memory);
+    for (key, value) in size.environment {
+        text.push_str(&format!(", {key}={value}"));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_fit_the_host() {
+        let small_vps = Host {
+            memory: 2 * GB,
+            cpus: 2,
+        };
+        assert_eq!(Preset::recommended(small_vps), Preset::Small);
+        assert_eq!(
+            Preset::recommended(Host {
+                memory: 32 * GB,
+                cpus: 16,
+            }),
+            Preset::Large
+        );
+
+        let mut advanced = AdvancedSettings::default();
+        Preset::Large.apply(Some(small_vps), &mut advanced);
+        let identity = &advanced.services["identity"];
+        assert_eq!(identity.cpus.as_deref(), Some("2"));
+        assert_eq!(identity.memory.as_deref(), Some("4g"));
+        assert!(identity.environment["JAVA_OPTS_KC_HEAP"].contains("-Xmx3g"));
+        assert!(Preset::Large.in_use(Some(small_vps), &advanced));
+        assert!(!Preset::Small.in_use(Some(small_vps), &advanced));
+
+        let env = EnvFile::parse("SERVER_IP=10.0.0.5\nPOSTGRES_SHARED_BUFFERS=4GB\n");
+        let env = Preset::Small.postgres_env(Some(small_vps), &env);
+        assert_eq!(env.get("POSTGRES_SHARED_BUFFERS"), Some("128MB"));
+        assert_eq!(env.get("SERVER_IP"), Some("10.0.0.5"));
+        assert_eq!(parse_memory("4g"), Some(4 * GB));
+    }
+}
@@ -0,0 +1,246 @@
+// smtp.rs - outgoing mail for Keycloak
+// Password resets, e-mail verification and admin notifications all need an
+// SMTP server, which Keycloak keeps per realm in its database. The installer
+// stores the server in .env (`SMTP_*`, so templates and answers files can set
+// it too) and writes it into every realm through the admin API: from the
+// SMTP screen, and after each install when SMTP_HOST is set. The test mail is
+// sent by Keycloak itself to the bootstrap admin's address, so it proves the
+// path the real mails take.
+
+use std::path::Path;
+
+use color_eyre::{Result, eyre::eyre};
+use serde_json::{Value, json};
+
+use crate::env_file::EnvFile;
+use crate::keycloak_migrate::{self, Endpoint};
+use crate::runner::CommandRunner;
+use crate::utils;
+
+pub const HOST: &str = "SMTP_HOST";
+pub const PORT: &str = "SMTP_PORT";
+pub const SECURITY: &str = "SMTP_SECURITY";
+pub const USER: &str = "SMTP_USER";
+pub const PASSWORD: &str = "SMTP_PASSWORD";
+pub const FROM: &str = "SMTP_FROM";
+
+/// How the connection to the SMTP server is protected.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Security {
+    /// Plain connection upgraded with STARTTLS, usually port 587.
+    #[default]
+    Starttls,
+    /// TLS from the first byte, usually port 465.
+    Ssl,
+    None,
+}
+
+impl Security {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Security::Starttls => "starttls",
+            Security::Ssl => "ssl",
+            Security::None => "none",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_ascii_lowercase().as_str() {
+            "" | "starttls" | "tls" => Ok(Security::Starttls),
+            "ssl" => Ok(Security::Ssl),
+            "none" => Ok(Security::None),
+            other => Err(format!("'{other}' is not one of starttls, ssl or none")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    pub security: Security,
+    /// Empty when the server accepts mail without authentication.
+    pub user: String,
+    pub password: String,
+    pub from: String,
+}
+
+impl SmtpSettings {
+    /// The server configured in .env; `None` without SMTP_HOST.
+    pub fn from_env(env: &EnvFile) -> Option<Self> {
+        let get = |key: &str| env.get(key).unwrap_or_default().to_string();
+        let host = get(HOST);
+        if host.is_empty() {
+            return None;
+        }
+        let security = Security::parse(&get(SECURITY)).unwrap_or_default();
+        Some(Self {
+            host,
+            port: get(PORT).parse().unwrap_or_else(|_| default_port(security)),
+            security,
+            user: get(USER),
+            password: get(PASSWORD),
+            from: get(FROM),
+        })
+    }
+
+    pub fn write_env(&self, env: &mut EnvFile) {
+        env.set(HOST, &self.host);
+        env.set(PORT, &self.port.to_string());
+        env.set(SECURITY, self.security.as_str());
+        env.set(USER, &self.user);
+        env.set(PASSWORD, &self.password);
+        env.set(FROM, &self.from);
+    }
+
+    /// The realm's `smtpServer` map, in Keycloak's all-strings form.
+    pub fn realm_config(&self) -> Value {
+        let flag = |on: bool| if on { "true" } else { "false" };
+        json!({
+            "host": self.host,
+            "port": self.port.to_string(),
+            "from": self.from,
+            "starttls": flag(self.security == Security::Starttls),
+            "ssl": flag(self.security == Security::Ssl),
+            "auth": flag(!self.user.is_empty()),
+            "user": self.user,
+            "password": self.password,
+        })
+    }
+}
+
+pub fn default_port(security: Security) -> u16 {
+    match security {
+        Security::Starttls => 587,
+        Security::Ssl => 465,
+        Security::None => 25,
+    }
+}
+
+/// Write the server into the master realm and every other realm. Returns
+/// the realms updated.
+pub async fn apply(endpoint: &Endpoint, settings: &SmtpSettings) -> Result<Vec<String>> {
+    let mut realms = vec!["master".to_string()];
+    realms.extend(endpoint.realm_names().await?);
+    let body = json!({ "smtpServer": settings.realm_config() });
+    for realm in &realms {
+        let (status, response) = endpoint.put(&format!("/realms/{realm}"), &body).await?;
+        if !status.is_success() {
+            return Err(eyre!(
+                "Updating realm {realm} failed (HTTP {status}): {response}"
+            ));
+        }
+    }
+    Ok(realms)
+}
+
+/// After an install: write the server from .env into the realms. Returns a
+/// line for the install log; `None` when .env sets no SMTP_HOST.
+pub async fn apply_from_env(runner: &dyn CommandRunner, root: &Path) -> Option<String> {
+    let env = EnvFile::load(&root.join(".env")).unwrap_or_default();
+    let settings = SmtpSettings::from_env(&env)?;
+    let result = async {
+        let endpoint = Endpoint::login_target(runner).await?;
+        apply(&endpoint, &settings).await
+    }
+    .await;
+    Some(match result {
+        Ok(realms) => format!(
+            "✉️  SMTP server {} set on realm(s): {}",
+            settings.host,
+            realms.join(", ")
+        ),
+        Err(e) => format!("⚠️  Could not set the SMTP server from .env: {e}"),
+    })
+}
+
+/// Have Keycloak send its test mail to the bootstrap admin. With `to`, that
+/// address is first saved on the admin user, since Keycloak only mails the
+/// logged-in user. Returns the recipient.
+pub async fn send_test(
+    endpoint: &Endpoint,
+    settings: &SmtpSettings,
+    to: Option<&str>,
+) -> Result<String> {
+    let (username, _) = keycloak_migrate::target_admin(&utils::project_root());
+    let users = endpoint
+        .get(&format!(
+            "/realms/master/users?username={username}&exact=true"
+        ))
+        .await?;
+    let admin = users
+        .as_array()
+        .and_then(|users| users.first())
+        .ok_or_else(|| eyre!("Admin user {username} not found in the master realm"))?;
+    let id = admin["id"].as_str().unwrap_or_default();
+
+    let recipient = match to {
+        Some(to) => {
+            let (status, response) = endpoint
+                .put(
+                    &format!("/realms/master/users/{id}"),
+                    &json!({ "email": to }),
+                )
+                .await?;
+            if !status.is_success() {
+                return Err(eyre!(
+                    "Setting the email of {username} failed (HTTP {status}): {response}"
+                ));
+            }
+            to.to_string()
+        }
+        None => admin["email"]
+            .as_str()
+            .filter(|email| !email.is_empty())
+            .ok_or_else(|| {
+                eyre!("{username} has no email address: enter one as the test recipient")
+            })?
+            .to_string(),
+    };
+
+    let (status, response) = endpoint
+        .post(
+            "/realms/master/testSMTPConnection",
+            &settings.realm_config(),
+        )
+        .await?;
+    if !status.is_success() {
+        let reason = response["errorMessage"]
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| response.to_string());
+        return Err(eyre!("Keycloak could not send the test mail: {reason}"));
+    }
+    Ok(recipient)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_round_trip_and_realm_config() {
+        let mut env = EnvFile::parse("SERVER_IP=10.0.0.5\n");
+        assert_eq!(SmtpSettings::from_env(&env), None);
+
+        let settings = SmtpSettings {
+            host: "smtp.corp.local".into(),
+            port: 465,
+            security: Security::Ssl,
+            user: String::new(),
+            password: String::new(),
+            from: "sso@corp.local".into(),
+        };
+        settings.write_env(&mut env);
+        assert_eq!(SmtpSettings::from_env(&env), Some(settings.clone()));
+
+        let config = settings.realm_config();
+        assert_eq!(config["port"], "465");
+        assert_eq!(config["ssl"], "true");
+        assert_eq!(config["starttls"], "false");
+        assert_eq!(config["auth"], "false");
+
+        assert_eq!(Security::parse("TLS"), Ok(Security::Starttls));
+        assert!(Security::parse("smtps").is_err());
+    }
+}
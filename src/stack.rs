@@ -0,0 +1,240 @@
+// stack.rs - the product stack this installer drives
+// Everything stack-specific (compose services, bundled files, the .env
+// template, airgapped images) hangs off a `Stack` so menus and templates
+// never hard-code one.
+
+use std::sync::OnceLock;
+
+use color_eyre::{Result, eyre::eyre};
+
+#[derive(Debug)]
+pub struct StackService {
+    /// Compose service name.
+    pub service: &'static str,
+    /// `container_name` from the compose file, as printed by compose progress.
+    pub container: &'static str,
+    /// `image` from the compose file, used when rewriting to a mirror.
+    pub image: &'static str,
+    /// One-line summary for the Confirmation screen.
+    pub description: &'static str,
+    /// How to tell the service is really serving once `compose up` returns.
+    pub probe: Probe,
+    /// Can be left out on the Services screen; no other service needs it.
+    pub optional: bool,
+    /// Limits under the Small, Medium and Large presets; see `sizing`.
+    pub sizes: [Size; 3],
+}
+
+/// CPU and memory limit of one service under a sizing preset.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Size {
+    pub cpus: f64,
+    /// Docker notation, e.g. `512m`.
+    pub memory: &'static str,
+    /// Set alongside the limits, e.g. a JVM heap that fits under them.
+    pub environment: &'static [(&'static str, &'static str)],
+}
+
+impl Size {
+    const fn new(cpus: f64, memory: &'static str) -> Self {
+        Self {
+            cpus,
+            memory,
+            environment: &[],
+        }
+    }
+}
+
+/// Post-install check for one service; see `verify`.
+#[derive(Debug, Clone, Copy)]
+pub enum Probe {
+    /// HTTP GET run inside the container, for ports compose doesn't publish.
+    ContainerHttp(&'static str),
+    /// TCP connect to a published host port.
+    Tcp(u16),
+    /// TLS handshake on a published host port.
+    Tls(u16),
+}
+
+pub struct Stack {
+    /// Value accepted by `--stack`.
+    pub id: &'static str,
+    pub display_name: &'static str,
+    pub services: &'static [StackService],
+    /// Files written into the project directory: (file name, embedded contents).
+    /// The first entry is the compose file.
+    pub bundle: &'static [(&'static str, &'static str)],
+    /// Images loaded in airgapped mode: (image reference, payload file name).
+    /// Must match scripts/airgapped/save-images.sh.
+    pub required_images: &'static [(&'static str, &'static str)],
+    /// Variables the compose file (or the installer, after the stack is up)
+    /// reads from .env; a generated .env may only set these.
+    pub env_keys: &'static [&'static str],
+    /// .env the installer renders for the host; see `env_template`. Sets
+    /// only `env_keys`.
+    pub env_template: &'static str,
+    /// Network the compose file declares and every service joins.
+    pub network: &'static str,
+    /// Service other machines connect to; the firewall helper opens the
+    /// ports it publishes.
+    pub entry_service: &'static str,
+    /// Published instead when `entry_service` is left out, for a reverse
+    /// proxy already running on the host: (service, host address, container port).
+    pub direct: (&'static str, &'static str, u16),
+}
+
+impl Stack {
+    pub fn compose_file(&self) -> &'static str {
+        self.bundle[0].0
+    }
+
+    pub fn service_names(&self) -> impl Iterator<Item = &'static str> {
+        self.services.iter().map(|s| s.service)
+    }
+}
+
+pub const IDENTITY: Stack = Stack {
+    id: "identity",
+    display_name: "NQRust Identity",
+    services: &[
+        StackService {
+            service: "postgres",
+            container: "identity-db",
+            image: "postgres:16-alpine",
+            description: "PostgreSQL 16 — port 5436",
+            probe: Probe::Tcp(5436),
+            // Replaced through "External database", which rewrites its dependents.
+            optional: false,
+            sizes: [
+                Size::new(1.0, "512m"),
+                Size::new(2.0, "1g"),
+                Size::new(4.0, "4g"),
+            ],
+        },
+        StackService {
+            service: "identity",
+            container: "identity",
+            image: "ghcr.io/nexusquantum/nqrust-identity:${IDENTITY_TAG:-latest}",
+            description: "Keycloak — port 8008",
+            probe: Probe::ContainerHttp("http://localhost:9000/health/ready"),
+            optional: false,
+            // Keycloak's default heap is 70% of the limit, which leaves too
+            // little for metaspace and threads under a small one.
+            sizes: [
+                Size {
+                    cpus: 1.0,
+                    memory: "1g",
+                    environment: &[("JAVA_OPTS_KC_HEAP", "-Xms256m -Xmx640m")],
+                },
+                Size {
+                    cpus: 2.0,
+                    memory: "2g",
+                    environment: &[("JAVA_OPTS_KC_HEAP", "-Xms512m -Xmx1280m")],
+                },
+                Size {
+                    cpus: 4.0,
+                    memory: "4g",
+                    environment: &[("JAVA_OPTS_KC_HEAP", "-Xms1g -Xmx3g")],
+                },
+            ],
+        },
+        StackService {
+            service: "caddy",
+            container: "identity-caddy",
+            image: "caddy:2-alpine",
+            description: "HTTPS proxy — port 8008",
+            probe: Probe::Tls(8008),
+            optional: true,
+            sizes: [
+                Size::new(0.5, "128m"),
+                Size::new(1.0, "256m"),
+                Size::new(2.0, "512m"),
+            ],
+        },
+    ],
+    bundle: &[
+        (
+            "docker-compose.yaml",
+            include_str!("../docker-compose.yaml"),
+        ),
+        ("Caddyfile", include_str!("../Caddyfile")),
+    ],
+    required_images: &[
+        ("postgres:16-alpine", "postgres.tar.gz"),
+        (
+            "ghcr.io/nexusquantum/nqrust-identity:latest",
+            "nqrust-identity.tar.gz",
+        ),
+        ("caddy:2-alpine", "caddy.tar.gz"),
+    ],
+    env_keys: &[
+        "SERVER_IP",
+        "IDENTITY_TAG",
+        "KC_THEME_DEFAULT",
+        "KC_THEME_WELCOME",
+        "KC_BOOTSTRAP_ADMIN_USERNAME",
+        "KC_BOOTSTRAP_ADMIN_PASSWORD",
+        "SMTP_HOST",
+        "SMTP_PORT",
+        "SMTP_SECURITY",
+        "SMTP_USER",
+        "SMTP_PASSWORD",
+        "SMTP_FROM",
+        "LDAP_URL",
+        "LDAP_VENDOR",
+        "LDAP_BIND_DN",
+        "LDAP_BIND_CREDENTIAL",
+        "LDAP_USERS_DN",
+        "LDAP_GROUPS_DN",
+        "LDAP_REALM",
+        "POSTGRES_SHARED_BUFFERS",
+        "POSTGRES_EFFECTIVE_CACHE_SIZE",
+        "POSTGRES_WORK_MEM",
+        "POSTGRES_MAINTENANCE_WORK_MEM",
+        "POSTGRES_MAX_CONNECTIONS",
+        "POSTGRES_WAL_BUFFERS",
+        "POSTGRES_MIN_WAL_SIZE",
+        "POSTGRES_MAX_WAL_SIZE",
+        "POSTGRES_MAX_WORKER_PROCESSES",
+        "POSTGRES_MAX_PARALLEL_WORKERS",
+        "LOG_LEVEL",
+        "KC_LOG_LEVEL",
+        "RUST_LOG",
+        "EXTERNAL_DB_HOST",
+        "EXTERNAL_DB_PORT",
+        "EXTERNAL_DB_NAME",
+        "EXTERNAL_DB_USER",
+        "EXTERNAL_DB_PASSWORD",
+        "EXTERNAL_DB_SSLMODE",
+    ],
+    env_template: include_str!("../identity.env"),
+    network: "identity-net",
+    entry_service: "caddy",
+    direct: ("identity", "127.0.0.1:8080", 8080),
+};
+
+/// Stacks this binary can install. Other products register here once their
+/// compose bundle is embedded.
+pub const STACKS: &[Stack] = &[IDENTITY];
+
+static SELECTED: OnceLock<&'static Stack> = OnceLock::new();
+
+/// Pick the stack for this run; called once from `main`.
+pub fn select(id: &str) -> Result<()> {
+    let stack = STACKS.iter().find(|s| s.id == id).ok_or_else(|| {
+        let known: Vec<&str> = STACKS.iter().map(|s| s.id).collect();
+        eyre!("Unknown stack '{id}' (available: {})", known.join(", "))
+    })?;
+    SELECTED
+        .set(stack)
+        .map_err(|_| eyre!("Stack already selected"))
+}
+
+/// The selected stack, or the first one when none was selected.
+pub fn current() -> &'static Stack {
+    SELECTED.get().copied().unwrap_or(&STACKS[0])
+}
+
+pub fn ids() -> Vec<&'static str> {
+    STACKS.iter().map(|s| s.id).collect()
+}
@@ -0,0 +1,173 @@
+// systemd.rs - boot-time unit for the installed stack
+// Containers only come back after a reboot if something starts them. The
+// unit runs `compose up -d` on boot and `compose down` on shutdown, with the
+// same compose files and image tag the installer used.
+
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::{Result, eyre};
+
+use crate::runner::{Cmd, CommandRunner};
+
+pub const UNIT_NAME: &str = "nqrust-identity.service";
+const UNIT_DIR: &str = "/etc/systemd/system";
+
+/// True on hosts booted with systemd.
+pub fn available() -> bool {
+    cfg!(target_os = "linux") && Path::new("/run/systemd/system").is_dir()
+}
+
+pub fn unit_path() -> PathBuf {
+    Path::new(UNIT_DIR).join(UNIT_NAME)
+}
+
+/// Unit file text. `compose_cmd[0]` is resolved against `PATH`, since
+/// systemd wants absolute executables.
+pub fn render(
+    root: &Path,
+    compose_cmd: &[String],
+    compose_args: &[String],
+    identity_tag: &str,
+) -> String {
+    let program = find_in_path(&compose_cmd[0])
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|| compose_cmd[0].clone());
+    let command = |action: &[&str]| {
+        std::iter::once(program.as_str())
+            .chain(compose_cmd[1..].iter().map(String::as_str))
+            .chain(compose_args.iter().map(String::as_str))
+            .chain(action.iter().copied())
+            .map(quote)
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    let mut environment = vec![format!("IDENTITY_TAG={identity_tag}")];
+    if let Ok(host) = std::env::var("DOCKER_HOST") {
+        environment.push(format!("DOCKER_HOST={host}"));
+    }
+    let environment: String = environment
+        .iter()
+        .map(|var| format!("Environment={}\n", quote(var)))
+        .collect();
+
+    format!(
+        "# Written by nqrust-identity. Re-run \"Install as systemd service\" after\n\
+         # changing compose files instead of editing this unit.\n\
+         [Unit]\n\
+         Description=NQRust Identity stack (docker compose)\n\
+         Requires=docker.service\n\
+         After=docker.service network-online.target\n\
+         Wants=network-online.target\n\
+         \n\
+         [Service]\n\
+         Type=oneshot\n\
+         RemainAfterExit=yes\n\
+         WorkingDirectory={}\n\
+         {environment}\
+         ExecStart={}\n\
+         ExecStop={}\n\
+         TimeoutStartSec=0\n\
+         \n\
+         [Install]\n\
+         WantedBy=multi-user.target\n",
+        quote(&root.display().to_string()),
+        command(&["up", "-d", "--remove-orphans"]),
+        command(&["down"]),
+    )
+}
+
+/// Where the unit is written before it is copied into place.
+pub fn staged_path(staging_dir: &Path) -> PathBuf {
+    staging_dir.join(UNIT_NAME)
+}
+
+/// Copy `unit` into place and `enable --now` it, under `sudo -n` so a
+/// password prompt never takes over the TUI. Returns the steps that ran.
+/// On failure the staged copy is left for `manual_commands`.
+pub async fn install(
+    runner: &dyn CommandRunner,
+    unit: &str,
+    staging_dir: &Path,
+) -> Result<Vec<String>> {
+    let staged = staged_path(staging_dir);
+    std::fs::write(&staged, unit)?;
+    let target = unit_path().display().to_string();
+    let staged_path = staged.display().to_string();
+    let steps: [&[&str]; 3] = [
+        &["install", "-m", "644", &staged_path, &target],
+        &["systemctl", "daemon-reload"],
+        &["systemctl", "enable", "--now", UNIT_NAME],
+    ];
+
+    let mut done = Vec::new();
+    for step in steps {
+        run_privileged(runner, step).await?;
+        done.push(step.join(" "));
+    }
+    let _ = std::fs::remove_file(&staged);
+    Ok(done)
+}
+
+/// What to run by hand when `sudo -n` is not allowed.
+pub fn manual_commands(staging_dir: &Path) -> Vec<String> {
+    vec![
+        format!(
+            "sudo install -m 644 {} {}",
+            staged_path(staging_dir).display(),
+            unit_path().display()
+        ),
+        "sudo systemctl daemon-reload".to_string(),
+        format!("sudo systemctl enable --now {UNIT_NAME}"),
+    ]
+}
+
+/// Run `command` under `sudo -n`, failing with its stderr.
+pub async fn run_privileged(runner: &dyn CommandRunner, command: &[&str]) -> Result<()> {
+    let output = runner
+        .output(&Cmd::new("sudo").arg("-n").args(command))
+        .await?;
+    if !output.success {
+        return Err(eyre!("{}: {}", command.join(" "), output.stderr.trim()));
+    }
+    Ok(())
+}
+
+fn find_in_path(program: &str) -> Option<PathBuf> {
+    if program.contains('/') {
+        return Some(PathBuf::from(program));
+    }
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(program))
+        .find(|path| path.is_file())
+}
+
+/// Quote a word for a unit file when it contains spaces or quotes.
+fn quote(word: &str) -> String {
+    if word.contains([' ', '"', '\\']) {
+        format!("\"{}\"", word.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        word.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_wraps_compose() {
+        let unit = render(
+            Path::new("/opt/nq identity"),
+            &["/usr/bin/docker".to_string(), "compose".to_string()],
+            &["-f".to_string(), "docker-compose.yaml".to_string()],
+            "v1.2.0",
+        );
+        assert!(unit.contains("WorkingDirectory=\"/opt/nq identity\"\n"));
+        assert!(unit.contains("Environment=IDENTITY_TAG=v1.2.0\n"));
+        assert!(unit.contains(
+            "ExecStart=/usr/bin/docker compose -f docker-compose.yaml up -d --remove-orphans\n"
+        ));
+        assert!(unit.contains("ExecStop=/usr/bin/docker compose -f docker-compose.yaml down\n"));
+    }
+}
@@ -1,10 +1,532 @@
-// templates.rs - stub for NQRust Identity installer
-// Config template generation is not needed for Identity.
+// templates.rs - config templates loaded from TOML/YAML definitions
+// Built-in templates are embedded from the repo's templates/ directory; the
+// project's own templates/ folder adds new ones (or replaces a built-in with
+// the same file name) without rebuilding the installer. Bodies are Jinja
+// templates (minijinja), so a file can be conditional on a field's value.
 
-#[allow(dead_code)]
+use std::collections::BTreeMap;
+use std::fs;
+use std::net::IpAddr;
+use std::path::{Component, Path, PathBuf};
+
+use include_dir::{Dir, include_dir};
+use minijinja::{AutoEscape, Environment, UndefinedBehavior, Value};
+use serde::Deserialize;
+
+use crate::{env_file, stack};
+
+static BUILTIN: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/templates");
+
+/// Check applied to a field's value before it is rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rule {
+    Port,
+    Url,
+    Email,
+    Ip,
+    Number,
+    Hostname,
+}
+
+/// Vendor whose API a key field is checked against.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyProvider {
+    Openai,
+    Anthropic,
+    Gemini,
+}
+
+impl KeyProvider {
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyProvider::Openai => "OpenAI",
+            KeyProvider::Anthropic => "Anthropic",
+            KeyProvider::Gemini => "Gemini",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TemplateField {
+    /// Name used in `{{key}}` placeholders.
+    pub key: String,
+    #[serde(default)]
+    pub label: String,
+    #[serde(default)]
+    pub default: String,
+    #[serde(default)]
+    pub required: bool,
+    /// Masked in the form.
+    #[serde(default)]
+    pub secret: bool,
+    /// Allowed values; any value when empty.
+    #[serde(default)]
+    pub options: Vec<String>,
+    /// Comma-separated value, rendered as a list to loop over.
+    #[serde(default)]
+    pub list: bool,
+    #[serde(default)]
+    pub validate: Option<Rule>,
+    /// URL of an OpenAI-compatible API (LLM or embeddings): saving first lists
+    /// its models and reports latency, or why it failed.
+    #[serde(default)]
+    pub probe: bool,
+    /// API key of this vendor: saving first makes a cheap authenticated call
+    /// and reports whether the key is accepted.
+    #[serde(default)]
+    pub verify: Option<KeyProvider>,
+}
+
+impl TemplateField {
+    pub fn label(&self) -> &str {
+        if self.label.is_empty() {
+            &self.key
+        } else {
+            &self.label
+        }
+    }
+
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        if self.list {
+            let items = split_list(value);
+            if items.is_empty() && self.required {
+                return Err(format!("{} is required", self.label()));
+            }
+            return items.iter().try_for_each(|item| self.validate_one(item));
+        }
+        self.validate_one(value)
+    }
+
+    fn validate_one(&self, value: &str) -> Result<(), String> {
+        let label = self.label();
+        if value.is_empty() {
+            return if self.required {
+                Err(format!("{label} is required"))
+            } else {
+                Ok(())
+            };
+        }
+        if !self.options.is_empty() && !self.options.iter().any(|o| o == value) {
+            return Err(format!(
+                "{label} must be one of: {}",
+                self.options.join(", ")
+            ));
+        }
+        let valid = match self.validate {
+            None => true,
+            Some(Rule::Port) => value.parse::<u16>().is_ok_and(|p| p != 0),
+            Some(Rule::Url) => reqwest::Url::parse(value).is_ok(),
+            Some(Rule::Email) => value
+                .split_once('@')
+                .is_some_and(|(user, domain)| !user.is_empty() && domain.contains('.')),
+            Some(Rule::Ip) => value.parse::<IpAddr>().is_ok(),
+            Some(Rule::Number) => value.parse::<f64>().is_ok(),
+            Some(Rule::Hostname) => {
+                value.len() <= 253
+                    && value.split('.').all(|part| {
+                        !part.is_empty()
+                            && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                    })
+            }
+        };
+        if valid {
+            Ok(())
+        } else {
+            let expected = match self.validate {
+                Some(Rule::Port) => "a port number (1-65535)",
+                Some(Rule::Url) => "a valid URL",
+                Some(Rule::Email) => "an email address",
+                Some(Rule::Ip) => "an IP address",
+                Some(Rule::Number) => "a number",
+                Some(Rule::Hostname) | None => "a hostname",
+            };
+            Err(format!("{label} must be {expected}"))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct ConfigTemplate {
-    pub key: &'static str,
+    /// File name without extension; unique across built-in and user templates.
+    #[serde(skip)]
+    pub key: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// File written, relative to the project directory.
+    pub output: PathBuf,
+    #[serde(default)]
+    pub fields: Vec<TemplateField>,
+    /// Jinja template: `{{ key }}` placeholders, `{% if %}` and `{% for %}`.
+    pub body: String,
+    /// Compose services that need an NVIDIA GPU, e.g. local LLM inference.
+    /// Choosing the template checks the host, and saving reserves the GPU for
+    /// them in the compose override when it is usable.
+    #[serde(default)]
+    pub gpu_services: Vec<String>,
+    /// Whether this came from the project's templates/ folder.
+    #[serde(skip)]
+    pub user: bool,
+}
+
+impl ConfigTemplate {
+    /// Whether the name, key or description contains `filter`, ignoring case.
+    pub fn matches(&self, filter: &str) -> bool {
+        let filter = filter.to_lowercase();
+        [&self.name, &self.key, &self.description]
+            .iter()
+            .any(|text| text.to_lowercase().contains(&filter))
+    }
+
+    /// Parse a `.toml`, `.yaml` or `.yml` definition named `file_name`.
+    pub fn parse(file_name: &str, contents: &str) -> Result<Self, String> {
+        let path = Path::new(file_name);
+        let key = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or_else(|| format!("{file_name}: bad file name"))?;
+        let mut template: ConfigTemplate = match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => toml::from_str(contents).map_err(|e| format!("{file_name}: {e}"))?,
+            Some("yaml" | "yml") => {
+                serde_yaml::from_str(contents).map_err(|e| format!("{file_name}: {e}"))?
+            }
+            _ => return Err(format!("{file_name}: not a .toml or .yaml file")),
+        };
+        template.key = key.to_string();
+        template.check().map_err(|e| format!("{file_name}: {e}"))?;
+        Ok(template)
+    }
+
+    /// Reject definitions that could never render: bad Jinja syntax,
+    /// variables without a field, or an output outside the project directory.
+    fn check(&self) -> Result<(), String> {
+        let escapes = self.output.as_os_str().is_empty()
+            || self
+                .output
+                .components()
+                .any(|c| !matches!(c, Component::Normal(_)));
+        if escapes {
+            return Err(format!(
+                "output '{}' must be a relative path inside the project",
+                self.output.display()
+            ));
+        }
+        if let Some(service) = self.gpu_services.iter().find(|s| {
+            !stack::current()
+                .service_names()
+                .any(|name| name == s.as_str())
+        }) {
+            return Err(format!(
+                "gpu_services: '{service}' is not a service of this stack"
+            ));
+        }
+        let env = environment();
+        let body = env
+            .template_from_str(&self.body)
+            .map_err(|e| e.to_string())?;
+        let mut unknown: Vec<String> = body
+            .undeclared_variables(false)
+            .into_iter()
+            .filter(|name| !self.fields.iter().any(|f| &f.key == name))
+            .collect();
+        if !unknown.is_empty() {
+            unknown.sort();
+            return Err(format!("no field for {}", unknown.join(", ")));
+        }
+        for field in &self.fields {
+            if field.probe && field.validate != Some(Rule::Url) {
+                return Err(format!("{}: probe needs validate = \"url\"", field.key));
+            }
+            if !field.default.is_empty() {
+                field.validate(&field.default)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Validate `values` (missing keys take the field default) and fill in
+    /// the body.
+    pub fn render(&self, values: &BTreeMap<String, String>) -> Result<String, String> {
+        let mut context = BTreeMap::new();
+        for field in &self.fields {
+            let value = values
+                .get(&field.key)
+                .map(|v| v.trim())
+                .unwrap_or(&field.default);
+            field.validate(value)?;
+            let value = if field.list {
+                Value::from(split_list(value))
+            } else {
+                Value::from(value)
+            };
+            context.insert(field.key.as_str(), value);
+        }
+        let env = environment();
+        let rendered = env
+            .render_str(&self.body, context)
+            .map_err(|e| format!("{}: {e}", self.name))?;
+        check_output(&self.output, &rendered)
+            .map_err(|e| format!("{} would be invalid: {e}", self.output.display()))?;
+        Ok(rendered)
+    }
+
+    pub fn output_path(&self, root: &Path) -> PathBuf {
+        root.join(&self.output)
+    }
+}
+
+/// Refuse output its consumer would choke on, judged by the file name: a
+/// `.env` must pass `env_file::check` (and the project's own `.env` may only
+/// set keys the stack reads); YAML and JSON must parse.
+fn check_output(output: &Path, rendered: &str) -> Result<(), String> {
+    let is_env = output.file_name().is_some_and(|name| name == ".env")
+        || output.extension().is_some_and(|ext| ext == "env");
+    match output.extension().and_then(|e| e.to_str()) {
+        _ if is_env => {
+            let allowed = (output == Path::new(".env")).then(|| stack::current().env_keys);
+            env_file::check(rendered, allowed)
+        }
+        Some("yaml" | "yml") => serde_yaml::from_str::<serde_yaml::Value>(rendered)
+            .map(drop)
+            .map_err(|e| e.to_string()),
+        Some("json") => serde_json::from_str::<serde_json::Value>(rendered)
+            .map(drop)
+            .map_err(|e| e.to_string()),
+        _ => Ok(()),
+    }
 }
 
-#[allow(dead_code)]
-pub const CONFIG_TEMPLATES: &[ConfigTemplate] = &[];
+/// Jinja settings for template bodies: no HTML escaping, undefined variables
+/// are errors, and block tags don't leave blank lines behind.
+fn environment() -> Environment<'static> {
+    let mut env = Environment::new();
+    env.set_auto_escape_callback(|_| AutoEscape::None);
+    env.set_undefined_behavior(UndefinedBehavior::Strict);
+    env.set_keep_trailing_newline(true);
+    env.set_trim_blocks(true);
+    env.set_lstrip_blocks(true);
+    env
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Built-in templates plus those in `<root>/templates`; a user template
+/// replaces the built-in with the same key. Files that fail to parse are
+/// skipped and reported in the returned warnings.
+pub fn load_all(root: &Path) -> (Vec<ConfigTemplate>, Vec<String>) {
+    let mut templates: Vec<ConfigTemplate> = Vec::new();
+    let mut warnings = Vec::new();
+
+    for file in BUILTIN.files() {
+        let name = file.path().to_string_lossy();
+        let Some(contents) = file.contents_utf8() else {
+            continue;
+        };
+        match ConfigTemplate::parse(&name, contents) {
+            Ok(template) => templates.push(template),
+            Err(e) => warnings.push(e),
+        }
+    }
+
+    let dir = root.join("templates");
+    let mut user_files: Vec<PathBuf> = fs::read_dir(&dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            matches!(
+                path.extension().and_then(|e| e.to_str()),
+                Some("toml" | "yaml" | "yml")
+            )
+        })
+        .collect();
+    user_files.sort();
+    for path in user_files {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let parsed = fs::read_to_string(&path)
+            .map_err(|e| format!("{name}: {e}"))
+            .and_then(|contents| ConfigTemplate::parse(&name, &contents));
+        match parsed {
+            Ok(mut template) => {
+                template.user = true;
+                templates.retain(|t| t.key != template.key);
+                templates.push(template);
+            }
+            Err(e) => warnings.push(e),
+        }
+    }
+
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+    (templates, warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_templates_parse() {
+        let (templates, warnings) = load_all(Path::new("/nonexistent"));
+        assert!(warnings.is_empty(), "{warnings:?}");
+        let caddy = templates.iter().find(|t| t.key == "caddy").unwrap();
+        let rendered = caddy.render(&BTreeMap::new()).unwrap();
+        assert!(rendered.contains("reverse_proxy identity:8080"));
+        assert!(rendered.contains("redir https://{host}:8008{uri}"));
+        assert!(caddy.matches("CADDY"));
+        assert!(caddy.matches(""));
+        assert!(!caddy.matches("no such template"));
+    }
+
+    #[test]
+    fn test_yaml_template_validates_and_renders() {
+        let yaml = "\
+name: SMTP relay
+output: conf/smtp.env
+body: \"HOST={{ host }}\\nPORT={{port}}\\n\"
+fields:
+  - key: host
+    required: true
+  - key: port
+    default: \"25\"
+    validate: port
+";
+        let template = ConfigTemplate::parse("smtp.yaml", yaml).unwrap();
+        let mut values = BTreeMap::new();
+        assert_eq!(
+            template.render(&values).unwrap_err(),
+            "host is required".to_string()
+        );
+        values.insert("host".to_string(), "mail.local".to_string());
+        values.insert("port".to_string(), "99999".to_string());
+        assert!(template.render(&values).is_err());
+        values.insert("port".to_string(), "587".to_string());
+        assert_eq!(
+            template.render(&values).unwrap(),
+            "HOST=mail.local\nPORT=587\n"
+        );
+    }
+
+    #[test]
+    fn test_gpu_services_must_be_stack_services() {
+        let yaml = "name: Local LLM\noutput: llm.env\nbody: \"\"\ngpu_services: [identity]\n";
+        let template = ConfigTemplate::parse("llm.yaml", yaml).unwrap();
+        assert_eq!(template.gpu_services, ["identity"]);
+        let err =
+            ConfigTemplate::parse("llm.yaml", &yaml.replace("identity", "ollama")).unwrap_err();
+        assert!(err.contains("'ollama' is not a service"), "{err}");
+    }
+
+    #[test]
+    fn test_conditionals_and_loops() {
+        let toml = r#"
+name = "Mail"
+output = "mail.env"
+body = """
+PROVIDER={{ provider }}
+{% if provider == "openai" %}
+OPENAI_API_KEY={{ api_key }}
+{% endif %}
+{% for host in hosts %}
+HOST_{{ loop.index }}={{ host }}
+{% endfor %}
+"""
+
+[[fields]]
+key = "provider"
+options = ["openai", "ollama"]
+default = "ollama"
+
+[[fields]]
+key = "api_key"
+secret = true
+
+[[fields]]
+key = "hosts"
+list = true
+validate = "hostname"
+"#;
+        let template = ConfigTemplate::parse("mail.toml", toml).unwrap();
+        let mut values = BTreeMap::new();
+        values.insert("hosts".to_string(), "a.local, b.local".to_string());
+        assert_eq!(
+            template.render(&values).unwrap(),
+            "PROVIDER=ollama\nHOST_1=a.local\nHOST_2=b.local\n"
+        );
+        values.insert("provider".to_string(), "openai".to_string());
+        values.insert("api_key".to_string(), "sk-1".to_string());
+        values.insert("hosts".to_string(), String::new());
+        assert_eq!(
+            template.render(&values).unwrap(),
+            "PROVIDER=openai\nOPENAI_API_KEY=sk-1\n"
+        );
+        values.insert("hosts".to_string(), "bad host".to_string());
+        assert!(template.render(&values).is_err());
+    }
+
+    #[test]
+    fn test_invalid_output_is_refused() {
+        let toml = r#"
+name = "env"
+output = ".env"
+body = """
+SERVER_IP={{ ip }}
+{{ extra }}
+"""
+
+[[fields]]
+key = "ip"
+
+[[fields]]
+key = "extra"
+"#;
+        let template = ConfigTemplate::parse("env.toml", toml).unwrap();
+        let mut values = BTreeMap::new();
+        values.insert("ip".to_string(), "10.0.0.5".to_string());
+        assert!(template.render(&values).is_ok());
+        values.insert("extra".to_string(), "UNKNOWN=1".to_string());
+        assert_eq!(
+            template.render(&values).unwrap_err(),
+            format!(
+                ".env would be invalid: line 2: UNKNOWN is not read by the stack (known: {})",
+                stack::current().env_keys.join(", ")
+            )
+        );
+        values.insert("extra".to_string(), String::new());
+        values.insert("ip".to_string(), "not-an-ip".to_string());
+        assert!(template.render(&values).is_err());
+
+        let yaml = r#"
+name = "y"
+output = "config.yaml"
+body = """
+port: {{ port }}
+models: [
+"""
+
+[[fields]]
+key = "port"
+"#;
+        let template = ConfigTemplate::parse("y.toml", yaml).unwrap();
+        assert!(template.render(&BTreeMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_rejects_unknown_placeholder_and_escaping_output() {
+        let toml = "name = 'x'\noutput = 'a.conf'\nbody = '{{missing}}'\n";
+        assert!(ConfigTemplate::parse("x.toml", toml).is_err());
+        let toml = "name = 'x'\noutput = 'a.conf'\nbody = '{% if %}'\n";
+        assert!(ConfigTemplate::parse("x.toml", toml).is_err());
+        let toml = "name = 'x'\noutput = '../a.conf'\nbody = ''\n";
+        assert!(ConfigTemplate::parse("x.toml", toml).is_err());
+    }
+}
@@ -0,0 +1,135 @@
+// throttle.rs - keeping installs from filling a shared link
+// Branch offices often reach GitHub over one thin WAN link. Two knobs keep an
+// install or update from saturating it: a byte rate for the downloads the
+// installer makes itself (the self-update), which reads the body in chunks
+// and sleeps to stay under the rate, and how many images docker pulls at
+// once, handed to compose as COMPOSE_PARALLEL_LIMIT and used by batch pulls
+// on the update list. Docker's own transfer speed cannot be capped from
+// outside the daemon.
+
+use std::num::NonZeroUsize;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use crate::settings::BandwidthSettings;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// Bytes per second.
+    pub download_rate: Option<u64>,
+    pub pull_parallelism: Option<NonZeroUsize>,
+}
+
+impl Limits {
+    /// From settings; a rate that does not parse means no limit.
+    pub fn from_settings(settings: &BandwidthSettings) -> Self {
+        Self {
+            download_rate: settings
+                .download_limit
+                .as_deref()
+                .and_then(|rate| parse_rate(rate).ok()),
+            pull_parallelism: settings.pull_parallelism.and_then(NonZeroUsize::new),
+        }
+    }
+}
+
+static LIMITS: RwLock<Limits> = RwLock::new(Limits {
+    download_rate: None,
+    pull_parallelism: None,
+});
+
+/// Use `limits` for downloads and pulls from now on.
+pub fn select(limits: Limits) {
+    if let Ok(mut current) = LIMITS.write() {
+        *current = limits;
+    }
+}
+
+pub fn limits() -> Limits {
+    LIMITS.read().map(|limits| *limits).unwrap_or_default()
+}
+
+/// `500K`, `2M`, `1.5m`, `1G` or plain bytes, per second.
+pub fn parse_rate(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let (number, unit) = match value.char_indices().last() {
+        Some((idx, c)) if c.is_ascii_alphabetic() => (&value[..idx], c.to_ascii_lowercase()),
+        _ => (value, 'b'),
+    };
+    let factor: u64 = match unit {
+        'b' => 1,
+        'k' => 1 << 10,
+        'm' => 1 << 20,
+        'g' => 1 << 30,
+        _ => return Err(format!("'{value}': the unit must be K, M or G")),
+    };
+    match number.trim().parse::<f64>() {
+        Ok(n) if n > 0.0 => Ok((n * factor as f64) as u64),
+        _ => Err(format!("'{value}' is not a rate such as 500K or 2M")),
+    }
+}
+
+/// Paces a download to the selected rate: call `consume` with every chunk.
+#[derive(Debug)]
+pub struct Pacer {
+    rate: Option<u64>,
+    started: Instant,
+    bytes: u64,
+}
+
+impl Pacer {
+    pub fn new() -> Self {
+        Self {
+            rate: limits().download_rate,
+            started: Instant::now(),
+            bytes: 0,
+        }
+    }
+
+    /// Sleep until `bytes` more fit under the rate.
+    pub async fn consume(&mut self, bytes: usize) {
+        self.bytes += bytes as u64;
+        if let Some(rate) = self.rate {
+            let wait = delay(rate, self.bytes, self.started.elapsed());
+            if !wait.is_zero() {
+                tokio::time::sleep(wait).await;
+            }
+        }
+    }
+}
+
+impl Default for Pacer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How long to wait so `bytes` in `elapsed` plus the wait stays at `rate`.
+fn delay(rate: u64, bytes: u64, elapsed: Duration) -> Duration {
+    Duration::from_secs_f64(bytes as f64 / rate as f64).saturating_sub(elapsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rate_and_delay() {
+        assert_eq!(parse_rate("500K"), Ok(500 * 1024));
+        assert_eq!(parse_rate("1.5m"), Ok(3 << 19));
+        assert_eq!(parse_rate("2048"), Ok(2048));
+        assert!(parse_rate("2T").is_err());
+        assert!(parse_rate("fast").is_err());
+        assert!(parse_rate("0").is_err());
+
+        // 1 MiB at 512 KiB/s takes two seconds; one has gone already.
+        assert_eq!(
+            delay(512 << 10, 1 << 20, Duration::from_secs(1)),
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            delay(512 << 10, 1 << 20, Duration::from_secs(3)),
+            Duration::ZERO
+        );
+    }
+}
@@ -0,0 +1,131 @@
+// token_check.rs - GitHub-side checks for a GHCR token before `docker login`
+// docker only reports "denied"; the GitHub API tells us why.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use reqwest::Client;
+
+use crate::http;
+
+const USER_API: &str = "https://api.github.com/user";
+/// Tokens expiring within this many days get a warning.
+const EXPIRY_WARNING_DAYS: i64 = 7;
+
+/// What GitHub said about a usable token.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Checked {
+    pub warning: Option<String>,
+    /// Set for tokens created with an expiration date.
+    pub expires: Option<DateTime<Utc>>,
+}
+
+/// Ok carries an optional warning and the expiry; Err is a message for
+/// `registry_status`.
+pub async fn check(client: &Client, token: &str) -> Result<Checked, String> {
+    let response = match http::send(client.get(USER_API).bearer_auth(token)).await {
+        Ok(response) => response,
+        // GitHub API unreachable (firewalled host): leave it to docker login.
+        Err(_) => return Ok(Checked::default()),
+    };
+    match response.status().as_u16() {
+        401 => {
+            return Err("❌ GitHub rejected the token (mistyped, revoked or expired)".to_string());
+        }
+        403 if response
+            .headers()
+            .get("x-ratelimit-remaining")
+            .is_some_and(|v| v == "0") =>
+        {
+            return Ok(Checked::default());
+        }
+        _ => {}
+    }
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+    };
+    evaluate(
+        token,
+        header("x-oauth-scopes").as_deref(),
+        header("github-authentication-token-expiration").as_deref(),
+        Utc::now(),
+    )
+}
+
+fn evaluate(
+    token: &str,
+    scopes: Option<&str>,
+    expiration: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<Checked, String> {
+    // Classic PATs list their scopes; write:packages implies read.
+    if let Some(scopes) = scopes {
+        let scopes: Vec<&str> = scopes.split(',').map(str::trim).collect();
+        if !scopes
+            .iter()
+            .any(|s| *s == "read:packages" || *s == "write:packages")
+        {
+            let has = if scopes.iter().all(|s| s.is_empty()) {
+                "none".to_string()
+            } else {
+                scopes.join(", ")
+            };
+            return Err(format!(
+                "❌ Token is missing the read:packages scope (has: {has})"
+            ));
+        }
+    }
+
+    let mut warnings = Vec::new();
+    if token.starts_with("github_pat_") {
+        warnings.push("fine-grained token: GHCR officially supports classic PATs only".to_string());
+    }
+    let expires = expiration
+        .and_then(|raw| NaiveDateTime::parse_from_str(raw, "%Y-%m-%d %H:%M:%S UTC").ok())
+        .map(|naive| naive.and_utc());
+    if let Some(expires) = expires {
+        let days = (expires - now).num_days();
+        if days < EXPIRY_WARNING_DAYS {
+            warnings.push(format!(
+                "token expires {} ({})",
+                if days <= 0 {
+                    "within a day".to_string()
+                } else {
+                    format!("in {days} days")
+                },
+                expires.format("%Y-%m-%d")
+            ));
+        }
+    }
+    Ok(Checked {
+        warning: (!warnings.is_empty()).then(|| format!("⚠️  {}", warnings.join("; "))),
+        expires,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_scopes_and_expiry() {
+        let now = DateTime::parse_from_rfc3339("2025-01-01T00:00:00Z")
+            .unwrap()
+            .to_utc();
+        assert!(evaluate("ghp_x", Some("repo, gist"), None, now).is_err());
+        assert_eq!(
+            evaluate("ghp_x", Some("repo, read:packages"), None, now),
+            Ok(Checked::default())
+        );
+        let checked = evaluate("github_pat_x", None, Some("2025-01-04 12:00:00 UTC"), now).unwrap();
+        let warning = checked.warning.unwrap();
+        assert!(warning.contains("fine-grained"));
+        assert!(warning.contains("in 3 days"));
+        assert_eq!(
+            checked.expires.unwrap().to_rfc3339(),
+            "2025-01-04T12:00:00+00:00"
+        );
+    }
+}
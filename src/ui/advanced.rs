@@ -0,0 +1,81 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::app::advanced_form::AdvancedForm;
+use crate::compose_override::OVERRIDE_FILE;
+use crate::i18n::{tr, trf};
+use crate::ui::form::{button_line, field_lines, field_scroll};
+use crate::ui::{get_orange_accent, get_orange_color};
+
+pub struct AdvancedOptionsView<'a> {
+    pub form: &'a AdvancedForm,
+}
+
+pub fn render_advanced_options(frame: &mut Frame, view: &AdvancedOptionsView<'_>) {
+    let area = frame.area();
+    let form = &view.form.form;
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(6),
+            Constraint::Length(3),
+            Constraint::Length(2),
+        ])
+        .split(area);
+
+    let header = Paragraph::new(tr("advanced.title"))
+        .style(
+            Style::default()
+                .fg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent())),
+        )
+        .centered();
+    frame.render_widget(header, chunks[0]);
+
+    let fields = Paragraph::new(field_lines(form, " : "))
+        .scroll((field_scroll(form, chunks[1].height), 0))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title(tr("advanced.fields_title"))
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        );
+    frame.render_widget(fields, chunks[1]);
+
+    let (status_message, status_style) = if !form.error_message.is_empty() {
+        (form.error_message.clone(), Style::default().fg(Color::Red))
+    } else {
+        (
+            trf("advanced.hint", &[("file", OVERRIDE_FILE)]),
+            Style::default().fg(Color::Yellow),
+        )
+    };
+    frame.render_widget(
+        Paragraph::new(status_message).style(status_style).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(tr("advanced.status_title")),
+        ),
+        chunks[2],
+    );
+
+    let buttons = button_line(form, tr("advanced.keys"));
+    frame.render_widget(Paragraph::new(buttons).centered(), chunks[3]);
+}
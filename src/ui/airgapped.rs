@@ -0,0 +1,159 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+
+use crate::app::AirgappedSetup;
+use crate::i18n::{tr, trf};
+use crate::ui::{get_orange_accent, get_orange_color};
+
+/// Extraction and image loading of an offline install, before the menu.
+pub struct AirgappedSetupView<'a> {
+    pub setup: &'a AirgappedSetup,
+    pub logs: &'a [String],
+}
+
+pub fn render_airgapped_setup(frame: &mut Frame, view: &AirgappedSetupView<'_>) {
+    let setup = view.setup;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Min(6),
+            Constraint::Length(1),
+        ])
+        .split(frame.area());
+
+    let (title, title_color) = if setup.error.is_some() {
+        (tr("airgapped.failed"), Color::Red)
+    } else {
+        (tr("airgapped.title"), get_orange_color())
+    };
+    let title = Paragraph::new(title)
+        .style(
+            Style::default()
+                .fg(title_color)
+                .add_modifier(Modifier::BOLD),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent())),
+        )
+        .centered();
+    frame.render_widget(title, chunks[0]);
+
+    render_bar(frame, chunks[1], tr("airgapped.extracting"), setup.extract);
+    let image_title = match &setup.image {
+        Some((index, total, name)) => trf(
+            "airgapped.loading_image",
+            &[
+                ("index", &index.to_string()),
+                ("total", &total.to_string()),
+                ("name", name),
+            ],
+        ),
+        None => tr("airgapped.loading_images").to_string(),
+    };
+    render_bar(frame, chunks[2], &image_title, setup.image_load);
+
+    // The failure report goes last so it stays in view below the log tail.
+    let mut lines: Vec<Line> = view
+        .logs
+        .iter()
+        .map(|log| {
+            let style = if log.contains("❌") {
+                Style::default().fg(Color::Red)
+            } else if log.contains('⚠') {
+                Style::default().fg(Color::Yellow)
+            } else if log.contains('✓') {
+                Style::default().fg(Color::Green)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            Line::from(Span::styled(log.clone(), style))
+        })
+        .collect();
+    if let Some(error) = &setup.error {
+        lines.push(Line::from(""));
+        lines.extend(
+            error
+                .lines()
+                .map(|line| Line::from(line.to_string()).style(Style::default().fg(Color::Red))),
+        );
+    }
+    let visible = chunks[3].height.saturating_sub(2) as usize;
+    let scroll = lines.len().saturating_sub(visible) as u16;
+    let logs = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title(tr("airgapped.logs_title"))
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .wrap(Wrap { trim: false })
+        .scroll((scroll, 0));
+    frame.render_widget(logs, chunks[3]);
+
+    let help = if setup.error.is_some() {
+        tr("airgapped.keys_failed")
+    } else {
+        tr("airgapped.keys")
+    };
+    let help = Paragraph::new(help)
+        .style(Style::default().fg(Color::DarkGray))
+        .centered();
+    frame.render_widget(help, chunks[4]);
+}
+
+/// A byte progress bar in the style of the install screen; empty until the
+/// stage starts.
+fn render_bar(frame: &mut Frame, area: Rect, title: &str, progress: Option<(u64, u64)>) {
+    let bar_space = area.width.saturating_sub(24) as usize;
+    let fraction = match progress {
+        Some((done, total)) if total > 0 => (done as f64 / total as f64).min(1.0),
+        _ => 0.0,
+    };
+    let filled = ((bar_space as f64) * fraction).round() as usize;
+    let sizes = progress
+        .map(|(done, total)| {
+            format!(
+                " {:.0}/{:.0} MB",
+                done as f64 / 1_048_576.0,
+                total as f64 / 1_048_576.0
+            )
+        })
+        .unwrap_or_default();
+    let text = format!(
+        "[{}{}] {:.0}%{sizes}",
+        "█".repeat(filled),
+        "░".repeat(bar_space - filled),
+        fraction * 100.0
+    );
+    let widget = Paragraph::new(text)
+        .style(Style::default().fg(get_orange_color()))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title(title.to_string())
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .centered();
+    frame.render_widget(widget, area);
+}
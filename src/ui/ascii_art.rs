@@ -1,5 +1,7 @@
 use ratatui::style::Color;
 
+use crate::ui::theme;
+
 pub const ASCII_HEADER: &str = r#"
 
 ███╗░░██╗░██████╗░██████╗░██╗░░░██╗░██████╗████████╗░░░░░░██╗██████╗░███████╗███╗░░██╗████████╗██╗████████╗██╗░░░██╗
@@ -17,10 +19,12 @@ pub const ASCII_HEADER: &str = r#"
                         ╚═╝╚═════╝░╚══════╝╚═╝░░╚══╝░░░╚═╝░░░╚═╝░░░╚═╝░░░░░░╚═╝░░░
 "#;
 
+/// Accent colour of the active theme (orange on dark terminals).
 pub fn get_orange_color() -> Color {
-    Color::Rgb(255, 165, 0) // Orange color
+    theme::current().accent()
 }
 
+/// Darker accent for borders.
 pub fn get_orange_accent() -> Color {
-    Color::Rgb(255, 140, 0) // Darker orange for accent
+    theme::current().accent_dim()
 }
@@ -0,0 +1,86 @@
+use std::path::PathBuf;
+
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+};
+
+use crate::i18n::{tr, trf};
+use crate::ui::{get_orange_accent, get_orange_color};
+
+/// Backup archives to restore from, newest first.
+pub struct RestorePickerView<'a> {
+    pub backups: &'a [PathBuf],
+    pub selected: usize,
+    /// Enter was pressed once; the next Enter overwrites the database.
+    pub confirm: bool,
+}
+
+pub fn render_restore_picker(frame: &mut Frame, view: &RestorePickerView<'_>) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Min(5),
+            Constraint::Length(2),
+            Constraint::Length(1),
+        ])
+        .split(frame.area());
+
+    let items: Vec<ListItem> = view
+        .backups
+        .iter()
+        .map(|path| {
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let size = path.metadata().map(|m| m.len()).unwrap_or(0);
+            ListItem::new(trf(
+                "backup.entry",
+                &[
+                    ("name", &name),
+                    ("size", &format!("{:.1}", size as f64 / 1_048_576.0)),
+                ],
+            ))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title(tr("backup.restore_title"))
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▶ ");
+    let mut state = ListState::default().with_selected(Some(view.selected));
+    frame.render_stateful_widget(list, chunks[0], &mut state);
+
+    let warning = if view.confirm {
+        Line::from(tr("backup.confirm"))
+            .style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+    } else {
+        Line::from(tr("backup.notice")).style(Style::default().fg(Color::Yellow))
+    };
+    frame.render_widget(Paragraph::new(warning).centered(), chunks[1]);
+
+    let help = Paragraph::new(tr("backup.keys"))
+        .style(Style::default().fg(Color::DarkGray))
+        .centered();
+    frame.render_widget(help, chunks[2]);
+}
@@ -0,0 +1,45 @@
+use ratatui::{
+    Frame,
+    layout::Alignment,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+use crate::app::task::Busy;
+use crate::i18n::tr;
+use crate::ui::{centered_rect, get_orange_accent, get_orange_color};
+
+pub struct BusyView<'a> {
+    pub busy: &'a Busy,
+}
+
+/// Popup drawn over the current screen while a background task runs.
+pub fn render_busy(frame: &mut Frame, view: &BusyView<'_>) {
+    let width = (view.busy.label.chars().count() as u16 + 16).clamp(40, 76);
+    let area = centered_rect(width, 5, frame.area());
+    frame.render_widget(Clear, area);
+
+    let lines = vec![
+        Line::from(vec![
+            Span::styled(
+                format!("{} ", view.busy.spinner()),
+                Style::default()
+                    .fg(get_orange_color())
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(view.busy.label.clone()),
+        ]),
+        Line::from(Span::styled(
+            format!("{}s", view.busy.started.elapsed().as_secs()),
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    let popup = Paragraph::new(lines).alignment(Alignment::Center).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(get_orange_accent()))
+            .title_bottom(Line::from(tr("busy.cancel")).alignment(Alignment::Center)),
+    );
+    frame.render_widget(popup, area);
+}
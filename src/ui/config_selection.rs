@@ -5,13 +5,35 @@ use ratatui::{
     text::{Line, Span},
     widgets::{Block, Borders, Paragraph, Wrap},
 };
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
+use crate::i18n::{tr, trf};
 use crate::templates::ConfigTemplate;
 use crate::ui::{get_orange_accent, get_orange_color};
 
+/// Cards per row of the template grid.
+pub const GRID_COLUMNS: usize = 4;
+
 pub struct ConfigSelectionView<'a> {
-    pub templates: &'a [ConfigTemplate],
+    /// Templates matching `filter`, in catalog order.
+    pub templates: &'a [&'a ConfigTemplate],
+    /// Size of the whole catalog.
+    pub total: usize,
+    pub filter: &'a str,
+    /// Index into `templates`.
     pub selected_index: usize,
+    /// Template files that failed to load.
+    pub warnings: &'a [String],
+}
+
+/// Height of one card.
+const CARD_HEIGHT: u16 = 3;
+
+/// First grid row shown: the grid pages by whole screens, so the page the
+/// selected card is on.
+fn first_row(selected_row: usize, visible_rows: usize) -> usize {
+    selected_row / visible_rows.max(1) * visible_rows.max(1)
 }
 
 pub fn render_config_selection(frame: &mut Frame, view: &ConfigSelectionView<'_>) {
@@ -23,17 +45,41 @@ pub fn render_config_selection(frame: &mut Frame, view: &ConfigSelectionView<'_>
         .constraints([
             Constraint::Length(3),
             Constraint::Min(10),
-            Constraint::Length(6),
+            Constraint::Length(8),
         ])
         .split(area);
 
-    // Header
-    let title = Paragraph::new("🧩 Choose a configuration template")
-        .style(
-            Style::default()
-                .fg(get_orange_color())
-                .add_modifier(Modifier::BOLD),
-        )
+    // Header: the filter typed so far
+    let header = if view.filter.is_empty() {
+        Line::from(vec![
+            Span::styled(
+                tr("config_selection.title"),
+                Style::default()
+                    .fg(get_orange_color())
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                tr("config_selection.type_to_filter"),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ])
+    } else {
+        Line::from(vec![
+            Span::styled(
+                tr("config_selection.filter"),
+                Style::default().fg(Color::Yellow),
+            ),
+            Span::styled(
+                format!("{}▏", view.filter),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                tr("config_selection.filter_keys"),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ])
+    };
+    let title = Paragraph::new(header)
         .block(
             Block::default()
                 .borders(Borders::ALL)
@@ -44,46 +90,89 @@ pub fn render_config_selection(frame: &mut Frame, view: &ConfigSelectionView<'_>
 
     // Grid layout for providers
     let grid_area = chunks[1];
+    let cols = GRID_COLUMNS;
+    let card_width = (grid_area.width.saturating_sub(2)) / cols as u16; // -2 for borders
 
-    // Calculate grid dimensions
-    let cols = 4; // 4 columns
-    let total_items = view.templates.len();
-    let _rows = (total_items + cols - 1) / cols; // Ceiling division
+    // Inner area for cards (inside the border)
+    let inner_area = Rect {
+        x: grid_area.x + 1,
+        y: grid_area.y + 1,
+        width: grid_area.width.saturating_sub(2),
+        height: grid_area.height.saturating_sub(2),
+    };
 
-    // Calculate card dimensions
-    let card_width = (grid_area.width.saturating_sub(2)) / cols as u16; // -2 for borders
-    let card_height = 3; // Fixed height for each card
+    let count = view.templates.len();
+    let visible_rows = (inner_area.height / CARD_HEIGHT).max(1) as usize;
+    let pages = count.div_ceil(cols).div_ceil(visible_rows).max(1);
+    let first = first_row(view.selected_index / cols, visible_rows);
+    let shown = first * cols..((first + visible_rows) * cols).min(count);
 
-    // Render grid
+    let mut grid_title = if count == view.total {
+        trf(
+            "config_selection.templates",
+            &[("count", &count.to_string())],
+        )
+    } else {
+        trf(
+            "config_selection.templates_filtered",
+            &[
+                ("count", &count.to_string()),
+                ("total", &view.total.to_string()),
+            ],
+        )
+    };
+    if pages > 1 {
+        grid_title.push_str(&trf(
+            "config_selection.page",
+            &[
+                ("page", &(first / visible_rows + 1).to_string()),
+                ("pages", &pages.to_string()),
+            ],
+        ));
+    }
+    let mut hidden = Vec::new();
+    if shown.start > 0 {
+        hidden.push(trf(
+            "config_selection.more_above",
+            &[("count", &shown.start.to_string())],
+        ));
+    }
+    if shown.end < count {
+        hidden.push(trf(
+            "config_selection.more_below",
+            &[("count", &(count - shown.end).to_string())],
+        ));
+    }
     let grid_block = Block::default()
         .borders(Borders::ALL)
         .border_style(Style::default().fg(get_orange_accent()))
-        .title("Model Providers")
+        .title(grid_title)
         .title_style(
             Style::default()
                 .fg(get_orange_color())
                 .add_modifier(Modifier::BOLD),
+        )
+        .title_bottom(
+            Line::styled(hidden.join("  "), Style::default().fg(Color::Yellow)).right_aligned(),
         );
     frame.render_widget(grid_block, grid_area);
 
-    // Inner area for cards (inside the border)
-    let inner_area = Rect {
-        x: grid_area.x + 1,
-        y: grid_area.y + 1,
-        width: grid_area.width.saturating_sub(2),
-        height: grid_area.height.saturating_sub(2),
-    };
-
-    // Render each card
-    for (index, template) in view.templates.iter().enumerate() {
-        let row = index / cols;
+    // Render the cards of the page
+    for (index, template) in view
+        .templates
+        .iter()
+        .enumerate()
+        .take(shown.end)
+        .skip(shown.start)
+    {
+        let row = index / cols - first;
         let col = index % cols;
 
         let x = inner_area.x + (col as u16 * card_width);
-        let y = inner_area.y + (row as u16 * card_height);
+        let y = inner_area.y + (row as u16 * CARD_HEIGHT);
 
-        // Skip if card would be outside visible area
-        if y + card_height > inner_area.y + inner_area.height {
+        // Only when the grid is shorter than one card.
+        if y + CARD_HEIGHT > inner_area.y + inner_area.height {
             continue;
         }
 
@@ -91,7 +180,7 @@ pub fn render_config_selection(frame: &mut Frame, view: &ConfigSelectionView<'_>
             x,
             y,
             width: card_width.saturating_sub(1), // -1 for spacing
-            height: card_height,
+            height: CARD_HEIGHT,
         };
 
         let is_selected = index == view.selected_index;
@@ -113,8 +202,15 @@ pub fn render_config_selection(frame: &mut Frame, view: &ConfigSelectionView<'_>
 
         // Truncate name if too long
         let max_name_len = card_width.saturating_sub(4) as usize;
-        let display_name = if template.name.len() > max_name_len {
-            format!("{}…", &template.name[..max_name_len.saturating_sub(1)])
+        let display_name = if template.name.width() > max_name_len {
+            let mut shortened = String::new();
+            for grapheme in template.name.graphemes(true) {
+                if shortened.width() + grapheme.width() >= max_name_len {
+                    break;
+                }
+                shortened.push_str(grapheme);
+            }
+            format!("{shortened}…")
         } else {
             template.name.to_string()
         };
@@ -132,45 +228,85 @@ pub fn render_config_selection(frame: &mut Frame, view: &ConfigSelectionView<'_>
     }
 
     // Details panel
-    let detail_lines = if let Some(template) = view.templates.get(view.selected_index) {
+    let mut detail_lines = if let Some(template) = view.templates.get(view.selected_index).copied()
+    {
         vec![
             Line::from(vec![
-                Span::styled("Selected: ", Style::default().fg(Color::Yellow)),
                 Span::styled(
-                    template.name,
+                    tr("config_selection.selected"),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::styled(
+                    template.name.as_str(),
                     Style::default()
                         .fg(Color::White)
                         .add_modifier(Modifier::BOLD),
                 ),
             ]),
             Line::from(vec![
-                Span::styled("Description: ", Style::default().fg(Color::Yellow)),
-                Span::styled(template.description, Style::default().fg(Color::Gray)),
+                Span::styled(
+                    tr("config_selection.description"),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::styled(
+                    template.description.as_str(),
+                    Style::default().fg(Color::Gray),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled(
+                    tr("config_selection.writes"),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::raw(format!(
+                    "{} ({})",
+                    template.output.display(),
+                    if template.user {
+                        format!("templates/{}", template.key)
+                    } else {
+                        tr("config_selection.built_in").to_string()
+                    }
+                )),
             ]),
             Line::from(""),
             Line::from(vec![
-                Span::styled("Navigation: ", Style::default().fg(Color::Yellow)),
-                Span::raw("←→↑↓ to move | "),
+                Span::styled(
+                    tr("config_selection.navigation"),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::raw(tr("config_selection.nav_move")),
                 Span::styled("Enter", Style::default().fg(get_orange_color())),
-                Span::raw(" to select | "),
+                Span::raw(tr("config_selection.nav_select")),
                 Span::styled("Esc", Style::default().fg(Color::Red)),
-                Span::raw(" to go back"),
+                Span::raw(tr("config_selection.nav_back")),
             ]),
         ]
+    } else if !view.filter.is_empty() {
+        vec![
+            Line::from(trf("config_selection.no_match", &[("filter", view.filter)])),
+            Line::from(""),
+            Line::from(tr("config_selection.no_match_keys")),
+        ]
     } else {
         vec![
-            Line::from("No templates available"),
+            Line::from(tr("config_selection.empty")),
             Line::from(""),
-            Line::from("Press Esc to go back, Ctrl+C to exit"),
+            Line::from(tr("config_selection.empty_keys")),
         ]
     };
+    for warning in view.warnings {
+        detail_lines.push(Line::from(Span::styled(
+            trf("config_selection.skipped", &[("warning", warning)]),
+            Style::default().fg(Color::Yellow),
+        )));
+    }
 
     let details = Paragraph::new(detail_lines)
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(get_orange_accent()))
-                .title("Details")
+                .title(tr("config_selection.details"))
                 .title_style(
                     Style::default()
                         .fg(get_orange_color())
@@ -1,21 +1,49 @@
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Paragraph},
 };
 
-use crate::app::MenuSelection;
+use crate::app::{MenuSelection, QuickAction};
+use crate::artifacts::{Origin, Summary};
+use crate::file_security::Report;
+use crate::i18n::{tr, trf};
+use crate::stack::{self, StackService};
 use crate::ui::{ASCII_HEADER, get_orange_accent, get_orange_color};
+use crate::update_check::UpdateStatus;
 
 pub struct ConfirmationView<'a> {
     pub cert_exists: bool,
     pub env_has_ip: bool,
+    /// Permissions of .env and the registry token files.
+    pub security: &'a Report,
     pub menu_selection: &'a MenuSelection,
     pub menu_options: &'a [MenuSelection],
+    /// Hotkeys available from this screen, shown in the bottom bar.
+    pub quick_actions: &'a [QuickAction],
     /// True when running as airgapped binary (offline mode)
     pub airgapped: bool,
+    /// Result of the last scheduled update check, if one ran.
+    pub update_status: Option<&'a UpdateStatus>,
+    /// Expiry of the GHCR token in use, when GitHub reported one.
+    pub token_expires: Option<DateTime<Utc>>,
+    pub project_dir: &'a Path,
+    /// Set with --project-dir or the picker rather than discovered.
+    pub project_dir_explicit: bool,
+    /// `--remote` destination, when installing onto another host.
+    pub remote: Option<&'a str>,
+    /// Daemon picked under Docker daemon or set by DOCKER_HOST, if any.
+    pub docker_daemon: Option<&'a str>,
+    /// Services this install runs, listed once it is ready.
+    pub services: &'a [&'a StackService],
+    /// Files summary shown instead of the checklist while open.
+    pub artifacts: Option<&'a Summary>,
+    pub artifacts_scroll: u16,
 }
 
 pub fn render_confirmation(frame: &mut Frame, view: &ConfirmationView<'_>) {
@@ -25,10 +53,11 @@ pub fn render_confirmation(frame: &mut Frame, view: &ConfirmationView<'_>) {
         .direction(Direction::Vertical)
         .margin(1)
         .constraints([
-            Constraint::Length(7), // ASCII header
-            Constraint::Min(10),   // status / checklist
-            Constraint::Length(6), // menu
-            Constraint::Length(2), // help
+            Constraint::Length(7),                                  // ASCII header
+            Constraint::Min(10),                                    // status / checklist
+            Constraint::Length(view.menu_options.len() as u16 + 3), // menu
+            Constraint::Length(1),                                  // quick actions
+            Constraint::Length(1),                                  // help
         ])
         .split(area);
 
@@ -51,21 +80,237 @@ pub fn render_confirmation(frame: &mut Frame, view: &ConfirmationView<'_>) {
         .centered();
     frame.render_widget(header, chunks[0]);
 
-    // ── Status / Checklist ─────────────────────────────────────────────────
+    // ── Status / Checklist, or the files summary ───────────────────────────
+    if let Some(summary) = view.artifacts {
+        let panel = Paragraph::new(artifact_lines(summary))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(get_orange_accent()))
+                    .title(tr("artifacts.title"))
+                    .title_style(
+                        Style::default()
+                            .fg(get_orange_color())
+                            .add_modifier(Modifier::BOLD),
+                    ),
+            )
+            .scroll((view.artifacts_scroll, 0));
+        frame.render_widget(panel, chunks[1]);
+    } else {
+        render_status(frame, view, chunks[1]);
+    }
+
+    // ── Menu ───────────────────────────────────────────────────────────────
+    let mut menu_lines = vec![Line::from("")];
+
+    for option in view.menu_options {
+        let (label, fg_color, highlight_color) = match option {
+            MenuSelection::GenerateSsl => (
+                tr("menu.generate_ssl"),
+                get_orange_color(),
+                get_orange_color(),
+            ),
+            MenuSelection::CheckUpdates => (tr("menu.check_updates"), Color::Cyan, Color::Cyan),
+            MenuSelection::UpdateToken => (tr("menu.update_token"), Color::Yellow, Color::Yellow),
+            MenuSelection::Registries => (tr("menu.registries"), Color::Yellow, Color::Yellow),
+            MenuSelection::ForgetCredentials => {
+                (tr("menu.forget_credentials"), Color::Red, Color::Red)
+            }
+            MenuSelection::ProxySettings => {
+                (tr("menu.proxy_settings"), Color::Magenta, Color::Magenta)
+            }
+            MenuSelection::EditConfig => (tr("menu.edit_config"), Color::Cyan, Color::Cyan),
+            MenuSelection::EditYamlConfig => (tr("menu.edit_yaml"), Color::Cyan, Color::Cyan),
+            MenuSelection::RewriteBundle => (tr("menu.rewrite_bundle"), Color::Cyan, Color::Cyan),
+            MenuSelection::GenerateFromTemplate => {
+                (tr("menu.from_template"), Color::Cyan, Color::Cyan)
+            }
+            MenuSelection::ProjectDir => (tr("menu.project_dir"), Color::Cyan, Color::Cyan),
+            MenuSelection::DockerContext => (tr("menu.docker_context"), Color::Cyan, Color::Cyan),
+            MenuSelection::Services => (tr("menu.services"), Color::Magenta, Color::Magenta),
+            MenuSelection::ResourcePresets => {
+                (tr("menu.resource_presets"), Color::Magenta, Color::Magenta)
+            }
+            MenuSelection::AdvancedOptions => {
+                (tr("menu.advanced_options"), Color::Magenta, Color::Magenta)
+            }
+            MenuSelection::MigrateKeycloak => {
+                (tr("menu.migrate_keycloak"), Color::Blue, Color::Blue)
+            }
+            MenuSelection::RotateSecrets => {
+                (tr("menu.rotate_secrets"), Color::Yellow, Color::Yellow)
+            }
+            MenuSelection::ExternalDatabase => {
+                (tr("menu.external_database"), Color::Blue, Color::Blue)
+            }
+            MenuSelection::ConfigureLdap => (tr("menu.configure_ldap"), Color::Blue, Color::Blue),
+            MenuSelection::RegisterClient => (tr("menu.register_client"), Color::Blue, Color::Blue),
+            MenuSelection::ConfigureSmtp => (tr("menu.configure_smtp"), Color::Blue, Color::Blue),
+            MenuSelection::BackupDatabase => (tr("menu.backup_database"), Color::Cyan, Color::Cyan),
+            MenuSelection::RestoreDatabase => {
+                (tr("menu.restore_database"), Color::Yellow, Color::Yellow)
+            }
+            MenuSelection::InstallSystemd => (tr("menu.install_systemd"), Color::Blue, Color::Blue),
+            MenuSelection::Proceed => (tr("menu.proceed"), Color::Green, Color::Green),
+            MenuSelection::Cancel => (tr("menu.cancel"), Color::Red, Color::Red),
+        };
+
+        let style = if option == view.menu_selection {
+            Style::default()
+                .fg(Color::Black)
+                .bg(highlight_color)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(fg_color)
+        };
+
+        menu_lines.push(Line::from(Span::styled(format!("  ▶  {}", label), style)));
+    }
+
+    let menu = Paragraph::new(menu_lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title(tr("menu.title"))
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .centered();
+    frame.render_widget(menu, chunks[2]);
+
+    // ── Quick actions ──────────────────────────────────────────────────────
+    let mut quick_spans = Vec::new();
+    for action in view.quick_actions {
+        quick_spans.push(Span::styled(
+            format!(" {} ", action.key().to_ascii_uppercase()),
+            Style::default()
+                .fg(Color::Black)
+                .bg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        ));
+        quick_spans.push(Span::styled(
+            format!(" {}   ", action.label()),
+            Style::default().fg(Color::White),
+        ));
+    }
+    frame.render_widget(
+        Paragraph::new(Line::from(quick_spans)).centered(),
+        chunks[3],
+    );
+
+    let help = Paragraph::new(tr("menu.help"))
+        .style(Style::default().fg(Color::DarkGray))
+        .centered();
+    frame.render_widget(help, chunks[4]);
+}
+
+/// The project, notices and setup checklist.
+fn render_status(frame: &mut Frame, view: &ConfirmationView<'_>, area: Rect) {
     let all_ready = view.cert_exists && view.env_has_ip;
 
     let mut content_lines = vec![Line::from("")];
 
+    let mut project = vec![Span::styled(
+        trf(
+            "status.project",
+            &[("path", &view.project_dir.display().to_string())],
+        ),
+        Style::default()
+            .fg(get_orange_color())
+            .add_modifier(Modifier::BOLD),
+    )];
+    let installed = view
+        .project_dir
+        .join(stack::current().compose_file())
+        .is_file();
+    if !installed {
+        project.push(Span::styled(
+            format!("  {}", tr("status.project_new")),
+            Style::default().fg(Color::Yellow),
+        ));
+    } else if !view.project_dir_explicit {
+        project.push(Span::styled(
+            format!("  {}", tr("status.project_found")),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    content_lines.push(Line::from(project));
+    if let Some(host) = view.remote {
+        content_lines.push(Line::from(Span::styled(
+            trf("status.remote", &[("host", host)]),
+            Style::default().fg(Color::Cyan),
+        )));
+    } else if let Some(daemon) = view.docker_daemon {
+        content_lines.push(Line::from(Span::styled(
+            trf("status.docker_daemon", &[("daemon", daemon)]),
+            Style::default().fg(Color::Cyan),
+        )));
+    }
+    content_lines.push(Line::from(""));
+
     if view.airgapped {
         content_lines.push(Line::from(Span::styled(
-            "🔒 Offline / Airgapped mode — images from embedded payload only",
+            tr("status.airgapped"),
             Style::default().fg(Color::Cyan),
         )));
         content_lines.push(Line::from(""));
     }
 
+    if let Some(status) = view.update_status
+        && !status.available.is_empty()
+    {
+        let names: Vec<&str> = status.available.iter().map(|u| u.name.as_str()).collect();
+        content_lines.push(Line::from(Span::styled(
+            trf(
+                "status.updates_available",
+                &[
+                    ("count", &status.available.len().to_string()),
+                    ("names", &names.join(", ")),
+                    (
+                        "when",
+                        &status
+                            .checked_at
+                            .with_timezone(&chrono::Local)
+                            .format("%Y-%m-%d %H:%M")
+                            .to_string(),
+                    ),
+                ],
+            ),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )));
+        content_lines.push(Line::from(""));
+    }
+
+    if let Some(expires) = view.token_expires {
+        let days = (expires - Utc::now()).num_days();
+        let date = expires.format("%Y-%m-%d").to_string();
+        let (text, color) = if expires <= Utc::now() {
+            (trf("status.token_expired", &[("date", &date)]), Color::Red)
+        } else {
+            (
+                trf(
+                    "status.token_expires",
+                    &[("days", &days.to_string()), ("date", &date)],
+                ),
+                if days < 7 {
+                    Color::Yellow
+                } else {
+                    Color::DarkGray
+                },
+            )
+        };
+        content_lines.push(Line::from(Span::styled(text, Style::default().fg(color))));
+        content_lines.push(Line::from(""));
+    }
+
     content_lines.push(Line::from(Span::styled(
-        "Setup Checklist:",
+        tr("status.checklist"),
         Style::default().fg(if all_ready {
             Color::Green
         } else {
@@ -84,12 +329,12 @@ pub fn render_confirmation(frame: &mut Frame, view: &ConfirmationView<'_>) {
     content_lines.push(Line::from(vec![
         Span::raw("  "),
         Span::styled(cert_icon, Style::default().fg(cert_color)),
-        Span::raw("  SSL Certificate  "),
+        Span::raw(format!("  {:<17}", tr("status.ssl_cert"))),
         Span::styled(
             if view.cert_exists {
-                "(certs/server.crt + server.key)"
+                tr("status.cert_present")
             } else {
-                "(missing — generate below)"
+                tr("status.missing")
             },
             Style::default().fg(if view.cert_exists {
                 Color::DarkGray
@@ -112,9 +357,9 @@ pub fn render_confirmation(frame: &mut Frame, view: &ConfirmationView<'_>) {
         Span::raw("  SERVER_IP         "),
         Span::styled(
             if view.env_has_ip {
-                "(set in .env)"
+                tr("status.ip_present")
             } else {
-                "(missing — generate below)"
+                tr("status.missing")
             },
             Style::default().fg(if view.env_has_ip {
                 Color::DarkGray
@@ -124,39 +369,66 @@ pub fn render_confirmation(frame: &mut Frame, view: &ConfirmationView<'_>) {
         ),
     ]));
 
+    // Security check row, with each problem under it
+    let secure = view.security.findings.is_empty();
+    content_lines.push(Line::from(vec![
+        Span::raw("  "),
+        Span::styled(
+            if secure { "✓" } else { "⚠" },
+            Style::default().fg(if secure { Color::Green } else { Color::Yellow }),
+        ),
+        Span::raw(format!("  {:<17}", tr("status.security_check"))),
+        Span::styled(
+            if secure {
+                trf(
+                    "status.security_ok",
+                    &[("count", &view.security.checked.len().to_string())],
+                )
+            } else {
+                trf(
+                    "status.security_issues",
+                    &[("count", &view.security.findings.len().to_string())],
+                )
+            },
+            Style::default().fg(if secure {
+                Color::DarkGray
+            } else {
+                Color::Yellow
+            }),
+        ),
+    ]));
+    for finding in &view.security.findings {
+        content_lines.push(Line::from(Span::styled(
+            format!("     • {finding}"),
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+
     content_lines.push(Line::from(""));
 
     if all_ready {
         content_lines.push(Line::from(Span::styled(
-            "✅ All requirements met — ready to install!",
+            tr("status.ready"),
             Style::default()
                 .fg(Color::Green)
                 .add_modifier(Modifier::BOLD),
         )));
         content_lines.push(Line::from(""));
-        content_lines.push(Line::from("Services to be started:"));
-        content_lines.push(Line::from(Span::styled(
-            "  • identity-db     (PostgreSQL 16 — port 5436)",
-            Style::default().fg(Color::White),
-        )));
-        content_lines.push(Line::from(Span::styled(
-            "  • identity        (Keycloak — port 8008)",
-            Style::default().fg(Color::White),
-        )));
-        content_lines.push(Line::from(Span::styled(
-            "  • identity-caddy  (HTTPS proxy — port 8008)",
-            Style::default().fg(Color::White),
-        )));
+        content_lines.push(Line::from(tr("status.services")));
+        for service in view.services {
+            content_lines.push(Line::from(Span::styled(
+                format!("  • {:<15} ({})", service.container, service.description),
+                Style::default().fg(Color::White),
+            )));
+        }
     } else {
         content_lines.push(Line::from(Span::styled(
-            "⚠️  Some requirements are missing.",
+            tr("status.not_ready"),
             Style::default()
                 .fg(Color::Yellow)
                 .add_modifier(Modifier::BOLD),
         )));
-        content_lines.push(Line::from(
-            "Generate the SSL cert & .env before proceeding.",
-        ));
+        content_lines.push(Line::from(tr("status.not_ready_hint")));
     }
 
     let content = Paragraph::new(content_lines)
@@ -164,7 +436,7 @@ pub fn render_confirmation(frame: &mut Frame, view: &ConfirmationView<'_>) {
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(get_orange_accent()))
-                .title(" Status ")
+                .title(tr("status.title"))
                 .title_style(
                     Style::default()
                         .fg(get_orange_color())
@@ -172,53 +444,68 @@ pub fn render_confirmation(frame: &mut Frame, view: &ConfirmationView<'_>) {
                 ),
         )
         .centered();
-    frame.render_widget(content, chunks[1]);
-
-    // ── Menu ───────────────────────────────────────────────────────────────
-    let mut menu_lines = vec![Line::from("")];
-
-    for option in view.menu_options {
-        let (label, fg_color, highlight_color) = match option {
-            MenuSelection::GenerateSsl => (
-                "Generate SSL Cert & write .env",
-                get_orange_color(),
-                get_orange_color(),
-            ),
-            MenuSelection::CheckUpdates => ("Check for updates", Color::Cyan, Color::Cyan),
-            MenuSelection::UpdateToken => ("Update GHCR token", Color::Yellow, Color::Yellow),
-            MenuSelection::Proceed => ("Proceed with installation", Color::Green, Color::Green),
-            MenuSelection::Cancel => ("Cancel", Color::Red, Color::Red),
-        };
+    frame.render_widget(content, area);
+}
 
-        let style = if option == view.menu_selection {
-            Style::default()
-                .fg(Color::Black)
-                .bg(highlight_color)
-                .add_modifier(Modifier::BOLD)
-        } else {
-            Style::default().fg(fg_color)
+/// One line per file, then the .env keys.
+fn artifact_lines(summary: &Summary) -> Vec<Line<'static>> {
+    let width = summary
+        .files
+        .iter()
+        .map(|file| file.path.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut lines = vec![Line::from("")];
+    for file in &summary.files {
+        let (origin, color) = match &file.origin {
+            Origin::Bundle { current: true } => {
+                (tr("artifacts.bundle").to_string(), Color::DarkGray)
+            }
+            Origin::Bundle { current: false } => {
+                (tr("artifacts.bundle_edited").to_string(), Color::Yellow)
+            }
+            Origin::Template(name) => (trf("artifacts.template", &[("name", name)]), Color::Cyan),
+            Origin::Installer => (tr("artifacts.installer").to_string(), Color::DarkGray),
         };
-
-        menu_lines.push(Line::from(Span::styled(format!("  ▶  {}", label), style)));
+        let mut spans = vec![Span::raw("  ")];
+        match &file.file {
+            Some((bytes, checksum)) => {
+                spans.push(Span::styled("✓", Style::default().fg(Color::Green)));
+                spans.push(Span::raw(format!("  {:<width$}", file.path)));
+                spans.push(Span::styled(
+                    format!("  {bytes:>7} B  sha256 {checksum}  "),
+                    Style::default().fg(Color::DarkGray),
+                ));
+                spans.push(Span::styled(origin, Style::default().fg(color)));
+            }
+            None => {
+                spans.push(Span::styled("✗", Style::default().fg(Color::DarkGray)));
+                spans.push(Span::styled(
+                    format!("  {:<width$}  {}", file.path, tr("artifacts.missing")),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+        }
+        lines.push(Line::from(spans));
     }
 
-    let menu = Paragraph::new(menu_lines)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(get_orange_accent()))
-                .title(" Menu ")
-                .title_style(
-                    Style::default()
-                        .fg(get_orange_color())
-                        .add_modifier(Modifier::BOLD),
-                ),
-        )
-        .centered();
-    frame.render_widget(menu, chunks[2]);
-
-    let help = Paragraph::new("Use ↑↓ to navigate, Enter to select, Esc to cancel")
-        .style(Style::default().fg(Color::DarkGray))
-        .centered();
-    frame.render_widget(help, chunks[3]);
+    lines.push(Line::from(""));
+    if summary.env.is_empty() {
+        lines.push(Line::from(tr("artifacts.no_env")));
+        return lines;
+    }
+    lines.push(Line::from(tr("artifacts.env")));
+    let width = summary
+        .env
+        .iter()
+        .map(|(key, _)| key.len())
+        .max()
+        .unwrap_or(0);
+    for (key, value) in &summary.env {
+        lines.push(Line::from(vec![
+            Span::raw(format!("  {key:<width$} = ")),
+            Span::styled(value.clone(), Style::default().fg(Color::DarkGray)),
+        ]));
+    }
+    lines
 }
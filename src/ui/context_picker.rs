@@ -0,0 +1,105 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+};
+
+use crate::app::context_picker::ContextPicker;
+use crate::i18n::{tr, trf};
+use crate::ui::{get_orange_accent, get_orange_color};
+
+pub struct ContextPickerView<'a> {
+    pub picker: &'a ContextPicker,
+}
+
+pub fn render_context_picker(frame: &mut Frame, view: &ContextPickerView<'_>) {
+    let picker = view.picker;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Min(5),
+            Constraint::Length(2),
+            Constraint::Length(1),
+        ])
+        .split(frame.area());
+
+    let items: Vec<ListItem> = picker
+        .contexts
+        .iter()
+        .map(|context| {
+            let marker = if picker.in_use(context) { "● " } else { "  " };
+            let mut spans = vec![
+                Span::styled(
+                    format!("{marker}{:<16}", context.name),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(format!(" {}", context.docker_endpoint)),
+            ];
+            if !context.error.is_empty() {
+                spans.push(Span::styled(
+                    format!("  ({})", context.error),
+                    Style::default().fg(Color::Red),
+                ));
+            } else if !context.description.is_empty() {
+                spans.push(Span::styled(
+                    format!("  {}", context.description),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let title = match &picker.chosen {
+        Some(name) => trf("context_picker.title", &[("name", name)]),
+        None => tr("context_picker.title_default").to_string(),
+    };
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title(title)
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▶ ");
+    let mut state = ListState::default().with_selected(Some(picker.selected));
+    frame.render_stateful_widget(list, chunks[0], &mut state);
+
+    let status = match (&picker.error, &picker.check) {
+        (Some(error), _) => {
+            Line::from(format!("❌ {error}")).style(Style::default().fg(Color::Red))
+        }
+        (None, Some(Ok(version))) => {
+            Line::from(trf("context_picker.reachable", &[("version", version)]))
+                .style(Style::default().fg(Color::Green))
+        }
+        (None, Some(Err(e))) => Line::from(trf(
+            "context_picker.unreachable",
+            &[("error", &e.to_string())],
+        ))
+        .style(Style::default().fg(Color::Yellow)),
+        (None, None) => {
+            Line::from(tr("context_picker.marker")).style(Style::default().fg(Color::DarkGray))
+        }
+    };
+    frame.render_widget(Paragraph::new(status).centered(), chunks[1]);
+
+    let help = Paragraph::new(tr("context_picker.keys"))
+        .style(Style::default().fg(Color::DarkGray))
+        .centered();
+    frame.render_widget(help, chunks[2]);
+}
@@ -0,0 +1,81 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+};
+
+use crate::app::dir_picker::DirPicker;
+use crate::i18n::{tr, trf};
+use crate::stack;
+use crate::ui::{get_orange_accent, get_orange_color};
+
+pub struct DirPickerView<'a> {
+    pub picker: &'a DirPicker,
+}
+
+pub fn render_dir_picker(frame: &mut Frame, view: &DirPickerView<'_>) {
+    let picker = view.picker;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Min(5),
+            Constraint::Length(2),
+            Constraint::Length(1),
+        ])
+        .split(frame.area());
+
+    let items: Vec<ListItem> = picker
+        .entries
+        .iter()
+        .map(|name| {
+            if picker.has_install(Some(name)) {
+                ListItem::new(format!("📦 {name}/")).style(Style::default().fg(Color::Green))
+            } else {
+                ListItem::new(format!("   {name}/"))
+            }
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title(format!(" {} ", picker.dir.display()))
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▶ ");
+    let mut state = ListState::default().with_selected(Some(picker.selected));
+    frame.render_stateful_widget(list, chunks[0], &mut state);
+
+    let status = if let Some(error) = &picker.error {
+        Line::from(error.clone()).style(Style::default().fg(Color::Red))
+    } else if picker.has_install(None) {
+        Line::from(trf(
+            "dir_picker.has_install",
+            &[("file", stack::current().compose_file())],
+        ))
+        .style(Style::default().fg(Color::Green))
+    } else {
+        Line::from(tr("dir_picker.no_install")).style(Style::default().fg(Color::Yellow))
+    };
+    frame.render_widget(Paragraph::new(status).centered(), chunks[1]);
+
+    let help = Paragraph::new(tr("dir_picker.keys"))
+        .style(Style::default().fg(Color::DarkGray))
+        .centered();
+    frame.render_widget(help, chunks[2]);
+}
@@ -0,0 +1,84 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::app::env_form::EnvForm;
+use crate::i18n::{tr, trf};
+use crate::ui::form::{button_line, field_lines, field_scroll};
+use crate::ui::{get_orange_accent, get_orange_color};
+
+pub struct EnvEditorView<'a> {
+    pub form: &'a EnvForm,
+}
+
+pub fn render_env_editor(frame: &mut Frame, view: &EnvEditorView<'_>) {
+    let area = frame.area();
+    let form = &view.form.form;
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(6),
+            Constraint::Length(3),
+            Constraint::Length(2),
+        ])
+        .split(area);
+
+    let header = Paragraph::new(tr("env_editor.title"))
+        .style(
+            Style::default()
+                .fg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent())),
+        )
+        .centered();
+    frame.render_widget(header, chunks[0]);
+
+    // Secrets stay masked until Ctrl+R reveals them.
+    let body = if form.fields.is_empty() {
+        Paragraph::new(tr("env_editor.empty"))
+    } else {
+        Paragraph::new(field_lines(form, " = ")).scroll((field_scroll(form, chunks[1].height), 0))
+    };
+    let form_block = body.block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(get_orange_accent()))
+            .title(trf(
+                "env_editor.keys_count",
+                &[("count", &form.fields.len().to_string())],
+            ))
+            .title_style(
+                Style::default()
+                    .fg(get_orange_color())
+                    .add_modifier(Modifier::BOLD),
+            ),
+    );
+    frame.render_widget(form_block, chunks[1]);
+
+    let (status_message, status_style) = if !form.error_message.is_empty() {
+        (form.error_message.as_str(), Style::default().fg(Color::Red))
+    } else {
+        (tr("env_editor.hint"), Style::default().fg(Color::Yellow))
+    };
+    frame.render_widget(
+        Paragraph::new(status_message).style(status_style).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(tr("env_editor.status")),
+        ),
+        chunks[2],
+    );
+
+    let buttons = button_line(form, tr("env_editor.keys"));
+    frame.render_widget(Paragraph::new(buttons).centered(), chunks[3]);
+}
@@ -0,0 +1,104 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+};
+
+use crate::app::env_review::EnvReview;
+use crate::env_file::{self, ChangeKind};
+use crate::i18n::tr;
+use crate::ui::{get_orange_accent, get_orange_color};
+
+pub struct EnvReviewView<'a> {
+    pub review: &'a EnvReview,
+}
+
+pub fn render_env_review(frame: &mut Frame, view: &EnvReviewView<'_>) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(6),
+            Constraint::Length(2),
+        ])
+        .split(frame.area());
+
+    let header = Paragraph::new(tr("env_review.title"))
+        .style(
+            Style::default()
+                .fg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent())),
+        )
+        .centered();
+    frame.render_widget(header, chunks[0]);
+
+    let show = |key: &str, value: &Option<String>| match value {
+        None => "—".to_string(),
+        Some(_) if env_file::is_secret_key(key) => "••••••".to_string(),
+        Some(v) if v.is_empty() => "\"\"".to_string(),
+        Some(v) => v.clone(),
+    };
+
+    let items: Vec<ListItem> = view
+        .review
+        .changes
+        .iter()
+        .zip(&view.review.keep_existing)
+        .map(|(change, keep)| {
+            let (tag, color) = match change.kind {
+                ChangeKind::Added => (tr("env_review.added"), Color::Green),
+                ChangeKind::Changed => (tr("env_review.changed"), Color::Yellow),
+                ChangeKind::Removed => (tr("env_review.removed"), Color::Red),
+            };
+            let decision = match (change.overwrites(), keep) {
+                (false, _) => Span::styled("        ", Style::default()),
+                (true, true) => {
+                    Span::styled(tr("env_review.keep"), Style::default().fg(Color::Cyan))
+                }
+                (true, false) => {
+                    Span::styled(tr("env_review.update"), Style::default().fg(Color::Yellow))
+                }
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{tag} "), Style::default().fg(color)),
+                decision,
+                Span::raw(format!(
+                    " {}: {} → {}",
+                    change.key,
+                    show(&change.key, &change.old),
+                    show(&change.key, &change.new)
+                )),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title(tr("env_review.block"))
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("▶ ");
+    let mut state = ListState::default().with_selected(Some(view.review.selected));
+    frame.render_stateful_widget(list, chunks[1], &mut state);
+
+    let help = Paragraph::new(tr("env_review.keys"))
+        .style(Style::default().fg(Color::DarkGray))
+        .centered();
+    frame.render_widget(help, chunks[2]);
+}
@@ -1,3 +1,5 @@
+use std::path::Path;
+
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout},
@@ -6,11 +8,23 @@ use ratatui::{
     widgets::{Block, Borders, Paragraph, Wrap},
 };
 
+use crate::app::recovery::Retry;
+use crate::i18n::{tr, trf};
 use crate::ui::{get_orange_accent, get_orange_color};
 
 pub struct ErrorView<'a> {
     pub error: &'a str,
+    /// Recent log lines, or the whole install log when `full_log`.
     pub logs: &'a [String],
+    pub full_log: bool,
+    pub log_path: Option<&'a Path>,
+    /// Remediation for the docker / compose failures recognised.
+    pub hints: &'a [String],
+    pub retry: Option<Retry>,
+    /// Lines scrolled up from the end of the log.
+    pub scroll: u16,
+    /// Outcome of saving the error to a file.
+    pub status: Option<&'a Result<String, String>>,
 }
 
 pub fn render_error(frame: &mut Frame, view: &ErrorView<'_>) {
@@ -27,7 +41,7 @@ pub fn render_error(frame: &mut Frame, view: &ErrorView<'_>) {
         ])
         .split(area);
 
-    let title = Paragraph::new("❌ Installation Failed")
+    let title = Paragraph::new(format!("❌ {}", tr("done.failed")))
         .style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
         .block(
             Block::default()
@@ -37,23 +51,58 @@ pub fn render_error(frame: &mut Frame, view: &ErrorView<'_>) {
         .centered();
     frame.render_widget(title, chunks[0]);
 
-    let message = vec![
+    let mut message = vec![
         Line::from(""),
         Line::from(Span::styled(
-            "An error occurred:",
+            tr("done.error_intro"),
             Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
         Line::from(Span::styled(view.error, Style::default().fg(Color::White))),
         Line::from(""),
     ];
+    if !view.hints.is_empty() {
+        message.push(Line::from(Span::styled(
+            tr("done.hints"),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )));
+        for hint in view.hints {
+            for (idx, line) in hint.lines().enumerate() {
+                let bullet = if idx == 0 { "  • " } else { "    " };
+                message.push(Line::from(Span::styled(
+                    format!("{bullet}{line}"),
+                    Style::default().fg(Color::Cyan),
+                )));
+            }
+        }
+        message.push(Line::from(""));
+    }
+    if let Some(path) = view.log_path {
+        message.push(Line::from(Span::styled(
+            trf("done.full_log", &[("path", &path.display().to_string())]),
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+    match view.status {
+        Some(Ok(saved)) => message.push(Line::from(Span::styled(
+            format!("✓ {saved}"),
+            Style::default().fg(Color::Green),
+        ))),
+        Some(Err(e)) => message.push(Line::from(Span::styled(
+            format!("❌ {e}"),
+            Style::default().fg(Color::Red),
+        ))),
+        None => {}
+    }
 
     let message_widget = Paragraph::new(message)
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(get_orange_accent()))
-                .title("Error Details")
+                .title(tr("done.error_title"))
                 .title_style(
                     Style::default()
                         .fg(get_orange_color())
@@ -74,7 +123,11 @@ pub fn render_error(frame: &mut Frame, view: &ErrorView<'_>) {
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(get_orange_accent()))
-                .title("Installation Logs")
+                .title(if view.full_log {
+                    tr("done.full_log_title")
+                } else {
+                    tr("done.logs_title")
+                })
                 .title_style(
                     Style::default()
                         .fg(get_orange_color())
@@ -83,14 +136,19 @@ pub fn render_error(frame: &mut Frame, view: &ErrorView<'_>) {
         )
         .wrap(Wrap { trim: false })
         .scroll((
-            view.logs
-                .len()
-                .saturating_sub(chunks[2].height as usize - 2) as u16,
+            (view.logs.len() as u16)
+                .saturating_sub(chunks[2].height.saturating_sub(2))
+                .saturating_sub(view.scroll),
             0,
         ));
     frame.render_widget(logs_widget, chunks[2]);
 
-    let help = Paragraph::new("Press Ctrl+C to exit")
+    let actions = if view.retry.is_some() {
+        tr("done.actions_retry")
+    } else {
+        tr("done.actions")
+    };
+    let help = Paragraph::new(actions)
         .style(Style::default().fg(Color::DarkGray))
         .centered();
     frame.render_widget(help, chunks[3]);
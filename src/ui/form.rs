@@ -0,0 +1,105 @@
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::app::form::Form;
+use crate::app::registry_form::FocusState;
+use crate::ui::{get_orange_color, text_input_spans};
+
+/// One `▶ label : value` line per field, labels padded to the widest one.
+pub fn field_lines(form: &Form, separator: &str) -> Vec<Line<'static>> {
+    let label_width = form
+        .fields
+        .iter()
+        .map(|field| field.label.width())
+        .max()
+        .unwrap_or(0);
+    form.fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| {
+            let focused = form.focus_state == FocusState::Field(idx);
+            let style = if focused {
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(get_orange_color())
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            let mut spans = vec![
+                Span::styled(if focused { "▶" } else { " " }, style),
+                Span::raw(" "),
+                Span::styled(
+                    format!(
+                        "{}{}{separator}",
+                        field.label,
+                        " ".repeat(label_width - field.label.width())
+                    ),
+                    style,
+                ),
+            ];
+            spans.extend(text_input_spans(
+                &field.input,
+                focused,
+                &field.placeholder,
+                style,
+            ));
+            if let Some(error) = &field.error {
+                spans.push(Span::styled(
+                    format!("  ⚠ {error}"),
+                    Style::default().fg(Color::Red),
+                ));
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
+
+/// Rows to scroll a list of `field_lines` so the focused field stays inside
+/// a bordered block `height` rows tall.
+pub fn field_scroll(form: &Form, height: u16) -> u16 {
+    let visible = height.saturating_sub(2) as usize;
+    let focused_row = match form.focus_state {
+        FocusState::Field(idx) => idx,
+        _ => form.fields.len().saturating_sub(1),
+    };
+    (focused_row + 1).saturating_sub(visible) as u16
+}
+
+/// The submit and cancel buttons, then `hint` in grey when not empty.
+pub fn button_line(form: &Form, hint: &str) -> Line<'static> {
+    let button_style = |focused: bool, color: Color| {
+        if focused {
+            Style::default()
+                .fg(Color::Black)
+                .bg(color)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(color).add_modifier(Modifier::BOLD)
+        }
+    };
+    let (submit, cancel) = form.buttons;
+    let mut spans = vec![
+        Span::raw("  "),
+        Span::styled(
+            format!(" {submit} "),
+            button_style(form.focus_state == FocusState::SaveButton, Color::Green),
+        ),
+        Span::raw("  "),
+        Span::styled(
+            format!(" {cancel} "),
+            button_style(form.focus_state == FocusState::CancelButton, Color::Red),
+        ),
+    ];
+    if !hint.is_empty() {
+        spans.push(Span::raw("  "));
+        spans.push(Span::styled(
+            hint.to_string(),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    Line::from(spans)
+}
@@ -0,0 +1,69 @@
+use ratatui::{
+    Frame,
+    layout::Alignment,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+use crate::i18n::{tr, trf};
+use crate::ui::{centered_rect, get_orange_accent, get_orange_color};
+
+pub struct HelpView<'a> {
+    pub title: &'a str,
+    pub bindings: &'a [(&'a str, &'a str)],
+    /// Remaps from keybindings.toml, as (pressed, acts as).
+    pub remaps: &'a [(String, String)],
+    pub warnings: &'a [String],
+}
+
+/// Modal listing the keys of the current screen.
+pub fn render_help(frame: &mut Frame, view: &HelpView<'_>) {
+    let key_style = Style::default()
+        .fg(get_orange_color())
+        .add_modifier(Modifier::BOLD);
+    let dim = Style::default().fg(Color::DarkGray);
+    let row = |key: &str, what: &str| {
+        Line::from(vec![
+            Span::styled(format!("  {key:<18}"), key_style),
+            Span::raw(what.to_string()),
+        ])
+    };
+
+    let mut lines = vec![Line::from("")];
+    lines.extend(view.bindings.iter().map(|(key, what)| row(key, what)));
+    lines.push(row("Ctrl+C", tr("help.quit")));
+    lines.push(row("? / F1", tr("help.this_help")));
+
+    if !view.remaps.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!("  {}", tr("help.remaps")),
+            dim,
+        )));
+        lines.extend(
+            view.remaps
+                .iter()
+                .map(|(from, to)| row(from, &trf("help.acts_as", &[("key", to)]))),
+        );
+    }
+    for warning in view.warnings {
+        lines.push(Line::from(Span::styled(
+            format!("  ⚠ {warning}"),
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+
+    let height = lines.len() as u16 + 3;
+    let area = centered_rect(64, height, frame.area());
+    frame.render_widget(Clear, area);
+    let popup = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(get_orange_accent()))
+            .title(trf("help.title", &[("screen", view.title)]))
+            .title_style(key_style)
+            .title_bottom(Line::from(tr("help.close")).alignment(Alignment::Center)),
+    );
+    frame.render_widget(popup, area);
+}
@@ -1,21 +1,34 @@
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph, Wrap},
+    widgets::{
+        Block, Borders, Cell, Clear, List, ListItem, ListState, Paragraph, Row, Table, Wrap,
+    },
 };
 
-use crate::ui::{get_orange_accent, get_orange_color};
+use crate::app::{CleanupChoice, DiskChoice, QuitChoice, StallChoice, StallPrompt};
+use crate::i18n::{tr, trf};
+use crate::progress::{InstallProgress, ServiceState};
+use crate::ui::{centered_rect, get_orange_accent, get_orange_color};
 
 pub struct InstallingView<'a> {
-    pub progress: f64,
+    pub progress: &'a InstallProgress,
     pub current_service: &'a str,
-    pub completed_services: usize,
-    pub total_services: usize,
     pub logs: &'a [String],
     /// True when running in airgapped mode (using pre-loaded images)
     pub airgapped: bool,
+    /// Highlighted entry of the post-cancel cleanup prompt, when it is open.
+    pub cleanup_selection: Option<usize>,
+    /// Highlighted entry of the low-disk prompt, when it is open.
+    pub disk_selection: Option<usize>,
+    /// Open when the running compose step went quiet or ran too long.
+    pub stall_prompt: Option<&'a StallPrompt>,
+    /// Raw compose output of the running step.
+    pub compose_output: &'a [String],
+    /// Highlighted entry of the prompt Ctrl+C opens, when it is open.
+    pub quit_selection: Option<usize>,
 }
 
 pub fn render_installing(frame: &mut Frame, view: &InstallingView<'_>) {
@@ -28,15 +41,16 @@ pub fn render_installing(frame: &mut Frame, view: &InstallingView<'_>) {
             Constraint::Length(3),
             Constraint::Length(3),
             Constraint::Length(3),
-            Constraint::Min(10),
+            Constraint::Length(view.progress.services.len() as u16 + 3),
+            Constraint::Min(6),
             Constraint::Length(2),
         ])
         .split(area);
 
     let title_text = if view.airgapped {
-        "🔄 Installing Identity (Offline Mode)... Please wait"
+        tr("installing.title_offline")
     } else {
-        "🔄 Installing Identity... Please wait"
+        tr("installing.title")
     };
 
     let title = Paragraph::new(title_text)
@@ -54,18 +68,19 @@ pub fn render_installing(frame: &mut Frame, view: &InstallingView<'_>) {
     frame.render_widget(title, chunks[0]);
 
     let bar_space = chunks[1].width.saturating_sub(10) as usize;
-    let filled_width = ((bar_space as f64) * (view.progress / 100.0)).round() as usize;
+    let percent = view.progress.percent();
+    let filled_width = ((bar_space as f64) * (percent / 100.0)).round() as usize;
     let filled = "█".repeat(filled_width.min(bar_space));
-    let empty = "░".repeat(bar_space.saturating_sub(filled.len()));
+    let empty = "░".repeat(bar_space.saturating_sub(filled_width.min(bar_space)));
 
-    let progress_text = format!("[{}{}] {:.0}%", filled, empty, view.progress);
+    let progress_text = format!("[{}{}] {:.0}%", filled, empty, percent);
     let progress_widget = Paragraph::new(progress_text)
         .style(Style::default().fg(get_orange_color()))
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(get_orange_accent()))
-                .title("Progress")
+                .title(tr("installing.progress_title"))
                 .title_style(
                     Style::default()
                         .fg(get_orange_color())
@@ -76,20 +91,30 @@ pub fn render_installing(frame: &mut Frame, view: &InstallingView<'_>) {
     frame.render_widget(progress_widget, chunks[1]);
 
     let current = if !view.current_service.is_empty() {
-        format!(
-            "Current: {} ({}/{})",
-            view.current_service, view.completed_services, view.total_services
+        trf(
+            "installing.current",
+            &[
+                ("service", view.current_service),
+                ("started", &view.progress.started().to_string()),
+                ("total", &view.progress.services.len().to_string()),
+            ],
         )
     } else {
-        "Initializing...".to_string()
+        tr("installing.initializing").to_string()
     };
 
     let current_widget = Paragraph::new(current)
         .style(Style::default().fg(Color::Green))
-        .block(Block::default().borders(Borders::ALL).title("Status"))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(tr("installing.status_title")),
+        )
         .centered();
     frame.render_widget(current_widget, chunks[2]);
 
+    render_service_table(frame, view.progress, chunks[3]);
+
     let log_lines: Vec<Line> = view
         .logs
         .iter()
@@ -115,7 +140,7 @@ pub fn render_installing(frame: &mut Frame, view: &InstallingView<'_>) {
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(get_orange_accent()))
-                .title("📋 Installation Logs")
+                .title(tr("installing.logs_title"))
                 .title_style(
                     Style::default()
                         .fg(get_orange_color())
@@ -126,13 +151,292 @@ pub fn render_installing(frame: &mut Frame, view: &InstallingView<'_>) {
         .scroll((
             view.logs
                 .len()
-                .saturating_sub(chunks[3].height as usize - 2) as u16,
+                .saturating_sub(chunks[4].height as usize - 2) as u16,
             0,
         ));
-    frame.render_widget(logs_widget, chunks[3]);
+    frame.render_widget(logs_widget, chunks[4]);
 
-    let help = Paragraph::new("Press Ctrl+C to cancel")
+    let help = Paragraph::new(tr("installing.keys"))
         .style(Style::default().fg(Color::DarkGray))
         .centered();
-    frame.render_widget(help, chunks[4]);
+    frame.render_widget(help, chunks[5]);
+
+    if let Some(selected) = view.cleanup_selection {
+        render_cleanup_prompt(frame, selected);
+    }
+    if let Some(selected) = view.disk_selection {
+        render_disk_prompt(frame, selected);
+    }
+    if let Some(prompt) = view.stall_prompt {
+        if prompt.show_output {
+            render_compose_output(frame, view.compose_output);
+        } else {
+            render_stall_prompt(frame, prompt);
+        }
+    }
+    if let Some(selected) = view.quit_selection {
+        render_quit_prompt(frame, selected);
+    }
+}
+
+fn render_service_table(frame: &mut Frame, progress: &InstallProgress, area: Rect) {
+    let header_style = Style::default()
+        .fg(get_orange_color())
+        .add_modifier(Modifier::BOLD);
+    let header = Row::new(vec![
+        Cell::from(tr("installing.col_service")).style(header_style),
+        Cell::from(tr("installing.col_container")).style(header_style),
+        Cell::from(tr("installing.col_state")).style(header_style),
+    ]);
+    let rows: Vec<Row> = progress
+        .services
+        .iter()
+        .map(|service| {
+            let color = match service.state {
+                ServiceState::Waiting => Color::DarkGray,
+                ServiceState::Failed => Color::Red,
+                ServiceState::Started | ServiceState::Healthy => Color::Green,
+                ServiceState::Pulling | ServiceState::Pulled => Color::Blue,
+                _ => Color::Yellow,
+            };
+            Row::new(vec![
+                Cell::from(service.name.clone()),
+                Cell::from(service.container.clone().unwrap_or_default()),
+                Cell::from(match (service.state, service.pull_percent()) {
+                    (ServiceState::Pulling, Some(percent)) => trf(
+                        "installing.pulling_percent",
+                        &[("percent", &format!("{percent:.0}"))],
+                    ),
+                    (state, _) => state.display_label().to_string(),
+                }),
+            ])
+            .style(Style::default().fg(color))
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(20),
+            Constraint::Length(24),
+            Constraint::Min(10),
+        ],
+    )
+    .header(header)
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(get_orange_accent()))
+            .title(tr("installing.services_title"))
+            .title_style(
+                Style::default()
+                    .fg(get_orange_color())
+                    .add_modifier(Modifier::BOLD),
+            ),
+    )
+    .column_spacing(2);
+    frame.render_widget(table, area);
+}
+
+fn render_cleanup_prompt(frame: &mut Frame, selected: usize) {
+    let area = centered_rect(60, 10, frame.area());
+    frame.render_widget(Clear, area);
+
+    let items: Vec<ListItem> = CleanupChoice::ALL
+        .iter()
+        .map(|choice| {
+            let color = match choice {
+                CleanupChoice::Down | CleanupChoice::LeaveAsIs => Color::White,
+                CleanupChoice::StopServices => Color::Yellow,
+                CleanupChoice::DownVolumes => Color::Red,
+            };
+            ListItem::new(choice.label()).style(Style::default().fg(color))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title(tr("installing.cleanup_title"))
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                )
+                .title_bottom(Line::from(tr("installing.cleanup_keys")).centered()),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▶ ");
+
+    let mut state = ListState::default().with_selected(Some(selected));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn render_quit_prompt(frame: &mut Frame, selected: usize) {
+    let area = centered_rect(60, 9, frame.area());
+    frame.render_widget(Clear, area);
+
+    let items: Vec<ListItem> = QuitChoice::ALL
+        .iter()
+        .map(|choice| {
+            let color = match choice {
+                QuitChoice::Wait => Color::White,
+                QuitChoice::StopAndExit => Color::Yellow,
+                QuitChoice::Detach => Color::Red,
+            };
+            ListItem::new(choice.label()).style(Style::default().fg(color))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title(tr("quit.title"))
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                )
+                .title_bottom(Line::from(tr("quit.keys")).centered()),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▶ ");
+
+    let mut state = ListState::default().with_selected(Some(selected));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn render_disk_prompt(frame: &mut Frame, selected: usize) {
+    let area = centered_rect(60, 9, frame.area());
+    frame.render_widget(Clear, area);
+
+    let items: Vec<ListItem> = DiskChoice::ALL
+        .iter()
+        .map(|choice| {
+            let color = match choice {
+                DiskChoice::Prune => Color::Yellow,
+                DiskChoice::ContinueAnyway => Color::White,
+                DiskChoice::Cancel => Color::Red,
+            };
+            ListItem::new(choice.label()).style(Style::default().fg(color))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title(tr("installing.disk_title"))
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                )
+                .title_bottom(Line::from(tr("installing.disk_keys")).centered()),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▶ ");
+
+    let mut state = ListState::default().with_selected(Some(selected));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn render_stall_prompt(frame: &mut Frame, prompt: &StallPrompt) {
+    let area = centered_rect(60, 12, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(get_orange_accent()))
+        .title(tr("installing.stall_title"))
+        .title_style(
+            Style::default()
+                .fg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        )
+        .title_bottom(Line::from(tr("installing.stall_keys")).centered());
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(3)])
+        .split(inner);
+
+    let reason = Paragraph::new(prompt.reason.as_str())
+        .style(Style::default().fg(Color::Yellow))
+        .wrap(Wrap { trim: true });
+    frame.render_widget(reason, chunks[0]);
+
+    let items: Vec<ListItem> = StallChoice::ALL
+        .iter()
+        .map(|choice| {
+            let color = match choice {
+                StallChoice::Abort => Color::Red,
+                _ => Color::White,
+            };
+            ListItem::new(choice.label()).style(Style::default().fg(color))
+        })
+        .collect();
+    let list = List::new(items)
+        .highlight_style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▶ ");
+
+    let mut state = ListState::default().with_selected(Some(prompt.selected));
+    frame.render_stateful_widget(list, chunks[1], &mut state);
+}
+
+fn render_compose_output(frame: &mut Frame, output: &[String]) {
+    let full = frame.area();
+    let area = centered_rect(
+        full.width.saturating_sub(8),
+        full.height.saturating_sub(4),
+        full,
+    );
+    frame.render_widget(Clear, area);
+
+    let height = area.height.saturating_sub(2) as usize;
+    let lines: Vec<Line> = output
+        .iter()
+        .skip(output.len().saturating_sub(height))
+        .map(|line| Line::from(line.as_str()))
+        .collect();
+    let widget = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(get_orange_accent()))
+            .title(tr("installing.output_title"))
+            .title_style(
+                Style::default()
+                    .fg(get_orange_color())
+                    .add_modifier(Modifier::BOLD),
+            )
+            .title_bottom(Line::from(tr("installing.output_keys")).centered()),
+    );
+    frame.render_widget(widget, area);
 }
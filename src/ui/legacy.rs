@@ -0,0 +1,86 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+
+use crate::app::legacy::LegacyInstall;
+use crate::i18n::{tr, trf};
+use crate::ui::{get_orange_accent, get_orange_color};
+
+pub struct LegacyFoundView<'a> {
+    pub found: &'a LegacyInstall,
+}
+
+pub fn render_legacy_found(frame: &mut Frame, view: &LegacyFoundView<'_>) {
+    let found = view.found;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(8),
+            Constraint::Length(2),
+        ])
+        .split(frame.area());
+
+    let header = Paragraph::new(tr("legacy.title"))
+        .style(
+            Style::default()
+                .fg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent())),
+        )
+        .centered();
+    frame.render_widget(header, chunks[0]);
+
+    let mut lines = vec![
+        Line::from(tr("legacy.intro")),
+        Line::from(tr("legacy.steps_intro")),
+        Line::from(""),
+    ];
+    let mut step = |text: String| {
+        lines.push(Line::from(Span::styled(
+            format!("  • {text}"),
+            Style::default().fg(Color::Cyan),
+        )))
+    };
+    if let Some(db) = &found.running_db {
+        step(trf("legacy.dump", &[("db", db)]));
+    }
+    if !found.containers.is_empty() {
+        step(trf(
+            "legacy.remove",
+            &[("containers", &found.containers.join(", "))],
+        ));
+    }
+    if let Some(volume) = &found.volume {
+        step(trf("legacy.copy_volume", &[("volume", volume)]));
+    }
+    for (old, new) in &found.env_renames {
+        step(trf("legacy.rename", &[("old", old), ("new", new)]));
+    }
+    let plan = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(get_orange_accent()))
+            .title(tr("legacy.plan_title"))
+            .title_style(
+                Style::default()
+                    .fg(get_orange_color())
+                    .add_modifier(Modifier::BOLD),
+            ),
+    );
+    frame.render_widget(plan, chunks[1]);
+
+    let hint = Paragraph::new(tr("legacy.keys"))
+        .style(Style::default().fg(Color::DarkGray))
+        .centered();
+    frame.render_widget(hint, chunks[2]);
+}
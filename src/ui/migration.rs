@@ -0,0 +1,93 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::app::migration_form::MigrationForm;
+use crate::i18n::tr;
+use crate::ui::form::{button_line, field_lines};
+use crate::ui::{get_orange_accent, get_orange_color};
+
+pub struct MigrationSetupView<'a> {
+    pub form: &'a MigrationForm,
+}
+
+pub fn render_migration_setup(frame: &mut Frame, view: &MigrationSetupView<'_>) {
+    let area = frame.area();
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(8),
+            Constraint::Min(4),
+            Constraint::Length(3),
+        ])
+        .split(area);
+
+    let header = Paragraph::new(tr("migration.title"))
+        .style(
+            Style::default()
+                .fg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent())),
+        )
+        .centered();
+    frame.render_widget(header, chunks[0]);
+
+    let mut lines = vec![Line::from(tr("migration.intro")), Line::from("")];
+    lines.extend(field_lines(&view.form.form, " : "));
+
+    let form_block = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title(tr("migration.source_title"))
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .wrap(ratatui::widgets::Wrap { trim: false });
+    frame.render_widget(form_block, chunks[1]);
+
+    let (status_message, status_style) = if !view.form.form.error_message.is_empty() {
+        (
+            view.form.form.error_message.clone(),
+            Style::default().fg(Color::Red),
+        )
+    } else {
+        (
+            tr("migration.hint").to_string(),
+            Style::default().fg(Color::Yellow),
+        )
+    };
+    let status_block = Paragraph::new(status_message)
+        .style(status_style)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title(tr("migration.status_title"))
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .wrap(ratatui::widgets::Wrap { trim: true });
+    frame.render_widget(status_block, chunks[2]);
+
+    let buttons = button_line(&view.form.form, "");
+    frame.render_widget(Paragraph::new(buttons).centered(), chunks[3]);
+}
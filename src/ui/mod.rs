@@ -1,17 +1,95 @@
+mod advanced;
+mod airgapped;
 mod ascii_art;
+mod backup;
+mod busy;
+mod config_selection;
 mod confirmation;
+mod context_picker;
+mod dir_picker;
+mod env_editor;
+mod env_review;
 mod error;
+mod external_db;
+mod form;
+mod help;
 mod installing;
+mod ldap;
+mod legacy;
+mod migration;
+mod oidc_client;
+mod onboarding;
+mod output;
+mod presets;
+mod proxy;
+mod registries;
 mod registry;
+mod services;
+mod smtp;
 mod ssl_setup;
 mod success;
+mod template_form;
+mod text_input;
+pub mod theme;
+mod too_small;
 mod update;
+mod yaml_editor;
 
+pub use advanced::{AdvancedOptionsView, render_advanced_options};
+pub use airgapped::{AirgappedSetupView, render_airgapped_setup};
 pub use ascii_art::{ASCII_HEADER, get_orange_accent, get_orange_color};
+pub use backup::{RestorePickerView, render_restore_picker};
+pub use busy::{BusyView, render_busy};
+pub use config_selection::{ConfigSelectionView, GRID_COLUMNS, render_config_selection};
 pub use confirmation::{ConfirmationView, render_confirmation};
+pub use context_picker::{ContextPickerView, render_context_picker};
+pub use dir_picker::{DirPickerView, render_dir_picker};
+pub use env_editor::{EnvEditorView, render_env_editor};
+pub use env_review::{EnvReviewView, render_env_review};
 pub use error::{ErrorView, render_error};
+pub use external_db::{ExternalDbSetupView, render_external_db_setup};
+pub use help::{HelpView, render_help};
 pub use installing::{InstallingView, render_installing};
+pub use ldap::{LdapSetupView, render_ldap_setup};
+pub use legacy::{LegacyFoundView, render_legacy_found};
+pub use migration::{MigrationSetupView, render_migration_setup};
+pub use oidc_client::{OidcClientSetupView, render_oidc_client_setup};
+pub use onboarding::{ONBOARDING_STEPS, OnboardingView, render_onboarding};
+pub use output::{ActionOutputView, render_action_output};
+pub use presets::{PresetPickerView, render_preset_picker};
+pub use proxy::{ProxySetupView, render_proxy_setup};
+pub use registries::{RegistriesView, render_registries};
 pub use registry::{RegistrySetupView, render_registry_setup};
+pub use services::{ServicePickerView, render_service_picker};
+pub use smtp::{SmtpSetupView, render_smtp_setup};
 pub use ssl_setup::{SslSetupView, render_ssl_setup};
 pub use success::{SuccessView, render_success};
-pub use update::{UpdateListView, render_update_list};
+pub use template_form::{TemplateFormView, render_template_form};
+pub use text_input::text_input_spans;
+pub use too_small::{fits, render_too_small};
+pub use update::{TagPickerView, UpdateListView, render_update_list};
+pub use yaml_editor::{YamlEditorView, render_yaml_editor};
+
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+
+/// Rect of `width` x `height` cells centered in `area`, clamped to fit.
+pub fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(area.height.saturating_sub(height) / 2),
+            Constraint::Length(height),
+            Constraint::Min(0),
+        ])
+        .split(area);
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Length(area.width.saturating_sub(width) / 2),
+            Constraint::Length(width),
+            Constraint::Min(0),
+        ])
+        .split(vertical[1])[1]
+}
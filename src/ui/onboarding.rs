@@ -0,0 +1,145 @@
+use ratatui::{
+    Frame,
+    layout::Alignment,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+use crate::i18n::tr;
+use crate::ui::{centered_rect, get_orange_accent, get_orange_color};
+
+/// Number of pages in the first-run tour.
+pub const ONBOARDING_STEPS: usize = 4;
+
+pub struct OnboardingView<'a> {
+    pub step: usize,
+    /// Directory the installer writes certs, .env and compose files into.
+    pub project_root: &'a str,
+    /// Directory saved tokens and installer settings go to.
+    pub config_dir: &'a str,
+}
+
+pub fn render_onboarding(frame: &mut Frame, view: &OnboardingView<'_>) {
+    let area = centered_rect(72, 18, frame.area());
+    frame.render_widget(Clear, area);
+
+    let (title, body) = step_content(view);
+
+    let mut lines = vec![Line::from("")];
+    lines.extend(body);
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        step_dots(view.step),
+        Style::default().fg(get_orange_color()),
+    )));
+
+    let footer = if view.step + 1 < ONBOARDING_STEPS {
+        tr("onboarding.keys")
+    } else {
+        tr("onboarding.keys_last")
+    };
+
+    let popup = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(get_orange_accent()))
+            .title(format!(" {title} ({}/{ONBOARDING_STEPS}) ", view.step + 1))
+            .title_style(
+                Style::default()
+                    .fg(get_orange_color())
+                    .add_modifier(Modifier::BOLD),
+            )
+            .title_bottom(Line::from(footer).alignment(Alignment::Center)),
+    );
+    frame.render_widget(popup, area);
+}
+
+fn step_content(view: &OnboardingView<'_>) -> (&'static str, Vec<Line<'static>>) {
+    let dim = Style::default().fg(Color::DarkGray);
+    let item = |name: &'static str, what: &'static str| {
+        Line::from(vec![
+            Span::styled(format!("  {name:<32}"), Style::default().fg(Color::White)),
+            Span::styled(what, dim),
+        ])
+    };
+
+    match view.step {
+        0 => (
+            tr("onboarding.welcome_title"),
+            vec![
+                Line::from(tr("onboarding.welcome_1")),
+                Line::from(tr("onboarding.welcome_2")),
+                Line::from(""),
+                Line::from(tr("onboarding.welcome_3")),
+                Line::from(tr("onboarding.welcome_4")),
+            ],
+        ),
+        1 => (
+            tr("onboarding.checklist_title"),
+            vec![
+                Line::from(tr("onboarding.checklist_intro")),
+                Line::from(""),
+                item(
+                    tr("onboarding.checklist_cert"),
+                    tr("onboarding.checklist_cert_what"),
+                ),
+                item(
+                    tr("onboarding.checklist_ip"),
+                    tr("onboarding.checklist_ip_what"),
+                ),
+                Line::from(""),
+                Line::from(tr("onboarding.checklist_hint")),
+            ],
+        ),
+        2 => (
+            tr("onboarding.menu_title"),
+            vec![
+                Line::from(tr("onboarding.menu_intro")),
+                Line::from(""),
+                item(tr("menu.proceed"), tr("onboarding.proceed_what")),
+                item(
+                    tr("menu.check_updates"),
+                    tr("onboarding.check_updates_what"),
+                ),
+                item(tr("menu.update_token"), tr("onboarding.update_token_what")),
+                item(tr("menu.cancel"), tr("onboarding.cancel_what")),
+                Line::from(""),
+                Line::from(tr("onboarding.menu_hotkeys")),
+            ],
+        ),
+        _ => (
+            tr("onboarding.files_title"),
+            vec![
+                Line::from(tr("onboarding.files_intro")),
+                Line::from(Span::styled(
+                    format!("  {}", view.project_root),
+                    Style::default().fg(Color::Cyan),
+                )),
+                Line::from(""),
+                item(
+                    tr("onboarding.files_certs"),
+                    tr("onboarding.files_certs_what"),
+                ),
+                item(tr("onboarding.files_env"), tr("onboarding.files_env_what")),
+                item(
+                    tr("onboarding.files_override"),
+                    tr("onboarding.files_override_what"),
+                ),
+                Line::from(""),
+                Line::from(tr("onboarding.files_config")),
+                Line::from(Span::styled(
+                    format!("  {}", view.config_dir),
+                    Style::default().fg(Color::Cyan),
+                )),
+            ],
+        ),
+    }
+}
+
+fn step_dots(step: usize) -> String {
+    (0..ONBOARDING_STEPS)
+        .map(|i| if i == step { "●" } else { "○" })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,60 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::i18n::tr;
+use crate::ui::{get_orange_accent, get_orange_color};
+
+/// Read-only command output opened from a quick action (logs, status, doctor).
+pub struct ActionOutputView<'a> {
+    pub title: &'a str,
+    pub lines: &'a [String],
+    /// Lines scrolled up from the bottom; 0 follows the tail.
+    pub scroll_back: u16,
+}
+
+pub fn render_action_output(frame: &mut Frame, view: &ActionOutputView<'_>) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Min(5), Constraint::Length(1)])
+        .split(frame.area());
+
+    let lines: Vec<Line> = view
+        .lines
+        .iter()
+        .map(|line| {
+            Line::from(Span::styled(
+                line.clone(),
+                Style::default().fg(Color::White),
+            ))
+        })
+        .collect();
+
+    let visible = chunks[0].height.saturating_sub(2) as usize;
+    let tail = view.lines.len().saturating_sub(visible) as u16;
+
+    let output = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title(format!(" {} ", view.title))
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .scroll((tail.saturating_sub(view.scroll_back), 0));
+    frame.render_widget(output, chunks[0]);
+
+    let help = Paragraph::new(tr("output.keys"))
+        .style(Style::default().fg(Color::DarkGray))
+        .centered();
+    frame.render_widget(help, chunks[1]);
+}
@@ -0,0 +1,108 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+};
+
+use crate::app::preset_picker::PresetPicker;
+use crate::i18n::{tr, trf};
+use crate::settings::AdvancedSettings;
+use crate::sizing::{self, Preset};
+use crate::ui::{get_orange_accent, get_orange_color};
+
+pub struct PresetPickerView<'a> {
+    pub picker: &'a PresetPicker,
+    pub advanced: &'a AdvancedSettings,
+}
+
+pub fn render_preset_picker(frame: &mut Frame, view: &PresetPickerView<'_>) {
+    let picker = view.picker;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Min(5),
+            Constraint::Length(2),
+            Constraint::Length(1),
+        ])
+        .split(frame.area());
+
+    let items: Vec<ListItem> = Preset::ALL
+        .iter()
+        .map(|preset| {
+            let marker = if preset.in_use(picker.host, view.advanced) {
+                "● "
+            } else {
+                "  "
+            };
+            let mut title = vec![Span::styled(
+                format!("{marker}{}", preset.name()),
+                Style::default().add_modifier(Modifier::BOLD),
+            )];
+            if picker.recommended() == Some(*preset) {
+                title.push(Span::styled(
+                    tr("presets.recommended"),
+                    Style::default().fg(Color::Green),
+                ));
+            }
+            let mut lines = vec![Line::from(title)];
+            for (service, size) in preset.sizes(picker.host) {
+                lines.push(Line::from(Span::styled(
+                    format!("    {service:<10} {}", sizing::describe(&size)),
+                    Style::default().fg(Color::DarkGray),
+                )));
+            }
+            ListItem::new(Text::from(lines))
+        })
+        .collect();
+
+    let title = match picker.host {
+        Some(host) => trf(
+            "presets.title",
+            &[
+                (
+                    "memory",
+                    &format!("{:.1}", host.memory as f64 / f64::from(1 << 30)),
+                ),
+                ("cpus", &host.cpus.to_string()),
+            ],
+        ),
+        None => tr("presets.title_unknown").to_string(),
+    };
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title(title)
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▶ ");
+    let mut state = ListState::default().with_selected(Some(picker.selected));
+    frame.render_stateful_widget(list, chunks[0], &mut state);
+
+    let status = match &picker.status {
+        Some(Ok(message)) => {
+            Line::from(format!("✓ {message}")).style(Style::default().fg(Color::Green))
+        }
+        Some(Err(e)) => Line::from(format!("❌ {e}")).style(Style::default().fg(Color::Red)),
+        None => Line::from(tr("presets.marker")).style(Style::default().fg(Color::DarkGray)),
+    };
+    frame.render_widget(Paragraph::new(status).centered(), chunks[1]);
+
+    let help = Paragraph::new(tr("presets.keys"))
+        .style(Style::default().fg(Color::DarkGray))
+        .centered();
+    frame.render_widget(help, chunks[2]);
+}
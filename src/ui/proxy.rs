@@ -0,0 +1,106 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::app::proxy_form::ProxyForm;
+use crate::i18n::{tr, trf};
+use crate::platform::DockerRuntime;
+use crate::ui::form::{button_line, field_lines};
+use crate::ui::{get_orange_accent, get_orange_color};
+
+pub struct ProxySetupView<'a> {
+    pub form: &'a ProxyForm,
+    /// Proxy values found in the environment, shown as a hint.
+    pub env_hint: Option<&'a str>,
+}
+
+pub fn render_proxy_setup(frame: &mut Frame, view: &ProxySetupView<'_>) {
+    let area = frame.area();
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(9),
+            Constraint::Min(4),
+            Constraint::Length(3),
+        ])
+        .split(area);
+
+    let header = Paragraph::new(tr("proxy.title"))
+        .style(
+            Style::default()
+                .fg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent())),
+        )
+        .centered();
+    frame.render_widget(header, chunks[0]);
+
+    let mut lines = vec![Line::from(tr("proxy.intro")), Line::from("")];
+    lines.extend(field_lines(&view.form.form, " : "));
+    if let Some(hint) = view.env_hint {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            trf("proxy.env", &[("hint", hint)]),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    let form_block = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title(tr("proxy.block"))
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .wrap(ratatui::widgets::Wrap { trim: false });
+    frame.render_widget(form_block, chunks[1]);
+
+    let (status_message, status_style) = if !view.form.form.error_message.is_empty() {
+        (
+            view.form.form.error_message.clone(),
+            Style::default().fg(Color::Red),
+        )
+    } else {
+        (
+            trf(
+                "proxy.docker_hint",
+                &[("file", DockerRuntime::detect().proxy_hint())],
+            ),
+            Style::default().fg(Color::Yellow),
+        )
+    };
+    let status_block = Paragraph::new(status_message)
+        .style(status_style)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title(tr("proxy.status"))
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .wrap(ratatui::widgets::Wrap { trim: true });
+    frame.render_widget(status_block, chunks[2]);
+
+    let buttons = button_line(&view.form.form, "");
+    frame.render_widget(Paragraph::new(buttons).centered(), chunks[3]);
+}
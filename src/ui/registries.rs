@@ -0,0 +1,116 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+};
+
+use crate::i18n::tr;
+use crate::registry::Registry;
+use crate::ui::{get_orange_accent, get_orange_color};
+
+pub struct RegistriesView<'a> {
+    pub registries: &'a [Registry],
+    /// Per registry: a credential is saved (keyring, file or environment).
+    pub saved: &'a [bool],
+    /// Name of the registry images are pulled through.
+    pub mirror: Option<&'a str>,
+    pub selected: usize,
+    pub status: Option<&'a str>,
+}
+
+pub fn render_registries(frame: &mut Frame, view: &RegistriesView<'_>) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(6),
+            Constraint::Length(3),
+            Constraint::Length(2),
+        ])
+        .split(frame.area());
+
+    let header = Paragraph::new(tr("registries.title"))
+        .style(
+            Style::default()
+                .fg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent())),
+        )
+        .centered();
+    frame.render_widget(header, chunks[0]);
+
+    let items: Vec<ListItem> = view
+        .registries
+        .iter()
+        .enumerate()
+        .map(|(i, registry)| {
+            let (login, color) = if !registry.needs_secret() {
+                (tr("registries.aws_cli"), Color::Cyan)
+            } else if view.saved.get(i).copied().unwrap_or(false) {
+                (tr("registries.saved"), Color::Green)
+            } else {
+                (tr("registries.no_password"), Color::Red)
+            };
+            let mirror = if view.mirror == Some(registry.name.as_str()) {
+                Span::styled(tr("registries.mirror"), Style::default().fg(Color::Magenta))
+            } else {
+                Span::raw("")
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{:<12}", registry.name),
+                    Style::default().fg(Color::White),
+                ),
+                Span::styled(
+                    format!("{:<28}", registry.kind.label()),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::raw(format!("{:<40}", registry.host())),
+                Span::styled(format!("{login:<11}"), Style::default().fg(color)),
+                mirror,
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title(tr("registries.block"))
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("▶ ");
+    let mut state = ListState::default().with_selected(Some(view.selected));
+    frame.render_stateful_widget(list, chunks[1], &mut state);
+
+    let status = view.status.unwrap_or(match view.mirror {
+        Some(_) => tr("registries.via_mirror"),
+        None => tr("registries.direct"),
+    });
+    let status_block = Paragraph::new(status)
+        .style(Style::default().fg(Color::Yellow))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent())),
+        );
+    frame.render_widget(status_block, chunks[2]);
+
+    let help = Paragraph::new(tr("registries.keys"))
+        .style(Style::default().fg(Color::DarkGray))
+        .centered();
+    frame.render_widget(help, chunks[3]);
+}
@@ -2,16 +2,20 @@ use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout},
     style::{Color, Modifier, Style},
-    text::{Line, Span},
+    text::Line,
     widgets::{Block, Borders, Paragraph},
 };
 
-use crate::app::registry_form::{FocusState, RegistryForm};
+use crate::app::StatusMessage;
+use crate::app::registry_form::RegistryForm;
+use crate::i18n::{tr, trf};
+use crate::registry::RegistryKind;
+use crate::ui::form::{button_line, field_lines};
 use crate::ui::{get_orange_accent, get_orange_color};
 
 pub struct RegistrySetupView<'a> {
     pub form: &'a RegistryForm,
-    pub status: Option<&'a str>,
+    pub status: Option<&'a StatusMessage>,
 }
 
 pub fn render_registry_setup(frame: &mut Frame, view: &RegistrySetupView<'_>) {
@@ -28,7 +32,8 @@ pub fn render_registry_setup(frame: &mut Frame, view: &RegistrySetupView<'_>) {
         ])
         .split(area);
 
-    let header = Paragraph::new("🔐 GitHub Container Registry Login")
+    let registry = &view.form.registry;
+    let header = Paragraph::new(trf("registry.title", &[("kind", registry.kind.label())]))
         .style(
             Style::default()
                 .fg(get_orange_color())
@@ -42,77 +47,61 @@ pub fn render_registry_setup(frame: &mut Frame, view: &RegistrySetupView<'_>) {
         .centered();
     frame.render_widget(header, chunks[0]);
 
-    // Token field
-    let is_field_focused = matches!(&view.form.focus_state, FocusState::Field(_));
-    let raw_value = view.form.token.as_str();
-
-    let display = if raw_value.is_empty() {
-        "<paste token here>".to_string()
-    } else {
-        "*".repeat(raw_value.chars().count())
-    };
-
-    let field_style = if is_field_focused {
-        Style::default()
-            .fg(Color::Black)
-            .bg(get_orange_color())
-            .add_modifier(Modifier::BOLD)
+    let prompt = if registry.kind == RegistryKind::Ghcr {
+        tr("registry.ghcr_prompt").to_string()
     } else {
-        Style::default().fg(Color::White)
+        trf(
+            "registry.password_prompt",
+            &[
+                (
+                    "user",
+                    registry
+                        .username
+                        .as_deref()
+                        .unwrap_or(tr("registry.no_username")),
+                ),
+                ("host", registry.host()),
+            ],
+        )
     };
+    let mut lines = vec![Line::from(prompt), Line::from("")];
+    lines.extend(field_lines(&view.form.form, ": "));
 
-    let cursor = if is_field_focused { "▶" } else { " " };
-
-    let field_line = Line::from(vec![
-        Span::styled(cursor, field_style),
-        Span::raw(" "),
-        Span::styled("Personal access token: ", field_style),
-        Span::styled(display, field_style),
-    ]);
-
-    let form_block = Paragraph::new(vec![
-        Line::from("Provide a GitHub token with `read:packages` scope."),
-        Line::from(""),
-        field_line,
-    ])
-    .block(
-        Block::default()
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(get_orange_accent()))
-            .title("Credentials")
-            .title_style(
-                Style::default()
-                    .fg(get_orange_color())
-                    .add_modifier(Modifier::BOLD),
-            ),
-    )
-    .wrap(ratatui::widgets::Wrap { trim: false });
+    let form_block = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title(tr("registry.credentials_title"))
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .wrap(ratatui::widgets::Wrap { trim: false });
     frame.render_widget(form_block, chunks[1]);
 
-    // Status
-    let status_message = if let Some(message) = view.status {
-        message.to_string()
-    } else if !view.form.error_message.is_empty() {
-        view.form.error_message.clone()
-    } else {
-        "Awaiting input...".to_string()
+    // Status: the form's error reports a failed login.
+    let error = &view.form.form.error_message;
+    let status = match view.status {
+        Some(status) => status.clone(),
+        None if !error.is_empty() => StatusMessage::Failure(error.clone()),
+        None => StatusMessage::Info(tr("registry.awaiting").to_string()),
     };
-
-    let status_style = if status_message.contains("success") {
-        Style::default().fg(Color::Green)
-    } else if status_message.contains("failed") || status_message.contains("error") {
-        Style::default().fg(Color::Red)
-    } else {
-        Style::default().fg(Color::Yellow)
+    let color = match status {
+        StatusMessage::Info(_) => Color::Yellow,
+        StatusMessage::Success(_) => Color::Green,
+        StatusMessage::Failure(_) => Color::Red,
     };
 
-    let status_block = Paragraph::new(status_message)
-        .style(status_style)
+    let status_block = Paragraph::new(status.text().to_string())
+        .style(Style::default().fg(color))
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(get_orange_accent()))
-                .title("Status")
+                .title(tr("registry.status_title"))
                 .title_style(
                     Style::default()
                         .fg(get_orange_color())
@@ -122,37 +111,6 @@ pub fn render_registry_setup(frame: &mut Frame, view: &RegistrySetupView<'_>) {
         .wrap(ratatui::widgets::Wrap { trim: true });
     frame.render_widget(status_block, chunks[2]);
 
-    // Buttons
-    let save_focused = matches!(&view.form.focus_state, FocusState::SaveButton);
-    let cancel_focused = matches!(&view.form.focus_state, FocusState::CancelButton);
-
-    let save_style = if save_focused {
-        Style::default()
-            .fg(Color::Black)
-            .bg(Color::Green)
-            .add_modifier(Modifier::BOLD)
-    } else {
-        Style::default()
-            .fg(Color::Green)
-            .add_modifier(Modifier::BOLD)
-    };
-
-    let cancel_style = if cancel_focused {
-        Style::default()
-            .fg(Color::Black)
-            .bg(Color::Red)
-            .add_modifier(Modifier::BOLD)
-    } else {
-        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
-    };
-
-    let button_line = Line::from(vec![
-        Span::raw("  "),
-        Span::styled(" Submit ", save_style),
-        Span::raw("  "),
-        Span::styled(" Skip ", cancel_style),
-    ]);
-
-    let buttons = Paragraph::new(button_line).centered();
-    frame.render_widget(buttons, chunks[3]);
+    let buttons = button_line(&view.form.form, "");
+    frame.render_widget(Paragraph::new(buttons).centered(), chunks[3]);
 }
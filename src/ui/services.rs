@@ -0,0 +1,99 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+};
+
+use crate::app::service_picker::ServicePicker;
+use crate::i18n::{tr, trf};
+use crate::ui::{get_orange_accent, get_orange_color};
+use crate::{external_db, stack};
+
+pub struct ServicePickerView<'a> {
+    pub picker: &'a ServicePicker,
+}
+
+pub fn render_service_picker(frame: &mut Frame, view: &ServicePickerView<'_>) {
+    let picker = view.picker;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Min(5),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ])
+        .split(frame.area());
+
+    let items: Vec<ListItem> = picker
+        .services
+        .iter()
+        .zip(&picker.enabled)
+        .map(|(service, enabled)| {
+            let check = if *enabled { "[x]" } else { "[ ]" };
+            let mut spans = vec![
+                Span::styled(
+                    format!("{check} {:<12}", service.service),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(format!(" {}", service.description)),
+            ];
+            if !service.optional {
+                spans.push(Span::styled(
+                    tr("services.required"),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title(tr("services.block"))
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▶ ");
+    let mut state = ListState::default().with_selected(Some(picker.selected));
+    frame.render_stateful_widget(list, chunks[0], &mut state);
+
+    let highlighted = &picker.services[picker.selected];
+    let stack = stack::current();
+    let note = if highlighted.service == stack.entry_service {
+        trf(
+            "services.entry_note",
+            &[("service", stack.direct.0), ("port", stack.direct.1)],
+        )
+    } else if highlighted.service == external_db::DB_SERVICE {
+        tr("services.db_note").to_string()
+    } else {
+        tr("services.required_note").to_string()
+    };
+    frame.render_widget(
+        Paragraph::new(note)
+            .style(Style::default().fg(Color::DarkGray))
+            .wrap(Wrap { trim: true })
+            .centered(),
+        chunks[1],
+    );
+
+    let help = Paragraph::new(tr("services.keys"))
+        .style(Style::default().fg(Color::DarkGray))
+        .centered();
+    frame.render_widget(help, chunks[2]);
+}
@@ -7,6 +7,7 @@ use ratatui::{
 };
 
 use crate::app::state::SslSetupMenuSelection;
+use crate::i18n::tr;
 use crate::ui::{get_orange_accent, get_orange_color};
 
 pub struct SslSetupView<'a> {
@@ -32,7 +33,7 @@ pub fn render_ssl_setup(frame: &mut Frame, view: &SslSetupView<'_>) {
         .split(area);
 
     // ── Title ──────────────────────────────────────────────────────────────
-    let title = Paragraph::new("🔐  SSL Certificate Setup")
+    let title = Paragraph::new(format!("🔐  {}", tr("ssl.title")))
         .style(
             Style::default()
                 .fg(get_orange_color())
@@ -49,22 +50,22 @@ pub fn render_ssl_setup(frame: &mut Frame, view: &SslSetupView<'_>) {
     // ── Info Block ─────────────────────────────────────────────────────────
     let cert_icon = if view.cert_exists { "✅" } else { "⚠️ " };
     let cert_label = if view.cert_exists {
-        "SSL cert found (certs/server.crt + server.key)"
+        tr("ssl.cert_found")
     } else {
-        "SSL cert NOT found — will be generated"
+        tr("ssl.cert_missing")
     };
 
     let env_icon = if view.env_has_ip { "✅" } else { "⚠️ " };
     let env_label = if view.env_has_ip {
-        "SERVER_IP already set in .env"
+        tr("ssl.ip_set")
     } else {
-        "SERVER_IP not set — will be written to .env"
+        tr("ssl.ip_missing")
     };
 
     let info_lines = vec![
         Line::from(""),
         Line::from(vec![
-            Span::raw("  🌐  Detected IP   : "),
+            Span::raw(format!("  🌐  {:<14}: ", tr("ssl.detected_ip"))),
             Span::styled(
                 view.detected_ip,
                 Style::default()
@@ -87,7 +88,7 @@ pub fn render_ssl_setup(frame: &mut Frame, view: &SslSetupView<'_>) {
         Block::default()
             .borders(Borders::ALL)
             .border_style(Style::default().fg(get_orange_accent()))
-            .title(" Status ")
+            .title(tr("status.title"))
             .title_style(
                 Style::default()
                     .fg(get_orange_color())
@@ -129,20 +130,20 @@ pub fn render_ssl_setup(frame: &mut Frame, view: &SslSetupView<'_>) {
 
     let menu_lines = vec![
         make_item(
-            "Generate SSL Cert & Write .env",
+            tr("ssl.generate"),
             view.menu_selection == &SslSetupMenuSelection::Generate,
         ),
         make_item(
-            "Skip (use existing / no SSL)",
+            tr("ssl.skip"),
             view.menu_selection == &SslSetupMenuSelection::Skip,
         ),
         make_item(
-            "Cancel",
+            tr("menu.cancel"),
             view.menu_selection == &SslSetupMenuSelection::Cancel,
         ),
         Line::from(""),
         Line::from(Span::styled(
-            "  ↑↓ to move   Enter to select   Ctrl+C to quit",
+            format!("  {}", tr("ssl.help")),
             Style::default().fg(Color::DarkGray),
         )),
     ];
@@ -151,7 +152,7 @@ pub fn render_ssl_setup(frame: &mut Frame, view: &SslSetupView<'_>) {
         Block::default()
             .borders(Borders::ALL)
             .border_style(Style::default().fg(get_orange_accent()))
-            .title(" Action ")
+            .title(tr("ssl.action"))
             .title_style(
                 Style::default()
                     .fg(get_orange_color())
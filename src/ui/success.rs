@@ -1,3 +1,5 @@
+use std::path::Path;
+
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout},
@@ -6,10 +8,18 @@ use ratatui::{
     widgets::{Block, Borders, Paragraph},
 };
 
+use crate::app::connection::ConnectionDetails;
+use crate::firewall::FirewallOffer;
+use crate::i18n::{tr, trf};
+use crate::ui::theme::{self, Theme};
 use crate::ui::{get_orange_accent, get_orange_color};
 
 pub struct SuccessView<'a> {
     pub logs: &'a [String],
+    pub log_path: Option<&'a Path>,
+    pub connection: &'a ConnectionDetails,
+    /// Ports an active host firewall still blocks.
+    pub firewall: Option<&'a FirewallOffer>,
 }
 
 pub fn render_success(frame: &mut Frame, view: &SuccessView<'_>) {
@@ -20,13 +30,13 @@ pub fn render_success(frame: &mut Frame, view: &SuccessView<'_>) {
         .margin(2)
         .constraints([
             Constraint::Length(3),
-            Constraint::Min(5),
-            Constraint::Min(10),
+            Constraint::Min(20),
+            Constraint::Min(6),
             Constraint::Length(2),
         ])
         .split(area);
 
-    let title = Paragraph::new("✅ Installation Complete!")
+    let title = Paragraph::new(format!("✅ {}", tr("done.complete")))
         .style(
             Style::default()
                 .fg(Color::Green)
@@ -40,49 +50,110 @@ pub fn render_success(frame: &mut Frame, view: &SuccessView<'_>) {
         .centered();
     frame.render_widget(title, chunks[0]);
 
-    let message = vec![
+    let mut message = vec![
         Line::from(""),
         Line::from(Span::styled(
-            "NQRust Identity has been successfully installed!",
+            tr("done.success"),
             Style::default()
                 .fg(Color::Green)
                 .add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
-        Line::from("Services are now running:"),
+        Line::from(tr("done.services_running")),
+        Line::from(Span::styled(
+            trf("done.database", &[("url", &view.connection.database)]),
+            Style::default().fg(Color::White),
+        )),
         Line::from(Span::styled(
-            "  • identity-db  → PostgreSQL 16 on host port 5436",
+            tr("done.identity"),
             Style::default().fg(Color::White),
         )),
         Line::from(Span::styled(
-            "  • identity     → Identity Console:",
+            format!("    {}", view.connection.url),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::UNDERLINED),
+        )),
+        Line::from(Span::styled(
+            format!("  • {}", tr("done.admin_console")),
             Style::default().fg(Color::White),
         )),
         Line::from(Span::styled(
-            "    https://localhost:8008",
+            format!("    {}", view.connection.admin_console),
             Style::default()
                 .fg(Color::Cyan)
                 .add_modifier(Modifier::UNDERLINED),
         )),
         Line::from(""),
         Line::from(Span::styled(
-            "  Default admin credentials:  admin / admin",
+            format!(
+                "  {}",
+                trf(
+                    "done.admin_login",
+                    &[
+                        ("user", &view.connection.admin_user),
+                        ("password", &view.connection.admin_password_hint),
+                    ],
+                )
+            ),
             Style::default().fg(Color::Yellow),
         )),
         Line::from(Span::styled(
-            "  ⚠  Please change the admin password after first login!",
+            format!("  {}", tr("done.change_password")),
             Style::default()
                 .fg(Color::Yellow)
                 .add_modifier(Modifier::BOLD),
         )),
     ];
+    if let Some(offer) = view.firewall {
+        message.push(Line::from(""));
+        message.push(Line::from(Span::styled(
+            trf(
+                "done.firewall",
+                &[
+                    ("firewall", offer.firewall.label()),
+                    ("ports", &offer.port_list()),
+                ],
+            ),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )));
+    }
+    if let Some(path) = view.log_path {
+        message.push(Line::from(""));
+        message.push(Line::from(Span::styled(
+            trf("done.install_log", &[("path", &path.display().to_string())]),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    // The QR code goes beside the details when the terminal is wide enough.
+    // Plain mode would blank its block glyphs, so it is left out there.
+    let plain = theme::current() == Theme::Plain;
+    let qr = view.connection.qr.as_deref().filter(|qr| {
+        let width = qr.lines().map(|l| l.chars().count()).max().unwrap_or(0) as u16;
+        let height = qr.lines().count() as u16;
+        !plain && chunks[1].width >= width * 3 && chunks[1].height >= height + 2
+    });
+    let (details_area, qr_area) = match qr {
+        Some(qr) => {
+            let width = qr.lines().map(|l| l.chars().count()).max().unwrap_or(0) as u16;
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Min(0), Constraint::Length(width + 2)])
+                .split(chunks[1]);
+            (columns[0], Some(columns[1]))
+        }
+        None => (chunks[1], None),
+    };
 
     let message_widget = Paragraph::new(message)
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(get_orange_accent()))
-                .title("Success")
+                .title(tr("done.success_title"))
                 .title_style(
                     Style::default()
                         .fg(get_orange_color())
@@ -90,7 +161,22 @@ pub fn render_success(frame: &mut Frame, view: &SuccessView<'_>) {
                 ),
         )
         .centered();
-    frame.render_widget(message_widget, chunks[1]);
+    frame.render_widget(message_widget, details_area);
+
+    if let (Some(qr), Some(area)) = (qr, qr_area) {
+        let qr_widget = Paragraph::new(qr).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title(tr("done.scan_title"))
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        );
+        frame.render_widget(qr_widget, area);
+    }
 
     let log_lines: Vec<Line> = view
         .logs
@@ -105,7 +191,7 @@ pub fn render_success(frame: &mut Frame, view: &SuccessView<'_>) {
         Block::default()
             .borders(Borders::ALL)
             .border_style(Style::default().fg(get_orange_accent()))
-            .title("Installation Summary")
+            .title(tr("done.summary_title"))
             .title_style(
                 Style::default()
                     .fg(get_orange_color())
@@ -114,7 +200,7 @@ pub fn render_success(frame: &mut Frame, view: &SuccessView<'_>) {
     );
     frame.render_widget(logs_widget, chunks[2]);
 
-    let help = Paragraph::new("Press Ctrl+C to exit")
+    let help = Paragraph::new(tr("done.exit_hint"))
         .style(Style::default().fg(Color::DarkGray))
         .centered();
     frame.render_widget(help, chunks[3]);
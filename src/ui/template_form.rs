@@ -0,0 +1,136 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::app::template_form::TemplateForm;
+use crate::i18n::{tr, trf};
+use crate::ui::form::{button_line, field_lines, field_scroll};
+use crate::ui::{get_orange_accent, get_orange_color};
+
+pub struct TemplateFormView<'a> {
+    pub form: &'a TemplateForm,
+}
+
+pub fn render_template_form(frame: &mut Frame, view: &TemplateFormView<'_>) {
+    let area = frame.area();
+    let form = view.form;
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(6),
+            Constraint::Length(3 + form.checks.len() as u16),
+            Constraint::Length(2),
+        ])
+        .split(area);
+
+    let header = Paragraph::new(format!(
+        "🧩 {} → {}",
+        form.template.name,
+        form.template.output.display()
+    ))
+    .style(
+        Style::default()
+            .fg(get_orange_color())
+            .add_modifier(Modifier::BOLD),
+    )
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(get_orange_accent())),
+    )
+    .centered();
+    frame.render_widget(header, chunks[0]);
+
+    let body = if form.form.fields.is_empty() {
+        Paragraph::new(tr("template_form.empty"))
+    } else {
+        Paragraph::new(field_lines(&form.form, " : "))
+            .scroll((field_scroll(&form.form, chunks[1].height), 0))
+    };
+    let form_block = body.block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(get_orange_accent()))
+            .title(if form.template.description.is_empty() {
+                tr("template_form.block").to_string()
+            } else {
+                form.template.description.clone()
+            })
+            .title_style(
+                Style::default()
+                    .fg(get_orange_color())
+                    .add_modifier(Modifier::BOLD),
+            ),
+    );
+    frame.render_widget(form_block, chunks[1]);
+
+    let (status_message, status_style) = if !form.form.error_message.is_empty() {
+        (
+            form.form.error_message.clone(),
+            Style::default().fg(Color::Red),
+        )
+    } else if let Some(status) = &form.status {
+        (status.clone(), Style::default().fg(Color::Green))
+    } else if let Some(problem) = form.gpu.as_ref().and_then(|gpu| gpu.problem()) {
+        (
+            trf(
+                "template_form.gpu_problem",
+                &[
+                    ("problem", problem),
+                    ("services", &form.template.gpu_services.join(", ")),
+                ],
+            ),
+            Style::default().fg(Color::Red),
+        )
+    } else if let Some(gpu) = &form.gpu {
+        (
+            trf(
+                "template_form.gpu_reserve",
+                &[
+                    ("gpu", &gpu.names.join(", ")),
+                    ("services", &form.template.gpu_services.join(", ")),
+                ],
+            ),
+            Style::default().fg(Color::Yellow),
+        )
+    } else {
+        (
+            trf(
+                "template_form.saving",
+                &[("file", &form.template.output.display().to_string())],
+            ),
+            Style::default().fg(Color::Yellow),
+        )
+    };
+    let mut status_lines: Vec<Line> = form
+        .checks
+        .iter()
+        .map(|check| {
+            let color = if check.starts_with('✗') {
+                Color::Red
+            } else {
+                Color::Green
+            };
+            Line::from(Span::styled(check.clone(), Style::default().fg(color)))
+        })
+        .collect();
+    status_lines.push(Line::from(Span::styled(status_message, status_style)));
+    frame.render_widget(
+        Paragraph::new(status_lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(tr("template_form.status")),
+        ),
+        chunks[2],
+    );
+
+    let buttons = button_line(&form.form, tr("template_form.keys"));
+    frame.render_widget(Paragraph::new(buttons).centered(), chunks[3]);
+}
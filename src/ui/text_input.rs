@@ -0,0 +1,41 @@
+use ratatui::{
+    style::{Modifier, Style},
+    text::Span,
+};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::app::text_input::TextInput;
+
+/// Spans for a text field: its (possibly masked) value, or `placeholder` when
+/// empty, with a block cursor while focused.
+pub fn text_input_spans(
+    input: &TextInput,
+    focused: bool,
+    placeholder: &str,
+    style: Style,
+) -> Vec<Span<'static>> {
+    let cursor_style = style.add_modifier(Modifier::REVERSED);
+    if input.is_empty() {
+        let mut spans = Vec::new();
+        if focused {
+            spans.push(Span::styled(" ", cursor_style));
+        }
+        spans.push(Span::styled(placeholder.to_string(), style));
+        return spans;
+    }
+    let display = input.display();
+    if !focused {
+        return vec![Span::styled(display, style)];
+    }
+    // The block cursor covers a whole grapheme, as wide as it is drawn.
+    let shown: Vec<&str> = display.graphemes(true).collect();
+    let at = input.cursor().min(shown.len());
+    let before = shown[..at].concat();
+    let under = shown.get(at).copied().unwrap_or(" ").to_string();
+    let after = shown.get(at + 1..).unwrap_or_default().concat();
+    vec![
+        Span::styled(before, style),
+        Span::styled(under, cursor_style),
+        Span::styled(after, style),
+    ]
+}
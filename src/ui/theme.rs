@@ -0,0 +1,277 @@
+// theme.rs - colour palettes for dark, light and colourless terminals
+// Views are written against the dark palette. Once a frame is drawn,
+// `Theme::apply` maps its colours to the active theme, so no view needs to
+// know which one is in use; the accent colours come from here directly.
+//
+// `--plain` goes further for screen readers and braille displays: frames,
+// emoji and spinners are blanked or spelled in ASCII, the selected row gets
+// a `>` where colour alone marked it, and the terminal cursor is moved onto
+// the focus so the reader follows it. Screens with block art (the success
+// screen's QR code) leave it out, and the last log is printed to the terminal
+// on exit.
+
+use std::sync::OnceLock;
+
+use ratatui::buffer::{Buffer, Cell};
+use ratatui::layout::Position;
+use ratatui::style::{Color, Modifier};
+
+use crate::cli::ThemeChoice;
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Theme {
+    Dark,
+    Light,
+    /// No colours at all; highlights become reverse video.
+    Mono,
+    /// Mono without box drawing or emoji, for screen readers (`--plain`).
+    Plain,
+}
+
+impl Theme {
+    /// Resolve `choice` given the `NO_COLOR` and `COLORFGBG` variables. An
+    /// explicit choice wins; without a hint the dark palette is used.
+    pub fn detect(choice: ThemeChoice, no_color: Option<&str>, colorfgbg: Option<&str>) -> Self {
+        match choice {
+            ThemeChoice::Dark => Theme::Dark,
+            ThemeChoice::Light => Theme::Light,
+            ThemeChoice::Mono => Theme::Mono,
+            ThemeChoice::Auto if no_color.is_some_and(|v| !v.is_empty()) => Theme::Mono,
+            // COLORFGBG is "fg;bg" (sometimes "fg;default;bg"); backgrounds 7
+            // and 9-15 are the light ANSI colours.
+            ThemeChoice::Auto => match colorfgbg
+                .and_then(|v| v.rsplit(';').next())
+                .and_then(|bg| bg.parse::<u8>().ok())
+            {
+                Some(7 | 9..=15) => Theme::Light,
+                _ => Theme::Dark,
+            },
+        }
+    }
+
+    pub fn accent(self) -> Color {
+        match self {
+            Theme::Dark => Color::Rgb(255, 165, 0),
+            Theme::Light => Color::Rgb(190, 90, 0),
+            Theme::Mono | Theme::Plain => Color::Reset,
+        }
+    }
+
+    /// Borders and secondary highlights.
+    pub fn accent_dim(self) -> Color {
+        match self {
+            Theme::Dark => Color::Rgb(255, 140, 0),
+            Theme::Light => Color::Rgb(160, 75, 0),
+            Theme::Mono | Theme::Plain => Color::Reset,
+        }
+    }
+
+    /// Foreground `fg` as it should look on a cell with background `bg`.
+    /// Text on an explicit background keeps its colour: that pair was
+    /// chosen for contrast already.
+    fn map_fg(self, fg: Color, bg: Color) -> Color {
+        match (self, fg) {
+            (Theme::Dark, fg) => fg,
+            (Theme::Mono | Theme::Plain, _) => Color::Reset,
+            (Theme::Light, fg) if bg != Color::Reset => fg,
+            (Theme::Light, Color::White) => Color::Black,
+            (Theme::Light, Color::Gray) => Color::DarkGray,
+            (Theme::Light, Color::Yellow) => Color::Rgb(150, 100, 0),
+            (Theme::Light, Color::Cyan) => Color::Blue,
+            (Theme::Light, Color::Green) => Color::Rgb(0, 120, 0),
+            (Theme::Light, fg) => fg,
+        }
+    }
+
+    /// Recolour a drawn frame for this theme. In plain mode, also returns
+    /// where the terminal cursor should sit.
+    pub fn apply(self, buffer: &mut Buffer) -> Option<Position> {
+        match self {
+            Theme::Dark => return None,
+            Theme::Plain => return flatten(buffer),
+            Theme::Light | Theme::Mono => {}
+        }
+        for cell in &mut buffer.content {
+            cell.fg = self.map_fg(cell.fg, cell.bg);
+            if self == Theme::Mono && cell.bg != Color::Reset {
+                cell.bg = Color::Reset;
+                cell.modifier |= Modifier::REVERSED;
+            }
+        }
+        None
+    }
+}
+
+/// ASCII stand-in for a symbol a screen reader or braille display would
+/// garble: box drawing, block art, braille spinners and emoji. `None` keeps
+/// the symbol as it is.
+fn plain_symbol(symbol: &str) -> Option<&'static str> {
+    let symbol = symbol.trim_end_matches('\u{fe0f}');
+    let replacement = match symbol {
+        "✓" => "+",
+        "✗" => "x",
+        "✅" => "ok",
+        "❌" => "no",
+        "⚠" => "!",
+        "•" => "*",
+        "⬆" => "^",
+        _ if symbol.chars().any(|c| {
+            matches!(c as u32,
+                0x2500..=0x259F      // box drawing, block elements
+                | 0x25B6             // ▶, replaced by the `>` marker
+                | 0x2600..=0x27BF    // dingbats
+                | 0x2800..=0x28FF    // braille patterns (spinners)
+                | 0x2B00..=0x2BFF
+                | 0x1F000..)
+        }) =>
+        {
+            " "
+        }
+        _ => return None,
+    };
+    Some(replacement)
+}
+
+/// The plain theme: strip colour and symbols, mark the selection with `>`
+/// and return the cursor position, which is the text cursor of a focused
+/// field or else the selection marker.
+fn flatten(buffer: &mut Buffer) -> Option<Position> {
+    let area = buffer.area;
+    let highlighted =
+        |cell: &Cell| cell.bg != Color::Reset || cell.modifier.contains(Modifier::REVERSED);
+    let mut caret = None;
+    let mut selection = None;
+    for y in area.top()..area.bottom() {
+        let mut x = area.left();
+        while x < area.right() {
+            if !highlighted(&buffer[(x, y)]) {
+                x += 1;
+                continue;
+            }
+            let start = x;
+            let reversed = |cell: &Cell| cell.modifier.contains(Modifier::REVERSED);
+            let run_reversed = reversed(&buffer[(start, y)]);
+            while x < area.right()
+                && highlighted(&buffer[(x, y)])
+                && reversed(&buffer[(x, y)]) == run_reversed
+            {
+                x += 1;
+            }
+            if x - start == 1 && run_reversed {
+                // A one-cell reverse-video run is a text field's cursor.
+                caret = caret.or(Some(Position::new(start, y)));
+            } else if x - start > 3 && selection.is_none() {
+                // Shorter runs are key badges such as ` I `, not a selection.
+                selection = Some(Position::new(start, y));
+            }
+        }
+    }
+
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            let Some(text) = plain_symbol(buffer[(x, y)].symbol()) else {
+                continue;
+            };
+            // A wide emoji is followed by a blank cell its replacement may use.
+            for (i, c) in text.chars().enumerate() {
+                let at = x + i as u16;
+                if at < area.right() && (i == 0 || buffer[(at, y)].symbol() == " ") {
+                    buffer[(at, y)].set_char(c);
+                }
+            }
+        }
+    }
+    for cell in &mut buffer.content {
+        cell.fg = Color::Reset;
+        cell.bg = Color::Reset;
+        cell.modifier = Modifier::empty();
+    }
+    if let Some(at) = selection
+        && buffer[at].symbol() == " "
+    {
+        buffer[at].set_char('>');
+    }
+    caret.or(selection)
+}
+
+/// Pick the theme for the rest of the run from `--theme`, `--plain` and
+/// the environment. Only the first call counts.
+pub fn init(choice: ThemeChoice, plain: bool) {
+    let no_color = std::env::var("NO_COLOR").ok();
+    let colorfgbg = std::env::var("COLORFGBG").ok();
+    let theme = if plain {
+        Theme::Plain
+    } else {
+        Theme::detect(choice, no_color.as_deref(), colorfgbg.as_deref())
+    };
+    let _ = THEME.set(theme);
+}
+
+pub fn current() -> Theme {
+    THEME.get().copied().unwrap_or(Theme::Dark)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::layout::Rect;
+    use ratatui::style::Style;
+
+    #[test]
+    fn test_detect_and_apply() {
+        assert_eq!(
+            Theme::detect(ThemeChoice::Auto, Some("1"), Some("0;15")),
+            Theme::Mono
+        );
+        assert_eq!(
+            Theme::detect(ThemeChoice::Auto, Some(""), Some("0;default;15")),
+            Theme::Light
+        );
+        assert_eq!(
+            Theme::detect(ThemeChoice::Auto, None, Some("15;0")),
+            Theme::Dark
+        );
+        assert_eq!(
+            Theme::detect(ThemeChoice::Dark, Some("1"), None),
+            Theme::Dark
+        );
+
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 2, 1));
+        buffer.set_string(0, 0, "a", Style::default().fg(Color::White));
+        buffer.set_string(1, 0, "b", Style::default().fg(Color::White).bg(Color::Blue));
+        let mut light = buffer.clone();
+        Theme::Light.apply(&mut light);
+        assert_eq!(light.content[0].fg, Color::Black);
+        assert_eq!(light.content[1].fg, Color::White);
+
+        Theme::Mono.apply(&mut buffer);
+        assert_eq!(buffer.content[1].fg, Color::Reset);
+        assert_eq!(buffer.content[1].bg, Color::Reset);
+        assert!(buffer.content[1].modifier.contains(Modifier::REVERSED));
+    }
+
+    #[test]
+    fn test_plain_marks_selection_and_spells_symbols() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 12, 3));
+        let selected = Style::default().fg(Color::Black).bg(Color::Green);
+        buffer.set_string(0, 0, "┌──────────┐", Style::default());
+        buffer.set_string(0, 1, "  ▶  Cancel", selected);
+        buffer.set_string(0, 2, "✅ ready ⠋", Style::default().fg(Color::Green));
+
+        let cursor = Theme::Plain.apply(&mut buffer);
+        let row = |y: u16| (0..12).map(|x| buffer[(x, y)].symbol()).collect::<String>();
+        assert_eq!(row(0).trim(), "");
+        assert_eq!(row(1), ">    Cancel ");
+        assert_eq!(row(2), "ok ready    ");
+        assert_eq!(cursor, Some(Position::new(0, 1)));
+        assert!(buffer.content.iter().all(|c| c.bg == Color::Reset));
+
+        // A focused field's text cursor wins over the selection.
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 8, 1));
+        buffer.set_string(0, 0, "> ab", selected);
+        buffer.set_string(4, 0, " ", selected.add_modifier(Modifier::REVERSED));
+        assert_eq!(Theme::Plain.apply(&mut buffer), Some(Position::new(4, 0)));
+    }
+}
@@ -0,0 +1,51 @@
+use ratatui::{
+    Frame,
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Clear, Paragraph, Wrap},
+};
+
+use crate::i18n::{tr, trf};
+use crate::ui::get_orange_color;
+
+/// Smallest terminal every screen is laid out for: the template grid and
+/// the log panes overlap or clip below it.
+pub const MIN_WIDTH: u16 = 100;
+pub const MIN_HEIGHT: u16 = 30;
+
+pub fn fits(area: Rect) -> bool {
+    area.width >= MIN_WIDTH && area.height >= MIN_HEIGHT
+}
+
+/// Drawn instead of the current screen while the terminal is below the
+/// minimum; the screen comes back as soon as it is large enough.
+pub fn render_too_small(frame: &mut Frame) {
+    let area = frame.area();
+    frame.render_widget(Clear, area);
+    let (width, height) = (MIN_WIDTH.to_string(), MIN_HEIGHT.to_string());
+    let (cols, rows) = (area.width.to_string(), area.height.to_string());
+    let lines = vec![
+        Line::styled(
+            trf("too_small.title", &[("width", &width), ("height", &height)]),
+            Style::default()
+                .fg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        ),
+        Line::from(trf("too_small.size", &[("cols", &cols), ("rows", &rows)])),
+        Line::styled(tr("too_small.quit"), Style::default().fg(Color::DarkGray)),
+    ];
+    // Vertically centered when there is room; wrapped from the top otherwise.
+    let top = area.height.saturating_sub(lines.len() as u16) / 2;
+    let text_area = Rect {
+        y: area.y + top,
+        height: area.height - top,
+        ..area
+    };
+    frame.render_widget(
+        Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true }),
+        text_area,
+    );
+}
@@ -1,22 +1,39 @@
+use std::collections::BTreeSet;
+
 use chrono::{DateTime, Utc};
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Cell, Paragraph, Row, Table, Wrap},
+    widgets::{
+        Block, Borders, Cell, Clear, List, ListItem, ListState, Paragraph, Row, Table, Wrap,
+    },
 };
 
 use crate::app::UpdateInfo;
-use crate::ui::{get_orange_accent, get_orange_color};
+use crate::i18n::{tr, trf};
+use crate::ui::{centered_rect, get_orange_accent, get_orange_color};
 
 pub struct UpdateListView<'a> {
     pub updates: &'a [UpdateInfo],
     pub selected_index: usize,
+    /// Rows marked for a batch pull.
+    pub marked: &'a BTreeSet<usize>,
     pub message: Option<&'a str>,
     pub logs: &'a [String],
     pub pulling: bool,
     pub progress: Option<f64>,
+    /// Release notes for the selected row, scrolled by `notes_scroll` lines.
+    pub release_notes: Option<&'a str>,
+    pub notes_scroll: u16,
+    pub tag_picker: Option<TagPickerView<'a>>,
+}
+
+pub struct TagPickerView<'a> {
+    pub tags: &'a [String],
+    /// 0 = "follow latest release", n = tags[n - 1]
+    pub selected: usize,
 }
 
 pub fn render_update_list(frame: &mut Frame, view: &UpdateListView<'_>) {
@@ -33,10 +50,12 @@ pub fn render_update_list(frame: &mut Frame, view: &UpdateListView<'_>) {
         ])
         .split(area);
 
-    let title_text = if view.pulling {
-        "🔄 Pulling selected image..."
+    let title_text = if view.pulling && view.progress.is_some() {
+        tr("update.pulling_marked")
+    } else if view.pulling {
+        tr("update.pulling_selected")
     } else {
-        "🚀 Check for Updates"
+        tr("update.title")
     };
 
     let title = Paragraph::new(title_text)
@@ -54,12 +73,12 @@ pub fn render_update_list(frame: &mut Frame, view: &UpdateListView<'_>) {
     frame.render_widget(title, chunks[0]);
 
     if view.updates.is_empty() {
-        let placeholder = Paragraph::new("No GHCR-backed services found")
+        let placeholder = Paragraph::new(tr("update.no_services"))
             .block(
                 Block::default()
                     .borders(Borders::ALL)
                     .border_style(Style::default().fg(get_orange_accent()))
-                    .title("Services")
+                    .title(tr("update.services_title"))
                     .title_style(
                         Style::default()
                             .fg(get_orange_color())
@@ -70,12 +89,13 @@ pub fn render_update_list(frame: &mut Frame, view: &UpdateListView<'_>) {
         frame.render_widget(placeholder, chunks[1]);
     } else {
         let header = Row::new(vec![
-            Cell::from("Service").style(header_style()),
-            Cell::from("Current Tag").style(header_style()),
-            Cell::from("Latest Release").style(header_style()),
-            Cell::from("Remote Updated").style(header_style()),
-            Cell::from("Local Image").style(header_style()),
-            Cell::from("Status").style(header_style()),
+            Cell::from(""),
+            Cell::from(tr("update.col_service")).style(header_style()),
+            Cell::from(tr("update.col_current")).style(header_style()),
+            Cell::from(tr("update.col_latest")).style(header_style()),
+            Cell::from(tr("update.col_remote_updated")).style(header_style()),
+            Cell::from(tr("update.col_local")).style(header_style()),
+            Cell::from(tr("update.col_status")).style(header_style()),
         ]);
 
         let rows: Vec<Row> = view
@@ -96,8 +116,18 @@ pub fn render_update_list(frame: &mut Frame, view: &UpdateListView<'_>) {
                 }
 
                 Row::new(vec![
+                    Cell::from(if view.marked.contains(&idx) {
+                        "[x]"
+                    } else if info.batchable() {
+                        "[ ]"
+                    } else {
+                        ""
+                    }),
                     Cell::from(info.display_name.clone()),
-                    Cell::from(info.current_tag.clone()),
+                    Cell::from(match &info.pinned_tag {
+                        Some(pin) => format!("📌 {pin}"),
+                        None => info.current_tag.clone(),
+                    }),
                     Cell::from(
                         info.latest_release_tag
                             .clone()
@@ -105,7 +135,7 @@ pub fn render_update_list(frame: &mut Frame, view: &UpdateListView<'_>) {
                     ),
                     Cell::from(format_time(info.remote_latest_updated)),
                     Cell::from(format_time(info.local_created)),
-                    Cell::from(status_text(info)),
+                    Cell::from(status_badge(info)),
                 ])
                 .style(style)
             })
@@ -114,6 +144,7 @@ pub fn render_update_list(frame: &mut Frame, view: &UpdateListView<'_>) {
         let table = Table::new(
             rows,
             [
+                Constraint::Length(3),
                 Constraint::Length(24),
                 Constraint::Length(12),
                 Constraint::Length(18),
@@ -127,7 +158,7 @@ pub fn render_update_list(frame: &mut Frame, view: &UpdateListView<'_>) {
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(get_orange_accent()))
-                .title("Services")
+                .title(tr("update.services_title"))
                 .title_style(
                     Style::default()
                         .fg(get_orange_color())
@@ -139,9 +170,15 @@ pub fn render_update_list(frame: &mut Frame, view: &UpdateListView<'_>) {
         frame.render_widget(table, chunks[1]);
     }
 
-    let message_text = view
-        .message
-        .unwrap_or("Enter/P: pull image or self-update installer | R: refresh | Esc: back");
+    let marked_hint = trf(
+        "update.keys_marked",
+        &[("count", &view.marked.len().to_string())],
+    );
+    let message_text = view.message.unwrap_or(if view.marked.is_empty() {
+        tr("update.keys")
+    } else {
+        &marked_hint
+    });
 
     let message = Paragraph::new(message_text)
         .style(Style::default().fg(Color::Gray))
@@ -149,7 +186,7 @@ pub fn render_update_list(frame: &mut Frame, view: &UpdateListView<'_>) {
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(get_orange_accent()))
-                .title("Instructions")
+                .title(tr("update.instructions"))
                 .title_style(
                     Style::default()
                         .fg(get_orange_color())
@@ -159,9 +196,16 @@ pub fn render_update_list(frame: &mut Frame, view: &UpdateListView<'_>) {
         .wrap(Wrap { trim: true });
     frame.render_widget(message, chunks[2]);
 
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[3]);
+    render_release_notes(frame, view, bottom[0]);
+    let logs_area = bottom[1];
+
     let mut log_lines: Vec<Line> = if view.logs.is_empty() {
         vec![Line::from(Span::styled(
-            "No recent docker operations",
+            tr("update.no_logs"),
             Style::default().fg(Color::DarkGray),
         ))]
     } else {
@@ -184,19 +228,25 @@ pub fn render_update_list(frame: &mut Frame, view: &UpdateListView<'_>) {
     };
 
     // Prepend a simple progress bar when pulling and a value is provided.
-    if view.pulling {
-        if let Some(pct) = view.progress {
-            let pct = pct.clamp(0.0, 100.0);
-            let bar_space = chunks[3].width.saturating_sub(12) as usize;
-            let filled_width = ((bar_space as f64) * (pct / 100.0)).round() as usize;
-            let filled = "█".repeat(filled_width.min(bar_space));
-            let empty = "░".repeat(bar_space.saturating_sub(filled.len()));
-            let bar = format!("Progress: [{filled}{empty}] {pct:.0}%");
-            log_lines.insert(
-                0,
-                Line::from(Span::styled(bar, Style::default().fg(get_orange_color()))),
-            );
-        }
+    if view.pulling
+        && let Some(pct) = view.progress
+    {
+        let pct = pct.clamp(0.0, 100.0);
+        let bar_space = logs_area.width.saturating_sub(12) as usize;
+        let filled_width = ((bar_space as f64) * (pct / 100.0)).round() as usize;
+        let filled = "█".repeat(filled_width.min(bar_space));
+        let empty = "░".repeat(bar_space.saturating_sub(filled.len()));
+        let bar = trf(
+            "update.progress",
+            &[
+                ("bar", &format!("[{filled}{empty}]")),
+                ("pct", &format!("{pct:.0}")),
+            ],
+        );
+        log_lines.insert(
+            0,
+            Line::from(Span::styled(bar, Style::default().fg(get_orange_color()))),
+        );
     }
 
     let logs_widget = Paragraph::new(log_lines)
@@ -204,7 +254,7 @@ pub fn render_update_list(frame: &mut Frame, view: &UpdateListView<'_>) {
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(get_orange_accent()))
-                .title("Logs")
+                .title(tr("update.logs_title"))
                 .title_style(
                     Style::default()
                         .fg(get_orange_color())
@@ -215,10 +265,88 @@ pub fn render_update_list(frame: &mut Frame, view: &UpdateListView<'_>) {
         .scroll((
             view.logs
                 .len()
-                .saturating_sub(chunks[3].height as usize - 2) as u16,
+                .saturating_sub(logs_area.height as usize - 2) as u16,
             0,
         ));
-    frame.render_widget(logs_widget, chunks[3]);
+    frame.render_widget(logs_widget, logs_area);
+
+    if let Some(picker) = &view.tag_picker {
+        render_tag_picker(frame, picker);
+    }
+}
+
+fn render_release_notes(frame: &mut Frame, view: &UpdateListView<'_>, area: Rect) {
+    let lines: Vec<Line> = match view.release_notes {
+        Some(notes) => notes
+            .lines()
+            .map(|line| {
+                if line.starts_with('#') {
+                    Line::from(Span::styled(
+                        line.trim_start_matches('#').trim().to_string(),
+                        Style::default()
+                            .fg(get_orange_color())
+                            .add_modifier(Modifier::BOLD),
+                    ))
+                } else {
+                    Line::from(line.to_string())
+                }
+            })
+            .collect(),
+        None => vec![Line::from(Span::styled(
+            tr("update.no_notes"),
+            Style::default().fg(Color::DarkGray),
+        ))],
+    };
+
+    let notes = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title(tr("update.notes_title"))
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .wrap(Wrap { trim: false })
+        .scroll((view.notes_scroll, 0));
+    frame.render_widget(notes, area);
+}
+
+fn render_tag_picker(frame: &mut Frame, picker: &TagPickerView<'_>) {
+    let height = (picker.tags.len() as u16 + 3).clamp(5, 20);
+    let area = centered_rect(40, height, frame.area());
+    frame.render_widget(Clear, area);
+
+    let items: Vec<ListItem> = std::iter::once(tr("update.unpin").to_string())
+        .chain(picker.tags.iter().cloned())
+        .map(ListItem::new)
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent()))
+                .title(tr("update.pin_title"))
+                .title_style(
+                    Style::default()
+                        .fg(get_orange_color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▶ ");
+
+    let mut state = ListState::default().with_selected(Some(picker.selected));
+    frame.render_stateful_widget(list, area, &mut state);
 }
 
 fn header_style() -> Style {
@@ -227,14 +355,34 @@ fn header_style() -> Style {
         .add_modifier(Modifier::BOLD)
 }
 
-fn status_text(info: &UpdateInfo) -> String {
+/// Status cell: an explicit badge, plus whether it was decided by digest
+/// (exact) or by creation timestamps (best effort).
+fn status_badge(info: &UpdateInfo) -> Line<'static> {
     if let Some(note) = &info.status_note {
-        note.clone()
-    } else if info.has_update {
-        "Update available".to_string()
+        return Line::from(note.clone());
+    }
+
+    let (label, color) = if info.has_update {
+        (tr("update.badge_available"), Color::Yellow)
     } else {
-        "Up to date".to_string()
+        (tr("update.badge_current"), Color::Green)
+    };
+    let mut spans = vec![Span::styled(
+        label,
+        Style::default()
+            .fg(Color::Black)
+            .bg(color)
+            .add_modifier(Modifier::BOLD),
+    )];
+    if !info.is_self {
+        let basis = if info.digest_checked() {
+            tr("update.by_digest")
+        } else {
+            tr("update.by_date")
+        };
+        spans.push(Span::styled(basis, Style::default().fg(Color::DarkGray)));
     }
+    Line::from(spans)
 }
 
 fn format_time(value: Option<DateTime<Utc>>) -> String {
@@ -0,0 +1,90 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::app::yaml_form::YamlForm;
+use crate::i18n::{tr, trf};
+use crate::ui::form::{button_line, field_lines, field_scroll};
+use crate::ui::{get_orange_accent, get_orange_color};
+
+pub struct YamlEditorView<'a> {
+    pub form: &'a YamlForm,
+}
+
+pub fn render_yaml_editor(frame: &mut Frame, view: &YamlEditorView<'_>) {
+    let area = frame.area();
+    let form = &view.form.form;
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(6),
+            Constraint::Length(3),
+            Constraint::Length(2),
+        ])
+        .split(area);
+
+    let file_name = view
+        .form
+        .path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let header = Paragraph::new(trf("yaml_editor.title", &[("file", &file_name)]))
+        .style(
+            Style::default()
+                .fg(get_orange_color())
+                .add_modifier(Modifier::BOLD),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(get_orange_accent())),
+        )
+        .centered();
+    frame.render_widget(header, chunks[0]);
+
+    // Secrets stay masked until Ctrl+R reveals them.
+    let body = if form.fields.is_empty() {
+        Paragraph::new(trf("yaml_editor.empty", &[("file", &file_name)]))
+    } else {
+        Paragraph::new(field_lines(form, " = ")).scroll((field_scroll(form, chunks[1].height), 0))
+    };
+    let form_block = body.block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(get_orange_accent()))
+            .title(trf(
+                "yaml_editor.values_count",
+                &[("count", &form.fields.len().to_string())],
+            ))
+            .title_style(
+                Style::default()
+                    .fg(get_orange_color())
+                    .add_modifier(Modifier::BOLD),
+            ),
+    );
+    frame.render_widget(form_block, chunks[1]);
+
+    let (status_message, status_style) = if !form.error_message.is_empty() {
+        (form.error_message.as_str(), Style::default().fg(Color::Red))
+    } else {
+        (tr("yaml_editor.hint"), Style::default().fg(Color::Yellow))
+    };
+    frame.render_widget(
+        Paragraph::new(status_message).style(status_style).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(tr("yaml_editor.status")),
+        ),
+        chunks[2],
+    );
+
+    let buttons = button_line(form, tr("yaml_editor.keys"));
+    frame.render_widget(Paragraph::new(buttons).centered(), chunks[3]);
+}
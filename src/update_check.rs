@@ -0,0 +1,135 @@
+// update_check.rs - scheduled update checks
+// `--check-updates-daemon` repeats the update list lookup on an interval and
+// writes the outcome to a status file next to the compose bundle. The TUI
+// reads that file to show an "updates available" badge without hitting the
+// network on startup.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::{Result, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::cli::Cli;
+use crate::install_log::logln;
+use crate::registry::Registry;
+use crate::runner::SystemRunner;
+use crate::self_update::PackageFormat;
+use crate::settings::Settings;
+use crate::updates::{UpdateInfo, collect_update_infos};
+use crate::{http, utils, xdg};
+
+/// Where earlier versions kept it, in the project directory.
+const LEGACY_STATUS_FILE: &str = ".nqrust-update-status.json";
+
+/// Result of the last background check.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateStatus {
+    pub checked_at: DateTime<Utc>,
+    pub available: Vec<AvailableUpdate>,
+    /// Why the last check failed; `available` is then empty.
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvailableUpdate {
+    pub name: String,
+    pub current: String,
+    pub latest: String,
+}
+
+impl UpdateStatus {
+    pub fn from_infos(infos: &[UpdateInfo]) -> Self {
+        Self {
+            checked_at: Utc::now(),
+            available: infos
+                .iter()
+                .filter(|info| info.has_update)
+                .map(|info| AvailableUpdate {
+                    name: info.display_name.clone(),
+                    current: info.current_tag.clone(),
+                    latest: info.target_tag().to_string(),
+                })
+                .collect(),
+            error: None,
+        }
+    }
+
+    fn path(root: &Path) -> PathBuf {
+        xdg::adopt(
+            &root.join(LEGACY_STATUS_FILE),
+            &xdg::project_state(root).join("update-status.json"),
+        )
+    }
+
+    pub fn load(root: &Path) -> Option<Self> {
+        fs::read_to_string(Self::path(root))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+    }
+
+    pub fn save(&self, root: &Path) -> Result<()> {
+        fs::write(Self::path(root), serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(())
+    }
+}
+
+/// One update lookup with the registry credential and proxy from `settings`.
+/// Failures end up in `UpdateStatus::error`.
+pub async fn check_once(settings: &Settings, no_root: bool) -> UpdateStatus {
+    let client = http::client(false, &settings.proxy);
+    let token = Registry::ghcr().credential();
+    match collect_update_infos(
+        &SystemRunner,
+        &client,
+        token.as_deref(),
+        &settings.pinned_tags,
+        &settings.update_sources,
+        PackageFormat::detect(&SystemRunner, no_root).await,
+    )
+    .await
+    {
+        Ok(infos) => UpdateStatus::from_infos(&infos),
+        Err(e) => UpdateStatus {
+            checked_at: Utc::now(),
+            available: Vec::new(),
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+/// Check for updates every `update_check_interval_hours` until killed.
+pub async fn run_daemon(cli: &Cli) -> Result<()> {
+    if cli.airgapped() {
+        bail!("Update checks need network access and are not available offline");
+    }
+    let root = utils::project_root();
+    loop {
+        // Re-read each round so interval and proxy changes apply without a restart.
+        let settings = Settings::load();
+        let status = check_once(&settings, cli.no_root).await;
+        match &status.error {
+            Some(error) => logln!("⚠️  Update check failed: {error}"),
+            None if status.available.is_empty() => logln!("✅ Everything is up to date"),
+            None => {
+                for update in &status.available {
+                    logln!(
+                        "⬆️  {}: {} → {}",
+                        update.name,
+                        update.current,
+                        update.latest
+                    );
+                }
+            }
+        }
+        if let Err(e) = status.save(&root) {
+            logln!("❌ Could not write the update status: {e}");
+        }
+
+        let hours = settings.update_check_interval_hours.max(1);
+        tokio::time::sleep(Duration::from_secs(hours * 3600)).await;
+    }
+}
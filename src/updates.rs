@@ -0,0 +1,747 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{DateTime, Duration, Utc};
+use color_eyre::eyre::{Result, bail};
+use reqwest::{Client, StatusCode};
+use semver::Version;
+use serde::Deserialize;
+
+use crate::runner::{Cmd, CommandRunner};
+use crate::settings::{PinnedTag, UpdateSources};
+use crate::{disk, http};
+
+use crate::self_update::{self, PackageFormat, Target};
+
+const OWNER: &str = "NexusQuantum";
+
+struct ServiceConfig {
+    pub display_name: &'static str,
+    pub image: &'static str,
+    pub package: &'static str,
+    pub current_tag: &'static str,
+    /// GitHub repository whose releases describe this image.
+    pub release_repo: &'static str,
+    /// Compose service and container name backing this image.
+    pub service: &'static str,
+    pub container: &'static str,
+}
+
+const SERVICE_CONFIGS: &[ServiceConfig] = &[ServiceConfig {
+    display_name: "NQRust Identity",
+    image: "ghcr.io/nexusquantum/nqrust-identity",
+    package: "nqrust-identity",
+    current_tag: "latest",
+    release_repo: "nqrust-identity",
+    service: "identity",
+    container: "identity",
+}];
+
+#[derive(Debug, Clone)]
+pub struct UpdateInfo {
+    pub display_name: String,
+    pub image: String,
+    pub current_tag: String,
+    pub available_tags: Vec<String>,
+    pub latest_release_tag: Option<String>,
+    pub latest_release_published: Option<DateTime<Utc>>,
+    /// Markdown body of the GitHub release for `latest_release_tag`.
+    pub release_notes: Option<String>,
+    pub remote_latest_updated: Option<DateTime<Utc>>,
+    pub local_created: Option<DateTime<Utc>>,
+    /// Manifest digest the registry serves for `target_tag()`.
+    pub remote_digest: Option<String>,
+    /// `RepoDigests` of the local image (`repo@sha256:...`).
+    pub local_digests: Vec<String>,
+    pub status_note: Option<String>,
+    pub has_update: bool,
+    pub is_self: bool,
+    /// Tag pinned via the tag picker; overrides the latest release when pulling.
+    pub pinned_tag: Option<String>,
+    /// Compose service / container name; None for the installer self-update entry.
+    pub service: Option<String>,
+    pub container: Option<String>,
+    pub download_url: Option<String>,
+    pub checksum_url: Option<String>,
+}
+
+impl UpdateInfo {
+    fn new(config: &ServiceConfig) -> Self {
+        Self {
+            display_name: config.display_name.to_string(),
+            image: config.image.to_string(),
+            current_tag: config.current_tag.to_string(),
+            available_tags: Vec::new(),
+            latest_release_tag: None,
+            latest_release_published: None,
+            release_notes: None,
+            remote_latest_updated: None,
+            local_created: None,
+            remote_digest: None,
+            local_digests: Vec::new(),
+            status_note: None,
+            has_update: false,
+            is_self: false,
+            pinned_tag: None,
+            service: Some(config.service.to_string()),
+            container: Some(config.container.to_string()),
+            download_url: None,
+            checksum_url: None,
+        }
+    }
+
+    /// True when both digests are known, so `has_update` is exact rather
+    /// than inferred from creation timestamps.
+    pub fn digest_checked(&self) -> bool {
+        self.remote_digest.is_some() && !self.local_digests.is_empty()
+    }
+
+    pub fn recompute_status(&mut self) {
+        if let Some(remote) = &self.remote_digest
+            && !self.local_digests.is_empty()
+        {
+            self.has_update = !self
+                .local_digests
+                .iter()
+                .any(|local| local.rsplit('@').next() == Some(remote.as_str()));
+            return;
+        }
+
+        if let Some(remote) = self.remote_latest_updated {
+            match self.local_created {
+                Some(local) => {
+                    let delta = remote - local;
+                    self.has_update = delta > Duration::seconds(5);
+                }
+                None => {
+                    self.has_update = true;
+                }
+            }
+        } else {
+            self.has_update = false;
+        }
+    }
+
+    /// A batch pull can take it: an image, not the installer itself, and one
+    /// the update check could read.
+    pub fn batchable(&self) -> bool {
+        !self.is_self && self.status_note.is_none()
+    }
+
+    /// Tag that a pull should fetch: the pin if set, else the latest release.
+    pub fn target_tag(&self) -> &str {
+        self.pinned_tag
+            .as_deref()
+            .or(self.latest_release_tag.as_deref())
+            .unwrap_or(self.current_tag.as_str())
+    }
+
+    /// Tags ordered for the picker: semver releases newest first, then the rest.
+    pub fn picker_tags(&self) -> Vec<String> {
+        let mut semver_tags: Vec<(Version, &String)> = Vec::new();
+        let mut other_tags: Vec<&String> = Vec::new();
+        for tag in &self.available_tags {
+            match Version::parse(tag.trim_start_matches('v')) {
+                Ok(version) => semver_tags.push((version, tag)),
+                Err(_) => other_tags.push(tag),
+            }
+        }
+        semver_tags.sort_by(|a, b| b.0.cmp(&a.0));
+        other_tags.sort();
+        semver_tags
+            .into_iter()
+            .map(|(_, tag)| tag.clone())
+            .chain(other_tags.into_iter().cloned())
+            .collect()
+    }
+
+    pub fn apply_local_created(&mut self, created: Option<DateTime<Utc>>) {
+        self.local_created = created;
+        self.recompute_status();
+    }
+
+    #[allow(dead_code)]
+    pub fn append_status(&mut self, message: &str) {
+        append_status(&mut self.status_note, message);
+    }
+
+    #[allow(dead_code)]
+    pub fn clear_local_error(&mut self) {
+        if let Some(note) = &self.status_note
+            && note.contains("Failed to inspect local image")
+        {
+            self.status_note = None;
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PackageVersion {
+    #[serde(default)]
+    metadata: Option<PackageMetadata>,
+    #[serde(default)]
+    created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PackageMetadata {
+    #[serde(default)]
+    container: Option<ContainerMetadata>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReleaseAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReleaseResponse {
+    tag_name: String,
+    published_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RegistryToken {
+    token: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RegistryTagList {
+    #[serde(default)]
+    tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContainerMetadata {
+    #[serde(default)]
+    tags: Option<Vec<String>>,
+}
+
+pub async fn collect_update_infos(
+    runner: &dyn CommandRunner,
+    client: &Client,
+    token: Option<&str>,
+    pins: &BTreeMap<String, PinnedTag>,
+    sources: &UpdateSources,
+    package_format: PackageFormat,
+) -> Result<Vec<UpdateInfo>> {
+    let mut infos = Vec::new();
+    let api = sources.github_api();
+    // The GitHub token stays with GitHub.
+    let api_token = token.filter(|_| !sources.github_mirrored());
+
+    for config in SERVICE_CONFIGS {
+        let mut info = UpdateInfo::new(config);
+        info.pinned_tag = pins.get(config.service).map(|pin| pin.tag.clone());
+
+        match fetch_package_versions(client, &api, OWNER, config.package, api_token).await? {
+            Some(versions) => apply_remote_versions(&mut info, versions),
+            None => append_status(
+                &mut info.status_note,
+                "Package not found in GitHub Container Registry",
+            ),
+        }
+
+        // The registry tag list also sees tags the packages API omits
+        // (and works anonymously for public images).
+        if let Ok(tags) = fetch_registry_tags(client, sources, config.image, token).await {
+            merge_tags(&mut info, tags);
+        }
+
+        if let Some(tag) = info.latest_release_tag.clone() {
+            info.release_notes =
+                fetch_release_notes(client, &api, config.release_repo, &tag, api_token).await;
+        }
+
+        // Compare digests for the tag a pull would fetch.
+        let target = info.target_tag().to_string();
+        info.remote_digest = fetch_manifest_digest(client, sources, config.image, &target, token)
+            .await
+            .ok()
+            .flatten();
+        info.local_digests = inspect_local_repo_digests(runner, config.image, &target).await;
+
+        match inspect_local_image_created_at(runner, config.image, config.current_tag).await {
+            Ok(created) => info.apply_local_created(created),
+            Err(e) => {
+                append_status(
+                    &mut info.status_note,
+                    &format!("Failed to inspect local image: {}", e),
+                );
+                info.apply_local_created(None);
+            }
+        }
+
+        infos.push(info);
+    }
+
+    if let Some(self_update) = fetch_installer_update(client, &api, package_format).await? {
+        infos.push(self_update);
+    }
+
+    Ok(infos)
+}
+
+/// Release notes for `tag`, or None when the tag has no GitHub release.
+async fn fetch_release_notes(
+    client: &Client,
+    api: &str,
+    repo: &str,
+    tag: &str,
+    token: Option<&str>,
+) -> Option<String> {
+    let url = format!("{api}/repos/{OWNER}/{repo}/releases/tags/{tag}");
+    let mut request = client
+        .get(&url)
+        .header("Accept", "application/vnd.github+json");
+    if let Some(token) = token {
+        request = request.header("Authorization", format!("Bearer {token}"));
+    }
+    let release: ReleaseResponse = http::send(request)
+        .await
+        .ok()?
+        .error_for_status()
+        .ok()?
+        .json()
+        .await
+        .ok()?;
+    release.body.filter(|body| !body.trim().is_empty())
+}
+
+async fn fetch_installer_update(
+    client: &Client,
+    api: &str,
+    package_format: PackageFormat,
+) -> Result<Option<UpdateInfo>> {
+    let url = format!(
+        "{api}/repos/{owner}/installer-NQRust-Identity/releases/latest",
+        owner = OWNER
+    );
+
+    let response = http::send(
+        client
+            .get(&url)
+            .header("Accept", "application/vnd.github+json"),
+    )
+    .await?;
+
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+
+    let release: ReleaseResponse = response.error_for_status()?.json().await?;
+
+    let current_version =
+        Version::parse(env!("CARGO_PKG_VERSION")).unwrap_or_else(|_| Version::new(0, 0, 0));
+
+    let remote_version = Version::parse(release.tag_name.trim_start_matches('v')).ok();
+
+    let names: Vec<&str> = release.assets.iter().map(|a| a.name.as_str()).collect();
+    let url_of = |name: &str| {
+        release
+            .assets
+            .iter()
+            .find(|a| a.name == name)
+            .map(|a| a.browser_download_url.clone())
+    };
+    let checksum_url = names
+        .iter()
+        .find(|name| name.eq_ignore_ascii_case("SHA256SUMS"))
+        .and_then(|name| url_of(name));
+
+    // A release without any installer packages is not worth listing.
+    if !names
+        .iter()
+        .any(|name| PackageFormat::from_asset_name(name).is_some())
+    {
+        return Ok(None);
+    }
+
+    // Keep the entry when our platform is missing so the reason is visible.
+    let (download_url, status_note) =
+        match self_update::select_asset(&names, package_format, &Target::current()) {
+            Ok(name) => (url_of(name), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+
+    let mut info = UpdateInfo {
+        display_name: "Installer (self-update)".to_string(),
+        image: "installer".to_string(),
+        current_tag: format!("v{}", env!("CARGO_PKG_VERSION")),
+        available_tags: Vec::new(),
+        latest_release_tag: Some(release.tag_name.clone()),
+        latest_release_published: release.published_at,
+        release_notes: release.body.clone(),
+        remote_latest_updated: release.published_at,
+        local_created: None,
+        remote_digest: None,
+        local_digests: Vec::new(),
+        status_note,
+        has_update: false,
+        is_self: true,
+        pinned_tag: None,
+        service: None,
+        container: None,
+        download_url,
+        checksum_url,
+    };
+
+    if let Some(remote) = remote_version {
+        info.has_update = remote > current_version;
+    }
+
+    Ok(Some(info))
+}
+
+fn apply_remote_versions(info: &mut UpdateInfo, versions: Vec<PackageVersion>) {
+    let mut tags = Vec::new();
+    let mut seen = HashSet::new();
+    let mut tag_dates: HashMap<String, DateTime<Utc>> = HashMap::new();
+
+    for version in versions {
+        let timestamp = version.updated_at.or(version.created_at);
+        let Some(metadata) = version.metadata else {
+            continue;
+        };
+        let Some(container) = metadata.container else {
+            continue;
+        };
+        let Some(version_tags) = container.tags else {
+            continue;
+        };
+
+        for tag in version_tags {
+            if seen.insert(tag.clone()) {
+                tags.push(tag.clone());
+            }
+            if let Some(ts) = timestamp {
+                tag_dates.entry(tag.clone()).or_insert(ts);
+            }
+        }
+    }
+
+    tags.sort();
+    info.available_tags = tags.clone();
+
+    if let Some(latest_tag) = determine_latest_release_tag(&tags) {
+        info.latest_release_published = tag_dates.get(&latest_tag).cloned();
+        info.latest_release_tag = Some(latest_tag);
+    }
+
+    info.remote_latest_updated = tag_dates.get("latest").cloned();
+    info.recompute_status();
+
+    if info.available_tags.is_empty() {
+        append_status(&mut info.status_note, "No tags found for this image yet");
+    }
+}
+
+fn merge_tags(info: &mut UpdateInfo, tags: Vec<String>) {
+    let mut seen: HashSet<String> = info.available_tags.iter().cloned().collect();
+    for tag in tags {
+        if seen.insert(tag.clone()) {
+            info.available_tags.push(tag);
+        }
+    }
+    info.available_tags.sort();
+    if info.latest_release_tag.is_none() {
+        info.latest_release_tag = determine_latest_release_tag(&info.available_tags);
+    }
+}
+
+/// List tags through the OCI distribution API (`/v2/<repo>/tags/list`),
+/// exchanging the PAT (or nothing, for public images) for a pull token first.
+async fn fetch_registry_tags(
+    client: &Client,
+    sources: &UpdateSources,
+    image: &str,
+    token: Option<&str>,
+) -> Result<Vec<String>> {
+    let (base, repository, pull_token) = registry_pull_token(client, sources, image, token).await?;
+
+    let mut request = client.get(format!("{base}/v2/{repository}/tags/list?n=1000"));
+    if let Some(pull_token) = pull_token {
+        request = request.bearer_auth(pull_token);
+    }
+    let list: RegistryTagList = http::send(request)
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(list.tags.unwrap_or_default())
+}
+
+/// Split `image` into the registry's base URL and repository and exchange
+/// the PAT (or nothing, for public images) for a registry pull token. A
+/// mirror gets no PAT and may need no token at all.
+async fn registry_pull_token<'a>(
+    client: &Client,
+    sources: &UpdateSources,
+    image: &'a str,
+    token: Option<&str>,
+) -> Result<(String, &'a str, Option<String>)> {
+    let Some((registry, repository)) = image.split_once('/') else {
+        bail!("Image {image} has no registry host");
+    };
+    let base = sources.registry(registry);
+    if sources.registry_mirrored() {
+        let pull_token = disk::pull_token(client, &base, repository, None).await;
+        return Ok((base, repository, pull_token));
+    }
+
+    let mut request = client.get(format!("{base}/token")).query(&[
+        ("scope", format!("repository:{repository}:pull")),
+        ("service", registry.to_string()),
+    ]);
+    if let Some(token) = token {
+        request = request.basic_auth("token", Some(token));
+    }
+    let pull_token: RegistryToken = http::send(request)
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok((base, repository, Some(pull_token.token)))
+}
+
+/// Digest the registry serves for `image:tag` (the `Docker-Content-Digest`
+/// of a HEAD on the manifest). Multi-arch tags resolve to the index digest,
+/// which is also what `docker pull` records in `RepoDigests`.
+async fn fetch_manifest_digest(
+    client: &Client,
+    sources: &UpdateSources,
+    image: &str,
+    tag: &str,
+    token: Option<&str>,
+) -> Result<Option<String>> {
+    let (base, repository, pull_token) = registry_pull_token(client, sources, image, token).await?;
+
+    let mut request = client
+        .head(format!("{base}/v2/{repository}/manifests/{tag}"))
+        .header(
+            "Accept",
+            "application/vnd.oci.image.index.v1+json, \
+             application/vnd.docker.distribution.manifest.list.v2+json, \
+             application/vnd.oci.image.manifest.v1+json, \
+             application/vnd.docker.distribution.manifest.v2+json",
+        );
+    if let Some(pull_token) = pull_token {
+        request = request.bearer_auth(pull_token);
+    }
+    let response = http::send(request).await?;
+
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let response = response.error_for_status()?;
+    Ok(response
+        .headers()
+        .get("Docker-Content-Digest")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string))
+}
+
+pub async fn inspect_local_repo_digests(
+    runner: &dyn CommandRunner,
+    image: &str,
+    tag: &str,
+) -> Vec<String> {
+    let reference = format!("{image}:{tag}");
+    let Ok(output) = runner
+        .output(&Cmd::new("docker").args([
+            "image",
+            "inspect",
+            &reference,
+            "--format",
+            "{{range .RepoDigests}}{{println .}}{{end}}",
+        ]))
+        .await
+    else {
+        return Vec::new();
+    };
+    if !output.success {
+        return Vec::new();
+    }
+    output
+        .stdout
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn determine_latest_release_tag(tags: &[String]) -> Option<String> {
+    let mut semver_tags: Vec<(Version, String)> = tags
+        .iter()
+        .filter_map(|tag| {
+            let candidate = tag.trim_start_matches('v');
+            Version::parse(candidate)
+                .ok()
+                .map(|version| (version, tag.clone()))
+        })
+        .collect();
+
+    if semver_tags.is_empty() {
+        return None;
+    }
+
+    semver_tags.sort_by(|a, b| b.0.cmp(&a.0));
+    Some(semver_tags.remove(0).1)
+}
+
+async fn fetch_package_versions(
+    client: &Client,
+    api: &str,
+    owner: &str,
+    package: &str,
+    token: Option<&str>,
+) -> Result<Option<Vec<PackageVersion>>> {
+    let endpoints = [
+        format!("{api}/orgs/{owner}/packages/container/{package}/versions?per_page=100"),
+        format!("{api}/users/{owner}/packages/container/{package}/versions?per_page=100"),
+    ];
+
+    for url in endpoints {
+        let mut request = client
+            .get(&url)
+            .header("Accept", "application/vnd.github+json");
+
+        if let Some(token) = token {
+            request = request.header("Authorization", format!("Bearer {}", token));
+        }
+
+        let response = http::send(request).await?;
+        match response.status() {
+            StatusCode::OK => {
+                let data = response.json::<Vec<PackageVersion>>().await?;
+                return Ok(Some(data));
+            }
+            StatusCode::NOT_FOUND => continue,
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                let body = response.text().await.unwrap_or_default();
+                bail!("GitHub API request for package {package} requires authentication: {body}");
+            }
+            status if status.is_server_error() => {
+                let body = response.text().await.unwrap_or_default();
+                bail!("GitHub API error {status} for package {package}: {body}");
+            }
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                bail!("Failed to fetch package {package}: {status} {body}");
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+async fn inspect_local_image_created_at(
+    runner: &dyn CommandRunner,
+    image: &str,
+    tag: &str,
+) -> Result<Option<DateTime<Utc>>> {
+    let reference = format!("{}:{}", image, tag);
+    let output = runner
+        .output(&Cmd::new("docker").args([
+            "image",
+            "inspect",
+            &reference,
+            "--format",
+            "{{.Created}}",
+        ]))
+        .await?;
+
+    if !output.success {
+        return Ok(None);
+    }
+
+    let value = output.stdout.trim();
+
+    if value.is_empty() {
+        return Ok(None);
+    }
+
+    let created = DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .ok();
+
+    Ok(created)
+}
+
+pub async fn get_local_image_created(
+    runner: &dyn CommandRunner,
+    image: &str,
+    tag: &str,
+) -> Result<Option<DateTime<Utc>>> {
+    inspect_local_image_created_at(runner, image, tag).await
+}
+
+/// Fetch the latest release tag for nqrust-identity from GitHub Releases API.
+/// Returns e.g. "v0.0.1", or None if not found / network error.
+/// Falls back to GHCR package tags if no GitHub Release exists yet.
+pub async fn fetch_latest_identity_tag(
+    client: &Client,
+    sources: &UpdateSources,
+    token: Option<&str>,
+) -> Option<String> {
+    let api = sources.github_api();
+    let token = token.filter(|_| !sources.github_mirrored());
+    // Try GitHub Releases first (most reliable semver source)
+    let release_url = format!("{api}/repos/{OWNER}/nqrust-identity/releases/latest");
+    let mut req = client
+        .get(&release_url)
+        .header("Accept", "application/vnd.github+json");
+    if let Some(tok) = token {
+        req = req.header("Authorization", format!("Bearer {tok}"));
+    }
+    if let Ok(resp) = http::send(req).await
+        && resp.status().is_success()
+        && let Ok(release) = resp.json::<ReleaseResponse>().await
+    {
+        let tag = release.tag_name.trim().to_string();
+        if !tag.is_empty() && tag != "latest" {
+            return Some(tag);
+        }
+    }
+
+    // Fallback: GHCR package versions — pick highest semver tag
+    if let Ok(Some(versions)) =
+        fetch_package_versions(client, &api, OWNER, "nqrust-identity", token).await
+    {
+        let all_tags: Vec<String> = versions
+            .into_iter()
+            .filter_map(|v| {
+                v.metadata?
+                    .container?
+                    .tags?
+                    .into_iter()
+                    .find(|t| t != "latest")
+            })
+            .collect();
+        if let Some(best) = determine_latest_release_tag(&all_tags) {
+            return Some(best);
+        }
+    }
+
+    None
+}
+
+fn append_status(target: &mut Option<String>, message: &str) {
+    match target {
+        Some(existing) => {
+            existing.push_str("; ");
+            existing.push_str(message);
+        }
+        None => {
+            *target = Some(message.to_string());
+        }
+    }
+}
@@ -1,10 +1,14 @@
 use std::fs;
+use std::net::IpAddr;
 use std::path::{Path, PathBuf};
+use std::sync::RwLock;
 
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{Result, eyre};
+use rcgen::{Certificate, CertificateParams, SanType};
 
-pub const COMPOSE_TEMPLATE: &str = include_str!("../docker-compose.yaml");
-pub const CADDYFILE_TEMPLATE: &str = include_str!("../Caddyfile");
+use crate::external_db::{self, ExternalDb};
+use crate::runner::{Cmd, CommandRunner};
+use crate::{registry, stack};
 
 #[allow(dead_code)]
 pub fn find_file(filename: &str) -> bool {
@@ -12,16 +16,36 @@ pub fn find_file(filename: &str) -> bool {
     root.join(filename).exists()
 }
 
+/// Set by `--project-dir` or the directory picker; wins over discovery.
+static PROJECT_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Use `dir` as the project directory from now on.
+pub fn set_project_root(dir: PathBuf) {
+    let dir = std::path::absolute(&dir).unwrap_or(dir);
+    if let Ok(mut chosen) = PROJECT_DIR.write() {
+        *chosen = Some(dir);
+    }
+}
+
+/// True when the project directory was chosen rather than discovered.
+pub fn project_root_is_explicit() -> bool {
+    PROJECT_DIR.read().is_ok_and(|chosen| chosen.is_some())
+}
+
+/// The chosen project directory, or the nearest directory from the current
+/// one upwards that holds a compose file, or the current directory.
 pub fn project_root() -> PathBuf {
+    if let Some(dir) = PROJECT_DIR.read().ok().and_then(|chosen| chosen.clone()) {
+        return dir;
+    }
     let start = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
 
-    // Walk up to find a directory that contains the compose file or Cargo.toml
+    // Only compose files count: any source checkout has a Cargo.toml.
     let candidates = [
         "docker-compose.yaml",
         "docker-compose.yml",
         "compose.yml",
         "compose.yaml",
-        "Cargo.toml",
     ];
 
     let mut current = start.as_path();
@@ -40,39 +64,116 @@ pub fn project_root() -> PathBuf {
         .to_str()
         .map(|s| s.contains("target"))
         .unwrap_or(false)
+        && let Some(parent) = start.parent().and_then(|p| p.parent())
     {
-        if let Some(parent) = start.parent().and_then(|p| p.parent()) {
-            return parent.to_path_buf();
-        }
+        return parent.to_path_buf();
     }
 
     start
 }
 
-/// Ensure docker-compose.yaml and Caddyfile exist in the working dir.
-/// Writes the embedded templates if the files are missing.
-/// Also fixes the case where Caddyfile was accidentally created as a directory.
+/// Ensure the selected stack's bundle (compose file, Caddyfile, ...) exists
+/// in the working dir. Writes the embedded templates if the files are missing.
+/// Also fixes the case where a file was accidentally created as a directory
+/// (a bind mount of a missing Caddyfile does that). With an external database
+/// the compose file is always regenerated without identity-db, and a file
+/// generated that way is replaced by the embedded one once it is not.
 pub fn ensure_compose_bundle(root: &Path) -> Result<()> {
-    // --- docker-compose.yaml ---
-    let compose_path = root.join("docker-compose.yaml");
-    if !compose_path.exists() {
-        if let Some(parent) = compose_path.parent() {
-            fs::create_dir_all(parent)?;
+    fs::create_dir_all(root)?;
+    let external_db = ExternalDb::load(root).is_some();
+    for (name, contents) in stack::current().bundle {
+        let path = root.join(name);
+        if path.is_dir() {
+            fs::remove_dir_all(&path)?;
+        }
+        if *name == stack::current().compose_file() {
+            if external_db {
+                fs::write(&path, external_db::compose(contents)?)?;
+                continue;
+            }
+            let generated = fs::read_to_string(&path)
+                .is_ok_and(|current| current.starts_with(external_db::MARKER));
+            if generated {
+                fs::write(&path, contents)?;
+                continue;
+            }
+        }
+        // An empty file is as good as missing (a truncated write, a `touch`).
+        if fs::metadata(&path).map_or(true, |meta| meta.len() == 0) {
+            fs::write(&path, contents)?;
         }
-        fs::write(&compose_path, COMPOSE_TEMPLATE)?;
     }
+    Ok(())
+}
+
+/// Detect the VM's outbound IP by opening a UDP-like socket toward 8.8.8.8.
+/// Falls back to 127.0.0.1 if detection fails.
+pub fn detect_ip() -> String {
+    use std::net::UdpSocket;
+    UdpSocket::bind("0.0.0.0:0")
+        .and_then(|s| {
+            s.connect("8.8.8.8:80")?;
+            s.local_addr()
+        })
+        .map(|addr| addr.ip().to_string())
+        .unwrap_or_else(|_| "127.0.0.1".to_string())
+}
 
-    // --- Caddyfile ---
-    let caddyfile_path = root.join("Caddyfile");
-    // Guard: if Caddyfile exists as a *directory* (accidental mkdir), remove it first
-    if caddyfile_path.exists() && caddyfile_path.is_dir() {
-        fs::remove_dir_all(&caddyfile_path)?;
+/// Generate a self-signed TLS cert using rcgen (no openssl required).
+/// Writes certs/server.crt and certs/server.key; .env is updated separately.
+pub fn generate_ssl_cert(ip: &str) -> Result<()> {
+    let root = project_root();
+    let certs_dir = root.join("certs");
+    fs::create_dir_all(&certs_dir)?;
+
+    let ip_addr: IpAddr = ip
+        .parse()
+        .map_err(|_| eyre!("Invalid IP address: {}", ip))?;
+
+    let mut params = CertificateParams::default();
+    params.subject_alt_names = vec![SanType::IpAddress(ip_addr)];
+    // Valid for ~100 years
+    params.not_before = rcgen::date_time_ymd(2024, 1, 1);
+    params.not_after = rcgen::date_time_ymd(2124, 1, 1);
+
+    let cert = Certificate::from_params(params).map_err(|e| eyre!("rcgen cert error: {e}"))?;
+
+    let cert_pem = cert
+        .serialize_pem()
+        .map_err(|e| eyre!("cert serialize error: {e}"))?;
+    let key_pem = cert.serialize_private_key_pem();
+
+    fs::write(certs_dir.join("server.crt"), cert_pem)?;
+    registry::write_private(&certs_dir.join("server.key"), &key_pem)?;
+
+    Ok(())
+}
+
+/// `docker compose` when the plugin is installed, else `docker-compose`.
+pub async fn detect_compose_command(runner: &dyn CommandRunner) -> Result<Vec<String>> {
+    // Try `docker compose` (plugin, Docker 20.10+)
+    let result = runner
+        .output(&Cmd::new("docker").args(["compose", "version"]))
+        .await;
+
+    if result.is_ok_and(|output| output.success) {
+        return Ok(vec!["docker".to_string(), "compose".to_string()]);
     }
-    if !caddyfile_path.exists() {
-        fs::write(&caddyfile_path, CADDYFILE_TEMPLATE)?;
+
+    // Fallback to standalone docker-compose
+    let result = runner
+        .output(&Cmd::new("docker-compose").arg("version"))
+        .await;
+
+    if result.is_ok_and(|output| output.success) {
+        return Ok(vec!["docker-compose".to_string()]);
     }
 
-    Ok(())
+    Err(eyre!(
+        "Neither 'docker compose' nor 'docker-compose' found.\n\
+         Please install Docker 20.10+ (includes Compose plugin)\n\
+         or install docker-compose separately."
+    ))
 }
 
 #[cfg(test)]
@@ -0,0 +1,135 @@
+// verify.rs - post-install health verification
+// `compose up` returning only means the containers were started. Before the
+// install counts as a success each service is probed the way a client would
+// reach it (see `stack::Probe`), retrying while Keycloak warms up. Published
+// ports are probed on the runner's host, so `--remote` checks the server.
+
+use std::time::{Duration, Instant};
+
+use reqwest::Client;
+use tokio::net::TcpStream;
+
+use crate::runner::{Cmd, CommandRunner};
+use crate::stack::{Probe, StackService};
+
+/// How long verification keeps retrying before a service counts as unhealthy.
+pub const VERIFY_TIMEOUT: Duration = Duration::from_secs(300);
+/// Pause between attempts on the same service.
+pub const RETRY_INTERVAL: Duration = Duration::from_secs(3);
+/// Log lines shown for a service that failed verification.
+const LOG_TAIL: usize = 20;
+
+/// One attempt; the error says what the service did instead of answering.
+pub async fn check(runner: &dyn CommandRunner, service: &StackService) -> Result<(), String> {
+    match service.probe {
+        Probe::ContainerHttp(url) => {
+            let output = runner
+                .output(&Cmd::new("docker").args([
+                    "exec",
+                    service.container,
+                    "curl",
+                    "-fsS",
+                    "-o",
+                    "/dev/null",
+                    url,
+                ]))
+                .await
+                .map_err(|e| format!("docker exec failed: {e}"))?;
+            if output.success {
+                return Ok(());
+            }
+            Err(match output.stderr.trim() {
+                "" => format!("{url} is not ready"),
+                detail => format!("{url}: {detail}"),
+            })
+        }
+        Probe::Tcp(port) => tokio::time::timeout(
+            Duration::from_secs(5),
+            TcpStream::connect(format!("{}:{port}", runner.host())),
+        )
+        .await
+        .map_err(|_| format!("port {port} did not accept a connection"))?
+        .map(|_| ())
+        .map_err(|e| format!("port {port}: {e}")),
+        Probe::Tls(port) => {
+            // The certificate is self-signed; any HTTP answer proves the
+            // handshake worked, whatever the backend returned.
+            let client = Client::builder()
+                .danger_accept_invalid_certs(true)
+                .no_proxy()
+                .timeout(Duration::from_secs(5))
+                .build()
+                .map_err(|e| e.to_string())?;
+            client
+                .get(format!("https://{}:{port}/", runner.host()))
+                .send()
+                .await
+                .map(|_| ())
+                .map_err(|e| format!("TLS handshake on port {port} failed: {e}"))
+        }
+    }
+}
+
+/// Retry `check` until it passes or `timeout` elapses; the last error on failure.
+pub async fn wait(
+    runner: &dyn CommandRunner,
+    service: &StackService,
+    timeout: Duration,
+) -> Result<(), String> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match check(runner, service).await {
+            Ok(()) => return Ok(()),
+            Err(reason) if Instant::now() >= deadline => return Err(reason),
+            Err(_) => tokio::time::sleep(RETRY_INTERVAL).await,
+        }
+    }
+}
+
+/// Last lines the container wrote: its stdout, then its stderr.
+pub async fn last_logs(runner: &dyn CommandRunner, container: &str) -> Vec<String> {
+    let Ok(output) = runner
+        .output(&Cmd::new("docker").args(["logs", "--tail", &LOG_TAIL.to_string(), container]))
+        .await
+    else {
+        return Vec::new();
+    };
+    let mut lines: Vec<String> = output
+        .stdout
+        .lines()
+        .chain(output.stderr.lines())
+        .map(str::to_string)
+        .collect();
+    let skip = lines.len().saturating_sub(LOG_TAIL);
+    lines.drain(..skip);
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::MockRunner;
+    use tokio::net::TcpListener;
+
+    fn tcp_service(port: u16) -> StackService {
+        let postgres = &crate::stack::current().services[0];
+        StackService {
+            probe: Probe::Tcp(port),
+            ..*postgres
+        }
+    }
+
+    // 127.0.0.2 stands in for the --remote server: loopback on Linux, but
+    // not where a local probe would look.
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_remote_probes_the_server() {
+        let listener = TcpListener::bind("127.0.0.2:0").await.unwrap();
+        let service = tcp_service(listener.local_addr().unwrap().port());
+
+        let remote = MockRunner::new().remote("127.0.0.2");
+        assert_eq!(check(&remote, &service).await, Ok(()));
+        assert!(check(&MockRunner::new(), &service).await.is_err());
+        assert!(remote.calls().is_empty());
+    }
+}
@@ -0,0 +1,148 @@
+// xdg.rs - where the installer keeps its own files
+// Registry tokens and settings live under $XDG_CONFIG_HOME/nqrust-installer
+// (default ~/.config), logs and update bookkeeping under
+// $XDG_STATE_HOME/nqrust-installer (default ~/.local/state), so nothing
+// private sits next to docker-compose.yml where it could be committed or
+// copied along with the bundle. Per-project files go in
+// `projects/<escaped project path>/`. Files earlier versions left in the
+// project directory are moved over the first time they are looked up.
+
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::utils;
+
+const APP_DIR: &str = "nqrust-installer";
+
+/// `$XDG_CONFIG_HOME/nqrust-installer`: tokens and settings.
+pub fn config_dir() -> PathBuf {
+    base_dir(std::env::var_os("XDG_CONFIG_HOME"), ".config")
+}
+
+/// `$XDG_STATE_HOME/nqrust-installer`: logs and update state.
+pub fn state_dir() -> PathBuf {
+    base_dir(std::env::var_os("XDG_STATE_HOME"), ".local/state")
+}
+
+/// Per-project directory under the config dir for `root`.
+pub fn project_config(root: &Path) -> PathBuf {
+    config_dir().join("projects").join(project_key(root))
+}
+
+/// Per-project directory under the state dir for `root`.
+pub fn project_state(root: &Path) -> PathBuf {
+    state_dir().join("projects").join(project_key(root))
+}
+
+/// Relative XDG values are ignored, as the spec asks. Without a home
+/// directory the project directory is used, like earlier versions did.
+fn base_dir(xdg: Option<OsString>, home_default: &str) -> PathBuf {
+    let base = xdg
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .filter(|home| !home.is_empty())
+                .map(|home| PathBuf::from(home).join(home_default))
+        });
+    match base {
+        Some(base) => base.join(APP_DIR),
+        None => utils::project_root().join(format!(".{APP_DIR}")),
+    }
+}
+
+/// The absolute project path as one directory name, `%` and `/` escaped so
+/// two projects never share one.
+fn project_key(root: &Path) -> String {
+    let root = std::path::absolute(root).unwrap_or_else(|_| root.to_path_buf());
+    let root = root.to_string_lossy();
+    root.trim_start_matches('/')
+        .replace('%', "%25")
+        .replace('/', "%2F")
+}
+
+/// `new`, after moving `old` there if only `old` exists. Parent directories
+/// are created private. Falls back to `old` when it can't be moved, so a
+/// read-only home never hides an existing file.
+pub fn adopt(old: &Path, new: &Path) -> PathBuf {
+    if new.exists() || !old.exists() {
+        let _ = new.parent().map(create_private_dir);
+        return new.to_path_buf();
+    }
+    match move_path(old, new) {
+        Ok(()) => new.to_path_buf(),
+        Err(_) => old.to_path_buf(),
+    }
+}
+
+fn move_path(old: &Path, new: &Path) -> io::Result<()> {
+    if let Some(parent) = new.parent() {
+        create_private_dir(parent)?;
+    }
+    if fs::rename(old, new).is_ok() {
+        return Ok(());
+    }
+    // Another filesystem: copy, then drop the original.
+    if old.is_dir() {
+        fs::create_dir_all(new)?;
+        for entry in fs::read_dir(old)? {
+            let entry = entry?;
+            move_path(&entry.path(), &new.join(entry.file_name()))?;
+        }
+        fs::remove_dir(old)
+    } else {
+        fs::copy(old, new)?;
+        fs::remove_file(old)
+    }
+}
+
+/// `create_dir_all`, with the directory readable only by its owner.
+pub fn create_private_dir(dir: &Path) -> io::Result<()> {
+    if dir.is_dir() {
+        return Ok(());
+    }
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder.create(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base_dir_and_project_key() {
+        assert_eq!(
+            base_dir(Some("/xdg/config".into()), ".config"),
+            Path::new("/xdg/config/nqrust-installer")
+        );
+        // Relative XDG values fall through to $HOME.
+        assert!(base_dir(Some("rel".into()), ".config").ends_with(".config/nqrust-installer"));
+        assert_eq!(project_key(Path::new("/opt/identity")), "opt%2Fidentity");
+        assert_eq!(project_key(Path::new("/opt/a%b")), "opt%2Fa%25b");
+    }
+
+    #[test]
+    fn test_adopt_moves_old_file_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = dir.path().join(".ghcr_token");
+        let new = dir.path().join("config/ghcr_token");
+        fs::write(&old, "secret").unwrap();
+
+        assert_eq!(adopt(&old, &new), new);
+        assert!(!old.exists());
+        assert_eq!(fs::read_to_string(&new).unwrap(), "secret");
+
+        // A stale copy left behind later does not overwrite the new file.
+        fs::write(&old, "stale").unwrap();
+        assert_eq!(adopt(&old, &new), new);
+        assert_eq!(fs::read_to_string(&new).unwrap(), "secret");
+    }
+}
@@ -0,0 +1,204 @@
+// yaml_file.rs - flat key/value view of a YAML config file
+// Nested mappings and sequences are shown as dotted paths (`models.0.name`)
+// so the same row-per-key editor as .env works on them. Values written back
+// keep the type they were read with: a number stays a number, a bool a bool.
+// serde_yaml does not keep comments, so saving rewrites the file without them.
+
+use std::fs;
+use std::path::Path;
+
+use color_eyre::{Result, eyre::eyre};
+use serde_yaml::Value;
+
+/// One step from a parent to a child value.
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(Value),
+    Index(usize),
+}
+
+/// A scalar leaf of the document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct YamlEntry {
+    path: Vec<Segment>,
+    /// Dotted path shown to the user.
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct YamlFile {
+    doc: Value,
+}
+
+impl YamlFile {
+    pub fn parse(content: &str) -> Result<Self> {
+        let doc: Value = serde_yaml::from_str(content)?;
+        if !matches!(doc, Value::Mapping(_)) {
+            return Err(eyre!("top level is not a mapping"));
+        }
+        Ok(Self { doc })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn render(&self) -> Result<String> {
+        Ok(serde_yaml::to_string(&self.doc)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.render()?)?;
+        Ok(())
+    }
+
+    /// Scalar leaves in document order. Empty mappings and sequences, and
+    /// tagged values, are not editable here and are left as they are.
+    pub fn entries(&self) -> Vec<YamlEntry> {
+        let mut out = Vec::new();
+        collect(&self.doc, &mut Vec::new(), &mut out);
+        out
+    }
+
+    /// Replace the leaf at `entry` with `text`, parsed as the type it had.
+    pub fn set(&mut self, entry: &YamlEntry, text: &str) -> std::result::Result<(), String> {
+        let mut node = &mut self.doc;
+        for segment in &entry.path {
+            node = match (segment, node) {
+                (Segment::Key(key), Value::Mapping(map)) => map.get_mut(key),
+                (Segment::Index(idx), Value::Sequence(seq)) => seq.get_mut(*idx),
+                _ => None,
+            }
+            .ok_or_else(|| format!("{} is no longer in the file", entry.key))?;
+        }
+        *node = parse_like(node, text)
+            .map_err(|expected| format!("{} must be {expected}", entry.key))?;
+        Ok(())
+    }
+}
+
+fn collect(value: &Value, path: &mut Vec<Segment>, out: &mut Vec<YamlEntry>) {
+    match value {
+        Value::Mapping(map) => {
+            for (key, child) in map {
+                path.push(Segment::Key(key.clone()));
+                collect(child, path, out);
+                path.pop();
+            }
+        }
+        Value::Sequence(seq) => {
+            for (idx, child) in seq.iter().enumerate() {
+                path.push(Segment::Index(idx));
+                collect(child, path, out);
+                path.pop();
+            }
+        }
+        Value::Tagged(_) => {}
+        scalar => out.push(YamlEntry {
+            key: dotted(path),
+            value: scalar_text(scalar),
+            path: path.clone(),
+        }),
+    }
+}
+
+fn dotted(path: &[Segment]) -> String {
+    path.iter()
+        .map(|segment| match segment {
+            Segment::Key(key) => scalar_text(key),
+            Segment::Index(idx) => idx.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+fn scalar_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.clone(),
+        other => serde_yaml::to_string(other)
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+    }
+}
+
+/// `text` as a value of the same type as `old`; the error names the type
+/// that was expected.
+fn parse_like(old: &Value, text: &str) -> std::result::Result<Value, &'static str> {
+    let text = text.trim();
+    match old {
+        Value::Bool(_) => text
+            .parse::<bool>()
+            .map(Value::Bool)
+            .map_err(|_| "true or false"),
+        Value::Number(_) => serde_yaml::from_str::<Value>(text)
+            .ok()
+            .filter(Value::is_number)
+            .ok_or("a number"),
+        Value::Null if text.is_empty() => Ok(Value::Null),
+        // An unset value takes whatever the text reads as (number, bool, string).
+        Value::Null => Ok(serde_yaml::from_str::<Value>(text)
+            .ok()
+            .filter(|v| matches!(v, Value::Bool(_) | Value::Number(_) | Value::String(_)))
+            .unwrap_or_else(|| Value::String(text.to_string()))),
+        _ => Ok(Value::String(text.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "\
+settings:
+  port: 8080
+  debug: false
+models:
+  - name: gpt-4o
+    temperature: 0.2
+  - name: llama3
+api_key: ''
+";
+
+    #[test]
+    fn test_entries_are_dotted_and_in_order() {
+        let file = YamlFile::parse(CONFIG).unwrap();
+        let keys: Vec<String> = file.entries().into_iter().map(|e| e.key).collect();
+        assert_eq!(
+            keys,
+            [
+                "settings.port",
+                "settings.debug",
+                "models.0.name",
+                "models.0.temperature",
+                "models.1.name",
+                "api_key"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_set_keeps_types() {
+        let mut file = YamlFile::parse(CONFIG).unwrap();
+        let entries = file.entries();
+        file.set(&entries[0], "9090").unwrap();
+        file.set(&entries[3], "0.7").unwrap();
+        file.set(&entries[4], "123").unwrap();
+        assert_eq!(
+            file.set(&entries[1], "maybe").unwrap_err(),
+            "settings.debug must be true or false"
+        );
+        assert!(file.set(&entries[0], "eighty").is_err());
+
+        let reparsed = YamlFile::parse(&file.render().unwrap()).unwrap();
+        let rendered = reparsed.render().unwrap();
+        assert!(rendered.contains("port: 9090"));
+        assert!(rendered.contains("temperature: 0.7"));
+        // A string stays a string even when it looks like a number.
+        assert!(rendered.contains("name: '123'"));
+    }
+}
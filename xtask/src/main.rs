@@ -0,0 +1,251 @@
+// xtask - developer tasks, run with `cargo xtask <task>`.
+//
+// `e2e` drives the real installer binary in headless mode against a throwaway
+// Docker-in-Docker daemon and checks the whole flow: .env/cert generation,
+// compose up, identity healthcheck and uninstall. The host's own Docker is
+// only used to start and remove the dind container.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const DIND_IMAGE: &str = "docker:27-dind";
+const BIN_NAME: &str = "nqrust-identity";
+
+type Result<T> = std::result::Result<T, String>;
+
+fn main() -> ExitCode {
+    let task = env::args().nth(1);
+    let result = match task.as_deref() {
+        Some("e2e") => e2e(),
+        _ => {
+            eprintln!("usage: cargo xtask e2e");
+            return ExitCode::FAILURE;
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("❌ {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn e2e() -> Result<()> {
+    let root = workspace_root();
+    step("Building installer");
+    run(
+        Command::new(env::var("CARGO").unwrap_or_else(|_| "cargo".into()))
+            .args(["build", "--bin", BIN_NAME])
+            .current_dir(&root),
+    )?;
+    let binary = root.join("target/debug").join(BIN_NAME);
+
+    // The work dir is mounted into dind at the same path so the compose
+    // bind mounts (./certs, ./Caddyfile) resolve inside the inner daemon.
+    // Declared before `dind` so it is dropped after the container is gone.
+    let work_dir = WorkDir::create()?;
+    let work = work_dir.path();
+    let dind = Dind::start(work)?;
+    let docker_host = dind.wait_ready()?;
+
+    let installer = |args: &[&str]| {
+        let mut cmd = Command::new(&binary);
+        cmd.args(args)
+            .current_dir(work)
+            .env("DOCKER_HOST", &docker_host)
+            // Public images only; never hand the developer's token to the test.
+            .env_remove("GHCR_TOKEN");
+        cmd
+    };
+
+    step("Headless install");
+    run(&mut installer(&["--headless", "--server-ip", "127.0.0.1"]))?;
+
+    step("Checking generated files");
+    let env_file = fs::read_to_string(work.join(".env")).map_err(|e| format!(".env: {e}"))?;
+    check(
+        env_file.lines().any(|l| l == "SERVER_IP=127.0.0.1"),
+        ".env has SERVER_IP",
+    )?;
+    check(
+        work.join("certs/server.crt").is_file(),
+        "certificate written",
+    )?;
+    check(
+        work.join("certs/server.key").is_file(),
+        "private key written",
+    )?;
+    check(
+        work.join(".nqrust-install-manifest.json").is_file(),
+        "install manifest written",
+    )?;
+
+    step("Checking container health");
+    let health = docker(
+        &docker_host,
+        &[
+            "inspect",
+            "--format",
+            "{{.State.Health.Status}}",
+            "identity",
+        ],
+    )?;
+    check(health.trim() == "healthy", "identity is healthy")?;
+    let running = docker(&docker_host, &["ps", "--format", "{{.Names}}"])?;
+    for name in ["identity", "identity-db", "identity-caddy"] {
+        check(
+            running.lines().any(|l| l == name),
+            &format!("{name} running"),
+        )?;
+    }
+
+    step("Uninstall");
+    run(&mut installer(&["--uninstall", "--purge"]))?;
+    let left = docker(
+        &docker_host,
+        &[
+            "ps",
+            "-aq",
+            "--filter",
+            "label=com.docker.compose.project=identity",
+        ],
+    )?;
+    check(left.trim().is_empty(), "no containers left")?;
+    let volumes = docker(&docker_host, &["volume", "ls", "-q"])?;
+    check(
+        !volumes.lines().any(|v| v.ends_with("identity_pgdata")),
+        "database volume removed",
+    )?;
+
+    println!("\n✅ e2e passed");
+    Ok(())
+}
+
+/// Temporary work directory; removed on drop, even after a failure.
+struct WorkDir {
+    path: PathBuf,
+}
+
+impl WorkDir {
+    fn create() -> Result<Self> {
+        let path = env::temp_dir().join(format!("nqrust-e2e-{}", std::process::id()));
+        fs::create_dir_all(&path).map_err(|e| format!("create {}: {e}", path.display()))?;
+        Ok(Self { path })
+    }
+
+    fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for WorkDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Disposable Docker-in-Docker daemon; removed on drop, even after a failure.
+struct Dind {
+    name: String,
+}
+
+impl Dind {
+    fn start(work: &Path) -> Result<Self> {
+        step("Starting Docker-in-Docker");
+        let name = format!("nqrust-e2e-{}", std::process::id());
+        let mount = format!("{0}:{0}", work.display());
+        run(Command::new("docker").args([
+            "run",
+            "-d",
+            "--privileged",
+            "--name",
+            &name,
+            "-e",
+            "DOCKER_TLS_CERTDIR=",
+            "-p",
+            "127.0.0.1::2375",
+            "-v",
+            &mount,
+            DIND_IMAGE,
+        ]))?;
+        Ok(Self { name })
+    }
+
+    /// Wait for the inner daemon and return its DOCKER_HOST.
+    fn wait_ready(&self) -> Result<String> {
+        let port = output(Command::new("docker").args(["port", &self.name, "2375"]))?;
+        let addr = port
+            .lines()
+            .next()
+            .ok_or("dind did not publish port 2375")?
+            .trim();
+        let host = format!("tcp://{addr}");
+
+        let deadline = Instant::now() + Duration::from_secs(60);
+        while Instant::now() < deadline {
+            if docker(&host, &["info"]).is_ok() {
+                return Ok(host);
+            }
+            thread::sleep(Duration::from_secs(1));
+        }
+        Err("dind daemon did not become ready within 60s".into())
+    }
+}
+
+impl Drop for Dind {
+    fn drop(&mut self) {
+        let _ = Command::new("docker")
+            .args(["rm", "-f", "-v", &self.name])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+}
+
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+fn step(name: &str) {
+    println!("\n▶ {name}");
+}
+
+fn check(ok: bool, what: &str) -> Result<()> {
+    if ok {
+        println!("  ✓ {what}");
+        Ok(())
+    } else {
+        Err(format!("check failed: {what}"))
+    }
+}
+
+fn run(cmd: &mut Command) -> Result<()> {
+    let status = cmd.status().map_err(|e| format!("{cmd:?}: {e}"))?;
+    if !status.success() {
+        return Err(format!("{cmd:?} exited with {status}"));
+    }
+    Ok(())
+}
+
+fn output(cmd: &mut Command) -> Result<String> {
+    let out = cmd.output().map_err(|e| format!("{cmd:?}: {e}"))?;
+    if !out.status.success() {
+        return Err(format!(
+            "{cmd:?}: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).into_owned())
+}
+
+fn docker(host: &str, args: &[&str]) -> Result<String> {
+    output(Command::new("docker").arg("-H").arg(host).args(args))
+}